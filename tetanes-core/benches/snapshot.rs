@@ -0,0 +1,66 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use std::{fs::File, io::BufReader, path::Path};
+use tetanes_core::{
+    control_deck::{Config, ControlDeck},
+    cpu::Cpu,
+    mem::RamState,
+};
+
+/// Loads a test ROM and runs a few frames so there's meaningful state to snapshot.
+fn control_deck(rom_path: impl AsRef<Path>, frames: u32) -> ControlDeck {
+    let base_path = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let rom_path = base_path.join(rom_path);
+    assert!(rom_path.exists(), "No test rom found for {rom_path:?}");
+    let mut rom = BufReader::new(File::open(&rom_path).expect("failed to open path"));
+    let mut deck = ControlDeck::with_config(Config {
+        ram_state: RamState::AllZeros,
+        ..Default::default()
+    });
+    deck.load_rom(rom_path.to_string_lossy(), &mut rom)
+        .expect("failed to load rom");
+    while deck.frame_number() < frames {
+        deck.clock_frame().expect("valid frame clock");
+        deck.clear_audio_samples();
+    }
+    deck
+}
+
+// Compare against `cargo bench --bench snapshot --features zstd`
+fn snapshots(c: &mut Criterion) {
+    let mut deck = control_deck("test_roms/spritecans.nes", 60);
+    let mut snapshot = Vec::new();
+    deck.snapshot_into(&mut snapshot).expect("valid snapshot");
+
+    let mut group = c.benchmark_group("snapshot");
+    group.bench_function("bincode_serialize", |b| {
+        b.iter(|| bincode::serialize(black_box(deck.cpu())).expect("valid serialize"));
+    });
+    group.bench_function("bincode_deserialize", |b| {
+        b.iter(|| bincode::deserialize::<Cpu>(black_box(&snapshot)).expect("valid deserialize"));
+    });
+    group.bench_function("snapshot_into", |b| {
+        let mut buffer = Vec::new();
+        b.iter(|| deck.snapshot_into(black_box(&mut buffer)));
+    });
+    group.bench_function("restore_snapshot", |b| {
+        b.iter(|| deck.restore_snapshot(black_box(&snapshot)));
+    });
+    #[cfg(feature = "zstd")]
+    {
+        use tetanes_core::fs;
+
+        let mut compressed = Vec::new();
+        fs::compress_snapshot(&snapshot, &mut compressed).expect("valid compress");
+        group.bench_function("compress_snapshot", |b| {
+            b.iter(|| fs::compress_snapshot(black_box(&snapshot), &mut compressed));
+        });
+        group.bench_function("decompress_snapshot", |b| {
+            let mut decompressed = Vec::new();
+            b.iter(|| fs::decompress_snapshot(black_box(&compressed), &mut decompressed));
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, snapshots);
+criterion_main!(benches);
@@ -0,0 +1,44 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use std::{fs::File, io::BufReader, path::Path};
+use tetanes_core::{
+    control_deck::{Config, ControlDeck},
+    mem::RamState,
+    video::{Frame, Video},
+};
+
+/// Renders a few frames of a test ROM and returns the raw PPU frame buffer.
+fn frame_buffer(rom_path: impl AsRef<Path>, frames: u32) -> Vec<u16> {
+    let base_path = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let rom_path = base_path.join(rom_path);
+    assert!(rom_path.exists(), "No test rom found for {rom_path:?}");
+    let mut rom = BufReader::new(File::open(&rom_path).expect("failed to open path"));
+    let mut deck = ControlDeck::with_config(Config {
+        ram_state: RamState::AllZeros,
+        ..Default::default()
+    });
+    deck.load_rom(rom_path.to_string_lossy(), &mut rom)
+        .expect("failed to load rom");
+    while deck.frame_number() < frames {
+        deck.clock_frame().expect("valid frame clock");
+        deck.clear_audio_samples();
+    }
+    deck.frame_buffer_raw().to_vec()
+}
+
+// Compare against `cargo bench --bench video_filter --features parallel-video`
+fn filters(c: &mut Criterion) {
+    let buffer = frame_buffer("test_roms/spritecans.nes", 60);
+    let mut output = Frame::new();
+
+    let mut group = c.benchmark_group("video_filter");
+    group.bench_function("pixellate", |b| {
+        b.iter(|| Video::decode_buffer(black_box(&buffer), &mut output));
+    });
+    group.bench_function("ntsc", |b| {
+        b.iter(|| Video::apply_ntsc_filter(black_box(&buffer), black_box(1), &mut output));
+    });
+    group.finish();
+}
+
+criterion_group!(benches, filters);
+criterion_main!(benches);
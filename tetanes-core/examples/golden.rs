@@ -0,0 +1,70 @@
+//! Regenerate the golden images compared by the `golden_images` test after intended visual
+//! changes, e.g. to the PPU or video filters.
+//!
+//! Usage: `cargo run -p tetanes-core --example golden -- [NAME...]`
+//!
+//! Regenerates every golden listed in `test_roms/golden/goldens.json`, or only those named.
+
+use anyhow::Context;
+use image::{ImageBuffer, Rgba};
+use serde::Deserialize;
+use std::{fs::File, io::BufReader, path::PathBuf};
+use tetanes_core::{prelude::*, video::VideoFilter};
+
+#[derive(Debug, Deserialize)]
+struct Golden {
+    name: String,
+    rom: PathBuf,
+    frame: u32,
+    filter: VideoFilter,
+}
+
+fn main() -> anyhow::Result<()> {
+    let names = std::env::args().skip(1).collect::<Vec<_>>();
+    let test_roms = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("test_roms");
+    let golden_dir = test_roms.join("golden");
+    let goldens_file = golden_dir.join("goldens.json");
+    let goldens: Vec<Golden> = serde_json::from_reader(BufReader::new(
+        File::open(&goldens_file).with_context(|| format!("failed to open {goldens_file:?}"))?,
+    ))
+    .with_context(|| format!("invalid golden list: {goldens_file:?}"))?;
+
+    for name in &names {
+        anyhow::ensure!(
+            goldens.iter().any(|golden| golden.name == *name),
+            "no golden named {name:?} in {goldens_file:?}"
+        );
+    }
+
+    for golden in goldens
+        .iter()
+        .filter(|golden| names.is_empty() || names.contains(&golden.name))
+    {
+        let mut control_deck = ControlDeck::with_config(Config {
+            ram_state: RamState::AllZeros,
+            filter: golden.filter,
+            ..Default::default()
+        });
+        control_deck.load_rom_path(test_roms.join(&golden.rom))?;
+        control_deck.cpu_mut().bus.apu.skip_mixing = true;
+        while control_deck.frame_number() < golden.frame {
+            control_deck.clock_frame()?;
+            control_deck.clear_audio_samples();
+        }
+
+        let path = golden_dir.join(&golden.name).with_extension("png");
+        let frame = control_deck.frame_buffer();
+        let unchanged = image::open(&path).is_ok_and(|image| image.into_rgba8().as_raw() == frame);
+        if unchanged {
+            println!("unchanged {}", golden.name);
+            continue;
+        }
+        ImageBuffer::<Rgba<u8>, &[u8]>::from_raw(Ppu::WIDTH, Ppu::HEIGHT, frame)
+            .context("invalid frame size")?
+            .save(&path)
+            .with_context(|| format!("failed to save {path:?}"))?;
+        println!("updated {} -> {path:?}", golden.name);
+    }
+
+    Ok(())
+}
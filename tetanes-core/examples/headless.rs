@@ -0,0 +1,41 @@
+//! Minimal example of embedding `tetanes-core` in a custom frontend.
+//!
+//! Usage: `cargo run -p tetanes-core --example headless -- <rom> [frames]`
+
+use anyhow::Context;
+use tetanes_core::{input::JoypadBtn, prelude::*};
+
+fn main() -> anyhow::Result<()> {
+    let mut args = std::env::args().skip(1);
+    let rom = args.next().context("usage: headless <rom> [frames]")?;
+    let frames = args
+        .next()
+        .map(|frames| frames.parse::<u32>())
+        .transpose()
+        .context("invalid frame count")?
+        .unwrap_or(60);
+
+    let mut control_deck = ControlDeck::new();
+    let loaded_rom = control_deck.load_rom_path(&rom)?;
+    println!("loaded {} ({:?})", loaded_rom.name, loaded_rom.region);
+
+    for frame in 0..frames {
+        // Set controller state before clocking each frame, e.g. tapping Start to get past title
+        // screens
+        control_deck
+            .joypad_mut(Player::One)
+            .set_button(JoypadBtn::Start, frame % 60 < 5);
+
+        let output = control_deck.run_frame()?;
+        // Render `output.frame_buffer` (RGBA, `Frame::SIZE` bytes) and queue
+        // `output.audio_samples` to an audio device here
+        println!(
+            "frame {}: {} cycles, {} audio samples",
+            output.frame_number,
+            output.cycles,
+            output.audio_samples.len()
+        );
+    }
+
+    Ok(())
+}
@@ -36,6 +36,8 @@ pub enum Action {
     FourPlayer(FourPlayer),
     /// Set the slot to use for save states.
     SetSaveSlot(u8),
+    /// Swap back to the previously set save slot.
+    SwapSaveSlot,
     /// Save the current state to the currently set save slot.
     SaveState,
     /// Load the current state from the currently set save slot.
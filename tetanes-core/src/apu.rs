@@ -5,10 +5,11 @@
 use crate::{
     apu::{
         dmc::Dmc,
-        filter::{Consume, FilterChain},
+        filter::{Consume, DmcClickFilter, FilterChain},
         frame_counter::{FrameCounter, FrameType},
         noise::Noise,
         pulse::{OutputFreq, Pulse, PulseChannel},
+        stereo::{Stereo, StereoConfig, StereoMode},
         timer::TimerCycle,
         triangle::Triangle,
     },
@@ -28,6 +29,8 @@ pub mod envelope;
 pub mod filter;
 pub mod frame_counter;
 pub mod length_counter;
+pub mod midi;
+pub mod stereo;
 pub mod timer;
 
 /// Error when parsing `Channel` from a `usize`.
@@ -98,6 +101,14 @@ pub struct Apu {
     pub noise: Noise,
     pub dmc: Dmc,
     pub filter_chain: FilterChain,
+    /// Optional filter to reduce pops from direct DMC output level writes. Not part of save
+    /// states, as it only affects audio output.
+    #[serde(skip)]
+    pub dmc_click_filter: DmcClickFilter,
+    /// Stereo mixing state, or `None` for mono output. Not part of save states, as it only
+    /// affects audio output.
+    #[serde(skip)]
+    pub stereo: Option<Stereo>,
     #[serde(skip, default = "Apu::default_channel_outputs")]
     pub channel_outputs: Vec<f32>,
     #[serde(skip)]
@@ -135,6 +146,8 @@ impl Apu {
             noise: Noise::new(region),
             dmc: Dmc::new(region),
             filter_chain: FilterChain::new(region, sample_rate),
+            dmc_click_filter: DmcClickFilter::default(),
+            stereo: None,
             channel_outputs: Self::default_channel_outputs(),
             audio_samples: Vec::with_capacity((sample_rate / 60.0) as usize),
             sample_rate,
@@ -171,20 +184,51 @@ impl Apu {
                 warn!("invalid channel outputs");
                 return;
             };
-            let pulse_idx = (pulse1 + pulse2) as usize;
-            let tnd_idx = (3.0f32.mul_add(*triangle, 2.0 * noise) + dmc) as usize;
-            let apu_output = PULSE_TABLE[pulse_idx] + TND_TABLE[tnd_idx];
-            let mapper_output = if self.mapper_silenced { 0.0 } else { *mapper };
-
-            self.filter_chain.consume(apu_output + mapper_output);
+            let dmc = if self.dmc_click_filter.enabled {
+                self.dmc_click_filter.consume(*dmc);
+                self.dmc_click_filter.output()
+            } else {
+                *dmc
+            };
+            let mapper = if self.mapper_silenced { 0.0 } else { *mapper };
+            let outputs = [*pulse1, *pulse2, *triangle, *noise, dmc, mapper];
+
+            match &mut self.stereo {
+                Some(stereo) if stereo.panned() => {
+                    self.filter_chain
+                        .consume(Self::mix(&outputs, stereo.gains.map(|[left, _]| left)));
+                    stereo
+                        .filter_chain
+                        .consume(Self::mix(&outputs, stereo.gains.map(|[_, right]| right)));
+                }
+                _ => self
+                    .filter_chain
+                    .consume(Self::mix(&outputs, [1.0; Self::MAX_CHANNEL_COUNT])),
+            }
             self.sample_counter -= 1.0;
             if self.sample_counter <= 1.0 {
-                self.audio_samples.push(self.filter_chain.output());
+                let left = self.filter_chain.output();
+                match &mut self.stereo {
+                    Some(stereo) => {
+                        let right = stereo.filter_chain.output();
+                        self.audio_samples.extend(stereo.output(left, right));
+                    }
+                    None => self.audio_samples.push(left),
+                }
                 self.sample_counter += self.sample_period;
             }
         }
     }
 
+    /// Mix channel outputs, each scaled by a gain, using the non-linear APU mixer lookup tables.
+    fn mix(outputs: &[f32; Self::MAX_CHANNEL_COUNT], gains: [f32; Self::MAX_CHANNEL_COUNT]) -> f32 {
+        let [pulse1, pulse2, triangle, noise, dmc, mapper] = outputs;
+        let pulse_idx = gains[0].mul_add(*pulse1, gains[1] * pulse2) as usize;
+        let tnd_idx =
+            (3.0f32.mul_add(gains[2] * triangle, 2.0 * gains[3] * noise) + gains[4] * dmc) as usize;
+        PULSE_TABLE[pulse_idx] + TND_TABLE[tnd_idx] + gains[5] * mapper
+    }
+
     /// Set the audio sample rate.
     #[inline]
     pub fn set_sample_rate(&mut self, sample_rate: f32) {
@@ -193,6 +237,7 @@ impl Apu {
         self.filter_chain = FilterChain::new(self.region, sample_rate);
         let clock_rate = Cpu::region_clock_rate(self.region);
         self.sample_period = clock_rate / sample_rate;
+        self.update_stereo();
     }
 
     /// Set the frame speed of the APU, which affects the sampling rate.
@@ -202,6 +247,49 @@ impl Apu {
         self.filter_chain = FilterChain::new(self.region, sample_rate);
         let clock_rate = Cpu::region_clock_rate(self.region);
         self.sample_period = clock_rate / sample_rate;
+        self.update_stereo();
+    }
+
+    /// Set how channels are mixed into stereo output. Audio samples are interleaved when the
+    /// [`StereoConfig::channels`] is more than one.
+    pub fn set_stereo(&mut self, config: StereoConfig) {
+        self.stereo = (config.mode != StereoMode::Mono).then(|| {
+            Stereo::new(
+                config,
+                self.region,
+                self.sample_rate / self.speed,
+                self.sample_rate,
+            )
+        });
+    }
+
+    /// Returns the current stereo settings.
+    pub fn stereo(&self) -> StereoConfig {
+        self.stereo
+            .as_ref()
+            .map_or_else(StereoConfig::default, |stereo| stereo.config)
+    }
+
+    /// Number of interleaved channels in the audio samples output.
+    #[must_use]
+    pub fn audio_channels(&self) -> usize {
+        self.stereo().channels()
+    }
+
+    /// Rebuild stereo mixing state after the sample rate or region changes.
+    fn update_stereo(&mut self) {
+        if let Some(stereo) = &self.stereo {
+            self.set_stereo(stereo.config);
+        }
+    }
+
+    /// Set whether to reduce pops caused by large jumps in the DMC output level, such as from
+    /// games writing directly to `$4011`.
+    pub fn set_dmc_click_reduction(&mut self, enabled: bool) {
+        self.dmc_click_filter = DmcClickFilter {
+            enabled,
+            ..DmcClickFilter::default()
+        };
     }
 
     /// Whether a given channel is enabled.
@@ -570,6 +658,7 @@ impl Regional for Apu {
             self.clock_rate = Cpu::region_clock_rate(region);
             self.filter_chain = FilterChain::new(region, self.sample_rate);
             self.sample_period = self.clock_rate / self.sample_rate;
+            self.update_stereo();
             self.frame_counter.set_region(region);
             self.noise.set_region(region);
             self.dmc.set_region(region);
@@ -109,6 +109,10 @@ impl Dmc {
 
     pub fn load_buffer(&mut self, val: u8) {
         if self.bytes_remaining > 0 {
+            debug_assert!(
+                self.buffer_empty,
+                "APU DMC DMA loaded a sample while the buffer was full"
+            );
             self.sample_buffer = val;
             self.buffer_empty = false;
             if self.addr == 0xFFFF {
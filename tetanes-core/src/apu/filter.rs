@@ -201,6 +201,42 @@ impl From<Fir> for Filter {
     }
 }
 
+/// Reduces pops from large jumps in the DMC output level, usually caused by games writing
+/// directly to `$4011`. Jumps are absorbed into an offset that decays back to zero, acting as a
+/// high-pass filter on just those writes while leaving regular sample playback untouched.
+#[derive(Default, Debug, Copy, Clone, Serialize, Deserialize)]
+#[must_use]
+pub struct DmcClickFilter {
+    pub enabled: bool,
+    pub prev_input: f32,
+    pub offset: f32,
+}
+
+impl DmcClickFilter {
+    /// Smallest change in output level treated as a click. Sample playback only changes the
+    /// level by 2 at a time.
+    const THRESHOLD: f32 = 8.0;
+    /// How much of the offset remains after each APU cycle, decaying over ~20ms.
+    const DECAY: f32 = 0.999_97;
+}
+
+impl Consume for DmcClickFilter {
+    fn consume(&mut self, sample: f32) {
+        let delta = sample - self.prev_input;
+        self.prev_input = sample;
+        if delta.abs() >= Self::THRESHOLD {
+            self.offset -= delta;
+        }
+        self.offset *= Self::DECAY;
+    }
+}
+
+impl Sample for DmcClickFilter {
+    fn output(&self) -> f32 {
+        (self.prev_input + self.offset).clamp(0.0, 127.0)
+    }
+}
+
 /// Represents a filter with a given sampling period.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[must_use]
@@ -298,3 +334,30 @@ impl Sample for FilterChain {
         self.filters.last().map_or(0.0, |f| f.filter.output())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dmc_click_filter() {
+        let mut filter = DmcClickFilter {
+            enabled: true,
+            ..DmcClickFilter::default()
+        };
+
+        // Sample playback steps are passed through
+        for level in [2.0, 4.0, 6.0, 4.0] {
+            filter.consume(level);
+            assert!((filter.output() - level).abs() < 0.1, "{level}");
+        }
+
+        // A direct jump is smoothed out and decays back to the new level
+        filter.consume(100.0);
+        assert!(filter.output() < 10.0, "{}", filter.output());
+        for _ in 0..400_000 {
+            filter.consume(100.0);
+        }
+        assert!((filter.output() - 100.0).abs() < 0.1, "{}", filter.output());
+    }
+}
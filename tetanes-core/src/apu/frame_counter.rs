@@ -113,6 +113,12 @@ impl FrameCounter {
     pub fn clock_with(&mut self, cycles: usize, mut on_clock: impl FnMut(FrameType)) -> usize {
         let mut cycles_ran = 0;
         let step_cycles = self.step_cycles[self.mode][self.step] as usize;
+        debug_assert!(
+            self.cycle <= step_cycles,
+            "APU Frame Counter cycle {} overran step {} at {step_cycles}",
+            self.cycle,
+            self.step
+        );
         if self.cycle + cycles >= step_cycles {
             if !self.inhibit_irq && self.mode == 0 && self.step >= 3 {
                 trace!(
@@ -176,14 +182,23 @@ impl FrameCounter {
 impl Reset for FrameCounter {
     fn reset(&mut self, kind: ResetKind) {
         self.cycle = 0;
-        if kind == ResetKind::Hard {
-            self.mode = 0;
-            // After reset, APU acts as if $4017 was written 9-12 clocks before first instruction,
-            // Reset acts as if $00 was written to $4017
-            self.write(0x00, 0);
-            self.write_delay -= 1; // FIXME: Startup timing is slightly wrong, reset_timing fails
-                                   // with the default
-        }
+        // After reset, APU acts as if $4017 was written 9-12 clocks before first instruction.
+        // Power on acts as if $00 was written to $4017, while a soft reset rewrites the last value
+        // written
+        let val = match kind {
+            ResetKind::Soft => self.write_buffer.unwrap_or_else(|| {
+                let mode = if self.mode == 1 { 0x80 } else { 0x00 };
+                let inhibit_irq = if self.inhibit_irq { 0x40 } else { 0x00 };
+                mode | inhibit_irq
+            }),
+            ResetKind::Hard => {
+                self.mode = 0;
+                0x00
+            }
+        };
+        self.write(val, 0);
+        self.write_delay -= 1; // FIXME: Startup timing is slightly wrong, reset_timing fails
+                               // with the default
         self.step = 0;
         self.block_counter = 0;
     }
@@ -0,0 +1,267 @@
+//! Conversion of [`Apu`] channel state into MIDI messages, for driving external synths.
+//!
+//! Channel state is polled, usually once per frame, and compared against the previous poll to
+//! generate note on/off, pitch bend and expression messages:
+//!
+//! | APU Channel | MIDI Channel | Messages                                        |
+//! |-------------|--------------|-------------------------------------------------|
+//! | Pulse1      | 1            | Notes, pitch bend and expression (volume)       |
+//! | Pulse2      | 2            | Notes, pitch bend and expression (volume)       |
+//! | Triangle    | 3            | Notes and pitch bend                            |
+//! | Noise       | 10           | Hi-hat, snare or kick drum notes by noise pitch |
+//! | DMC         | 10           | A percussion note per sample address            |
+
+use crate::{
+    apu::{noise::ShiftMode, Apu},
+    cpu::Cpu,
+};
+
+/// A raw 3-byte MIDI channel message.
+pub type MidiMessage = [u8; 3];
+
+const NOTE_OFF: u8 = 0x80;
+const NOTE_ON: u8 = 0x90;
+const CONTROL_CHANGE: u8 = 0xB0;
+const PITCH_BEND: u8 = 0xE0;
+const EXPRESSION: u8 = 11;
+const ALL_NOTES_OFF: u8 = 123;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct Voice {
+    note: u8,
+    volume: u8,
+}
+
+/// Tracks [`Apu`] channel state to generate MIDI messages.
+#[derive(Default, Debug, Clone)]
+#[must_use]
+pub struct MidiTracker {
+    /// Pulse1, Pulse2 and Triangle voices.
+    voices: [Option<Voice>; 3],
+    bends: [Option<u16>; 3],
+    noise: Option<Voice>,
+    dmc_note: Option<u8>,
+    dmc_bytes_remaining: u16,
+}
+
+impl MidiTracker {
+    /// MIDI channels for Pulse1, Pulse2 and Triangle.
+    pub const CHANNELS: [u8; 3] = [0, 1, 2];
+    /// General MIDI percussion channel, used for Noise and DMC.
+    pub const DRUM_CHANNEL: u8 = 9;
+    /// Pitch bend range in semitones, the General MIDI default.
+    pub const BEND_RANGE: f32 = 2.0;
+    const VELOCITY: u8 = 100;
+    const HI_HAT: u8 = 42;
+    const SNARE: u8 = 38;
+    const KICK: u8 = 36;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Compare the current [`Apu`] channel state to the last update, appending any resulting
+    /// MIDI messages.
+    pub fn update(&mut self, apu: &Apu, messages: &mut Vec<MidiMessage>) {
+        let clock_rate = Cpu::region_clock_rate(apu.region);
+
+        let pulse_frequency = |period: usize| clock_rate / (16.0 * (period as f32 + 1.0));
+        let pulses = [&apu.pulse1, &apu.pulse2].map(|pulse| {
+            (!pulse.is_muted() && pulse.volume() > 0)
+                .then(|| (pulse_frequency(pulse.real_period), pulse.volume()))
+        });
+        let triangle = &apu.triangle;
+        let triangle = (!triangle.silent()
+            && triangle.length.counter > 0
+            && triangle.linear.counter > 0
+            // Ultrasonic periods are used to silence the triangle channel
+            && triangle.timer.period >= 2)
+            .then(|| {
+                let frequency = clock_rate / (32.0 * (triangle.timer.period as f32 + 1.0));
+                (frequency, 15)
+            });
+
+        for (i, target) in [pulses[0], pulses[1], triangle].into_iter().enumerate() {
+            self.update_voice(i, target, messages);
+        }
+        self.update_noise(apu, messages);
+        self.update_dmc(apu, messages);
+    }
+
+    /// Turn off all notes, e.g. when emulation is paused.
+    pub fn silence(&mut self, messages: &mut Vec<MidiMessage>) {
+        for (voice, channel) in self.voices.iter_mut().zip(Self::CHANNELS) {
+            if let Some(voice) = voice.take() {
+                messages.push([NOTE_OFF | channel, voice.note, 0]);
+            }
+        }
+        if let Some(noise) = self.noise.take() {
+            messages.push([NOTE_OFF | Self::DRUM_CHANNEL, noise.note, 0]);
+        }
+        if let Some(note) = self.dmc_note.take() {
+            messages.push([NOTE_OFF | Self::DRUM_CHANNEL, note, 0]);
+        }
+        for channel in Self::CHANNELS.into_iter().chain([Self::DRUM_CHANNEL]) {
+            messages.push([CONTROL_CHANGE | channel, ALL_NOTES_OFF, 0]);
+        }
+    }
+
+    /// Convert a frequency in Hz to the nearest MIDI note and a 14-bit pitch bend for the
+    /// remainder, or `None` if it's outside the MIDI note range.
+    #[must_use]
+    pub fn frequency_to_note(frequency: f32) -> Option<(u8, u16)> {
+        let note = 12.0f32.mul_add((frequency / 440.0).log2(), 69.0);
+        let rounded = note.round();
+        if !(0.0..=127.0).contains(&rounded) {
+            return None;
+        }
+        let bend = ((note - rounded) / Self::BEND_RANGE).mul_add(8192.0, 8192.0);
+        Some((rounded as u8, bend.clamp(0.0, 16383.0) as u16))
+    }
+
+    fn update_voice(
+        &mut self,
+        index: usize,
+        target: Option<(f32, u8)>,
+        messages: &mut Vec<MidiMessage>,
+    ) {
+        let channel = Self::CHANNELS[index];
+        let target = target.and_then(|(frequency, volume)| {
+            Self::frequency_to_note(frequency).map(|(note, bend)| (Voice { note, volume }, bend))
+        });
+        let prev = self.voices[index];
+
+        match target {
+            Some((voice, bend)) => {
+                // A rising volume means the envelope restarted, so treat it as a new note
+                let retrigger = prev.map_or(true, |prev| {
+                    prev.note != voice.note || voice.volume > prev.volume
+                });
+                if retrigger {
+                    if let Some(prev) = prev {
+                        messages.push([NOTE_OFF | channel, prev.note, 0]);
+                    }
+                }
+                if self.bends[index] != Some(bend) {
+                    messages.push([PITCH_BEND | channel, (bend & 0x7F) as u8, (bend >> 7) as u8]);
+                    self.bends[index] = Some(bend);
+                }
+                if prev.map_or(true, |prev| prev.volume != voice.volume) {
+                    let expression = u16::from(voice.volume) * 127 / 15;
+                    messages.push([CONTROL_CHANGE | channel, EXPRESSION, expression as u8]);
+                }
+                if retrigger {
+                    messages.push([NOTE_ON | channel, voice.note, Self::VELOCITY]);
+                }
+                self.voices[index] = Some(voice);
+            }
+            None => {
+                if let Some(prev) = self.voices[index].take() {
+                    messages.push([NOTE_OFF | channel, prev.note, 0]);
+                }
+            }
+        }
+    }
+
+    fn update_noise(&mut self, apu: &Apu, messages: &mut Vec<MidiMessage>) {
+        let noise = &apu.noise;
+        let target = (!noise.silent() && noise.volume() > 0).then(|| {
+            let note = match (noise.shift_mode, noise.timer.period) {
+                (ShiftMode::One, _) | (_, 0..=63) => Self::HI_HAT,
+                (_, 64..=253) => Self::SNARE,
+                _ => Self::KICK,
+            };
+            Voice {
+                note,
+                volume: noise.volume(),
+            }
+        });
+
+        let prev = self.noise;
+        let retrigger = match (prev, target) {
+            (Some(prev), Some(voice)) => prev.note != voice.note || voice.volume > prev.volume,
+            (None, Some(_)) => true,
+            (_, None) => false,
+        };
+        if let Some(prev) = prev.filter(|_| retrigger || target.is_none()) {
+            messages.push([NOTE_OFF | Self::DRUM_CHANNEL, prev.note, 0]);
+        }
+        if let Some(voice) = target.filter(|_| retrigger) {
+            let velocity = (u16::from(voice.volume) * 127 / 15) as u8;
+            messages.push([NOTE_ON | Self::DRUM_CHANNEL, voice.note, velocity]);
+        }
+        self.noise = target;
+    }
+
+    fn update_dmc(&mut self, apu: &Apu, messages: &mut Vec<MidiMessage>) {
+        let dmc = &apu.dmc;
+        // Remaining bytes only go up when a sample is (re)started
+        let started = !dmc.silent() && dmc.bytes_remaining > self.dmc_bytes_remaining;
+        self.dmc_bytes_remaining = dmc.bytes_remaining;
+
+        if started || dmc.bytes_remaining == 0 {
+            if let Some(note) = self.dmc_note.take() {
+                messages.push([NOTE_OFF | Self::DRUM_CHANNEL, note, 0]);
+            }
+        }
+        if started {
+            // Give each sample address its own note in the General MIDI percussion range
+            let note = 35 + (dmc.sample_addr.wrapping_sub(0xC000) >> 6) as u8 % 47;
+            messages.push([NOTE_ON | Self::DRUM_CHANNEL, note, Self::VELOCITY]);
+            self.dmc_note = Some(note);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::NesRegion;
+
+    #[test]
+    fn frequency_to_note() {
+        assert_eq!(MidiTracker::frequency_to_note(440.0), Some((69, 8192)));
+        assert_eq!(
+            MidiTracker::frequency_to_note(261.63).map(|(note, _)| note),
+            Some(60)
+        );
+        let (note, bend) = MidiTracker::frequency_to_note(450.0).unwrap();
+        assert_eq!(note, 69);
+        assert!(bend > 8192, "sharp");
+        assert_eq!(MidiTracker::frequency_to_note(1.0), None);
+        assert_eq!(MidiTracker::frequency_to_note(50_000.0), None);
+    }
+
+    #[test]
+    fn pulse_notes() {
+        let mut apu = Apu::new(NesRegion::Ntsc);
+        let mut tracker = MidiTracker::new();
+        let mut messages = Vec::new();
+
+        tracker.update(&apu, &mut messages);
+        assert!(messages.is_empty(), "silent apu");
+
+        // Constant volume 15, A4 (~440Hz)
+        apu.pulse1.set_enabled(true);
+        apu.pulse1.write_ctrl(0x1F);
+        apu.pulse1.write_timer_lo(0xFD);
+        apu.pulse1.write_timer_hi(0x08);
+        apu.pulse1.length.reload();
+        tracker.update(&apu, &mut messages);
+        assert_eq!(messages.last(), Some(&[NOTE_ON, 69, MidiTracker::VELOCITY]));
+
+        messages.clear();
+        tracker.update(&apu, &mut messages);
+        assert!(messages.is_empty(), "held note");
+
+        apu.pulse1.write_ctrl(0x10);
+        tracker.update(&apu, &mut messages);
+        assert_eq!(messages, [[NOTE_OFF, 69, 0]], "volume 0");
+
+        messages.clear();
+        tracker.silence(&mut messages);
+        assert!(messages
+            .iter()
+            .all(|[status, ..]| status & 0xF0 == CONTROL_CHANGE));
+    }
+}
@@ -0,0 +1,203 @@
+//! Stereo output modes for the [`Apu`] mixer.
+
+use crate::{
+    apu::{filter::FilterChain, Apu},
+    common::NesRegion,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// How [`Apu`] channels are mixed into audio output.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[must_use]
+pub enum StereoMode {
+    /// A single output channel, like the original hardware.
+    #[default]
+    Mono,
+    /// Each channel is placed at its own [`StereoConfig::pan`] position.
+    Panned,
+    /// Mono output widened by adding a short delay to the left side and subtracting it from
+    /// the right.
+    PseudoStereo,
+    /// Panned, with some of each side mixed into the other to avoid the fatigue of hard panning
+    /// on headphones.
+    Headphones,
+}
+
+impl StereoMode {
+    pub const fn as_slice() -> &'static [Self] {
+        &[
+            Self::Mono,
+            Self::Panned,
+            Self::PseudoStereo,
+            Self::Headphones,
+        ]
+    }
+}
+
+impl AsRef<str> for StereoMode {
+    fn as_ref(&self) -> &str {
+        match self {
+            Self::Mono => "Mono",
+            Self::Panned => "Panned",
+            Self::PseudoStereo => "Pseudo-Stereo",
+            Self::Headphones => "Headphones",
+        }
+    }
+}
+
+/// Stereo output settings.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[must_use]
+#[serde(default)]
+pub struct StereoConfig {
+    pub mode: StereoMode,
+    /// Pan position of each [`Channel`](crate::apu::Channel) from `-1.0` (left) to `1.0`
+    /// (right).
+    pub pan: [f32; Apu::MAX_CHANNEL_COUNT],
+    /// Delay used by [`StereoMode::PseudoStereo`], in milliseconds.
+    pub delay_ms: f32,
+    /// How much of the delay is mixed in by [`StereoMode::PseudoStereo`], from `0.0` to `1.0`.
+    pub depth: f32,
+}
+
+impl Default for StereoConfig {
+    fn default() -> Self {
+        Self {
+            mode: StereoMode::Mono,
+            // Pulse1, Pulse2, Triangle, Noise, DMC, Mapper
+            pan: [-0.5, 0.5, 0.0, 0.25, -0.25, 0.0],
+            delay_ms: 15.0,
+            depth: 0.3,
+        }
+    }
+}
+
+impl StereoConfig {
+    /// Number of interleaved channels in the audio samples output.
+    #[must_use]
+    pub const fn channels(&self) -> usize {
+        match self.mode {
+            StereoMode::Mono => 1,
+            StereoMode::Panned | StereoMode::PseudoStereo | StereoMode::Headphones => 2,
+        }
+    }
+
+    /// Left and right gains for each channel. Centered channels play at full volume on both
+    /// sides, so panning never makes a channel louder than in mono.
+    #[must_use]
+    pub fn gains(&self) -> [[f32; 2]; Apu::MAX_CHANNEL_COUNT] {
+        self.pan.map(|pan| {
+            let pan = pan.clamp(-1.0, 1.0);
+            [(1.0 - pan).min(1.0), (1.0 + pan).min(1.0)]
+        })
+    }
+}
+
+/// Mixing state for stereo output modes other than [`StereoMode::Mono`].
+#[derive(Debug, Clone)]
+#[must_use]
+pub struct Stereo {
+    pub config: StereoConfig,
+    pub gains: [[f32; 2]; Apu::MAX_CHANNEL_COUNT],
+    /// Filters for the right side, with the left side using the [`Apu`] filters.
+    pub filter_chain: FilterChain,
+    pub delay: VecDeque<f32>,
+}
+
+impl Stereo {
+    /// How much of each side is mixed into the other for [`StereoMode::Headphones`].
+    const CROSSFEED: f32 = 0.3;
+
+    /// Create stereo mixing state, where `filter_rate` is the rate samples are filtered at and
+    /// `sample_rate` is the rate they are played back at.
+    pub fn new(
+        config: StereoConfig,
+        region: NesRegion,
+        filter_rate: f32,
+        sample_rate: f32,
+    ) -> Self {
+        let delay_len = (config.delay_ms.max(0.0) / 1000.0 * sample_rate) as usize;
+        Self {
+            config,
+            gains: config.gains(),
+            filter_chain: FilterChain::new(region, filter_rate),
+            delay: std::iter::repeat(0.0).take(delay_len).collect(),
+        }
+    }
+
+    /// Whether the left and right sides are mixed separately, otherwise they're derived from
+    /// the mono mix.
+    #[must_use]
+    pub const fn panned(&self) -> bool {
+        matches!(
+            self.config.mode,
+            StereoMode::Panned | StereoMode::Headphones
+        )
+    }
+
+    /// Returns the final left and right samples, given the filtered left (or mono) sample.
+    pub fn output(&mut self, left: f32, right: f32) -> [f32; 2] {
+        match self.config.mode {
+            StereoMode::Mono => [left, left],
+            StereoMode::Panned => [left, right],
+            StereoMode::PseudoStereo => {
+                let depth = self.config.depth.clamp(0.0, 1.0);
+                self.delay.push_back(left);
+                let delayed = self.delay.pop_front().unwrap_or_default();
+                let scale = 1.0 / (1.0 + depth);
+                [
+                    depth.mul_add(delayed, left) * scale,
+                    (-depth).mul_add(delayed, left) * scale,
+                ]
+            }
+            StereoMode::Headphones => {
+                let scale = 1.0 / (1.0 + Self::CROSSFEED);
+                [
+                    Self::CROSSFEED.mul_add(right, left) * scale,
+                    Self::CROSSFEED.mul_add(left, right) * scale,
+                ]
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stereo_output() {
+        let mut config = StereoConfig {
+            pan: [-1.0, 1.0, 0.0, 0.5, -0.5, 0.0],
+            ..Default::default()
+        };
+        assert_eq!(config.channels(), 1);
+        assert_eq!(
+            config.gains(),
+            [
+                [1.0, 0.0],
+                [0.0, 1.0],
+                [1.0, 1.0],
+                [0.5, 1.0],
+                [1.0, 0.5],
+                [1.0, 1.0]
+            ]
+        );
+
+        config.mode = StereoMode::PseudoStereo;
+        config.delay_ms = 1.0;
+        config.depth = 1.0;
+        assert_eq!(config.channels(), 2);
+        let mut stereo = Stereo::new(config, NesRegion::Ntsc, 44_100.0, 1_000.0);
+        assert!(!stereo.panned());
+        assert_eq!(stereo.output(1.0, 0.0), [0.5, 0.5], "delay starts silent");
+        assert_eq!(stereo.output(0.0, 0.0), [0.5, -0.5], "delayed sample");
+
+        config.mode = StereoMode::Headphones;
+        let mut stereo = Stereo::new(config, NesRegion::Ntsc, 44_100.0, 1_000.0);
+        assert!(stereo.panned());
+        let [left, right] = stereo.output(1.0, 0.0);
+        assert!(left > right && right > 0.0, "crossfeed");
+    }
+}
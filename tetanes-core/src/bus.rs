@@ -7,10 +7,11 @@ use crate::{
     cart::Cart,
     common::{Clock, ClockTo, NesRegion, Regional, Reset, ResetKind, Sample, Sram},
     cpu::Cpu,
+    debug::profiler::CodeAddr,
     fs,
     genie::GenieCode,
     input::{Input, InputRegisters, Player},
-    mapper::{Mapped, MappedRead, MappedWrite, Mapper, MemMap},
+    mapper::{Mapped, MappedRead, MappedWrite, Mapper, MapperIrqEvent, MapperWrite, MemMap},
     mem::{Mem, Memory, RamState},
     ppu::{Ppu, Registers},
 };
@@ -144,6 +145,42 @@ impl Bus {
             .map_or(val, |genie_code| genie_code.read(val))
     }
 
+    fn log_mapper_write(&mut self, addr: u16, val: u8) {
+        if !self.ppu.bus.log_mapper_writes {
+            return;
+        }
+        let write = MapperWrite {
+            addr,
+            val,
+            frame: self.ppu.frame_number(),
+            scanline: self.ppu.scanline,
+            cycle: self.ppu.cycle,
+        };
+        self.ppu.bus.log_mapper_write(write);
+    }
+
+    /// Record the mapper asserting its IRQ line at the current PPU position.
+    pub fn log_mapper_irq(&mut self) {
+        let irq = MapperIrqEvent {
+            frame: self.ppu.frame_number(),
+            scanline: self.ppu.scanline,
+            cycle: self.ppu.cycle,
+        };
+        self.ppu.bus.log_mapper_irq(irq);
+    }
+
+    /// Where the code at CPU address `addr` lives, identifying its PRG-ROM bank if any.
+    pub fn code_addr(&self, addr: u16) -> CodeAddr {
+        let prg_offset = match addr {
+            0x4020..=0xFFFF => match self.ppu.bus.mapper.map_peek(addr) {
+                MappedRead::PrgRom(offset) => Some(offset),
+                _ => None,
+            },
+            _ => None,
+        };
+        CodeAddr { addr, prg_offset }
+    }
+
     #[must_use]
     pub fn audio_samples(&self) -> &[f32] {
         &self.apu.audio_samples
@@ -247,7 +284,11 @@ impl Mem for Bus {
                         }
                     }
                 }
-                MappedWrite::PrgRamProtect(protect) => self.prg_ram_protect = protect,
+                MappedWrite::PrgRamProtect(protect) => {
+                    self.prg_ram_protect = protect;
+                    self.log_mapper_write(addr, val);
+                }
+                MappedWrite::Bus | MappedWrite::None => self.log_mapper_write(addr, val),
                 _ => (),
             },
             0x2000 => self.ppu.write_ctrl(val),
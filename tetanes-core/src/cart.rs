@@ -3,11 +3,12 @@
 use crate::{
     common::{NesRegion, Regional},
     fs,
+    input::Peripherals,
     mapper::{
         self, m024_m026_vrc6::Revision as Vrc6Revision, m034_nina001::Nina001, Axrom, BandaiFCG,
         Bf909x, Bnrom, Cnrom, ColorDreams, Dxrom154, Dxrom206, Dxrom76, Dxrom88, Dxrom95, Exrom,
-        Fxrom, Gxrom, JalecoSs88006, Mapper, Mmc1Revision, Namco163, Nina003006, Nrom, Pxrom,
-        SunsoftFme7, Sxrom, Txrom, Uxrom, Vrc6,
+        Fxrom, Gxrom, JalecoSs88006, JyCompany, Mapper, Mmc1Revision, Namco163, Nanjing,
+        Nina003006, Nrom, Pxrom, SunsoftFme7, Sxrom, Txrom, Uxrom, Vrc6, Wxn,
     },
     mem::{Memory, RamState},
     ppu::Mirroring,
@@ -17,10 +18,13 @@ use std::{
     fs::File,
     io::{BufReader, Read},
     path::Path,
+    sync::OnceLock,
 };
 use thiserror::Error;
 use tracing::{debug, error, info};
 
+pub mod header_fix;
+
 const PRG_ROM_BANK_SIZE: usize = 0x4000;
 const CHR_ROM_BANK_SIZE: usize = 0x2000;
 
@@ -62,12 +66,40 @@ pub struct GameInfo {
     pub submapper_num: u8,
 }
 
+/// Where the region of a [`Cart`] was detected from.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[must_use]
+pub enum RegionSource {
+    /// The TV system flag of the NES 2.0 header.
+    Header,
+    /// The game database, matched by the ROM checksum.
+    Database,
+    /// Region tags in the filename, like `(E)` or `(PAL)`.
+    Filename,
+    /// Nothing indicated a region, so NTSC is assumed.
+    #[default]
+    Default,
+}
+
+impl std::fmt::Display for RegionSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Self::Header => "ROM header",
+            Self::Database => "game database",
+            Self::Filename => "filename",
+            Self::Default => "default",
+        };
+        write!(f, "{s}")
+    }
+}
+
 /// An NES cartridge.
 #[must_use]
 pub struct Cart {
     name: String,
     header: NesHeader,
     region: NesRegion,
+    region_source: RegionSource,
     ram_state: RamState,
     pub(crate) mapper: Mapper,
     pub(crate) chr_rom: Memory, // Character ROM
@@ -76,6 +108,7 @@ pub struct Cart {
     pub(crate) prg_ram: Memory, // Program RAM
     pub(crate) ex_ram: Memory,  // Internal Extra RAM
     pub(crate) game_info: Option<GameInfo>,
+    crc32: u32,
 }
 
 impl Default for Cart {
@@ -90,6 +123,7 @@ impl Cart {
             name: "Empty Cart".to_string(),
             header: NesHeader::default(),
             region: NesRegion::Ntsc,
+            region_source: RegionSource::Default,
             ram_state: RamState::default(),
             mapper: Mapper::none(),
             chr_rom: Memory::with_size(CHR_ROM_BANK_SIZE),
@@ -98,6 +132,7 @@ impl Cart {
             prg_ram: Memory::new(),
             ex_ram: Memory::new(),
             game_info: None,
+            crc32: 0,
         };
         empty.mapper = Nrom::load(&mut empty).expect("valid empty mapper");
         empty
@@ -178,27 +213,15 @@ impl Cart {
             }
         }
 
-        let game_info = Self::lookup_info(&prg_rom, &chr_rom);
-        let region = if matches!(header.variant, NesVariant::INes | NesVariant::Nes2) {
-            match header.tv_mode {
-                1 => NesRegion::Pal,
-                3 => NesRegion::Dendy,
-                _ => game_info
-                    .as_ref()
-                    .map(|info| info.region)
-                    .unwrap_or_default(),
-            }
-        } else {
-            game_info
-                .as_ref()
-                .map(|info| info.region)
-                .unwrap_or_default()
-        };
+        let crc32 = Self::compute_crc32(&prg_rom, &chr_rom);
+        let game_info = Self::lookup_info(crc32);
+        let (region, region_source) = Self::detect_region(&name, &header, game_info.as_ref());
 
         let mut cart = Self {
             name,
             header,
             region,
+            region_source,
             ram_state,
             mapper: Mapper::none(),
             chr_rom,
@@ -207,6 +230,7 @@ impl Cart {
             prg_ram,
             ex_ram: Memory::new(),
             game_info,
+            crc32,
         };
         cart.mapper = match cart.header.mapper_num {
             0 => Nrom::load(&mut cart)?,
@@ -238,8 +262,11 @@ impl Cart {
             76 => Dxrom76::load(&mut cart)?,
             79 | 113 | 146 => Nina003006::load(&mut cart)?,
             88 => Dxrom88::load(&mut cart)?,
+            90 | 209 | 211 => JyCompany::load(&mut cart)?,
             95 => Dxrom95::load(&mut cart)?,
             154 => Dxrom154::load(&mut cart)?,
+            163 => Nanjing::load(&mut cart)?,
+            176 if Wxn::supported(&cart) => Wxn::load(&mut cart)?,
             206 => Dxrom206::load(&mut cart)?,
             155 => Sxrom::load(&mut cart, Mmc1Revision::A)?,
             _ => Mapper::none(),
@@ -309,6 +336,11 @@ impl Cart {
         self.header.flags & 0x02 == 0x02
     }
 
+    /// Returns where the region of this cartridge was detected from.
+    pub const fn region_source(&self) -> RegionSource {
+        self.region_source
+    }
+
     /// Returns `RamState`.
     pub const fn ram_state(&self) -> RamState {
         self.ram_state
@@ -327,6 +359,17 @@ impl Cart {
         }
     }
 
+    /// Returns the combined PRG-ROM and CHR-ROM CRC32 for this Cart.
+    #[must_use]
+    pub const fn crc32(&self) -> u32 {
+        self.crc32
+    }
+
+    /// Returns the recommended input peripherals for this Cart, if any.
+    pub fn peripherals(&self) -> Option<Peripherals> {
+        Peripherals::lookup(self.crc32)
+    }
+
     /// Returns the Mapper number for this Cart.
     #[must_use]
     pub fn mapper_num(&self) -> u16 {
@@ -383,29 +426,116 @@ impl Cart {
         }
     }
 
-    fn lookup_info(prg_rom: &[u8], chr: &[u8]) -> Option<GameInfo> {
-        const GAME_DB: &[u8] = include_bytes!("../game_db.dat");
+    /// Game database CRC32 of raw ROM data, computed without loading the ROM into a `Cart`.
+    ///
+    /// # Errors
+    ///
+    /// If the NES header is invalid or the PRG-ROM is shorter than the header claims, then an
+    /// error is returned.
+    pub fn rom_crc32(mut rom_data: &[u8]) -> Result<u32> {
+        let header = NesHeader::load(&mut rom_data)?;
+        let prg_rom_len = (header.prg_rom_banks as usize) * PRG_ROM_BANK_SIZE;
+        let chr_rom_len = (header.chr_rom_banks as usize) * CHR_ROM_BANK_SIZE;
+        let prg_rom = rom_data
+            .get(..prg_rom_len)
+            .ok_or_else(|| Error::InvalidHeader {
+                byte: 4,
+                value: header.prg_rom_banks as u8,
+                message: format!(
+                    "expected `{}` prg-rom banks ({prg_rom_len} total bytes)",
+                    header.prg_rom_banks
+                ),
+            })?;
+        let chr_rom = &rom_data[prg_rom_len..];
+        let chr_rom = &chr_rom[..chr_rom_len.min(chr_rom.len())];
+        Ok(Self::compute_crc32(prg_rom, chr_rom))
+    }
 
-        let Ok(games) = fs::load_bytes::<Vec<GameInfo>>(GAME_DB) else {
-            error!("failed to load `game_regions.dat`");
-            return None;
-        };
+    /// Game database CRC32 of the PRG-ROM followed by any CHR-ROM, excluding the header.
+    fn compute_crc32(prg_rom: &[u8], chr_rom: &[u8]) -> u32 {
+        let crc32 = fs::compute_crc32(prg_rom);
+        if chr_rom.is_empty() {
+            crc32
+        } else {
+            fs::compute_combine_crc32(crc32, chr_rom)
+        }
+    }
 
-        let mut crc32 = fs::compute_crc32(prg_rom);
-        if !chr.is_empty() {
-            crc32 = fs::compute_combine_crc32(crc32, chr);
+    /// Detects the region from the NES 2.0 header, then the game database, then region tags in
+    /// the filename, falling back to NTSC.
+    fn detect_region(
+        name: &str,
+        header: &NesHeader,
+        game_info: Option<&GameInfo>,
+    ) -> (NesRegion, RegionSource) {
+        let header_region = match header.variant {
+            NesVariant::Nes2 => match header.tv_mode {
+                1 => Some(NesRegion::Pal),
+                3 => Some(NesRegion::Dendy),
+                _ => None,
+            },
+            _ => None,
+        };
+        if let Some(region) = header_region {
+            (region, RegionSource::Header)
+        } else if let Some(info) = game_info.filter(|info| !info.region.is_auto()) {
+            (info.region, RegionSource::Database)
+        } else if let Some(region) = Self::filename_region(name) {
+            (region, RegionSource::Filename)
+        } else {
+            (NesRegion::Ntsc, RegionSource::Default)
         }
+    }
+
+    /// Detects the region from the parenthesized tags of a ROM filename, like `(E)`, `(Europe)`
+    /// or `(PAL)`. Returns `None` if no tags name a region or they name regions with different
+    /// timing, like `(USA, Europe)`.
+    fn filename_region(name: &str) -> Option<NesRegion> {
+        let filename = Path::new(name)
+            .file_name()
+            .map_or_else(|| name.into(), |filename| filename.to_string_lossy());
+        let mut regions = filename
+            .split('(')
+            .skip(1)
+            .filter_map(|tag| tag.split_once(')').map(|(tag, _)| tag))
+            .flat_map(|tag| tag.split(','))
+            .filter_map(|code| match code.trim() {
+                "E" | "A" | "G" | "F" | "S" | "I" | "Sw" | "Nl" | "Sc" | "UK" | "Europe"
+                | "Australia" | "Germany" | "France" | "Spain" | "Italy" | "Sweden"
+                | "Netherlands" | "Scandinavia" | "PAL" => Some(NesRegion::Pal),
+                "U" | "J" | "JU" | "K" | "USA" | "Japan" | "Korea" | "Canada" | "NTSC" => {
+                    Some(NesRegion::Ntsc)
+                }
+                "Dendy" => Some(NesRegion::Dendy),
+                _ => None,
+            });
+        let region = regions.next()?;
+        regions.all(|other| other == region).then_some(region)
+    }
+
+    /// Look up a game in the game database by its [`Cart::rom_crc32`]. The database is decoded
+    /// once on first use.
+    pub fn lookup_info(crc32: u32) -> Option<GameInfo> {
+        const GAME_DB: &[u8] = include_bytes!("../game_db.dat");
+        static GAMES: OnceLock<Vec<GameInfo>> = OnceLock::new();
+
+        let games = GAMES.get_or_init(|| {
+            fs::load_bytes::<Vec<GameInfo>>(GAME_DB).unwrap_or_else(|err| {
+                error!("failed to load `game_db.dat`: {err:?}");
+                Vec::new()
+            })
+        });
 
         match games.binary_search_by(|game| game.crc32.cmp(&crc32)) {
             Ok(index) => {
-                info!(
+                debug!(
                     "found game matching crc: {crc32:#010X}. info: {:?}",
                     games[index]
                 );
                 Some(games[index].clone())
             }
             Err(_) => {
-                info!("no game found matching crc: {crc32:#010X}");
+                debug!("no game found matching crc: {crc32:#010X}");
                 None
             }
         }
@@ -446,6 +576,7 @@ impl std::fmt::Debug for Cart {
             .field("name", &self.name)
             .field("header", &self.header)
             .field("region", &self.region)
+            .field("region_source", &self.region_source)
             .field("ram_state", &self.ram_state)
             .field("mapper", &self.mapper)
             .field("mirroring", &self.mirroring())
@@ -828,7 +959,7 @@ impl NesHeader {
             173 => "Mapper 173",
             174 => "Mapper 174",
             175 => "Mapper 175 - Kaiser KS-7022",
-            176 => "Mapper 176 - MMC3",
+            176 => "Mapper 176 - Waixing WXN",
             177 => "Mapper 177 - Hénggé Diànzǐ",
             178 => "Mapper 178",
             179 => "Mapper 179",
@@ -863,7 +994,7 @@ impl NesHeader {
             208 => "Mapper 208 - MMC3",
             209 => "Mapper 209 - JY Company",
             210 => "Mapper 210 - Namco",
-            211 => "Mapper 211 - JyCompany",
+            211 => "Mapper 211 - JY Company",
             212 => "Mapper 212",
             213 => "Mapper 213",
             214 => "Mapper 214",
@@ -977,4 +1108,67 @@ mod tests {
             },
         ),
     );
+
+    #[test]
+    fn filename_region() {
+        for (name, region) in [
+            ("Game (E).nes", Some(NesRegion::Pal)),
+            ("roms/Game (Europe) (Rev A).nes", Some(NesRegion::Pal)),
+            ("Game (Germany, Sweden) [!].nes", Some(NesRegion::Pal)),
+            ("Game [PAL].nes", None),
+            ("Game (PAL).nes", Some(NesRegion::Pal)),
+            ("Game (U) [!].nes", Some(NesRegion::Ntsc)),
+            ("Game (Dendy).nes", Some(NesRegion::Dendy)),
+            ("Game (USA, Europe).nes", None),
+            ("Game (Hack).nes", None),
+            ("(E)/Game.nes", None),
+        ] {
+            assert_eq!(Cart::filename_region(name), region, "{name}");
+        }
+    }
+
+    #[test]
+    fn detect_region() {
+        let mut rom = vec![0x00; 16 + 0x4000 + 0x2000];
+        rom[..6].copy_from_slice(b"NES\x1A\x01\x01");
+
+        let cart = Cart::from_rom("Game (E).nes", &mut rom.as_slice(), RamState::AllZeros)
+            .expect("valid rom");
+        assert_eq!(cart.region(), NesRegion::Pal);
+        assert_eq!(cart.region_source(), RegionSource::Filename);
+
+        let cart =
+            Cart::from_rom("Game.nes", &mut rom.as_slice(), RamState::AllZeros).expect("valid rom");
+        assert_eq!(cart.region(), NesRegion::Ntsc);
+        assert_eq!(cart.region_source(), RegionSource::Default);
+
+        // The iNES 1.0 PAL flag is unreliable and ignored
+        rom[9] = 0x01;
+        let cart =
+            Cart::from_rom("Game.nes", &mut rom.as_slice(), RamState::AllZeros).expect("valid rom");
+        assert_eq!(cart.region(), NesRegion::Ntsc);
+        assert_eq!(cart.region_source(), RegionSource::Default);
+        rom[9] = 0x00;
+
+        // NES 2.0 header takes priority
+        rom[12] = 0x03;
+        rom[7] = 0x08;
+        let cart = Cart::from_rom("Game (E).nes", &mut rom.as_slice(), RamState::AllZeros)
+            .expect("valid rom");
+        assert_eq!(cart.region(), NesRegion::Dendy);
+        assert_eq!(cart.region_source(), RegionSource::Header);
+    }
+
+    #[test]
+    fn rom_crc32() {
+        let mut rom = vec![0x00; 16 + 0x4000 + 0x2000];
+        rom[..6].copy_from_slice(b"NES\x1A\x01\x01");
+        rom[16] = 0x4C;
+        rom[16 + 0x4000] = 0xFF;
+
+        let cart =
+            Cart::from_rom("Game.nes", &mut rom.as_slice(), RamState::AllZeros).expect("valid rom");
+        assert_eq!(Cart::rom_crc32(&rom).expect("valid rom"), cart.crc32());
+        assert!(Cart::rom_crc32(&rom[..16 + 0x2000]).is_err());
+    }
 }
@@ -0,0 +1,223 @@
+//! Detection and repair of common iNES header problems.
+//!
+//! Many ROM dumps in circulation have headers that were written by old tools or edited by hand,
+//! resulting in corrupted flags or the wrong mapper. Problems are detected by inspecting the raw
+//! header and by comparing it against the game database, which is keyed by the CRC32 of the
+//! PRG-ROM and CHR-ROM and so isn't affected by a bad header.
+//!
+//! <https://www.nesdev.org/wiki/INES#Variant_comparison>
+
+use crate::cart::{Cart, NesHeader, CHR_ROM_BANK_SIZE, PRG_ROM_BANK_SIZE};
+use serde::{Deserialize, Serialize};
+
+const HEADER_SIZE: usize = 16;
+const TRAINER_SIZE: usize = 512;
+
+/// A problem found in an iNES header.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[must_use]
+pub enum HeaderIssue {
+    /// Bytes 7-15 contain garbage, typically the string `DiskDude!` written by an old dumping
+    /// tool, which corrupts the upper mapper bits.
+    DiskDude,
+    /// A 512-byte trainer precedes PRG-ROM. Trainers were added by copier devices and are not
+    /// needed by the game.
+    Trainer,
+    /// The mapper number doesn't match the game database entry for this ROM.
+    WrongMapper { header: u16, expected: u16 },
+}
+
+impl HeaderIssue {
+    /// Human-readable description of the issue.
+    #[must_use]
+    pub fn description(&self) -> String {
+        match self {
+            Self::DiskDude => "Header bytes 7-15 contain garbage (e.g. `DiskDude!`)".to_string(),
+            Self::Trainer => "ROM contains a 512-byte trainer".to_string(),
+            Self::WrongMapper { header, expected } => {
+                format!("Header mapper {header} should be mapper {expected}")
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for HeaderIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.description())
+    }
+}
+
+/// Detect problems with the header of `rom`, which should contain the entire ROM file.
+///
+/// Data that isn't an iNES ROM, or that is too short to match its header, returns no issues so
+/// that loading reports the error as usual.
+pub fn detect(rom: &[u8]) -> Vec<HeaderIssue> {
+    let mut issues = Vec::new();
+    if rom.len() < HEADER_SIZE || rom[0..4] != *b"NES\x1a" {
+        return issues;
+    }
+
+    if has_garbage(&rom[..HEADER_SIZE]) {
+        issues.push(HeaderIssue::DiskDude);
+    }
+    if rom[6] & 0x04 == 0x04 {
+        issues.push(HeaderIssue::Trainer);
+    }
+
+    // Check the mapper against the database using the header with any other issues repaired,
+    // since the mapper can't be read reliably until they are.
+    let repaired = repair(rom, &issues);
+    let Ok(header) = NesHeader::load(&mut &repaired[..HEADER_SIZE]) else {
+        return issues;
+    };
+    let prg_rom_len = usize::from(header.prg_rom_banks) * PRG_ROM_BANK_SIZE;
+    let chr_rom_len = usize::from(header.chr_rom_banks) * CHR_ROM_BANK_SIZE;
+    let data = &repaired[HEADER_SIZE..];
+    if data.len() < prg_rom_len + chr_rom_len {
+        return issues;
+    }
+    let (prg_rom, chr_rom) = data.split_at(prg_rom_len);
+    let crc32 = Cart::compute_crc32(prg_rom, &chr_rom[..chr_rom_len]);
+    if let Some(info) = Cart::lookup_info(crc32) {
+        if info.mapper_num != header.mapper_num {
+            issues.push(HeaderIssue::WrongMapper {
+                header: header.mapper_num,
+                expected: info.mapper_num,
+            });
+        }
+    }
+
+    issues
+}
+
+/// Return a copy of `rom` with `issues` repaired.
+#[must_use]
+pub fn repair(rom: &[u8], issues: &[HeaderIssue]) -> Vec<u8> {
+    let mut rom = rom.to_vec();
+    if rom.len() < HEADER_SIZE {
+        return rom;
+    }
+
+    for issue in issues {
+        match issue {
+            HeaderIssue::DiskDude => rom[7..HEADER_SIZE].fill(0x00),
+            HeaderIssue::Trainer => {
+                rom[6] &= !0x04;
+                let trainer_end = (HEADER_SIZE + TRAINER_SIZE).min(rom.len());
+                rom.drain(HEADER_SIZE..trainer_end);
+            }
+            HeaderIssue::WrongMapper { expected, .. } => set_mapper(&mut rom, *expected),
+        }
+    }
+
+    rom
+}
+
+/// Whether the header has garbage in bytes 7-15 that iNES readers would misinterpret.
+fn has_garbage(header: &[u8]) -> bool {
+    match header[7] & 0x0C {
+        // NES 2.0 uses all 16 bytes
+        0x08 => false,
+        // Archaic iNES flag is almost always the result of garbage
+        0x04 | 0x0C => true,
+        _ => header[12..HEADER_SIZE].iter().any(|&value| value > 0),
+    }
+}
+
+fn set_mapper(rom: &mut [u8], mapper_num: u16) {
+    let [lo, hi] = mapper_num.to_le_bytes();
+    rom[6] = (rom[6] & 0x0F) | ((lo & 0x0F) << 4);
+    rom[7] = (rom[7] & 0x0F) | (lo & 0xF0);
+    if hi > 0 && rom[7] & 0x0C != 0x08 {
+        // Mappers above 255 require NES 2.0
+        rom[7] = (rom[7] & !0x0C) | 0x08;
+        rom[8..HEADER_SIZE].fill(0x00);
+    }
+    if rom[7] & 0x0C == 0x08 {
+        rom[8] = (rom[8] & 0xF0) | (hi & 0x0F);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rom(header: [u8; HEADER_SIZE], trainer: bool) -> Vec<u8> {
+        let mut rom = header.to_vec();
+        if trainer {
+            rom.extend([0xFF; TRAINER_SIZE]);
+        }
+        let banks =
+            usize::from(header[4]) * PRG_ROM_BANK_SIZE + usize::from(header[5]) * CHR_ROM_BANK_SIZE;
+        rom.extend((0..banks).map(|i| (i % 251) as u8));
+        rom
+    }
+
+    #[rustfmt::skip]
+    const HEADER: [u8; HEADER_SIZE] = [
+        0x4E, 0x45, 0x53, 0x1A,
+        0x01, 0x01, 0x01, 0x00,
+        0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00,
+    ];
+
+    #[test]
+    fn valid_header() {
+        assert!(detect(&rom(HEADER, false)).is_empty());
+        assert!(detect(b"not a rom").is_empty());
+    }
+
+    #[test]
+    fn disk_dude() {
+        let mut header = HEADER;
+        header[7..HEADER_SIZE].copy_from_slice(b"DiskDude!");
+        let rom = rom(header, false);
+        let issues = detect(&rom);
+        assert_eq!(issues, [HeaderIssue::DiskDude]);
+
+        let repaired = repair(&rom, &issues);
+        assert!(detect(&repaired).is_empty());
+        let header = NesHeader::load(&mut repaired.as_slice()).expect("valid header");
+        assert_eq!(header.mapper_num, 0);
+    }
+
+    #[test]
+    fn trainer() {
+        let mut header = HEADER;
+        header[6] |= 0x04;
+        let rom = rom(header, true);
+        let issues = detect(&rom);
+        assert_eq!(issues, [HeaderIssue::Trainer]);
+
+        let repaired = repair(&rom, &issues);
+        assert_eq!(repaired.len(), rom.len() - TRAINER_SIZE);
+        assert!(detect(&repaired).is_empty());
+        assert_eq!(repaired[HEADER_SIZE..], rom[HEADER_SIZE + TRAINER_SIZE..]);
+    }
+
+    #[test]
+    fn wrong_mapper() {
+        let rom = rom(HEADER, false);
+        let repaired = repair(
+            &rom,
+            &[HeaderIssue::WrongMapper {
+                header: 0,
+                expected: 66,
+            }],
+        );
+        let header = NesHeader::load(&mut repaired.as_slice()).expect("valid header");
+        assert_eq!(header.mapper_num, 66);
+        assert_eq!(header.variant, crate::cart::NesVariant::INes);
+
+        let repaired = repair(
+            &rom,
+            &[HeaderIssue::WrongMapper {
+                header: 0,
+                expected: 268,
+            }],
+        );
+        let header = NesHeader::load(&mut repaired.as_slice()).expect("valid header");
+        assert_eq!(header.mapper_num, 268);
+        assert_eq!(header.variant, crate::cart::NesVariant::Nes2);
+    }
+}
@@ -273,6 +273,22 @@ pub(crate) mod tests {
         };
     }
 
+    /// Defines tests for ROMs that report their result at `$6000`, following the convention used
+    /// by blargg's test suites. Only run with the `test-roms` feature.
+    #[macro_export]
+    macro_rules! status_test_roms {
+        ($mod:ident, $directory:expr, $( $(#[ignore = $reason:expr])? $test:ident ),* $(,)?) => {
+            #[cfg(feature = "test-roms")]
+            mod $mod {$(
+                $(#[ignore = $reason])?
+                #[test]
+                fn $test() -> anyhow::Result<()> {
+                    $crate::common::tests::test_rom_status($directory, stringify!($test))
+                }
+            )*}
+        };
+    }
+
     // TODO: Instead of a bunch of optional fields, it should be an enum:
     // enum FrameAction {
     //   DeckAction(DeckAction),
@@ -347,6 +363,7 @@ pub(crate) mod tests {
                 Action::LoadState
                 | Action::SaveState
                 | Action::SetSaveSlot(_)
+                | Action::SwapSaveSlot
                 | Action::ToggleApuChannel(_)
                 | Action::ZapperAimOffscreen
                 | Action::FourPlayer(_) => (),
@@ -521,6 +538,172 @@ pub(crate) mod tests {
         Ok(())
     }
 
+    /// Boot a test ROM headless and assert the result code it writes to `$6000`.
+    ///
+    /// `$6001-$6003` hold a signature once the status is valid. The status is `$80` while
+    /// running, `$81` when the ROM requests a reset, and the result code once done, with `$00`
+    /// meaning passed. A description of the result is written as text starting at `$6004`.
+    #[allow(unused)]
+    pub(crate) fn test_rom_status(directory: &str, test_name: &str) -> anyhow::Result<()> {
+        use crate::mem::Mem;
+
+        const STATUS_ADDR: u16 = 0x6000;
+        const SIGNATURE: [u8; 3] = [0xDE, 0xB0, 0x61];
+        const STATUS_RUNNING: u8 = 0x80;
+        const STATUS_RESET: u8 = 0x81;
+        // The ROM needs time to finish writing to $6000 before being reset
+        const RESET_DELAY_FRAMES: u32 = 6;
+        const MAX_FRAMES: u32 = 60 * 60;
+
+        let rom = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join(directory)
+            .join(test_name)
+            .with_extension("nes");
+        assert!(rom.exists(), "No test rom found for {rom:?}");
+
+        let mut deck = load_control_deck(&rom);
+        deck.cpu_mut().bus.apu.skip_mixing = true;
+
+        // Frame numbers restart on reset, so count frames separately
+        let mut reset_delay = None;
+        for _ in 0..MAX_FRAMES {
+            deck.clock_frame()
+                .with_context(|| format!("failed to clock {rom:?}"))?;
+            deck.clear_audio_samples();
+
+            let cpu = deck.cpu();
+            let signature = [0x6001, 0x6002, 0x6003].map(|addr| cpu.peek(addr));
+            if signature != SIGNATURE {
+                continue;
+            }
+            match cpu.peek(STATUS_ADDR) {
+                STATUS_RUNNING => reset_delay = None,
+                STATUS_RESET => {
+                    let delay = reset_delay.get_or_insert(RESET_DELAY_FRAMES);
+                    if *delay == 0 {
+                        deck.reset(ResetKind::Soft);
+                        // Clear the stale reset request so only a new one is honored
+                        deck.cpu_mut().bus.write(STATUS_ADDR, STATUS_RUNNING);
+                        reset_delay = None;
+                    } else {
+                        *delay -= 1;
+                    }
+                }
+                status => {
+                    let message = (0x6004..0x7000)
+                        .map(|addr| cpu.peek(addr))
+                        .take_while(|&byte| byte != 0x00)
+                        .map(char::from)
+                        .collect::<String>();
+                    assert!(
+                        status == 0x00,
+                        "{rom:?} failed with status ${status:02X}: {}",
+                        message.trim()
+                    );
+                    return Ok(());
+                }
+            }
+        }
+
+        anyhow::bail!("{rom:?} didn't finish within {MAX_FRAMES} frames")
+    }
+
+    /// A frame compared pixel for pixel against a checked-in PNG in [`GOLDEN_DIR`].
+    #[derive(Debug, Clone, Deserialize)]
+    #[must_use]
+    struct Golden {
+        name: String,
+        /// ROM path, relative to `test_roms`.
+        rom: PathBuf,
+        frame: u32,
+        filter: VideoFilter,
+    }
+
+    const GOLDEN_DIR: &str = "test_roms/golden";
+
+    /// Run each ROM listed in `test_roms/golden/goldens.json` to its frame and compare the frame
+    /// buffer against its golden image. Mismatched frames are saved to `test_results/golden`
+    /// along with a diff highlighting changed pixels in red.
+    ///
+    /// Regenerate golden images after intended visual changes with
+    /// `cargo run -p tetanes-core --example golden -- [NAME...]`.
+    #[test]
+    fn golden_images() -> anyhow::Result<()> {
+        let base_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let golden_dir = base_dir.join(GOLDEN_DIR);
+        let goldens_file = golden_dir.join("goldens.json");
+        let goldens: Vec<Golden> = File::open(&goldens_file)
+            .map(BufReader::new)
+            .map_err(anyhow::Error::from)
+            .and_then(|reader| Ok(serde_json::from_reader(reader)?))
+            .with_context(|| format!("failed to read golden list: {goldens_file:?}"))?;
+        assert!(!goldens.is_empty(), "No golden images found");
+
+        let mut failures = Vec::new();
+        for golden in &goldens {
+            let mut deck = ControlDeck::with_config(Config {
+                ram_state: RamState::AllZeros,
+                filter: golden.filter,
+                ..Default::default()
+            });
+            deck.load_rom_path(base_dir.join("test_roms").join(&golden.rom))?;
+            deck.cpu_mut().bus.apu.skip_mixing = true;
+            while deck.frame_number() < golden.frame {
+                deck.clock_frame()?;
+                deck.clear_audio_samples();
+            }
+
+            let path = golden_dir.join(&golden.name).with_extension("png");
+            let expected = match image::open(&path) {
+                Ok(image) => image.into_rgba8(),
+                Err(err) => {
+                    failures.push(format!("{}: failed to open {path:?}: {err}", golden.name));
+                    continue;
+                }
+            };
+            let actual = deck.frame_buffer();
+            let diff_count = expected
+                .chunks_exact(4)
+                .zip(actual.chunks_exact(4))
+                .filter(|(expected, actual)| expected != actual)
+                .count();
+            if expected.dimensions() == (Ppu::WIDTH, Ppu::HEIGHT) && diff_count == 0 {
+                continue;
+            }
+
+            let result_dir = base_dir.join(RESULT_DIR).join("golden");
+            fs::create_dir_all(&result_dir)?;
+            let actual_path = result_dir.join(&golden.name).with_extension("png");
+            ImageBuffer::<Rgba<u8>, &[u8]>::from_raw(Ppu::WIDTH, Ppu::HEIGHT, actual)
+                .expect("valid frame")
+                .save(&actual_path)?;
+            let diff = ImageBuffer::from_fn(Ppu::WIDTH, Ppu::HEIGHT, |x, y| {
+                let actual = &actual[4 * (y * Ppu::WIDTH + x) as usize..][..4];
+                match expected.get_pixel_checked(x, y) {
+                    Some(expected) if expected.0 == actual => {
+                        Rgba([actual[0] / 4, actual[1] / 4, actual[2] / 4, 0xFF])
+                    }
+                    _ => Rgba([0xFF, 0x00, 0x00, 0xFF]),
+                }
+            });
+            let diff_path = result_dir
+                .join(format!("{}_diff", golden.name))
+                .with_extension("png");
+            diff.save(&diff_path)?;
+            failures.push(format!(
+                "{}: {diff_count} pixels differ -> {actual_path:?}, {diff_path:?}",
+                golden.name
+            ));
+        }
+
+        assert!(
+            failures.is_empty(),
+            "mismatched golden images:\n{}",
+            failures.join("\n")
+        );
+        Ok(())
+    }
+
     test_roms!(
         cpu,
         "test_roms/cpu",
@@ -1148,4 +1331,34 @@ pub(crate) mod tests {
         rev_a,
     );
     test_roms!(m005_exram, "test_roms/mapper/m005_exrom", exram, basics);
+    status_test_roms!(
+        apu_status,
+        "test_roms/apu",
+        // blargg's apu_test
+        len_timing,
+        dmc_basics,
+        dmc_rates,
+        // blargg's apu_reset
+        reset_4015_cleared,
+        reset_4017_timing,
+        reset_4017_written,
+        reset_irq_flag_cleared,
+        reset_len_ctrs_enabled,
+        reset_works_immediately,
+        // blargg's apu_mixer
+        dmc,
+        noise,
+        square,
+        triangle,
+        // blargg's dmc_dma_during_read4 ROMs (`dmc_dma_*`) only print their result to the screen
+        // and never write to $6000, so they're checked by frame hash in `apu` instead
+    );
+    status_test_roms!(
+        cpu_status,
+        "test_roms/cpu",
+        // Verifies DMC DMA timing against sprite DMA and IRQs
+        int_irq_and_dma,
+        sprdma_and_dmc_dma,
+        sprdma_and_dmc_dma_512,
+    );
 }
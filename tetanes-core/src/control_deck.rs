@@ -1,24 +1,27 @@
 //! Control Deck implementation. The primary entry-point for emulating the NES.
 
 use crate::{
-    apu::{self, Apu, Channel},
+    apu::{self, stereo::StereoConfig, Apu, Channel},
     bus::Bus,
-    cart::{self, Cart},
+    cart::{self, Cart, RegionSource},
     common::{Clock, NesRegion, Regional, Reset, ResetKind, Sram},
     cpu::Cpu,
-    debug::Debugger,
+    debug::{profiler::ProfileReport, Breakpoints, Debugger, PpuLayers},
     fs,
     genie::{self, GenieCode},
-    input::{FourPlayer, Joypad, Player},
-    mapper::{Bf909Revision, Mapper, MapperRevision, Mmc3Revision},
-    mem::RamState,
+    input::{FourPlayer, Joypad, Peripherals, Player, ZapperConfig},
+    mapper::{
+        bandai_fcg::{BandaiFCG, BarcodeReader},
+        Bf909Revision, Mapper, MapperRevision, Mmc3Revision,
+    },
+    mem::{self, RamState},
     ppu::Ppu,
-    video::{Video, VideoFilter},
+    video::{Frame, Video, VideoFilter},
 };
 use bitflags::bitflags;
 use serde::{Deserialize, Serialize};
 use std::{
-    io::Read,
+    io::{self, Read, Write},
     path::{Path, PathBuf},
 };
 use thiserror::Error;
@@ -50,14 +53,28 @@ pub enum Error {
     /// corrupt save state.
     #[error("cpu state is corrupted")]
     CpuCorrupted,
+    /// Invalid barcode for the [`BarcodeReader`](crate::mapper::bandai_fcg::BarcodeReader).
+    #[error("invalid barcode `{0}`, expected 8 or 13 digits")]
+    InvalidBarcode(String),
+    /// When trying to scan a barcode when the loaded ROM doesn't have a barcode reader.
+    #[error("no barcode reader connected")]
+    NoBarcodeReader,
     /// Invalid Game Genie code error.
     #[error(transparent)]
     InvalidGenieCode(#[from] genie::Error),
+    /// HD pack error.
+    #[cfg(feature = "hd-pack")]
+    #[error(transparent)]
+    HdPack(#[from] crate::ppu::hd_pack::Error),
     /// Invalid file path.
     #[error("invalid file path {0:?}")]
     InvalidFilePath(PathBuf),
     #[error("unimplemented mapper `{0}`")]
     UnimplementedMapper(u16),
+    /// Emulation stopped on one or more [`Breakpoints`] set with
+    /// [`ControlDeck::set_breakpoints`]. Clocking again resumes where it stopped.
+    #[error("breakpoint hit: {0:?}")]
+    Breakpoint(Breakpoints),
     /// Filesystem error.
     #[error(transparent)]
     Fs(#[from] fs::Error),
@@ -128,12 +145,21 @@ pub struct Config {
     pub four_player: FourPlayer,
     /// Enable zapper gun.
     pub zapper: bool,
+    /// Zapper light sensing settings.
+    pub zapper_config: ZapperConfig,
+    /// Enable Arkanoid "Vaus" paddle controller.
+    pub vaus: bool,
+    /// Whether to automatically connect the recommended peripherals, like the zapper gun or a
+    /// four player adapter, when loading a ROM that requires them.
+    pub auto_peripherals: bool,
     /// Game Genie codes.
     pub genie_codes: Vec<GenieCode>,
     /// Whether to support concurrent D-Pad input which wasn't possible on the original NES.
     pub concurrent_dpad: bool,
     /// Apu channels enabled.
     pub channels_enabled: [bool; Apu::MAX_CHANNEL_COUNT],
+    /// How Apu channels are mixed into stereo output.
+    pub stereo: StereoConfig,
     /// Headless mode.
     pub headless_mode: HeadlessMode,
     /// Data directory for storing battery-backed RAM.
@@ -145,6 +171,16 @@ pub struct Config {
     ///
     /// See: <https://www.nesdev.org/wiki/PPU_power_up_state>
     pub emulate_ppu_warmup: bool,
+    /// Whether to skip the initial power-on frames, where many games wait for the PPU to warm up
+    /// and clear RAM, by emulating them without video or audio output. Affects accuracy, as any
+    /// input or timing during the skipped frames is lost.
+    pub fast_boot: bool,
+    /// Whether to limit sprites to 8 per scanline like the original hardware. Disabling reduces
+    /// flicker in games with many sprites on screen, while still setting the sprite overflow flag.
+    pub sprite_limit: bool,
+    /// Seed for [`RamState::Random`] initialization. When set, RAM contents are the same every
+    /// time a ROM is loaded, making emulation fully deterministic for a given set of inputs.
+    pub seed: Option<u64>,
 }
 
 impl Config {
@@ -179,13 +215,20 @@ impl Default for Config {
             ram_state: RamState::Random,
             four_player: FourPlayer::default(),
             zapper: false,
+            zapper_config: ZapperConfig::default(),
+            vaus: false,
+            auto_peripherals: true,
             genie_codes: vec![],
             concurrent_dpad: false,
             channels_enabled: [true; Apu::MAX_CHANNEL_COUNT],
+            stereo: StereoConfig::default(),
             headless_mode: HeadlessMode::empty(),
             data_dir: Self::default_data_dir(),
             mapper_revisions: MapperRevisionsConfig::default(),
             emulate_ppu_warmup: false,
+            fast_boot: false,
+            sprite_limit: true,
+            seed: None,
         }
     }
 }
@@ -197,8 +240,31 @@ pub struct LoadedRom {
     pub name: String,
     /// Whether the loaded Cart is battery-backed.
     pub battery_backed: bool,
-    /// Auto-detected of the loaded Cart.
+    /// Auto-detected region of the loaded Cart.
     pub region: NesRegion,
+    /// Where the region of the loaded Cart was detected from.
+    pub region_source: RegionSource,
+    /// Recommended peripherals for the loaded Cart that were automatically connected, if any.
+    pub peripherals: Option<Peripherals>,
+    /// Recommended peripherals for the loaded Cart, whether or not they were connected.
+    pub recommended_peripherals: Option<Peripherals>,
+    /// Whether the loaded Cart has a barcode reader, e.g. the Datach Joint ROM System.
+    pub barcode_reader: bool,
+}
+
+/// Output of a single frame clocked by [`ControlDeck::run_frame`].
+#[derive(Debug, Copy, Clone)]
+#[must_use]
+pub struct FrameOutput<'a> {
+    /// Number of CPU cycles clocked.
+    pub cycles: usize,
+    /// Frame number of the clocked frame.
+    pub frame_number: u32,
+    /// Filtered RGBA pixels for the frame.
+    pub frame_buffer: &'a [u8],
+    /// Audio samples generated while clocking the frame, interleaved if
+    /// [`ControlDeck::audio_channels`] is more than one.
+    pub audio_samples: &'a [f32],
 }
 
 /// Represents an NES Control Deck. Encapsulates the entire emulation state.
@@ -219,12 +285,23 @@ pub struct ControlDeck {
     mapper_revisions: MapperRevisionsConfig,
     /// Whether to auto-detect the region based on the loaded Cart.
     auto_detect_region: bool,
+    /// Whether to connect recommended peripherals based on the loaded Cart.
+    auto_peripherals: bool,
+    /// User-configured peripherals, restored when unloading a Cart that connected its recommended
+    /// peripherals.
+    peripherals: Peripherals,
     /// Remaining CPU cycles to execute used to clock a given number of seconds.
     cycles_remaining: f32,
+    /// Whether to skip the initial power-on frames after loading a ROM or a hard reset.
+    fast_boot: bool,
+    /// Seed for random RAM initialization, if emulation should be deterministic.
+    seed: Option<u64>,
     /// Emulated frame speed ranging from 0.25 to 2.0.
     frame_speed: f32,
     /// Accumulated frame speed to account for slower 1x speeds.
     frame_accumulator: f32,
+    /// Reusable snapshot buffer for restoring state after run-ahead frames.
+    run_ahead_snapshot: Vec<u8>,
     /// NES CPU.
     cpu: Cpu,
 }
@@ -236,6 +313,9 @@ impl Default for ControlDeck {
 }
 
 impl ControlDeck {
+    /// Maximum number of frames skipped on power-on when `fast_boot` is enabled.
+    pub const FAST_BOOT_MAX_FRAMES: u32 = 120;
+
     /// Create a NES `ControlDeck` with the default configuration.
     pub fn new() -> Self {
         Self::with_config(Config::default())
@@ -243,9 +323,13 @@ impl ControlDeck {
 
     /// Create a NES `ControlDeck` with a configuration.
     pub fn with_config(cfg: Config) -> Self {
+        if let Some(seed) = cfg.seed {
+            mem::seed_random_ram(seed);
+        }
         let mut cpu = Cpu::new(Bus::new(cfg.region, cfg.ram_state));
         cpu.bus.ppu.skip_rendering = cfg.headless_mode.contains(HeadlessMode::NO_VIDEO);
         cpu.bus.ppu.emulate_warmup = cfg.emulate_ppu_warmup;
+        cpu.bus.ppu.unlimited_sprites = !cfg.sprite_limit;
         cpu.bus.apu.skip_mixing = cfg.headless_mode.contains(HeadlessMode::NO_AUDIO);
         if cfg.region.is_auto() {
             cpu.set_region(NesRegion::Ntsc);
@@ -255,12 +339,15 @@ impl ControlDeck {
         cpu.bus.input.set_concurrent_dpad(cfg.concurrent_dpad);
         cpu.bus.input.set_four_player(cfg.four_player);
         cpu.bus.input.connect_zapper(cfg.zapper);
+        cpu.bus.input.set_zapper_config(cfg.zapper_config);
+        cpu.bus.input.connect_vaus(cfg.vaus);
         for (i, enabled) in cfg.channels_enabled.iter().enumerate() {
             match Channel::try_from(i) {
                 Ok(channel) => cpu.bus.apu.set_channel_enabled(channel, *enabled),
                 Err(apu::ParseChannelError) => tracing::error!("invalid APU channel: {i}"),
             }
         }
+        cpu.bus.apu.set_stereo(cfg.stereo);
         for genie_code in cfg.genie_codes.iter().cloned() {
             cpu.bus.add_genie_code(genie_code);
         }
@@ -273,9 +360,17 @@ impl ControlDeck {
             sram_dir: cfg.sram_dir(),
             mapper_revisions: cfg.mapper_revisions,
             auto_detect_region: cfg.region.is_auto(),
+            auto_peripherals: cfg.auto_peripherals,
+            peripherals: Peripherals {
+                zapper: cfg.zapper,
+                four_player: cfg.four_player,
+            },
             cycles_remaining: 0.0,
+            fast_boot: cfg.fast_boot,
+            seed: cfg.seed,
             frame_speed: 1.0,
             frame_accumulator: 0.0,
+            run_ahead_snapshot: Vec::new(),
             cpu,
         }
     }
@@ -287,6 +382,13 @@ impl ControlDeck {
         self.sram_dir.join(name)
     }
 
+    /// Set the directory used to store battery-backed Cart RAM. Takes effect the next time a ROM
+    /// is loaded or unloaded.
+    #[inline]
+    pub fn set_sram_dir(&mut self, dir: impl Into<PathBuf>) {
+        self.sram_dir = dir.into();
+    }
+
     /// Loads a ROM cartridge into memory
     ///
     /// # Errors
@@ -295,27 +397,49 @@ impl ControlDeck {
     pub fn load_rom<S: ToString, F: Read>(&mut self, name: S, rom: &mut F) -> Result<LoadedRom> {
         let name = name.to_string();
         self.unload_rom()?;
+        if let Some(seed) = self.seed {
+            // Work RAM isn't re-initialized on reset, so refill it to not depend on what ran
+            // previously
+            mem::seed_random_ram(seed);
+            let ram_state = self.cpu.bus.ram_state;
+            self.cpu.bus.wram.fill_ram(ram_state);
+        }
         let cart = Cart::from_rom(&name, rom, self.cpu.bus.ram_state)?;
         if cart.mapper.is_none() {
             return Err(Error::UnimplementedMapper(cart.mapper_num()));
         }
         let loaded_rom = LoadedRom {
             name: name.clone(),
-            battery_backed: cart.battery_backed(),
+            battery_backed: cart.battery_backed() || cart.mapper.has_nonvolatile_memory(),
             region: cart.region(),
+            region_source: cart.region_source(),
+            peripherals: cart.peripherals().filter(|_| self.auto_peripherals),
+            recommended_peripherals: cart.peripherals(),
+            // Mapper 157 is used for Datach Joint ROM System boards
+            barcode_reader: cart.mapper_num() == 157,
         };
         if self.auto_detect_region {
             self.cpu.set_region(loaded_rom.region);
         }
+        // Only connected while this Cart is loaded, the user-configured peripherals are restored
+        // when it's unloaded
+        if let Some(peripherals) = loaded_rom.peripherals {
+            self.cpu.bus.input.set_four_player(peripherals.four_player);
+            self.cpu.bus.input.connect_zapper(peripherals.zapper);
+        }
         self.cpu.bus.load_cart(cart);
         self.update_mapper_revisions();
         self.reset(ResetKind::Hard);
         self.running = true;
+        // Loading SRAM checks whether the loaded ROM is battery-backed
+        self.loaded_rom = Some(loaded_rom.clone());
         let sram_dir = self.sram_dir(&name);
         if let Err(err) = self.load_sram(sram_dir) {
             error!("failed to load SRAM: {err:?}");
         }
-        self.loaded_rom = Some(loaded_rom.clone());
+        if self.fast_boot {
+            self.skip_boot()?;
+        }
         Ok(loaded_rom)
     }
 
@@ -347,10 +471,26 @@ impl ControlDeck {
                 error!("failed to save SRAM: {err:?}");
             }
         }
-        self.loaded_rom = None;
+        self.discard_rom();
+        Ok(())
+    }
+
+    /// Unloads the currently loaded ROM without saving SRAM, e.g. when the emulation state can't
+    /// be trusted.
+    pub fn discard_rom(&mut self) {
+        if self
+            .loaded_rom
+            .take()
+            .is_some_and(|rom| rom.peripherals.is_some())
+        {
+            self.cpu
+                .bus
+                .input
+                .set_four_player(self.peripherals.four_player);
+            self.cpu.bus.input.connect_zapper(self.peripherals.zapper);
+        }
         self.cpu.bus.unload_cart();
         self.running = false;
-        Ok(())
     }
 
     /// Load a previously saved CPU state.
@@ -408,7 +548,10 @@ impl ControlDeck {
             | Mapper::Dxrom88(_)
             | Mapper::Dxrom95(_)
             | Mapper::Dxrom154(_)
-            | Mapper::Dxrom206(_) => (),
+            | Mapper::Dxrom206(_)
+            | Mapper::JyCompany(_)
+            | Mapper::Nanjing(_)
+            | Mapper::Wxn(_) => (),
         }
     }
 
@@ -448,6 +591,57 @@ impl ControlDeck {
         self.cpu.bus.ppu.emulate_warmup = enabled;
     }
 
+    /// Set whether to limit sprites to 8 per scanline. Disabling reduces flicker in games with many
+    /// sprites on screen, while still setting the sprite overflow flag.
+    #[inline]
+    pub fn set_sprite_limit(&mut self, enabled: bool) {
+        self.cpu.bus.ppu.unlimited_sprites = !enabled;
+    }
+
+    /// Set whether to skip the initial power-on frames after loading a ROM or a hard reset. Affects
+    /// accuracy, as any input or timing during the skipped frames is lost.
+    #[inline]
+    pub fn set_fast_boot(&mut self, enabled: bool) {
+        self.fast_boot = enabled;
+    }
+
+    /// Set the seed for [`RamState::Random`] initialization, or `None` to use a random seed. Takes
+    /// effect the next time a ROM is loaded.
+    #[inline]
+    pub fn set_seed(&mut self, seed: Option<u64>) {
+        self.seed = seed;
+    }
+
+    /// Emulates power-on frames without video or audio output until the loaded ROM enables
+    /// rendering or [`ControlDeck::FAST_BOOT_MAX_FRAMES`] have elapsed, skipping past any initial
+    /// PPU warmup and RAM clearing wait loops.
+    ///
+    /// # Errors
+    ///
+    /// If CPU encounters an invalid opcode, then an error is returned.
+    pub fn skip_boot(&mut self) -> Result<()> {
+        let skip_rendering = self.cpu.bus.ppu.skip_rendering;
+        let skip_mixing = self.cpu.bus.apu.skip_mixing;
+        self.cpu.bus.ppu.skip_rendering = true;
+        self.cpu.bus.apu.skip_mixing = true;
+
+        let start_frame = self.frame_number();
+        let mut res = Ok(());
+        while !self.cpu.bus.ppu.mask.rendering_enabled
+            && self.frame_number().wrapping_sub(start_frame) < Self::FAST_BOOT_MAX_FRAMES
+        {
+            if let Err(err) = self.clock_instr() {
+                res = Err(err);
+                break;
+            }
+        }
+
+        self.cpu.bus.ppu.skip_rendering = skip_rendering;
+        self.cpu.bus.apu.skip_mixing = skip_mixing;
+        self.clear_audio_samples();
+        res
+    }
+
     /// Adds a debugger callback to be executed any time the debugger conditions
     /// match.
     pub fn add_debugger(&mut self, debugger: Debugger) {
@@ -456,6 +650,48 @@ impl ControlDeck {
         }
     }
 
+    /// Conditions that stop emulation with [`Error::Breakpoint`].
+    pub const fn breakpoints(&self) -> Breakpoints {
+        self.cpu.breakpoints
+    }
+
+    /// Set conditions that stop emulation with [`Error::Breakpoint`], for debugging.
+    pub fn set_breakpoints(&mut self, breakpoints: Breakpoints) {
+        self.cpu.breakpoints = breakpoints;
+        self.cpu.breakpoints_hit = Breakpoints::empty();
+    }
+
+    /// Whether game code is being profiled.
+    pub const fn is_profiling(&self) -> bool {
+        self.cpu.profiler.enabled
+    }
+
+    /// Start or stop profiling game code. Starting clears any previous profile.
+    pub fn set_profiling(&mut self, enabled: bool) {
+        self.cpu.profiler.set_enabled(enabled, self.cpu.cycle);
+    }
+
+    /// Clear the game code profile gathered so far.
+    pub fn clear_profile(&mut self) {
+        self.cpu.profiler.clear(self.cpu.cycle);
+    }
+
+    /// The game code profile gathered since profiling started.
+    pub fn profile_report(&self) -> ProfileReport {
+        self.cpu.profiler.report()
+    }
+
+    /// Set whether to record recent CPU writes to mapper registers in
+    /// [`Bus::mapper_writes`](crate::ppu::bus::Bus::mapper_writes), for inspecting mapper state.
+    /// Disabling clears any recorded writes.
+    pub fn set_log_mapper_writes(&mut self, enabled: bool) {
+        let bus = &mut self.cpu.bus.ppu.bus;
+        bus.log_mapper_writes = enabled;
+        if !enabled {
+            bus.mapper_writes.clear();
+        }
+    }
+
     /// Removes a debugger callback.
     pub fn remove_debugger(&mut self, debugger: Debugger) {
         match debugger {
@@ -463,6 +699,40 @@ impl ControlDeck {
         }
     }
 
+    /// Set whether to reduce audible pops caused by games writing directly to the DMC output
+    /// level. Affects the accuracy of games that play samples that way.
+    #[inline]
+    pub fn set_dmc_click_reduction(&mut self, enabled: bool) {
+        self.cpu.bus.apu.set_dmc_click_reduction(enabled);
+    }
+
+    /// Set which PPU layers are drawn, for inspecting the background or sprites on their own.
+    #[inline]
+    pub fn set_ppu_layers(&mut self, layers: PpuLayers) {
+        self.cpu.bus.ppu.layers = layers;
+    }
+
+    /// Write a byte of sprite OAM directly, for experimenting with sprite positions, tiles and
+    /// attributes. Games that copy OAM from RAM every frame overwrite it on the next frame.
+    #[inline]
+    pub fn write_oam(&mut self, addr: u8, val: u8) {
+        self.cpu.bus.ppu.oamdata[usize::from(addr)] = val;
+    }
+
+    /// Write a palette entry at `addr` in `$3F00..=$3F1F`, even if locked with
+    /// [`ControlDeck::lock_palette`].
+    #[inline]
+    pub fn write_palette(&mut self, addr: u16, val: u8) {
+        self.cpu.bus.ppu.bus.poke_palette(addr, val);
+    }
+
+    /// Ignore game writes to the palette entry at `addr` in `$3F00..=$3F1F`, for trying out
+    /// colors live.
+    #[inline]
+    pub fn lock_palette(&mut self, addr: u16, locked: bool) {
+        self.cpu.bus.ppu.bus.lock_palette(addr, locked);
+    }
+
     /// Returns the name of the currently loaded ROM [`Cart`]. Returns `None` if no ROM is loaded.
     #[inline]
     #[must_use]
@@ -531,12 +801,10 @@ impl ControlDeck {
             if path.is_dir() {
                 return Err(Error::InvalidFilePath(path.to_path_buf()));
             }
-            if path.is_file() {
+            let path = path.with_extension(Config::SRAM_EXTENSION);
+            if fs::exists(&path) {
                 info!("loading SRAM...");
-                self.cpu
-                    .bus
-                    .load(path.with_extension(Config::SRAM_EXTENSION))
-                    .map_err(Error::Sram)?;
+                self.cpu.bus.load(path).map_err(Error::Sram)?;
             }
         }
         Ok(())
@@ -552,7 +820,8 @@ impl ControlDeck {
             return Err(Error::RomNotLoaded);
         };
         let path = path.as_ref();
-        fs::save(path, &self.cpu).map_err(Error::SaveState)
+        let thumbnail = Frame::thumbnail(self.frame_buffer());
+        fs::save_with_thumbnail(path, &self.cpu, &thumbnail).map_err(Error::SaveState)
     }
 
     /// Load the console with data saved from a save state, if it exists.
@@ -577,6 +846,122 @@ impl ControlDeck {
         }
     }
 
+    /// Serialize the current [`Cpu`] state into `buffer`, replacing its contents. The existing
+    /// allocation is reused, so passing the same buffer every frame avoids any allocations once
+    /// it has grown large enough. Unlike [`ControlDeck::save_state`], snapshots are uncompressed,
+    /// have no header and exclude the frame buffer, making them suitable for rewind or
+    /// run-ahead.
+    ///
+    /// # Errors
+    ///
+    /// If the [`Cpu`] state fails to serialize, then an error is returned.
+    pub fn snapshot_into(&self, buffer: &mut Vec<u8>) -> Result<()> {
+        #[cfg(feature = "profiling")]
+        puffin::profile_function!();
+
+        buffer.clear();
+        bincode::serialize_into(&mut *buffer, &self.cpu)
+            .map_err(|err| fs::Error::SerializationFailed(err.to_string()))?;
+        Ok(())
+    }
+
+    /// Returns a hash of the current emulation state, suitable for detecting desyncs between two
+    /// emulators given the same inputs, such as with netplay or when verifying a replay. Only
+    /// state that affects emulation is included, so frontend settings like audio sample rate or
+    /// emulation speed don't change the result. Hashes are consistent across platforms, but not
+    /// across versions.
+    ///
+    /// # Errors
+    ///
+    /// If the [`Cpu`] state fails to serialize, then an error is returned.
+    pub fn state_hash(&self) -> Result<u64> {
+        #[cfg(feature = "profiling")]
+        puffin::profile_function!();
+
+        let cpu = &self.cpu;
+        let bus = &cpu.bus;
+        let apu = &bus.apu;
+        // HashMap iteration order isn't stable
+        let mut genie_codes = bus.genie_codes.iter().collect::<Vec<_>>();
+        genie_codes.sort_unstable_by_key(|(addr, _)| **addr);
+        let state = (
+            (cpu.cycle, cpu.master_clock, cpu.pc, &cpu.status),
+            (cpu.acc, cpu.x, cpu.y, cpu.sp, cpu.nmi, cpu.run_irq),
+            (&bus.wram, &bus.prg_ram, bus.prg_ram_protect, bus.open_bus),
+            (&bus.input, &bus.ppu, genie_codes),
+            (apu.cycle, &apu.frame_counter, &apu.pulse1, &apu.pulse2),
+            (&apu.triangle, &apu.noise, &apu.dmc),
+        );
+
+        let mut hasher = StateHasher::new();
+        bincode::serialize_into(&mut hasher, &state)
+            .map_err(|err| fs::Error::SerializationFailed(err.to_string()))?;
+        Ok(hasher.0)
+    }
+
+    /// Returns a hash of the palette indexes of the current frame, for checking what's on screen
+    /// without comparing images. Unlike [`ControlDeck::state_hash`], hashes are consistent across
+    /// versions.
+    #[must_use]
+    pub fn frame_hash(&self) -> u64 {
+        let mut hasher = StateHasher::new();
+        for pixel in self.cpu.bus.ppu.frame_buffer() {
+            // Writing to the hasher can't fail
+            let _ = hasher.write(&pixel.to_le_bytes());
+        }
+        hasher.0
+    }
+
+    /// Restore the [`Cpu`] state from a snapshot created by [`ControlDeck::snapshot_into`]. The
+    /// current frame buffer is kept as-is.
+    ///
+    /// # Errors
+    ///
+    /// If the snapshot fails to deserialize, then an error is returned.
+    pub fn restore_snapshot(&mut self, snapshot: &[u8]) -> Result<()> {
+        #[cfg(feature = "profiling")]
+        puffin::profile_function!();
+
+        let mut cpu = bincode::deserialize::<Cpu>(snapshot)
+            .map_err(|err| fs::Error::DeserializationFailed(err.to_string()))?;
+        std::mem::swap(
+            &mut cpu.bus.ppu.frame.buffer,
+            &mut self.cpu.bus.ppu.frame.buffer,
+        );
+        self.load_cpu(cpu);
+        Ok(())
+    }
+
+    /// Load an HD pack from a directory containing a Mesen-compatible `hires.txt` definition,
+    /// replacing any previously loaded HD pack.
+    ///
+    /// # Errors
+    ///
+    /// If the HD pack fails to load, then an error is returned.
+    #[cfg(feature = "hd-pack")]
+    pub fn load_hd_pack(&mut self, path: impl AsRef<Path>) -> Result<()> {
+        use crate::ppu::hd_pack::{HdPack, HdRenderer};
+
+        let pack = HdPack::load(path)?;
+        self.cpu.bus.ppu.hd = Some(HdRenderer::new(std::sync::Arc::new(pack)));
+        Ok(())
+    }
+
+    /// Unload the current HD pack, if any.
+    #[cfg(feature = "hd-pack")]
+    pub fn unload_hd_pack(&mut self) {
+        self.cpu.bus.ppu.hd = None;
+    }
+
+    /// Load the last frame rendered at the HD pack scale with its tile replacements applied, if
+    /// an HD pack is loaded. Replacements are only available for frames rendered after the HD
+    /// pack was loaded.
+    #[cfg(feature = "hd-pack")]
+    pub fn hd_frame_buffer(&mut self) -> Option<crate::ppu::hd_pack::HdFrame<'_>> {
+        let ppu = &mut self.cpu.bus.ppu;
+        ppu.hd.as_mut().map(|hd| hd.frame(ppu.frame.buffer()))
+    }
+
     /// Load the raw underlying frame buffer from the PPU for further processing.
     pub fn frame_buffer_raw(&mut self) -> &[u16] {
         self.cpu.bus.ppu.frame_buffer()
@@ -643,9 +1028,18 @@ impl ControlDeck {
             return Err(Error::RomNotLoaded);
         }
         let cycles = self.clock();
+        let breakpoints = std::mem::take(&mut self.cpu.breakpoints_hit);
         if self.cpu_corrupted() {
             self.running = false;
-            return Err(Error::CpuCorrupted);
+            // Report jams as a breakpoint when debugging them
+            return Err(if breakpoints.contains(Breakpoints::JAM) {
+                Error::Breakpoint(breakpoints)
+            } else {
+                Error::CpuCorrupted
+            });
+        }
+        if !breakpoints.is_empty() {
+            return Err(Error::Breakpoint(breakpoints));
         }
         Ok(cycles)
     }
@@ -692,12 +1086,39 @@ impl ControlDeck {
             while frame == self.frame_number() {
                 total_cycles += self.clock_instr()?;
             }
+            if self.cpu.profiler.enabled {
+                self.cpu.profiler.end_frame();
+            }
         }
         self.cpu.bus.apu.clock_flush();
 
         Ok(total_cycles)
     }
 
+    /// Steps the control deck an entire frame, returning the [`FrameOutput`] for that frame.
+    ///
+    /// Any audio samples not yet consumed are cleared first, so `audio_samples` only contains
+    /// samples for the returned frame.
+    ///
+    /// # Errors
+    ///
+    /// If CPU encounters an invalid opcode, then an error is returned.
+    pub fn run_frame(&mut self) -> Result<FrameOutput<'_>> {
+        self.clear_audio_samples();
+        let cycles = self.clock_frame()?;
+        let frame_number = self.frame_number();
+        let frame_buffer = self
+            .video
+            .apply_filter(self.cpu.bus.ppu.frame_buffer(), frame_number);
+        self.last_frame_number = frame_number;
+        Ok(FrameOutput {
+            cycles,
+            frame_number,
+            frame_buffer,
+            audio_samples: self.cpu.bus.audio_samples(),
+        })
+    }
+
     /// Steps the control deck an entire frame, calling `handle_output` with the `cycles`, `frame_buffer` and
     /// `audio_samples` for that frame.
     ///
@@ -761,10 +1182,9 @@ impl ControlDeck {
 
         // Clock current frame and save state so we can rewind
         self.clock_frame()?;
-        let frame = std::mem::take(&mut self.cpu.bus.ppu.frame.buffer);
         // Save state so we can rewind
-        let state = bincode::serialize(&self.cpu)
-            .map_err(|err| fs::Error::SerializationFailed(err.to_string()))?;
+        let mut snapshot = std::mem::take(&mut self.run_ahead_snapshot);
+        self.snapshot_into(&mut snapshot)?;
 
         // Clock additional frames and discard video/audio
         self.cpu.bus.ppu.skip_rendering = true;
@@ -778,10 +1198,9 @@ impl ControlDeck {
         let result = self.clock_frame_output(handle_output)?;
 
         // Restore back to current frame
-        let mut state = bincode::deserialize::<Cpu>(&state)
-            .map_err(|err| fs::Error::DeserializationFailed(err.to_string()))?;
-        state.bus.ppu.frame.buffer = frame;
-        self.load_cpu(state);
+        let restored = self.restore_snapshot(&snapshot);
+        self.run_ahead_snapshot = snapshot;
+        restored?;
 
         Ok(result)
     }
@@ -806,10 +1225,9 @@ impl ControlDeck {
 
         // Clock current frame and save state so we can rewind
         self.clock_frame()?;
-        let frame = std::mem::take(&mut self.cpu.bus.ppu.frame.buffer);
         // Save state so we can rewind
-        let state = bincode::serialize(&self.cpu)
-            .map_err(|err| fs::Error::SerializationFailed(err.to_string()))?;
+        let mut snapshot = std::mem::take(&mut self.run_ahead_snapshot);
+        self.snapshot_into(&mut snapshot)?;
 
         // Clock additional frames and discard video/audio
         for _ in 1..run_ahead {
@@ -821,10 +1239,9 @@ impl ControlDeck {
         let cycles = self.clock_frame_into(frame_buffer, audio_samples)?;
 
         // Restore back to current frame
-        let mut state = bincode::deserialize::<Cpu>(&state)
-            .map_err(|err| fs::Error::DeserializationFailed(err.to_string()))?;
-        state.bus.ppu.frame.buffer = frame;
-        self.load_cpu(state);
+        let restored = self.restore_snapshot(&snapshot);
+        self.run_ahead_snapshot = snapshot;
+        restored?;
 
         Ok(cycles)
     }
@@ -923,6 +1340,7 @@ impl ControlDeck {
     /// Enable/Disable Four Score for 4-player controllers.
     #[inline]
     pub fn set_four_player(&mut self, four_player: FourPlayer) {
+        self.peripherals.four_player = four_player;
         self.cpu.bus.input.set_four_player(four_player);
     }
 
@@ -947,9 +1365,46 @@ impl ControlDeck {
     /// Enable [`Zapper`](crate::input::Zapper) gun.
     #[inline]
     pub fn connect_zapper(&mut self, enabled: bool) {
+        self.peripherals.zapper = enabled;
         self.cpu.bus.input.connect_zapper(enabled);
     }
 
+    /// Set whether to connect recommended peripherals when loading a ROM that requires them.
+    #[inline]
+    pub fn set_auto_peripherals(&mut self, enabled: bool) {
+        self.auto_peripherals = enabled;
+    }
+
+    /// Returns whether the [`Vaus`](crate::input::Vaus) paddle controller is connected.
+    #[inline]
+    pub const fn vaus_connected(&self) -> bool {
+        self.cpu.bus.input.vaus.connected
+    }
+
+    /// Enable [`Vaus`](crate::input::Vaus) paddle controller.
+    #[inline]
+    pub fn connect_vaus(&mut self, enabled: bool) {
+        self.cpu.bus.input.connect_vaus(enabled);
+    }
+
+    /// Turn the [`Vaus`](crate::input::Vaus) paddle knob by `delta`.
+    #[inline]
+    pub fn move_vaus(&mut self, delta: i32) {
+        self.cpu.bus.input.vaus.move_by(delta);
+    }
+
+    /// Press or release the [`Vaus`](crate::input::Vaus) fire button.
+    #[inline]
+    pub fn set_vaus_button(&mut self, pressed: bool) {
+        self.cpu.bus.input.vaus.set_button(pressed);
+    }
+
+    /// Set the [`Zapper`](crate::input::Zapper) gun light sensing settings.
+    #[inline]
+    pub fn set_zapper_config(&mut self, config: ZapperConfig) {
+        self.cpu.bus.input.set_zapper_config(config);
+    }
+
     /// Returns the current [`Zapper`](crate::input::Zapper) aim position.
     #[inline]
     #[must_use]
@@ -970,6 +1425,26 @@ impl ControlDeck {
         self.cpu.bus.input.zapper.aim(x, y);
     }
 
+    /// Scan a barcode with the [`BarcodeReader`](crate::mapper::bandai_fcg::BarcodeReader) of the
+    /// loaded ROM. Spaces and dashes are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the loaded ROM has no barcode reader or if the barcode isn't 8 or 13 digits.
+    pub fn scan_barcode(&mut self, barcode: &str) -> Result<()> {
+        let Mapper::BandaiFCG(BandaiFCG {
+            barcode_reader: Some(barcode_reader),
+            ..
+        }) = &mut self.cpu.bus.ppu.bus.mapper
+        else {
+            return Err(Error::NoBarcodeReader);
+        };
+        let (value, digit_count) = BarcodeReader::parse(barcode)
+            .ok_or_else(|| Error::InvalidBarcode(barcode.trim().to_string()))?;
+        barcode_reader.input(value, digit_count);
+        Ok(())
+    }
+
     /// Set the video filter for frame buffer output when calling [`ControlDeck::frame_buffer`].
     #[inline]
     pub fn set_filter(&mut self, filter: VideoFilter) {
@@ -1025,6 +1500,20 @@ impl ControlDeck {
         self.cpu.bus.apu.set_channel_enabled(channel, enabled);
     }
 
+    /// Set how [`Apu`] channels are mixed into stereo output. Audio samples are interleaved when
+    /// [`StereoConfig::channels`] is more than one.
+    #[inline]
+    pub fn set_stereo(&mut self, config: StereoConfig) {
+        self.cpu.bus.apu.set_stereo(config);
+    }
+
+    /// Number of interleaved channels in the audio samples output.
+    #[inline]
+    #[must_use]
+    pub fn audio_channels(&self) -> usize {
+        self.cpu.bus.apu.audio_channels()
+    }
+
     /// Toggle a given [`Apu`] [`Channel`].
     #[inline]
     pub fn toggle_apu_channel(&mut self, channel: Channel) {
@@ -1069,6 +1558,352 @@ impl Reset for ControlDeck {
         self.cpu.reset(kind);
         if self.loaded_rom.is_some() {
             self.running = true;
+            if self.fast_boot && kind == ResetKind::Hard {
+                if let Err(err) = self.skip_boot() {
+                    error!("failed to fast boot: {err:?}");
+                }
+            }
         }
     }
 }
+
+/// 64-bit FNV-1a hasher used by [`ControlDeck::state_hash`]. Unlike
+/// [`std::collections::hash_map::DefaultHasher`], results are stable across platforms and Rust
+/// versions.
+#[derive(Debug)]
+struct StateHasher(u64);
+
+impl StateHasher {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    const fn new() -> Self {
+        Self(Self::OFFSET_BASIS)
+    }
+}
+
+impl Write for StateHasher {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        for byte in buf {
+            self.0 = (self.0 ^ u64::from(*byte)).wrapping_mul(Self::PRIME);
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mem::Mem;
+
+    fn seeded_deck(seed: u64) -> ControlDeck {
+        let rom = Path::new(env!("CARGO_MANIFEST_DIR")).join("test_roms/cpu/nestest.nes");
+        let mut deck = ControlDeck::with_config(Config {
+            seed: Some(seed),
+            ..Default::default()
+        });
+        deck.load_rom_path(rom).expect("failed to load rom");
+        deck
+    }
+
+    #[test]
+    fn seeded_state_hash() {
+        let mut a = seeded_deck(1);
+        let mut b = seeded_deck(1);
+        assert_eq!(a.wram(), b.wram(), "same seed wram");
+        for _ in 0..10 {
+            a.clock_frame().expect("valid frame");
+            b.clock_frame().expect("valid frame");
+        }
+        assert_eq!(a.state_hash().unwrap(), b.state_hash().unwrap());
+
+        // Frontend settings shouldn't affect the hash
+        b.set_frame_speed(2.0);
+        b.set_sample_rate(48_000.0);
+        assert_eq!(a.state_hash().unwrap(), b.state_hash().unwrap());
+
+        a.clock_frame().expect("valid frame");
+        assert_ne!(a.state_hash().unwrap(), b.state_hash().unwrap());
+
+        assert_ne!(
+            seeded_deck(2).wram(),
+            seeded_deck(1).wram(),
+            "different seed wram"
+        );
+    }
+
+    #[test]
+    fn recommended_peripherals_restored() {
+        let mut deck = seeded_deck(1);
+        // Connected automatically for this Cart only
+        let peripherals = Peripherals {
+            zapper: true,
+            four_player: FourPlayer::FourScore,
+        };
+        deck.cpu.bus.input.connect_zapper(peripherals.zapper);
+        deck.cpu.bus.input.set_four_player(peripherals.four_player);
+        if let Some(rom) = &mut deck.loaded_rom {
+            rom.peripherals = Some(peripherals);
+        }
+
+        deck.unload_rom().expect("unloaded rom");
+        assert!(!deck.zapper_connected());
+        assert_eq!(deck.four_player(), FourPlayer::Disabled);
+    }
+
+    #[test]
+    fn scan_barcode() {
+        let mut deck = seeded_deck(1);
+        assert!(matches!(
+            deck.scan_barcode("4901234567894"),
+            Err(Error::NoBarcodeReader)
+        ));
+
+        assert_eq!(
+            BarcodeReader::parse("4901-2345 67894"),
+            Some((4_901_234_567_894, 13))
+        );
+        assert_eq!(BarcodeReader::parse("00123456"), Some((123_456, 8)));
+        assert_eq!(BarcodeReader::parse("1234567"), None, "too short");
+        assert_eq!(BarcodeReader::parse("1234567a"), None, "non-digit");
+
+        let mut reader = BarcodeReader::new();
+        reader.input(123_456, 8);
+        assert_eq!(reader.barcode(), "00123456");
+        reader.clock();
+        assert_eq!(reader.read(), 0x08, "leading quiet zone");
+        for _ in 0..33_000 {
+            reader.clock();
+        }
+        assert_eq!(reader.read(), 0x00, "start guard");
+        for _ in 0..100_000 {
+            reader.clock();
+        }
+        assert_eq!(reader.read(), 0x00, "end of stream");
+    }
+
+    #[test]
+    fn sram_persists() {
+        let dir = std::env::temp_dir().join(format!("tetanes_sram_{}", std::process::id()));
+        // NROM with battery-backed PRG-RAM
+        let mut rom = vec![0x00; 16 + 0x4000 + 0x2000];
+        rom[..7].copy_from_slice(b"NES\x1A\x01\x01\x02");
+
+        let mut deck = ControlDeck::new();
+        deck.set_sram_dir(&dir);
+        deck.load_rom("sram.nes", &mut rom.as_slice())
+            .expect("failed to load rom");
+        deck.cpu.write(0x6000, 0x42);
+        deck.unload_rom().expect("unloaded rom");
+
+        deck.load_rom("sram.nes", &mut rom.as_slice())
+            .expect("failed to load rom");
+        assert_eq!(deck.cpu.peek(0x6000), 0x42);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn save_state_thumbnail() {
+        let path =
+            std::env::temp_dir().join(format!("tetanes_thumbnail_{}.sav", std::process::id()));
+        let mut deck = seeded_deck(1);
+        for _ in 0..10 {
+            deck.clock_frame().expect("valid frame");
+        }
+        deck.save_state(&path).expect("save state");
+
+        let thumbnail = fs::load_thumbnail(&path)
+            .expect("load thumbnail")
+            .expect("saved thumbnail");
+        assert_eq!(thumbnail.len(), Frame::THUMBNAIL_SIZE);
+        assert_eq!(thumbnail, Frame::thumbnail(deck.frame_buffer()));
+
+        let hash = deck.state_hash().unwrap();
+        deck.clock_frame().expect("valid frame");
+        deck.load_state(&path).expect("load state");
+        assert_eq!(deck.state_hash().unwrap(), hash);
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn run_frame() {
+        let mut deck = seeded_deck(1);
+        let output = deck.run_frame().expect("valid frame");
+        let frame_number = output.frame_number;
+        assert!(output.cycles > 0);
+        assert_eq!(output.frame_buffer.len(), Frame::SIZE);
+        assert!(!output.audio_samples.is_empty());
+        let frame = output.frame_buffer.to_vec();
+        assert_eq!(deck.frame_buffer(), frame);
+
+        // Leave samples from previous frames unconsumed
+        deck.clock_frame().expect("valid frame");
+        let unconsumed = deck.audio_samples().len();
+        let output = deck.run_frame().expect("valid frame");
+        assert_eq!(output.frame_number, frame_number + 2);
+        assert!(
+            output.audio_samples.len() < unconsumed,
+            "audio samples are cleared between frames"
+        );
+    }
+
+    #[test]
+    fn mapper_debug_state() {
+        use crate::mapper::Mapped;
+
+        let rom =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("test_roms/mapper/m004_txrom/clocking.nes");
+        let mut deck = ControlDeck::new();
+        deck.load_rom_path(rom).expect("failed to load rom");
+        deck.clock_frame().expect("valid frame");
+        assert!(
+            deck.ppu().bus.mapper_writes.is_empty(),
+            "mapper writes aren't recorded unless enabled"
+        );
+
+        deck.set_log_mapper_writes(true);
+        for _ in 0..25 {
+            deck.clock_frame().expect("valid frame");
+        }
+
+        let bus = &deck.ppu().bus;
+        let state = bus.mapper.debug_state();
+        assert_eq!(state.prg_rom_banks.len(), 4, "8K PRG-ROM banks");
+        assert_eq!(state.chr_banks.len(), 8, "1K CHR banks");
+        assert_eq!(state.prg_rom_banks[3].start, 0xE000);
+        assert!(state.irq.is_some(), "MMC3 IRQ counter");
+
+        assert!(!bus.mapper_writes.is_empty(), "mapper register writes");
+        assert!(bus.mapper_writes.len() <= crate::ppu::bus::Bus::MAPPER_WRITES_LEN);
+        assert!(bus.mapper_writes.iter().all(|write| write.addr >= 0x4020));
+    }
+
+    #[test]
+    fn mapper_irq_breakpoint() {
+        let rom = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("test_roms/mapper/m004_txrom/scanline_timing.nes");
+        let mut deck = ControlDeck::new();
+        deck.load_rom_path(rom).expect("failed to load rom");
+        deck.set_breakpoints(Breakpoints::MAPPER_IRQ);
+        assert_eq!(deck.breakpoints(), Breakpoints::MAPPER_IRQ);
+
+        let mut hit = false;
+        for _ in 0..120 {
+            match deck.clock_frame() {
+                Ok(_) => (),
+                Err(Error::Breakpoint(breakpoints)) => {
+                    assert_eq!(breakpoints, Breakpoints::MAPPER_IRQ);
+                    hit = true;
+                    break;
+                }
+                Err(err) => panic!("unexpected error: {err:?}"),
+            }
+        }
+        assert!(hit, "mapper IRQ breakpoint");
+        let irqs = &deck.ppu().bus.mapper_irqs;
+        assert!(!irqs.is_empty(), "mapper IRQ events");
+
+        deck.set_breakpoints(Breakpoints::empty());
+        deck.clock_frame().expect("valid frame");
+    }
+
+    #[test]
+    fn interrupt_breakpoints() {
+        for (rom, breakpoint, vector) in [
+            ("ppu/_240pee.nes", Breakpoints::NMI, 0xFFFA),
+            (
+                "mapper/m004_txrom/scanline_timing.nes",
+                Breakpoints::IRQ,
+                0xFFFE,
+            ),
+        ] {
+            let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("test_roms")
+                .join(rom);
+            let mut deck = ControlDeck::new();
+            deck.load_rom_path(path).expect("failed to load rom");
+            deck.set_breakpoints(breakpoint);
+
+            let mut hit = false;
+            for _ in 0..120 {
+                match deck.clock_frame() {
+                    Ok(_) => (),
+                    Err(Error::Breakpoint(breakpoints)) => {
+                        assert_eq!(breakpoints, breakpoint, "{rom}");
+                        assert_eq!(deck.cpu().pc, deck.cpu().peek_u16(vector), "{rom}");
+                        hit = true;
+                        break;
+                    }
+                    Err(err) => panic!("unexpected error: {err:?}"),
+                }
+            }
+            assert!(hit, "{rom} {breakpoint:?}");
+        }
+    }
+
+    #[test]
+    fn jam_breakpoint() {
+        // NROM with a reset vector pointing at a jam opcode
+        let mut rom = vec![0x00; 16 + 0x4000 + 0x2000];
+        rom[..6].copy_from_slice(b"NES\x1A\x01\x01");
+        rom[16] = 0x02;
+        rom[16 + 0x3FFD] = 0x80;
+
+        let mut deck = ControlDeck::new();
+        deck.load_rom("jam", &mut rom.as_slice())
+            .expect("failed to load rom");
+        assert!(matches!(deck.clock_frame(), Err(Error::CpuCorrupted)));
+
+        deck.load_rom("jam", &mut rom.as_slice())
+            .expect("failed to load rom");
+        deck.set_breakpoints(Breakpoints::JAM);
+        assert!(matches!(
+            deck.clock_frame(),
+            Err(Error::Breakpoint(Breakpoints::JAM))
+        ));
+        assert!(deck.cpu_corrupted());
+    }
+
+    #[test]
+    fn profile_game_code() {
+        let rom = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("test_roms/mapper/m004_txrom/scanline_timing.nes");
+        let mut deck = ControlDeck::new();
+        deck.load_rom_path(rom).expect("failed to load rom");
+        deck.set_profiling(true);
+        assert!(deck.is_profiling());
+        for _ in 0..10 {
+            deck.clock_frame().expect("valid frame");
+        }
+
+        let report = deck.profile_report();
+        assert_eq!(report.frames, 10);
+        assert!(!report.routines.is_empty(), "routines profiled");
+        let self_cycles = report
+            .routines
+            .iter()
+            .map(|(_, stats)| stats.self_cycles)
+            .sum::<u64>();
+        let stack_cycles = report.stacks.iter().map(|(_, cycles)| cycles).sum::<u64>();
+        assert_eq!(self_cycles, stack_cycles);
+
+        deck.set_profiling(false);
+        deck.clear_profile();
+        assert!(deck.profile_report().routines.is_empty());
+    }
+
+    #[test]
+    fn write_oam() {
+        let mut deck = ControlDeck::new();
+        deck.write_oam(0x00, 0x40);
+        deck.write_oam(0xFF, 0x80);
+        assert_eq!(deck.ppu().oamdata[0x00], 0x40);
+        assert_eq!(deck.ppu().oamdata[0xFF], 0x80);
+    }
+}
@@ -5,6 +5,10 @@
 use crate::{
     bus::Bus,
     common::{Clock, ClockTo, NesRegion, Regional, Reset, ResetKind},
+    debug::{
+        profiler::{Profiler, Routine},
+        Breakpoints,
+    },
     mem::Mem,
 };
 use bitflags::bitflags;
@@ -118,6 +122,18 @@ pub struct Cpu {
     pub nmi: bool,
     pub prev_nmi: bool,
     pub prev_nmi_pending: bool,
+    /// Whether the mapper IRQ line was asserted last cycle, to detect new assertions.
+    #[serde(skip)]
+    pub prev_mapper_irq: bool,
+    /// Conditions to stop emulation on, for debugging.
+    #[serde(skip)]
+    pub breakpoints: Breakpoints,
+    /// Breakpoint conditions met since last checked.
+    #[serde(skip)]
+    pub breakpoints_hit: Breakpoints,
+    /// Game code profiler, for debugging.
+    #[serde(skip)]
+    pub profiler: Profiler,
     #[serde(skip)]
     pub corrupted: bool, // Encountering an invalid opcode corrupts CPU processing
     pub region: NesRegion,
@@ -169,6 +185,10 @@ impl Cpu {
             nmi: false,
             prev_nmi: false,
             prev_nmi_pending: false,
+            prev_mapper_irq: false,
+            breakpoints: Breakpoints::empty(),
+            breakpoints_hit: Breakpoints::empty(),
+            profiler: Profiler::default(),
             corrupted: false,
             cycle_accurate: true,
             disasm: String::with_capacity(100),
@@ -189,6 +209,21 @@ impl Cpu {
         };
         // Doesn't make sense to load a debugger from a previous state
         cpu.bus.ppu.debugger = std::mem::take(&mut self.bus.ppu.debugger);
+        cpu.breakpoints = self.breakpoints;
+        cpu.profiler = std::mem::take(&mut self.profiler);
+        cpu.profiler.clear_calls(cpu.cycle);
+        cpu.bus.ppu.bus.palette_locks = self.bus.ppu.bus.palette_locks;
+        cpu.bus.ppu.bus.log_mapper_writes = self.bus.ppu.bus.log_mapper_writes;
+        cpu.bus.ppu.unlimited_sprites = self.bus.ppu.unlimited_sprites;
+        cpu.bus.ppu.layers = self.bus.ppu.layers;
+        cpu.bus.apu.dmc_click_filter = self.bus.apu.dmc_click_filter;
+        cpu.bus.apu.stereo = self.bus.apu.stereo.take();
+        cpu.bus.input.zapper.config = self.bus.input.zapper.config;
+        cpu.bus.input.vaus = self.bus.input.vaus;
+        #[cfg(feature = "hd-pack")]
+        {
+            cpu.bus.ppu.hd = self.bus.ppu.hd.take();
+        }
         *self = cpu;
     }
 
@@ -356,6 +391,49 @@ impl Cpu {
         }
     }
 
+    /// Track calls and returns of the instruction just run for the [`Profiler`]. `sp` is the
+    /// stack pointer before the instruction ran.
+    fn profile_instr(&mut self, sp: u8) {
+        match self.instr.op() {
+            JSR => {
+                let routine = Routine::Subroutine(self.bus.code_addr(self.pc));
+                self.profiler.call(routine, sp, self.cycle);
+            }
+            BRK => self.profile_interrupt(sp),
+            RTS | RTI => self.profiler.ret(self.sp, self.cycle),
+            _ => (),
+        }
+    }
+
+    /// Track entering an interrupt handler for the [`Profiler`]. `sp` is the stack pointer before
+    /// the interrupt.
+    fn profile_interrupt(&mut self, sp: u8) {
+        let addr = self.bus.code_addr(self.pc);
+        let routine = if self.entered_nmi() {
+            Routine::Nmi(addr)
+        } else {
+            Routine::Irq(addr)
+        };
+        self.profiler.call(routine, sp, self.cycle);
+    }
+
+    /// Whether the interrupt just entered went to the NMI handler. NMI can hijack IRQ and `BRK`,
+    /// so this checks which vector was taken.
+    fn entered_nmi(&self) -> bool {
+        self.pc == self.peek_u16(Self::NMI_VECTOR)
+    }
+
+    /// Record any breakpoints hit by the instruction just run.
+    fn check_instr_breakpoints(&mut self) {
+        let hit = match self.instr.op() {
+            BRK => Breakpoints::BRK,
+            XXX => Breakpoints::JAM,
+            _ if self.instr.is_unofficial() => Breakpoints::UNOFFICIAL_OPCODE,
+            _ => Breakpoints::empty(),
+        };
+        self.breakpoints_hit |= hit & self.breakpoints;
+    }
+
     /// Handle CPU interrupt requests, if any are pending.
     fn handle_interrupts(&mut self) {
         // https://www.nesdev.org/wiki/CPU_interrupts
@@ -381,6 +459,15 @@ impl Cpu {
         if !self.prev_run_irq && self.run_irq {
             trace!("IRQs: {:?} - CYC:{}", irqs, self.cycle);
         }
+
+        let mapper_irq = irqs.contains(Irq::MAPPER);
+        if mapper_irq && !self.prev_mapper_irq {
+            self.bus.log_mapper_irq();
+            if self.breakpoints.contains(Breakpoints::MAPPER_IRQ) {
+                self.breakpoints_hit |= Breakpoints::MAPPER_IRQ;
+            }
+        }
+        self.prev_mapper_irq = mapper_irq;
     }
 
     /// Start a CPU cycle.
@@ -824,6 +911,7 @@ impl Clock for Cpu {
     /// Runs the CPU one instruction.
     fn clock(&mut self) -> usize {
         let start_cycle = self.cycle;
+        let start_sp = self.sp;
 
         self.trace_instr();
 
@@ -926,8 +1014,27 @@ impl Clock for Cpu {
             XXX => self.xxx(), // Unimplemented opcode
         }
 
+        if self.profiler.enabled {
+            self.profile_instr(start_sp);
+        }
+        if !self.breakpoints.is_empty() {
+            self.check_instr_breakpoints();
+        }
+
         if self.prev_run_irq || self.prev_nmi {
+            let sp = self.sp;
             self.irq();
+            if self.profiler.enabled {
+                self.profile_interrupt(sp);
+            }
+            if !self.breakpoints.is_empty() {
+                let hit = if self.entered_nmi() {
+                    Breakpoints::NMI
+                } else {
+                    Breakpoints::IRQ
+                };
+                self.breakpoints_hit |= hit & self.breakpoints;
+            }
         }
 
         let cycles_ran = self.cycle - start_cycle;
@@ -1110,4 +1217,26 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn instr_breakpoints() {
+        use super::*;
+        let mut cpu = Cpu::new(Bus::default());
+        cpu.bus.load_cart(Cart::empty());
+        cpu.breakpoints = Breakpoints::BRK | Breakpoints::UNOFFICIAL_OPCODE | Breakpoints::JAM;
+
+        for (opcode, hit) in [
+            (0xEA, Breakpoints::empty()),
+            (0x00, Breakpoints::BRK),
+            (0x1A, Breakpoints::UNOFFICIAL_OPCODE),
+            (0xEB, Breakpoints::UNOFFICIAL_OPCODE),
+            (0x02, Breakpoints::JAM),
+        ] {
+            cpu.reset(ResetKind::Hard);
+            cpu.breakpoints_hit = Breakpoints::empty();
+            cpu.bus.write(0x0000, opcode);
+            cpu.clock();
+            assert_eq!(cpu.breakpoints_hit, hit, "opcode ${opcode:02X}");
+        }
+    }
 }
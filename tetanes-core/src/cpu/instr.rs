@@ -74,6 +74,18 @@ impl Instr {
     pub const fn cycles(&self) -> usize {
         self.3
     }
+
+    /// Whether this is an unofficial opcode, including those that jam the CPU.
+    #[must_use]
+    pub const fn is_unofficial(&self) -> bool {
+        match self.op() {
+            XXX | ISB | DCP | AXS | LAS | LAX | AHX | SAX | XAA | SXA | RRA | TAS | SYA | ARR
+            | SRE | ALR | RLA | ANC | SLO | SKB | IGN => true,
+            NOP => self.opcode() != 0xEA, // 0xEA is the only official NOP
+            SBC => self.opcode() == 0xEB,
+            _ => false,
+        }
+    }
 }
 
 /// CPU Addressing Modes
@@ -1533,18 +1545,11 @@ impl Cpu {
 
 impl std::fmt::Display for Instr {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
-        let mut op = self.op();
-        let unofficial = match self.op() {
-            XXX | ISB | DCP | AXS | LAS | LAX | AHX | SAX | XAA | SXA | RRA | TAS | SYA | ARR
-            | SRE | ALR | RLA | ANC | SLO => "*",
-            NOP if self.opcode() != 0xEA => "*", // 0xEA is the only official NOP
-            SKB | IGN => {
-                op = NOP;
-                "*"
-            }
-            SBC if self.opcode() == 0xEB => "*",
-            _ => "",
+        let op = match self.op() {
+            SKB | IGN => NOP,
+            op => op,
         };
+        let unofficial = if self.is_unofficial() { "*" } else { "" };
         write!(f, "{unofficial:1}{op:?}")
     }
 }
@@ -1,5 +1,12 @@
-use crate::ppu::Ppu;
-use std::sync::Arc;
+use crate::{cpu::Cpu, fs, ppu::Ppu};
+use bitflags::bitflags;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{fmt, sync::Arc};
+
+pub mod harness;
+pub mod profiler;
+pub mod symbols;
 
 #[derive(Debug, Clone, PartialEq)]
 #[must_use]
@@ -27,6 +34,65 @@ impl PartialEq for PpuDebugger {
     }
 }
 
+bitflags! {
+    /// Conditions that stop emulation with
+    /// [`Error::Breakpoint`](crate::control_deck::Error::Breakpoint), for debugging.
+    #[derive(Default, Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
+    #[must_use]
+    pub struct Breakpoints: u8 {
+        /// A mapper asserts its IRQ line, e.g. the MMC3 scanline counter reaching zero.
+        const MAPPER_IRQ = 1 << 0;
+        /// The CPU enters the NMI handler.
+        const NMI = 1 << 1;
+        /// The CPU enters the IRQ handler for a hardware interrupt.
+        const IRQ = 1 << 2;
+        /// The CPU executes a `BRK` instruction.
+        const BRK = 1 << 3;
+        /// The CPU executes an unofficial opcode, other than one that jams it.
+        const UNOFFICIAL_OPCODE = 1 << 4;
+        /// The CPU jams on an opcode that halts it, which otherwise stops emulation with
+        /// [`Error::CpuCorrupted`](crate::control_deck::Error::CpuCorrupted).
+        const JAM = 1 << 5;
+    }
+}
+
+/// Which PPU layers are drawn to the frame, for inspecting each layer on its own. Hidden layers
+/// are still evaluated, so sprite zero hit and overflow behave normally.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+#[must_use]
+pub enum PpuLayers {
+    #[default]
+    All,
+    Background,
+    Sprites,
+}
+
+impl PpuLayers {
+    /// Whether background tiles are drawn.
+    pub const fn show_bg(&self) -> bool {
+        !matches!(self, Self::Sprites)
+    }
+
+    /// Whether sprites are drawn.
+    pub const fn show_spr(&self) -> bool {
+        !matches!(self, Self::Background)
+    }
+}
+
+/// How a nametable tile changed between two PPU snapshots, for diagnosing mapper bank switching
+/// glitches. See [`Ppu::load_nametable_changes`].
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+#[must_use]
+pub enum TileChange {
+    #[default]
+    None,
+    /// The tile index or attribute was written by the CPU.
+    Cpu,
+    /// The tile's pattern data or nametable mirroring changed without a nametable write, e.g.
+    /// from a mapper CHR bank switch. Writes to CHR-RAM also show up as this.
+    Mapper,
+}
+
 impl std::fmt::Debug for PpuDebugger {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("PpuDebugger")
@@ -35,3 +101,161 @@ impl std::fmt::Debug for PpuDebugger {
             .finish_non_exhaustive()
     }
 }
+
+/// A single field that differs between two save states.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[must_use]
+pub struct StateDiff {
+    /// Dotted path to the field, e.g. `bus.ppu.scanline`.
+    pub path: String,
+    /// Value of the field in the first state.
+    pub a: String,
+    /// Value of the field in the second state.
+    pub b: String,
+}
+
+impl StateDiff {
+    /// Arrays of numbers longer than this, like RAM, are summarized instead of listing every
+    /// differing element.
+    const SUMMARIZE_LEN: usize = 16;
+
+    /// Which component of the console this field belongs to, for grouping diffs.
+    pub fn component(&self) -> &'static str {
+        if self.path.starts_with("bus.ppu.bus.mapper") {
+            "Mapper"
+        } else if self.path.starts_with("bus.ppu") {
+            "PPU"
+        } else if self.path.starts_with("bus.apu") {
+            "APU"
+        } else {
+            "CPU"
+        }
+    }
+
+    fn push(diffs: &mut Vec<Self>, path: &str, a: &Value, b: &Value) {
+        match (a, b) {
+            (Value::Object(a_fields), Value::Object(b_fields)) => {
+                for (name, a_val) in a_fields {
+                    let path = Self::join(path, name);
+                    match b_fields.get(name) {
+                        Some(b_val) => Self::push(diffs, &path, a_val, b_val),
+                        None => diffs.push(Self::new(path, a_val.to_string(), "<missing>")),
+                    }
+                }
+                for (name, b_val) in b_fields {
+                    if !a_fields.contains_key(name) {
+                        let path = Self::join(path, name);
+                        diffs.push(Self::new(path, "<missing>", b_val.to_string()));
+                    }
+                }
+            }
+            (Value::Array(a_vals), Value::Array(b_vals))
+                if a_vals.len() == b_vals.len()
+                    && a_vals.len() > Self::SUMMARIZE_LEN
+                    && a_vals.iter().chain(b_vals).all(Value::is_number) =>
+            {
+                let mut differing = a_vals
+                    .iter()
+                    .zip(b_vals)
+                    .enumerate()
+                    .filter(|(_, (a, b))| a != b);
+                if let Some((index, (a_val, b_val))) = differing.next() {
+                    let count = differing.count() + 1;
+                    diffs.push(Self::new(
+                        format!("{path}[{index}]"),
+                        a_val.to_string(),
+                        format!("{b_val} ({count} of {} elements differ)", a_vals.len()),
+                    ));
+                }
+            }
+            (Value::Array(a_vals), Value::Array(b_vals)) if a_vals.len() == b_vals.len() => {
+                for (index, (a_val, b_val)) in a_vals.iter().zip(b_vals).enumerate() {
+                    Self::push(diffs, &format!("{path}[{index}]"), a_val, b_val);
+                }
+            }
+            (Value::Array(a_vals), Value::Array(b_vals)) => diffs.push(Self::new(
+                format!("{path}.len"),
+                a_vals.len().to_string(),
+                b_vals.len().to_string(),
+            )),
+            _ if a != b => diffs.push(Self::new(path, a.to_string(), b.to_string())),
+            _ => (),
+        }
+    }
+
+    fn new(path: impl Into<String>, a: impl Into<String>, b: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            a: a.into(),
+            b: b.into(),
+        }
+    }
+
+    fn join(path: &str, name: &str) -> String {
+        if path.is_empty() {
+            name.to_string()
+        } else {
+            format!("{path}.{name}")
+        }
+    }
+}
+
+impl fmt::Display for StateDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {} != {}", self.path, self.a, self.b)
+    }
+}
+
+/// Compare two [`Cpu`] states, such as those loaded from save states with [`fs::load`], returning
+/// every field that differs, sorted by path. Useful for tracking down replay desyncs and save
+/// state incompatibilities.
+///
+/// # Errors
+///
+/// If either state fails to serialize, then an error is returned.
+pub fn diff_states(a: &Cpu, b: &Cpu) -> fs::Result<Vec<StateDiff>> {
+    let to_value = |cpu| {
+        serde_json::to_value(cpu).map_err(|err| fs::Error::SerializationFailed(err.to_string()))
+    };
+    let mut diffs = Vec::new();
+    StateDiff::push(&mut diffs, "", &to_value(a)?, &to_value(b)?);
+    Ok(diffs)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::control_deck::{Config, ControlDeck};
+    use std::path::Path;
+
+    #[test]
+    fn diff_states() {
+        let rom = Path::new(env!("CARGO_MANIFEST_DIR")).join("test_roms/cpu/nestest.nes");
+        let mut deck = ControlDeck::with_config(Config {
+            seed: Some(1),
+            ..Default::default()
+        });
+        deck.load_rom_path(rom).expect("failed to load rom");
+        deck.clock_frame().expect("valid frame");
+        let a = deck.cpu().clone();
+        assert!(super::diff_states(&a, &a).expect("valid diff").is_empty());
+
+        deck.clock_frame().expect("valid frame");
+        let diffs = super::diff_states(&a, deck.cpu()).expect("valid diff");
+        let frame_count = diffs
+            .iter()
+            .find(|diff| diff.path == "bus.ppu.frame.count")
+            .expect("frame count diff");
+        assert_eq!(frame_count.component(), "PPU");
+        assert_eq!(
+            frame_count.b.parse::<u32>().ok(),
+            frame_count.a.parse::<u32>().ok().map(|frame| frame + 1)
+        );
+        assert!(
+            diffs
+                .iter()
+                .filter(|diff| diff.path.starts_with("bus.wram["))
+                .all(|diff| diff.b.contains("elements differ")),
+            "large arrays are summarized"
+        );
+    }
+}
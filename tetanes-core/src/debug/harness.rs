@@ -0,0 +1,350 @@
+//! Scripted regression tests for game logic, run headless against a ROM.
+//!
+//! A [`TestScript`] lists test cases that each power on the ROM, press buttons on given frames
+//! and check RAM or the screen on given frames. Frames count from power on, with inputs held
+//! while emulating their frame and assertions checked once their frame is reached. Scripts can be
+//! written in any format serde supports, such as TOML:
+//!
+//! ```toml
+//! rom = "my_hack.nes"
+//!
+//! [[test]]
+//! name = "start selects one player"
+//! input = [{ frame = 60, buttons = ["Start"], hold = 2 }]
+//! assert = [
+//!     { frame = 120, ram = 0x0075, value = 1 },
+//!     { frame = 120, ram = 0x00B0, op = ">=", value = 3, mask = 0x0F },
+//!     { frame = 120, pixel = [128, 120], value = 0x0F },
+//!     { frame = 120, screen = "9a1c2f03e5d7b648" },
+//! ]
+//! ```
+
+use crate::{
+    common::NesRegion,
+    control_deck::{Config, ControlDeck, HeadlessMode, Result},
+    input::{JoypadBtn, Player},
+    mem::{Mem, RamState},
+    ppu::Ppu,
+};
+use serde::{Deserialize, Serialize};
+use std::{fmt, path::PathBuf};
+
+/// A suite of test cases for a ROM.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[must_use]
+pub struct TestScript {
+    /// The ROM to test, relative to the script.
+    pub rom: PathBuf,
+    /// NES region to emulate, detected from the ROM if not set.
+    #[serde(default)]
+    pub region: Option<NesRegion>,
+    /// RAM initialization state, zeroed by default so results are repeatable.
+    #[serde(default)]
+    pub ram_state: RamState,
+    #[serde(default, rename = "test")]
+    pub tests: Vec<TestCase>,
+}
+
+/// Inputs to play from power on and assertions to check along the way.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[must_use]
+pub struct TestCase {
+    pub name: String,
+    #[serde(default, rename = "input")]
+    pub inputs: Vec<InputEvent>,
+    #[serde(default, rename = "assert")]
+    pub asserts: Vec<Assertion>,
+}
+
+/// Buttons pressed on a frame.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[must_use]
+pub struct InputEvent {
+    pub frame: u32,
+    #[serde(default)]
+    pub player: Player,
+    pub buttons: Vec<JoypadBtn>,
+    /// Number of frames to hold the buttons for.
+    #[serde(default = "InputEvent::default_hold")]
+    pub hold: u32,
+}
+
+impl InputEvent {
+    const fn default_hold() -> u32 {
+        1
+    }
+
+    fn set_buttons(&self, deck: &mut ControlDeck, pressed: bool) {
+        let joypad = deck.joypad_mut(self.player);
+        for button in &self.buttons {
+            joypad.set_button(*button, pressed);
+        }
+    }
+}
+
+/// A condition checked once a frame is reached.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[must_use]
+pub struct Assertion {
+    pub frame: u32,
+    #[serde(flatten)]
+    pub check: Check,
+}
+
+/// What an [`Assertion`] checks.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+#[must_use]
+pub enum Check {
+    /// A CPU memory address, masked with `mask`, compared to `value`.
+    Ram {
+        ram: u16,
+        #[serde(default)]
+        op: Compare,
+        value: u8,
+        #[serde(default = "Check::default_mask")]
+        mask: u8,
+    },
+    /// The palette index of a pixel, including emphasis bits, compared to `value`.
+    Pixel {
+        pixel: (u32, u32),
+        #[serde(default)]
+        op: Compare,
+        value: u16,
+    },
+    /// The hexadecimal [`ControlDeck::frame_hash`] of the screen. Failures print the actual
+    /// hash, so a placeholder can be used to find the hash for a screen known to be correct.
+    Screen { screen: String },
+}
+
+impl Check {
+    const fn default_mask() -> u8 {
+        0xFF
+    }
+
+    /// Check the condition, returning a description of the failure, if any.
+    fn check(&self, deck: &ControlDeck) -> Option<String> {
+        match self {
+            Self::Ram {
+                ram,
+                op,
+                value,
+                mask,
+            } => {
+                let actual = deck.cpu().peek(*ram) & mask;
+                let masked = if *mask == 0xFF {
+                    String::new()
+                } else {
+                    format!(" & ${mask:02X}")
+                };
+                (!op.compare(actual, *value)).then(|| {
+                    format!("${ram:04X}{masked} is ${actual:02X}, expected {op} ${value:02X}")
+                })
+            }
+            Self::Pixel { pixel, op, value } => {
+                let (x, y) = *pixel;
+                if x >= Ppu::WIDTH || y >= Ppu::HEIGHT {
+                    return Some(format!("pixel ({x}, {y}) is off screen"));
+                }
+                let actual = deck.ppu().frame_buffer()[(y * Ppu::WIDTH + x) as usize];
+                (!op.compare(actual, *value)).then(|| {
+                    format!("pixel ({x}, {y}) is ${actual:02X}, expected {op} ${value:02X}")
+                })
+            }
+            Self::Screen { screen } => {
+                let actual = deck.frame_hash();
+                let matches = u64::from_str_radix(screen, 16).is_ok_and(|hash| hash == actual);
+                (!matches).then(|| format!("screen hash is {actual:016x}, expected {screen}"))
+            }
+        }
+    }
+}
+
+/// Comparison made by a [`Check`], with the actual value on the left.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[must_use]
+pub enum Compare {
+    #[default]
+    #[serde(rename = "==")]
+    Eq,
+    #[serde(rename = "!=")]
+    Ne,
+    #[serde(rename = "<")]
+    Lt,
+    #[serde(rename = "<=")]
+    Le,
+    #[serde(rename = ">")]
+    Gt,
+    #[serde(rename = ">=")]
+    Ge,
+}
+
+impl Compare {
+    pub fn compare<T: PartialOrd>(&self, actual: T, expected: T) -> bool {
+        match self {
+            Self::Eq => actual == expected,
+            Self::Ne => actual != expected,
+            Self::Lt => actual < expected,
+            Self::Le => actual <= expected,
+            Self::Gt => actual > expected,
+            Self::Ge => actual >= expected,
+        }
+    }
+}
+
+impl fmt::Display for Compare {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::Eq => "==",
+            Self::Ne => "!=",
+            Self::Lt => "<",
+            Self::Le => "<=",
+            Self::Gt => ">",
+            Self::Ge => ">=",
+        };
+        write!(f, "{s}")
+    }
+}
+
+/// Outcome of a [`TestCase`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[must_use]
+pub struct TestResult {
+    pub name: String,
+    /// Descriptions of each failed assertion, prefixed by their frame.
+    pub failures: Vec<String>,
+}
+
+impl TestResult {
+    pub fn passed(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+impl TestScript {
+    /// Run each test case against the ROM data `rom` named `name`.
+    ///
+    /// # Errors
+    ///
+    /// If the ROM fails to load or emulation fails, then an error is returned.
+    pub fn run(&self, name: &str, rom: &[u8]) -> Result<Vec<TestResult>> {
+        let cfg = Config {
+            region: self.region.unwrap_or(NesRegion::Auto),
+            ram_state: self.ram_state,
+            headless_mode: HeadlessMode::NO_AUDIO,
+            // Keep saved games from changing results
+            data_dir: std::env::temp_dir().join("tetanes-harness"),
+            ..Default::default()
+        };
+        self.tests
+            .iter()
+            .map(|test| {
+                let mut deck = ControlDeck::with_config(cfg.clone());
+                deck.load_rom(name, &mut &rom[..])?;
+                test.run(&mut deck)
+            })
+            .collect()
+    }
+}
+
+impl TestCase {
+    /// Run the test from the current state of `deck`, up to the last frame checked.
+    ///
+    /// # Errors
+    ///
+    /// If emulation fails, then an error is returned.
+    pub fn run(&self, deck: &mut ControlDeck) -> Result<TestResult> {
+        let last_frame = self.asserts.iter().map(|assert| assert.frame).max();
+        let mut failures = vec![];
+        for frame in 0..=last_frame.unwrap_or_default() {
+            for assert in self.asserts.iter().filter(|assert| assert.frame == frame) {
+                if let Some(failure) = assert.check.check(deck) {
+                    failures.push(format!("frame {frame}: {failure}"));
+                }
+            }
+            if Some(frame) == last_frame {
+                break;
+            }
+
+            // Release buttons first so they can be pressed again right away
+            for input in &self.inputs {
+                if input.frame.saturating_add(input.hold) == frame {
+                    input.set_buttons(deck, false);
+                }
+            }
+            for input in &self.inputs {
+                if input.frame == frame && input.hold > 0 {
+                    input.set_buttons(deck, true);
+                }
+            }
+            deck.clock_frame()?;
+            deck.clear_audio_samples();
+        }
+        Ok(TestResult {
+            name: self.name.clone(),
+            failures,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// NROM that stores whether A is pressed on controller one to $0010.
+    fn joypad_rom() -> Vec<u8> {
+        #[rustfmt::skip]
+        let program = [
+            0xA9, 0x01, 0x8D, 0x16, 0x40, // LDA #1, STA $4016
+            0xA9, 0x00, 0x8D, 0x16, 0x40, // LDA #0, STA $4016
+            0xAD, 0x16, 0x40, 0x29, 0x01, // LDA $4016, AND #1
+            0x85, 0x10, 0x4C, 0x00, 0x80, // STA $10, JMP $8000
+        ];
+        let mut rom = vec![0x00; 16 + 0x4000 + 0x2000];
+        rom[..6].copy_from_slice(b"NES\x1A\x01\x01");
+        rom[16..16 + program.len()].copy_from_slice(&program);
+        rom[16 + 0x3FFD] = 0x80;
+        rom
+    }
+
+    #[test]
+    fn run_script() {
+        let script: TestScript = serde_json::from_str(
+            r#"{
+                "rom": "joypad.nes",
+                "test": [{
+                    "name": "hold a",
+                    "input": [{ "frame": 2, "buttons": ["A"], "hold": 2 }],
+                    "assert": [
+                        { "frame": 2, "ram": 16, "value": 0 },
+                        { "frame": 3, "ram": 16, "value": 1 },
+                        { "frame": 4, "ram": 16, "op": ">=", "value": 1 },
+                        { "frame": 5, "ram": 16, "value": 0 },
+                        { "frame": 5, "pixel": [0, 0], "op": "<", "value": 64 }
+                    ]
+                }, {
+                    "name": "failures",
+                    "assert": [
+                        { "frame": 1, "ram": 16, "value": 1, "mask": 1 },
+                        { "frame": 1, "pixel": [256, 0], "value": 0 },
+                        { "frame": 1, "screen": "0" }
+                    ]
+                }]
+            }"#,
+        )
+        .expect("valid script");
+
+        let results = script.run("joypad.nes", &joypad_rom()).expect("tests ran");
+        assert_eq!(results.len(), 2);
+        assert!(results[0].passed(), "{:?}", results[0].failures);
+
+        let failures = &results[1].failures;
+        assert_eq!(failures.len(), 3);
+        assert_eq!(failures[0], "frame 1: $0010 & $01 is $00, expected == $01");
+        assert_eq!(failures[1], "frame 1: pixel (256, 0) is off screen");
+        assert!(failures[2].starts_with("frame 1: screen hash is "));
+    }
+}
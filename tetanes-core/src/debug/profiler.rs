@@ -0,0 +1,233 @@
+//! Game code profiler, attributing CPU cycles to the 6502 routines they ran in.
+
+use std::collections::HashMap;
+
+/// Where a routine lives. Code in PRG-ROM is identified by its ROM offset so routines in
+/// different banks mapped to the same address are told apart.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[must_use]
+pub struct CodeAddr {
+    pub addr: u16,
+    pub prg_offset: Option<usize>,
+}
+
+impl CodeAddr {
+    /// The 16K PRG-ROM bank this code is in, if any, as numbered by `.nl` symbol files.
+    pub fn bank(&self) -> Option<usize> {
+        self.prg_offset.map(|offset| offset / 0x4000)
+    }
+}
+
+/// A profiled routine.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[must_use]
+pub enum Routine {
+    /// Code running outside of any tracked call, e.g. the main loop.
+    Main,
+    /// A subroutine called with `JSR`.
+    Subroutine(CodeAddr),
+    /// An NMI handler.
+    Nmi(CodeAddr),
+    /// An IRQ or `BRK` handler.
+    Irq(CodeAddr),
+}
+
+/// Cycles spent in a [`Routine`].
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+#[must_use]
+pub struct RoutineStats {
+    pub calls: u64,
+    /// Cycles spent in the routine itself, excluding routines it called.
+    pub self_cycles: u64,
+    /// Cycles spent in the routine including routines it called.
+    pub total_cycles: u64,
+}
+
+/// Accumulated profile of the routines run since profiling started.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+#[must_use]
+pub struct ProfileReport {
+    pub frames: u32,
+    pub routines: Vec<(Routine, RoutineStats)>,
+    /// Cycles spent at the end of each distinct call stack, outermost first, for flame graphs.
+    pub stacks: Vec<(Vec<Routine>, u64)>,
+}
+
+#[derive(Debug, Copy, Clone)]
+struct Call {
+    routine: Routine,
+    /// Stack pointer before the call, which it's back to once the call returns.
+    sp: u8,
+    start_cycle: usize,
+}
+
+/// Attributes CPU cycles to routines by following `JSR`/`RTS` and interrupts.
+#[derive(Default, Debug, Clone)]
+#[must_use]
+pub struct Profiler {
+    pub enabled: bool,
+    frames: u32,
+    calls: Vec<Call>,
+    last_cycle: usize,
+    routines: HashMap<Routine, RoutineStats>,
+    stacks: HashMap<Vec<Routine>, u64>,
+}
+
+impl Profiler {
+    /// Calls nested deeper than this, e.g. from runaway recursion, are counted as their caller.
+    pub const MAX_DEPTH: usize = 64;
+
+    /// Start or stop profiling, clearing any previous profile when starting.
+    pub fn set_enabled(&mut self, enabled: bool, cycle: usize) {
+        if enabled && !self.enabled {
+            self.clear(cycle);
+        }
+        self.enabled = enabled;
+    }
+
+    /// Clear the profile, starting over from `cycle`.
+    pub fn clear(&mut self, cycle: usize) {
+        self.frames = 0;
+        self.calls.clear();
+        self.last_cycle = cycle;
+        self.routines.clear();
+        self.stacks.clear();
+    }
+
+    /// Forget the current call stack, e.g. after loading a save state jumps elsewhere.
+    pub fn clear_calls(&mut self, cycle: usize) {
+        self.calls.clear();
+        self.last_cycle = cycle;
+    }
+
+    /// Count a completed frame.
+    pub fn end_frame(&mut self) {
+        self.frames += 1;
+    }
+
+    /// Enter `routine` at `cycle`, with `sp` being the stack pointer before the call.
+    pub fn call(&mut self, routine: Routine, sp: u8, cycle: usize) {
+        if self.calls.len() == Self::MAX_DEPTH {
+            return;
+        }
+        self.attribute(cycle);
+        self.routines.entry(routine).or_default().calls += 1;
+        self.calls.push(Call {
+            routine,
+            sp,
+            start_cycle: cycle,
+        });
+    }
+
+    /// Return from any calls the stack pointer `sp` has unwound past, after an `RTS` or `RTI`.
+    pub fn ret(&mut self, sp: u8, cycle: usize) {
+        self.attribute(cycle);
+        while let Some(call) = self.calls.last().filter(|call| call.sp <= sp) {
+            let elapsed = (cycle - call.start_cycle) as u64;
+            self.routines.entry(call.routine).or_default().total_cycles += elapsed;
+            self.calls.pop();
+        }
+    }
+
+    /// Add cycles since the last call or return to the routine currently running.
+    fn attribute(&mut self, cycle: usize) {
+        let elapsed = cycle.saturating_sub(self.last_cycle) as u64;
+        self.last_cycle = cycle;
+        if elapsed == 0 {
+            return;
+        }
+
+        let routine = self.calls.last().map_or(Routine::Main, |call| call.routine);
+        let stats = self.routines.entry(routine).or_default();
+        stats.self_cycles += elapsed;
+        if routine == Routine::Main {
+            stats.total_cycles += elapsed;
+        }
+
+        let mut stack = Vec::with_capacity(self.calls.len() + 1);
+        stack.push(Routine::Main);
+        stack.extend(self.calls.iter().map(|call| call.routine));
+        *self.stacks.entry(stack).or_default() += elapsed;
+    }
+
+    /// The profile so far, with routines sorted by the most cycles spent in them.
+    pub fn report(&self) -> ProfileReport {
+        let mut routines = self
+            .routines
+            .iter()
+            .map(|(routine, stats)| (*routine, *stats))
+            .collect::<Vec<_>>();
+        routines.sort_by(|(a, a_stats), (b, b_stats)| {
+            b_stats.self_cycles.cmp(&a_stats.self_cycles).then(a.cmp(b))
+        });
+        let mut stacks = self
+            .stacks
+            .iter()
+            .map(|(stack, cycles)| (stack.clone(), *cycles))
+            .collect::<Vec<_>>();
+        stacks.sort();
+        ProfileReport {
+            frames: self.frames,
+            routines,
+            stacks,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sub(addr: u16) -> Routine {
+        Routine::Subroutine(CodeAddr {
+            addr,
+            prg_offset: Some(usize::from(addr) - 0x8000),
+        })
+    }
+
+    #[test]
+    fn attribute_cycles() {
+        let mut profiler = Profiler::default();
+        profiler.set_enabled(true, 0);
+
+        // Main runs 10 cycles, then calls $8000 which calls $9000
+        profiler.call(sub(0x8000), 0xFD, 10);
+        profiler.call(sub(0x9000), 0xFB, 20);
+        profiler.ret(0xFB, 50);
+        profiler.ret(0xFD, 55);
+        profiler.end_frame();
+
+        let report = profiler.report();
+        assert_eq!(report.frames, 1);
+        let stats = |routine| {
+            report
+                .routines
+                .iter()
+                .find(|(r, _)| *r == routine)
+                .map(|(_, stats)| *stats)
+                .expect("routine profiled")
+        };
+        assert_eq!(stats(sub(0x9000)).self_cycles, 30);
+        assert_eq!(stats(sub(0x8000)).self_cycles, 15);
+        assert_eq!(stats(sub(0x8000)).total_cycles, 45);
+        assert_eq!(stats(sub(0x8000)).calls, 1);
+        assert_eq!(stats(Routine::Main).self_cycles, 10);
+        assert_eq!(report.routines[0].0, sub(0x9000), "sorted by self cycles");
+        assert!(report
+            .stacks
+            .contains(&(vec![Routine::Main, sub(0x8000), sub(0x9000)], 30)));
+    }
+
+    #[test]
+    fn rts_trick_keeps_caller() {
+        let mut profiler = Profiler::default();
+        profiler.set_enabled(true, 0);
+
+        // A jump table pushes an address and returns to it without leaving the caller
+        profiler.call(sub(0x8000), 0xFD, 0);
+        profiler.ret(0xFB, 10);
+        assert_eq!(profiler.calls.len(), 1);
+        profiler.ret(0xFD, 20);
+        assert!(profiler.calls.is_empty());
+    }
+}
@@ -0,0 +1,178 @@
+//! Symbol files naming routines and variables in game code, for debugging.
+//!
+//! Supports FCEUX `.nl` files and Mesen `.mlb` label files.
+
+use crate::debug::profiler::CodeAddr;
+use std::collections::HashMap;
+
+/// Names for code addresses loaded from symbol files.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+#[must_use]
+pub struct Symbols {
+    /// Names by PRG-ROM offset.
+    prg: HashMap<usize, String>,
+    /// Names by CPU address, for RAM and code outside PRG-ROM.
+    cpu: HashMap<u16, String>,
+}
+
+impl Symbols {
+    pub fn len(&self) -> usize {
+        self.prg.len() + self.cpu.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.prg.is_empty() && self.cpu.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.prg.clear();
+        self.cpu.clear();
+    }
+
+    /// Add the symbols from `other`, replacing names of any addresses already named.
+    pub fn extend(&mut self, other: Self) {
+        self.prg.extend(other.prg);
+        self.cpu.extend(other.cpu);
+    }
+
+    /// Name of the code at `addr`, if known.
+    pub fn name(&self, addr: CodeAddr) -> Option<&str> {
+        addr.prg_offset
+            .and_then(|offset| self.prg.get(&offset))
+            .or_else(|| self.cpu.get(&addr.addr))
+            .map(String::as_str)
+    }
+
+    /// Load symbols from the text of a symbol file, picking the format from `filename`. FCEUX
+    /// names `.nl` files for 16K PRG-ROM banks like `game.nes.1F.nl` and for RAM like
+    /// `game.nes.ram.nl`. Returns the number of symbols loaded.
+    pub fn load(&mut self, filename: &str, text: &str) -> usize {
+        let mut parts = filename.rsplit('.');
+        match parts.next().map(str::to_ascii_lowercase).as_deref() {
+            Some("mlb") => self.load_mlb(text),
+            Some("nl") => {
+                let bank = parts
+                    .next()
+                    .and_then(|bank| usize::from_str_radix(bank, 16).ok());
+                self.load_nl(text, bank)
+            }
+            _ => 0,
+        }
+    }
+
+    /// Load an FCEUX `.nl` file with lines like `$C000#Reset#Comment`. `bank` is the 16K PRG-ROM
+    /// bank the file names, or `None` for RAM. Returns the number of symbols loaded.
+    pub fn load_nl(&mut self, text: &str, bank: Option<usize>) -> usize {
+        let mut count = 0;
+        for line in text.lines() {
+            let mut fields = line.trim().split('#');
+            let (Some(addr), Some(name)) = (fields.next(), fields.next()) else {
+                continue;
+            };
+            let Some(addr) = addr
+                .strip_prefix('$')
+                .and_then(|addr| u16::from_str_radix(addr, 16).ok())
+            else {
+                continue;
+            };
+            if name.is_empty() {
+                continue;
+            }
+            match bank {
+                Some(bank) if addr >= 0x8000 => {
+                    let offset = bank * 0x4000 + usize::from(addr & 0x3FFF);
+                    self.prg.insert(offset, name.to_string());
+                }
+                _ => {
+                    self.cpu.insert(addr, name.to_string());
+                }
+            }
+            count += 1;
+        }
+        count
+    }
+
+    /// Load a Mesen `.mlb` file with lines like `P:1C000:Reset:Comment` for PRG-ROM offsets or
+    /// `R:0300:Buffer` for RAM. Returns the number of symbols loaded.
+    pub fn load_mlb(&mut self, text: &str) -> usize {
+        let mut count = 0;
+        for line in text.lines() {
+            let mut fields = line.trim().splitn(4, ':');
+            let (Some(kind), Some(addr), Some(name)) =
+                (fields.next(), fields.next(), fields.next())
+            else {
+                continue;
+            };
+            // Ranges like `0300-0301` name their first address
+            let addr = addr.split('-').next().unwrap_or(addr);
+            let Ok(addr) = usize::from_str_radix(addr, 16) else {
+                continue;
+            };
+            if name.is_empty() {
+                continue;
+            }
+            match kind {
+                "P" => {
+                    self.prg.insert(addr, name.to_string());
+                }
+                "R" => {
+                    self.cpu.insert((addr & 0x07FF) as u16, name.to_string());
+                }
+                "G" => {
+                    self.cpu.insert(addr as u16, name.to_string());
+                }
+                _ => continue,
+            }
+            count += 1;
+        }
+        count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn load_nl() {
+        let mut symbols = Symbols::default();
+        let count = symbols.load(
+            "game.nes.1.nl",
+            "$8010#DrawHud#Draws the HUD\n$C000#Reset#\n",
+        );
+        assert_eq!(count, 2);
+        let draw_hud = CodeAddr {
+            addr: 0x8010,
+            prg_offset: Some(0x4010),
+        };
+        assert_eq!(symbols.name(draw_hud), Some("DrawHud"));
+
+        assert_eq!(symbols.load("game.nes.ram.nl", "$0300#Buffer#\n"), 1);
+        let buffer = CodeAddr {
+            addr: 0x0300,
+            prg_offset: None,
+        };
+        assert_eq!(symbols.name(buffer), Some("Buffer"));
+    }
+
+    #[test]
+    fn load_mlb() {
+        let mut symbols = Symbols::default();
+        let count = symbols.load(
+            "game.mlb",
+            "P:1C000:Reset:Entry point\nR:0300-0301:Pointer\nbogus\n",
+        );
+        assert_eq!(count, 2);
+        let reset = CodeAddr {
+            addr: 0xC000,
+            prg_offset: Some(0x1C000),
+        };
+        assert_eq!(symbols.name(reset), Some("Reset"));
+        let pointer = CodeAddr {
+            addr: 0x0300,
+            prg_offset: None,
+        };
+        assert_eq!(symbols.name(pointer), Some("Pointer"));
+        assert_eq!(symbols.load("game.txt", "P:0:Nope"), 0);
+    }
+}
@@ -14,6 +14,11 @@ const SAVE_FILE_MAGIC_LEN: usize = 8;
 const SAVE_FILE_MAGIC: [u8; SAVE_FILE_MAGIC_LEN] = *b"TETANES\x1a";
 // Keep this separate from Semver because breaking API changes may not invalidate the save format.
 const SAVE_VERSION: &str = "1";
+// Same as `SAVE_VERSION`, but with a compressed thumbnail stored between the header and the data.
+const SAVE_VERSION_THUMBNAIL: &str = "2";
+// Favor speed over ratio since snapshots are compressed every few frames.
+#[cfg(feature = "zstd")]
+const SNAPSHOT_COMPRESSION_LEVEL: i32 = 1;
 
 pub type Result<T> = std::result::Result<T, Error>;
 
@@ -62,16 +67,20 @@ impl Error {
 ///
 /// If the header fails to write to disk, then an error is returned.
 pub(crate) fn write_header(f: &mut impl Write) -> std::io::Result<()> {
+    write_header_version(f, SAVE_VERSION)
+}
+
+fn write_header_version(f: &mut impl Write, version: &str) -> std::io::Result<()> {
     f.write_all(&SAVE_FILE_MAGIC)?;
-    f.write_all(SAVE_VERSION.as_bytes())
+    f.write_all(version.as_bytes())
 }
 
-/// Verifies a `TetaNES` saved state header.
+/// Verifies a `TetaNES` saved state header, returning whether a thumbnail follows it.
 ///
 /// # Errors
 ///
 /// If the header fails to validate, then an error is returned.
-pub(crate) fn validate_header(f: &mut impl Read) -> Result<()> {
+pub(crate) fn validate_header(f: &mut impl Read) -> Result<bool> {
     let mut magic = [0u8; SAVE_FILE_MAGIC_LEN];
     f.read_exact(&mut magic)
         .map_err(|s| Error::InvalidHeader(s.to_string()))?;
@@ -85,14 +94,27 @@ pub(crate) fn validate_header(f: &mut impl Read) -> Result<()> {
     f.read_exact(&mut version)
         .map_err(|s| Error::InvalidHeader(s.to_string()))?;
     if version == SAVE_VERSION.as_bytes() {
-        Ok(())
+        Ok(false)
+    } else if version == SAVE_VERSION_THUMBNAIL.as_bytes() {
+        Ok(true)
     } else {
         Err(Error::InvalidHeader(format!(
-            "invalid version (expected {SAVE_VERSION:?}, found: {version:?}",
+            "invalid version (expected {SAVE_VERSION:?} or {SAVE_VERSION_THUMBNAIL:?}, found: {version:?})",
         )))
     }
 }
 
+/// Reads the length-prefixed compressed thumbnail following a header.
+fn read_thumbnail(f: &mut impl Read) -> Result<Vec<u8>> {
+    let mut len = [0u8; 4];
+    f.read_exact(&mut len)
+        .map_err(|err| Error::io(err, "failed to read thumbnail length"))?;
+    let mut thumbnail = vec![0; u32::from_le_bytes(len) as usize];
+    f.read_exact(&mut thumbnail)
+        .map_err(|err| Error::io(err, "failed to read thumbnail"))?;
+    decode(thumbnail.as_slice()).map_err(Error::DecodingFailed)
+}
+
 pub fn encode(mut writer: &mut impl Write, data: &[u8]) -> std::io::Result<()> {
     let mut encoder = DeflateEncoder::new(&mut writer, Compression::default());
     encoder.write_all(data)?;
@@ -107,6 +129,22 @@ pub fn decode(data: impl Read) -> std::io::Result<Vec<u8>> {
     Ok(decoded)
 }
 
+/// Compresses a snapshot into `output` using `zstd`, replacing its contents while reusing its
+/// allocation.
+#[cfg(feature = "zstd")]
+pub fn compress_snapshot(snapshot: &[u8], output: &mut Vec<u8>) -> std::io::Result<()> {
+    output.clear();
+    zstd::stream::copy_encode(snapshot, &mut *output, SNAPSHOT_COMPRESSION_LEVEL)
+}
+
+/// Decompresses a snapshot compressed with [`compress_snapshot`] into `output`, replacing its
+/// contents while reusing its allocation.
+#[cfg(feature = "zstd")]
+pub fn decompress_snapshot(data: &[u8], output: &mut Vec<u8>) -> std::io::Result<()> {
+    output.clear();
+    zstd::stream::copy_decode(data, &mut *output)
+}
+
 pub fn save<T>(path: impl AsRef<Path>, value: &T) -> Result<()>
 where
     T: ?Sized + Serialize,
@@ -122,6 +160,42 @@ where
     Ok(())
 }
 
+/// Saves `value` like [`save`], along with a `thumbnail` that can be loaded on its own using
+/// [`load_thumbnail`] without decoding `value`.
+pub fn save_with_thumbnail<T>(path: impl AsRef<Path>, value: &T, thumbnail: &[u8]) -> Result<()>
+where
+    T: ?Sized + Serialize,
+{
+    let data =
+        bincode::serialize(value).map_err(|err| Error::SerializationFailed(err.to_string()))?;
+    let mut encoded_thumbnail = Vec::new();
+    encode(&mut encoded_thumbnail, thumbnail).map_err(Error::EncodingFailed)?;
+    let thumbnail_len = u32::try_from(encoded_thumbnail.len())
+        .map_err(|_| Error::custom("thumbnail is too large"))?;
+
+    let mut writer = fs::writer_impl(path)?;
+    write_header_version(&mut writer, SAVE_VERSION_THUMBNAIL).map_err(Error::WriteHeaderFailed)?;
+    writer
+        .write_all(&thumbnail_len.to_le_bytes())
+        .and_then(|_| writer.write_all(&encoded_thumbnail))
+        .map_err(|err| Error::io(err, "failed to save thumbnail"))?;
+    encode(&mut writer, &data).map_err(Error::EncodingFailed)?;
+    writer
+        .flush()
+        .map_err(|err| Error::io(err, "failed to save data"))?;
+    Ok(())
+}
+
+/// Loads only the thumbnail saved with [`save_with_thumbnail`], if there is one.
+pub fn load_thumbnail(path: impl AsRef<Path>) -> Result<Option<Vec<u8>>> {
+    let mut reader = fs::reader_impl(path)?;
+    if validate_header(&mut reader)? {
+        read_thumbnail(&mut reader).map(Some)
+    } else {
+        Ok(None)
+    }
+}
+
 pub fn save_raw(path: impl AsRef<Path>, value: &[u8]) -> Result<()> {
     let mut writer = fs::writer_impl(path)?;
     writer
@@ -138,7 +212,9 @@ where
     T: DeserializeOwned,
 {
     let mut reader = fs::reader_impl(path)?;
-    validate_header(&mut reader)?;
+    if validate_header(&mut reader)? {
+        read_thumbnail(&mut reader)?;
+    }
     let data = decode(&mut reader).map_err(Error::DecodingFailed)?;
     bincode::deserialize(&data).map_err(|err| Error::DeserializationFailed(err.to_string()))
 }
@@ -148,7 +224,9 @@ where
     T: DeserializeOwned,
 {
     let mut reader = Cursor::new(bytes);
-    validate_header(&mut reader)?;
+    if validate_header(&mut reader)? {
+        read_thumbnail(&mut reader)?;
+    }
     let data = decode(&mut reader).map_err(Error::DecodingFailed)?;
     bincode::deserialize(&data).map_err(|err| Error::SerializationFailed(err.to_string()))
 }
@@ -241,6 +319,20 @@ mod tests {
             validate_header(&mut file.as_slice()).is_ok(),
             "validate header"
         );
+        assert!(
+            !validate_header(&mut file.as_slice()).unwrap(),
+            "no thumbnail"
+        );
+
+        let mut file = Vec::new();
+        assert!(
+            write_header_version(&mut file, SAVE_VERSION_THUMBNAIL).is_ok(),
+            "write thumbnail header"
+        );
+        assert!(
+            validate_header(&mut file.as_slice()).unwrap(),
+            "has thumbnail"
+        );
     }
 
     #[test]
@@ -1,4 +1,4 @@
-//! [`Joypad`] and [`Zapper`] implementation.
+//! [`Joypad`], [`Zapper`] and [`Vaus`] implementation.
 
 use crate::{
     common::{Clock, NesRegion, Reset, ResetKind},
@@ -123,26 +123,107 @@ impl FromStr for FourPlayer {
     }
 }
 
+/// Recommended input peripherals for a game that doesn't work, or isn't fully playable, with
+/// standard controllers.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[must_use]
+pub struct Peripherals {
+    /// Whether the game requires the [`Zapper`] gun.
+    pub zapper: bool,
+    /// Which four player adapter the game supports.
+    pub four_player: FourPlayer,
+}
+
+impl Peripherals {
+    const ZAPPER: Self = Self {
+        zapper: true,
+        four_player: FourPlayer::Disabled,
+    };
+    const FOUR_SCORE: Self = Self {
+        zapper: false,
+        four_player: FourPlayer::FourScore,
+    };
+
+    /// Built-in list of recommended peripherals, sorted by combined PRG-ROM and CHR-ROM CRC32.
+    ///
+    /// CRCs and titles are taken from the No-Intro entries in `game_database.txt`.
+    const DB: [(u32, Self); 37] = [
+        (0x0939_852F, Self::FOUR_SCORE), // M.U.L.E. (USA)
+        (0x1B71_CCDB, Self::FOUR_SCORE), // Gauntlet II (USA)
+        (0x1EBB_5B42, Self::FOUR_SCORE), // Bomberman II (USA)
+        (0x1F66_60E6, Self::ZAPPER),     // Barker Bill's Trick Shooting (Europe)
+        (0x231B_C76E, Self::ZAPPER),     // Chiller (Australia) (Unl)
+        (0x2459_8791, Self::ZAPPER),     // Duck Hunt (World)
+        (0x27CA_0679, Self::FOUR_SCORE), // Danny Sullivan's Indy Heat (Europe)
+        (0x2A65_59A1, Self::ZAPPER),     // Operation Wolf (Japan)
+        (0x2E6E_E98D, Self::FOUR_SCORE), // Harlem Globetrotters (USA)
+        (0x3417_EC46, Self::FOUR_SCORE), // Swords and Serpents (USA)
+        (0x3E58_A87E, Self::ZAPPER),     // Freedom Force (USA)
+        (0x407D_6FFD, Self::FOUR_SCORE), // Super Spike V'Ball + Nintendo World Cup (USA)
+        (0x4318_A2F8, Self::ZAPPER),     // Barker Bill's Trick Shooting (USA)
+        (0x4613_5141, Self::FOUR_SCORE), // Swords and Serpents (France)
+        (0x4E95_9173, Self::ZAPPER),     // Gotcha! - The Sport! (USA)
+        (0x5112_DC21, Self::ZAPPER),     // Wild Gunman (World) (Rev A)
+        (0x5529_431F, Self::ZAPPER),     // Operation Wolf (Europe)
+        (0x5EE6_008E, Self::ZAPPER),     // Mechanized Attack (USA)
+        (0x790B_295B, Self::ZAPPER),     // To the Earth (Europe)
+        (0x79F6_88BC, Self::FOUR_SCORE), // Gauntlet II (Europe)
+        (0x851E_B9BE, Self::ZAPPER),     // Shooting Range (USA)
+        (0xB048_0AE9, Self::ZAPPER),     // Laser Invasion (USA)
+        (0xB79F_2651, Self::ZAPPER),     // Chiller (USA) (Unl)
+        (0xB8B9_ACA3, Self::ZAPPER),     // Wild Gunman (Japan, USA)
+        (0xB9B4_D9E0, Self::FOUR_SCORE), // NES Play Action Football (USA)
+        (0xBBE4_0DC4, Self::ZAPPER),     // Baby Boomer (USA) (Unl)
+        (0xC05A_63B2, Self::FOUR_SCORE), // Super Spike V'Ball (Europe)
+        (0xC1B4_3207, Self::FOUR_SCORE), // Danny Sullivan's Indy Heat (USA)
+        (0xC6C2_EDB5, Self::FOUR_SCORE), // Magic Johnson's Fast Break (USA)
+        (0xCFAE_9DFA, Self::FOUR_SCORE), // Spot - The Video Game (USA)
+        (0xD153_CAF6, Self::FOUR_SCORE), // Swords and Serpents (Europe)
+        (0xDA2C_B59A, Self::FOUR_SCORE), // Nightmare on Elm Street, A (USA)
+        (0xDE8F_D935, Self::ZAPPER),     // To the Earth (USA)
+        (0xE840_FD21, Self::FOUR_SCORE), // Super Spike V'Ball (USA)
+        (0xEDC3_662B, Self::ZAPPER),     // Operation Wolf (USA) (Rev 0A)
+        (0xF54B_34BD, Self::FOUR_SCORE), // Kings of the Beach (USA)
+        (0xFF24_D794, Self::ZAPPER),     // Hogan's Alley (World)
+    ];
+
+    /// Returns the recommended peripherals for a game matching the given combined PRG-ROM and
+    /// CHR-ROM CRC32, if any.
+    pub fn lookup(crc32: u32) -> Option<Self> {
+        Self::DB
+            .binary_search_by(|(crc, _)| crc.cmp(&crc32))
+            .ok()
+            .map(|index| Self::DB[index].1)
+    }
+}
+
 #[derive(Default, Debug, Copy, Clone, Serialize, Deserialize)]
 #[must_use]
 pub struct Input {
     pub joypads: [Joypad; 4],
     pub signatures: [Joypad; 2],
     pub zapper: Zapper,
+    /// Not saved, so existing save states and replays still load. The connected paddle is kept
+    /// when loading a save state instead.
+    #[serde(skip)]
+    pub vaus: Vaus,
     pub turbo_timer: u32,
     pub four_player: FourPlayer,
 }
 
 impl Input {
+    // Signature bits are reversed so they can shift right
+    const SIGNATURES: [Joypad; 2] = [
+        Joypad::from_bytes(0b0000_1000),
+        Joypad::from_bytes(0b0000_0100),
+    ];
+
     pub fn new(region: NesRegion) -> Self {
         Self {
             joypads: [Joypad::new(); 4],
-            // Signature bits are reversed so they can shift right
-            signatures: [
-                Joypad::from_bytes(0b0000_1000),
-                Joypad::from_bytes(0b0000_0100),
-            ],
+            signatures: Self::SIGNATURES,
             zapper: Zapper::new(region),
+            vaus: Vaus::new(),
             turbo_timer: 30,
             four_player: FourPlayer::default(),
         }
@@ -170,9 +251,32 @@ impl Input {
         self.zapper.connected = connected;
     }
 
+    pub fn set_zapper_config(&mut self, config: ZapperConfig) {
+        self.zapper.config = config;
+    }
+
+    pub fn connect_vaus(&mut self, connected: bool) {
+        self.vaus.connected = connected;
+    }
+
+    /// Change the four player adapter while running. Held buttons and the zapper are kept, only
+    /// the serial reads of each controller are restarted so the next read sees the new adapter.
     pub fn set_four_player(&mut self, four_player: FourPlayer) {
         self.four_player = four_player;
-        self.reset(ResetKind::Hard);
+        self.reset_serial();
+    }
+
+    /// Restart the serial reads of all controllers and signatures from the first bit.
+    fn reset_serial(&mut self) {
+        // The strobe is latched from the last $4016 write, which all controllers share
+        let strobe = self.joypads[0].strobe;
+        for pad in &mut self.joypads {
+            pad.index = 0;
+        }
+        self.signatures = Self::SIGNATURES.map(|mut signature| {
+            signature.strobe = strobe;
+            signature
+        });
     }
 
     pub fn clear(&mut self) {
@@ -180,6 +284,7 @@ impl Input {
             pad.clear();
         }
         self.zapper.clear();
+        self.vaus.clear();
     }
 }
 
@@ -189,7 +294,7 @@ impl InputRegisters for Input {
         // Read $4016/$4017 D0 8x for controller #3/#4.
         // Read $4016/$4017 D0 8x for signature: 0b00010000/0b00100000
         let zapper = if player == Player::Two {
-            self.zapper.read(ppu)
+            self.zapper.read(ppu) | self.vaus.read()
         } else {
             0x00
         };
@@ -222,7 +327,7 @@ impl InputRegisters for Input {
         // Read $4016/$4017 D0 8x for controller #3/#4.
         // Read $4016/$4017 D0 8x for signature: 0b00010000/0b00100000
         let zapper = if player == Player::Two {
-            self.zapper.read(ppu)
+            self.zapper.read(ppu) | self.vaus.peek()
         } else {
             0x00
         };
@@ -257,6 +362,7 @@ impl InputRegisters for Input {
         for sig in &mut self.signatures {
             sig.write(val);
         }
+        self.vaus.write(val);
     }
 }
 
@@ -289,9 +395,9 @@ impl Reset for Input {
         for pad in &mut self.joypads {
             pad.reset(kind);
         }
-        self.signatures[0] = Joypad::from_bytes(0b0000_1000);
-        self.signatures[1] = Joypad::from_bytes(0b0000_0100);
+        self.signatures = Self::SIGNATURES;
         self.zapper.reset(kind);
+        self.vaus.reset(kind);
     }
 }
 
@@ -463,6 +569,35 @@ impl Reset for Joypad {
     }
 }
 
+/// Light sensing settings for the [`Zapper`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+#[must_use]
+pub struct ZapperConfig {
+    /// Radius, in pixels, of the area around the aim position seen by the light sensor.
+    pub radius: u32,
+    /// Minimum brightness of a pixel, as the sum of its RGB values (`0..=765`), to be sensed as
+    /// light.
+    pub brightness_threshold: u32,
+    /// Number of scanlines the light sensor stays lit after a bright pixel is drawn.
+    pub light_duration: u32,
+    /// Number of scanlines between a pixel being drawn and the light sensor seeing it, simulating
+    /// the response time of a display. Some games, like Freedom Force, are sensitive to when light
+    /// is detected, so this can be used to compensate for their timing checks.
+    pub latency: u32,
+}
+
+impl Default for ZapperConfig {
+    fn default() -> Self {
+        Self {
+            radius: 3,
+            brightness_threshold: 85,
+            light_duration: 20,
+            latency: 0,
+        }
+    }
+}
+
 #[derive(Default, Debug, Copy, Clone, Serialize, Deserialize)]
 #[must_use]
 pub struct Zapper {
@@ -470,8 +605,13 @@ pub struct Zapper {
     pub trigger_release_delay: f32,
     pub x: u32,
     pub y: u32,
-    pub radius: u32,
+    /// Unused, as the radius is now part of [`ZapperConfig`]. Kept so existing save states and
+    /// replays still load.
+    radius: u32,
     pub connected: bool,
+    /// Light sensing settings, kept when loading a save state.
+    #[serde(skip)]
+    pub config: ZapperConfig,
 }
 
 impl Zapper {
@@ -512,8 +652,9 @@ impl Zapper {
             trigger_release_delay: Cpu::region_clock_rate(region) / 10.0,
             x: 0,
             y: 0,
-            radius: 3,
+            radius: ZapperConfig::default().radius,
             connected: false,
+            config: ZapperConfig::default(),
         }
     }
 
@@ -534,22 +675,44 @@ impl Zapper {
         }
     }
 
+    /// Returns `0x00` if light is sensed, otherwise `0x08`.
+    ///
+    /// Light is sensed when a pixel within [`ZapperConfig::radius`] of the aim position is at
+    /// least as bright as [`ZapperConfig::brightness_threshold`] and was drawn recently enough by
+    /// the PPU for the CRT phosphor to still be glowing.
     fn light_sense(&self, ppu: &Ppu) -> u8 {
-        let width = Ppu::WIDTH;
-        let height = Ppu::HEIGHT;
-        let scanline = ppu.scanline;
-        let cycle = ppu.cycle;
-        let min_y = self.y.saturating_sub(self.radius);
-        let max_y = (self.y + self.radius).min(height - 1);
-        let min_x = self.x.saturating_sub(self.radius);
-        let max_x = (self.x + self.radius).min(width - 1);
+        const DOTS_PER_SCANLINE: i64 = Ppu::CYCLE_END as i64 + 1;
+
+        let ZapperConfig {
+            radius,
+            brightness_threshold,
+            light_duration,
+            latency,
+        } = self.config;
+        // Position of the PPU in dots from the start of the frame
+        let now = i64::from(ppu.scanline) * DOTS_PER_SCANLINE + i64::from(ppu.cycle);
+        let lit_start = i64::from(latency) * DOTS_PER_SCANLINE;
+        let lit_end = lit_start + i64::from(light_duration) * DOTS_PER_SCANLINE;
+
+        let min_y = self.y.saturating_sub(radius);
+        let max_y = (self.y + radius).min(Ppu::HEIGHT - 1);
+        let min_x = self.x.saturating_sub(radius);
+        let max_x = (self.x + radius).min(Ppu::WIDTH - 1);
         for y in min_y..=max_y {
             for x in min_x..=max_x {
-                let behind_ppu =
-                    scanline >= y && (scanline - y) <= 20 && (scanline != y || cycle > x);
+                let (dx, dy) = (x.abs_diff(self.x), y.abs_diff(self.y));
+                if dx * dx + dy * dy > radius * radius {
+                    continue;
+                }
+                // Pixels are output one cycle after the start of the scanline
+                let drawn = i64::from(y) * DOTS_PER_SCANLINE + i64::from(x) + 1;
+                let elapsed = now - drawn;
+                if !(lit_start..lit_end).contains(&elapsed) {
+                    continue;
+                }
                 let brightness = ppu.pixel_brightness(x, y);
-                if behind_ppu && brightness >= 85 {
-                    trace!("zapper light: {brightness}");
+                if brightness >= brightness_threshold {
+                    trace!("zapper light: {brightness} at ({x}, {y})");
                     return 0x00;
                 }
             }
@@ -574,3 +737,232 @@ impl Reset for Zapper {
         self.triggered = 0.0;
     }
 }
+
+/// Arkanoid "Vaus" paddle controller, read serially through `$4017`.
+///
+/// See: <https://www.nesdev.org/wiki/Arkanoid_controller>
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[must_use]
+pub struct Vaus {
+    /// Paddle position, increasing to the right.
+    pub position: u8,
+    pub button: bool,
+    pub connected: bool,
+    shift: u8,
+    strobe: bool,
+}
+
+impl Default for Vaus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Vaus {
+    /// Lowest paddle position the potentiometer reports.
+    pub const MIN_POSITION: u8 = 98;
+    /// Highest paddle position the potentiometer reports.
+    pub const MAX_POSITION: u8 = 242;
+
+    pub const fn new() -> Self {
+        Self {
+            position: Self::MIN_POSITION + (Self::MAX_POSITION - Self::MIN_POSITION) / 2,
+            button: false,
+            connected: false,
+            shift: 0x00,
+            strobe: false,
+        }
+    }
+
+    /// Turn the paddle knob by `delta`, clamped to the potentiometer range.
+    pub fn move_by(&mut self, delta: i32) {
+        self.position = (i32::from(self.position) + delta)
+            .clamp(i32::from(Self::MIN_POSITION), i32::from(Self::MAX_POSITION))
+            as u8;
+    }
+
+    pub fn set_button(&mut self, pressed: bool) {
+        self.button = pressed;
+    }
+
+    pub fn clear(&mut self) {
+        self.button = false;
+    }
+
+    /// Returns the fire button in D3 and the next position bit, inverted and MSB first, in D4.
+    #[must_use]
+    fn read(&mut self) -> u8 {
+        let val = self.peek();
+        if !self.strobe {
+            self.shift <<= 1;
+        }
+        val
+    }
+
+    #[must_use]
+    const fn peek(&self) -> u8 {
+        if !self.connected {
+            return 0x00;
+        }
+        let data = (!self.shift & 0x80) >> 3;
+        let button = if self.button { 0x08 } else { 0x00 };
+        data | button
+    }
+
+    fn write(&mut self, val: u8) {
+        self.strobe = val & 0x01 == 0x01;
+        if self.strobe {
+            self.shift = self.position;
+        }
+    }
+}
+
+impl Reset for Vaus {
+    fn reset(&mut self, _kind: ResetKind) {
+        self.shift = 0x00;
+        self.strobe = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn peripherals_db_sorted() {
+        assert!(
+            Peripherals::DB.windows(2).all(|w| w[0].0 < w[1].0),
+            "peripherals db must be sorted by crc32"
+        );
+    }
+
+    #[test]
+    fn peripherals_db_in_game_database() {
+        let crcs = include_str!("../game_database.txt")
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+            .filter_map(|line| line.split(',').next())
+            .filter_map(|crc| u32::from_str_radix(crc.trim(), 16).ok())
+            .collect::<Vec<_>>();
+        for (crc, _) in Peripherals::DB {
+            assert!(
+                crcs.contains(&crc),
+                "{crc:08X} not found in game_database.txt"
+            );
+        }
+    }
+
+    #[test]
+    fn peripherals_lookup() {
+        // Duck Hunt (World)
+        assert_eq!(
+            Peripherals::lookup(0x2459_8791),
+            Some(Peripherals {
+                zapper: true,
+                four_player: FourPlayer::Disabled
+            })
+        );
+        // Gauntlet II (USA)
+        assert_eq!(
+            Peripherals::lookup(0x1B71_CCDB),
+            Some(Peripherals {
+                zapper: false,
+                four_player: FourPlayer::FourScore
+            })
+        );
+        assert_eq!(Peripherals::lookup(0x0000_0000), None);
+    }
+
+    #[test]
+    fn vaus_serial_read() {
+        let ppu = Ppu::new(NesRegion::Ntsc);
+        let mut input = Input::new(NesRegion::Ntsc);
+        input.connect_vaus(true);
+        input.vaus.position = 0b1010_0110;
+        input.vaus.set_button(true);
+
+        input.write(1);
+        input.write(0);
+        let bits = (0..8)
+            .map(|_| input.read(Player::Two, &ppu))
+            .collect::<Vec<_>>();
+        assert!(bits.iter().all(|val| val & 0x08 == 0x08), "button pressed");
+        let position = bits
+            .iter()
+            .fold(0u8, |position, val| (position << 1) | (!val >> 4 & 0x01));
+        assert_eq!(position, 0b1010_0110);
+
+        input.vaus.move_by(-1000);
+        assert_eq!(input.vaus.position, Vaus::MIN_POSITION);
+        input.vaus.move_by(1000);
+        assert_eq!(input.vaus.position, Vaus::MAX_POSITION);
+    }
+
+    #[test]
+    fn zapper_light_sense() {
+        const BLACK: u16 = 0x0F;
+        const WHITE: u16 = 0x30;
+
+        let mut ppu = Ppu::new(NesRegion::Ntsc);
+        ppu.frame.buffer.fill(BLACK);
+        ppu.frame.set_pixel(100, 100, WHITE);
+        let mut zapper = Zapper::new(NesRegion::Ntsc);
+        zapper.connected = true;
+        zapper.aim(101, 101);
+
+        let mut sense_at = |zapper: &Zapper, scanline, cycle| {
+            ppu.scanline = scanline;
+            ppu.cycle = cycle;
+            zapper.light_sense(&ppu)
+        };
+
+        assert_eq!(sense_at(&zapper, 99, 200), 0x08, "not drawn yet");
+        assert_eq!(sense_at(&zapper, 100, 100), 0x08, "not drawn yet");
+        assert_eq!(sense_at(&zapper, 100, 101), 0x00, "just drawn");
+        assert_eq!(sense_at(&zapper, 119, 340), 0x00, "still lit");
+        assert_eq!(sense_at(&zapper, 120, 101), 0x08, "faded");
+
+        zapper.config.latency = 10;
+        assert_eq!(sense_at(&zapper, 105, 0), 0x08, "before latency");
+        assert_eq!(sense_at(&zapper, 115, 0), 0x00, "after latency");
+
+        zapper.config.latency = 0;
+        zapper.aim(103, 103);
+        assert_eq!(sense_at(&zapper, 101, 0), 0x08, "outside radius");
+        zapper.config.radius = 5;
+        assert_eq!(sense_at(&zapper, 101, 0), 0x00, "inside radius");
+
+        zapper.config.brightness_threshold = 766;
+        assert_eq!(sense_at(&zapper, 101, 0), 0x08, "too dim");
+    }
+
+    #[test]
+    fn four_player_hot_swap() {
+        let ppu = Ppu::new(NesRegion::Ntsc);
+        let mut input = Input::new(NesRegion::Ntsc);
+        input
+            .joypad_mut(Player::One)
+            .set_button(JoypadBtnState::A, true);
+        input
+            .joypad_mut(Player::Three)
+            .set_button(JoypadBtnState::START, true);
+
+        let read_bits = |input: &mut Input, count: usize| {
+            (0..count)
+                .map(|_| input.read(Player::One, &ppu) & 0x01)
+                .collect::<Vec<_>>()
+        };
+
+        input.write(1);
+        input.write(0);
+        assert_eq!(read_bits(&mut input, 2), [1, 0]);
+
+        // Switching mid-read restarts reads with the new adapter without releasing buttons
+        input.set_four_player(FourPlayer::FourScore);
+        assert!(input.joypad(Player::One).button(JoypadBtnState::A));
+        let bits = read_bits(&mut input, 24);
+        assert_eq!(bits[0..8], [1, 0, 0, 0, 0, 0, 0, 0], "player one");
+        assert_eq!(bits[8..16], [0, 0, 0, 1, 0, 0, 0, 0], "player three");
+        assert_eq!(bits[16..24], [0, 0, 0, 1, 0, 0, 0, 0], "signature");
+    }
+}
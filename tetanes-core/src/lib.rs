@@ -32,7 +32,7 @@ pub mod prelude {
         apu::{Apu, Channel},
         cart::Cart,
         common::{Clock, ClockTo, NesRegion, Regional, Reset, ResetKind, Sample},
-        control_deck::{Config, ControlDeck, HeadlessMode},
+        control_deck::{Config, ControlDeck, FrameOutput, HeadlessMode},
         cpu::Cpu,
         genie::GenieCode,
         input::{FourPlayer, Input, Player},
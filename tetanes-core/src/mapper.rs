@@ -4,13 +4,14 @@
 
 use crate::{
     common::{Clock, Regional, Reset, Sram},
-    mem,
+    mem::{self, BankMapping},
     ppu::Mirroring,
 };
 use enum_dispatch::enum_dispatch;
 use serde::{Deserialize, Serialize};
 
 pub use bandai_fcg::BandaiFCG; // m016, m153, m157, m159
+pub use jy_company::JyCompany; // m090, m209, m211
 pub use m000_nrom::Nrom;
 pub use m001_sxrom::{Revision as Mmc1Revision, Sxrom};
 pub use m002_uxrom::Uxrom;
@@ -34,9 +35,12 @@ pub use m079_nina003_006::Nina003006;
 pub use m088_dxrom::Dxrom as Dxrom88;
 pub use m095_dxrom::Dxrom as Dxrom95;
 pub use m154_dxrom::Dxrom as Dxrom154;
+pub use m163_nanjing::Nanjing;
+pub use m176_wxn::Wxn;
 pub use m206_dxrom::Dxrom as Dxrom206;
 
 pub mod bandai_fcg;
+pub mod jy_company;
 pub mod m000_nrom;
 pub mod m001_sxrom;
 pub mod m002_uxrom;
@@ -60,6 +64,8 @@ pub mod m079_nina003_006;
 pub mod m088_dxrom;
 pub mod m095_dxrom;
 pub mod m154_dxrom;
+pub mod m163_nanjing;
+pub mod m176_wxn;
 pub mod m206_dxrom;
 pub mod vrc_irq;
 
@@ -129,6 +135,9 @@ pub enum Mapper {
     Dxrom95,
     Dxrom154,
     Dxrom206,
+    JyCompany,
+    Nanjing,
+    Wxn,
 }
 
 impl Mapper {
@@ -139,6 +148,21 @@ impl Mapper {
     pub const fn is_none(&self) -> bool {
         matches!(self, Self::None(_))
     }
+
+    /// Whether the mapper has non-volatile memory, like a serial EEPROM, that keeps saves without
+    /// a battery.
+    pub const fn has_nonvolatile_memory(&self) -> bool {
+        matches!(
+            self,
+            Self::BandaiFCG(BandaiFCG {
+                standard_eeprom: Some(_),
+                ..
+            }) | Self::BandaiFCG(BandaiFCG {
+                extra_eeprom: Some(_),
+                ..
+            })
+        )
+    }
 }
 
 impl Default for Mapper {
@@ -205,6 +229,55 @@ pub trait MemMap {
     }
 }
 
+/// Mapper IRQ counter state, for debugging.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+#[must_use]
+pub struct MapperIrq {
+    pub counter: u16,
+    /// Value the counter is reloaded with, or the target it's compared against.
+    pub reload: u16,
+    pub enabled: bool,
+}
+
+/// Bank mapping, IRQ and register state of a [`Mapper`], for debugging. See
+/// [`Mapped::debug_state`].
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+#[must_use]
+pub struct MapperDebugState {
+    pub mirroring: Mirroring,
+    /// PRG-ROM banks mapped into CPU addresses.
+    pub prg_rom_banks: Vec<BankMapping>,
+    /// PRG-RAM banks mapped into CPU addresses.
+    pub prg_ram_banks: Vec<BankMapping>,
+    /// CHR-ROM or CHR-RAM banks mapped into PPU addresses.
+    pub chr_banks: Vec<BankMapping>,
+    pub irq: Option<MapperIrq>,
+    /// Other mapper-specific registers, as name and formatted value pairs.
+    pub regs: Vec<(&'static str, String)>,
+}
+
+/// A CPU write to a mapper register, for debugging. See
+/// [`Bus::mapper_writes`](crate::ppu::bus::Bus::mapper_writes).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[must_use]
+pub struct MapperWrite {
+    pub addr: u16,
+    pub val: u8,
+    pub frame: u32,
+    pub scanline: u32,
+    pub cycle: u32,
+}
+
+/// A mapper asserting its IRQ line, for debugging raster effects. See
+/// [`Bus::mapper_irqs`](crate::ppu::bus::Bus::mapper_irqs).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[must_use]
+pub struct MapperIrqEvent {
+    pub frame: u32,
+    pub scanline: u32,
+    pub cycle: u32,
+}
+
 #[enum_dispatch(Mapper)]
 pub trait Mapped {
     fn mirroring(&self) -> Mirroring {
@@ -215,6 +288,13 @@ pub trait Mapped {
     fn ppu_bus_write(&mut self, _addr: u16, _val: u8) {}
     fn cpu_bus_read(&mut self, _addr: u16) {}
     fn cpu_bus_write(&mut self, _addr: u16, _val: u8) {}
+    /// Current bank mapping, IRQ and register state, for debugging.
+    fn debug_state(&self) -> MapperDebugState {
+        MapperDebugState {
+            mirroring: self.mirroring(),
+            ..Default::default()
+        }
+    }
 }
 
 #[derive(Debug, Copy, Clone, Serialize, Deserialize)]
@@ -7,7 +7,10 @@ use crate::{
     common::{Clock, Regional, Reset, Sram},
     cpu::{Cpu, Irq},
     fs,
-    mapper::{self, Mapped, MappedRead, MappedWrite, Mapper, MemMap, Mirroring},
+    mapper::{
+        self, Mapped, MappedRead, MappedWrite, Mapper, MapperDebugState, MapperIrq, MemMap,
+        Mirroring,
+    },
     mem::{Banks, Memory},
 };
 use serde::{Deserialize, Serialize};
@@ -233,6 +236,24 @@ impl Mapped for BandaiFCG {
     fn set_mirroring(&mut self, mirroring: Mirroring) {
         self.mirroring = mirroring;
     }
+
+    fn debug_state(&self) -> MapperDebugState {
+        MapperDebugState {
+            mirroring: self.mirroring,
+            prg_rom_banks: self.prg_rom_banks.mappings(),
+            chr_banks: self.chr_banks.mappings(),
+            irq: Some(MapperIrq {
+                counter: self.regs.irq_counter,
+                reload: self.regs.irq_reload,
+                enabled: self.regs.irq_enabled,
+            }),
+            regs: vec![
+                ("PRG Page", format!("${:02X}", self.regs.prg_page)),
+                ("CHR Registers", format!("{:02X?}", self.regs.chr_regs)),
+            ],
+            ..Default::default()
+        }
+    }
 }
 
 impl MemMap for BandaiFCG {
@@ -410,15 +431,38 @@ impl BarcodeReader {
         }
     }
 
+    /// Queue a barcode to be scanned on the next clock. `digit_count` is either 8 for EAN-8 or 13
+    /// for EAN-13 barcodes.
     pub fn input(&mut self, barcode: u64, digit_count: u32) {
         self.new_barcode = barcode;
         self.new_barcode_digit_count = digit_count;
     }
 
+    /// Parse a typed or pasted barcode into its value and digit count. Spaces and dashes are
+    /// ignored. Returns `None` if the barcode isn't 8 or 13 decimal digits.
+    pub fn parse(barcode: &str) -> Option<(u64, u32)> {
+        let digits = barcode
+            .chars()
+            .filter(|c| !c.is_whitespace() && *c != '-')
+            .collect::<String>();
+        let digit_count = digits.len() as u32;
+        if !matches!(digit_count, 8 | 13) || !digits.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        digits.parse().ok().map(|barcode| (barcode, digit_count))
+    }
+
+    /// The queued barcode, zero-padded to its digit count.
     pub fn barcode(&self) -> String {
-        todo!()
+        format!(
+            "{:0width$}",
+            self.new_barcode,
+            width = self.new_barcode_digit_count as usize
+        )
     }
 
+    /// Serialize the queued barcode into the bit stream returned by [`BarcodeReader::read`]
+    /// starting at the current cycle.
     pub fn init(&mut self) {
         self.insert_cycle = self.master_clock;
 
@@ -561,6 +605,11 @@ impl BarcodeReader {
 impl Clock for BarcodeReader {
     fn clock(&mut self) -> usize {
         self.master_clock += 1;
+        if self.new_barcode_digit_count > 0 {
+            self.init();
+            self.new_barcode = 0;
+            self.new_barcode_digit_count = 0;
+        }
         1
     }
 }
@@ -880,7 +929,48 @@ impl Sram for Eeprom {
     }
 
     fn load(&mut self, path: impl AsRef<Path>) -> fs::Result<()> {
-        let extension = self.sram_extension();
-        fs::load(path.as_ref().with_extension(extension)).map(|data| self.rom_data = data)
+        let path = path.as_ref().with_extension(self.sram_extension());
+        // Nothing to load until the EEPROM has been saved
+        if !fs::exists(&path) {
+            return Ok(());
+        }
+        fs::load(path).map(|data| self.rom_data = data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mem::RamState;
+
+    fn load_lz93d50() -> BandaiFCG {
+        // Mapper 159 without a battery flag, as EEPROMs don't need one
+        let mut rom = vec![0x00; 16 + 0x4000 + 0x2000];
+        rom[..8].copy_from_slice(b"NES\x1A\x01\x01\xF0\x90");
+        let cart =
+            Cart::from_rom("eeprom", &mut rom.as_slice(), RamState::AllZeros).expect("valid cart");
+        match cart.mapper {
+            Mapper::BandaiFCG(bandai_fcg) => bandai_fcg,
+            mapper => panic!("expected Bandai FCG, found {mapper:?}"),
+        }
+    }
+
+    #[test]
+    fn eeprom_persists() {
+        let path = std::env::temp_dir().join(format!("tetanes_eeprom_{}.sram", std::process::id()));
+        let mut bandai_fcg = load_lz93d50();
+        assert!(Mapper::from(bandai_fcg.clone()).has_nonvolatile_memory());
+        bandai_fcg.load(&path).expect("nothing to load yet");
+
+        let eeprom = bandai_fcg.standard_eeprom.as_mut().expect("24C01 EEPROM");
+        eeprom.rom_data[0x10] = 0x42;
+        bandai_fcg.save(&path).expect("saved eeprom");
+
+        let mut loaded = load_lz93d50();
+        loaded.load(&path).expect("loaded eeprom");
+        let eeprom = loaded.standard_eeprom.as_ref().expect("24C01 EEPROM");
+        assert_eq!(eeprom.rom_data[0x10], 0x42);
+
+        let _ = std::fs::remove_file(path.with_extension("eeprom128"));
     }
 }
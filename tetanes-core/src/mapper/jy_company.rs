@@ -0,0 +1,673 @@
+//! `J.Y. Company` ASIC (Mappers 090, 209 and 211)
+//!
+//! <https://www.nesdev.org/wiki/J.Y._Company_ASIC>
+
+use crate::{
+    cart::Cart,
+    common::{Clock, Regional, Reset, Sram},
+    cpu::{Cpu, Irq},
+    mapper::{self, Mapped, MappedRead, MappedWrite, Mapper, MapperDebugState, MapperIrq, MemMap},
+    mem::Banks,
+    ppu::Mirroring,
+};
+use serde::{Deserialize, Serialize};
+
+/// Source of the IRQ prescaler clock.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[must_use]
+pub enum IrqSource {
+    #[default]
+    CpuCycle,
+    PpuA12Rise,
+    PpuRead,
+    CpuWrite,
+}
+
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+#[must_use]
+pub struct Regs {
+    pub prg: [u8; 4],
+    pub chr_lo: [u8; 8],
+    pub chr_hi: [u8; 8],
+    pub nt_lo: [u8; 4],
+    pub nt_hi: [u8; 4],
+    pub mode: u8,
+    pub mirroring: u8,
+    pub nt_control: u8,
+    pub outer_bank: u8,
+    pub chr_latch: [usize; 2],
+    pub irq_enabled: bool,
+    pub irq_source: IrqSource,
+    pub irq_direction: u8,
+    pub irq_small_prescaler: bool,
+    pub irq_prescaler: u8,
+    pub irq_counter: u8,
+    pub irq_xor: u8,
+    pub last_ppu_addr: u16,
+    pub multiplicand: u8,
+    pub multiplier: u8,
+    pub ram: u8,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[must_use]
+pub struct JyCompany {
+    pub regs: Regs,
+    pub mapper_num: u16,
+    pub mirroring: Mirroring,
+    pub has_prg_ram: bool,
+    pub chr_banks: Banks,
+    pub nt_banks: Banks,
+    pub prg_6000_banks: Banks,
+    pub prg_rom_banks: Banks,
+}
+
+impl JyCompany {
+    const PRG_WINDOW: usize = 8 * 1024;
+    const CHR_WINDOW: usize = 1024;
+    const CHR_RAM_SIZE: usize = 8 * 1024;
+
+    const IRQ_UP: u8 = 0x01;
+    const IRQ_DOWN: u8 = 0x02;
+    // The PPU fetches all sprite patterns back to back, so the eight A12 rises real hardware
+    // sees per scanline between garbage nametable fetches show up as one.
+    const A12_RISES_PER_SCANLINE: usize = 8;
+
+    pub fn load(cart: &mut Cart) -> Result<Mapper, mapper::Error> {
+        if !cart.has_chr_rom() && cart.chr_ram.is_empty() {
+            cart.add_chr_ram(Self::CHR_RAM_SIZE);
+        };
+        let chr_len = cart.chr_rom.len().max(cart.chr_ram.len());
+        let mut jy_company = Self {
+            regs: Regs {
+                chr_latch: [0, 4],
+                ..Default::default()
+            },
+            mapper_num: cart.mapper_num(),
+            mirroring: cart.mirroring(),
+            has_prg_ram: cart.has_prg_ram(),
+            chr_banks: Banks::new(0x0000, 0x1FFF, chr_len, Self::CHR_WINDOW)?,
+            nt_banks: Banks::new(0x2000, 0x2FFF, chr_len, Self::CHR_WINDOW)?,
+            prg_6000_banks: Banks::new(0x6000, 0x7FFF, cart.prg_rom.len(), Self::PRG_WINDOW)?,
+            prg_rom_banks: Banks::new(0x8000, 0xFFFF, cart.prg_rom.len(), Self::PRG_WINDOW)?,
+        };
+        jy_company.update_banks();
+        Ok(jy_company.into())
+    }
+
+    const fn prg_mode(&self) -> u8 {
+        self.regs.mode & 0x03
+    }
+
+    const fn chr_mode(&self) -> u8 {
+        (self.regs.mode >> 3) & 0x03
+    }
+
+    const fn prg_rom_at_6000(&self) -> bool {
+        self.regs.mode & 0x80 == 0x80
+    }
+
+    /// Mapper 211 always uses the extended nametable control and mapper 090 never does.
+    const fn extended_nametables(&self) -> bool {
+        match self.mapper_num {
+            90 => false,
+            211 => true,
+            _ => self.regs.mode & 0x20 == 0x20,
+        }
+    }
+
+    /// Whether `nametable` maps CHR-ROM instead of CIRAM.
+    const fn rom_nametable(&self, nametable: usize) -> bool {
+        self.regs.mode & 0x40 == 0x40
+            || (self.regs.nt_lo[nametable] ^ self.regs.nt_control) & 0x80 == 0x80
+    }
+
+    fn prg_page(&self, reg: usize) -> usize {
+        let mut page = self.regs.prg[reg];
+        if self.prg_mode() == 3 {
+            // Bank bits are reversed
+            page = page.reverse_bits() >> 1;
+        }
+        usize::from(page)
+    }
+
+    fn update_prg_banks(&mut self) {
+        let last = if self.regs.mode & 0x04 == 0x04 {
+            self.prg_page(3)
+        } else {
+            0x7F
+        };
+        let (pages, page_6000) = match self.prg_mode() {
+            0 => {
+                let page = last << 2;
+                (
+                    [page, page + 1, page + 2, page + 3],
+                    (self.prg_page(3) << 2) + 3,
+                )
+            }
+            1 => {
+                let lo = self.prg_page(1) << 1;
+                let hi = last << 1;
+                ([lo, lo + 1, hi, hi + 1], (self.prg_page(3) << 1) + 1)
+            }
+            _ => (
+                [self.prg_page(0), self.prg_page(1), self.prg_page(2), last],
+                self.prg_page(3),
+            ),
+        };
+        // Outer bank selects a 512K block
+        let outer = usize::from((self.regs.outer_bank >> 1) & 0x03) << 6;
+        self.prg_6000_banks.set(0, (page_6000 & 0x3F) | outer);
+        for (slot, page) in pages.into_iter().enumerate() {
+            self.prg_rom_banks.set(slot, (page & 0x3F) | outer);
+        }
+    }
+
+    fn chr_page(&self, reg: usize, shift: u8) -> usize {
+        if self.regs.outer_bank & 0x20 == 0x20 {
+            // Outer bank selects a 256K block
+            let outer = (self.regs.outer_bank & 0x01) | ((self.regs.outer_bank >> 2) & 0x06);
+            usize::from(self.regs.chr_lo[reg] & (0xFF >> shift)) | usize::from(outer) << (8 - shift)
+        } else {
+            usize::from(self.regs.chr_lo[reg]) | usize::from(self.regs.chr_hi[reg]) << 8
+        }
+    }
+
+    fn update_chr_banks(&mut self) {
+        match self.chr_mode() {
+            0 => self.chr_banks.set_range(0, 7, self.chr_page(0, 3) << 3),
+            1 => {
+                let [left, right] = self.regs.chr_latch;
+                self.chr_banks.set_range(0, 3, self.chr_page(left, 2) << 2);
+                self.chr_banks.set_range(4, 7, self.chr_page(right, 2) << 2);
+            }
+            2 => {
+                for slot in (0..8).step_by(2) {
+                    let page = self.chr_page(slot, 1) << 1;
+                    self.chr_banks.set_range(slot, slot + 1, page);
+                }
+            }
+            _ => {
+                for slot in 0..8 {
+                    self.chr_banks.set(slot, self.chr_page(slot, 0));
+                }
+            }
+        }
+    }
+
+    fn update_nametables(&mut self) {
+        for nametable in 0..4 {
+            let page = usize::from(self.regs.nt_lo[nametable])
+                | usize::from(self.regs.nt_hi[nametable]) << 8;
+            self.nt_banks.set(nametable, page);
+        }
+        self.mirroring = match self.regs.mirroring & 0x03 {
+            0 => Mirroring::Vertical,
+            1 => Mirroring::Horizontal,
+            2 => Mirroring::SingleScreenA,
+            _ => Mirroring::SingleScreenB,
+        };
+    }
+
+    fn update_banks(&mut self) {
+        self.update_prg_banks();
+        self.update_chr_banks();
+        self.update_nametables();
+    }
+
+    fn clock_irq(&mut self) {
+        let mask = if self.regs.irq_small_prescaler {
+            0x07
+        } else {
+            0xFF
+        };
+        let prescaler = self.regs.irq_prescaler & mask;
+        let (prescaler, wrapped) = match self.regs.irq_direction {
+            Self::IRQ_UP => {
+                let prescaler = prescaler.wrapping_add(1) & mask;
+                (prescaler, prescaler == 0)
+            }
+            Self::IRQ_DOWN => {
+                let prescaler = prescaler.wrapping_sub(1) & mask;
+                (prescaler, prescaler == mask)
+            }
+            _ => return,
+        };
+        self.regs.irq_prescaler = (self.regs.irq_prescaler & !mask) | prescaler;
+        if !wrapped {
+            return;
+        }
+
+        let expired = if self.regs.irq_direction == Self::IRQ_UP {
+            self.regs.irq_counter = self.regs.irq_counter.wrapping_add(1);
+            self.regs.irq_counter == 0x00
+        } else {
+            self.regs.irq_counter = self.regs.irq_counter.wrapping_sub(1);
+            self.regs.irq_counter == 0xFF
+        };
+        if expired && self.regs.irq_enabled {
+            Cpu::set_irq(Irq::MAPPER);
+        }
+    }
+
+    fn ppu_read(&mut self, addr: u16) {
+        match self.regs.irq_source {
+            IrqSource::PpuA12Rise
+                if addr & 0x1000 == 0x1000 && self.regs.last_ppu_addr & 0x1000 == 0 =>
+            {
+                for _ in 0..Self::A12_RISES_PER_SCANLINE {
+                    self.clock_irq();
+                }
+            }
+            IrqSource::PpuRead => self.clock_irq(),
+            _ => (),
+        }
+        self.regs.last_ppu_addr = addr;
+
+        // Mapper 209 latches 4K CHR banks like MMC4
+        if self.mapper_num == 209 && self.chr_mode() == 1 {
+            let latch = match addr & 0x1FF8 {
+                0x0FD8 => Some((0, 0)),
+                0x0FE8 => Some((0, 2)),
+                0x1FD8 => Some((1, 4)),
+                0x1FE8 => Some((1, 6)),
+                _ => None,
+            };
+            if let Some((latch, reg)) = latch {
+                self.regs.chr_latch[latch] = reg;
+                self.update_chr_banks();
+            }
+        }
+    }
+}
+
+impl Mapped for JyCompany {
+    fn mirroring(&self) -> Mirroring {
+        self.mirroring
+    }
+
+    fn set_mirroring(&mut self, mirroring: Mirroring) {
+        self.mirroring = mirroring;
+    }
+
+    fn ppu_bus_read(&mut self, addr: u16) {
+        self.ppu_read(addr);
+    }
+
+    fn cpu_bus_write(&mut self, _addr: u16, _val: u8) {
+        if self.regs.irq_source == IrqSource::CpuWrite {
+            self.clock_irq();
+        }
+    }
+
+    fn debug_state(&self) -> MapperDebugState {
+        // $6000-$7FFF maps either PRG-RAM or PRG-ROM
+        let prg_rom_banks = if self.prg_rom_at_6000() {
+            let mut prg_rom_banks = self.prg_6000_banks.mappings();
+            prg_rom_banks.extend(self.prg_rom_banks.mappings());
+            prg_rom_banks
+        } else {
+            self.prg_rom_banks.mappings()
+        };
+        MapperDebugState {
+            mirroring: self.mirroring,
+            prg_rom_banks,
+            chr_banks: self.chr_banks.mappings(),
+            irq: Some(MapperIrq {
+                counter: self.regs.irq_counter.into(),
+                reload: 0,
+                enabled: self.regs.irq_enabled,
+            }),
+            regs: vec![
+                ("Mode", format!("${:02X}", self.regs.mode)),
+                ("Outer Bank", format!("${:02X}", self.regs.outer_bank)),
+                ("IRQ Source", format!("{:?}", self.regs.irq_source)),
+                ("IRQ Prescaler", format!("${:02X}", self.regs.irq_prescaler)),
+            ],
+            ..Default::default()
+        }
+    }
+}
+
+impl MemMap for JyCompany {
+    // PPU $0000..=$1FFF 1K/2K/4K/8K CHR-ROM/RAM Banks Switchable
+    // PPU $2000..=$3EFF 1K Nametables from CIRAM or CHR-ROM Switchable
+
+    // CPU $5000..=$5FFF DIP switches, multiplier and a byte of RAM
+    // CPU $6000..=$7FFF 8K PRG-RAM or PRG-ROM Bank Switchable
+    // CPU $8000..=$FFFF 8K/16K/32K PRG-ROM Banks Switchable
+
+    fn map_read(&mut self, addr: u16) -> MappedRead {
+        if addr < 0x4000 {
+            self.ppu_read(addr);
+        }
+        self.map_peek(addr)
+    }
+
+    fn map_peek(&self, addr: u16) -> MappedRead {
+        match addr {
+            0x0000..=0x1FFF => MappedRead::Chr(self.chr_banks.translate(addr)),
+            0x2000..=0x3EFF if self.extended_nametables() => {
+                let addr = addr & 0x2FFF;
+                let nametable = usize::from(addr >> 10) & 0x03;
+                if self.rom_nametable(nametable) {
+                    MappedRead::Chr(self.nt_banks.translate(addr))
+                } else {
+                    let page = usize::from(self.regs.nt_lo[nametable] & 0x01);
+                    MappedRead::CIRam(page << 10 | usize::from(addr & 0x03FF))
+                }
+            }
+            0x5000..=0x5FFF => match addr & 0xF803 {
+                // DIP switches
+                0x5000 => MappedRead::Data(0x00),
+                0x5800 => {
+                    let product =
+                        u16::from(self.regs.multiplicand) * u16::from(self.regs.multiplier);
+                    MappedRead::Data(product as u8)
+                }
+                0x5801 => {
+                    let product =
+                        u16::from(self.regs.multiplicand) * u16::from(self.regs.multiplier);
+                    MappedRead::Data((product >> 8) as u8)
+                }
+                0x5803 => MappedRead::Data(self.regs.ram),
+                _ => MappedRead::Bus,
+            },
+            0x6000..=0x7FFF if self.prg_rom_at_6000() => {
+                MappedRead::PrgRom(self.prg_6000_banks.translate(addr))
+            }
+            0x6000..=0x7FFF if self.has_prg_ram => MappedRead::PrgRam((addr & 0x1FFF).into()),
+            0x8000..=0xFFFF => MappedRead::PrgRom(self.prg_rom_banks.translate(addr)),
+            _ => MappedRead::Bus,
+        }
+    }
+
+    fn map_write(&mut self, addr: u16, val: u8) -> MappedWrite {
+        match addr {
+            0x0000..=0x1FFF => return MappedWrite::ChrRam(self.chr_banks.translate(addr), val),
+            0x2000..=0x3EFF if self.extended_nametables() => {
+                let addr = addr & 0x2FFF;
+                let nametable = usize::from(addr >> 10) & 0x03;
+                if self.rom_nametable(nametable) {
+                    return MappedWrite::None;
+                }
+                let page = usize::from(self.regs.nt_lo[nametable] & 0x01);
+                return MappedWrite::CIRam(page << 10 | usize::from(addr & 0x03FF), val);
+            }
+            0x5000..=0x5FFF => match addr & 0xF803 {
+                0x5800 => self.regs.multiplicand = val,
+                0x5801 => self.regs.multiplier = val,
+                0x5803 => self.regs.ram = val,
+                _ => (),
+            },
+            0x6000..=0x7FFF if !self.prg_rom_at_6000() && self.has_prg_ram => {
+                return MappedWrite::PrgRam((addr & 0x1FFF).into(), val);
+            }
+            0x8000..=0xFFFF => {
+                let reg = usize::from(addr & 0x07);
+                match addr & 0xF000 {
+                    0x8000 => {
+                        self.regs.prg[reg & 0x03] = val & 0x7F;
+                        self.update_prg_banks();
+                    }
+                    0x9000 => {
+                        self.regs.chr_lo[reg] = val;
+                        self.update_chr_banks();
+                    }
+                    0xA000 => {
+                        self.regs.chr_hi[reg] = val;
+                        self.update_chr_banks();
+                    }
+                    0xB000 => {
+                        if reg < 4 {
+                            self.regs.nt_lo[reg] = val;
+                        } else {
+                            self.regs.nt_hi[reg & 0x03] = val;
+                        }
+                        self.update_nametables();
+                    }
+                    0xC000 => match reg {
+                        0 => {
+                            self.regs.irq_enabled = val & 0x01 == 0x01;
+                            if !self.regs.irq_enabled {
+                                Cpu::clear_irq(Irq::MAPPER);
+                            }
+                        }
+                        1 => {
+                            self.regs.irq_source = match val & 0x03 {
+                                0 => IrqSource::CpuCycle,
+                                1 => IrqSource::PpuA12Rise,
+                                2 => IrqSource::PpuRead,
+                                _ => IrqSource::CpuWrite,
+                            };
+                            self.regs.irq_small_prescaler = val & 0x04 == 0x04;
+                            self.regs.irq_direction = val >> 6;
+                        }
+                        2 => {
+                            self.regs.irq_enabled = false;
+                            Cpu::clear_irq(Irq::MAPPER);
+                        }
+                        3 => self.regs.irq_enabled = true,
+                        4 => self.regs.irq_prescaler = val ^ self.regs.irq_xor,
+                        5 => self.regs.irq_counter = val ^ self.regs.irq_xor,
+                        6 => self.regs.irq_xor = val,
+                        _ => (),
+                    },
+                    0xD000 => {
+                        match reg & 0x03 {
+                            0 => self.regs.mode = val,
+                            1 => self.regs.mirroring = val,
+                            2 => self.regs.nt_control = val,
+                            _ => self.regs.outer_bank = val,
+                        }
+                        self.update_banks();
+                    }
+                    _ => (),
+                }
+            }
+            _ => return MappedWrite::Bus,
+        }
+        MappedWrite::None
+    }
+}
+
+impl Reset for JyCompany {}
+
+impl Clock for JyCompany {
+    fn clock(&mut self) -> usize {
+        if self.regs.irq_source == IrqSource::CpuCycle {
+            self.clock_irq();
+            1
+        } else {
+            0
+        }
+    }
+}
+
+impl Regional for JyCompany {}
+impl Sram for JyCompany {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mem::RamState;
+
+    fn load(mapper_num: u8) -> Mapper {
+        // 512K PRG-ROM and 512K CHR-ROM
+        let mut rom = vec![0x00; 16 + 0x80000 + 0x80000];
+        rom[..8].copy_from_slice(&[
+            b'N',
+            b'E',
+            b'S',
+            0x1A,
+            0x20,
+            0x40,
+            mapper_num << 4,
+            mapper_num & 0xF0,
+        ]);
+        let cart = Cart::from_rom("jy_company", &mut rom.as_slice(), RamState::AllZeros)
+            .expect("valid rom");
+        assert!(matches!(cart.mapper, Mapper::JyCompany(_)));
+        cart.mapper
+    }
+
+    fn write(mapper: &mut Mapper, addr: u16, val: u8) {
+        let _ = mapper.map_write(addr, val);
+    }
+
+    #[test]
+    fn prg_modes() {
+        let mut mapper = load(90);
+        assert_eq!(mapper.map_peek(0xE000), MappedRead::PrgRom(0x7E000));
+
+        // 8K mode
+        write(&mut mapper, 0xD000, 0x02);
+        write(&mut mapper, 0x8000, 0x01);
+        write(&mut mapper, 0x8001, 0x02);
+        write(&mut mapper, 0x8002, 0x03);
+        write(&mut mapper, 0x8003, 0x04);
+        assert_eq!(mapper.map_peek(0x8000), MappedRead::PrgRom(0x2000));
+        assert_eq!(mapper.map_peek(0xA000), MappedRead::PrgRom(0x4000));
+        assert_eq!(mapper.map_peek(0xC000), MappedRead::PrgRom(0x6000));
+        assert_eq!(mapper.map_peek(0xE000), MappedRead::PrgRom(0x7E000));
+
+        // Last bank switchable and mapped at $6000
+        write(&mut mapper, 0xD000, 0x86);
+        assert_eq!(mapper.map_peek(0xE000), MappedRead::PrgRom(0x8000));
+        assert_eq!(mapper.map_peek(0x6000), MappedRead::PrgRom(0x8000));
+
+        // 16K mode
+        write(&mut mapper, 0xD000, 0x01);
+        assert_eq!(mapper.map_peek(0x8000), MappedRead::PrgRom(0x8000));
+        assert_eq!(mapper.map_peek(0xC000), MappedRead::PrgRom(0x7C000));
+
+        // 32K mode
+        write(&mut mapper, 0xD000, 0x04);
+        assert_eq!(mapper.map_peek(0x8000), MappedRead::PrgRom(0x20000));
+
+        // 8K mode with reversed bits
+        write(&mut mapper, 0xD000, 0x03);
+        write(&mut mapper, 0x8000, 0x20);
+        assert_eq!(mapper.map_peek(0x8000), MappedRead::PrgRom(0x02 << 13));
+    }
+
+    #[test]
+    fn chr_modes() {
+        let mut mapper = load(90);
+        for reg in 0..8 {
+            write(&mut mapper, 0x9000 + reg, 0x10 + reg as u8);
+        }
+
+        // 8K mode
+        assert_eq!(
+            mapper.map_peek(0x1C00),
+            MappedRead::Chr((0x10 << 13) | 0x1C00)
+        );
+
+        // 2K mode
+        write(&mut mapper, 0xD000, 0x10);
+        assert_eq!(mapper.map_peek(0x0800), MappedRead::Chr(0x12 << 11));
+
+        // 1K mode
+        write(&mut mapper, 0xD000, 0x18);
+        assert_eq!(mapper.map_peek(0x1C00), MappedRead::Chr(0x17 << 10));
+
+        write(&mut mapper, 0xA007, 0x01);
+        assert_eq!(mapper.map_peek(0x1C00), MappedRead::Chr(0x117 << 10));
+
+        // Outer bank replaces the high registers
+        write(&mut mapper, 0xD003, 0x20);
+        assert_eq!(mapper.map_peek(0x1C00), MappedRead::Chr(0x17 << 10));
+        write(&mut mapper, 0xD003, 0x21);
+        assert_eq!(mapper.map_peek(0x1C00), MappedRead::Chr(0x117 << 10));
+    }
+
+    #[test]
+    fn mmc4_chr_latch() {
+        let mut mapper = load(209);
+        write(&mut mapper, 0xD000, 0x08);
+        write(&mut mapper, 0x9000, 0x01);
+        write(&mut mapper, 0x9002, 0x02);
+        assert_eq!(mapper.map_peek(0x0000), MappedRead::Chr(0x1000));
+
+        let _ = mapper.map_read(0x0FE8);
+        assert_eq!(mapper.map_peek(0x0000), MappedRead::Chr(0x2000));
+        let _ = mapper.map_read(0x0FD8);
+        assert_eq!(mapper.map_peek(0x0000), MappedRead::Chr(0x1000));
+    }
+
+    #[test]
+    fn rom_nametables() {
+        let mut mapper = load(211);
+        write(&mut mapper, 0xB000, 0x80);
+        write(&mut mapper, 0xB001, 0x01);
+        assert_eq!(
+            mapper.map_peek(0x2005),
+            MappedRead::Chr((0x80 << 10) | 0x05)
+        );
+        assert_eq!(mapper.map_peek(0x2405), MappedRead::CIRam(0x0405));
+        assert_eq!(mapper.map_write(0x2005, 0x00), MappedWrite::None);
+
+        // Mapper 090 only supports standard mirroring
+        let mut mapper = load(90);
+        write(&mut mapper, 0xB000, 0x80);
+        write(&mut mapper, 0xD001, 0x01);
+        assert_eq!(mapper.map_peek(0x2005), MappedRead::Bus);
+        assert_eq!(mapper.mirroring(), Mirroring::Horizontal);
+    }
+
+    #[test]
+    fn multiplier() {
+        let mut mapper = load(90);
+        write(&mut mapper, 0x5800, 0xC8);
+        write(&mut mapper, 0x5801, 0x64);
+        assert_eq!(mapper.map_peek(0x5800), MappedRead::Data(0x20));
+        assert_eq!(mapper.map_peek(0x5801), MappedRead::Data(0x4E));
+        write(&mut mapper, 0x5803, 0x42);
+        assert_eq!(mapper.map_peek(0x5803), MappedRead::Data(0x42));
+    }
+
+    #[test]
+    fn cpu_cycle_irq() {
+        let mut mapper = load(90);
+        Cpu::clear_irq(Irq::MAPPER);
+        // Count down with the 3-bit prescaler
+        write(&mut mapper, 0xC001, 0x84);
+        write(&mut mapper, 0xC004, 0x00);
+        write(&mut mapper, 0xC005, 0x01);
+        write(&mut mapper, 0xC003, 0x00);
+
+        for _ in 0..8 {
+            mapper.clock();
+        }
+        assert!(!Cpu::has_irq(Irq::MAPPER));
+        mapper.clock();
+        assert!(Cpu::has_irq(Irq::MAPPER));
+
+        write(&mut mapper, 0xC002, 0x00);
+        assert!(!Cpu::has_irq(Irq::MAPPER));
+    }
+
+    #[test]
+    fn a12_irq() {
+        let mut mapper = load(209);
+        Cpu::clear_irq(Irq::MAPPER);
+        // Count up scanlines, with the counter XORed
+        write(&mut mapper, 0xC006, 0xFF);
+        write(&mut mapper, 0xC001, 0x45);
+        write(&mut mapper, 0xC004, 0xFF);
+        write(&mut mapper, 0xC005, 0x02);
+        write(&mut mapper, 0xC000, 0x01);
+
+        for _ in 0..2 {
+            let _ = mapper.map_read(0x0000);
+            let _ = mapper.map_read(0x1000);
+        }
+        assert!(!Cpu::has_irq(Irq::MAPPER));
+        let _ = mapper.map_read(0x1000);
+        assert!(!Cpu::has_irq(Irq::MAPPER));
+        let _ = mapper.map_read(0x0000);
+        let _ = mapper.map_read(0x1000);
+        assert!(Cpu::has_irq(Irq::MAPPER));
+    }
+}
@@ -6,7 +6,7 @@
 use crate::{
     cart::Cart,
     common::{Clock, Regional, Reset, ResetKind, Sram},
-    mapper::{self, Mapped, MappedRead, MappedWrite, Mapper, MemMap},
+    mapper::{self, Mapped, MappedRead, MappedWrite, Mapper, MapperDebugState, MemMap},
     mem::Banks,
     ppu::Mirroring,
 };
@@ -169,6 +169,26 @@ impl Mapped for Sxrom {
     fn set_mirroring(&mut self, mirroring: Mirroring) {
         self.mirroring = mirroring;
     }
+
+    fn debug_state(&self) -> MapperDebugState {
+        MapperDebugState {
+            mirroring: self.mirroring,
+            prg_rom_banks: self.prg_rom_banks.mappings(),
+            prg_ram_banks: self.prg_ram_banks.mappings(),
+            chr_banks: self.chr_banks.mappings(),
+            irq: None,
+            regs: vec![
+                (
+                    "Shift Register",
+                    format!("${:02X}", self.regs.shift_register),
+                ),
+                ("Control", format!("${:02X}", self.regs.control)),
+                ("CHR0", format!("${:02X}", self.regs.chr0)),
+                ("CHR1", format!("${:02X}", self.regs.chr1)),
+                ("PRG", format!("${:02X}", self.regs.prg)),
+            ],
+        }
+    }
 }
 
 impl MemMap for Sxrom {
@@ -6,7 +6,7 @@
 use crate::{
     cart::Cart,
     common::{Clock, Regional, Reset, Sram},
-    mapper::{self, Mapped, MappedRead, MappedWrite, Mapper, MemMap},
+    mapper::{self, Mapped, MappedRead, MappedWrite, Mapper, MapperDebugState, MemMap},
     mem::Banks,
     ppu::Mirroring,
 };
@@ -41,6 +41,14 @@ impl Mapped for Cnrom {
     fn set_mirroring(&mut self, mirroring: Mirroring) {
         self.mirroring = mirroring;
     }
+
+    fn debug_state(&self) -> MapperDebugState {
+        MapperDebugState {
+            mirroring: self.mirroring,
+            chr_banks: self.chr_banks.mappings(),
+            ..Default::default()
+        }
+    }
 }
 
 impl MemMap for Cnrom {
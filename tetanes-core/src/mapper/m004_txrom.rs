@@ -7,7 +7,7 @@ use crate::{
     cart::Cart,
     common::{Clock, Regional, Reset, ResetKind, Sram},
     cpu::{Cpu, Irq},
-    mapper::{self, Mapped, MappedRead, MappedWrite, Mapper, MemMap},
+    mapper::{self, Mapped, MappedRead, MappedWrite, Mapper, MapperDebugState, MapperIrq, MemMap},
     mem::Banks,
     ppu::Mirroring,
 };
@@ -212,6 +212,25 @@ impl Mapped for Txrom {
     fn ppu_bus_write(&mut self, addr: u16, _val: u8) {
         self.clock_irq(addr);
     }
+
+    fn debug_state(&self) -> MapperDebugState {
+        MapperDebugState {
+            mirroring: self.mirroring,
+            prg_rom_banks: self.prg_rom_banks.mappings(),
+            prg_ram_banks: self.prg_ram_banks.mappings(),
+            chr_banks: self.chr_banks.mappings(),
+            irq: Some(MapperIrq {
+                counter: self.regs.irq_counter.into(),
+                reload: self.regs.irq_latch.into(),
+                enabled: self.regs.irq_enabled,
+            }),
+            regs: vec![
+                ("Bank Select", format!("${:02X}", self.regs.bank_select)),
+                ("Bank Registers", format!("{:02X?}", self.regs.bank_values)),
+                ("Revision", format!("{:?}", self.revision)),
+            ],
+        }
+    }
 }
 
 impl MemMap for Txrom {
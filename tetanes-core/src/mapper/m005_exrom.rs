@@ -12,7 +12,7 @@ use crate::{
     cart::Cart,
     common::{Clock, NesRegion, Regional, Reset, ResetKind, Sample, Sram},
     cpu::{Cpu, Irq},
-    mapper::{self, Mapped, MappedRead, MappedWrite, Mapper, MemMap},
+    mapper::{self, Mapped, MappedRead, MappedWrite, Mapper, MapperDebugState, MapperIrq, MemMap},
     mem::Banks,
     ppu::{bus::PpuAddr, Mirroring, Ppu},
 };
@@ -552,6 +552,30 @@ impl Mapped for Exrom {
             _ => (),
         }
     }
+
+    fn debug_state(&self) -> MapperDebugState {
+        MapperDebugState {
+            mirroring: self.mirroring,
+            prg_rom_banks: self.prg_rom_banks.mappings(),
+            prg_ram_banks: self.prg_ram_banks.mappings(),
+            chr_banks: self.chr_banks.mappings(),
+            irq: Some(MapperIrq {
+                counter: self.ppu_status.scanline,
+                reload: self.regs.irq_scanline,
+                enabled: self.regs.irq_enabled,
+            }),
+            regs: vec![
+                ("PRG Mode", format!("{:?}", self.regs.prg_mode)),
+                ("CHR Mode", format!("{:?}", self.regs.chr_mode)),
+                (
+                    "Nametables",
+                    format!("{:?}", self.regs.nametable_mapping.select),
+                ),
+                ("PRG Banks", format!("{:02X?}", self.regs.prg_banks)),
+                ("CHR Banks", format!("{:02X?}", self.regs.chr_banks)),
+            ],
+        }
+    }
 }
 
 impl MemMap for Exrom {
@@ -5,7 +5,7 @@
 use crate::{
     cart::Cart,
     common::{Clock, Regional, Reset, ResetKind, Sram},
-    mapper::{self, Mapped, MappedRead, MappedWrite, Mapper, MemMap, Mirroring},
+    mapper::{self, Mapped, MappedRead, MappedWrite, Mapper, MapperDebugState, MemMap, Mirroring},
     mem::Banks,
 };
 use serde::{Deserialize, Serialize};
@@ -69,6 +69,22 @@ impl Mapped for Pxrom {
     fn set_mirroring(&mut self, mirroring: Mirroring) {
         self.mirroring = mirroring;
     }
+
+    fn debug_state(&self) -> MapperDebugState {
+        MapperDebugState {
+            mirroring: self.mirroring,
+            prg_rom_banks: self.prg_rom_banks.mappings(),
+            chr_banks: self.chr_banks.mappings(),
+            regs: vec![
+                (
+                    "Latches",
+                    format!("{:?}", self.latch.map(|latch| 0xFD + latch)),
+                ),
+                ("Latch Banks", format!("{:02X?}", self.latch_banks)),
+            ],
+            ..Default::default()
+        }
+    }
 }
 
 impl MemMap for Pxrom {
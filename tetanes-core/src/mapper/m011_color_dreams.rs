@@ -5,7 +5,7 @@
 use crate::{
     cart::Cart,
     common::{Clock, Regional, Reset, Sram},
-    mapper::{self, Mapped, MappedRead, MappedWrite, Mapper, MemMap, Mirroring},
+    mapper::{self, Mapped, MappedRead, MappedWrite, Mapper, MapperDebugState, MemMap, Mirroring},
     mem::Banks,
 };
 use serde::{Deserialize, Serialize};
@@ -43,6 +43,15 @@ impl Mapped for ColorDreams {
     fn set_mirroring(&mut self, mirroring: Mirroring) {
         self.mirroring = mirroring;
     }
+
+    fn debug_state(&self) -> MapperDebugState {
+        MapperDebugState {
+            mirroring: self.mirroring,
+            prg_rom_banks: self.prg_rom_banks.mappings(),
+            chr_banks: self.chr_banks.mappings(),
+            ..Default::default()
+        }
+    }
 }
 
 impl MemMap for ColorDreams {
@@ -6,7 +6,7 @@ use crate::{
     cart::Cart,
     common::{Clock, Regional, Reset, ResetKind, Sram},
     cpu::{Cpu, Irq},
-    mapper::{self, Mapped, MappedRead, MappedWrite, Mapper, MemMap},
+    mapper::{self, Mapped, MappedRead, MappedWrite, Mapper, MapperDebugState, MapperIrq, MemMap},
     mem::{BankAccess, Banks},
     ppu::Mirroring,
 };
@@ -102,6 +102,30 @@ impl Mapped for JalecoSs88006 {
     fn set_mirroring(&mut self, mirroring: Mirroring) {
         self.mirroring = mirroring;
     }
+
+    fn debug_state(&self) -> MapperDebugState {
+        let reload = self
+            .regs
+            .irq_reload
+            .iter()
+            .enumerate()
+            .fold(0, |reload, (i, val)| reload | (u16::from(*val) << (4 * i)));
+        MapperDebugState {
+            mirroring: self.mirroring,
+            prg_rom_banks: self.prg_rom_banks.mappings(),
+            prg_ram_banks: self.prg_ram_banks.mappings(),
+            chr_banks: self.chr_banks.mappings(),
+            irq: Some(MapperIrq {
+                counter: self.irq_counter,
+                reload,
+                enabled: self.regs.irq_enabled,
+            }),
+            regs: vec![(
+                "IRQ Counter Size",
+                format!("${:02X}", self.regs.irq_counter_size),
+            )],
+        }
+    }
 }
 
 impl MemMap for JalecoSs88006 {
@@ -7,7 +7,7 @@ use crate::{
     common::{Clock, Regional, Reset, ResetKind, Sample, Sram},
     cpu::{Cpu, Irq},
     fs,
-    mapper::{self, Mapped, MappedRead, MappedWrite, Mapper, MemMap},
+    mapper::{self, Mapped, MappedRead, MappedWrite, Mapper, MapperDebugState, MapperIrq, MemMap},
     mem::{BankAccess, Banks, Memory},
     ppu::Mirroring,
 };
@@ -147,6 +147,27 @@ impl Mapped for Namco163 {
     fn mirroring(&self) -> Mirroring {
         self.mirroring
     }
+
+    fn debug_state(&self) -> MapperDebugState {
+        MapperDebugState {
+            mirroring: self.mirroring,
+            prg_rom_banks: self.prg_rom_banks.mappings(),
+            prg_ram_banks: self.prg_ram_banks.mappings(),
+            chr_banks: self.chr_banks.mappings(),
+            irq: Some(MapperIrq {
+                counter: self.regs.irq_counter & 0x7FFF,
+                reload: 0x7FFF,
+                enabled: self.regs.irq_counter & 0x8000 > 0,
+            }),
+            regs: vec![
+                ("Board", format!("{:?}", self.board)),
+                (
+                    "PRG-RAM Protect",
+                    format!("${:02X}", self.regs.prg_ram_protect),
+                ),
+            ],
+        }
+    }
 }
 
 impl MemMap for Namco163 {
@@ -6,7 +6,10 @@ use crate::{
     apu::PULSE_TABLE,
     cart::Cart,
     common::{Clock, Regional, Reset, ResetKind, Sample, Sram},
-    mapper::{self, vrc_irq::VrcIrq, Mapped, MappedRead, MappedWrite, Mapper, MemMap},
+    mapper::{
+        self, vrc_irq::VrcIrq, Mapped, MappedRead, MappedWrite, Mapper, MapperDebugState,
+        MapperIrq, MemMap,
+    },
     mem::Banks,
     ppu::Mirroring,
 };
@@ -228,6 +231,21 @@ impl Mapped for Vrc6 {
     fn set_mirroring(&mut self, mirroring: Mirroring) {
         self.mirroring = mirroring;
     }
+
+    fn debug_state(&self) -> MapperDebugState {
+        MapperDebugState {
+            mirroring: self.mirroring,
+            prg_rom_banks: self.prg_rom_banks.mappings(),
+            prg_ram_banks: self.prg_ram_banks.mappings(),
+            chr_banks: self.chr_banks.mappings(),
+            irq: Some(MapperIrq {
+                counter: self.irq.counter.into(),
+                reload: self.irq.reload.into(),
+                enabled: self.irq.enabled,
+            }),
+            regs: vec![("IRQ Cycle Mode", self.irq.cycle_mode.to_string())],
+        }
+    }
 }
 
 impl MemMap for Vrc6 {
@@ -5,7 +5,7 @@
 use crate::{
     cart::Cart,
     common::{Clock, Regional, Reset, Sram},
-    mapper::{self, Mapped, MappedRead, MappedWrite, Mapper, MemMap},
+    mapper::{self, Mapped, MappedRead, MappedWrite, Mapper, MapperDebugState, MemMap},
     mem::Banks,
     ppu::Mirroring,
 };
@@ -38,6 +38,14 @@ impl Mapped for Bnrom {
     fn mirroring(&self) -> Mirroring {
         self.mirroring
     }
+
+    fn debug_state(&self) -> MapperDebugState {
+        MapperDebugState {
+            mirroring: self.mirroring,
+            prg_rom_banks: self.prg_rom_banks.mappings(),
+            ..Default::default()
+        }
+    }
 }
 
 impl MemMap for Bnrom {
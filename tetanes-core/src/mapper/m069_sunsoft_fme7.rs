@@ -7,7 +7,7 @@ use crate::{
     cart::Cart,
     common::{Clock, Regional, Reset, Sample, Sram},
     cpu::{Cpu, Irq},
-    mapper::{self, Mapped, MappedRead, MappedWrite, Mapper, MemMap},
+    mapper::{self, Mapped, MappedRead, MappedWrite, Mapper, MapperDebugState, MapperIrq, MemMap},
     mem::Banks,
     ppu::Mirroring,
 };
@@ -65,6 +65,32 @@ impl Mapped for SunsoftFme7 {
     fn set_mirroring(&mut self, mirroring: Mirroring) {
         self.mirroring = mirroring;
     }
+
+    fn debug_state(&self) -> MapperDebugState {
+        // $6000-$7FFF maps either PRG-RAM or PRG-ROM
+        let (prg_rom_banks, prg_ram_banks) = if self.regs.prg_ram_enabled {
+            (self.prg_rom_banks.mappings(), self.prg_banks.mappings())
+        } else {
+            let mut prg_rom_banks = self.prg_banks.mappings();
+            prg_rom_banks.extend(self.prg_rom_banks.mappings());
+            (prg_rom_banks, Vec::new())
+        };
+        MapperDebugState {
+            mirroring: self.mirroring,
+            prg_rom_banks,
+            prg_ram_banks,
+            chr_banks: self.chr_banks.mappings(),
+            irq: Some(MapperIrq {
+                counter: self.regs.irq_counter,
+                reload: 0,
+                enabled: self.regs.irq_enabled && self.regs.irq_counter_enabled,
+            }),
+            regs: vec![
+                ("Command", format!("${:02X}", self.regs.command)),
+                ("Parameter", format!("${:02X}", self.regs.parameter)),
+            ],
+        }
+    }
 }
 
 impl MemMap for SunsoftFme7 {
@@ -7,7 +7,7 @@ use crate::{
     cart::Cart,
     common::{Clock, NesRegion, Regional, Reset, ResetKind, Sram},
     fs,
-    mapper::{self, Dxrom206, Mapped, MappedRead, MappedWrite, Mapper, MemMap},
+    mapper::{self, Dxrom206, Mapped, MappedRead, MappedWrite, Mapper, MapperDebugState, MemMap},
     ppu::Mirroring,
 };
 use serde::{Deserialize, Serialize};
@@ -69,6 +69,10 @@ impl Mapped for Dxrom {
     fn cpu_bus_write(&mut self, addr: u16, val: u8) {
         self.inner.cpu_bus_write(addr, val)
     }
+
+    fn debug_state(&self) -> MapperDebugState {
+        self.inner.debug_state()
+    }
 }
 
 impl MemMap for Dxrom {
@@ -7,7 +7,7 @@ use crate::{
     cart::Cart,
     common::{Clock, NesRegion, Regional, Reset, ResetKind, Sram},
     fs,
-    mapper::{self, Dxrom88, Mapped, MappedRead, MappedWrite, Mapper, MemMap},
+    mapper::{self, Dxrom88, Mapped, MappedRead, MappedWrite, Mapper, MapperDebugState, MemMap},
     ppu::Mirroring,
 };
 use serde::{Deserialize, Serialize};
@@ -49,6 +49,10 @@ impl Mapped for Dxrom {
     fn cpu_bus_write(&mut self, addr: u16, val: u8) {
         self.inner.cpu_bus_write(addr, val)
     }
+
+    fn debug_state(&self) -> MapperDebugState {
+        self.inner.debug_state()
+    }
 }
 
 impl MemMap for Dxrom {
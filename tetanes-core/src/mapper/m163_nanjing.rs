@@ -0,0 +1,306 @@
+//! `Nánjīng` (Mapper 163)
+//!
+//! <https://www.nesdev.org/wiki/INES_Mapper_163>
+
+use crate::{
+    cart::Cart,
+    common::{Clock, Regional, Reset, Sram},
+    mapper::{self, Mapped, MappedRead, MappedWrite, Mapper, MapperDebugState, MemMap},
+    mem::Banks,
+    ppu::Mirroring,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+#[must_use]
+pub struct Regs {
+    pub prg_lo: u8,    // $5000
+    pub prg_hi: u8,    // $5200
+    pub security: u8,  // $5100
+    pub feedback: u8,  // $5300
+    pub strobe: u8,    // $5101
+    pub trigger: bool, // Toggled by $5101 going to zero
+    pub scanline: Option<u16>,
+    pub prev_nt_addr: Option<u16>,
+    pub nt_matches: u8,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[must_use]
+pub struct Nanjing {
+    pub regs: Regs,
+    pub mirroring: Mirroring,
+    pub chr_banks: Banks,
+    pub prg_rom_banks: Banks,
+}
+
+impl Nanjing {
+    const PRG_ROM_WINDOW: usize = 32 * 1024;
+    const PRG_RAM_SIZE: usize = 8 * 1024;
+    const CHR_WINDOW: usize = 4 * 1024;
+    const CHR_RAM_SIZE: usize = 8 * 1024;
+
+    const CHR_SWITCH_SCANLINE: u16 = 128;
+    const CHR_RESET_SCANLINE: u16 = 240;
+
+    pub fn load(cart: &mut Cart) -> Result<Mapper, mapper::Error> {
+        if !cart.has_prg_ram() {
+            cart.add_prg_ram(Self::PRG_RAM_SIZE);
+        }
+        if !cart.has_chr_rom() && cart.chr_ram.is_empty() {
+            cart.add_chr_ram(Self::CHR_RAM_SIZE);
+        };
+        let chr_len = cart.chr_rom.len().max(cart.chr_ram.len());
+        let nanjing = Self {
+            regs: Regs::default(),
+            mirroring: cart.mirroring(),
+            chr_banks: Banks::new(0x0000, 0x1FFF, chr_len, Self::CHR_WINDOW)?,
+            prg_rom_banks: Banks::new(0x8000, 0xFFFF, cart.prg_rom.len(), Self::PRG_ROM_WINDOW)?,
+        };
+        Ok(nanjing.into())
+    }
+
+    /// Whether CHR-RAM switches from the first to the second 4K half in the middle of the
+    /// screen, giving the top and bottom halves their own tiles.
+    const fn auto_switch_chr(&self) -> bool {
+        self.regs.prg_lo & 0x80 == 0x80
+    }
+
+    fn update_prg_banks(&mut self) {
+        let bank = usize::from(self.regs.prg_hi) << 4 | usize::from(self.regs.prg_lo & 0x0F);
+        self.prg_rom_banks.set(0, bank);
+    }
+
+    fn set_chr_halves(&mut self, left: usize, right: usize) {
+        self.chr_banks.set(0, left);
+        self.chr_banks.set(1, right);
+    }
+
+    /// Detects scanlines from the dummy nametable fetches at the end of each one, like MMC5.
+    fn detect_scanline(&mut self, addr: u16) {
+        if addr <= 0x2FFF && Some(addr) == self.regs.prev_nt_addr {
+            self.regs.nt_matches += 1;
+            if self.regs.nt_matches == 2 {
+                let scanline = self.regs.scanline.map_or(0, |scanline| scanline + 1);
+                self.regs.scanline = Some(scanline);
+                if self.auto_switch_chr() {
+                    match scanline {
+                        Self::CHR_SWITCH_SCANLINE => self.set_chr_halves(1, 1),
+                        Self::CHR_RESET_SCANLINE => self.set_chr_halves(0, 0),
+                        _ => (),
+                    }
+                }
+            }
+        } else {
+            self.regs.nt_matches = 0;
+        }
+        self.regs.prev_nt_addr = Some(addr);
+    }
+}
+
+impl Mapped for Nanjing {
+    fn mirroring(&self) -> Mirroring {
+        self.mirroring
+    }
+
+    fn set_mirroring(&mut self, mirroring: Mirroring) {
+        self.mirroring = mirroring;
+    }
+
+    fn debug_state(&self) -> MapperDebugState {
+        MapperDebugState {
+            mirroring: self.mirroring,
+            prg_rom_banks: self.prg_rom_banks.mappings(),
+            chr_banks: self.chr_banks.mappings(),
+            regs: vec![
+                ("$5000", format!("${:02X}", self.regs.prg_lo)),
+                ("$5100", format!("${:02X}", self.regs.security)),
+                ("$5200", format!("${:02X}", self.regs.prg_hi)),
+                ("$5300", format!("${:02X}", self.regs.feedback)),
+                ("Trigger", format!("{}", self.regs.trigger)),
+            ],
+            ..Default::default()
+        }
+    }
+}
+
+impl MemMap for Nanjing {
+    // PPU $0000..=$0FFF 4K CHR-RAM Bank 1 Switchable
+    // PPU $1000..=$1FFF 4K CHR-RAM Bank 2 Switchable
+
+    // CPU $5000..=$5FFF Bank and copy protection registers
+    // CPU $6000..=$7FFF 8K PRG-RAM Bank Fixed
+    // CPU $8000..=$FFFF 32K PRG-ROM Bank Switchable
+
+    fn map_read(&mut self, addr: u16) -> MappedRead {
+        match addr {
+            0x2000..=0x3EFF => self.detect_scanline(addr),
+            // NMI ends the frame
+            0xFFFA | 0xFFFB => {
+                self.regs.scanline = None;
+                self.regs.prev_nt_addr = None;
+            }
+            _ => (),
+        }
+        self.map_peek(addr)
+    }
+
+    fn map_peek(&self, addr: u16) -> MappedRead {
+        match addr {
+            0x0000..=0x1FFF => MappedRead::Chr(self.chr_banks.translate(addr)),
+            0x5000..=0x5FFF => match addr & 0x7700 {
+                0x5100 => MappedRead::Data(
+                    self.regs.feedback
+                        | self.regs.prg_hi
+                        | self.regs.prg_lo
+                        | (self.regs.security ^ 0xFF),
+                ),
+                0x5500 => MappedRead::Data(if self.regs.trigger {
+                    self.regs.feedback | self.regs.prg_lo
+                } else {
+                    0x00
+                }),
+                _ => MappedRead::Data(0x04),
+            },
+            0x6000..=0x7FFF => MappedRead::PrgRam((addr & 0x1FFF).into()),
+            0x8000..=0xFFFF => MappedRead::PrgRom(self.prg_rom_banks.translate(addr)),
+            _ => MappedRead::Bus,
+        }
+    }
+
+    fn map_write(&mut self, addr: u16, val: u8) -> MappedWrite {
+        match addr {
+            0x0000..=0x1FFF => MappedWrite::ChrRam(self.chr_banks.translate(addr), val),
+            0x5101 => {
+                if self.regs.strobe != 0 && val == 0 {
+                    self.regs.trigger = !self.regs.trigger;
+                }
+                self.regs.strobe = val;
+                MappedWrite::None
+            }
+            // Protection check expects bank 3 without changing the registers
+            0x5100 if val == 6 => {
+                self.prg_rom_banks.set(0, 3);
+                MappedWrite::None
+            }
+            0x5000..=0x5FFF => {
+                match addr & 0x7300 {
+                    0x5000 => {
+                        self.regs.prg_lo = val;
+                        let top_half = self
+                            .regs
+                            .scanline
+                            .is_some_and(|scanline| scanline < Self::CHR_SWITCH_SCANLINE);
+                        if !self.auto_switch_chr() && top_half {
+                            self.set_chr_halves(0, 1);
+                        }
+                        self.update_prg_banks();
+                    }
+                    0x5100 => {
+                        self.regs.security = val;
+                        self.update_prg_banks();
+                    }
+                    0x5200 => {
+                        self.regs.prg_hi = val;
+                        self.update_prg_banks();
+                    }
+                    0x5300 => self.regs.feedback = val,
+                    _ => (),
+                }
+                MappedWrite::None
+            }
+            0x6000..=0x7FFF => MappedWrite::PrgRam((addr & 0x1FFF).into(), val),
+            _ => MappedWrite::Bus,
+        }
+    }
+}
+
+impl Reset for Nanjing {}
+impl Clock for Nanjing {}
+impl Regional for Nanjing {}
+impl Sram for Nanjing {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mem::RamState;
+
+    fn load() -> Mapper {
+        // 2M PRG-ROM and 8K CHR-RAM
+        let mut rom = vec![0x00; 16 + 0x200000];
+        rom[..8].copy_from_slice(b"NES\x1A\x80\x00\x30\xA0");
+        let cart =
+            Cart::from_rom("nanjing", &mut rom.as_slice(), RamState::AllZeros).expect("valid rom");
+        assert!(matches!(cart.mapper, Mapper::Nanjing(_)));
+        cart.mapper
+    }
+
+    fn write(mapper: &mut Mapper, addr: u16, val: u8) {
+        let _ = mapper.map_write(addr, val);
+    }
+
+    fn end_scanline(mapper: &mut Mapper) {
+        for _ in 0..3 {
+            let _ = mapper.map_read(0x2000);
+        }
+        let _ = mapper.map_read(0x23C0);
+    }
+
+    #[test]
+    fn prg_banks() {
+        let mut mapper = load();
+        write(&mut mapper, 0x5000, 0x05);
+        write(&mut mapper, 0x5200, 0x03);
+        assert_eq!(mapper.map_peek(0x8000), MappedRead::PrgRom(0x35 << 15));
+
+        write(&mut mapper, 0x5100, 0x06);
+        assert_eq!(mapper.map_peek(0x8000), MappedRead::PrgRom(3 << 15));
+        write(&mut mapper, 0x5200, 0x00);
+        assert_eq!(mapper.map_peek(0x8000), MappedRead::PrgRom(0x05 << 15));
+    }
+
+    #[test]
+    fn protection() {
+        let mut mapper = load();
+        write(&mut mapper, 0x5100, 0x0F);
+        write(&mut mapper, 0x5300, 0x20);
+        assert_eq!(mapper.map_peek(0x5100), MappedRead::Data(0xF0));
+        assert_eq!(mapper.map_peek(0x5500), MappedRead::Data(0x00));
+        assert_eq!(mapper.map_peek(0x5000), MappedRead::Data(0x04));
+
+        write(&mut mapper, 0x5101, 0x01);
+        write(&mut mapper, 0x5101, 0x00);
+        assert_eq!(mapper.map_peek(0x5500), MappedRead::Data(0x20));
+        write(&mut mapper, 0x5101, 0x01);
+        write(&mut mapper, 0x5101, 0x00);
+        assert_eq!(mapper.map_peek(0x5500), MappedRead::Data(0x00));
+    }
+
+    #[test]
+    fn auto_switch_chr() {
+        let mut mapper = load();
+        write(&mut mapper, 0x5000, 0x80);
+        assert_eq!(mapper.map_peek(0x1000), MappedRead::Chr(0x1000));
+
+        // Pre-render scanline
+        end_scanline(&mut mapper);
+        for _ in 0..127 {
+            end_scanline(&mut mapper);
+        }
+        assert_eq!(mapper.map_peek(0x0000), MappedRead::Chr(0x0000));
+        end_scanline(&mut mapper);
+        assert_eq!(mapper.map_peek(0x0000), MappedRead::Chr(0x1000));
+        assert_eq!(mapper.map_peek(0x1000), MappedRead::Chr(0x1000));
+
+        for _ in 128..240 {
+            end_scanline(&mut mapper);
+        }
+        assert_eq!(mapper.map_peek(0x1000), MappedRead::Chr(0x0000));
+
+        // Disabling in the top half of the screen restores both halves
+        let _ = mapper.map_read(0xFFFA);
+        end_scanline(&mut mapper);
+        write(&mut mapper, 0x5000, 0x00);
+        assert_eq!(mapper.map_peek(0x1000), MappedRead::Chr(0x1000));
+    }
+}
@@ -0,0 +1,174 @@
+//! `Waixing WXN` (Mapper 176)
+//!
+//! Only the simple Waixing `$5xxx` PRG/CHR banking is supported. Most mapper 176 games use the
+//! MMC3-based FK23C board instead, which isn't supported yet, so only known WXN games are loaded.
+//!
+//! <https://www.nesdev.org/wiki/INES_Mapper_176>
+
+use crate::{
+    cart::Cart,
+    common::{Clock, Regional, Reset, Sram},
+    mapper::{self, Mapped, MappedRead, MappedWrite, Mapper, MapperDebugState, MemMap},
+    mem::Banks,
+    ppu::Mirroring,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[must_use]
+pub struct Wxn {
+    /// Set by writing `$24` to `$5010`, which moves 32K PRG-ROM banking from `$5011` to `$5001`.
+    pub swap_bank_write: bool,
+    pub mirroring: Mirroring,
+    pub chr_banks: Banks,
+    pub prg_rom_banks: Banks,
+}
+
+impl Wxn {
+    const PRG_ROM_WINDOW: usize = 8 * 1024;
+    const PRG_RAM_SIZE: usize = 8 * 1024;
+    const CHR_WINDOW: usize = 8 * 1024;
+    const CHR_RAM_SIZE: usize = 8 * 1024;
+    /// Combined PRG-ROM and CHR-ROM CRC32s of games known to only use WXN banking, from
+    /// `game_database.txt`.
+    const GAMES: [u32; 1] = [
+        0x2CF5_DB05, // Zhi Li Xiao Zhuang Yuan (China) (Unl)
+    ];
+
+    /// Whether the Cart is a known WXN game, rather than an FK23C game that needs MMC3 banking.
+    #[must_use]
+    pub fn supported(cart: &Cart) -> bool {
+        Self::GAMES.contains(&cart.crc32())
+    }
+
+    pub fn load(cart: &mut Cart) -> Result<Mapper, mapper::Error> {
+        if !cart.has_prg_ram() {
+            cart.add_prg_ram(Self::PRG_RAM_SIZE);
+        }
+        if !cart.has_chr_rom() && cart.chr_ram.is_empty() {
+            cart.add_chr_ram(Self::CHR_RAM_SIZE);
+        };
+        let chr_len = cart.chr_rom.len().max(cart.chr_ram.len());
+        let mut wxn = Self {
+            swap_bank_write: false,
+            mirroring: cart.mirroring(),
+            chr_banks: Banks::new(0x0000, 0x1FFF, chr_len, Self::CHR_WINDOW)?,
+            prg_rom_banks: Banks::new(0x8000, 0xFFFF, cart.prg_rom.len(), Self::PRG_ROM_WINDOW)?,
+        };
+        let last = wxn.prg_rom_banks.last();
+        wxn.prg_rom_banks.set(2, last.saturating_sub(1));
+        wxn.prg_rom_banks.set(3, last);
+        Ok(wxn.into())
+    }
+
+    fn set_prg_bank(&mut self, bank: u8) {
+        self.prg_rom_banks.set_range(0, 3, usize::from(bank) << 2);
+    }
+}
+
+impl Mapped for Wxn {
+    fn mirroring(&self) -> Mirroring {
+        self.mirroring
+    }
+
+    fn set_mirroring(&mut self, mirroring: Mirroring) {
+        self.mirroring = mirroring;
+    }
+
+    fn debug_state(&self) -> MapperDebugState {
+        MapperDebugState {
+            mirroring: self.mirroring,
+            prg_rom_banks: self.prg_rom_banks.mappings(),
+            chr_banks: self.chr_banks.mappings(),
+            regs: vec![("Swap Bank Write", format!("{}", self.swap_bank_write))],
+            ..Default::default()
+        }
+    }
+}
+
+impl MemMap for Wxn {
+    // PPU $0000..=$1FFF 8K CHR-ROM/RAM Bank Switchable
+
+    // CPU $6000..=$7FFF 8K PRG-RAM Bank Fixed
+    // CPU $8000..=$FFFF 32K PRG-ROM Bank Switchable, starting with the first two and last two
+    //                   8K Banks
+
+    fn map_peek(&self, addr: u16) -> MappedRead {
+        match addr {
+            0x0000..=0x1FFF => MappedRead::Chr(self.chr_banks.translate(addr)),
+            0x6000..=0x7FFF => MappedRead::PrgRam((addr & 0x1FFF).into()),
+            0x8000..=0xFFFF => MappedRead::PrgRom(self.prg_rom_banks.translate(addr)),
+            _ => MappedRead::Bus,
+        }
+    }
+
+    fn map_write(&mut self, addr: u16, val: u8) -> MappedWrite {
+        match addr {
+            0x0000..=0x1FFF => return MappedWrite::ChrRam(self.chr_banks.translate(addr), val),
+            0x5001 if self.swap_bank_write => self.set_prg_bank(val),
+            0x5010 if val == 0x24 => self.swap_bank_write = true,
+            0x5011 if !self.swap_bank_write => self.set_prg_bank(val >> 1),
+            0x5FF1 => self.set_prg_bank(val >> 1),
+            0x5FF2 => self.chr_banks.set(0, val.into()),
+            0x6000..=0x7FFF => return MappedWrite::PrgRam((addr & 0x1FFF).into(), val),
+            _ => return MappedWrite::Bus,
+        }
+        MappedWrite::None
+    }
+}
+
+impl Reset for Wxn {}
+impl Clock for Wxn {}
+impl Regional for Wxn {}
+impl Sram for Wxn {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mem::RamState;
+
+    fn load() -> Mapper {
+        // 1M PRG-ROM and 64K CHR-ROM
+        let mut rom = vec![0x00; 16 + 0x100000 + 0x10000];
+        rom[..8].copy_from_slice(b"NES\x1A\x40\x08\x00\xB0");
+        let mut cart =
+            Cart::from_rom("wxn", &mut rom.as_slice(), RamState::AllZeros).expect("valid rom");
+        // Not a known WXN game, so it's assumed to need the unsupported FK23C MMC3 mode
+        assert!(cart.mapper.is_none());
+        Wxn::load(&mut cart).expect("valid mapper")
+    }
+
+    fn write(mapper: &mut Mapper, addr: u16, val: u8) {
+        let _ = mapper.map_write(addr, val);
+    }
+
+    #[test]
+    fn prg_banks() {
+        let mut mapper = load();
+        assert_eq!(mapper.map_peek(0xA000), MappedRead::PrgRom(0x2000));
+        assert_eq!(mapper.map_peek(0xC000), MappedRead::PrgRom(0xFC000));
+        assert_eq!(mapper.map_peek(0xE000), MappedRead::PrgRom(0xFE000));
+
+        write(&mut mapper, 0x5011, 0x06);
+        assert_eq!(mapper.map_peek(0xE000), MappedRead::PrgRom(0x1E000));
+        write(&mut mapper, 0x5001, 0x05);
+        assert_eq!(mapper.map_peek(0x8000), MappedRead::PrgRom(0x18000));
+
+        // Swap to banking with $5001
+        write(&mut mapper, 0x5010, 0x24);
+        write(&mut mapper, 0x5001, 0x05);
+        assert_eq!(mapper.map_peek(0x8000), MappedRead::PrgRom(0x28000));
+        write(&mut mapper, 0x5011, 0x02);
+        assert_eq!(mapper.map_peek(0x8000), MappedRead::PrgRom(0x28000));
+
+        write(&mut mapper, 0x5FF1, 0x02);
+        assert_eq!(mapper.map_peek(0x8000), MappedRead::PrgRom(0x8000));
+    }
+
+    #[test]
+    fn chr_banks() {
+        let mut mapper = load();
+        write(&mut mapper, 0x5FF2, 0x03);
+        assert_eq!(mapper.map_peek(0x0010), MappedRead::Chr(0x6010));
+    }
+}
@@ -7,7 +7,7 @@ use crate::{
     cart::Cart,
     common::{Clock, NesRegion, Regional, Reset, ResetKind, Sram},
     fs,
-    mapper::{self, Mapped, MappedRead, MappedWrite, Mapper, MemMap, Txrom},
+    mapper::{self, Mapped, MappedRead, MappedWrite, Mapper, MapperDebugState, MemMap, Txrom},
     mem::Banks,
     ppu::Mirroring,
 };
@@ -70,6 +70,14 @@ impl Mapped for Dxrom {
     fn cpu_bus_write(&mut self, addr: u16, val: u8) {
         self.inner.cpu_bus_write(addr, val)
     }
+
+    fn debug_state(&self) -> MapperDebugState {
+        // Same banking as MMC3, without the IRQ counter
+        MapperDebugState {
+            irq: None,
+            ..self.inner.debug_state()
+        }
+    }
 }
 
 impl MemMap for Dxrom {
@@ -1,15 +1,26 @@
 //! Memory and Bankswitching implementations.
 
 use crate::common::{Reset, ResetKind};
-use rand::Rng;
+use rand::{rngs::StdRng, Rng, SeedableRng};
 use serde::{Deserialize, Serialize};
 use std::{
+    cell::RefCell,
     fmt,
     num::NonZeroUsize,
     ops::{Deref, DerefMut},
     str::FromStr,
 };
 
+thread_local! {
+    static RAM_RNG: RefCell<StdRng> = RefCell::new(StdRng::from_entropy());
+}
+
+/// Seed the random number generator used to fill [`RamState::Random`] memory on the current
+/// thread, making subsequently created RAM contents deterministic.
+pub fn seed_random_ram(seed: u64) {
+    RAM_RNG.with_borrow_mut(|rng| *rng = StdRng::seed_from_u64(seed));
+}
+
 /// Represents ROM or RAM memory in bytes, with a custom Debug implementation that avoids printing
 /// the entire contents..
 #[derive(Default, Clone, Serialize, Deserialize)]
@@ -40,10 +51,11 @@ impl Memory {
             RamState::AllZeros => self.0.fill(0x00),
             RamState::AllOnes => self.0.fill(0xFF),
             RamState::Random => {
-                let mut rng = rand::thread_rng();
-                for val in &mut self.0 {
-                    *val = rng.gen_range(0x00..=0xFF);
-                }
+                RAM_RNG.with_borrow_mut(|rng| {
+                    for val in &mut self.0 {
+                        *val = rng.gen_range(0x00..=0xFF);
+                    }
+                });
             }
         }
     }
@@ -220,6 +232,19 @@ pub enum BankAccess {
     ReadWrite,
 }
 
+/// The page mapped into a single bank window. See [`Banks::mappings`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[must_use]
+pub struct BankMapping {
+    /// First address of the bank window.
+    pub start: usize,
+    /// Size of the bank window in bytes.
+    pub size: usize,
+    /// Page of ROM or RAM mapped into the bank window.
+    pub page: usize,
+    pub access: BankAccess,
+}
+
 /// Represents a set of [`Memory`] banks.
 #[derive(Clone, Serialize, Deserialize)]
 #[must_use]
@@ -364,6 +389,18 @@ impl Banks {
     pub const fn page_count(&self) -> usize {
         self.page_count
     }
+
+    /// The page mapped into each bank window, for debugging.
+    pub fn mappings(&self) -> Vec<BankMapping> {
+        (0..self.banks.len())
+            .map(|bank| BankMapping {
+                start: self.start + bank * self.window.get(),
+                size: self.window.get(),
+                page: self.page(bank),
+                access: self.access[bank],
+            })
+            .collect()
+    }
 }
 
 impl std::fmt::Debug for Banks {
@@ -427,4 +464,39 @@ mod tests {
         banks.set(0, banks.last());
         assert_eq!(banks.translate(0x8000), 0x1E000);
     }
+
+    #[test]
+    fn bank_mappings() {
+        let mut banks = Banks::new(0x8000, 0xFFFF, 128 * 1024, 0x4000).unwrap();
+        banks.set(1, banks.last());
+        banks.set_access(0, BankAccess::Read);
+
+        let mappings = banks.mappings();
+        assert_eq!(mappings.len(), 2);
+        assert_eq!(
+            mappings[0],
+            BankMapping {
+                start: 0x8000,
+                size: 0x4000,
+                page: 0,
+                access: BankAccess::Read,
+            }
+        );
+        assert_eq!((mappings[1].start, mappings[1].page), (0xC000, 7));
+    }
+
+    #[test]
+    fn seeded_random_ram() {
+        let mut a = Memory::ram(RamState::Random, 0x0800);
+        let mut b = Memory::ram(RamState::Random, 0x0800);
+
+        seed_random_ram(42);
+        a.fill_ram(RamState::Random);
+        seed_random_ram(42);
+        b.fill_ram(RamState::Random);
+        assert_eq!(a.0, b.0, "same seed");
+
+        b.fill_ram(RamState::Random);
+        assert_ne!(a.0, b.0, "advanced rng");
+    }
 }
@@ -3,12 +3,14 @@
 use crate::{
     common::{Clock, ClockTo, NesRegion, Regional, Reset, ResetKind},
     cpu::Cpu,
-    debug::PpuDebugger,
+    debug::{PpuDebugger, PpuLayers, TileChange},
     mapper::{Mapped, Mapper},
     mem::Mem,
     ppu::{bus::Bus, frame::Frame},
 };
 use ctrl::Ctrl;
+#[cfg(feature = "hd-pack")]
+use hd_pack::HdRenderer;
 use mask::Mask;
 use scroll::Scroll;
 use serde::{Deserialize, Serialize};
@@ -20,6 +22,8 @@ use tracing::trace;
 pub mod bus;
 pub mod ctrl;
 pub mod frame;
+#[cfg(feature = "hd-pack")]
+pub mod hd_pack;
 pub mod mask;
 pub mod scroll;
 pub mod sprite;
@@ -136,6 +140,13 @@ pub struct Ppu {
     pub sprites: [Sprite; 8],
     /// Whether a sprite is present at the given x-coordinate. Used for `spr_zero_hit` detection.
     pub spr_present: Vec<bool>,
+    /// Draw every sprite on a scanline instead of only the first eight. The sprite overflow flag
+    /// is still set as normal for games that rely on it.
+    #[serde(skip)]
+    pub unlimited_sprites: bool,
+    /// Sprites beyond the first eight on a scanline when `unlimited_sprites` is enabled.
+    #[serde(skip)]
+    pub extra_sprites: Vec<Sprite>,
 
     pub prevent_vbl: bool,
     pub frame: Frame,
@@ -153,6 +164,12 @@ pub struct Ppu {
 
     #[serde(skip)]
     pub debugger: Option<PpuDebugger>,
+    /// Which layers are drawn to the frame, for debugging.
+    #[serde(skip)]
+    pub layers: PpuLayers,
+    #[cfg(feature = "hd-pack")]
+    #[serde(skip)]
+    pub hd: Option<HdRenderer>,
 }
 
 impl Default for Ppu {
@@ -282,6 +299,8 @@ impl Ppu {
             secondary_oamdata: [0xFF; Self::SECONDARY_OAM_SIZE],
             sprites: [Sprite::new(); 8],
             spr_present: vec![false; Self::VISIBLE_END as usize],
+            unlimited_sprites: false,
+            extra_sprites: Vec::new(),
 
             prevent_vbl: false,
             frame: Frame::new(),
@@ -294,6 +313,9 @@ impl Ppu {
             open_bus: 0x00,
 
             debugger: None,
+            layers: PpuLayers::All,
+            #[cfg(feature = "hd-pack")]
+            hd: None,
         };
 
         ppu.set_region(ppu.region);
@@ -326,6 +348,8 @@ impl Ppu {
     #[inline]
     pub fn load_mapper(&mut self, mapper: Mapper) {
         self.bus.mapper = mapper;
+        self.bus.mapper_writes.clear();
+        self.bus.mapper_irqs.clear();
     }
 
     /// Return the current Nametable mirroring mode.
@@ -413,6 +437,56 @@ impl Ppu {
         }
     }
 
+    /// Compare each nametable tile against a previous snapshot, filling `changes` with a
+    /// [`TileChange`] per tile, laid out in rows of 64 tiles across all 4 nametables to match
+    /// [`Ppu::load_nametables`].
+    pub fn load_nametable_changes(&self, prev: &Ppu, changes: &mut [TileChange]) {
+        let mirroring_changed = self.mirroring() != prev.mirroring();
+        for i in 0..4 {
+            let base_addr = Ppu::NT_START + (i as u16) * Ppu::NT_SIZE;
+            let col_offset = (i % 2) * 32;
+            let row_offset = (i / 2) * 30;
+
+            for addr in base_addr..(base_addr + Ppu::NT_SIZE - 64) {
+                let x_scroll = addr & Scroll::COARSE_X_MASK;
+                let y_scroll = (addr & Scroll::COARSE_Y_MASK) >> 5;
+                let supertile = ((y_scroll & 0xFC) << 1) + (x_scroll >> 2);
+                let attr_addr = base_addr + Ppu::ATTR_OFFSET + supertile;
+                let attr_shift = (x_scroll & 0x02) | ((y_scroll & 0x02) << 1);
+
+                let entry = |ppu: &Ppu| {
+                    let tile_index = ppu.bus.peek_ciram(addr);
+                    let palette = (ppu.bus.peek_ciram(attr_addr) >> attr_shift) & 0x03;
+                    (tile_index, palette)
+                };
+                let pattern = |ppu: &Ppu, tile_index: u8| {
+                    let tile_addr = ppu.ctrl.bg_select | (u16::from(tile_index) << 4);
+                    std::array::from_fn::<_, 16, _>(|i| ppu.bus.peek_chr(tile_addr + i as u16))
+                };
+
+                let (tile_index, palette) = entry(self);
+                let (prev_tile_index, prev_palette) = entry(prev);
+                let change = if (tile_index, palette) != (prev_tile_index, prev_palette) {
+                    if mirroring_changed {
+                        TileChange::Mapper
+                    } else {
+                        TileChange::Cpu
+                    }
+                } else if pattern(self, tile_index) != pattern(prev, prev_tile_index) {
+                    TileChange::Mapper
+                } else {
+                    TileChange::None
+                };
+
+                let col = usize::from(x_scroll) + col_offset;
+                let row = usize::from(y_scroll) + row_offset;
+                if let Some(tile) = changes.get_mut(row * 64 + col) {
+                    *tile = change;
+                }
+            }
+        }
+    }
+
     /// Load the given buffer with RGBA pixels from the current pattern tables.
     pub fn load_pattern_tables(&self, pattern_tables: &mut [u8]) {
         for i in 0..2 {
@@ -437,6 +511,57 @@ impl Ppu {
         }
     }
 
+    /// Load the given buffer with the 2-bit color index of each pixel in the current pattern
+    /// tables, laid out side by side like [`Ppu::load_pattern_tables`].
+    pub fn load_pattern_table_indices(&self, indices: &mut [u8]) {
+        for tile_addr in (0..0x2000).step_by(16) {
+            let (x, y) = Self::pattern_tile_pos(tile_addr);
+            for row in 0..8 {
+                let tile_lo = self.bus.peek_chr(tile_addr + row);
+                let tile_hi = self.bus.peek_chr(tile_addr + row + 8);
+                let offset = (y + usize::from(row)) * Ppu::WIDTH as usize + x;
+                for (col, index) in indices[offset..offset + 8].iter_mut().enumerate() {
+                    let shift = 7 - col;
+                    *index = (((tile_hi >> shift) & 0x01) << 1) | ((tile_lo >> shift) & 0x01);
+                }
+            }
+        }
+    }
+
+    /// Write 2-bit color indices laid out like [`Ppu::load_pattern_table_indices`] back into the
+    /// pattern tables as currently banked. Returns `false` without writing anything if the cart
+    /// uses CHR-ROM.
+    pub fn write_pattern_table_indices(&mut self, indices: &[u8]) -> bool {
+        if self.bus.chr_ram.is_empty() {
+            return false;
+        }
+        for tile_addr in (0..0x2000).step_by(16) {
+            let (x, y) = Self::pattern_tile_pos(tile_addr);
+            for row in 0..8 {
+                let offset = (y + usize::from(row)) * Ppu::WIDTH as usize + x;
+                let (tile_lo, tile_hi) =
+                    indices[offset..offset + 8]
+                        .iter()
+                        .fold((0, 0), |(lo, hi), index| {
+                            (
+                                (lo << 1) | (index & 0x01),
+                                (hi << 1) | ((index >> 1) & 0x01),
+                            )
+                        });
+                self.bus.poke_chr(tile_addr + row, tile_lo);
+                self.bus.poke_chr(tile_addr + row + 8, tile_hi);
+            }
+        }
+        true
+    }
+
+    /// Top-left pixel of the tile at `tile_addr` with both pattern tables side by side.
+    const fn pattern_tile_pos(tile_addr: u16) -> (usize, usize) {
+        let table_x = (tile_addr / 0x1000) as usize * 128;
+        let tile = (tile_addr % 0x1000) as usize / 16;
+        (table_x + (tile % 16) * 8, (tile / 16) * 8)
+    }
+
     /// Load the given buffer with RGBA pixels from the current pattern tables.
     pub fn load_oam(
         &self,
@@ -549,6 +674,16 @@ impl Ppu {
         }
     }
 
+    /// RGB triplets for the 32 current palette entries, in the layout of a `.pal` file.
+    pub fn export_palette(&self) -> Vec<u8> {
+        (Ppu::PALETTE_START..Ppu::PALETTE_END)
+            .flat_map(|addr| {
+                let index = usize::from(self.bus.peek_palette(addr) & 0x3F) * 3;
+                Ppu::NTSC_PALETTE[index..index + 3].iter().copied()
+            })
+            .collect()
+    }
+
     fn set_pixel(color: u16, x: u32, y: u32, width: u32, pixels: &mut [u8]) {
         let index = (color as usize) * 3;
         let idx = 4 * (x + y * width) as usize;
@@ -620,6 +755,11 @@ impl Ppu {
         let addr = Self::NT_START | (self.scroll.addr() & nametable_addr_mask);
         let tile_index = u16::from(self.bus.read_ciram(addr));
         self.tile_addr = self.ctrl.bg_select | (tile_index << 4) | self.scroll.fine_y;
+
+        #[cfg(feature = "hd-pack")]
+        if let Some(hd) = &mut self.hd {
+            hd.fetch_bg_tile(&self.bus, self.tile_addr);
+        }
     }
 
     /// Fetch BG attribute byte.
@@ -808,6 +948,10 @@ impl Ppu {
             };
 
             if idx < spr_count {
+                #[cfg(feature = "hd-pack")]
+                if let Some(hd) = &mut self.hd {
+                    hd.fetch_sprite_tile(&self.bus, idx, tile_addr);
+                }
                 let sprite = &mut self.sprites[idx];
                 sprite.x = x;
                 sprite.y = y;
@@ -829,6 +973,62 @@ impl Ppu {
         }
     }
 
+    /// Loads any sprites in range of the current scanline that were dropped by sprite evaluation
+    /// because the first eight slots were already filled. CHR is peeked rather than read so mapper
+    /// behavior, like MMC3 IRQs, is unaffected.
+    fn load_extra_sprites(&mut self) {
+        if self.spr_count < self.sprites.len() {
+            return;
+        }
+
+        let scanline = self.scanline;
+        let height = self.ctrl.spr_height;
+        let in_range = self
+            .oamdata
+            .chunks_exact(4)
+            .filter(|oam| {
+                let y = u32::from(oam[0]);
+                (y..y + height).contains(&scanline)
+            })
+            .skip(self.sprites.len());
+        for oam in in_range {
+            let [y, tile_index, attr, x] = [oam[0], oam[1], oam[2], oam[3]];
+            let x = u32::from(x);
+            let y = u32::from(y);
+            let tile_index = u16::from(tile_index);
+            let flip_vertical = (attr & 0x80) == 0x80;
+
+            let mut line_offset = scanline - y;
+            if flip_vertical {
+                line_offset = height - 1 - line_offset;
+            }
+            let tile_addr = if height == 16 {
+                let sprite_select = (tile_index & 0x01) * 0x1000;
+                if line_offset >= 8 {
+                    line_offset += 8;
+                }
+                sprite_select | ((tile_index & 0xFE) << 4) | line_offset as u16
+            } else {
+                self.ctrl.spr_select | (tile_index << 4) | line_offset as u16
+            };
+
+            self.extra_sprites.push(Sprite {
+                x,
+                y,
+                tile_addr,
+                tile_lo: self.bus.peek_chr(tile_addr),
+                tile_hi: self.bus.peek_chr(tile_addr + 8),
+                palette: ((attr & 0x03) << 2) | 0x10,
+                bg_priority: (attr & 0x20) == 0x20,
+                flip_horizontal: (attr & 0x40) == 0x40,
+                flip_vertical,
+            });
+            for spr in self.spr_present.iter_mut().skip(x as usize).take(8) {
+                *spr = true;
+            }
+        }
+    }
+
     // http://wiki.nesdev.com/w/index.php/PPU_OAM
     fn fetch_sprites(&mut self) {
         // OAMADDR set to $00 on prerender and visible scanlines
@@ -865,7 +1065,12 @@ impl Ppu {
 
         let left_clip_spr = x < 8 && !show_left_spr;
         if show_spr && !left_clip_spr && self.spr_present[x as usize] {
-            for (i, sprite) in self.sprites.iter().take(self.spr_count).enumerate() {
+            let sprites = self
+                .sprites
+                .iter()
+                .take(self.spr_count)
+                .chain(self.extra_sprites.iter());
+            for (i, sprite) in sprites.enumerate() {
                 if x < sprite.x {
                     continue;
                 }
@@ -892,7 +1097,19 @@ impl Ppu {
                             self.status.set_spr_zero_hit(true);
                         }
 
-                        if bg_color == 0 || !sprite.bg_priority {
+                        if !self.layers.show_spr() {
+                            break;
+                        }
+                        if bg_color == 0 || !sprite.bg_priority || !self.layers.show_bg() {
+                            #[cfg(feature = "hd-pack")]
+                            if let Some(hd) = &mut self.hd {
+                                hd.select_sprite(
+                                    i,
+                                    shift,
+                                    sprite.flip_horizontal,
+                                    sprite.flip_vertical,
+                                );
+                            }
                             return sprite.palette + spr_color;
                         }
                         break;
@@ -900,7 +1117,14 @@ impl Ppu {
                 }
             }
         }
-        if (fine_x + ((x & 0x07) as u16)) < 8 {
+        let show_bg = show_bg && self.layers.show_bg();
+        let bg_color = if show_bg { bg_color } else { 0 };
+        let offset = fine_x + ((x & 0x07) as u16);
+        #[cfg(feature = "hd-pack")]
+        if let Some(hd) = &mut self.hd {
+            hd.select_bg(show_bg && !left_clip_bg, offset);
+        }
+        if offset < 8 {
             self.prev_palette + bg_color
         } else {
             self.curr_palette + bg_color
@@ -916,9 +1140,23 @@ impl Ppu {
         let color =
             if self.mask.rendering_enabled || (addr & Self::PALETTE_START) != Self::PALETTE_START {
                 let palette = u16::from(self.pixel_palette());
+                #[cfg(feature = "hd-pack")]
+                if let Some(hd) = &mut self.hd {
+                    // Replacement images can't be greyscaled or emphasized, so fall back to the
+                    // original pixel to keep those effects visible
+                    if self.mask.grayscale == 0x3F && self.mask.emphasis == 0 {
+                        hd.render_pixel(&self.bus, x, y, palette as u8);
+                    } else {
+                        hd.clear_pixel(x, y);
+                    }
+                }
                 self.bus
                     .read_palette(Self::PALETTE_START | ((palette & 0x03 > 0) as u16 * palette))
             } else {
+                #[cfg(feature = "hd-pack")]
+                if let Some(hd) = &mut self.hd {
+                    hd.clear_pixel(x, y);
+                }
                 self.bus.read_palette(addr)
             };
 
@@ -1012,6 +1250,10 @@ impl Ppu {
                 if spr_fetch_cycle {
                     if cycle == Self::SPR_FETCH_START {
                         self.spr_present.fill(false);
+                        self.extra_sprites.clear();
+                        if self.unlimited_sprites && visible_scanline {
+                            self.load_extra_sprites();
+                        }
                     }
                     self.fetch_sprites();
                 } else if spr_dummy_cycle {
@@ -1290,8 +1532,8 @@ impl Registers for Ppu {
             // Since we're reading from > $3EFF subtract $1000 to fill
             // buffer with nametable mirror data
             self.vram_buffer = self.bus.read(addr - 0x1000);
-            // Hi 2 bits of palette should be open bus
-            val | (self.open_bus & 0xC0)
+            // Hi 2 bits of palette should be open bus and greyscale applies to palette reads
+            (val & self.mask.grayscale) | (self.open_bus & 0xC0)
         };
 
         self.open_bus = val;
@@ -1316,8 +1558,8 @@ impl Registers for Ppu {
         if addr < Self::PALETTE_START {
             self.vram_buffer
         } else {
-            // Hi 2 bits of palette should be open bus
-            self.bus.peek(addr) | (self.open_bus & 0xC0)
+            // Hi 2 bits of palette should be open bus and greyscale applies to palette reads
+            (self.bus.peek(addr) & self.mask.grayscale) | (self.open_bus & 0xC0)
         }
     }
 
@@ -1450,6 +1692,7 @@ impl Reset for Ppu {
         self.spr_count = 0;
         self.sprites = [Sprite::new(); 8];
         self.spr_present.fill(false);
+        self.extra_sprites.clear();
         self.open_bus = 0x00;
         self.bus.reset(kind);
     }
@@ -1509,6 +1752,7 @@ mod tests {
     use crate::{
         cart::Cart,
         mapper::{Mmc1Revision, Sxrom},
+        mem::Memory,
     };
 
     #[test]
@@ -1577,6 +1821,28 @@ mod tests {
         assert_eq!(ppu.read_data(), 0x88);
     }
 
+    #[test]
+    fn nametable_changes() {
+        let mut ppu = Ppu::default();
+        ppu.bus.load_chr_ram(vec![0x00; 0x2000].into());
+        let mut changes = vec![TileChange::None; 64 * 60];
+        let prev = ppu.clone();
+        ppu.load_nametable_changes(&prev, &mut changes);
+        assert!(changes.iter().all(|change| *change == TileChange::None));
+
+        // Tile index at row 1, col 2 of the first nametable
+        ppu.bus.write(0x2022, 0x01);
+        ppu.load_nametable_changes(&prev, &mut changes);
+        assert_eq!(changes[64 + 2], TileChange::Cpu);
+        assert_eq!(changes[64 + 3], TileChange::None);
+
+        // Swap out all pattern data, like a CHR bank switch
+        let prev = ppu.clone();
+        ppu.bus.load_chr_ram(vec![0xFF; 0x2000].into());
+        ppu.load_nametable_changes(&prev, &mut changes);
+        assert!(changes.iter().all(|change| *change == TileChange::Mapper));
+    }
+
     // Horizontal: https://wiki.nesdev.com/w/index.php/Mirroring
     //   [0x2000 A ] [0x2400 a ]
     //   [0x2800 B ] [0x2C00 b ]
@@ -1721,4 +1987,101 @@ mod tests {
         ppu.write_oamaddr(0x11);
         assert_eq!(ppu.read_oamdata(), 0x77);
     }
+
+    #[test]
+    fn unlimited_sprites() {
+        let mut ppu = Ppu::default();
+        ppu.write_mask(0x18); // Show background and sprites
+                              // Place nine sprites on the same scanlines
+        for (i, oam) in ppu.oamdata.chunks_exact_mut(4).take(9).enumerate() {
+            oam.copy_from_slice(&[0x10, 0x00, 0x00, (i * 16) as u8]);
+        }
+
+        let clock_to_sprite_fetch = |ppu: &mut Ppu| {
+            while !(ppu.scanline == 0x12 && ppu.cycle > Ppu::SPR_FETCH_START) {
+                ppu.clock();
+            }
+        };
+
+        clock_to_sprite_fetch(&mut ppu);
+        assert_eq!(ppu.spr_count, 8);
+        assert!(ppu.status.spr_overflow);
+        assert!(ppu.extra_sprites.is_empty());
+
+        ppu.reset(ResetKind::Soft);
+        ppu.unlimited_sprites = true;
+        ppu.write_mask(0x18);
+        clock_to_sprite_fetch(&mut ppu);
+        assert_eq!(ppu.spr_count, 8);
+        assert!(ppu.status.spr_overflow);
+        assert_eq!(ppu.extra_sprites.len(), 1);
+        assert_eq!(ppu.extra_sprites[0].x, 128);
+        assert!(ppu.spr_present[128]);
+    }
+
+    #[test]
+    fn palette_read_grayscale() {
+        let mut ppu = Ppu::default();
+        ppu.bus.write(0x3F01, 0x16);
+        ppu.write_mask(0x01); // Grayscale
+
+        ppu.write_addr(0x3F);
+        ppu.write_addr(0x01);
+        // PPU writes to $2006 are delayed by 2 PPU clocks
+        ppu.clock();
+        ppu.clock();
+        assert_eq!(ppu.peek_data(), 0x10);
+        assert_eq!(ppu.read_data(), 0x10);
+    }
+
+    #[test]
+    fn hidden_layers_keep_sprite_zero_hit() {
+        let mut ppu = Ppu {
+            layers: PpuLayers::Background,
+            ..Default::default()
+        };
+        // Show background and sprites, including the left 8 pixels
+        ppu.write_mask(0x1E);
+        // Opaque background and sprite tiles
+        ppu.bus.load_chr_ram(vec![0xFF; 0x2000].into());
+        ppu.oamdata[..4].copy_from_slice(&[0x10, 0x00, 0x00, 0x10]);
+
+        while !ppu.status.spr_zero_hit && ppu.frame_number() < 2 {
+            ppu.clock();
+        }
+        assert!(ppu.status.spr_zero_hit);
+    }
+
+    #[test]
+    fn export_palette() {
+        let mut ppu = Ppu::default();
+        ppu.bus.poke_palette(0x3F01, 0x16);
+        let pal = ppu.export_palette();
+        assert_eq!(pal.len(), 32 * 3);
+        assert_eq!(pal[3..6], Ppu::NTSC_PALETTE[0x16 * 3..0x16 * 3 + 3]);
+    }
+
+    #[test]
+    fn pattern_table_indices() {
+        let mut ppu = Ppu::default();
+        let indices = (0..256 * 128)
+            .map(|i| (i % 7 % 4) as u8)
+            .collect::<Vec<_>>();
+        assert!(
+            !ppu.write_pattern_table_indices(&indices),
+            "CHR-ROM is read-only"
+        );
+
+        ppu.bus.load_chr_ram(Memory::with_size(0x2000));
+        assert!(ppu.write_pattern_table_indices(&indices));
+        let mut loaded = vec![0x00; indices.len()];
+        ppu.load_pattern_table_indices(&mut loaded);
+        assert_eq!(loaded, indices);
+
+        // Tile 1 row 0 starts 8 pixels in
+        let tile_lo = ppu.bus.peek_chr(0x0010);
+        let tile_hi = ppu.bus.peek_chr(0x0018);
+        let first = ((tile_hi >> 7) << 1) | (tile_lo >> 7);
+        assert_eq!(first, indices[8]);
+    }
 }
@@ -2,11 +2,12 @@
 
 use crate::{
     common::{NesRegion, Regional, Reset, ResetKind},
-    mapper::{Mapped, MappedRead, MappedWrite, Mapper, MemMap},
+    mapper::{Mapped, MappedRead, MappedWrite, Mapper, MapperIrqEvent, MapperWrite, MemMap},
     mem::{Mem, Memory},
     ppu::{Mirroring, Ppu},
 };
 use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 use tracing::error;
 
 pub trait PpuAddr {
@@ -34,8 +35,20 @@ pub struct Bus {
     pub chr_rom: Memory,
     pub ciram: Memory, // $2007 PPUDATA
     pub palette: [u8; Self::PALETTE_SIZE],
+    /// Palette entries that ignore writes, one bit per entry, for debugging.
+    #[serde(skip)]
+    pub palette_locks: u32,
     pub exram: Memory,
     pub open_bus: u8,
+    /// Whether to record [`Bus::mapper_writes`], only enabled while they're being inspected.
+    #[serde(skip)]
+    pub log_mapper_writes: bool,
+    /// Recent CPU writes to mapper registers, oldest first, for debugging.
+    #[serde(skip)]
+    pub mapper_writes: VecDeque<MapperWrite>,
+    /// Recent mapper IRQ assertions, oldest first, for debugging.
+    #[serde(skip)]
+    pub mapper_irqs: VecDeque<MapperIrqEvent>,
 }
 
 impl Default for Bus {
@@ -47,16 +60,22 @@ impl Default for Bus {
 impl Bus {
     pub const VRAM_SIZE: usize = 0x0800; // Two 1k Nametables
     pub const PALETTE_SIZE: usize = 32; // 32 possible colors at a time
+    pub const MAPPER_WRITES_LEN: usize = 64;
+    pub const MAPPER_IRQS_LEN: usize = 256;
 
     pub fn new() -> Self {
         Self {
             mapper: Mapper::none(),
             ciram: Memory::with_size(Self::VRAM_SIZE),
             palette: [0x00; Self::PALETTE_SIZE],
+            palette_locks: 0,
             chr_ram: Memory::new(),
             chr_rom: Memory::new(),
             exram: Memory::new(),
             open_bus: 0x00,
+            log_mapper_writes: false,
+            mapper_writes: VecDeque::new(),
+            mapper_irqs: VecDeque::new(),
         }
     }
 
@@ -76,6 +95,22 @@ impl Bus {
         self.exram = ex_ram;
     }
 
+    /// Record a CPU write to a mapper register, discarding the oldest if full.
+    pub fn log_mapper_write(&mut self, write: MapperWrite) {
+        if self.mapper_writes.len() == Self::MAPPER_WRITES_LEN {
+            self.mapper_writes.pop_front();
+        }
+        self.mapper_writes.push_back(write);
+    }
+
+    /// Record a mapper IRQ assertion, discarding the oldest if full.
+    pub fn log_mapper_irq(&mut self, irq: MapperIrqEvent) {
+        if self.mapper_irqs.len() == Self::MAPPER_IRQS_LEN {
+            self.mapper_irqs.pop_front();
+        }
+        self.mapper_irqs.push_back(irq);
+    }
+
     // Maps addresses to nametable pages based on mirroring mode
     //
     // Vram:            [ A ] [ B ]
@@ -180,6 +215,19 @@ impl Bus {
         val
     }
 
+    /// Write CHR-RAM at the PPU address `addr` as currently banked, for editing tiles. Does
+    /// nothing if the cart uses CHR-ROM.
+    pub fn poke_chr(&mut self, addr: u16, val: u8) {
+        let addr = if let MappedRead::Chr(addr) = self.mapper.map_peek(addr) {
+            addr
+        } else {
+            addr.into()
+        };
+        if let Some(v) = self.chr_ram.get_mut(addr) {
+            *v = val;
+        }
+    }
+
     pub fn peek_chr(&self, addr: u16) -> u8 {
         let addr = if let MappedRead::Chr(addr) = self.mapper.map_peek(addr) {
             addr
@@ -209,6 +257,30 @@ impl Bus {
             .copied()
             .unwrap_or(0)
     }
+
+    /// Write a palette entry, even if locked.
+    pub fn poke_palette(&mut self, addr: u16, val: u8) {
+        let addr = self.palette_mirror(addr);
+        if let Some(v) = self.palette.get_mut(addr) {
+            *v = val;
+        }
+    }
+
+    /// Whether writes to the palette entry at `addr` are ignored.
+    pub const fn palette_locked(&self, addr: u16) -> bool {
+        self.palette_locks & (1 << self.palette_mirror(addr)) != 0
+    }
+
+    /// Ignore writes to the palette entry at `addr`, to try out colors without the game
+    /// changing them back.
+    pub fn lock_palette(&mut self, addr: u16, locked: bool) {
+        let mask = 1 << self.palette_mirror(addr);
+        if locked {
+            self.palette_locks |= mask;
+        } else {
+            self.palette_locks &= !mask;
+        }
+    }
 }
 
 impl Mem for Bus {
@@ -271,9 +343,8 @@ impl Mem for Bus {
                 MappedWrite::None => (),
             },
             0x3F00..=0x3FFF => {
-                let addr = self.palette_mirror(addr);
-                if let Some(v) = self.palette.get_mut(addr) {
-                    *v = val;
+                if !self.palette_locked(addr) {
+                    self.poke_palette(addr, val);
                 }
             }
             _ => error!("unexpected PPU memory access at ${:04X}", addr),
@@ -367,4 +438,28 @@ mod tests {
         assert_eq!(Bus::ciram_mirror(0x2C05, Mirroring::SingleScreenB), 0x0405);
         assert_eq!(Bus::ciram_mirror(0x2FFF, Mirroring::SingleScreenB), 0x07FF);
     }
+
+    #[test]
+    fn palette_lock() {
+        let mut bus = Bus::new();
+        bus.write(0x3F01, 0x16);
+        bus.lock_palette(0x3F01, true);
+        assert!(bus.palette_locked(0x3F01));
+        bus.write(0x3F01, 0x2A);
+        assert_eq!(
+            bus.peek_palette(0x3F01),
+            0x16,
+            "locked entry ignores writes"
+        );
+        bus.poke_palette(0x3F01, 0x2A);
+        assert_eq!(bus.peek_palette(0x3F01), 0x2A, "poke ignores lock");
+
+        // $3F10 mirrors $3F00
+        bus.lock_palette(0x3F10, true);
+        assert!(bus.palette_locked(0x3F00));
+        bus.lock_palette(0x3F00, false);
+        assert!(!bus.palette_locked(0x3F10));
+        bus.write(0x3F10, 0x0F);
+        assert_eq!(bus.peek_palette(0x3F00), 0x0F);
+    }
 }
@@ -0,0 +1,551 @@
+//! HD pack support, replacing CHR tiles with high-resolution images as they're rendered.
+//!
+//! Supports the core of the [Mesen HD pack format](https://www.mesen.ca/docs/hdpacks.html): a
+//! `hires.txt` definition file with `<ver>`, `<scale>`, `<img>` and `<tile>` entries. Conditions,
+//! backgrounds, audio replacements and other tags are ignored.
+
+use crate::{
+    mapper::{MappedRead, MemMap},
+    ppu::{bus::Bus, Ppu},
+};
+use image::RgbaImage;
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use thiserror::Error;
+use tracing::warn;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Error, Debug)]
+#[must_use]
+pub enum Error {
+    #[error("failed to read hd pack {path:?}: {source:?}")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("failed to load hd pack image {path:?}: {source:?}")]
+    Image {
+        path: PathBuf,
+        source: image::ImageError,
+    },
+    #[error("invalid hd pack definition on line {line}: {reason}")]
+    InvalidDefinition { line: usize, reason: &'static str },
+}
+
+/// Identifies the CHR data of a tile.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+enum TileId {
+    /// Index of a tile in CHR-ROM, i.e. its absolute address divided by 16.
+    Index(u32),
+    /// Raw tile data, used by games with CHR-RAM.
+    Data([u8; 16]),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+struct TileKey {
+    id: TileId,
+    /// The four palette colors the tile is drawn with, packed most significant first.
+    palette: u32,
+}
+
+/// Location of a replacement tile within one of the [`HdPack`] images.
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct Replacement {
+    image: usize,
+    x: u32,
+    y: u32,
+    brightness: f32,
+}
+
+/// A set of high-resolution tile replacements loaded from a Mesen HD pack.
+#[derive(Clone)]
+#[must_use]
+pub struct HdPack {
+    scale: u32,
+    images: Vec<RgbaImage>,
+    tiles: HashMap<TileKey, Replacement>,
+}
+
+impl std::fmt::Debug for HdPack {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HdPack")
+            .field("scale", &self.scale)
+            .field("images_len", &self.images.len())
+            .field("tiles_len", &self.tiles.len())
+            .finish()
+    }
+}
+
+impl HdPack {
+    /// Name of the definition file in an HD pack directory.
+    pub const DEFINITION_FILE: &'static str = "hires.txt";
+
+    /// Load an HD pack from a directory containing a `hires.txt` definition file and the images
+    /// it references.
+    ///
+    /// # Errors
+    ///
+    /// If the definition file or any of its images fail to load, or the definition is invalid,
+    /// then an error is returned.
+    pub fn load(dir: impl AsRef<Path>) -> Result<Self> {
+        let dir = dir.as_ref();
+        let path = dir.join(Self::DEFINITION_FILE);
+        let definition =
+            std::fs::read_to_string(&path).map_err(|source| Error::Io { path, source })?;
+        Self::parse(&definition, |filename| {
+            let path = dir.join(filename);
+            image::open(&path)
+                .map(|image| image.into_rgba8())
+                .map_err(|source| Error::Image { path, source })
+        })
+    }
+
+    /// Parse an HD pack definition, calling `load_image` with the filename of each `<img>` entry.
+    ///
+    /// # Errors
+    ///
+    /// If `load_image` fails or the definition is invalid, then an error is returned.
+    pub fn parse(
+        definition: &str,
+        mut load_image: impl FnMut(&str) -> Result<RgbaImage>,
+    ) -> Result<Self> {
+        let mut pack = Self {
+            scale: 1,
+            images: Vec::new(),
+            tiles: HashMap::new(),
+        };
+        let mut ignored = HashSet::new();
+
+        for (index, line) in definition.lines().enumerate() {
+            let invalid = |reason| Error::InvalidDefinition {
+                line: index + 1,
+                reason,
+            };
+            let line = line.trim();
+            let Some(entry) = line.strip_prefix('<') else {
+                // Conditional entries are prefixed with `[conditions]`
+                if line.starts_with('[') {
+                    ignored.insert("[condition]");
+                }
+                continue;
+            };
+            let Some((tag, value)) = entry.split_once('>') else {
+                return Err(invalid("expected `>`"));
+            };
+            let value = value.trim();
+            match tag {
+                "ver" => (),
+                "scale" => {
+                    pack.scale = value
+                        .parse()
+                        .ok()
+                        .filter(|scale| *scale > 0)
+                        .ok_or_else(|| invalid("invalid scale"))?;
+                }
+                "img" => pack.images.push(load_image(value)?),
+                "tile" => {
+                    let (key, replacement) = pack.parse_tile(value).map_err(invalid)?;
+                    // Mesen uses the first matching entry
+                    pack.tiles.entry(key).or_insert(replacement);
+                }
+                _ => {
+                    ignored.insert(tag);
+                }
+            }
+        }
+
+        if !ignored.is_empty() {
+            warn!("ignoring unsupported hd pack entries: {ignored:?}");
+        }
+
+        Ok(pack)
+    }
+
+    /// Parse a `<tile>` entry of the form `image,tile,palette,x,y[,brightness[,default]]`.
+    fn parse_tile(&self, value: &str) -> std::result::Result<(TileKey, Replacement), &'static str> {
+        let mut fields = value.split(',').map(str::trim);
+        let mut next = |name| fields.next().filter(|field| !field.is_empty()).ok_or(name);
+
+        let image = next("missing image index")?
+            .parse::<usize>()
+            .map_err(|_| "invalid image index")?;
+        let tile = next("missing tile data")?;
+        let id = if tile.len() == 32 {
+            let mut data = [0x00; 16];
+            for (byte, hex) in data.iter_mut().zip(tile.as_bytes().chunks_exact(2)) {
+                *byte = std::str::from_utf8(hex)
+                    .ok()
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                    .ok_or("invalid tile data")?;
+            }
+            TileId::Data(data)
+        } else {
+            TileId::Index(tile.parse().map_err(|_| "invalid tile index")?)
+        };
+        let palette =
+            u32::from_str_radix(next("missing palette")?, 16).map_err(|_| "invalid palette")?;
+        let x = next("missing x")?.parse().map_err(|_| "invalid x")?;
+        let y = next("missing y")?.parse().map_err(|_| "invalid y")?;
+        let brightness = match fields.next() {
+            Some(brightness) => brightness.parse().map_err(|_| "invalid brightness")?,
+            None => 1.0,
+        };
+
+        let size = 8 * self.scale;
+        let img = self.images.get(image).ok_or("image index out of range")?;
+        if x + size > img.width() || y + size > img.height() {
+            return Err("tile position out of image bounds");
+        }
+
+        Ok((
+            TileKey { id, palette },
+            Replacement {
+                image,
+                x,
+                y,
+                brightness,
+            },
+        ))
+    }
+
+    /// Output scale of the HD pack relative to the native NES resolution.
+    #[must_use]
+    pub const fn scale(&self) -> u32 {
+        self.scale
+    }
+
+    fn replacement(&self, id: TileId, palette: u32) -> Option<Replacement> {
+        self.tiles.get(&TileKey { id, palette }).copied()
+    }
+}
+
+/// A tile fetched by the PPU. The CHR data is resolved when fetched, since mappers may switch
+/// banks before it's drawn.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct FetchedTile {
+    id: TileId,
+    row: u8,
+}
+
+impl FetchedTile {
+    const NONE: Self = Self {
+        id: TileId::Index(u32::MAX),
+        row: 0,
+    };
+
+    fn fetch(bus: &Bus, addr: u16) -> Self {
+        let base = addr & !0x0F;
+        let id = if bus.chr_ram.is_empty() {
+            match bus.mapper.map_peek(base) {
+                MappedRead::Chr(addr) => TileId::Index((addr / 16) as u32),
+                _ => TileId::Index(u32::from(base / 16)),
+            }
+        } else {
+            let mut data = [0x00; 16];
+            for (offset, byte) in (0..).zip(data.iter_mut()) {
+                *byte = bus.peek_chr(base + offset);
+            }
+            TileId::Data(data)
+        };
+        Self {
+            id,
+            row: (addr & 0x07) as u8,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+struct Selected {
+    tile: FetchedTile,
+    col: u8,
+    flip_horizontal: bool,
+    flip_vertical: bool,
+}
+
+#[derive(Default, Debug, Copy, Clone)]
+struct HdPixel {
+    replacement: Option<Replacement>,
+    col: u8,
+    row: u8,
+    flip_horizontal: bool,
+    flip_vertical: bool,
+}
+
+/// A frame rendered with [`HdPack`] replacements as RGBA pixels.
+#[derive(Debug, Copy, Clone)]
+#[must_use]
+pub struct HdFrame<'a> {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: &'a [u8],
+}
+
+/// Tracks which tile each pixel is drawn from while the PPU renders, in order to draw an
+/// [`HdPack`] replacement over it.
+#[derive(Debug, Clone)]
+#[must_use]
+pub struct HdRenderer {
+    pack: Arc<HdPack>,
+    prev_bg_tile: FetchedTile,
+    curr_bg_tile: FetchedTile,
+    next_bg_tile: FetchedTile,
+    sprite_tiles: [FetchedTile; 8],
+    selected: Option<Selected>,
+    pixels: Vec<HdPixel>,
+    output: Vec<u8>,
+}
+
+impl HdRenderer {
+    pub fn new(pack: Arc<HdPack>) -> Self {
+        let scale = pack.scale as usize;
+        Self {
+            pack,
+            prev_bg_tile: FetchedTile::NONE,
+            curr_bg_tile: FetchedTile::NONE,
+            next_bg_tile: FetchedTile::NONE,
+            sprite_tiles: [FetchedTile::NONE; 8],
+            selected: None,
+            pixels: vec![HdPixel::default(); Ppu::SIZE],
+            output: vec![0x00; Ppu::SIZE * scale * scale * 4],
+        }
+    }
+
+    pub const fn pack(&self) -> &Arc<HdPack> {
+        &self.pack
+    }
+
+    /// Called with each background tile fetch, mirroring how the PPU shifts background palettes.
+    pub(crate) fn fetch_bg_tile(&mut self, bus: &Bus, addr: u16) {
+        self.prev_bg_tile = self.curr_bg_tile;
+        self.curr_bg_tile = self.next_bg_tile;
+        self.next_bg_tile = FetchedTile::fetch(bus, addr);
+    }
+
+    pub(crate) fn fetch_sprite_tile(&mut self, bus: &Bus, index: usize, addr: u16) {
+        self.sprite_tiles[index] = FetchedTile::fetch(bus, addr);
+    }
+
+    /// Select the background tile for the next pixel, `offset` pixels into the previous tile.
+    pub(crate) fn select_bg(&mut self, visible: bool, offset: u16) {
+        self.selected = visible.then_some(Selected {
+            tile: if offset < 8 {
+                self.prev_bg_tile
+            } else {
+                self.curr_bg_tile
+            },
+            col: (offset & 0x07) as u8,
+            flip_horizontal: false,
+            flip_vertical: false,
+        });
+    }
+
+    /// Select a sprite tile for the next pixel, `shift` pixels from the sprite's left edge. Sprites
+    /// beyond the first eight on a scanline have no fetched tile and are drawn unreplaced.
+    pub(crate) fn select_sprite(
+        &mut self,
+        index: usize,
+        shift: u32,
+        flip_horizontal: bool,
+        flip_vertical: bool,
+    ) {
+        let shift = (shift & 0x07) as u8;
+        self.selected = self.sprite_tiles.get(index).map(|&tile| Selected {
+            tile,
+            col: if flip_horizontal { 7 - shift } else { shift },
+            flip_horizontal,
+            flip_vertical,
+        });
+    }
+
+    /// Record the selected tile for the pixel at `x`, `y` drawn with the `palette` index.
+    pub(crate) fn render_pixel(&mut self, bus: &Bus, x: u32, y: u32, palette: u8) {
+        let pixel = self
+            .selected
+            .take()
+            .map_or_else(HdPixel::default, |selected| {
+                let base = Ppu::PALETTE_START | u16::from(palette & 0x1C);
+                let palette = (u32::from(bus.peek_palette(Ppu::PALETTE_START)) << 24)
+                    | (u32::from(bus.peek_palette(base + 1)) << 16)
+                    | (u32::from(bus.peek_palette(base + 2)) << 8)
+                    | u32::from(bus.peek_palette(base + 3));
+                HdPixel {
+                    replacement: self.pack.replacement(selected.tile.id, palette),
+                    col: selected.col,
+                    row: selected.tile.row,
+                    flip_horizontal: selected.flip_horizontal,
+                    flip_vertical: selected.flip_vertical,
+                }
+            });
+        self.pixels[(x + (y << 8)) as usize] = pixel;
+    }
+
+    pub(crate) fn clear_pixel(&mut self, x: u32, y: u32) {
+        self.selected = None;
+        self.pixels[(x + (y << 8)) as usize] = HdPixel::default();
+    }
+
+    /// Render `buffer`, the last frame output by the PPU, at the [`HdPack`] scale with any tile
+    /// replacements applied.
+    pub fn frame(&mut self, buffer: &[u16]) -> HdFrame<'_> {
+        #[cfg(feature = "profiling")]
+        puffin::profile_function!();
+
+        let scale = self.pack.scale;
+        let width = Ppu::WIDTH * scale;
+        let row_len = width as usize * 4;
+        let scale = scale as usize;
+
+        for (i, (color, pixel)) in buffer.iter().zip(&self.pixels).enumerate() {
+            let (x, y) = (i % Ppu::WIDTH as usize, i / Ppu::WIDTH as usize);
+            let index = (*color as usize) * 3;
+            let nes_color = [
+                Ppu::NTSC_PALETTE[index],
+                Ppu::NTSC_PALETTE[index + 1],
+                Ppu::NTSC_PALETTE[index + 2],
+                0xFF,
+            ];
+            let image = pixel
+                .replacement
+                .map(|replacement| (replacement, &self.pack.images[replacement.image]));
+
+            for sy in 0..scale {
+                let start = (y * scale + sy) * row_len + x * scale * 4;
+                let output = &mut self.output[start..start + scale * 4];
+                for (sx, output) in output.chunks_exact_mut(4).enumerate() {
+                    let color = image.and_then(|(replacement, image)| {
+                        let sx = if pixel.flip_horizontal {
+                            scale - 1 - sx
+                        } else {
+                            sx
+                        };
+                        let sy = if pixel.flip_vertical {
+                            scale - 1 - sy
+                        } else {
+                            sy
+                        };
+                        let src_x = replacement.x as usize + usize::from(pixel.col) * scale + sx;
+                        let src_y = replacement.y as usize + usize::from(pixel.row) * scale + sy;
+                        let [r, g, b, a] = image.get_pixel(src_x as u32, src_y as u32).0;
+                        // Transparent pixels show the original tile
+                        (a > 0).then(|| {
+                            let brightness = replacement.brightness;
+                            let adjust = |c: u8| (f32::from(c) * brightness).min(255.0) as u8;
+                            [adjust(r), adjust(g), adjust(b), 0xFF]
+                        })
+                    });
+                    output.copy_from_slice(&color.unwrap_or(nes_color));
+                }
+            }
+        }
+
+        HdFrame {
+            width,
+            height: Ppu::HEIGHT * self.pack.scale,
+            pixels: &self.output,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    const RED: Rgba<u8> = Rgba([0xFF, 0x00, 0x00, 0xFF]);
+
+    fn load_image(filename: &str) -> Result<RgbaImage> {
+        assert_eq!(filename, "tiles.png");
+        Ok(RgbaImage::from_pixel(32, 16, RED))
+    }
+
+    #[test]
+    fn parse_definition() {
+        let pack = HdPack::parse(
+            "<ver>106\n\
+            <scale>2\n\
+            <img>tiles.png\n\
+            <tile>0,42,0F161A30,0,0,1,N\n\
+            <tile>0,00112233445566778899AABBCCDDEEFF,0F000000,16,0\n\
+            [hmirror]<tile>0,43,0F161A30,0,0,1,N\n\
+            <background>bg.png,1\n",
+            load_image,
+        )
+        .expect("valid definition");
+
+        assert_eq!(pack.scale(), 2);
+        assert_eq!(pack.tiles.len(), 2);
+        assert_eq!(
+            pack.replacement(TileId::Index(42), 0x0F16_1A30),
+            Some(Replacement {
+                image: 0,
+                x: 0,
+                y: 0,
+                brightness: 1.0
+            })
+        );
+        let data = [
+            0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xAA, 0xBB, 0xCC, 0xDD,
+            0xEE, 0xFF,
+        ];
+        assert!(pack.replacement(TileId::Data(data), 0x0F00_0000).is_some());
+        assert!(pack.replacement(TileId::Index(42), 0x0F00_0000).is_none());
+        assert!(pack.replacement(TileId::Index(43), 0x0F16_1A30).is_none());
+    }
+
+    #[test]
+    fn parse_invalid_definition() {
+        let err = HdPack::parse("<scale>2\n<img>tiles.png\n<tile>0,1,0F,24,0", load_image)
+            .expect_err("tile out of bounds");
+        assert!(
+            matches!(err, Error::InvalidDefinition { line: 3, .. }),
+            "{err:?}"
+        );
+        let err = HdPack::parse("<scale>0", load_image).expect_err("invalid scale");
+        assert!(
+            matches!(err, Error::InvalidDefinition { line: 1, .. }),
+            "{err:?}"
+        );
+    }
+
+    #[test]
+    fn render_replacement() {
+        let pack = HdPack::parse(
+            "<scale>2\n<img>tiles.png\n<tile>0,1,00000000,0,0",
+            load_image,
+        )
+        .expect("valid definition");
+        let mut renderer = HdRenderer::new(Arc::new(pack));
+        let tile = FetchedTile {
+            id: TileId::Index(1),
+            row: 0,
+        };
+        renderer.next_bg_tile = tile;
+        renderer.fetch_bg_tile(&Bus::new(), 0x0000);
+        renderer.fetch_bg_tile(&Bus::new(), 0x0000);
+        assert_eq!(renderer.prev_bg_tile, tile);
+
+        renderer.select_bg(true, 0);
+        renderer.render_pixel(&Bus::new(), 0, 0, 0);
+        renderer.clear_pixel(1, 0);
+
+        let buffer = vec![0x00; Ppu::SIZE];
+        let frame = renderer.frame(&buffer);
+        assert_eq!((frame.width, frame.height), (512, 480));
+        // Replaced pixel is scaled 2x from the replacement image
+        for offset in [0, 4, 2048, 2052] {
+            assert_eq!(frame.pixels[offset..offset + 4], RED.0, "offset {offset}");
+        }
+        // Remaining pixels use the NES palette
+        let gray = [
+            Ppu::NTSC_PALETTE[0],
+            Ppu::NTSC_PALETTE[1],
+            Ppu::NTSC_PALETTE[2],
+            0xFF,
+        ];
+        assert_eq!(frame.pixels[8..12], gray);
+    }
+}
@@ -1,6 +1,7 @@
 //! Video output and filtering.
 
 use crate::ppu::Ppu;
+use cfg_if::cfg_if;
 use serde::{Deserialize, Serialize};
 use std::{
     f64::consts::PI,
@@ -9,6 +10,8 @@ use std::{
 };
 use thiserror::Error;
 
+pub mod upscale;
+
 #[derive(Error, Debug)]
 #[must_use]
 #[error("failed to parse `VideoFilter`")]
@@ -55,6 +58,11 @@ pub struct Frame(Vec<u8>);
 
 impl Frame {
     pub const SIZE: usize = Ppu::SIZE * 4;
+    /// How many pixels in each direction are averaged into a single thumbnail pixel.
+    pub const THUMBNAIL_SCALE: u32 = 4;
+    pub const THUMBNAIL_WIDTH: u32 = Ppu::WIDTH / Self::THUMBNAIL_SCALE;
+    pub const THUMBNAIL_HEIGHT: u32 = Ppu::HEIGHT / Self::THUMBNAIL_SCALE;
+    pub const THUMBNAIL_SIZE: usize = (Self::THUMBNAIL_WIDTH * Self::THUMBNAIL_HEIGHT * 4) as usize;
 
     /// Allocate a new frame for video output.
     pub fn new() -> Self {
@@ -65,6 +73,37 @@ impl Frame {
                 .collect(),
         )
     }
+
+    /// Integer scale of the frame relative to the NES resolution, which is larger than `1` when
+    /// filled from an HD pack frame instead of [`Video`].
+    #[must_use]
+    pub fn scale(&self) -> u32 {
+        let area = (self.0.len() / Self::SIZE).max(1);
+        (1..).find(|scale| scale * scale >= area).unwrap_or(1) as u32
+    }
+
+    /// Downscale a full RGBA `frame` into a `THUMBNAIL_WIDTH`x`THUMBNAIL_HEIGHT` RGBA thumbnail.
+    pub fn thumbnail(frame: &[u8]) -> Vec<u8> {
+        let scale = Self::THUMBNAIL_SCALE as usize;
+        let count = (scale * scale) as u32;
+        let mut thumbnail = Vec::with_capacity(Self::THUMBNAIL_SIZE);
+        for y in 0..Self::THUMBNAIL_HEIGHT as usize {
+            for x in 0..Self::THUMBNAIL_WIDTH as usize {
+                let mut sum = [0u32; 3];
+                for row in y * scale..(y + 1) * scale {
+                    let start = (row * Ppu::WIDTH as usize + x * scale) * 4;
+                    for pixel in frame[start..start + scale * 4].chunks_exact(4) {
+                        for (sum, color) in sum.iter_mut().zip(pixel) {
+                            *sum += u32::from(*color);
+                        }
+                    }
+                }
+                thumbnail.extend(sum.map(|sum| (sum / count) as u8));
+                thumbnail.push(255);
+            }
+        }
+        thumbnail
+    }
 }
 
 impl Default for Frame {
@@ -139,6 +178,51 @@ impl Video {
 
     /// Fills a fully rendered frame with RGB colors.
     pub fn decode_buffer(buffer: &[u16], output: &mut [u8]) {
+        Self::for_each_scanline(buffer, output, |_, buffer, output| {
+            Self::decode_scanline(buffer, output);
+        });
+    }
+
+    /// Applies the NTSC filter to the given video buffer.
+    pub fn apply_ntsc_filter(buffer: &[u16], frame_number: u32, output: &mut [u8]) {
+        // Ensure palette is generated before any scanlines are processed in parallel
+        let palette = NTSC_PALETTE.get_or_init(generate_ntsc_palette);
+        Self::for_each_scanline(buffer, output, |y, buffer, output| {
+            Self::ntsc_scanline(palette, y, frame_number, buffer, output);
+        });
+    }
+
+    /// Calls `f` with the scanline index, video buffer and output buffer for each scanline.
+    /// Scanlines are independent, so with the `parallel-video` feature enabled they are
+    /// processed across multiple threads.
+    fn for_each_scanline(
+        buffer: &[u16],
+        output: &mut [u8],
+        f: impl Fn(usize, &[u16], &mut [u8]) + Send + Sync,
+    ) {
+        const WIDTH: usize = Ppu::WIDTH as usize;
+
+        cfg_if! {
+            if #[cfg(feature = "parallel-video")] {
+                use rayon::prelude::*;
+
+                buffer
+                    .par_chunks_exact(WIDTH)
+                    .zip(output.par_chunks_exact_mut(4 * WIDTH))
+                    .enumerate()
+                    .for_each(|(y, (buffer, output))| f(y, buffer, output));
+            } else {
+                buffer
+                    .chunks_exact(WIDTH)
+                    .zip(output.chunks_exact_mut(4 * WIDTH))
+                    .enumerate()
+                    .for_each(|(y, (buffer, output))| f(y, buffer, output));
+            }
+        }
+    }
+
+    /// Fills a single scanline with RGB colors.
+    fn decode_scanline(buffer: &[u16], output: &mut [u8]) {
         for (color, pixels) in buffer.iter().zip(output.chunks_exact_mut(4)) {
             let index = (*color as usize) * 3;
             assert!(Ppu::NTSC_PALETTE.len() > index + 2);
@@ -149,25 +233,28 @@ impl Video {
         }
     }
 
-    /// Applies the NTSC filter to the given video buffer.
+    /// Applies the NTSC filter to a single scanline.
     ///
     /// Amazing implementation Bisqwit! Much faster than my original, but boy what a pain
     /// to translate it to Rust
     /// Source: <https://bisqwit.iki.fi/jutut/kuvat/programming_examples/nesemu1/nesemu1.cc>
     /// See also: <http://wiki.nesdev.com/w/index.php/NTSC_video>
-    pub fn apply_ntsc_filter(buffer: &[u16], frame_number: u32, output: &mut [u8]) {
+    fn ntsc_scanline(
+        palette: &[u32],
+        y: usize,
+        frame_number: u32,
+        buffer: &[u16],
+        output: &mut [u8],
+    ) {
+        let even_phase = if frame_number & 0x01 == 0x01 { 0 } else { 1 };
         let mut prev_color = 0;
-        for (idx, (color, pixels)) in buffer.iter().zip(output.chunks_exact_mut(4)).enumerate() {
-            let x = idx % 256;
+        for (x, (color, pixels)) in buffer.iter().zip(output.chunks_exact_mut(4)).enumerate() {
             let rgba = if x == 0 {
                 // Remove pixel 0 artifact from not having a valid previous pixel
                 0
             } else {
-                let y = idx / 256;
-                let even_phase = if frame_number & 0x01 == 0x01 { 0 } else { 1 };
                 let phase = (2 + y * 341 + x + even_phase) % 3;
-                NTSC_PALETTE.get_or_init(generate_ntsc_palette)
-                    [phase + ((prev_color & 0x3F) as usize) * 3 + (*color as usize) * 3 * 64]
+                palette[phase + ((prev_color & 0x3F) as usize) * 3 + (*color as usize) * 3 * 64]
             };
             prev_color = u32::from(*color);
             assert!(pixels.len() > 2);
@@ -0,0 +1,298 @@
+//! Pixel-art upscaling filters.
+//!
+//! These enlarge a rendered frame by an integer factor while keeping edges sharp and smoothing
+//! diagonals, as an alternative look to the NTSC filter. They're applied to the final RGBA frame,
+//! so they work best with [`VideoFilter::Pixellate`](super::VideoFilter::Pixellate).
+//!
+//! - `Scale2x`/`Scale3x` are the EPX/AdvanceMAME scalers by Andrea Mazzoleni.
+//! - `HQ2x`/`HQ3x` are the hqx scalers by Maxim Stepin, provided by the `hqx` crate. They're
+//!   only available with the `hqx` feature, as the crate is LGPL-2.1-or-later licensed. Without
+//!   it, they fall back to `Scale2x`/`Scale3x`.
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+#[must_use]
+#[error("failed to parse `UpscaleFilter`")]
+pub struct ParseUpscaleFilterError;
+
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[must_use]
+pub enum UpscaleFilter {
+    #[default]
+    None,
+    Scale2x,
+    Scale3x,
+    Hq2x,
+    Hq3x,
+    /// No longer available. Kept so existing configs load, and falls back to `Scale2x`.
+    Xbrz2x,
+    /// No longer available. Kept so existing configs load, and falls back to `Scale3x`.
+    Xbrz3x,
+}
+
+impl UpscaleFilter {
+    /// The filters available in this build.
+    pub const fn as_slice() -> &'static [Self] {
+        &[
+            Self::None,
+            Self::Scale2x,
+            Self::Scale3x,
+            #[cfg(feature = "hqx")]
+            Self::Hq2x,
+            #[cfg(feature = "hqx")]
+            Self::Hq3x,
+        ]
+    }
+
+    /// The factor the frame width and height are multiplied by.
+    pub const fn scale(&self) -> u32 {
+        match self {
+            Self::None => 1,
+            Self::Scale2x | Self::Hq2x | Self::Xbrz2x => 2,
+            Self::Scale3x | Self::Hq3x | Self::Xbrz3x => 3,
+        }
+    }
+}
+
+impl AsRef<str> for UpscaleFilter {
+    fn as_ref(&self) -> &str {
+        match self {
+            Self::None => "None",
+            Self::Scale2x => "Scale2x",
+            Self::Scale3x => "Scale3x",
+            Self::Hq2x => "HQ2x",
+            Self::Hq3x => "HQ3x",
+            Self::Xbrz2x => "xBRZ 2x",
+            Self::Xbrz3x => "xBRZ 3x",
+        }
+    }
+}
+
+impl std::fmt::Display for UpscaleFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_ref())
+    }
+}
+
+impl TryFrom<usize> for UpscaleFilter {
+    type Error = ParseUpscaleFilterError;
+
+    fn try_from(value: usize) -> Result<Self, Self::Error> {
+        Self::as_slice()
+            .get(value)
+            .copied()
+            .ok_or(ParseUpscaleFilterError)
+    }
+}
+
+/// Upscales RGBA frames, reusing buffers between frames.
+#[derive(Default, Debug, Clone)]
+#[must_use]
+pub struct Upscaler {
+    src: Vec<u32>,
+    dst: Vec<u32>,
+    output: Vec<u8>,
+}
+
+impl Upscaler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Upscale an RGBA `frame` of `width` x `height` pixels, returning a frame of
+    /// `width * scale` x `height * scale` pixels. `UpscaleFilter::None` returns `frame` as-is.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `frame` is smaller than `width * height` RGBA pixels.
+    pub fn upscale<'a>(
+        &'a mut self,
+        filter: UpscaleFilter,
+        frame: &'a [u8],
+        width: u32,
+        height: u32,
+    ) -> &'a [u8] {
+        if filter == UpscaleFilter::None {
+            return frame;
+        }
+
+        let (width, height) = (width as usize, height as usize);
+        let scale = filter.scale() as usize;
+        let frame = &frame[..width * height * 4];
+
+        self.src.clear();
+        self.src.extend(
+            frame
+                .chunks_exact(4)
+                .map(|p| u32::from_be_bytes([p[3], p[0], p[1], p[2]])),
+        );
+        self.dst.clear();
+        self.dst.resize(width * height * scale * scale, 0);
+
+        match filter {
+            UpscaleFilter::None => unreachable!("handled above"),
+            UpscaleFilter::Scale2x => scale2x(&self.src, &mut self.dst, width, height),
+            UpscaleFilter::Scale3x => scale3x(&self.src, &mut self.dst, width, height),
+            #[cfg(feature = "hqx")]
+            UpscaleFilter::Hq2x => hqx::hq2x(&self.src, &mut self.dst, width as u32, height as u32),
+            #[cfg(feature = "hqx")]
+            UpscaleFilter::Hq3x => hqx::hq3x(&self.src, &mut self.dst, width as u32, height as u32),
+            // Keep configs that selected HQx working in builds without it
+            #[cfg(not(feature = "hqx"))]
+            UpscaleFilter::Hq2x => scale2x(&self.src, &mut self.dst, width, height),
+            #[cfg(not(feature = "hqx"))]
+            UpscaleFilter::Hq3x => scale3x(&self.src, &mut self.dst, width, height),
+            UpscaleFilter::Xbrz2x => scale2x(&self.src, &mut self.dst, width, height),
+            UpscaleFilter::Xbrz3x => scale3x(&self.src, &mut self.dst, width, height),
+        }
+
+        self.output.clear();
+        self.output.extend(self.dst.iter().flat_map(|p| {
+            let [a, r, g, b] = p.to_be_bytes();
+            [r, g, b, a]
+        }));
+        &self.output
+    }
+}
+
+/// Get the pixel at `(x, y)`, clamping coordinates to the frame edges.
+#[inline]
+fn pixel(src: &[u32], width: usize, height: usize, x: isize, y: isize) -> u32 {
+    let x = x.clamp(0, width as isize - 1) as usize;
+    let y = y.clamp(0, height as isize - 1) as usize;
+    src[y * width + x]
+}
+
+/// Get the 3x3 neighborhood around `(x, y)` in row-major order.
+#[inline]
+fn kernel_3x3(src: &[u32], width: usize, height: usize, x: usize, y: usize) -> [u32; 9] {
+    let (x, y) = (x as isize, y as isize);
+    std::array::from_fn(|i| {
+        let (dx, dy) = ((i % 3) as isize - 1, (i / 3) as isize - 1);
+        pixel(src, width, height, x + dx, y + dy)
+    })
+}
+
+fn scale2x(src: &[u32], dst: &mut [u32], width: usize, height: usize) {
+    let dst_width = width * 2;
+    for y in 0..height {
+        for x in 0..width {
+            let [_, b, _, d, e, f, _, h, _] = kernel_3x3(src, width, height, x, y);
+            let (e0, e1, e2, e3) = if b != h && d != f {
+                (
+                    if d == b { d } else { e },
+                    if b == f { f } else { e },
+                    if d == h { d } else { e },
+                    if h == f { f } else { e },
+                )
+            } else {
+                (e, e, e, e)
+            };
+            let i = y * 2 * dst_width + x * 2;
+            dst[i] = e0;
+            dst[i + 1] = e1;
+            dst[i + dst_width] = e2;
+            dst[i + dst_width + 1] = e3;
+        }
+    }
+}
+
+fn scale3x(src: &[u32], dst: &mut [u32], width: usize, height: usize) {
+    let dst_width = width * 3;
+    for y in 0..height {
+        for x in 0..width {
+            let [a, b, c, d, e, f, g, h, i] = kernel_3x3(src, width, height, x, y);
+            let block = if b != h && d != f {
+                [
+                    if d == b { d } else { e },
+                    if (d == b && e != c) || (b == f && e != a) {
+                        b
+                    } else {
+                        e
+                    },
+                    if b == f { f } else { e },
+                    if (d == b && e != g) || (d == h && e != a) {
+                        d
+                    } else {
+                        e
+                    },
+                    e,
+                    if (b == f && e != i) || (h == f && e != c) {
+                        f
+                    } else {
+                        e
+                    },
+                    if d == h { d } else { e },
+                    if (d == h && e != i) || (h == f && e != g) {
+                        h
+                    } else {
+                        e
+                    },
+                    if h == f { f } else { e },
+                ]
+            } else {
+                [e; 9]
+            };
+            let start = y * 3 * dst_width + x * 3;
+            for (row, pixels) in block.chunks_exact(3).enumerate() {
+                let start = start + row * dst_width;
+                dst[start..start + 3].copy_from_slice(pixels);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WHITE: [u8; 4] = [0xFF, 0xFF, 0xFF, 0xFF];
+    const BLACK: [u8; 4] = [0x00, 0x00, 0x00, 0xFF];
+
+    fn frame(width: u32, height: u32, pixel: impl Fn(u32, u32) -> [u8; 4]) -> Vec<u8> {
+        (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .flat_map(|(x, y)| pixel(x, y))
+            .collect()
+    }
+
+    #[test]
+    fn output_size() {
+        let mut upscaler = Upscaler::new();
+        let input = frame(8, 6, |x, y| if (x + y) % 3 == 0 { WHITE } else { BLACK });
+        for &filter in UpscaleFilter::as_slice() {
+            let scale = filter.scale();
+            let output = upscaler.upscale(filter, &input, 8, 6);
+            assert_eq!(output.len() as u32, 8 * scale * 6 * scale * 4, "{filter}");
+        }
+    }
+
+    #[test]
+    fn solid_color_unchanged() {
+        let mut upscaler = Upscaler::new();
+        let color = [0x12, 0x34, 0x56, 0xFF];
+        let input = frame(4, 4, |_, _| color);
+        for &filter in UpscaleFilter::as_slice() {
+            let output = upscaler.upscale(filter, &input, 4, 4);
+            assert!(
+                output.chunks_exact(4).all(|p| p == color),
+                "{filter} changed a solid color"
+            );
+        }
+    }
+
+    #[test]
+    fn scale2x_smooths_diagonal() {
+        // A black diagonal staircase on white
+        let input = frame(4, 4, |x, y| if x <= y { BLACK } else { WHITE });
+        let mut upscaler = Upscaler::new();
+        let output = upscaler.upscale(UpscaleFilter::Scale2x, &input, 4, 4);
+        let at = |x: usize, y: usize| &output[(y * 8 + x) * 4..][..4];
+        // The top-right quarter of the first white pixel above the diagonal stays white while
+        // its bottom-left quarter is filled in black
+        assert_eq!(at(3, 0), WHITE);
+        assert_eq!(at(2, 1), BLACK);
+    }
+}
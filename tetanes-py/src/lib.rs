@@ -0,0 +1,230 @@
+//! Python bindings exposing the TetaNES [`ControlDeck`] as a gym-style environment for
+//! reinforcement learning.
+//!
+//! The environment runs headless with audio disabled. Each [`NesEnv::step`] applies a set of
+//! controller buttons for player one, emulates one or more frames and returns the resulting
+//! observation.
+//!
+//! ```python
+//! import tetanes
+//!
+//! env = tetanes.NesEnv("game.nes", frame_skip=4, max_frames=10_000)
+//! frame, ram = env.reset(seed=42)
+//! done = False
+//! while not done:
+//!     frame, ram, done = env.step(tetanes.NesEnv.RIGHT | tetanes.NesEnv.A)
+//! ```
+
+use pyo3::{
+    exceptions::{PyRuntimeError, PyValueError},
+    prelude::*,
+    types::PyBytes,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::path::PathBuf;
+use tetanes_core::{
+    control_deck::{self, Config, ControlDeck, HeadlessMode},
+    input::{JoypadBtnState, Player},
+    mem::{self, RamState},
+    ppu::Ppu,
+};
+
+/// Observation returned by [`NesEnv::reset`]: `(frame_buffer, ram)`.
+type Observation = (Option<Py<PyBytes>>, Py<PyBytes>);
+
+/// Result returned by [`NesEnv::step`]: `(frame_buffer, ram, done)`.
+type Step = (Option<Py<PyBytes>>, Py<PyBytes>, bool);
+
+fn to_py_err(err: control_deck::Error) -> PyErr {
+    PyRuntimeError::new_err(err.to_string())
+}
+
+/// Creates a fresh [`ControlDeck`] with the ROM loaded. Random RAM is only filled when memory is
+/// created, so a new deck is required for each episode to be reproducible.
+fn create_deck(cfg: &Config, name: &str, rom: &[u8]) -> PyResult<ControlDeck> {
+    let mut deck = ControlDeck::with_config(cfg.clone());
+    deck.load_rom(name, &mut &rom[..]).map_err(to_py_err)?;
+    Ok(deck)
+}
+
+/// A headless NES environment.
+///
+/// Observations are the RGBA frame buffer as `bytes` of length `WIDTH * HEIGHT * 4` (or `None`
+/// when created with `render=False`) and the 2KB of internal work RAM as `bytes`.
+#[pyclass(module = "tetanes", unsendable)]
+#[must_use]
+pub struct NesEnv {
+    name: String,
+    rom: Vec<u8>,
+    cfg: Config,
+    deck: ControlDeck,
+    rng: StdRng,
+    render: bool,
+    frame_skip: u32,
+    max_frames: Option<u32>,
+    frames: u32,
+    done: bool,
+}
+
+impl NesEnv {
+    fn observation(&mut self, py: Python<'_>) -> Observation {
+        let frame_buffer = self
+            .render
+            .then(|| PyBytes::new_bound(py, self.deck.frame_buffer()).unbind());
+        let ram = PyBytes::new_bound(py, self.deck.wram()).unbind();
+        (frame_buffer, ram)
+    }
+}
+
+#[pymethods]
+impl NesEnv {
+    #[classattr]
+    const A: u16 = JoypadBtnState::A.bits();
+    #[classattr]
+    const B: u16 = JoypadBtnState::B.bits();
+    #[classattr]
+    const SELECT: u16 = JoypadBtnState::SELECT.bits();
+    #[classattr]
+    const START: u16 = JoypadBtnState::START.bits();
+    #[classattr]
+    const UP: u16 = JoypadBtnState::UP.bits();
+    #[classattr]
+    const DOWN: u16 = JoypadBtnState::DOWN.bits();
+    #[classattr]
+    const LEFT: u16 = JoypadBtnState::LEFT.bits();
+    #[classattr]
+    const RIGHT: u16 = JoypadBtnState::RIGHT.bits();
+    #[classattr]
+    const WIDTH: u32 = Ppu::WIDTH;
+    #[classattr]
+    const HEIGHT: u32 = Ppu::HEIGHT;
+
+    /// Create a new environment from a ROM file.
+    ///
+    /// `ram_state` is one of `"all-zeros"`, `"all-ones"` or `"random"`. Each step emulates
+    /// `frame_skip` frames with the same buttons held. Episodes end after `max_frames` emulated
+    /// frames, if set, or when the CPU jams.
+    #[new]
+    #[pyo3(signature = (path, *, seed = None, ram_state = "random", frame_skip = 1, max_frames = None, render = true, fast_boot = true))]
+    fn new(
+        path: PathBuf,
+        seed: Option<u64>,
+        ram_state: &str,
+        frame_skip: u32,
+        max_frames: Option<u32>,
+        render: bool,
+        fast_boot: bool,
+    ) -> PyResult<Self> {
+        if frame_skip == 0 {
+            return Err(PyValueError::new_err("frame_skip must be at least 1"));
+        }
+        let ram_state = ram_state
+            .parse::<RamState>()
+            .map_err(PyValueError::new_err)?;
+        let rom = std::fs::read(&path).map_err(|err| {
+            PyValueError::new_err(format!("failed to read rom {}: {err}", path.display()))
+        })?;
+        let name = path
+            .file_stem()
+            .map_or_else(|| "rom".to_string(), |name| name.to_string_lossy().into());
+
+        let mut headless_mode = HeadlessMode::NO_AUDIO;
+        if !render {
+            headless_mode |= HeadlessMode::NO_VIDEO;
+        }
+        let cfg = Config {
+            ram_state,
+            headless_mode,
+            fast_boot,
+            // Battery-backed RAM is never saved, and shouldn't be loaded from any previous
+            // TetaNES session, so episodes always start from the same state.
+            data_dir: std::env::temp_dir().join("tetanes-py"),
+            ..Config::default()
+        };
+        let deck = create_deck(&cfg, &name, &rom)?;
+
+        Ok(Self {
+            name,
+            rom,
+            cfg,
+            deck,
+            rng: seed.map_or_else(StdRng::from_entropy, StdRng::seed_from_u64),
+            render,
+            frame_skip,
+            max_frames,
+            frames: 0,
+            done: true,
+        })
+    }
+
+    /// Reset the console to power-on state and return the initial `(frame_buffer, ram)`
+    /// observation.
+    ///
+    /// When `seed` is provided, random RAM contents for this and subsequent episodes are
+    /// deterministic.
+    #[pyo3(signature = (seed = None))]
+    fn reset(&mut self, py: Python<'_>, seed: Option<u64>) -> PyResult<Observation> {
+        if let Some(seed) = seed {
+            self.rng = StdRng::seed_from_u64(seed);
+        }
+        mem::seed_random_ram(self.rng.gen());
+        self.deck = create_deck(&self.cfg, &self.name, &self.rom)?;
+        self.frames = 0;
+        self.done = false;
+        Ok(self.observation(py))
+    }
+
+    /// Hold `buttons` for player one, a bitmask of the button constants on this class, and
+    /// emulate `frame_skip` frames. Returns `(frame_buffer, ram, done)`.
+    fn step(&mut self, py: Python<'_>, buttons: u16) -> PyResult<Step> {
+        if self.done {
+            return Err(PyRuntimeError::new_err(
+                "episode is done, call reset() before step()",
+            ));
+        }
+        let buttons = JoypadBtnState::from_bits(buttons)
+            .filter(|buttons| {
+                !buttons.intersects(JoypadBtnState::TURBO_A | JoypadBtnState::TURBO_B)
+            })
+            .ok_or_else(|| PyValueError::new_err(format!("invalid buttons: {buttons:#04X}")))?;
+        self.deck.joypad_mut(Player::One).buttons = buttons;
+
+        for _ in 0..self.frame_skip {
+            match self.deck.clock_frame() {
+                Ok(_) => (),
+                Err(control_deck::Error::CpuCorrupted) => {
+                    self.done = true;
+                    break;
+                }
+                Err(err) => return Err(to_py_err(err)),
+            }
+            self.frames += 1;
+            if self.max_frames.is_some_and(|max| self.frames >= max) {
+                self.done = true;
+                break;
+            }
+        }
+        self.deck.clear_audio_samples();
+
+        let (frame_buffer, ram) = self.observation(py);
+        Ok((frame_buffer, ram, self.done))
+    }
+
+    /// Number of frames emulated in the current episode.
+    #[getter]
+    const fn frames(&self) -> u32 {
+        self.frames
+    }
+
+    /// Whether the current episode has ended.
+    #[getter]
+    const fn done(&self) -> bool {
+        self.done
+    }
+}
+
+#[pymodule]
+fn tetanes(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<NesEnv>()?;
+    Ok(())
+}
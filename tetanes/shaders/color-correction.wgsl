@@ -0,0 +1,101 @@
+//  Color-blind correction shader
+//
+//  Passes the rendered frame through unchanged other than applying the selected color-blind
+//  correction mode and any playing transition.
+
+var<private> vertices: array<vec2<f32>, 3> = array<vec2<f32>, 3>(
+    vec2<f32>(-1.0, -1.0),
+    vec2<f32>(3.0, -1.0),
+    vec2<f32>(-1.0, 3.0),
+);
+
+// Vertex shader
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) v_uv: vec2<f32>,
+};
+
+@vertex
+fn vs_main(
+    @builtin(vertex_index) v_idx: u32
+) -> VertexOutput {
+    var out: VertexOutput;
+    let vert = vertices[v_idx];
+
+    // Convert x from -1.0..1.0 to 0.0..1.0 and y from -1.0..1.0 to 1.0..0.0
+    out.position = vec4(vert, 0.0, 1.0);
+    out.v_uv = fma(vert, vec2(0.5, -0.5), vec2(0.5, 0.5));
+    return out;
+}
+
+// Fragment shader
+
+struct Output {
+    screen_size: vec2<f32>,
+    color_blind_mode: u32,
+    transition: u32,
+    transition_progress: f32,
+    // Uniform buffers need to be padded to 16 bytes in WebGL.
+    // See https://github.com/gfx-rs/wgpu/issues/2072
+    _padding0: u32,
+    _padding1: u32,
+    _padding2: u32,
+}
+@group(0) @binding(0) var<uniform> out: Output;
+
+@group(1) @binding(0) var tex: texture_2d<f32>;
+@group(1) @binding(1) var tex_sampler: sampler;
+
+// `daltonize` is appended from `daltonize.wgsl` when the shader is built.
+
+// Cosmetic transitions, which must match `Transition` in `shader.rs`. The progress of the playing
+// transition goes from 0.0 to 1.0.
+
+const MELT_COLUMNS: f32 = 160.0;
+
+fn hash(x: f32) -> f32 {
+    return fract(sin(x * 12.9898) * 43758.5453);
+}
+
+// Scale of the picture for the CRT power off transition, collapsing into a horizontal line and
+// then a dot over the first half and expanding back over the second.
+fn power_off_scale(progress: f32) -> vec2<f32> {
+    let t = abs(1.0 - 2.0 * progress);
+    return vec2<f32>(clamp(t * 4.0, 0.01, 1.0), max(t, 0.005));
+}
+
+// Coordinates to sample at `uv` while the picture is moved by the playing transition.
+fn transition_uv(uv: vec2<f32>) -> vec2<f32> {
+    if out.transition == 3u {
+        return (uv - 0.5) / power_off_scale(out.transition_progress) + 0.5;
+    }
+    return uv;
+}
+
+fn transition(col: vec3<f32>, uv: vec2<f32>) -> vec3<f32> {
+    let progress = out.transition_progress;
+    if out.transition == 1u {
+        // Melt, with each column of black dropping off the bottom at a different speed
+        let top = clamp(progress * 1.6 - hash(floor(uv.x * MELT_COLUMNS)) * 0.6, 0.0, 1.0);
+        return select(col, vec3<f32>(0.0), uv.y >= top);
+    } else if out.transition == 2u {
+        // Fade
+        return col * progress;
+    } else if out.transition == 3u {
+        // CRT power off, brightening as the beam concentrates into a line
+        let scale = power_off_scale(progress);
+        let pos = abs(uv - 0.5) / scale;
+        let glow = min(col * (1.0 + (1.0 - scale.y) * 2.0), vec3<f32>(1.0));
+        return select(vec3<f32>(0.0), glow, pos.x <= 0.5 && pos.y <= 0.5);
+    }
+    return col;
+}
+
+@fragment
+fn fs_main(
+    @location(0) v_uv: vec2<f32>
+) -> @location(0) vec4<f32> {
+    let col = textureSample(tex, tex_sampler, transition_uv(v_uv));
+    return vec4<f32>(transition(daltonize(col.rgb, out.color_blind_mode), v_uv), col.a);
+}
@@ -0,0 +1,40 @@
+// Shared by all shaders, appended to each shader's source by `include_shader!` in `shader.rs`.
+
+// Color-blind correction adapted from "Daltonize" by Onur Fidaner, Poliang Lin and Nevran Ozguven.
+//
+// Simulates how colors are perceived with the given deficiency and redistributes the lost
+// information to channels that remain distinguishable.
+fn daltonize(col: vec3<f32>, mode: u32) -> vec3<f32> {
+    if mode == 0u {
+        return col;
+    }
+
+    // Rows of the RGB to LMS and LMS to RGB conversion matrices, used with `v * m`
+    let rgb_to_lms = mat3x3<f32>(
+        vec3<f32>(17.8824, 43.5161, 4.11935),
+        vec3<f32>(3.45565, 27.1554, 3.86714),
+        vec3<f32>(0.0299566, 0.184309, 1.46709),
+    );
+    let lms_to_rgb = mat3x3<f32>(
+        vec3<f32>(0.0809444479, -0.130504409, 0.116721066),
+        vec3<f32>(-0.0102485335, 0.0540193266, -0.113614708),
+        vec3<f32>(-0.000365296938, -0.00412161469, 0.693511405),
+    );
+
+    let lms = col * rgb_to_lms;
+    var sim = lms;
+    if mode == 1u {
+        // Deuteranopia
+        sim.y = 0.494207 * lms.x + 1.24827 * lms.z;
+    } else if mode == 2u {
+        // Protanopia
+        sim.x = 2.02344 * lms.y - 2.52581 * lms.z;
+    } else {
+        // Tritanopia
+        sim.z = -0.395913 * lms.x + 0.801109 * lms.y;
+    }
+
+    let err = col - sim * lms_to_rgb;
+    let correction = vec3<f32>(0.0, 0.7 * err.r + err.g, 0.7 * err.r + err.b);
+    return clamp(col + correction, vec3<f32>(0.0), vec3<f32>(1.0));
+}
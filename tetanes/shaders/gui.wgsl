@@ -8,9 +8,14 @@ struct VertexOutput {
 
 struct Output {
     screen_size: vec2<f32>,
-    // Uniform buffers need to be at least 16 bytes in WebGL.
+    color_blind_mode: u32,
+    transition: u32,
+    transition_progress: f32,
+    // Uniform buffers need to be padded to 16 bytes in WebGL.
     // See https://github.com/gfx-rs/wgpu/issues/2072
-    _padding: vec2<u32>,
+    _padding0: u32,
+    _padding1: u32,
+    _padding2: u32,
 };
 @group(0) @binding(0) var<uniform> out: Output;
 
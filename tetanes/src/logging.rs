@@ -1,13 +1,155 @@
 use crate::sys::logging;
-use std::env;
+use chrono::{DateTime, Local};
+use parking_lot::{const_mutex, Mutex};
+use std::{collections::VecDeque, env, fmt, sync::OnceLock};
+use tracing::{
+    field::{Field, Visit},
+    level_filters::LevelFilter,
+    Event, Level, Subscriber,
+};
 use tracing_subscriber::{
     filter::Targets,
-    layer::{Layered, SubscriberExt},
+    layer::{Context, Layered, SubscriberExt},
+    registry::LookupSpan,
+    reload,
     util::SubscriberInitExt,
-    Registry,
+    Layer, Registry,
 };
 
-fn create_registry() -> Layered<Targets, Registry> {
+/// Maximum number of log entries kept in memory.
+const MAX_LOG_ENTRIES: usize = 1000;
+
+static LOG_ENTRIES: Mutex<VecDeque<LogEntry>> = const_mutex(VecDeque::new());
+static FILTER_HANDLE: OnceLock<reload::Handle<Targets, Registry>> = OnceLock::new();
+
+/// A single captured log event.
+#[derive(Debug, Clone)]
+#[must_use]
+pub struct LogEntry {
+    pub timestamp: DateTime<Local>,
+    pub level: Level,
+    pub target: String,
+    pub message: String,
+}
+
+impl fmt::Display for LogEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {:>5} {}: {}",
+            self.timestamp.format("%H:%M:%S%.3f"),
+            self.level,
+            self.target,
+            self.message
+        )
+    }
+}
+
+/// Returns the captured log entries, oldest first.
+pub fn log_entries() -> Vec<LogEntry> {
+    LOG_ENTRIES.lock().iter().cloned().collect()
+}
+
+/// Returns the captured log entries formatted as lines, oldest first.
+pub fn recent_logs() -> Vec<String> {
+    LOG_ENTRIES.lock().iter().map(ToString::to_string).collect()
+}
+
+/// Clears all captured log entries.
+pub fn clear_log_entries() {
+    LOG_ENTRIES.lock().clear();
+}
+
+/// Returns the current maximum log level for `tetanes` targets.
+pub fn level() -> LevelFilter {
+    FILTER_HANDLE
+        .get()
+        .and_then(|handle| {
+            handle
+                .with_current(|filter| {
+                    [
+                        Level::TRACE,
+                        Level::DEBUG,
+                        Level::INFO,
+                        Level::WARN,
+                        Level::ERROR,
+                    ]
+                    .into_iter()
+                    .find(|level| filter.would_enable("tetanes", level))
+                    .map_or(LevelFilter::OFF, LevelFilter::from_level)
+                })
+                .ok()
+        })
+        .unwrap_or_else(LevelFilter::current)
+}
+
+/// Change the maximum log level for `tetanes` targets at runtime, replacing any filter set by
+/// `RUST_LOG`.
+pub fn set_level(level: LevelFilter) -> anyhow::Result<()> {
+    let Some(handle) = FILTER_HANDLE.get() else {
+        anyhow::bail!("logging is not initialized");
+    };
+    let filter = Targets::new()
+        .with_default(LevelFilter::WARN)
+        .with_target("tetanes", level);
+    handle
+        .reload(filter)
+        .map_err(|err| anyhow::anyhow!("failed to set log level: {err:?}"))?;
+    tracing::info!("log level set to {level}");
+    Ok(())
+}
+
+/// [`Layer`] that keeps the most recent log events in memory so they can be shown in the UI and
+/// included in error reports.
+#[derive(Default, Debug, Copy, Clone)]
+#[must_use]
+pub struct LogBuffer;
+
+impl<S> Layer<S> for LogBuffer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+        let entry = LogEntry {
+            timestamp: Local::now(),
+            level: *metadata.level(),
+            target: metadata.target().to_string(),
+            message: visitor.message,
+        };
+
+        let mut entries = LOG_ENTRIES.lock();
+        if entries.len() >= MAX_LOG_ENTRIES {
+            entries.pop_front();
+        }
+        entries.push_back(entry);
+    }
+}
+
+/// Formats the `message` field of an event, followed by any other fields.
+#[derive(Default, Debug)]
+struct MessageVisitor {
+    message: String,
+}
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        use std::fmt::Write;
+
+        if !self.message.is_empty() {
+            self.message.push(' ');
+        }
+        let _ = if field.name() == "message" {
+            write!(self.message, "{value:?}")
+        } else {
+            write!(self.message, "{}={value:?}", field.name())
+        };
+    }
+}
+
+fn create_registry() -> Layered<LogBuffer, Layered<reload::Layer<Targets, Registry>, Registry>> {
     let default_log = if cfg!(debug_assertions) {
         "warn,tetanes=debug,tetanes-core=debug"
     } else {
@@ -15,12 +157,15 @@ fn create_registry() -> Layered<Targets, Registry> {
     };
     let default_filter = default_log.parse::<Targets>().unwrap_or_default();
 
-    tracing_subscriber::registry().with(
+    let (filter, handle) = reload::Layer::new(
         env::var("RUST_LOG")
             .ok()
             .and_then(|filter| filter.parse::<Targets>().ok())
             .unwrap_or(default_filter),
-    )
+    );
+    let _ = FILTER_HANDLE.set(handle);
+
+    tracing_subscriber::registry().with(filter).with(LogBuffer)
 }
 
 /// Initialize logging.
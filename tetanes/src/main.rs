@@ -2,6 +2,10 @@
 //!
 //! USAGE:
 //!     tetanes [FLAGS] [OPTIONS] [path]
+//!     tetanes self-test
+//!     tetanes state-diff <a> <b>
+//!     tetanes run-tests <file>
+//!     tetanes --verify-replay <replay> <path>
 //!
 //! FLAGS:
 //!     -f, --fullscreen    Start fullscreen.
@@ -22,8 +26,28 @@ use tetanes::{logging, nes::Nes};
 
 #[cfg(not(target_arch = "wasm32"))]
 mod opts;
+#[cfg(not(target_arch = "wasm32"))]
+mod run_tests;
+#[cfg(not(target_arch = "wasm32"))]
+mod self_test;
+#[cfg(not(target_arch = "wasm32"))]
+mod state_diff;
+#[cfg(not(target_arch = "wasm32"))]
+mod verify_replay;
 
 fn main() -> anyhow::Result<()> {
+    // Portable mode changes where logs are stored, so options must be parsed first
+    #[cfg(not(target_arch = "wasm32"))]
+    let opts = {
+        use clap::Parser;
+
+        let opts = opts::Opts::parse();
+        if opts.portable {
+            tetanes::nes::config::Config::enable_portable();
+        }
+        opts
+    };
+
     let log = logging::init();
     if let Err(err) = log {
         eprintln!("failed to initialize logging: {err:?}");
@@ -37,11 +61,18 @@ fn main() -> anyhow::Result<()> {
             if #[cfg(target_arch = "wasm32")] {
                 tetanes::nes::config::Config::load(None)
             } else {
-                use clap::Parser;
-
-                let opts = opts::Opts::parse();
                 tracing::debug!("CLI Options: {opts:?}");
 
+                match opts.command.clone() {
+                    Some(opts::Command::SelfTest) => return self_test::run(&opts.load()?),
+                    Some(opts::Command::StateDiff { a, b }) => return state_diff::run(&a, &b),
+                    Some(opts::Command::RunTests { file }) => return run_tests::run(&file),
+                    None => (),
+                }
+                if let Some(replay) = opts.verify_replay.clone() {
+                    return verify_replay::run(&opts.load()?, &replay);
+                }
+
                 opts.load()?
             }
         }
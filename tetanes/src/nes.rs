@@ -2,13 +2,16 @@
 
 use crate::{
     nes::{
+        attract::AttractMode,
         event::NesEventProxy,
-        input::{Gamepads, InputBindings},
-        renderer::{painter::Painter, FrameRecycle, Resources},
+        input::{ChordState, Gamepads, InputBindings},
+        renderer::{gui::MessageType, painter::Painter, FrameRecycle, Resources},
+        rom_library::RomWatcher,
     },
     platform::Initialize,
 };
 use anyhow::Context;
+use audio::SampleTap;
 use cfg_if::cfg_if;
 use config::Config;
 use crossbeam::channel::Receiver;
@@ -26,13 +29,17 @@ use winit::{
 };
 
 pub mod action;
+pub mod attract;
 pub mod audio;
 pub mod config;
 pub mod emulation;
 pub mod event;
 pub mod input;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod phone_pad;
 pub mod renderer;
 pub mod rom;
+pub mod rom_library;
 pub mod version;
 
 /// Represents all the NES Emulation state.
@@ -98,18 +105,20 @@ impl RunState {
 #[derive(Debug)]
 pub(crate) struct Running {
     pub(crate) cfg: Config,
-    // Only used by wasm currently
-    #[allow(unused)]
     pub(crate) tx: NesEventProxy,
     pub(crate) emulation: Emulation,
     pub(crate) renderer: Renderer,
     pub(crate) input_bindings: InputBindings,
+    pub(crate) chords: ChordState,
     pub(crate) gamepads: Gamepads,
     pub(crate) modifiers: Modifiers,
     pub(crate) run_state: RunState,
     pub(crate) replay_recording: bool,
     pub(crate) audio_recording: bool,
     pub(crate) rewinding: bool,
+    pub(crate) fast_forwarding: bool,
+    pub(crate) attract: AttractMode,
+    pub(crate) rom_watcher: Option<RomWatcher>,
     pub(crate) repaint_times: HashMap<WindowId, Instant>,
 }
 
@@ -189,6 +198,11 @@ impl Nes {
                     painter: painter_rx.recv()?,
                 };
                 let (frame_tx, frame_rx) = blocking::with_recycle::<Frame, _>(10, FrameRecycle);
+                let (race_frame_tx, race_frame_rx) =
+                    blocking::with_recycle::<Frame, _>(10, FrameRecycle);
+                let (ghost_frame_tx, ghost_frame_rx) =
+                    blocking::with_recycle::<Frame, _>(10, FrameRecycle);
+                let audio_tap = Arc::new(SampleTap::new());
                 let (mut cfg, tx) = self
                     .init_state
                     .take()
@@ -198,8 +212,23 @@ impl Nes {
                 let gamepads = Gamepads::new();
                 cfg.input.update_gamepad_assignments(&gamepads);
 
-                let emulation = Emulation::new(tx.clone(), frame_tx.clone(), &cfg)?;
-                let renderer = Renderer::new(tx.clone(), resources, frame_rx, &cfg)?;
+                let emulation = Emulation::new(
+                    tx.clone(),
+                    frame_tx,
+                    race_frame_tx,
+                    ghost_frame_tx,
+                    Arc::clone(&audio_tap),
+                    &cfg,
+                )?;
+                let renderer = Renderer::new(
+                    tx.clone(),
+                    resources,
+                    frame_rx,
+                    race_frame_rx,
+                    ghost_frame_rx,
+                    audio_tap,
+                    &cfg,
+                )?;
 
                 let mut running = Running {
                     cfg,
@@ -207,14 +236,21 @@ impl Nes {
                     emulation,
                     renderer,
                     input_bindings,
+                    chords: ChordState::default(),
                     gamepads,
                     modifiers: Modifiers::default(),
                     run_state: RunState::Running,
                     replay_recording: false,
                     audio_recording: false,
                     rewinding: false,
+                    fast_forwarding: false,
+                    attract: AttractMode::new(),
+                    rom_watcher: None,
                     repaint_times: HashMap::default(),
                 };
+                for warning in std::mem::take(&mut running.cfg.startup.config_warnings) {
+                    running.renderer.add_message(MessageType::Warn, warning);
+                }
                 running.initialize()?;
                 self.state = State::Running(running);
                 Ok(())
@@ -37,21 +37,38 @@ impl Ord for Action {
 }
 
 impl Action {
-    pub const BINDABLE: [Self; 111] = [
+    pub const BINDABLE: [Self; 134] = [
         Self::Ui(Ui::Quit),
         Self::Ui(Ui::TogglePause),
         Self::Ui(Ui::LoadRom),
         Self::Ui(Ui::UnloadRom),
         Self::Ui(Ui::LoadReplay),
         Self::Menu(Menu::About),
+        Self::Menu(Menu::AudioVisualizer),
+        Self::Menu(Menu::BigPicture),
         Self::Menu(Menu::Keybinds),
         Self::Menu(Menu::PerfStats),
         Self::Menu(Menu::Preferences),
+        Self::Menu(Menu::RamWatch),
+        Self::Menu(Menu::Shortcuts),
         Self::Feature(Feature::ToggleReplayRecording),
         Self::Feature(Feature::ToggleAudioRecording),
         Self::Feature(Feature::VisualRewind),
         Self::Feature(Feature::InstantRewind),
         Self::Feature(Feature::TakeScreenshot),
+        Self::Feature(Feature::ScreenshotBurst),
+        Self::Feature(Feature::UndoLoadState),
+        Self::Feature(Feature::SaveStateRing),
+        Self::Feature(Feature::LoadStateRing),
+        // Only allow bindings up to 4 macro slots
+        Self::Feature(Feature::ToggleMacroRecording(1)),
+        Self::Feature(Feature::ToggleMacroRecording(2)),
+        Self::Feature(Feature::ToggleMacroRecording(3)),
+        Self::Feature(Feature::ToggleMacroRecording(4)),
+        Self::Feature(Feature::PlayMacro(1)),
+        Self::Feature(Feature::PlayMacro(2)),
+        Self::Feature(Feature::PlayMacro(3)),
+        Self::Feature(Feature::PlayMacro(4)),
         Self::Setting(Setting::ToggleFullscreen),
         Self::Setting(Setting::ToggleEmbedViewports),
         Self::Setting(Setting::ToggleAlwaysOnTop),
@@ -67,6 +84,10 @@ impl Action {
         Self::Setting(Setting::DecrementScale),
         Self::Setting(Setting::IncrementSpeed),
         Self::Setting(Setting::DecrementSpeed),
+        Self::Setting(Setting::SetSpeed(50)),
+        Self::Setting(Setting::SetSpeed(100)),
+        Self::Setting(Setting::SetSpeed(150)),
+        Self::Setting(Setting::SetSpeed(200)),
         Self::Deck(DeckAction::Reset(ResetKind::Soft)),
         Self::Deck(DeckAction::Reset(ResetKind::Hard)),
         Self::Deck(DeckAction::Joypad((Player::One, JoypadBtn::Left))),
@@ -124,6 +145,7 @@ impl Action {
         Self::Deck(DeckAction::SetSaveSlot(6)),
         Self::Deck(DeckAction::SetSaveSlot(7)),
         Self::Deck(DeckAction::SetSaveSlot(8)),
+        Self::Deck(DeckAction::SwapSaveSlot),
         Self::Deck(DeckAction::SaveState),
         Self::Deck(DeckAction::LoadState),
         Self::Deck(DeckAction::ToggleApuChannel(Channel::Pulse1)),
@@ -161,8 +183,22 @@ impl Action {
         Self::Debug(Debug::Step(DebugStep::Over)),
         Self::Debug(Debug::Step(DebugStep::Scanline)),
         Self::Debug(Debug::Step(DebugStep::Frame)),
+        Self::Debug(Debug::Step(DebugStep::Back)),
+        Self::Debug(Debug::Step(DebugStep::ReverseContinue)),
     ];
 
+    /// Heading the action is grouped under when listing shortcuts.
+    pub const fn category(&self) -> &'static str {
+        match self {
+            Self::Ui(_) => "General",
+            Self::Menu(_) => "Windows",
+            Self::Feature(_) => "Features",
+            Self::Setting(_) => "Settings",
+            Self::Deck(_) => "Emulation",
+            Self::Debug(_) => "Debug",
+        }
+    }
+
     pub const fn is_joypad(&self) -> bool {
         matches!(self, Action::Deck(DeckAction::Joypad(_)))
     }
@@ -190,10 +226,16 @@ impl AsRef<str> for Action {
             },
             Action::Menu(menu) => match menu {
                 Menu::About => "Toggle About",
+                Menu::AudioVisualizer => "Toggle Audio Visualizer",
+                Menu::BigPicture => "Toggle Big Picture",
                 Menu::Keybinds => "Toggle Keybinds",
+                Menu::MapperInspector => "Toggle Mapper Inspector",
                 Menu::PerfStats => "Toggle Performance Stats",
                 Menu::PpuViewer => "Toggle PPU Viewer",
                 Menu::Preferences => "Toggle Preferences",
+                Menu::Profiler => "Toggle Profiler",
+                Menu::RamWatch => "Toggle RAM Watch",
+                Menu::Shortcuts => "Toggle Keyboard Shortcuts",
             },
             Action::Feature(feature) => match feature {
                 Feature::ToggleReplayRecording => "Toggle Replay Recording",
@@ -201,6 +243,20 @@ impl AsRef<str> for Action {
                 Feature::VisualRewind => "Visual Rewind",
                 Feature::InstantRewind => "Instant Rewind",
                 Feature::TakeScreenshot => "Take Screenshot",
+                Feature::ScreenshotBurst => "Screenshot Burst",
+                Feature::UndoLoadState => "Undo Load State",
+                Feature::SaveStateRing => "Quick Save to Ring",
+                Feature::LoadStateRing => "Quick Load from Ring",
+                Feature::ToggleMacroRecording(1) => "Toggle Macro 1 Recording",
+                Feature::ToggleMacroRecording(2) => "Toggle Macro 2 Recording",
+                Feature::ToggleMacroRecording(3) => "Toggle Macro 3 Recording",
+                Feature::ToggleMacroRecording(4) => "Toggle Macro 4 Recording",
+                Feature::ToggleMacroRecording(_) => "Toggle Macro N Recording",
+                Feature::PlayMacro(1) => "Play Macro 1",
+                Feature::PlayMacro(2) => "Play Macro 2",
+                Feature::PlayMacro(3) => "Play Macro 3",
+                Feature::PlayMacro(4) => "Play Macro 4",
+                Feature::PlayMacro(_) => "Play Macro N",
             },
             Action::Setting(setting) => match setting {
                 Setting::ToggleFullscreen => "Toggle Fullscreen",
@@ -219,6 +275,11 @@ impl AsRef<str> for Action {
                 Setting::DecrementScale => "Scale Decrement",
                 Setting::IncrementSpeed => "Speed Increment",
                 Setting::DecrementSpeed => "Speed Increment",
+                Setting::SetSpeed(50) => "Set Speed 0.5x",
+                Setting::SetSpeed(100) => "Set Speed 1x",
+                Setting::SetSpeed(150) => "Set Speed 1.5x",
+                Setting::SetSpeed(200) => "Set Speed 2x",
+                Setting::SetSpeed(_) => "Set Speed",
             },
             Action::Deck(deck) => match deck {
                 DeckAction::Reset(kind) => match kind {
@@ -253,6 +314,7 @@ impl AsRef<str> for Action {
                 DeckAction::SetSaveSlot(7) => "Set Save Slot 7",
                 DeckAction::SetSaveSlot(8) => "Set Save Slot 8",
                 DeckAction::SetSaveSlot(_) => "Set Save Slot N",
+                DeckAction::SwapSaveSlot => "Swap Save Slot",
                 DeckAction::SaveState => "Save State",
                 DeckAction::LoadState => "Load State",
                 DeckAction::ToggleApuChannel(channel) => match channel {
@@ -297,6 +359,8 @@ impl AsRef<str> for Action {
                     DebugStep::Over => "Debug Step Over",
                     DebugStep::Scanline => "Debug Step Scanline",
                     DebugStep::Frame => "Debug Step Frame",
+                    DebugStep::Back => "Debug Step Back",
+                    DebugStep::ReverseContinue => "Debug Reverse Continue",
                 },
             },
         }
@@ -314,15 +378,33 @@ impl TryFrom<&str> for Action {
             "Unload ROM" => Self::Ui(Ui::UnloadRom),
             "Load Replay" => Self::Ui(Ui::LoadReplay),
             "Toggle About Window" => Self::Menu(Menu::About),
+            "Toggle Audio Visualizer" => Self::Menu(Menu::AudioVisualizer),
+            "Toggle Big Picture" => Self::Menu(Menu::BigPicture),
             "Toggle Keybinds Menu" => Self::Menu(Menu::Keybinds),
+            "Toggle Mapper Inspector" => Self::Menu(Menu::MapperInspector),
             "Toggle Performance Stats Window" => Self::Menu(Menu::PerfStats),
             "Toggle PPU Viewer" => Self::Menu(Menu::PpuViewer),
+            "Toggle Profiler" => Self::Menu(Menu::Profiler),
             "Toggle Preferences Menu" => Self::Menu(Menu::Preferences),
+            "Toggle RAM Watch" => Self::Menu(Menu::RamWatch),
+            "Toggle Keyboard Shortcuts" => Self::Menu(Menu::Shortcuts),
             "Toggle Replay Recording" => Self::Feature(Feature::ToggleReplayRecording),
             "Toggle Audio Recording" => Self::Feature(Feature::ToggleAudioRecording),
             "Visual Rewind" => Self::Feature(Feature::VisualRewind),
             "Instant Rewind" => Self::Feature(Feature::InstantRewind),
             "Take Screenshot" => Self::Feature(Feature::TakeScreenshot),
+            "Screenshot Burst" => Self::Feature(Feature::ScreenshotBurst),
+            "Undo Load State" => Self::Feature(Feature::UndoLoadState),
+            "Quick Save to Ring" => Self::Feature(Feature::SaveStateRing),
+            "Quick Load from Ring" => Self::Feature(Feature::LoadStateRing),
+            "Toggle Macro 1 Recording" => Self::Feature(Feature::ToggleMacroRecording(1)),
+            "Toggle Macro 2 Recording" => Self::Feature(Feature::ToggleMacroRecording(2)),
+            "Toggle Macro 3 Recording" => Self::Feature(Feature::ToggleMacroRecording(3)),
+            "Toggle Macro 4 Recording" => Self::Feature(Feature::ToggleMacroRecording(4)),
+            "Play Macro 1" => Self::Feature(Feature::PlayMacro(1)),
+            "Play Macro 2" => Self::Feature(Feature::PlayMacro(2)),
+            "Play Macro 3" => Self::Feature(Feature::PlayMacro(3)),
+            "Play Macro 4" => Self::Feature(Feature::PlayMacro(4)),
             "Toggle Fullscreen" => Self::Setting(Setting::ToggleFullscreen),
             "Toggle Embed Viewports" => Self::Setting(Setting::ToggleEmbedViewports),
             "Toggle Always On Top" => Self::Setting(Setting::ToggleAlwaysOnTop),
@@ -338,6 +420,10 @@ impl TryFrom<&str> for Action {
             "Decrement Scale" => Self::Setting(Setting::DecrementScale),
             "Increment Speed" => Self::Setting(Setting::IncrementSpeed),
             "Decrement Speed" => Self::Setting(Setting::DecrementSpeed),
+            "Set Speed 0.5x" => Self::Setting(Setting::SetSpeed(50)),
+            "Set Speed 1x" => Self::Setting(Setting::SetSpeed(100)),
+            "Set Speed 1.5x" => Self::Setting(Setting::SetSpeed(150)),
+            "Set Speed 2x" => Self::Setting(Setting::SetSpeed(200)),
             "Reset" => Self::Deck(DeckAction::Reset(ResetKind::Soft)),
             "Power Cycle" => Self::Deck(DeckAction::Reset(ResetKind::Hard)),
             "Joypad Left (P1)" => Self::Deck(DeckAction::Joypad((Player::One, JoypadBtn::Left))),
@@ -375,6 +461,7 @@ impl TryFrom<&str> for Action {
             "Set Save Slot 6" => Self::Deck(DeckAction::SetSaveSlot(6)),
             "Set Save Slot 7" => Self::Deck(DeckAction::SetSaveSlot(7)),
             "Set Save Slot 8" => Self::Deck(DeckAction::SetSaveSlot(8)),
+            "Swap Save Slot" => Self::Deck(DeckAction::SwapSaveSlot),
             "Save State" => Self::Deck(DeckAction::SaveState),
             "Load State" => Self::Deck(DeckAction::LoadState),
             "Toggle Pulse1 Channel" => Self::Deck(DeckAction::ToggleApuChannel(Channel::Pulse1)),
@@ -416,6 +503,10 @@ impl TryFrom<&str> for Action {
             "Step Over (CPU Debugger)" => Self::Debug(Debug::Step(DebugStep::Over)),
             "Step Scanline (CPU Debugger)" => Self::Debug(Debug::Step(DebugStep::Scanline)),
             "Step Frame (CPU Debugger)" => Self::Debug(Debug::Step(DebugStep::Frame)),
+            "Step Back (CPU Debugger)" => Self::Debug(Debug::Step(DebugStep::Back)),
+            "Reverse Continue (CPU Debugger)" => {
+                Self::Debug(Debug::Step(DebugStep::ReverseContinue))
+            }
             _ => return Err(anyhow::anyhow!("Invalid action string")),
         })
     }
@@ -479,6 +570,18 @@ pub enum Feature {
     VisualRewind,
     InstantRewind,
     TakeScreenshot,
+    /// Save each frame as a screenshot for a number of frames, or while held.
+    ScreenshotBurst,
+    /// Restore the state from before the most recent load state.
+    UndoLoadState,
+    /// Save a state to the quick save state ring, replacing the oldest once full.
+    SaveStateRing,
+    /// Load the newest state in the quick save state ring, cycling to older ones when repeated.
+    LoadStateRing,
+    /// Start or stop recording an input macro into a slot.
+    ToggleMacroRecording(u8),
+    /// Play back the input macro recorded in a slot.
+    PlayMacro(u8),
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -499,6 +602,8 @@ pub enum Setting {
     DecrementScale,
     IncrementSpeed,
     DecrementSpeed,
+    /// Set the emulation speed to a preset, as a percentage of normal speed.
+    SetSpeed(u16),
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -517,6 +622,10 @@ pub enum DebugStep {
     Over,
     Scanline,
     Frame,
+    /// Step back one instruction by re-emulating from a rewind snapshot.
+    Back,
+    /// Run backwards to the previous breakpoint hit by re-emulating from rewind snapshots.
+    ReverseContinue,
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -0,0 +1,89 @@
+//! Attract mode for cabinet and kiosk setups, playing back a recent ROM while idle on the ROM
+//! library screen.
+
+use crate::nes::config::RecentRoms;
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    path::{Path, PathBuf},
+};
+use tetanes_core::{fs, time::Instant};
+
+/// Tracks user input to start attract mode after a period of inactivity.
+#[derive(Debug)]
+#[must_use]
+pub struct AttractMode {
+    last_input: Instant,
+    active: bool,
+}
+
+impl Default for AttractMode {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AttractMode {
+    pub fn new() -> Self {
+        Self {
+            last_input: Instant::now(),
+            active: false,
+        }
+    }
+
+    /// Whether a ROM is currently being played back in attract mode.
+    pub const fn active(&self) -> bool {
+        self.active
+    }
+
+    /// Record user input, returning `true` if attract mode was active and should be stopped.
+    pub fn on_input(&mut self) -> bool {
+        self.last_input = Instant::now();
+        std::mem::take(&mut self.active)
+    }
+
+    /// Whether attract mode should start, given the configured number of idle minutes, where `0`
+    /// disables attract mode.
+    pub fn should_start(&self, idle_minutes: u32, rom_loaded: bool) -> bool {
+        idle_minutes > 0
+            && !self.active
+            && !rom_loaded
+            && self.last_input.elapsed().as_secs() >= u64::from(idle_minutes) * 60
+    }
+
+    /// Mark attract mode as started, or restart the idle timer if there was nothing to play.
+    pub fn start(&mut self, started: bool) {
+        self.active = started;
+        self.last_input = Instant::now();
+    }
+
+    /// Choose a random recent ROM that still exists, along with its most recent replay
+    /// recording, if any.
+    pub fn pick(recent_roms: &RecentRoms, replay_dir: &Path) -> Option<(PathBuf, Option<PathBuf>)> {
+        let roms = recent_roms
+            .iter()
+            .filter(|rom| rom.path.is_file())
+            .collect::<Vec<_>>();
+        if roms.is_empty() {
+            return None;
+        }
+        let index = RandomState::new().build_hasher().finish() as usize % roms.len();
+        let rom = roms[index].path.clone();
+        let replay = Self::latest_replay(&rom, replay_dir);
+        Some((rom, replay))
+    }
+
+    /// Find the most recent replay recorded for a ROM. Replay filenames end in a sortable
+    /// timestamp, so the last one in name order is the newest.
+    fn latest_replay(rom: &Path, replay_dir: &Path) -> Option<PathBuf> {
+        let prefix = format!("tetanes_replay_{}_", fs::filename(rom));
+        std::fs::read_dir(replay_dir)
+            .ok()?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                path.extension().is_some_and(|ext| ext == "replay")
+                    && fs::filename(path).starts_with(&prefix)
+            })
+            .max()
+    }
+}
@@ -1,15 +1,25 @@
-use crate::nes::config::Config;
 use anyhow::{anyhow, Context};
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use recording::{Recording, RecordingLimits};
 use ringbuf::{
     producer::Producer,
     traits::{Consumer, Observer, Split},
     CachingCons, CachingProd, HeapRb,
 };
-use std::{fs::File, io::BufWriter, iter, path::PathBuf, sync::Arc};
-use tetanes_core::time::Duration;
+use serde::{Deserialize, Serialize};
+use std::{
+    iter,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU32, AtomicUsize, Ordering},
+        Arc,
+    },
+};
+use tetanes_core::time::{Duration, Instant};
 use tracing::{debug, error, info, trace, warn};
 
+pub mod recording;
+
 type SampleRb = Arc<HeapRb<f32>>;
 type SampleProducer = CachingProd<SampleRb>;
 type SampleConsumer = CachingCons<SampleRb>;
@@ -28,6 +38,51 @@ pub enum State {
     Stopped,
 }
 
+/// Audio host API used for output.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[must_use]
+pub enum AudioBackend {
+    /// The platform default, e.g. shared-mode WASAPI on Windows or ALSA on Linux.
+    #[default]
+    Default,
+    /// A host that bypasses the system mixer for lower latency: JACK on Linux or ASIO on
+    /// Windows. Requires building with the `jack` or `asio` feature, falling back to the default
+    /// host otherwise.
+    LowLatency,
+}
+
+impl AudioBackend {
+    pub const fn as_slice() -> &'static [Self] {
+        &[Self::Default, Self::LowLatency]
+    }
+
+    /// Host names, as returned by [`cpal::HostId::name`], that are considered low latency.
+    const LOW_LATENCY_HOSTS: [&'static str; 2] = ["JACK", "ASIO"];
+
+    fn host(&self) -> cpal::Host {
+        if *self == Self::LowLatency {
+            let host_id = cpal::available_hosts()
+                .into_iter()
+                .find(|id| Self::LOW_LATENCY_HOSTS.contains(&id.name()));
+            match host_id.map(cpal::host_from_id) {
+                Some(Ok(host)) => return host,
+                Some(Err(err)) => warn!("low latency audio host unavailable: {err:?}"),
+                None => warn!("no low latency audio host available, using default"),
+            }
+        }
+        cpal::default_host()
+    }
+}
+
+impl AsRef<str> for AudioBackend {
+    fn as_ref(&self) -> &str {
+        match self {
+            Self::Default => "Default",
+            Self::LowLatency => "Low Latency (JACK/ASIO)",
+        }
+    }
+}
+
 #[derive(Debug)]
 #[must_use]
 pub enum CallbackMsg {
@@ -37,54 +92,151 @@ pub enum CallbackMsg {
     Record(bool),
 }
 
+/// Lock-free ring buffer of the most recently played samples, written by the audio callback and
+/// read by the audio visualizer. The oldest samples are overwritten instead of ever blocking the
+/// audio callback.
+#[must_use]
+pub struct SampleTap {
+    samples: Box<[AtomicU32]>,
+    head: AtomicUsize,
+    sample_rate: AtomicU32,
+}
+
+impl Default for SampleTap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for SampleTap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SampleTap")
+            .field("head", &self.head.load(Ordering::Relaxed))
+            .field("sample_rate", &self.sample_rate())
+            .finish_non_exhaustive()
+    }
+}
+
+impl SampleTap {
+    /// Number of samples kept. Must be a power of two so indexes remain contiguous when the write
+    /// position wraps.
+    pub const CAPACITY: usize = 4096;
+
+    pub fn new() -> Self {
+        Self {
+            samples: iter::repeat_with(|| AtomicU32::new(0.0f32.to_bits()))
+                .take(Self::CAPACITY)
+                .collect(),
+            head: AtomicUsize::new(0),
+            sample_rate: AtomicU32::new(0),
+        }
+    }
+
+    /// Sample rate of the samples being played, or `0` if no audio stream has been started.
+    #[must_use]
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate.load(Ordering::Relaxed)
+    }
+
+    fn set_sample_rate(&self, sample_rate: u32) {
+        self.sample_rate.store(sample_rate, Ordering::Relaxed);
+    }
+
+    /// Push a sample, overwriting the oldest one. Only a single writer is supported.
+    fn push(&self, sample: f32) {
+        let head = self.head.load(Ordering::Relaxed);
+        self.samples[head % Self::CAPACITY].store(sample.to_bits(), Ordering::Relaxed);
+        self.head.store(head.wrapping_add(1), Ordering::Release);
+    }
+
+    /// Copy the most recent samples into `out`, oldest first, up to [`SampleTap::CAPACITY`].
+    /// Returns the total number of samples pushed so far, which callers can compare to see if any
+    /// new samples have been played.
+    pub fn read_latest(&self, out: &mut [f32]) -> usize {
+        let head = self.head.load(Ordering::Acquire);
+        let len = out.len().min(Self::CAPACITY);
+        let start = head.wrapping_sub(len);
+        for (i, sample) in out.iter_mut().take(len).enumerate() {
+            let index = start.wrapping_add(i) % Self::CAPACITY;
+            *sample = f32::from_bits(self.samples[index].load(Ordering::Relaxed));
+        }
+        head
+    }
+}
+
 #[must_use]
 pub struct Audio {
     pub enabled: bool,
+    pub backend: AudioBackend,
     pub sample_rate: f32,
     pub latency: Duration,
     pub buffer_size: usize,
     pub host: cpal::Host,
+    pub recording_limits: RecordingLimits,
+    tap: Arc<SampleTap>,
     output: Option<Output>,
+    recording: Option<Recording>,
 }
 
 impl std::fmt::Debug for Audio {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Audio")
             .field("enabled", &self.enabled)
+            .field("backend", &self.backend)
+            .field("host", &self.host.id())
             .field("sample_rate", &self.sample_rate)
             .field("latency", &self.latency)
             .field("buffer_size", &self.buffer_size)
+            .field("recording_limits", &self.recording_limits)
             .field("output", &self.output)
+            .field("recording", &self.recording)
             .finish_non_exhaustive()
     }
 }
 
 impl Audio {
-    /// Creates a new audio mixer.
+    /// Creates a new audio mixer. Played samples are written to `tap` for visualization.
     ///
     /// # Errors
     ///
     /// Returns an error if the audio device fails to be opened.
-    pub fn new(enabled: bool, mut sample_rate: f32, latency: Duration, buffer_size: usize) -> Self {
-        let host = cpal::default_host();
+    pub fn new(
+        enabled: bool,
+        backend: AudioBackend,
+        sample_rate: f32,
+        latency: Duration,
+        buffer_size: usize,
+        tap: Arc<SampleTap>,
+    ) -> Self {
+        let host = backend.host();
         let output = Output::create(&host, sample_rate, latency, buffer_size);
-        if let Some(output) = &output {
-            let desired_sample_rate = cpal::SampleRate(sample_rate as u32);
-            if output.config.sample_rate != desired_sample_rate {
-                sample_rate = output.config.sample_rate.0 as f32;
-                debug!(
-                    "Unable to match desired sample_rate: {}. Using {sample_rate} instead",
-                    desired_sample_rate.0
-                );
-            }
-        }
-        Self {
+        let mut audio = Self {
             enabled,
+            backend,
             sample_rate,
             latency,
             buffer_size,
             host,
+            recording_limits: RecordingLimits::default(),
+            tap,
             output,
+            recording: None,
+        };
+        audio.match_output_sample_rate();
+        audio
+    }
+
+    /// Use the output sample rate if the desired sample rate isn't supported.
+    fn match_output_sample_rate(&mut self) {
+        if let Some(output) = &self.output {
+            let desired_sample_rate = cpal::SampleRate(self.sample_rate as u32);
+            if output.config.sample_rate != desired_sample_rate {
+                self.sample_rate = output.config.sample_rate.0 as f32;
+                debug!(
+                    "Unable to match desired sample_rate: {}. Using {} instead",
+                    desired_sample_rate.0, self.sample_rate
+                );
+            }
         }
     }
 
@@ -109,14 +261,14 @@ impl Audio {
         }
     }
 
-    /// Processes generated audio samples.
-    pub fn process(&mut self, samples: &[f32]) {
+    /// Processes generated audio samples, interleaved by `input_channels`.
+    pub fn process(&mut self, samples: &[f32], input_channels: usize) {
         if let Some(mixer) = &mut self
             .output
             .as_mut()
             .and_then(|output| output.mixer.as_mut())
         {
-            mixer.process(samples);
+            mixer.process(samples, input_channels, &mut self.recording);
         }
     }
 
@@ -141,14 +293,47 @@ impl Audio {
             })
     }
 
-    /// Pause or resume the audio output stream. If `paused` is false and the stream is not started
-    /// yet, it will be started.
+    /// Returns how full the queue of samples waiting to be played is, from `0.0` to `1.0`.
+    #[must_use]
+    pub fn buffer_fill(&self) -> f32 {
+        self.output
+            .as_ref()
+            .and_then(|output| output.mixer.as_ref())
+            .map_or(0.0, |mixer| {
+                mixer.producer.occupied_len() as f32 / mixer.producer.capacity().get() as f32
+            })
+    }
+
+    /// Suspend the audio output stream while paused once all queued samples have finished
+    /// playing, so the audio device isn't kept busy while idle. Once idle for a few seconds, the
+    /// stream is closed entirely and reopened on resume. Returns whether the stream is suspended
+    /// or there is no stream to suspend.
+    pub fn try_suspend(&mut self) -> bool {
+        self.output.as_mut().map_or(true, Output::try_suspend)
+    }
+
+    /// Time remaining until a suspended audio output stream is closed, if one is open.
+    pub fn close_timeout(&self) -> Option<Duration> {
+        self.output
+            .as_ref()
+            .and_then(|output| output.mixer.as_ref())
+            .and_then(|mixer| mixer.idle_time())
+            .map(|idle_time| Output::CLOSE_TIMEOUT.saturating_sub(idle_time))
+    }
+
+    /// Pause or resume the audio output stream. If `paused` is false and the stream was closed
+    /// while idle, it will be reopened.
     pub fn pause(&mut self, paused: bool) {
-        if let Some(mixer) = &mut self
-            .output
-            .as_mut()
-            .and_then(|output| output.mixer.as_mut())
-        {
+        let Some(output) = &mut self.output else {
+            return;
+        };
+        if !paused && output.idle_closed && self.enabled {
+            debug!("reopening idle audio stream");
+            if let Err(err) = output.start(&self.tap) {
+                error!("failed to reopen audio stream: {err:?}");
+            }
+        }
+        if let Some(mixer) = &mut output.mixer {
             mixer.pause(paused);
         }
     }
@@ -160,6 +345,18 @@ impl Audio {
         self.start()
     }
 
+    /// Set the audio host API used for output. Requires restarting the audio stream and so may
+    /// fail. The sample rate may change to match the new output device.
+    pub fn set_backend(&mut self, backend: AudioBackend) -> anyhow::Result<State> {
+        self.backend = backend;
+        let _ = self.stop();
+        self.host = backend.host();
+        info!("using audio host: {}", self.host.id().name());
+        self.output = Output::create(&self.host, self.sample_rate, self.latency, self.buffer_size);
+        self.match_output_sample_rate();
+        self.start()
+    }
+
     /// Set the output sample rate that the audio device uses. Requires restarting the audio stream
     /// and so may fail.
     pub fn set_sample_rate(&mut self, sample_rate: f32) -> anyhow::Result<State> {
@@ -181,33 +378,33 @@ impl Audio {
         self.recreate_output()
     }
 
-    /// Whether the mixer is currently recording samples to a file.
+    /// Whether audio is currently being recorded to a file.
     pub fn is_recording(&self) -> bool {
-        self.output
-            .as_ref()
-            .and_then(|output| output.mixer.as_ref())
-            .is_some_and(|mixer| mixer.recording.is_some())
+        self.recording.is_some()
     }
 
-    /// Start recording audio to a file.
-    pub fn start_recording(&mut self) -> anyhow::Result<()> {
-        if let Some(mixer) = &mut self
-            .output
-            .as_mut()
-            .and_then(|output| output.mixer.as_mut())
-        {
-            mixer.start_recording()
-        } else {
-            Ok(())
-        }
+    /// Start recording audio for the game named `title` to a file in `dir`. The recording
+    /// continues across pauses and audio stream restarts until stopped.
+    pub fn start_recording(&mut self, dir: &Path, title: &str) -> anyhow::Result<()> {
+        let _ = self.stop_recording();
+        self.recording = Some(Recording::start(dir, title, self.recording_limits)?);
+        Ok(())
     }
 
-    /// Stop recording audio to a file.
-    pub fn stop_recording(&mut self) -> anyhow::Result<Option<PathBuf>> {
-        self.output
-            .as_mut()
-            .and_then(|output| output.mixer.as_mut())
-            .map_or(Ok(None), |mixer| mixer.stop_recording())
+    /// Stop recording audio, returning the paths of each file written.
+    pub fn stop_recording(&mut self) -> anyhow::Result<Vec<PathBuf>> {
+        self.recording
+            .take()
+            .map_or(Ok(Vec::new()), Recording::finish)
+    }
+
+    /// Set the limits at which recordings are split into a new file, including the current
+    /// recording.
+    pub fn set_recording_limits(&mut self, limits: RecordingLimits) {
+        self.recording_limits = limits;
+        if let Some(recording) = &mut self.recording {
+            recording.set_limits(limits);
+        }
     }
 
     /// Start the audio output stream. Returns [`State`] representing the state of the audio stream.
@@ -218,7 +415,7 @@ impl Audio {
     pub fn start(&mut self) -> anyhow::Result<State> {
         if self.enabled {
             if let Some(output) = &mut self.output {
-                output.start()?;
+                output.start(&self.tap)?;
                 Ok(State::Started)
             } else {
                 Ok(State::NoOutputDevice)
@@ -276,6 +473,8 @@ struct Output {
     sample_format: cpal::SampleFormat,
     latency: Duration,
     mixer: Option<Mixer>,
+    /// Whether the mixer was closed after being suspended for a while, to be reopened on resume.
+    idle_closed: bool,
 }
 
 impl std::fmt::Debug for Output {
@@ -320,6 +519,7 @@ impl Output {
             sample_format,
             latency,
             mixer: None,
+            idle_closed: false,
         })
     }
 
@@ -381,7 +581,12 @@ impl Output {
         Ok((config, sample_format))
     }
 
-    fn start(&mut self) -> anyhow::Result<()> {
+    /// How long the stream stays suspended before being closed, releasing the audio device.
+    /// Reopening takes longer than resuming, so brief pauses keep the stream open.
+    const CLOSE_TIMEOUT: Duration = Duration::from_secs(5);
+
+    fn start(&mut self, tap: &Arc<SampleTap>) -> anyhow::Result<()> {
+        self.idle_closed = false;
         if let Some(ref mixer) = self.mixer {
             mixer.stream.play()?;
             return Ok(());
@@ -393,39 +598,61 @@ impl Output {
             &self.config,
             self.latency,
             self.sample_format,
+            Arc::clone(tap),
         )?);
         Ok(())
     }
 
     fn stop(&mut self) {
+        self.idle_closed = false;
         if let Some(mut mixer) = self.mixer.take() {
             mixer.pause(true);
         }
     }
+
+    fn try_suspend(&mut self) -> bool {
+        let Some(mixer) = &mut self.mixer else {
+            return true;
+        };
+        if !mixer.try_suspend() {
+            return false;
+        }
+        if mixer
+            .idle_time()
+            .is_some_and(|idle_time| idle_time >= Self::CLOSE_TIMEOUT)
+        {
+            debug!("closing idle audio stream");
+            self.mixer = None;
+            self.idle_closed = true;
+        }
+        true
+    }
 }
 
 #[must_use]
 pub(crate) struct Mixer {
     stream: cpal::Stream,
     paused: bool,
+    suspended: bool,
+    drained_time: Option<Instant>,
+    latency: Duration,
     channels: u16,
     sample_rate: u32,
     sample_latency: usize,
     producer: SampleProducer,
     processed_samples: Vec<f32>,
-    recording: Option<(PathBuf, hound::WavWriter<BufWriter<File>>)>,
 }
 
 impl std::fmt::Debug for Mixer {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Audio")
             .field("paused", &self.paused)
+            .field("suspended", &self.suspended)
             .field("channels", &self.channels)
             .field("sample_rate", &self.sample_rate)
             .field("sample_latency", &self.sample_latency)
             .field("queued_len", &self.producer.occupied_len())
             .field("processed_len", &self.processed_samples.len())
-            .field("recording", &self.recording.is_some())
             .finish_non_exhaustive()
     }
 }
@@ -436,11 +663,13 @@ impl Mixer {
         config: &cpal::StreamConfig,
         latency: Duration,
         sample_format: cpal::SampleFormat,
+        tap: Arc<SampleTap>,
     ) -> anyhow::Result<Self> {
         use cpal::SampleFormat;
 
         let channels = config.channels;
         let sample_rate = config.sample_rate.0;
+        tap.set_sample_rate(sample_rate);
         let sample_latency =
             (latency.as_secs_f32() * sample_rate as f32 * channels as f32).ceil() as usize;
         let processed_samples = Vec::with_capacity(2 * sample_latency);
@@ -448,16 +677,16 @@ impl Mixer {
         let (producer, consumer) = buffer.split();
 
         let stream = match sample_format {
-            SampleFormat::I8 => Self::make_stream::<i8>(device, config, consumer),
-            SampleFormat::I16 => Self::make_stream::<i16>(device, config, consumer),
-            SampleFormat::I32 => Self::make_stream::<i32>(device, config, consumer),
-            SampleFormat::I64 => Self::make_stream::<i64>(device, config, consumer),
-            SampleFormat::U8 => Self::make_stream::<u8>(device, config, consumer),
-            SampleFormat::U16 => Self::make_stream::<u16>(device, config, consumer),
-            SampleFormat::U32 => Self::make_stream::<u32>(device, config, consumer),
-            SampleFormat::U64 => Self::make_stream::<u64>(device, config, consumer),
-            SampleFormat::F32 => Self::make_stream::<f32>(device, config, consumer),
-            SampleFormat::F64 => Self::make_stream::<f64>(device, config, consumer),
+            SampleFormat::I8 => Self::make_stream::<i8>(device, config, consumer, tap),
+            SampleFormat::I16 => Self::make_stream::<i16>(device, config, consumer, tap),
+            SampleFormat::I32 => Self::make_stream::<i32>(device, config, consumer, tap),
+            SampleFormat::I64 => Self::make_stream::<i64>(device, config, consumer, tap),
+            SampleFormat::U8 => Self::make_stream::<u8>(device, config, consumer, tap),
+            SampleFormat::U16 => Self::make_stream::<u16>(device, config, consumer, tap),
+            SampleFormat::U32 => Self::make_stream::<u32>(device, config, consumer, tap),
+            SampleFormat::U64 => Self::make_stream::<u64>(device, config, consumer, tap),
+            SampleFormat::F32 => Self::make_stream::<f32>(device, config, consumer, tap),
+            SampleFormat::F64 => Self::make_stream::<f64>(device, config, consumer, tap),
             sample_format => Err(anyhow!("Unsupported sample format {sample_format}")),
         }?;
         stream.play()?;
@@ -465,12 +694,14 @@ impl Mixer {
         Ok(Self {
             stream,
             paused: false,
+            suspended: false,
+            drained_time: None,
+            latency,
             channels,
             sample_rate,
             sample_latency,
             producer,
             processed_samples,
-            recording: None,
         })
     }
 
@@ -478,72 +709,62 @@ impl Mixer {
     /// yet, it will be started.
     fn pause(&mut self, paused: bool) {
         if paused && !self.paused {
-            let _ = self.stop_recording();
             self.processed_samples.clear();
-            // FIXME: Currently cpal doesn't let the underyling audio device empty samples before
-            // pausing which leads to the remaining audio playing again upon resume. The only work
-            // around is to leave the stream playing
-            // if let Err(err) = self.stream.pause() {
-            //     error!("failed to pause audio stream: {err:?}");
-            // }
+            // cpal doesn't let the underlying audio device empty samples before pausing which
+            // leads to the remaining audio playing again upon resume, so the stream is left
+            // playing until drained. See `try_suspend`.
         } else if !paused && self.paused {
-            // if let Err(err) = self.stream.play() {
-            //     error!("failed to resume audio stream: {err:?}");
-            // }
+            if self.suspended {
+                if let Err(err) = self.stream.play() {
+                    error!("failed to resume audio stream: {err:?}");
+                }
+                self.suspended = false;
+            }
+            self.drained_time = None;
         }
         self.paused = paused;
     }
 
-    fn start_recording(&mut self) -> anyhow::Result<()> {
-        let _ = self.stop_recording();
-        let path = Config::default_audio_dir()
-            .join(
-                chrono::Local::now()
-                    .format("recording_%Y-%m-%d_at_%H_%M_%S")
-                    .to_string(),
-            )
-            .with_extension("wav");
-        if let Some(parent) = path.parent() {
-            if !parent.exists() {
-                std::fs::create_dir_all(parent).with_context(|| {
-                    format!(
-                        "failed to create audio recording directory: {}",
-                        parent.display()
-                    )
-                })?;
-            }
-        }
-        let spec = hound::WavSpec {
-            channels: self.channels,
-            sample_rate: self.sample_rate,
-            bits_per_sample: 32,
-            sample_format: hound::SampleFormat::Float,
-        };
-        let writer =
-            hound::WavWriter::create(&path, spec).context("failed to create audio recording")?;
-        self.recording = Some((path, writer));
-        Ok(())
+    /// Time since queued samples finished playing while paused.
+    fn idle_time(&self) -> Option<Duration> {
+        self.drained_time.map(|drained_time| drained_time.elapsed())
     }
 
-    fn stop_recording(&mut self) -> anyhow::Result<Option<PathBuf>> {
-        if let Some((path, mut recording)) = self.recording.take() {
-            match recording.flush() {
-                Ok(_) => Ok(Some(path)),
-                Err(err) => Err(anyhow!("failed to flush audio recording: {err:?}")),
-            }
-        } else {
-            Ok(None)
+    fn try_suspend(&mut self) -> bool {
+        if !self.paused {
+            return false;
+        }
+        if self.suspended {
+            return true;
         }
+        // Wait for queued samples to finish playing and for the device to be filled with silence
+        // before pausing the stream
+        if !self.producer.is_empty() {
+            self.drained_time = None;
+            return false;
+        }
+        let drained_time = *self.drained_time.get_or_insert_with(Instant::now);
+        if drained_time.elapsed() < self.latency {
+            return false;
+        }
+        debug!("suspending idle audio stream");
+        if let Err(err) = self.stream.pause() {
+            error!("failed to pause audio stream: {err:?}");
+        }
+        self.suspended = true;
+        true
     }
 
     fn make_stream<T>(
         device: &cpal::Device,
         config: &cpal::StreamConfig,
         mut consumer: SampleConsumer,
+        tap: Arc<SampleTap>,
     ) -> anyhow::Result<cpal::Stream>
     where
         T: cpal::SizedSample + cpal::FromSample<f32>,
     {
+        let channels = usize::from(config.channels);
         Ok(device.build_output_stream(
             config,
             move |out: &mut [T], _info| {
@@ -558,11 +779,16 @@ impl Mixer {
                     );
                 }
 
-                for (sample, value) in out
+                for (i, (sample, value)) in out
                     .iter_mut()
                     .zip(consumer.pop_iter().chain(iter::repeat(0.0)))
+                    .enumerate()
                 {
                     *sample = T::from_sample(value);
+                    // Only the first (left) channel is visualized
+                    if i % channels == 0 {
+                        tap.push(value);
+                    }
                 }
             },
             |err| error!("an error occurred on stream: {err}"),
@@ -570,23 +796,40 @@ impl Mixer {
         )?)
     }
 
-    fn process(&mut self, samples: &[f32]) {
+    /// Processes generated audio samples, writing them to `recording` as well if set. Nothing
+    /// is processed while paused, so recordings don't contain the silence in between.
+    fn process(
+        &mut self,
+        samples: &[f32],
+        input_channels: usize,
+        recording: &mut Option<Recording>,
+    ) {
         #[cfg(feature = "profiling")]
         puffin::profile_function!();
 
         if self.paused {
             return;
         }
-        for sample in samples {
-            for _ in 0..self.channels {
-                self.processed_samples.push(*sample);
+        let input_channels = input_channels.max(1);
+        let processed_start = self.processed_samples.len();
+        for frame in samples.chunks_exact(input_channels) {
+            for channel in 0..usize::from(self.channels) {
+                // Stereo input is averaged for mono output, otherwise input channels repeat
+                // across output channels
+                let sample = if self.channels == 1 {
+                    frame.iter().sum::<f32>() / input_channels as f32
+                } else {
+                    frame[channel % input_channels]
+                };
+                self.processed_samples.push(sample);
             }
-            if let Some((_, recording)) = &mut self.recording {
-                // TODO: push slice to recording thread
-                if let Err(err) = recording.write_sample(*sample) {
-                    error!("failed to write audio sample: {err:?}");
-                    let _ = self.stop_recording();
-                }
+        }
+        if let Some(rec) = recording {
+            // TODO: push slice to recording thread
+            let processed = &self.processed_samples[processed_start..];
+            if let Err(err) = rec.write(processed, self.channels, self.sample_rate) {
+                error!("failed to write audio recording: {err:?}");
+                *recording = None;
             }
         }
         let processed_len = self.processed_samples.len();
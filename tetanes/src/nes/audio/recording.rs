@@ -0,0 +1,231 @@
+//! Audio recording to WAV files.
+//!
+//! Recordings only receive samples while the emulator is running, so pausing and resuming
+//! continues the same file without a silent gap. Recordings can be split into numbered parts
+//! once a part reaches a configured duration or file size, and each part is tagged with the game
+//! title and recording date in a RIFF `LIST`/`INFO` chunk, the WAV equivalent of ID3 tags.
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, BufWriter, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+};
+use tracing::{debug, error};
+
+/// Limits at which an audio recording is split into a new part. A value of `0` means no limit.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[must_use]
+#[serde(default)]
+pub struct RecordingLimits {
+    /// Maximum length of a part, in minutes.
+    pub max_minutes: u32,
+    /// Maximum size of a part, in megabytes.
+    pub max_megabytes: u32,
+}
+
+impl RecordingLimits {
+    /// Size of the WAV header written before samples.
+    const HEADER_LEN: u64 = 44;
+
+    /// Whether a part with `samples` interleaved samples written in `spec` has reached a limit.
+    fn reached(&self, samples: u64, spec: hound::WavSpec) -> bool {
+        let frames = samples / u64::from(spec.channels.max(1));
+        let bytes = Self::HEADER_LEN + samples * u64::from(spec.bits_per_sample / 8);
+        (self.max_minutes > 0
+            && frames >= u64::from(self.max_minutes) * 60 * u64::from(spec.sample_rate))
+            || (self.max_megabytes > 0 && bytes >= u64::from(self.max_megabytes) * 1024 * 1024)
+    }
+}
+
+/// An audio recording in progress, written as one or more WAV parts.
+#[must_use]
+pub struct Recording {
+    dir: PathBuf,
+    name: String,
+    title: String,
+    date: String,
+    limits: RecordingLimits,
+    part: Option<Part>,
+    paths: Vec<PathBuf>,
+}
+
+/// The WAV file currently being written.
+struct Part {
+    path: PathBuf,
+    spec: hound::WavSpec,
+    writer: hound::WavWriter<BufWriter<File>>,
+    samples: u64,
+}
+
+impl std::fmt::Debug for Recording {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Recording")
+            .field("dir", &self.dir)
+            .field("name", &self.name)
+            .field("title", &self.title)
+            .field("limits", &self.limits)
+            .field("parts", &self.paths.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Recording {
+    /// Start a recording in `dir` for the game named `title`. Files are created once the first
+    /// samples are written, since the output format isn't known until then.
+    pub fn start(dir: &Path, title: &str, limits: RecordingLimits) -> anyhow::Result<Self> {
+        if !dir.exists() {
+            std::fs::create_dir_all(dir).with_context(|| {
+                format!(
+                    "failed to create audio recording directory: {}",
+                    dir.display()
+                )
+            })?;
+        }
+        let now = chrono::Local::now();
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            name: now.format("recording_%Y-%m-%d_at_%H_%M_%S").to_string(),
+            title: title.to_string(),
+            date: now.format("%Y-%m-%d").to_string(),
+            limits,
+            part: None,
+            paths: Vec::new(),
+        })
+    }
+
+    pub fn set_limits(&mut self, limits: RecordingLimits) {
+        self.limits = limits;
+    }
+
+    /// Write interleaved samples. A new part is started if the output format changed or the
+    /// current part reached a limit.
+    pub fn write(
+        &mut self,
+        samples: &[f32],
+        channels: u16,
+        sample_rate: u32,
+    ) -> anyhow::Result<()> {
+        let spec = hound::WavSpec {
+            channels,
+            sample_rate,
+            bits_per_sample: 32,
+            sample_format: hound::SampleFormat::Float,
+        };
+        if self
+            .part
+            .as_ref()
+            .is_some_and(|part| part.spec != spec || self.limits.reached(part.samples, part.spec))
+        {
+            self.finish_part()?;
+        }
+        let part = match self.part.take() {
+            Some(part) => part,
+            None => self.create_part(spec)?,
+        };
+        let part = self.part.insert(part);
+        for sample in samples {
+            part.writer
+                .write_sample(*sample)
+                .context("failed to write audio sample")?;
+        }
+        part.samples += samples.len() as u64;
+        Ok(())
+    }
+
+    /// Finish the recording, returning the paths of all parts written.
+    pub fn finish(mut self) -> anyhow::Result<Vec<PathBuf>> {
+        self.finish_part()?;
+        Ok(std::mem::take(&mut self.paths))
+    }
+
+    fn create_part(&mut self, spec: hound::WavSpec) -> anyhow::Result<Part> {
+        let number = self.paths.len() + 1;
+        let name = if number == 1 {
+            self.name.clone()
+        } else {
+            format!("{}_part{number}", self.name)
+        };
+        let path = self.dir.join(name).with_extension("wav");
+        debug!("starting audio recording part: {}", path.display());
+        let writer =
+            hound::WavWriter::create(&path, spec).context("failed to create audio recording")?;
+        self.paths.push(path.clone());
+        Ok(Part {
+            path,
+            spec,
+            writer,
+            samples: 0,
+        })
+    }
+
+    fn finish_part(&mut self) -> anyhow::Result<()> {
+        let Some(part) = self.part.take() else {
+            return Ok(());
+        };
+        part.writer
+            .finalize()
+            .context("failed to finalize audio recording")?;
+
+        let title = match self.paths.len() {
+            1 if self.title.is_empty() => String::from("Audio Recording"),
+            1 => self.title.clone(),
+            number if self.title.is_empty() => format!("Audio Recording (Part {number})"),
+            number => format!("{} (Part {number})", self.title),
+        };
+        let software = concat!("TetaNES ", env!("CARGO_PKG_VERSION"));
+        let mut tags = vec![
+            (b"INAM", title.as_str()),
+            (b"ICRD", self.date.as_str()),
+            (b"ISFT", software),
+        ];
+        if !self.title.is_empty() {
+            tags.push((b"IPRD", self.title.as_str()));
+        }
+        write_info_chunk(&part.path, &tags).with_context(|| {
+            format!(
+                "failed to write audio recording metadata: {}",
+                part.path.display()
+            )
+        })
+    }
+}
+
+impl Drop for Recording {
+    fn drop(&mut self) {
+        if let Err(err) = self.finish_part() {
+            error!("{err:?}");
+        }
+    }
+}
+
+/// Append a `LIST`/`INFO` chunk with the given tags to a finalized WAV file.
+fn write_info_chunk(path: &Path, tags: &[(&[u8; 4], &str)]) -> io::Result<()> {
+    let mut info = b"INFO".to_vec();
+    for (id, value) in tags {
+        // Values are NUL-terminated and chunks are padded to an even length
+        let len = value.len() + 1;
+        info.extend_from_slice(*id);
+        info.extend_from_slice(&(len as u32).to_le_bytes());
+        info.extend_from_slice(value.as_bytes());
+        info.push(0);
+        if len % 2 == 1 {
+            info.push(0);
+        }
+    }
+
+    let mut file = OpenOptions::new().read(true).write(true).open(path)?;
+    if file.seek(SeekFrom::End(0))? % 2 == 1 {
+        file.write_all(&[0])?;
+    }
+    file.write_all(b"LIST")?;
+    file.write_all(&(info.len() as u32).to_le_bytes())?;
+    file.write_all(&info)?;
+
+    // Update the RIFF size to include the new chunk
+    let riff_len = file.stream_position()? - 8;
+    file.seek(SeekFrom::Start(4))?;
+    file.write_all(&(riff_len as u32).to_le_bytes())?;
+    file.flush()
+}
@@ -1,32 +1,54 @@
-use crate::nes::{
-    action::Action,
-    input::{ActionBindings, Gamepads, Input},
-    renderer::shader::Shader,
+use crate::{
+    feature,
+    nes::{
+        action::Action,
+        audio::{recording::RecordingLimits, AudioBackend},
+        emulation::ram_watch::RamWatch,
+        input::{ActionBindings, Gamepads, Input, InputDevice, ShortcutPreset},
+        renderer::{
+            painter::PresentMode,
+            shader::{ColorBlindMode, Shader, Transition},
+            texture::{Rotation, TextureFilter},
+        },
+    },
+};
+use anyhow::{bail, Context};
+use egui::{ahash::HashSet, Color32};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    sync::OnceLock,
 };
-use anyhow::Context;
-use egui::ahash::HashSet;
-use serde::{Deserialize, Serialize};
-use std::{collections::BTreeMap, path::PathBuf};
 use tetanes_core::{
     action::Action as DeckAction, common::NesRegion, control_deck::Config as DeckConfig, fs,
-    input::Player, ppu::Ppu, time::Duration,
+    input::Player, ppu::Ppu, time::Duration, video::upscale::UpscaleFilter,
 };
-use tracing::{error, info};
+use tracing::{error, info, warn};
 use uuid::Uuid;
+use winit::event::DeviceId;
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[must_use]
 #[serde(default)] // Ensures new fields don't break existing configurations
 pub struct AudioConfig {
     pub enabled: bool,
+    pub backend: AudioBackend,
     pub buffer_size: usize,
     pub latency: Duration,
+    /// Names of ROMs to reduce pops from DMC output level writes for.
+    pub dmc_click_reduction: BTreeSet<String>,
+    /// Limits at which audio recordings are split into a new file.
+    pub recording_limits: RecordingLimits,
 }
 
 impl Default for AudioConfig {
     fn default() -> Self {
         Self {
             enabled: true,
+            backend: AudioBackend::default(),
             buffer_size: if cfg!(target_arch = "wasm32") {
                 // Too low a value for wasm causes audio underruns in Chrome
                 2048
@@ -38,6 +60,8 @@ impl Default for AudioConfig {
             } else {
                 Duration::from_millis(50)
             },
+            dmc_click_reduction: BTreeSet::new(),
+            recording_limits: RecordingLimits::default(),
         }
     }
 }
@@ -49,12 +73,27 @@ pub struct EmulationConfig {
     pub auto_load: bool,
     pub auto_save: bool,
     pub auto_save_interval: Duration,
+    pub fast_forward_frame_skip: u32,
+    pub fast_forward_speed: f32,
+    pub fast_forward_toggle: bool,
+    pub fast_forward_uncapped: bool,
     pub rewind: bool,
     pub rewind_seconds: u32,
     pub rewind_interval: u32,
     pub run_ahead: usize,
     pub save_slot: u8,
+    /// The save slot used before `save_slot`, swapped back to with `SwapSaveSlot`.
+    pub previous_save_slot: Option<u8>,
+    /// Number of states kept in the quick save state ring.
+    pub save_ring_len: usize,
+    /// Number of frames saved by a screenshot burst, or `0` to save frames while the key is held.
+    pub screenshot_burst_frames: u32,
+    /// Number of frames between the emulation state hashes recorded with replays, used to detect
+    /// desyncs on playback. Hashing serializes the entire emulation state, so lower intervals
+    /// cost more.
+    pub replay_hash_interval: u32,
     pub speed: f32,
+    pub sync_to_display: bool,
     pub threaded: bool,
 }
 
@@ -64,6 +103,10 @@ impl Default for EmulationConfig {
             auto_load: true,
             auto_save: true,
             auto_save_interval: Duration::from_secs(5),
+            fast_forward_frame_skip: 0,
+            fast_forward_speed: 2.0,
+            fast_forward_toggle: false,
+            fast_forward_uncapped: false,
             rewind: true,
             rewind_seconds: 30,
             rewind_interval: 2,
@@ -75,57 +118,288 @@ impl Default for EmulationConfig {
                 1
             },
             save_slot: 1,
+            previous_save_slot: None,
+            save_ring_len: 5,
+            screenshot_burst_frames: 60,
+            replay_hash_interval: 60,
             speed: 1.0,
+            sync_to_display: true,
             threaded: true,
         }
     }
 }
 
+impl EmulationConfig {
+    /// Set the active save slot, remembering the previous one. Returns whether it changed.
+    pub fn set_save_slot(&mut self, slot: u8) -> bool {
+        if self.save_slot == slot {
+            return false;
+        }
+        self.previous_save_slot = Some(self.save_slot);
+        self.save_slot = slot;
+        true
+    }
+
+    /// The slot `SwapSaveSlot` switches to, if a different slot was used before.
+    pub fn swap_save_slot(&self) -> Option<u8> {
+        self.previous_save_slot
+            .filter(|slot| *slot != self.save_slot)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[must_use]
 #[serde(default)] // Ensures new fields don't break existing configurations
 pub struct RendererConfig {
     pub fullscreen: bool,
+    pub fullscreen_target: FullscreenTarget,
+    /// Main window geometry from the last run, restored on startup.
+    pub window_geometry: Option<WindowGeometry>,
     pub always_on_top: bool,
+    /// Layout for streaming: hides the menu bar, keeps the main window on top and sizes it to
+    /// the NES frame at an integer scale.
+    pub compact_mode: bool,
     pub hide_overscan: bool,
+    pub rotation: Rotation,
+    pub flip_horizontal: bool,
+    /// Draw a black line between each NES line, like a 240p signal on a CRT.
+    pub scanlines: bool,
+    /// Show a black frame after each NES frame for motion clarity on 120Hz displays.
+    pub black_frame_insertion: bool,
     pub scale: f32,
     pub zoom: f32,
-    pub recent_roms: HashSet<PathBuf>,
+    pub font_size: FontSize,
+    pub recent_roms: RecentRoms,
     pub roms_path: Option<PathBuf>,
+    /// Whether to watch the ROM directory in the background to keep the ROM library up to date.
+    pub watch_roms_path: bool,
     pub show_perf_stats: bool,
     pub show_messages: bool,
+    /// Speak messages out loud, such as saving states or errors.
+    pub announce_messages: bool,
     pub show_menubar: bool,
     pub embed_viewports: bool,
     pub dark_theme: bool,
+    pub high_contrast: bool,
+    pub theme: ThemePreset,
+    /// Custom color for highlights, overriding the theme.
+    pub accent_color: Option<Color32>,
     pub shader: Shader,
+    /// Pixel-art upscaling applied to NES frames before they're drawn.
+    pub upscale_filter: UpscaleFilter,
+    /// How NES frames are sampled when the window isn't an integer multiple of the frame size.
+    pub texture_filter: TextureFilter,
+    pub color_blind_mode: ColorBlindMode,
+    /// Effect played when loading a ROM, resetting or loading a state.
+    pub transition: Transition,
+    pub present_mode: PresentMode,
+    pub ram_watches: Vec<RamWatch>,
+    /// Whether to keep a local count of unsupported mappers for exporting as a report.
+    pub mapper_stats: bool,
+    /// Minutes idle on the ROM library screen before playing back a random recent ROM, or `0`
+    /// to disable attract mode.
+    pub attract_mode_minutes: u32,
+    /// How often to check for a newer release on startup. Disabled by default, as checking
+    /// contacts GitHub.
+    pub update_check: UpdateCheck,
+    /// Unix timestamp in seconds of the last update check, or `0` if never checked.
+    pub last_update_check: i64,
 }
 
 impl Default for RendererConfig {
     fn default() -> Self {
         Self {
             fullscreen: false,
+            fullscreen_target: FullscreenTarget::default(),
+            window_geometry: None,
             always_on_top: false,
+            compact_mode: false,
             hide_overscan: true,
+            rotation: Rotation::default(),
+            flip_horizontal: false,
+            scanlines: false,
+            black_frame_insertion: false,
             scale: 3.0,
             zoom: 1.0,
-            recent_roms: HashSet::default(),
+            font_size: FontSize::default(),
+            recent_roms: RecentRoms::default(),
             roms_path: std::env::current_dir().ok(),
+            watch_roms_path: true,
             show_perf_stats: false,
             show_messages: true,
+            announce_messages: false,
             show_menubar: true,
             embed_viewports: false,
             dark_theme: true,
+            high_contrast: false,
+            theme: ThemePreset::default(),
+            accent_color: None,
             shader: Shader::default(),
+            upscale_filter: UpscaleFilter::default(),
+            texture_filter: TextureFilter::default(),
+            color_blind_mode: ColorBlindMode::default(),
+            transition: Transition::default(),
+            present_mode: PresentMode::default(),
+            ram_watches: Vec::new(),
+            mapper_stats: false,
+            attract_mode_minutes: 0,
+            update_check: UpdateCheck::default(),
+            last_update_check: 0,
         }
     }
 }
 
+/// A monitor video mode used for exclusive fullscreen.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[must_use]
+pub struct VideoMode {
+    pub width: u32,
+    pub height: u32,
+    pub refresh_rate_millihertz: u32,
+}
+
+impl std::fmt::Display for VideoMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}x{} @ {:.2} Hz",
+            self.width,
+            self.height,
+            self.refresh_rate_millihertz as f32 / 1000.0
+        )
+    }
+}
+
+/// Which monitor and mode to use when fullscreen.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[must_use]
+#[serde(default)] // Ensures new fields don't break existing configurations
+pub struct FullscreenTarget {
+    /// Name of the monitor to go fullscreen on, or the monitor the window is on if `None`.
+    pub monitor: Option<String>,
+    /// Switch the monitor video mode instead of using a borderless window, for the lowest latency.
+    pub exclusive: bool,
+    /// Video mode to switch to when `exclusive`, or the best mode of the monitor if `None`.
+    pub video_mode: Option<VideoMode>,
+}
+
+/// Position and size of the main window when not fullscreen.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[must_use]
+pub struct WindowGeometry {
+    /// Outer position, in physical pixels.
+    pub position: Option<(i32, i32)>,
+    /// Inner size, in physical pixels.
+    pub size: (u32, u32),
+    pub maximized: bool,
+    /// Name of the monitor the window was on.
+    pub monitor: Option<String>,
+}
+
+/// A recently loaded ROM.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[must_use]
+pub struct RecentRom {
+    pub path: PathBuf,
+    /// When the ROM was last loaded, in seconds since the Unix epoch. `0` if unknown.
+    pub last_played: i64,
+    pub pinned: bool,
+}
+
+impl RecentRom {
+    /// When the ROM was last loaded, formatted in local time.
+    pub fn last_played_text(&self) -> Option<String> {
+        (self.last_played > 0)
+            .then(|| chrono::DateTime::from_timestamp(self.last_played, 0))
+            .flatten()
+            .map(|time| {
+                time.with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M")
+                    .to_string()
+            })
+    }
+}
+
+/// Recently loaded ROMs, ordered with pinned ROMs first, then by most recently played.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize)]
+#[must_use]
+pub struct RecentRoms(Vec<RecentRom>);
+
+impl<'de> Deserialize<'de> for RecentRoms {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let mut roms = Self(Vec::deserialize(deserializer)?);
+        roms.sort();
+        Ok(roms)
+    }
+}
+
+impl RecentRoms {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &RecentRom> {
+        self.0.iter()
+    }
+
+    /// Add `path` as the most recently played ROM, or update it if it already exists.
+    pub fn insert(&mut self, path: PathBuf) {
+        let last_played = chrono::Local::now().timestamp();
+        match self.0.iter_mut().find(|rom| rom.path == path) {
+            Some(rom) => rom.last_played = last_played,
+            None => self.0.push(RecentRom {
+                path,
+                last_played,
+                pinned: false,
+            }),
+        }
+        self.sort();
+    }
+
+    pub fn remove(&mut self, path: &Path) {
+        self.0.retain(|rom| rom.path != path);
+    }
+
+    pub fn set_pinned(&mut self, path: &Path, pinned: bool) {
+        if let Some(rom) = self.0.iter_mut().find(|rom| rom.path == path) {
+            rom.pinned = pinned;
+            self.sort();
+        }
+    }
+
+    /// Remove all ROMs that aren't pinned.
+    pub fn clear(&mut self) {
+        self.0.retain(|rom| rom.pinned);
+    }
+
+    /// Remove all ROMs whose files no longer exist.
+    pub fn remove_missing(&mut self) {
+        self.0.retain(|rom| rom.path.is_file());
+    }
+
+    fn sort(&mut self) {
+        self.0
+            .sort_by_key(|rom| std::cmp::Reverse((rom.pinned, rom.last_played)));
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[must_use]
 #[serde(default)] // Ensures new fields don't break existing configurations
 pub struct InputConfig {
     pub action_bindings: Vec<ActionBindings>,
+    pub profile: String,
+    pub profiles: BTreeSet<String>,
     pub gamepad_assignments: [(Player, Option<Uuid>); 4],
+    /// Keyboard IDs only last for the current session, so keyboard assignments aren't saved.
+    #[serde(skip)]
+    pub keyboard_assignments: [(Player, Option<DeviceId>); 4],
+    /// Only let assigned keyboards and gamepads control the joypad of the player they're
+    /// assigned to.
+    pub exclusive_devices: bool,
     #[serde(skip)]
     pub shortcuts: BTreeMap<Action, ActionBindings>,
     #[serde(skip)]
@@ -145,16 +419,131 @@ impl Default for InputConfig {
 
         Self {
             action_bindings,
+            profile: Self::DEFAULT_PROFILE.to_string(),
+            profiles: BTreeSet::from([Self::DEFAULT_PROFILE.to_string()]),
             shortcuts,
             joypads,
             gamepad_assignments: std::array::from_fn(|i| {
                 (Player::try_from(i).expect("valid player assignment"), None)
             }),
+            keyboard_assignments: std::array::from_fn(|i| {
+                (Player::try_from(i).expect("valid player assignment"), None)
+            }),
+            exclusive_devices: false,
         }
     }
 }
 
 impl InputConfig {
+    pub const DEFAULT_PROFILE: &'static str = "Default";
+
+    /// Replace all action bindings, rebuilding the shortcut and joypad lookups.
+    pub fn set_action_bindings(&mut self, action_bindings: Vec<ActionBindings>) {
+        self.shortcuts.clear();
+        for joypad in &mut self.joypads {
+            joypad.clear();
+        }
+        for binding in &action_bindings {
+            if let Action::Deck(DeckAction::Joypad((player, _))) = binding.action {
+                self.joypads[player as usize].insert(binding.action, *binding);
+            } else {
+                self.shortcuts.insert(binding.action, *binding);
+            }
+        }
+        self.action_bindings = action_bindings;
+    }
+
+    /// Returns the currently active bindings as a named profile.
+    pub fn active_profile(&self) -> KeybindProfile {
+        KeybindProfile {
+            name: self.profile.clone(),
+            action_bindings: self.action_bindings.clone(),
+        }
+    }
+
+    /// Save the currently active bindings to the active profile file.
+    pub fn save_profile(&self) -> anyhow::Result<()> {
+        self.active_profile()
+            .save(Config::keybind_profile_path(&self.profile))
+    }
+
+    /// Save the active profile and switch to the profile with the given name. If the profile
+    /// doesn't exist yet, it's created with a copy of the current bindings.
+    pub fn switch_profile(&mut self, name: &str) -> anyhow::Result<()> {
+        if name == self.profile {
+            return Ok(());
+        }
+        Config::validate_profile_name(name, &self.profiles)?;
+
+        self.save_profile()?;
+        let path = Config::keybind_profile_path(name);
+        if fs::exists(&path) {
+            let profile = KeybindProfile::load(&path)?;
+            self.set_action_bindings(profile.action_bindings);
+        }
+        self.profile = name.to_string();
+        self.profiles.insert(self.profile.clone());
+
+        Ok(())
+    }
+
+    /// Save the active profile and switch to an imported profile, replacing any existing
+    /// profile with the same name.
+    pub fn import_profile(&mut self, profile: KeybindProfile) -> anyhow::Result<()> {
+        Config::validate_profile_name(&profile.name, &self.profiles)?;
+        self.save_profile()?;
+        self.profile = profile.name;
+        self.profiles.insert(self.profile.clone());
+        self.set_action_bindings(profile.action_bindings);
+        self.save_profile()
+    }
+
+    /// Save the active profile and switch to a profile named after `preset` with its shortcuts,
+    /// keeping the current joypad bindings. An existing profile with the same name is reset.
+    pub fn load_preset(&mut self, preset: ShortcutPreset) -> anyhow::Result<()> {
+        let joypad_bindings = self
+            .joypads
+            .iter()
+            .flat_map(|joypad| joypad.values().copied())
+            .collect::<Vec<_>>();
+        let joypad_inputs = joypad_bindings
+            .iter()
+            .flat_map(|bind| bind.bindings.iter().flatten().copied())
+            .collect::<HashSet<_>>();
+
+        let mut shortcuts = ActionBindings::preset_shortcuts(preset);
+        // Joypad bindings take priority over any shortcuts they overlap with
+        for bind in shortcuts.values_mut() {
+            for input in &mut bind.bindings {
+                if input.is_some_and(|input| joypad_inputs.contains(&input)) {
+                    *input = None;
+                }
+            }
+        }
+
+        self.import_profile(KeybindProfile {
+            name: preset.as_ref().to_string(),
+            action_bindings: shortcuts.into_values().chain(joypad_bindings).collect(),
+        })
+    }
+
+    /// Remove a profile and delete its file, switching back to the default profile if it was
+    /// active. The default profile can't be removed.
+    pub fn remove_profile(&mut self, name: &str) -> anyhow::Result<()> {
+        if name == Self::DEFAULT_PROFILE || !self.profiles.contains(name) {
+            return Ok(());
+        }
+        if name == self.profile {
+            self.switch_profile(Self::DEFAULT_PROFILE)?;
+        }
+        self.profiles.remove(name);
+        let path = Config::keybind_profile_path(name);
+        if fs::exists(&path) {
+            std::fs::remove_file(&path).with_context(|| format!("failed to remove {path:?}"))?;
+        }
+        Ok(())
+    }
+
     pub fn set_binding(&mut self, action: Action, input: Input, binding: usize) {
         // Clear existing binding, if any
         self.clear_binding(input);
@@ -273,33 +662,343 @@ impl InputConfig {
             None
         }
     }
+
+    pub const fn keyboard_assigned_to(&self, player: Player) -> Option<DeviceId> {
+        self.keyboard_assignments[player as usize].1
+    }
+
+    pub fn keyboard_assignment(&self, device_id: &DeviceId) -> Option<Player> {
+        self.keyboard_assignments
+            .iter()
+            .find(|(_, id)| id.as_ref() == Some(device_id))
+            .map(|(player, _)| *player)
+    }
+
+    /// Assign a keyboard to a player, moving it from any player it was assigned to before.
+    pub fn assign_keyboard(&mut self, player: Player, device_id: DeviceId) {
+        for (_, id) in &mut self.keyboard_assignments {
+            if id.as_ref() == Some(&device_id) {
+                *id = None;
+            }
+        }
+        self.keyboard_assignments[player as usize].1 = Some(device_id);
+    }
+
+    pub fn unassign_keyboard(&mut self, player: Player) -> Option<DeviceId> {
+        std::mem::take(&mut self.keyboard_assignments[player as usize].1)
+    }
+
+    /// Whether input from `device` may control the joypad for `player`. With exclusive devices
+    /// enabled, an assigned device only controls its own player and a player with an assigned
+    /// keyboard ignores every other keyboard.
+    pub fn device_allowed(&self, device: InputDevice, player: Player) -> bool {
+        if !self.exclusive_devices {
+            return true;
+        }
+        match device {
+            InputDevice::Keyboard(device_id) => match self.keyboard_assignment(&device_id) {
+                Some(assigned) => assigned == player,
+                None => self.keyboard_assigned_to(player).is_none(),
+            },
+            InputDevice::Gamepad(uuid) => self.gamepad_assignment(&uuid) == Some(player),
+            InputDevice::Mouse => true,
+        }
+    }
+}
+
+/// Custom directories for files created while playing. Any directory left unset uses the
+/// platform default.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[must_use]
+#[serde(default)] // Ensures new fields don't break existing configurations
+pub struct PathsConfig {
+    /// Folder for all other data, such as playtime statistics and the ROM library cache. Also
+    /// the default parent folder of save states, Save RAM and replays.
+    pub data_dir: Option<PathBuf>,
+    pub save_dir: Option<PathBuf>,
+    pub sram_dir: Option<PathBuf>,
+    pub screenshot_dir: Option<PathBuf>,
+    pub audio_dir: Option<PathBuf>,
+    pub replay_dir: Option<PathBuf>,
+    /// Folder that save states and Save RAM are mirrored to, such as a Dropbox or Syncthing
+    /// folder. Syncing is disabled if unset.
+    pub sync_dir: Option<PathBuf>,
 }
 
+impl PathsConfig {
+    /// Directory where all other data is stored.
+    #[must_use]
+    pub fn data_dir(&self) -> PathBuf {
+        self.data_dir
+            .clone()
+            .unwrap_or_else(Config::default_data_dir)
+    }
+
+    /// Directory where save states are stored.
+    #[must_use]
+    pub fn save_dir(&self) -> PathBuf {
+        self.save_dir
+            .clone()
+            .unwrap_or_else(|| self.data_dir().join(Config::SAVE_DIR))
+    }
+
+    /// Directory where battery-backed Cart RAM is stored.
+    #[must_use]
+    pub fn sram_dir(&self) -> PathBuf {
+        self.sram_dir
+            .clone()
+            .unwrap_or_else(|| self.data_dir().join(DeckConfig::SRAM_DIR))
+    }
+
+    /// Directory where screenshots are stored.
+    #[must_use]
+    pub fn screenshot_dir(&self) -> PathBuf {
+        self.screenshot_dir
+            .clone()
+            .unwrap_or_else(Config::default_picture_dir)
+    }
+
+    /// Directory where audio recordings are stored.
+    #[must_use]
+    pub fn audio_dir(&self) -> PathBuf {
+        self.audio_dir
+            .clone()
+            .unwrap_or_else(Config::default_audio_dir)
+    }
+
+    /// Directory where replay recordings are stored.
+    #[must_use]
+    pub fn replay_dir(&self) -> PathBuf {
+        self.replay_dir.clone().unwrap_or_else(|| self.data_dir())
+    }
+
+    /// Directory of the HD pack for a given ROM name, named after the ROM without its extension
+    /// like Mesen's `HdPacks` folder.
+    #[must_use]
+    pub fn hd_pack_dir(&self, name: &str) -> PathBuf {
+        self.data_dir()
+            .join(Config::HD_PACKS_DIR)
+            .join(Path::new(name).with_extension(""))
+    }
+
+    /// Path to the input macros file for a given ROM name.
+    #[must_use]
+    pub fn macro_path(&self, name: &str) -> PathBuf {
+        self.save_dir().join(name).join("macros.json")
+    }
+
+    /// Path to the file of play statistics for every ROM played.
+    #[must_use]
+    pub fn playtime_path(&self) -> PathBuf {
+        self.data_dir().join("playtime.json")
+    }
+
+    /// Path to the game notes file for a given ROM name, used when there's no notes file next to
+    /// the ROM.
+    #[must_use]
+    pub fn notes_path(&self, name: &str) -> PathBuf {
+        self.save_dir().join(name).join("notes.md")
+    }
+
+    /// Path to the save state file for a given ROM name and slot.
+    #[must_use]
+    pub fn save_path(&self, name: &str, slot: u8) -> PathBuf {
+        self.save_dir()
+            .join(name)
+            .join(format!("slot-{}", slot))
+            .with_extension(Config::SAVE_EXTENSION)
+    }
+
+    /// Path to a save state file in the quick save state ring for a given ROM name.
+    #[must_use]
+    pub fn save_ring_path(&self, name: &str, index: usize) -> PathBuf {
+        self.save_dir()
+            .join(name)
+            .join(format!("ring-{}", index))
+            .with_extension(Config::SAVE_EXTENSION)
+    }
+
+    /// Path to the file tracking when each quick save state ring entry was saved for a given ROM
+    /// name.
+    #[must_use]
+    pub fn save_ring_index_path(&self, name: &str) -> PathBuf {
+        self.save_dir().join(name).join("ring.json")
+    }
+}
+
+/// A named set of keybindings, stored separately from the main configuration so that layouts
+/// can be switched between and shared.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[must_use]
+pub struct KeybindProfile {
+    pub name: String,
+    pub action_bindings: Vec<ActionBindings>,
+}
+
+impl KeybindProfile {
+    pub const EXTENSION: &'static str = "json";
+
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let data = fs::load_raw(path).context("failed to load keybind profile")?;
+        let mut profile = serde_json::from_slice::<Self>(&data)
+            .with_context(|| format!("failed to parse {path:?}"))?;
+        profile.name = profile.name.trim().to_string();
+        if profile.name.is_empty() {
+            anyhow::bail!("keybind profile {path:?} is missing a name");
+        }
+        Ok(profile)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let data =
+            serde_json::to_vec_pretty(&self).context("failed to serialize keybind profile")?;
+        fs::save_raw(path, &data).context("failed to save keybind profile")?;
+        Ok(())
+    }
+}
+
+/// Actions to perform once after startup, typically set from the command line. Not persisted.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+#[must_use]
+pub struct StartupConfig {
+    /// Save slot to load state from after loading the initial ROM.
+    pub load_slot: Option<u8>,
+    /// Replay recording to play back after loading the initial ROM.
+    pub replay_path: Option<PathBuf>,
+    /// File to save a replay recording to, started after loading the initial ROM.
+    pub record_replay_path: Option<PathBuf>,
+    /// Whether to save state to the current save slot on exit, even if auto-save is disabled.
+    pub save_on_exit: bool,
+    /// File to append frame timing samples to for the whole session.
+    pub perf_log_path: Option<PathBuf>,
+    /// Directory to restrict ROM loading to, enabling kiosk mode when set.
+    pub kiosk_dir: Option<PathBuf>,
+    /// Address to stream raw video and audio to TCP clients on.
+    pub stream_addr: Option<SocketAddr>,
+    /// Name of a MIDI output port to send APU channel notes to.
+    pub midi_port: Option<String>,
+    /// Problems found while loading the configuration, shown once running.
+    pub config_warnings: Vec<String>,
+}
+
+impl StartupConfig {
+    /// Whether kiosk mode is enabled, hiding configuration and debug menus and disabling quit
+    /// shortcuts other than an admin chord.
+    #[must_use]
+    pub const fn kiosk(&self) -> bool {
+        self.kiosk_dir.is_some()
+    }
+
+    /// Whether a ROM is allowed to be loaded, which in kiosk mode requires it to be inside
+    /// [`StartupConfig::kiosk_dir`].
+    #[must_use]
+    pub fn rom_allowed(&self, path: &Path) -> bool {
+        self.kiosk_dir.as_ref().map_or(true, |dir| {
+            path.canonicalize().is_ok_and(|path| path.starts_with(dir))
+        })
+    }
+}
+
+/// Directory where all files are stored when running in portable mode, if enabled.
+static PORTABLE_DIR: OnceLock<Option<PathBuf>> = OnceLock::new();
+
+/// An action and its formatted bindings, as listed by [`Config::shortcut_keybinds`].
+pub type ShortcutKeybind = (Action, Vec<String>);
+
 /// NES emulation configuration settings.
 ///
 /// # Config JSON
 ///
 /// Configuration for `TetaNES` is stored (by default) in `~/.config/tetanes/config.json`
 /// with defaults that can be customized in the `TetaNES` config menu.
-#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[must_use]
 #[serde(default)] // Ensures new fields don't break existing configurations
 pub struct Config {
+    /// Layout version of the configuration, used to migrate older configurations on load.
+    pub version: u32,
     pub deck: DeckConfig,
     pub emulation: EmulationConfig,
     pub audio: AudioConfig,
     pub renderer: RendererConfig,
     pub input: InputConfig,
+    pub paths: PathsConfig,
+    /// Name of the active configuration profile, or `None` for the default profile stored in
+    /// [`Config::FILENAME`].
+    #[serde(skip)]
+    pub profile: Option<String>,
+    #[serde(skip)]
+    pub startup: StartupConfig,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            version: Self::VERSION,
+            deck: DeckConfig::default(),
+            emulation: EmulationConfig::default(),
+            audio: AudioConfig::default(),
+            renderer: RendererConfig::default(),
+            input: InputConfig::default(),
+            paths: PathsConfig::default(),
+            profile: None,
+            startup: StartupConfig::default(),
+        }
+    }
 }
 
 impl Config {
+    /// Current layout version of the configuration. Incremented whenever a field changes in a way
+    /// that [`Config::migrate`] needs to convert older configurations.
+    pub const VERSION: u32 = 1;
     pub const SAVE_DIR: &'static str = "save";
     pub const SAVE_EXTENSION: &'static str = "sav";
     pub const WINDOW_TITLE: &'static str = "TetaNES";
     pub const FILENAME: &'static str = "config.json";
+    pub const KEYBINDS_DIR: &'static str = "keybinds";
+    pub const PROFILES_DIR: &'static str = "profiles";
+    pub const HD_PACKS_DIR: &'static str = "hd_packs";
+    pub const DEFAULT_PROFILE: &'static str = "Default";
+    pub const PORTABLE_DIR: &'static str = "tetanes-data";
+    /// File placed next to the executable to enable portable mode without `--portable`.
+    pub const PORTABLE_MARKER: &'static str = "portable.txt";
+
+    /// Enable portable mode, storing all configuration and data in a folder next to the
+    /// executable. Must be called before any paths are used.
+    pub fn enable_portable() {
+        let dir = Self::exe_dir().join(Self::PORTABLE_DIR);
+        if PORTABLE_DIR.set(Some(dir)).is_err() {
+            warn!("portable mode must be enabled before any paths are used");
+        }
+    }
+
+    /// Returns the directory used in portable mode, if enabled either by
+    /// [`Config::enable_portable`] or by a [`Config::PORTABLE_MARKER`] file next to the
+    /// executable.
+    #[must_use]
+    pub fn portable_dir() -> Option<&'static Path> {
+        PORTABLE_DIR
+            .get_or_init(|| {
+                let exe_dir = Self::exe_dir();
+                (feature!(Filesystem) && exe_dir.join(Self::PORTABLE_MARKER).is_file())
+                    .then(|| exe_dir.join(Self::PORTABLE_DIR))
+            })
+            .as_deref()
+    }
+
+    fn exe_dir() -> PathBuf {
+        std::env::current_exe()
+            .ok()
+            .and_then(|exe| exe.parent().map(Path::to_path_buf))
+            .unwrap_or_default()
+    }
 
     #[must_use]
     pub fn default_config_dir() -> PathBuf {
+        if let Some(dir) = Self::portable_dir() {
+            return dir.to_path_buf();
+        }
         dirs::config_local_dir().map_or_else(
             || PathBuf::from("config"),
             |dir| dir.join(DeckConfig::BASE_DIR),
@@ -308,6 +1007,9 @@ impl Config {
 
     #[must_use]
     pub fn default_data_dir() -> PathBuf {
+        if let Some(dir) = Self::portable_dir() {
+            return dir.to_path_buf();
+        }
         dirs::data_local_dir().map_or_else(
             || PathBuf::from("data"),
             |dir| dir.join(DeckConfig::BASE_DIR),
@@ -316,6 +1018,9 @@ impl Config {
 
     #[must_use]
     pub fn default_picture_dir() -> PathBuf {
+        if let Some(dir) = Self::portable_dir() {
+            return dir.join("screenshots");
+        }
         dirs::picture_dir().map_or_else(
             || PathBuf::from("pictures"),
             |dir| dir.join(DeckConfig::BASE_DIR),
@@ -324,6 +1029,9 @@ impl Config {
 
     #[must_use]
     pub fn default_audio_dir() -> PathBuf {
+        if let Some(dir) = Self::portable_dir() {
+            return dir.join("recordings");
+        }
         dirs::audio_dir().map_or_else(
             || PathBuf::from("music"),
             |dir| dir.join(DeckConfig::BASE_DIR),
@@ -335,59 +1043,286 @@ impl Config {
         Self::default_config_dir().join(Self::FILENAME)
     }
 
+    /// Checks that a profile name can be used as a file name as-is, so that two profiles never
+    /// share a file. Names that only differ by case from an `existing` profile are rejected as
+    /// well, as they'd share a file on case-insensitive file systems.
+    pub fn validate_profile_name<'a>(
+        name: &str,
+        existing: impl IntoIterator<Item = &'a String>,
+    ) -> anyhow::Result<()> {
+        if name.is_empty() {
+            bail!("profile name can't be empty");
+        }
+        if !name
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, ' ' | '-' | '_'))
+        {
+            bail!("profile name `{name}` can only contain letters, numbers, spaces, `-` and `_`");
+        }
+        if let Some(other) = existing
+            .into_iter()
+            .find(|other| *other != name && other.to_lowercase() == name.to_lowercase())
+        {
+            bail!("profile name `{name}` is too similar to the existing profile `{other}`");
+        }
+        Ok(())
+    }
+
+    /// Returns the path to a keybind profile file.
     #[must_use]
-    pub fn save_path(name: &str, slot: u8) -> PathBuf {
-        Self::default_data_dir()
-            .join(Self::SAVE_DIR)
+    pub fn keybind_profile_path(name: &str) -> PathBuf {
+        Self::default_config_dir()
+            .join(Self::KEYBINDS_DIR)
             .join(name)
-            .join(format!("slot-{}", slot))
-            .with_extension(Self::SAVE_EXTENSION)
+            .with_extension(KeybindProfile::EXTENSION)
+    }
+
+    /// Returns the name of a configuration profile, or `None` for the default profile.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the name isn't a valid profile name. See [`Config::validate_profile_name`].
+    pub fn profile_name(name: &str) -> anyhow::Result<Option<String>> {
+        let name = name.trim();
+        if name.is_empty() || name == Self::DEFAULT_PROFILE {
+            return Ok(None);
+        }
+        Self::validate_profile_name(name, &Self::profiles())?;
+        Ok(Some(name.to_string()))
+    }
+
+    /// Returns the path to a configuration profile file, or the default config path for the
+    /// default profile.
+    #[must_use]
+    pub fn profile_path(profile: Option<&str>) -> PathBuf {
+        match profile {
+            Some(name) => Self::default_config_dir()
+                .join(Self::PROFILES_DIR)
+                .join(name)
+                .with_extension("json"),
+            None => Self::config_path(),
+        }
+    }
+
+    /// Lists the names of saved configuration profiles, not including the default profile.
+    #[must_use]
+    pub fn profiles() -> BTreeSet<String> {
+        let dir = Self::default_config_dir().join(Self::PROFILES_DIR);
+        std::fs::read_dir(dir)
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                    .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+                    .filter_map(|path| Some(path.file_stem()?.to_str()?.to_string()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Name of the active configuration profile, for display.
+    #[must_use]
+    pub fn profile_label(&self) -> &str {
+        self.profile.as_deref().unwrap_or(Self::DEFAULT_PROFILE)
+    }
+
+    /// Load a configuration profile returned by [`Config::profile_name`], starting from the
+    /// default profile if it hasn't been saved yet.
+    pub fn load_profile(profile: Option<String>) -> Self {
+        let path = Self::profile_path(profile.as_deref());
+        let mut config = if fs::exists(&path) {
+            Self::load(Some(path))
+        } else {
+            info!("Creating configuration profile {profile:?} from the default profile");
+            Self::load(None)
+        };
+        config.profile = profile;
+        config
+    }
+
+    /// Delete a saved configuration profile. The default profile can't be removed.
+    pub fn remove_profile(name: &str) -> anyhow::Result<()> {
+        let Some(profile) = Self::profile_name(name)? else {
+            bail!("the default config profile can't be removed");
+        };
+        let path = Self::profile_path(Some(&profile));
+        std::fs::remove_file(&path).with_context(|| format!("failed to remove {path:?}"))
     }
 
     pub fn reset(&mut self) {
         *self = Self::default();
     }
 
+    /// Save the configuration to the file for the active profile.
     pub fn save(&self) -> anyhow::Result<()> {
-        let path = Config::config_path();
+        let path = Config::profile_path(self.profile.as_deref());
         let data = serde_json::to_vec_pretty(&self).context("failed to serialize config")?;
 
         fs::save_raw(path, &data).context("failed to save config")?;
+        self.input.save_profile()?;
 
         Ok(())
     }
 
+    /// Load the configuration from `path`, or the default config path, migrating older layouts
+    /// forward. Any settings that can't be loaded are reset to defaults, backing up the original
+    /// file if the configuration can't be loaded at all. Warnings are kept in
+    /// [`StartupConfig::config_warnings`] to show once running.
     pub fn load(path: Option<PathBuf>) -> Self {
         let path = path.unwrap_or_else(Config::config_path);
 
-        let mut config = fs::exists(&path)
-            .then(|| {
-                info!("Loading saved configuration");
-                fs::load_raw(&path)
-                    .context("failed to load config")
-                    .and_then(|data| Ok(serde_json::from_slice::<Self>(&data)?))
-                    .with_context(|| format!("failed to parse {path:?}"))
-                    .unwrap_or_else(|err| {
-                        error!("Invalid config: {path:?}, reverting to defaults. Error: {err:?}",);
-                        Self::default()
-                    })
-            })
-            .unwrap_or_else(|| {
-                info!("Loading default configuration");
-                Self::default()
-            });
-
-        for binding in &config.input.action_bindings {
-            if let Action::Deck(DeckAction::Joypad((player, _))) = binding.action {
-                config.input.joypads[player as usize].insert(binding.action, *binding);
-            } else {
-                config.input.shortcuts.insert(binding.action, *binding);
+        let mut config = if fs::exists(&path) {
+            info!("Loading saved configuration");
+            match fs::load_raw(&path).context("failed to load config") {
+                Ok(data) => Self::parse(&data).unwrap_or_else(|err| {
+                    error!("Invalid config: {path:?}, reverting to defaults. Error: {err:?}");
+                    let mut config = Self::default();
+                    config
+                        .startup
+                        .config_warnings
+                        .push(Self::backup(&path, &data));
+                    config
+                }),
+                Err(err) => {
+                    error!("{err:?}");
+                    let mut config = Self::default();
+                    config.startup.config_warnings.push(format!(
+                        "Failed to read {}. Using default settings.",
+                        path.display()
+                    ));
+                    config
+                }
             }
+        } else {
+            info!("Loading default configuration");
+            Self::default()
+        };
+        for warning in &config.startup.config_warnings {
+            warn!("{warning}");
+        }
+
+        let action_bindings = std::mem::take(&mut config.input.action_bindings);
+        config.input.set_action_bindings(action_bindings);
+        config.input.profiles.insert(config.input.profile.clone());
+
+        if let Some(dir) = Self::portable_dir() {
+            info!("Portable mode enabled, storing data in {dir:?}");
+            config.deck.data_dir = Self::default_data_dir();
         }
 
         config
     }
 
+    /// Parse a configuration, migrating it from older layouts and resetting any sections that
+    /// fail to load to their defaults, with a warning for each change made.
+    fn parse(data: &[u8]) -> anyhow::Result<Self> {
+        let mut value = serde_json::from_slice::<Value>(data).context("invalid json")?;
+        if !value.is_object() {
+            bail!("expected a json object");
+        }
+        let mut warnings = Self::migrate(&mut value)?;
+
+        let config = match serde_json::from_value::<Self>(value.clone()) {
+            Ok(config) => config,
+            Err(_) => {
+                type Validate = fn(&Value) -> serde_json::Result<()>;
+                let sections: [(&str, Validate); 6] = [
+                    ("deck", Self::validate::<DeckConfig>),
+                    ("emulation", Self::validate::<EmulationConfig>),
+                    ("audio", Self::validate::<AudioConfig>),
+                    ("renderer", Self::validate::<RendererConfig>),
+                    ("input", Self::validate::<InputConfig>),
+                    ("paths", Self::validate::<PathsConfig>),
+                ];
+                if let Some(object) = value.as_object_mut() {
+                    for (name, validate) in sections {
+                        if let Some(Err(err)) = object.get(name).map(validate) {
+                            warnings
+                                .push(format!("Reset invalid {name} settings to defaults: {err}"));
+                            object.remove(name);
+                        }
+                    }
+                }
+                serde_json::from_value::<Self>(value)?
+            }
+        };
+
+        Ok(Self {
+            version: Self::VERSION,
+            startup: StartupConfig {
+                config_warnings: warnings,
+                ..config.startup
+            },
+            ..config
+        })
+    }
+
+    fn validate<T: DeserializeOwned>(value: &Value) -> serde_json::Result<()> {
+        T::deserialize(value).map(|_| ())
+    }
+
+    /// Migrate an older configuration layout forward to [`Config::VERSION`], returning a warning
+    /// for each change made.
+    fn migrate(value: &mut Value) -> anyhow::Result<Vec<String>> {
+        let version = match value.get("version") {
+            Some(version) => version
+                .as_u64()
+                .and_then(|version| u32::try_from(version).ok())
+                .with_context(|| format!("invalid config version: {version}"))?,
+            None => 0,
+        };
+        let mut warnings = Vec::new();
+        if version > Self::VERSION {
+            warnings.push(format!(
+                "Settings are from a newer version of TetaNES (v{version}). \
+                Some settings may not be loaded."
+            ));
+            return Ok(warnings);
+        }
+
+        if version < 1 {
+            // Version 1 stores when recent ROMs were last played and whether they're pinned,
+            // instead of only their paths
+            let recent_roms = value
+                .pointer_mut("/renderer/recent_roms")
+                .and_then(Value::as_array_mut);
+            let mut migrated = false;
+            for rom in recent_roms.into_iter().flatten() {
+                if let Value::String(path) = rom {
+                    *rom = serde_json::json!({ "path": path, "last_played": 0, "pinned": false });
+                    migrated = true;
+                }
+            }
+            if migrated {
+                warnings.push("Migrated recent ROMs from an older settings format.".to_string());
+            }
+        }
+
+        if let Some(object) = value.as_object_mut() {
+            object.insert("version".to_string(), Self::VERSION.into());
+        }
+        Ok(warnings)
+    }
+
+    /// Back up a configuration that can't be loaded next to the original, returning a warning
+    /// describing where it was saved.
+    fn backup(path: &Path, data: &[u8]) -> String {
+        let backup_path = path.with_file_name(format!(
+            "config-backup-{}.json",
+            chrono::Local::now().format("%Y%m%d-%H%M%S")
+        ));
+        match fs::save_raw(&backup_path, data) {
+            Ok(()) => format!(
+                "Settings were invalid and have been reset to defaults. \
+                The old settings were backed up to {}.",
+                backup_path.display()
+            ),
+            Err(err) => {
+                error!("failed to back up invalid config: {err:?}");
+                "Settings were invalid and have been reset to defaults.".to_string()
+            }
+        }
+    }
+
     pub fn increment_speed(&mut self) -> f32 {
         self.emulation.speed = self.next_increment_speed();
         self.emulation.speed
@@ -448,10 +1383,11 @@ impl Config {
     #[must_use]
     pub fn window_size_for_scale(&self, aspect_ratio: f32, scale: f32) -> egui::Vec2 {
         let texture_size = self.texture_size();
-        egui::Vec2::new(
+        let size = egui::Vec2::new(
             (scale * aspect_ratio * texture_size.x).ceil(),
             (scale * texture_size.y).ceil(),
-        )
+        );
+        self.renderer.rotation.rotate_size(size)
     }
 
     #[must_use]
@@ -465,6 +1401,29 @@ impl Config {
         egui::Vec2::new(width as f32, height as f32)
     }
 
+    /// All bound shortcuts grouped by [`Action::category`], formatted for display.
+    pub fn shortcut_keybinds(&self) -> Vec<(&'static str, Vec<ShortcutKeybind>)> {
+        let mut categories: Vec<(&'static str, Vec<ShortcutKeybind>)> = Vec::new();
+        for (action, bind) in &self.input.shortcuts {
+            let inputs = bind
+                .bindings
+                .iter()
+                .flatten()
+                .map(|input| Input::fmt(*input))
+                .collect::<Vec<_>>();
+            if inputs.is_empty() {
+                continue;
+            }
+            let category = action.category();
+            match categories.iter_mut().find(|(name, _)| *name == category) {
+                Some((_, actions)) => actions.push((*action, inputs)),
+                None => categories.push((category, vec![(*action, inputs)])),
+            }
+        }
+        categories.sort_by_key(|(name, _)| *name);
+        categories
+    }
+
     pub fn shortcut(&self, action: impl Into<Action>) -> String {
         let action = action.into();
         self.input
@@ -569,3 +1528,244 @@ impl std::fmt::Display for FrameRate {
         write!(f, "{}", self.as_ref())
     }
 }
+
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[must_use]
+pub enum FontSize {
+    Small,
+    #[default]
+    Medium,
+    Large,
+    ExtraLarge,
+}
+
+impl FontSize {
+    pub const fn as_slice() -> &'static [Self] {
+        &[Self::Small, Self::Medium, Self::Large, Self::ExtraLarge]
+    }
+
+    /// Multiplier applied to the default UI text sizes.
+    #[must_use]
+    pub const fn scale(&self) -> f32 {
+        match self {
+            Self::Small => 0.85,
+            Self::Medium => 1.0,
+            Self::Large => 1.25,
+            Self::ExtraLarge => 1.5,
+        }
+    }
+}
+
+impl AsRef<str> for FontSize {
+    fn as_ref(&self) -> &str {
+        match self {
+            Self::Small => "Small",
+            Self::Medium => "Medium",
+            Self::Large => "Large",
+            Self::ExtraLarge => "Extra Large",
+        }
+    }
+}
+
+impl std::fmt::Display for FontSize {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_ref())
+    }
+}
+
+/// How often to check for a newer release on startup.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[must_use]
+pub enum UpdateCheck {
+    #[default]
+    Never,
+    EveryLaunch,
+    Daily,
+    Weekly,
+}
+
+impl UpdateCheck {
+    pub const fn as_slice() -> &'static [Self] {
+        &[Self::Never, Self::EveryLaunch, Self::Daily, Self::Weekly]
+    }
+
+    /// Whether a check is due at `now`, given the time of the last check, both as Unix
+    /// timestamps in seconds.
+    #[must_use]
+    pub const fn is_due(&self, last_check: i64, now: i64) -> bool {
+        const DAY: i64 = 24 * 60 * 60;
+        match self {
+            Self::Never => false,
+            Self::EveryLaunch => true,
+            Self::Daily => now - last_check >= DAY,
+            Self::Weekly => now - last_check >= 7 * DAY,
+        }
+    }
+}
+
+impl AsRef<str> for UpdateCheck {
+    fn as_ref(&self) -> &str {
+        match self {
+            Self::Never => "Never",
+            Self::EveryLaunch => "Every Launch",
+            Self::Daily => "Daily",
+            Self::Weekly => "Weekly",
+        }
+    }
+}
+
+impl std::fmt::Display for UpdateCheck {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_ref())
+    }
+}
+
+/// Color scheme for menus and windows. High contrast mode takes priority over any preset.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[must_use]
+pub enum ThemePreset {
+    /// Dark or light theme, depending on `dark_theme`.
+    #[default]
+    Default,
+    /// Red and gold, like the Famicom.
+    Famicom,
+    /// Green on black, like a monochrome CRT.
+    Phosphor,
+}
+
+impl ThemePreset {
+    pub const fn as_slice() -> &'static [Self] {
+        &[Self::Default, Self::Famicom, Self::Phosphor]
+    }
+}
+
+impl AsRef<str> for ThemePreset {
+    fn as_ref(&self) -> &str {
+        match self {
+            Self::Default => "Default",
+            Self::Famicom => "Famicom",
+            Self::Phosphor => "Green Phosphor",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_profile_name() {
+        let existing = [
+            InputConfig::DEFAULT_PROFILE.to_string(),
+            "Laptop".to_string(),
+        ];
+        for name in ["Laptop", "Left Hand", "arcade-cabinet_2"] {
+            assert!(
+                Config::validate_profile_name(name, &existing).is_ok(),
+                "{name} is valid"
+            );
+        }
+        // Names that would share a file with another profile
+        for name in ["", "a/b", "../config", "a.b", "laptop", "default"] {
+            assert!(
+                Config::validate_profile_name(name, &existing).is_err(),
+                "{name} is invalid"
+            );
+        }
+    }
+
+    fn parse(value: Value) -> Config {
+        Config::parse(&serde_json::to_vec(&value).expect("valid json")).expect("valid config")
+    }
+
+    #[test]
+    fn migrate_legacy_recent_roms() {
+        // Version 0 has no version field and stores recent ROMs as plain paths
+        let config = parse(serde_json::json!({
+            "renderer": {
+                "recent_roms": ["/roms/zelda.nes", "/roms/metroid.nes"],
+                "fullscreen": true,
+            },
+        }));
+
+        assert_eq!(config.version, Config::VERSION);
+        assert!(config.renderer.fullscreen, "other settings are kept");
+        let recent_roms = config.renderer.recent_roms.iter().collect::<Vec<_>>();
+        assert_eq!(
+            recent_roms,
+            [
+                &RecentRom {
+                    path: PathBuf::from("/roms/zelda.nes"),
+                    last_played: 0,
+                    pinned: false,
+                },
+                &RecentRom {
+                    path: PathBuf::from("/roms/metroid.nes"),
+                    last_played: 0,
+                    pinned: false,
+                },
+            ]
+        );
+        assert_eq!(config.startup.config_warnings.len(), 1);
+    }
+
+    #[test]
+    fn migrate_current_version() {
+        let config = parse(serde_json::json!({
+            "version": Config::VERSION,
+            "renderer": {
+                "recent_roms": [{ "path": "/roms/zelda.nes", "last_played": 10, "pinned": true }],
+            },
+        }));
+
+        assert_eq!(
+            config.renderer.recent_roms.iter().collect::<Vec<_>>(),
+            [&RecentRom {
+                path: PathBuf::from("/roms/zelda.nes"),
+                last_played: 10,
+                pinned: true,
+            }]
+        );
+        assert!(config.startup.config_warnings.is_empty());
+        assert_eq!(
+            parse(serde_json::to_value(Config::default()).expect("valid config")),
+            Config::default(),
+            "default config round trips"
+        );
+    }
+
+    #[test]
+    fn migrate_newer_version() {
+        let config = parse(serde_json::json!({
+            "version": Config::VERSION + 1,
+            "emulation": { "rewind_seconds": 10 },
+        }));
+
+        assert_eq!(config.version, Config::VERSION);
+        assert_eq!(config.emulation.rewind_seconds, 10);
+        assert_eq!(config.startup.config_warnings.len(), 1);
+    }
+
+    #[test]
+    fn validate_invalid_sections() {
+        let config = parse(serde_json::json!({
+            "version": Config::VERSION,
+            "emulation": { "rewind_seconds": "thirty" },
+            "audio": { "enabled": false },
+        }));
+
+        assert_eq!(config.emulation, EmulationConfig::default());
+        assert!(!config.audio.enabled, "valid sections are kept");
+        assert_eq!(config.startup.config_warnings.len(), 1);
+        assert!(config.startup.config_warnings[0].contains("emulation"));
+
+        for data in [
+            "[]",
+            "not json",
+            r#"{ "version": "one" }"#,
+            r#"{ "version": -1 }"#,
+        ] {
+            assert!(Config::parse(data.as_bytes()).is_err(), "{data} is invalid");
+        }
+    }
+}
@@ -1,41 +1,75 @@
 use crate::{
     nes::{
         action::DebugStep,
-        audio::{Audio, State as AudioState},
-        config::{Config, FrameRate},
-        emulation::{replay::Record, rewind::Rewind},
+        audio::{Audio, SampleTap, State as AudioState},
+        config::{Config, FrameRate, PathsConfig},
+        emulation::{
+            av_stream::AvStream,
+            ghost::Ghost,
+            input_macro::{MacroPlayer, Macros},
+            perf_log::{FrameSample, PerfLog},
+            playtime::{PlaySession, Playtime},
+            race::{Race, RaceInputs},
+            ram_watch::RamWatch,
+            replay::Record,
+            rewind::Rewind,
+            save_ring::SaveRing,
+            screenshot_burst::ScreenshotBurst,
+            sync::{SaveSync, SyncChoice, SyncEntry, SyncKind},
+        },
         event::{ConfigEvent, EmulationEvent, NesEvent, NesEventProxy, RendererEvent, UiEvent},
         renderer::{gui::MessageType, FrameRecycle},
         RunState,
     },
     thread,
 };
-use anyhow::{anyhow, Context};
+use anyhow::{anyhow, bail, Context};
 use chrono::Local;
 use crossbeam::channel;
 use egui::ViewportId;
 use replay::Replay;
 use std::{
-    collections::VecDeque,
+    backtrace::Backtrace,
+    cell::RefCell,
+    collections::{BTreeSet, VecDeque},
     io::{self, Read},
+    panic::{self, AssertUnwindSafe},
     path::{Path, PathBuf},
+    sync::{Arc, Once},
     thread::JoinHandle,
 };
 use tetanes_core::{
     apu::Apu,
+    cart::header_fix::{self, HeaderIssue},
     common::{NesRegion, Regional, Reset, ResetKind},
     control_deck::{self, ControlDeck, LoadedRom},
     cpu::Cpu,
+    debug::Breakpoints,
+    fs,
+    input::FourPlayer,
+    mapper::{Mapped, MapperDebugState, MapperIrqEvent, MapperWrite},
     ppu::Ppu,
     time::{Duration, Instant},
     video::Frame,
 };
 use thingbuf::mpsc::{blocking::Sender as BufSender, errors::TrySendError};
-use tracing::{debug, error, trace};
+use tracing::{debug, error, trace, warn};
 use winit::event::ElementState;
 
+pub mod av_stream;
+pub mod ghost;
+pub mod input_macro;
+#[cfg(feature = "midi")]
+pub mod midi;
+pub mod perf_log;
+pub mod playtime;
+pub mod race;
+pub mod ram_watch;
 pub mod replay;
 pub mod rewind;
+pub mod save_ring;
+pub mod screenshot_burst;
+pub mod sync;
 
 #[derive(Debug, Copy, Clone, PartialEq)]
 #[must_use]
@@ -46,6 +80,16 @@ pub struct FrameStats {
     pub frame_time: f32,
     pub frame_time_max: f32,
     pub frame_count: usize,
+    pub late_frames: usize,
+    pub dropped_frames: usize,
+    /// Time between the two most recent frames, in milliseconds.
+    pub last_frame_time: f32,
+    /// Time spent emulating the most recent frame, in milliseconds.
+    pub emulation_time: f32,
+    /// How full the queue of audio samples waiting to be played is, from `0.0` to `1.0`.
+    pub audio_buffer_fill: f32,
+    /// Bytes used by rewind snapshots.
+    pub rewind_memory: usize,
 }
 
 impl Default for FrameStats {
@@ -57,6 +101,12 @@ impl Default for FrameStats {
             frame_time: 0.0,
             frame_time_max: 0.0,
             frame_count: 0,
+            late_frames: 0,
+            dropped_frames: 0,
+            last_frame_time: 0.0,
+            emulation_time: 0.0,
+            audio_buffer_fill: 0.0,
+            rewind_memory: 0,
         }
     }
 }
@@ -71,7 +121,13 @@ impl FrameStats {
 #[must_use]
 pub struct FrameTimeDiag {
     frame_count: usize,
+    late_frames: usize,
+    dropped_frames: usize,
+    /// Time spent emulating the most recent frame, in seconds.
+    emulation_time: f32,
     history: VecDeque<f32>,
+    /// Recent frame samples, kept for exporting.
+    samples: VecDeque<FrameSample>,
     sum: f32,
     avg: f32,
     last_update: Instant,
@@ -80,11 +136,17 @@ pub struct FrameTimeDiag {
 impl FrameTimeDiag {
     const MAX_HISTORY: usize = 120;
     const UPDATE_INTERVAL: Duration = Duration::from_millis(300);
+    /// About a minute of frame samples at 60 FPS.
+    const MAX_SAMPLES: usize = 3600;
 
     fn new() -> Self {
         Self {
             frame_count: 0,
+            late_frames: 0,
+            dropped_frames: 0,
+            emulation_time: 0.0,
             history: VecDeque::with_capacity(Self::MAX_HISTORY),
+            samples: VecDeque::new(),
             sum: 0.0,
             avg: 1.0 / 60.0,
             last_update: Instant::now(),
@@ -106,6 +168,13 @@ impl FrameTimeDiag {
         }
     }
 
+    fn push_sample(&mut self, sample: FrameSample) {
+        if self.samples.len() >= Self::MAX_SAMPLES {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+
     fn avg(&mut self) -> f32 {
         if !self.history.is_empty() {
             let now = Instant::now();
@@ -123,7 +192,11 @@ impl FrameTimeDiag {
 
     fn reset(&mut self) {
         self.frame_count = 0;
+        self.late_frames = 0;
+        self.dropped_frames = 0;
+        self.emulation_time = 0.0;
         self.history.clear();
+        self.samples.clear();
         self.sum = 0.0;
         self.avg = 1.0 / 60.0;
         self.last_update = Instant::now();
@@ -136,6 +209,24 @@ fn shutdown(tx: &NesEventProxy, err: impl std::fmt::Display) {
     std::process::exit(1);
 }
 
+thread_local! {
+    /// Backtrace of the most recent panic on this thread, captured by the panic hook.
+    static PANIC_BACKTRACE: RefCell<Option<Backtrace>> = const { RefCell::new(None) };
+}
+
+/// Install a panic hook that captures a backtrace for each panic so emulation panics can be
+/// reported to the user before falling through to the default hook.
+fn install_panic_hook() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let default_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            PANIC_BACKTRACE.set(Some(Backtrace::force_capture()));
+            default_hook(info);
+        }));
+    });
+}
+
 #[derive(Debug)]
 #[must_use]
 enum Threads {
@@ -160,6 +251,9 @@ impl Multi {
     fn spawn(
         proxy_tx: NesEventProxy,
         frame_tx: BufSender<Frame, FrameRecycle>,
+        race_frame_tx: BufSender<Frame, FrameRecycle>,
+        ghost_frame_tx: BufSender<Frame, FrameRecycle>,
+        audio_tap: Arc<SampleTap>,
         cfg: &Config,
     ) -> anyhow::Result<Self> {
         let (tx, rx) = channel::bounded(1024);
@@ -169,7 +263,17 @@ impl Multi {
                 .name("emulation".into())
                 .spawn({
                     let cfg = cfg.clone();
-                    move || Self::main(proxy_tx, rx, frame_tx, &cfg)
+                    move || {
+                        Self::main(
+                            proxy_tx,
+                            rx,
+                            frame_tx,
+                            race_frame_tx,
+                            ghost_frame_tx,
+                            audio_tap,
+                            &cfg,
+                        )
+                    }
                 })?,
         })
     }
@@ -178,19 +282,24 @@ impl Multi {
         tx: NesEventProxy,
         rx: channel::Receiver<NesEvent>,
         frame_tx: BufSender<Frame, FrameRecycle>,
+        race_frame_tx: BufSender<Frame, FrameRecycle>,
+        ghost_frame_tx: BufSender<Frame, FrameRecycle>,
+        audio_tap: Arc<SampleTap>,
         cfg: &Config,
     ) {
         debug!("emulation thread started");
-        let mut state = State::new(tx, frame_tx, cfg); // Has to be created on the thread, since
+        let mut state = State::new(tx, frame_tx, race_frame_tx, ghost_frame_tx, audio_tap, cfg); // Has to be created on the thread, since
         loop {
             #[cfg(feature = "profiling")]
             puffin::profile_scope!("emulation loop");
 
-            while let Ok(event) = rx.try_recv() {
-                state.on_event(&event);
-            }
+            state.catch_panic(|state| {
+                while let Ok(event) = rx.try_recv() {
+                    state.on_event(&event);
+                }
 
-            state.try_clock_frame();
+                state.try_clock_frame();
+            });
         }
     }
 }
@@ -206,15 +315,27 @@ impl Emulation {
     pub fn new(
         tx: NesEventProxy,
         frame_tx: BufSender<Frame, FrameRecycle>,
+        race_frame_tx: BufSender<Frame, FrameRecycle>,
+        ghost_frame_tx: BufSender<Frame, FrameRecycle>,
+        audio_tap: Arc<SampleTap>,
         cfg: &Config,
     ) -> anyhow::Result<Self> {
+        install_panic_hook();
+
         let threaded = cfg.emulation.threaded
             && std::thread::available_parallelism().is_ok_and(|count| count.get() > 1);
         let backend = if threaded {
-            Threads::Multi(Multi::spawn(tx, frame_tx, cfg)?)
+            Threads::Multi(Multi::spawn(
+                tx,
+                frame_tx,
+                race_frame_tx,
+                ghost_frame_tx,
+                audio_tap,
+                cfg,
+            )?)
         } else {
             Threads::Single(Single {
-                state: State::new(tx, frame_tx, cfg),
+                state: State::new(tx, frame_tx, race_frame_tx, ghost_frame_tx, audio_tap, cfg),
             })
         };
 
@@ -224,34 +345,45 @@ impl Emulation {
     /// Handle event.
     pub fn on_event(&mut self, event: &NesEvent) {
         match &mut self.threads {
-            Threads::Single(Single { state }) => state.on_event(event),
+            Threads::Single(Single { state }) => state.catch_panic(|state| state.on_event(event)),
             Threads::Multi(Multi { tx, handle }) => {
-                handle.thread().unpark();
+                // Send before unparking, otherwise the thread could wake, find no events and
+                // park again while idle
                 if let Err(err) = tx.try_send(event.clone()) {
                     error!("failed to send emulation event: {event:?}. {err:?}");
                     std::process::exit(1);
                 }
+                handle.thread().unpark();
             }
         }
     }
 
     pub fn try_clock_frame(&mut self) {
         match &mut self.threads {
-            Threads::Single(Single { state }) => state.try_clock_frame(),
+            Threads::Single(Single { state }) => state.catch_panic(State::try_clock_frame),
             // Multi-threaded emulation handles it's own clock timing and redraw requests
             Threads::Multi(Multi { handle, .. }) => handle.thread().unpark(),
         }
     }
 
+    /// Whether emulation relies on redraws being requested in order to be clocked.
+    pub fn wants_redraw(&mut self) -> bool {
+        match &mut self.threads {
+            Threads::Single(Single { state }) => !state.is_idle(),
+            // Multi-threaded emulation handles it's own clock timing and redraw requests
+            Threads::Multi(_) => false,
+        }
+    }
+
     pub fn terminate(&mut self) {
         match &mut self.threads {
             Threads::Single(_) => (),
             Threads::Multi(Multi { tx, handle }) => {
-                handle.thread().unpark();
                 if let Err(err) = tx.try_send(NesEvent::Ui(UiEvent::Terminate)) {
                     error!("failed to send termination event. {err:?}");
                     std::process::exit(1);
                 }
+                handle.thread().unpark();
             }
         }
     }
@@ -264,6 +396,10 @@ pub struct State {
     control_deck: ControlDeck,
     audio: Audio,
     frame_tx: BufSender<Frame, FrameRecycle>,
+    race_frame_tx: BufSender<Frame, FrameRecycle>,
+    race: Option<Race>,
+    ghost_frame_tx: BufSender<Frame, FrameRecycle>,
+    ghost: Option<Ghost>,
     frame_latency: usize,
     target_frame_duration: Duration,
     last_clock_time: Instant,
@@ -273,17 +409,59 @@ pub struct State {
     run_state: RunState,
     threaded: bool,
     rewinding: bool,
+    /// Whether an HD pack is loaded for the current ROM, so frames are sent at the HD pack scale.
+    hd_pack_loaded: bool,
+    /// ROM held back until the user chooses whether to repair its header problems.
+    held_rom: Option<(String, Vec<u8>, Vec<HeaderIssue>)>,
     rewind: Rewind,
     record: Record,
     replay: Replay,
+    macros: MacroPlayer,
     save_slot: u8,
+    save_ring: SaveRing,
+    save_ring_len: usize,
+    /// State from before the most recent load state, restored by
+    /// [`EmulationEvent::UndoLoadState`].
+    undo_load_state: Option<Vec<u8>>,
     auto_save: bool,
     auto_save_interval: Duration,
     last_auto_save: Instant,
     auto_load: bool,
+    /// Whether a ROM is being played back in attract mode, which shouldn't touch save states.
+    attract_mode: bool,
     speed: f32,
+    fast_forwarding: bool,
+    fast_forward_speed: f32,
+    fast_forward_uncapped: bool,
+    fast_forward_frame_skip: u32,
+    frames_skipped: u32,
+    sync_to_display: bool,
+    display_speed: f32,
+    refresh_rate: Option<f32>,
     run_ahead: usize,
     show_frame_stats: bool,
+    /// Appends frame timing samples to a file for the whole session, if enabled.
+    perf_log: Option<PerfLog>,
+    /// Streams raw video and audio to external tools, if enabled.
+    av_stream: Option<AvStream>,
+    /// Saves each frame as a screenshot while capturing a burst.
+    screenshot_burst: Option<ScreenshotBurst>,
+    /// Sends APU channel notes to a MIDI output port, if enabled.
+    #[cfg(feature = "midi")]
+    midi: Option<midi::MidiOut>,
+    /// Names of ROMs to reduce DMC pops for.
+    dmc_click_reduction: BTreeSet<String>,
+    ram_watches: Vec<RamWatch>,
+    ram_watch_values: Vec<u16>,
+    show_mapper_state: bool,
+    /// Last mapper state sent to the mapper inspector.
+    mapper_state: Option<(MapperDebugState, Vec<MapperWrite>, Vec<MapperIrqEvent>)>,
+    paths: PathsConfig,
+    save_sync: Option<SaveSync>,
+    /// Play statistics for every ROM played.
+    playtime: Playtime,
+    /// Time played of the loaded ROM, if it's being tracked.
+    play_session: Option<PlaySession>,
 }
 
 impl Drop for State {
@@ -293,14 +471,34 @@ impl Drop for State {
 }
 
 impl State {
-    fn new(tx: NesEventProxy, frame_tx: BufSender<Frame, FrameRecycle>, cfg: &Config) -> Self {
+    const MAX_CATCH_UP_FRAMES: f32 = 3.0;
+    /// Grayscale shade for each 2-bit CHR color index in exported sprite sheets, so edited sheets
+    /// import back exactly regardless of the game's palettes.
+    const CHR_SHADES: [u8; 4] = [0x00, 0x55, 0xAA, 0xFF];
+    /// Frames between sending game code profiles, as building a report isn't free.
+    const PROFILE_REPORT_INTERVAL: u32 = 30;
+
+    fn new(
+        tx: NesEventProxy,
+        frame_tx: BufSender<Frame, FrameRecycle>,
+        race_frame_tx: BufSender<Frame, FrameRecycle>,
+        ghost_frame_tx: BufSender<Frame, FrameRecycle>,
+        audio_tap: Arc<SampleTap>,
+        cfg: &Config,
+    ) -> Self {
         let mut control_deck = ControlDeck::with_config(cfg.deck.clone());
-        let audio = Audio::new(
+        if cfg.paths.sram_dir.is_some() || cfg.paths.data_dir.is_some() {
+            control_deck.set_sram_dir(cfg.paths.sram_dir());
+        }
+        let mut audio = Audio::new(
             cfg.audio.enabled,
+            cfg.audio.backend,
             Apu::DEFAULT_SAMPLE_RATE,
             cfg.audio.latency,
             cfg.audio.buffer_size,
+            audio_tap,
         );
+        audio.set_recording_limits(cfg.audio.recording_limits);
         if Apu::DEFAULT_SAMPLE_RATE != audio.sample_rate {
             control_deck.set_sample_rate(audio.sample_rate);
         }
@@ -310,11 +508,50 @@ impl State {
             cfg.emulation.rewind_interval,
         );
         let target_frame_duration = FrameRate::from(cfg.deck.region).duration();
+        let perf_log =
+            cfg.startup
+                .perf_log_path
+                .as_ref()
+                .and_then(|path| match PerfLog::open(path) {
+                    Ok(perf_log) => Some(perf_log),
+                    Err(err) => {
+                        error!("{err:?}");
+                        None
+                    }
+                });
+        let av_stream = cfg.startup.stream_addr.and_then(|addr| {
+            AvStream::bind(addr, audio.sample_rate)
+                .map_err(|err| error!("{err:?}"))
+                .ok()
+        });
+        #[cfg(feature = "midi")]
+        let midi = cfg.startup.midi_port.as_deref().and_then(|port| {
+            midi::MidiOut::connect(port)
+                .map_err(|err| error!("{err:?}"))
+                .ok()
+        });
+        #[cfg(not(feature = "midi"))]
+        if cfg.startup.midi_port.is_some() {
+            error!("MIDI output requires building with the `midi` feature");
+        }
+        let save_sync = SaveSync::new(&cfg.paths).unwrap_or_else(|err| {
+            error!("failed to start save sync: {err:?}");
+            None
+        });
+        let playtime = Playtime::load(cfg.paths.playtime_path()).unwrap_or_else(|err| {
+            error!("{err:?}");
+            Playtime::default()
+        });
+        tx.event(RendererEvent::Playtime(playtime.clone()));
         let mut state = Self {
             tx,
             control_deck,
             audio,
             frame_tx,
+            race_frame_tx,
+            race: None,
+            ghost_frame_tx,
+            ghost: None,
             frame_latency: 1,
             target_frame_duration,
             last_clock_time: Instant::now(),
@@ -325,17 +562,46 @@ impl State {
             threaded: cfg.emulation.threaded
                 && std::thread::available_parallelism().is_ok_and(|count| count.get() > 1),
             rewinding: false,
+            hd_pack_loaded: false,
+            held_rom: None,
             rewind,
-            record: Record::new(),
+            record: Record::new(cfg.emulation.replay_hash_interval),
             replay: Replay::new(),
+            macros: MacroPlayer::new(),
             save_slot: cfg.emulation.save_slot,
+            save_ring: SaveRing::default(),
+            save_ring_len: cfg.emulation.save_ring_len,
+            undo_load_state: None,
             auto_save: cfg.emulation.auto_save,
             auto_save_interval: cfg.emulation.auto_save_interval,
             last_auto_save: Instant::now(),
             auto_load: cfg.emulation.auto_load,
+            attract_mode: false,
             speed: cfg.emulation.speed,
+            fast_forwarding: false,
+            fast_forward_speed: cfg.emulation.fast_forward_speed,
+            fast_forward_uncapped: cfg.emulation.fast_forward_uncapped,
+            fast_forward_frame_skip: cfg.emulation.fast_forward_frame_skip,
+            frames_skipped: 0,
+            sync_to_display: cfg.emulation.sync_to_display,
+            display_speed: 1.0,
+            refresh_rate: None,
             run_ahead: cfg.emulation.run_ahead,
             show_frame_stats: false,
+            perf_log,
+            av_stream,
+            screenshot_burst: None,
+            #[cfg(feature = "midi")]
+            midi,
+            dmc_click_reduction: cfg.audio.dmc_click_reduction.clone(),
+            ram_watches: cfg.renderer.ram_watches.clone(),
+            ram_watch_values: Vec::new(),
+            show_mapper_state: false,
+            mapper_state: None,
+            paths: cfg.paths.clone(),
+            save_sync,
+            playtime,
+            play_session: None,
         };
         state.update_region(cfg.deck.region);
         state
@@ -391,6 +657,7 @@ impl State {
             EmulationEvent::RemoveDebugger(debugger) => {
                 self.control_deck.remove_debugger(debugger.clone());
             }
+            EmulationEvent::AttractMode(attract_mode) => self.attract_mode = *attract_mode,
             EmulationEvent::AudioRecord(recording) => {
                 if self.control_deck.is_running() {
                     self.audio_record(*recording);
@@ -423,12 +690,28 @@ impl State {
                                 self.send_frame();
                             }
                         }
+                        DebugStep::Back => self.step_back(),
+                        DebugStep::ReverseContinue => self.reverse_continue(),
                     }
                 }
             }
+            EmulationEvent::DisplayRefreshRate(refresh_rate) => {
+                if self.refresh_rate != *refresh_rate {
+                    self.refresh_rate = *refresh_rate;
+                    self.update_display_speed();
+                }
+            }
             EmulationEvent::EmulatePpuWarmup(enabled) => {
                 self.control_deck.set_emulate_ppu_warmup(*enabled);
             }
+            EmulationEvent::FastForward(fast_forwarding) => {
+                self.fast_forwarding = *fast_forwarding;
+                self.frames_skipped = 0;
+                self.control_deck.set_frame_speed(self.frame_speed());
+                if self.fast_forwarding {
+                    self.add_message(MessageType::Info, "Fast forwarding");
+                }
+            }
             EmulationEvent::InstantRewind => {
                 if self.control_deck.is_running() {
                     self.instant_rewind();
@@ -437,10 +720,22 @@ impl State {
             EmulationEvent::Joypad((player, button, state)) => {
                 if self.control_deck.is_running() {
                     let pressed = *state == ElementState::Pressed;
-                    let joypad = self.control_deck.joypad_mut(*player);
-                    joypad.set_button(*button, pressed);
-                    self.record
-                        .push(self.control_deck.frame_number(), event.clone());
+                    let handled_by_race = self
+                        .race
+                        .as_mut()
+                        .is_some_and(|race| !race.on_joypad(*player, *button, pressed));
+                    if !handled_by_race {
+                        let joypad = self.control_deck.joypad_mut(*player);
+                        joypad.set_button(*button, pressed);
+                        let frame = self.control_deck.frame_number();
+                        self.macros.push(frame, *player, *button, pressed);
+                        self.record.push(frame, event.clone());
+                    }
+                }
+            }
+            EmulationEvent::LoadGhostPath(path) => {
+                if self.control_deck.is_running() {
+                    self.load_ghost_path(path);
                 }
             }
             EmulationEvent::LoadReplay((name, replay)) => {
@@ -453,21 +748,43 @@ impl State {
                     self.load_replay_path(path);
                 }
             }
-            EmulationEvent::LoadRom((name, rom)) => {
-                self.load_rom(name, &mut io::Cursor::new(rom));
-            }
+            EmulationEvent::LoadRom((name, rom)) => self.load_rom(name, rom.as_ref(), true),
             EmulationEvent::LoadRomPath(path) => self.load_rom_path(path),
+            EmulationEvent::LoadHeldRom(repair) => self.load_held_rom(*repair),
             EmulationEvent::LoadState(slot) => self.load_state(*slot),
+            EmulationEvent::LoadStateRing => self.load_state_ring(),
+            EmulationEvent::MacroRecord(slot) => {
+                if self.control_deck.is_running() {
+                    self.macro_record(*slot);
+                }
+            }
+            EmulationEvent::MacroPlay(slot) => {
+                if self.control_deck.is_running() {
+                    self.macro_play(*slot);
+                }
+            }
+            EmulationEvent::ResolveSyncConflict((entry, choice)) => {
+                self.resolve_sync_conflict(entry, *choice);
+            }
             EmulationEvent::RunState(mode) => self.set_run_state(*mode),
             EmulationEvent::ReplayRecord(recording) => {
                 if self.control_deck.is_running() {
                     self.replay_record(*recording);
                 }
             }
+            EmulationEvent::ReplayRecordPath(path) => {
+                if self.control_deck.is_running() {
+                    self.record
+                        .start(self.control_deck.cpu().clone(), Some(path.clone()));
+                }
+            }
             EmulationEvent::Reset(kind) => {
                 self.frame_time_diag.reset();
                 if self.control_deck.is_running() {
                     self.control_deck.reset(*kind);
+                    if let Some(race) = &mut self.race {
+                        race.reset(*kind);
+                    }
                     self.set_run_state(RunState::Running);
                     match kind {
                         ResetKind::Soft => self.add_message(MessageType::Info, "Reset"),
@@ -489,10 +806,62 @@ impl State {
                 }
             }
             EmulationEvent::SaveState(slot) => self.save_state(*slot, false),
+            EmulationEvent::SaveStateRing => self.save_state_ring(),
+            EmulationEvent::ScanBarcode(barcode) => {
+                if self.control_deck.is_running() {
+                    match self.control_deck.scan_barcode(barcode) {
+                        Ok(()) => {
+                            self.record
+                                .push(self.control_deck.frame_number(), event.clone());
+                            self.add_message(
+                                MessageType::Info,
+                                format!("Scanned Barcode: {barcode}"),
+                            );
+                        }
+                        Err(err) => self.on_error(err),
+                    }
+                }
+            }
+            EmulationEvent::StartRace((viewport_id, inputs)) => {
+                if self.control_deck.is_running() {
+                    self.start_race(*viewport_id, *inputs);
+                }
+            }
+            EmulationEvent::StopRace => self.stop_race(),
+            EmulationEvent::UnloadGhost => self.unload_ghost(),
             EmulationEvent::ShowFrameStats(show) => {
                 self.frame_time_diag.reset();
                 self.show_frame_stats = *show;
             }
+            EmulationEvent::Breakpoints((breakpoints, enabled)) => {
+                let mut current = self.control_deck.breakpoints();
+                current.set(*breakpoints, *enabled);
+                self.control_deck.set_breakpoints(current);
+            }
+            EmulationEvent::Profile(enabled) => {
+                self.control_deck.set_profiling(*enabled);
+                self.update_profile();
+            }
+            EmulationEvent::ClearProfile => self.control_deck.clear_profile(),
+            EmulationEvent::ShowMapperState(show) => {
+                self.show_mapper_state = *show;
+                self.control_deck.set_log_mapper_writes(*show);
+                self.mapper_state = None;
+                self.update_mapper_state();
+            }
+            EmulationEvent::ExportFrameStats(path) => self.export_frame_stats(path),
+            EmulationEvent::ExportInputScript(path) => self.export_input_script(path),
+            EmulationEvent::ShowPpuLayers(layers) => self.control_deck.set_ppu_layers(*layers),
+            EmulationEvent::WriteOam((addr, val)) => self.control_deck.write_oam(*addr, *val),
+            EmulationEvent::WritePalette((addr, val)) => {
+                self.control_deck.write_palette(*addr, *val);
+            }
+            EmulationEvent::LockPalette((addr, locked)) => {
+                self.control_deck.lock_palette(*addr, *locked);
+            }
+            EmulationEvent::ExportPalette(path) => self.export_palette(path),
+            EmulationEvent::ExportChr(path) => self.export_chr(path),
+            EmulationEvent::ImportChr(path) => self.import_chr(path),
             EmulationEvent::Screenshot => {
                 if self.control_deck.is_running() {
                     match self.save_screenshot() {
@@ -506,7 +875,25 @@ impl State {
                     }
                 }
             }
+            EmulationEvent::ScreenshotBurst(frames) => match frames {
+                Some(frames) if self.control_deck.is_running() => {
+                    self.start_screenshot_burst(*frames);
+                }
+                Some(_) => (),
+                None => self.stop_screenshot_burst(),
+            },
+            EmulationEvent::UndoLoadState => self.undo_load_state(),
             EmulationEvent::UnloadRom => self.unload_rom(),
+            EmulationEvent::VausButton(pressed) => {
+                self.control_deck.set_vaus_button(*pressed);
+                self.record
+                    .push(self.control_deck.frame_number(), event.clone());
+            }
+            EmulationEvent::VausMove(delta) => {
+                self.control_deck.move_vaus(*delta);
+                self.record
+                    .push(self.control_deck.frame_number(), event.clone());
+            }
             EmulationEvent::ZapperAim((x, y)) => {
                 self.control_deck.aim_zapper(*x, *y);
                 self.record
@@ -538,6 +925,12 @@ impl State {
                     );
                 }
             }
+            ConfigEvent::AudioBackend(backend) => {
+                if let Err(err) = self.audio.set_backend(*backend) {
+                    self.on_error(err);
+                }
+                self.control_deck.set_sample_rate(self.audio.sample_rate);
+            }
             ConfigEvent::AudioBuffer(buffer_size) => {
                 if let Err(err) = self.audio.set_buffer_size(*buffer_size) {
                     self.on_error(err);
@@ -558,7 +951,11 @@ impl State {
                     self.on_error(err);
                 }
             }
+            ConfigEvent::AudioRecordingLimits(limits) => self.audio.set_recording_limits(*limits),
             ConfigEvent::AutoLoad(enabled) => self.auto_load = *enabled,
+            ConfigEvent::AutoPeripherals(enabled) => {
+                self.control_deck.set_auto_peripherals(*enabled);
+            }
             ConfigEvent::AutoSave(enabled) => self.auto_save = *enabled,
             ConfigEvent::AutoSaveInterval(interval) => self.auto_save_interval = *interval,
             ConfigEvent::ConcurrentDpad(enabled) => {
@@ -567,6 +964,28 @@ impl State {
             ConfigEvent::CycleAccurate(enabled) => {
                 self.control_deck.set_cycle_accurate(*enabled);
             }
+            ConfigEvent::DmcClickReduction((name, enabled)) => {
+                if *enabled {
+                    self.dmc_click_reduction.insert(name.clone());
+                } else {
+                    self.dmc_click_reduction.remove(name);
+                }
+                if self
+                    .control_deck
+                    .loaded_rom()
+                    .is_some_and(|rom| rom.name == *name)
+                {
+                    self.control_deck.set_dmc_click_reduction(*enabled);
+                }
+            }
+            ConfigEvent::DmcClickReductionRoms(names) => {
+                self.dmc_click_reduction = names.clone();
+                if let Some(rom) = self.control_deck.loaded_rom() {
+                    let enabled = self.dmc_click_reduction.contains(&rom.name);
+                    self.control_deck.set_dmc_click_reduction(enabled);
+                }
+            }
+            ConfigEvent::FastBoot(enabled) => self.control_deck.set_fast_boot(*enabled),
             ConfigEvent::FourPlayer(four_player) => {
                 self.control_deck.set_four_player(*four_player);
             }
@@ -582,6 +1001,12 @@ impl State {
             ConfigEvent::RamState(ram_state) => {
                 self.control_deck.set_ram_state(*ram_state);
             }
+            ConfigEvent::RamWatches(watches) => {
+                self.ram_watches = watches.clone();
+                // Force the new list of values to be sent
+                self.ram_watch_values.clear();
+                self.update_ram_watches();
+            }
             ConfigEvent::Region(region) => {
                 self.control_deck.set_region(*region);
                 self.update_region(*region);
@@ -589,16 +1014,43 @@ impl State {
             ConfigEvent::RewindEnabled(enabled) => self.rewind.set_enabled(*enabled),
             ConfigEvent::RewindInterval(interval) => self.rewind.set_interval(*interval),
             ConfigEvent::RewindSeconds(seconds) => self.rewind.set_seconds(*seconds),
+            ConfigEvent::ReplayHashInterval(interval) => self.record.hash_interval = *interval,
             ConfigEvent::RunAhead(run_ahead) => self.run_ahead = *run_ahead,
+            ConfigEvent::Paths(paths) => {
+                self.control_deck.set_sram_dir(paths.sram_dir());
+                // The sync manifest is stored in the data directory
+                if paths.sync_dir != self.paths.sync_dir || paths.data_dir != self.paths.data_dir {
+                    self.save_sync = SaveSync::new(paths).unwrap_or_else(|err| {
+                        self.on_error(err);
+                        None
+                    });
+                }
+                self.paths = paths.clone();
+            }
             ConfigEvent::MapperRevisions(revs) => {
                 self.control_deck.set_mapper_revisions(*revs);
             }
+            ConfigEvent::SaveRingLen(len) => self.save_ring_len = *len,
             ConfigEvent::SaveSlot(slot) => self.save_slot = *slot,
+            ConfigEvent::FastForwardFrameSkip(frames) => self.fast_forward_frame_skip = *frames,
+            ConfigEvent::FastForwardSpeed(speed) => {
+                self.fast_forward_speed = *speed;
+                self.control_deck.set_frame_speed(self.frame_speed());
+            }
+            ConfigEvent::FastForwardUncapped(uncapped) => self.fast_forward_uncapped = *uncapped,
             ConfigEvent::Speed(speed) => {
                 self.speed = *speed;
-                self.control_deck.set_frame_speed(*speed);
+                self.control_deck.set_frame_speed(self.frame_speed());
+            }
+            ConfigEvent::SpriteLimit(enabled) => self.control_deck.set_sprite_limit(*enabled),
+            ConfigEvent::Stereo(stereo) => self.control_deck.set_stereo(*stereo),
+            ConfigEvent::SyncToDisplay(sync) => {
+                self.sync_to_display = *sync;
+                self.update_display_speed();
             }
             ConfigEvent::VideoFilter(filter) => self.control_deck.set_filter(*filter),
+            ConfigEvent::VausConnected(connected) => self.control_deck.connect_vaus(*connected),
+            ConfigEvent::ZapperConfig(config) => self.control_deck.set_zapper_config(*config),
             ConfigEvent::ZapperConnected(connected) => {
                 self.control_deck.connect_zapper(*connected);
             }
@@ -606,17 +1058,85 @@ impl State {
         }
     }
 
+    /// The current emulation speed, taking fast forwarding and display synchronization into
+    /// account.
+    fn frame_speed(&self) -> f32 {
+        let speed = if self.fast_forwarding {
+            self.fast_forward_speed
+        } else {
+            self.speed
+        };
+        speed * self.display_speed
+    }
+
+    /// Adjusts emulation speed slightly to match the display refresh rate when it's close to a
+    /// multiple of the NES frame rate (e.g. 59.94 Hz, 120 Hz), avoiding periodic stutter from
+    /// repeated or skipped frames.
+    fn update_display_speed(&mut self) {
+        const MAX_ADJUSTMENT: f32 = 0.01;
+
+        let frame_rate = self.target_frame_duration.as_secs_f32().recip();
+        self.display_speed = self
+            .refresh_rate
+            .filter(|_| self.sync_to_display)
+            .and_then(|refresh_rate| {
+                let multiple = (refresh_rate / frame_rate).round();
+                (multiple >= 1.0).then(|| refresh_rate / multiple / frame_rate)
+            })
+            .filter(|ratio| (ratio - 1.0).abs() <= MAX_ADJUSTMENT)
+            .unwrap_or(1.0);
+        self.control_deck.set_frame_speed(self.frame_speed());
+    }
+
+    /// Whether emulation should run as fast as possible.
+    const fn uncapped(&self) -> bool {
+        self.fast_forwarding && self.fast_forward_uncapped
+    }
+
+    /// The target duration of a single frame, scaled by the current emulation speed.
+    fn frame_duration(&self) -> Duration {
+        self.target_frame_duration.div_f32(self.frame_speed())
+    }
+
     fn update_frame_stats(&mut self) {
-        if !self.show_frame_stats {
+        if !self.show_frame_stats && self.perf_log.is_none() {
             return;
         }
 
         #[cfg(feature = "profiling")]
         puffin::profile_function!();
 
-        self.frame_time_diag
-            .push(self.last_frame_time.elapsed().as_secs_f32());
+        let elapsed = self.last_frame_time.elapsed();
+        // A frame is late if it took more than half a frame longer than expected
+        if !self.uncapped() && elapsed > self.frame_duration().mul_f32(1.5) {
+            self.frame_time_diag.late_frames += 1;
+        }
+        self.frame_time_diag.push(elapsed.as_secs_f32());
         self.last_frame_time = Instant::now();
+
+        let sample = FrameSample {
+            frame: self.control_deck.frame_number(),
+            frame_time_ms: elapsed.as_secs_f32() * 1000.0,
+            emulation_time_ms: self.frame_time_diag.emulation_time * 1000.0,
+            audio_buffer_fill: self.audio.buffer_fill(),
+            rewind_memory: self.rewind.memory_usage(),
+        };
+        if let Some(perf_log) = &mut self.perf_log {
+            if let Err(err) = perf_log.push(&sample) {
+                error!("{err:?}");
+                self.perf_log = None;
+            }
+        }
+        self.frame_time_diag.push_sample(sample);
+
+        if self.show_frame_stats {
+            let stats = self.frame_stats();
+            self.tx.event(RendererEvent::FrameStats(stats));
+        }
+    }
+
+    /// Summarize the recent frame history.
+    fn frame_stats(&mut self) -> FrameStats {
         let frame_time = self.frame_time_diag.avg();
         let frame_time_max = self
             .frame_time_diag
@@ -630,30 +1150,219 @@ impl State {
         if !fps_min.is_finite() {
             fps_min = 0.0;
         }
-        self.tx.event(RendererEvent::FrameStats(FrameStats {
+        let sample = self.frame_time_diag.samples.back();
+        FrameStats {
             timestamp: Instant::now(),
             fps,
             fps_min,
             frame_time: frame_time * 1000.0,
             frame_time_max: frame_time_max * 1000.0,
             frame_count: self.frame_time_diag.frame_count,
-        }));
+            late_frames: self.frame_time_diag.late_frames,
+            dropped_frames: self.frame_time_diag.dropped_frames,
+            last_frame_time: sample.map_or(0.0, |sample| sample.frame_time_ms),
+            emulation_time: sample.map_or(0.0, |sample| sample.emulation_time_ms),
+            audio_buffer_fill: sample.map_or(0.0, |sample| sample.audio_buffer_fill),
+            rewind_memory: sample.map_or(0, |sample| sample.rewind_memory),
+        }
+    }
+
+    fn export_frame_stats(&mut self, path: &Path) {
+        let stats = self.frame_stats();
+        match perf_log::export(path, &stats, &self.frame_time_diag.samples) {
+            Ok(()) => self.add_message(
+                MessageType::Info,
+                format!("Exported frame stats to {path:?}."),
+            ),
+            Err(err) => self.on_error(err),
+        }
+    }
+
+    fn export_input_script(&mut self, path: &Path) {
+        match replay::export_input_script(path) {
+            Ok(script_path) => self.add_message(
+                MessageType::Info,
+                format!("Exported input script to {script_path:?}."),
+            ),
+            Err(err) => self.on_error(err),
+        }
+    }
+
+    fn export_palette(&mut self, path: &Path) {
+        let palette = self.control_deck.ppu().export_palette();
+        match std::fs::write(path, palette).with_context(|| format!("failed to write {path:?}")) {
+            Ok(()) => self.add_message(MessageType::Info, format!("Exported palette to {path:?}.")),
+            Err(err) => self.on_error(err),
+        }
+    }
+
+    fn export_chr(&mut self, path: &Path) {
+        let mut indices = vec![0x00; (Ppu::WIDTH * Ppu::WIDTH / 2) as usize];
+        self.control_deck
+            .ppu()
+            .load_pattern_table_indices(&mut indices);
+        let pixels = indices
+            .into_iter()
+            .map(|index| Self::CHR_SHADES[usize::from(index)])
+            .collect::<Vec<_>>();
+        let res = image::GrayImage::from_raw(Ppu::WIDTH, Ppu::WIDTH / 2, pixels)
+            .ok_or_else(|| anyhow!("failed to create image buffer"))
+            .and_then(|image| {
+                image
+                    .save(path)
+                    .with_context(|| format!("failed to save {path:?}"))
+            });
+        match res {
+            Ok(()) => self.add_message(
+                MessageType::Info,
+                format!("Exported pattern tables to {path:?}."),
+            ),
+            Err(err) => self.on_error(err),
+        }
+    }
+
+    fn import_chr(&mut self, path: &Path) {
+        let res = image::open(path)
+            .with_context(|| format!("failed to open {path:?}"))
+            .and_then(|image| {
+                let image = image.into_luma8();
+                let (width, height) = image.dimensions();
+                if (width, height) != (Ppu::WIDTH, Ppu::WIDTH / 2) {
+                    bail!(
+                        "expected a {}x{} pattern table image, found {width}x{height}",
+                        Ppu::WIDTH,
+                        Ppu::WIDTH / 2
+                    );
+                }
+                // Round each shade to the nearest of `CHR_SHADES`
+                let indices = image
+                    .pixels()
+                    .map(|pixel| ((u16::from(pixel[0]) + 42) / 85) as u8)
+                    .collect::<Vec<_>>();
+                if !self
+                    .control_deck
+                    .ppu_mut()
+                    .write_pattern_table_indices(&indices)
+                {
+                    bail!("the loaded ROM uses CHR-ROM, which can't be edited");
+                }
+                Ok(())
+            });
+        match res {
+            Ok(()) => self.add_message(
+                MessageType::Info,
+                format!("Imported pattern tables from {path:?}."),
+            ),
+            Err(err) => self.on_error(err),
+        }
     }
 
     fn send_frame(&mut self) {
+        self.send_video_frame();
+        self.update_ram_watches();
+        self.update_mapper_state();
+        if self.control_deck.frame_number() % Self::PROFILE_REPORT_INTERVAL == 0 {
+            self.update_profile();
+        }
+    }
+
+    /// Send only the current frame to be rendered, from the HD pack renderer if one is loaded.
+    fn send_video_frame(&mut self) {
         match self.frame_tx.try_send_ref() {
-            Ok(mut frame) => self.control_deck.frame_buffer_into(&mut frame),
-            Err(TrySendError::Full(_)) => trace!("dropped frame"),
+            Ok(mut frame) => {
+                #[cfg(feature = "hd-pack")]
+                if let Some(hd_frame) = self.control_deck.hd_frame_buffer() {
+                    frame.clear();
+                    frame.extend_from_slice(hd_frame.pixels);
+                    return;
+                }
+                // Frames are larger while an HD pack is loaded
+                frame.resize(Frame::SIZE, 0);
+                self.control_deck.frame_buffer_into(&mut frame);
+            }
+            Err(TrySendError::Full(_)) => {
+                trace!("dropped frame");
+                self.frame_time_diag.dropped_frames += 1;
+            }
             Err(_) => shutdown(&self.tx, "failed to get frame"),
         }
     }
 
+    /// Send the game code profile to be displayed, if profiling.
+    fn update_profile(&mut self) {
+        if self.control_deck.is_profiling() {
+            self.tx.event(RendererEvent::ProfileReport(
+                self.control_deck.profile_report(),
+            ));
+        }
+    }
+
+    /// Send the mapper state to be displayed if it has changed.
+    fn update_mapper_state(&mut self) {
+        if !self.show_mapper_state || !self.control_deck.is_running() {
+            return;
+        }
+
+        // The frame number increments at the post-render scanline, so the frame just drawn is the
+        // previous one
+        let frame = self.control_deck.frame_number();
+        let bus = &self.control_deck.ppu().bus;
+        let state = (
+            bus.mapper.debug_state(),
+            bus.mapper_writes.iter().copied().collect::<Vec<_>>(),
+            bus.mapper_irqs
+                .iter()
+                .filter(|irq| irq.frame + 1 >= frame)
+                .copied()
+                .collect::<Vec<_>>(),
+        );
+        if self.mapper_state.as_ref() != Some(&state) {
+            self.mapper_state = Some(state.clone());
+            self.tx.event(RendererEvent::MapperState(state));
+        }
+    }
+
+    /// Read all RAM watches and send their values to be displayed if any have changed.
+    fn update_ram_watches(&mut self) {
+        if self.ram_watches.is_empty() || !self.control_deck.is_running() {
+            return;
+        }
+
+        let values = self
+            .ram_watches
+            .iter()
+            .map(|watch| watch.read(&self.control_deck))
+            .collect::<Vec<_>>();
+        if values != self.ram_watch_values {
+            self.ram_watch_values.clone_from(&values);
+            self.tx.event(RendererEvent::RamWatchValues(values));
+        }
+    }
+
+    /// Pause on hitting a breakpoint and show where it was hit.
+    fn on_breakpoint(&mut self, breakpoints: Breakpoints) {
+        // Manually paused so it stays paused until resumed, and the UI knows it
+        self.set_run_state(RunState::ManuallyPaused);
+        self.tx
+            .event(EmulationEvent::RunState(RunState::ManuallyPaused));
+        let cpu = self.control_deck.cpu();
+        let ppu = self.control_deck.ppu();
+        let location = format!("${:04X} after {}", cpu.pc, cpu.instr);
+        let msg = format!(
+            "Breakpoint: {breakpoints:?} at {location}, scanline {}, cycle {}",
+            ppu.scanline, ppu.cycle
+        );
+        self.add_message(MessageType::Info, msg);
+        self.send_frame();
+    }
+
     fn set_run_state(&mut self, mode: RunState) {
-        if !self.control_deck.cpu_corrupted() {
+        // A corrupted CPU can't resume, but can still be paused to inspect it
+        if mode.paused() || !self.control_deck.cpu_corrupted() {
             self.run_state = mode;
             if self.run_state.paused() {
                 if let Some(rom) = self.control_deck.loaded_rom() {
-                    if let Err(err) = self.record.stop(&rom.name) {
+                    if let Err(err) = self.record.stop(&rom.name, &self.paths.replay_dir()) {
                         self.on_error(err);
                     }
                 }
@@ -663,16 +1372,27 @@ impl State {
                 self.last_frame_time = Instant::now();
             }
             self.audio.pause(self.run_state.paused());
+            if let Some(session) = &mut self.play_session {
+                session.pause(self.run_state.paused());
+            }
+            #[cfg(feature = "midi")]
+            if let Some(midi) = self.midi.as_mut().filter(|_| self.run_state.paused()) {
+                midi.silence();
+            }
         }
     }
 
     fn save_state(&mut self, slot: u8, auto: bool) {
         if let Some(rom) = self.control_deck.loaded_rom() {
-            let data_dir = Config::save_path(&rom.name, slot);
-            match self.control_deck.save_state(data_dir) {
+            let name = rom.name.clone();
+            let save_path = self.paths.save_path(&name, slot);
+            match self.control_deck.save_state(save_path) {
                 Ok(_) => {
+                    self.tx.event(RendererEvent::StateSaved(slot));
                     if !auto {
                         self.add_message(MessageType::Info, format!("State {slot} Saved"));
+                        // Periodic auto-saves are synced when the ROM is unloaded instead
+                        self.sync_saves(vec![SyncEntry::save_state(&self.paths, &name, slot)]);
                     }
                 }
                 Err(err) => self.on_error(err),
@@ -682,9 +1402,16 @@ impl State {
 
     fn load_state(&mut self, slot: u8) {
         if let Some(rom) = self.control_deck.loaded_rom() {
-            let save_path = Config::save_path(&rom.name, slot);
+            let save_path = self.paths.save_path(&rom.name, slot);
+            let mut backup = Vec::new();
+            if let Err(err) = self.control_deck.snapshot_into(&mut backup) {
+                error!("failed to back up state before loading: {err:?}");
+            }
             match self.control_deck.load_state(save_path) {
-                Ok(_) => self.add_message(MessageType::Info, format!("State {slot} Loaded")),
+                Ok(_) => {
+                    self.undo_load_state = (!backup.is_empty()).then_some(backup);
+                    self.add_message(MessageType::Info, format!("State {slot} Loaded"));
+                }
                 Err(control_deck::Error::NoSaveStateFound) => {
                     self.add_message(MessageType::Warn, format!("State {slot} Not Found"));
                 }
@@ -695,20 +1422,200 @@ impl State {
         }
     }
 
+    fn save_state_ring(&mut self) {
+        let Some(rom) = self.control_deck.loaded_rom() else {
+            return;
+        };
+        let name = rom.name.clone();
+        let index = self.save_ring.next_save_index(self.save_ring_len);
+        let save_path = self.paths.save_ring_path(&name, index);
+        if let Err(err) = self.control_deck.save_state(save_path) {
+            return self.on_error(err);
+        }
+        self.save_ring.saved(index, Local::now().timestamp_millis());
+        match self.save_ring.save(self.paths.save_ring_index_path(&name)) {
+            Ok(()) => {
+                self.add_message(MessageType::Info, format!("Ring State {} Saved", index + 1));
+            }
+            Err(err) => self.on_error(err),
+        }
+    }
+
+    fn load_state_ring(&mut self) {
+        let Some(rom) = self.control_deck.loaded_rom() else {
+            return;
+        };
+        let Some((index, saved_at)) = self.save_ring.next_load_index(self.save_ring_len) else {
+            return self.add_message(MessageType::Warn, "No Ring States Saved");
+        };
+        let save_path = self.paths.save_ring_path(&rom.name, index);
+        let mut backup = Vec::new();
+        if let Err(err) = self.control_deck.snapshot_into(&mut backup) {
+            error!("failed to back up state before loading: {err:?}");
+        }
+        match self.control_deck.load_state(save_path) {
+            Ok(_) => {
+                self.undo_load_state = (!backup.is_empty()).then_some(backup);
+                let saved_at = chrono::DateTime::from_timestamp_millis(saved_at)
+                    .map(|saved_at| {
+                        let saved_at = saved_at.with_timezone(&Local);
+                        saved_at.format(" from %H:%M:%S").to_string()
+                    })
+                    .unwrap_or_default();
+                self.add_message(
+                    MessageType::Info,
+                    format!("Ring State {} Loaded{saved_at}", index + 1),
+                );
+            }
+            Err(control_deck::Error::NoSaveStateFound) => {
+                self.add_message(
+                    MessageType::Warn,
+                    format!("Ring State {} Not Found", index + 1),
+                );
+            }
+            Err(err) => self.on_error(err),
+        }
+    }
+
+    fn undo_load_state(&mut self) {
+        let Some(backup) = self.undo_load_state.take() else {
+            return self.add_message(MessageType::Warn, "No Load State to Undo");
+        };
+        match self.control_deck.restore_snapshot(&backup) {
+            Ok(()) => self.add_message(MessageType::Info, "Load State Undone"),
+            Err(err) => self.on_error(err),
+        }
+    }
+
+    /// Mirror save files to the sync provider, if enabled, prompting to resolve any conflicts.
+    fn sync_saves(&mut self, entries: Vec<SyncEntry>) {
+        let Some(save_sync) = &mut self.save_sync else {
+            return;
+        };
+        match save_sync.sync(&entries) {
+            Ok(result) => {
+                for entry in &result.pulled {
+                    self.on_sync_pulled(entry);
+                }
+                if !result.conflicts.is_empty() {
+                    self.add_message(
+                        MessageType::Warn,
+                        "Saves changed on another device, choose which to keep",
+                    );
+                    self.tx
+                        .event(RendererEvent::SyncConflicts(result.conflicts));
+                }
+            }
+            Err(err) => self.on_error(err),
+        }
+    }
+
+    fn resolve_sync_conflict(&mut self, entry: &SyncEntry, choice: SyncChoice) {
+        let Some(save_sync) = &mut self.save_sync else {
+            return;
+        };
+        match save_sync.resolve(entry, choice) {
+            Ok(()) => {
+                if choice == SyncChoice::KeepRemote {
+                    self.on_sync_pulled(entry);
+                }
+            }
+            Err(err) => self.on_error(err),
+        }
+    }
+
+    /// Reload Save RAM replaced by a sync if it belongs to the loaded ROM.
+    fn on_sync_pulled(&mut self, entry: &SyncEntry) {
+        let loaded = self
+            .control_deck
+            .loaded_rom()
+            .is_some_and(|rom| rom.name == entry.name);
+        if loaded && entry.kind == SyncKind::Sram {
+            if let Err(err) = self.control_deck.load_sram(&entry.path) {
+                self.on_error(err);
+            }
+        }
+    }
+
+    /// Run `f`, recovering from any panic by unloading the current ROM and reporting the panic
+    /// instead of taking down the entire application. Requires `panic = "unwind"`, which dev and
+    /// `dist` builds use, while `release` builds abort and wasm doesn't support unwinding.
+    fn catch_panic(&mut self, f: impl FnOnce(&mut Self)) {
+        let Err(payload) = panic::catch_unwind(AssertUnwindSafe(|| f(self))) else {
+            return;
+        };
+
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic".to_string());
+        let backtrace = PANIC_BACKTRACE
+            .take()
+            .map(|backtrace| backtrace.to_string())
+            .unwrap_or_default();
+        error!("emulation panicked: {message}");
+
+        let recovered = panic::catch_unwind(AssertUnwindSafe(|| {
+            self.rewinding = false;
+            // Emulation state may be inconsistent, so don't save over a good save state or SRAM
+            self.close_rom(false);
+        }));
+        if recovered.is_err() {
+            return shutdown(&self.tx, "failed to recover from emulation panic");
+        }
+
+        self.tx
+            .event(UiEvent::EmulationPanic { message, backtrace });
+    }
+
     fn unload_rom(&mut self) {
+        self.close_rom(true);
+    }
+
+    /// Unload the current ROM, only saving its state, SRAM and syncing saves if `save` is set.
+    fn close_rom(&mut self, save: bool) {
         if let Some(rom) = self.control_deck.loaded_rom() {
-            if self.auto_save {
-                let save_path = Config::save_path(&rom.name, self.save_slot);
+            let name = rom.name.clone();
+            if save && self.auto_save && !self.attract_mode {
+                let save_path = self.paths.save_path(&name, self.save_slot);
                 if let Err(err) = self.control_deck.save_state(save_path) {
                     self.on_error(err);
                 }
             }
             self.replay_record(false);
+            if self.macros.recording().is_some() {
+                self.stop_macro_recording();
+            }
+            self.macros.set_macros(Macros::default());
+            self.save_ring = SaveRing::default();
+            self.undo_load_state = None;
             self.rewind.clear();
+            self.stop_race();
+            self.unload_ghost();
+            self.stop_screenshot_burst();
             let _ = self.audio.stop();
-            if let Err(err) = self.control_deck.unload_rom() {
-                self.on_error(err);
+            #[cfg(feature = "midi")]
+            if let Some(midi) = &mut self.midi {
+                midi.silence();
             }
+            if save {
+                if let Err(err) = self.control_deck.unload_rom() {
+                    self.on_error(err);
+                }
+                self.sync_saves(SyncEntry::all(&self.paths, &name));
+            } else {
+                self.control_deck.discard_rom();
+            }
+            if let Some(session) = self.play_session.take() {
+                self.playtime.add_time(&session.name, session.played());
+                self.save_playtime();
+            }
+            #[cfg(feature = "hd-pack")]
+            self.control_deck.unload_hd_pack();
+            self.hd_pack_loaded = false;
+            self.ram_watch_values.clear();
+            self.mapper_state = None;
             self.tx.event(RendererEvent::RomUnloaded);
             self.tx.event(RendererEvent::RequestRedraw {
                 viewport_id: ViewportId::ROOT,
@@ -719,8 +1626,35 @@ impl State {
     }
 
     fn on_load_rom(&mut self, rom: LoadedRom) {
-        if self.auto_load {
-            let save_path = Config::save_path(&rom.name, self.save_slot);
+        self.control_deck
+            .set_dmc_click_reduction(self.dmc_click_reduction.contains(&rom.name));
+        #[cfg(feature = "hd-pack")]
+        self.load_hd_pack(&rom.name);
+        if let Some(peripherals) = rom.peripherals {
+            if peripherals.zapper {
+                self.add_message(MessageType::Info, "Zapper Gun connected for this game");
+            }
+            match peripherals.four_player {
+                FourPlayer::FourScore => {
+                    self.add_message(MessageType::Info, "Four Score enabled for this game");
+                }
+                FourPlayer::Satellite => {
+                    self.add_message(MessageType::Info, "Satellite enabled for this game");
+                }
+                FourPlayer::Disabled => (),
+            }
+        }
+        match Macros::load(self.paths.macro_path(&rom.name)) {
+            Ok(macros) => self.macros.set_macros(macros),
+            Err(err) => self.on_error(err),
+        }
+        match SaveRing::load(self.paths.save_ring_index_path(&rom.name)) {
+            Ok(save_ring) => self.save_ring = save_ring,
+            Err(err) => self.on_error(err),
+        }
+        self.sync_saves(SyncEntry::all(&self.paths, &rom.name));
+        if self.auto_load && !self.attract_mode {
+            let save_path = self.paths.save_path(&rom.name, self.save_slot);
             if let Err(err) = self.control_deck.load_state(save_path) {
                 if !matches!(err, control_deck::Error::NoSaveStateFound) {
                     error!("failed to load state: {err:?}");
@@ -730,6 +1664,11 @@ impl State {
         if let Err(err) = self.audio.start() {
             self.on_error(err);
         }
+        if !self.attract_mode {
+            self.playtime.start_session(&rom.name);
+            self.play_session = Some(PlaySession::new(&rom.name));
+            self.save_playtime();
+        }
         self.set_run_state(RunState::Running);
         self.tx.event(RendererEvent::RomLoaded(rom));
         self.tx.event(RendererEvent::RequestRedraw {
@@ -742,20 +1681,129 @@ impl State {
         self.last_frame_time = Instant::now();
     }
 
-    fn load_rom_path(&mut self, path: impl AsRef<std::path::Path>) {
+    /// Load the HD pack for a ROM from its HD pack directory, if there is one.
+    #[cfg(feature = "hd-pack")]
+    fn load_hd_pack(&mut self, name: &str) {
+        use tetanes_core::ppu::hd_pack::HdPack;
+
+        let dir = self.paths.hd_pack_dir(name);
+        if !fs::exists(&dir.join(HdPack::DEFINITION_FILE)) {
+            return;
+        }
+        match self.control_deck.load_hd_pack(&dir) {
+            Ok(()) => {
+                self.hd_pack_loaded = true;
+                self.add_message(MessageType::Info, "Loaded HD pack");
+            }
+            Err(err) => self.on_error(err),
+        }
+    }
+
+    fn save_playtime(&mut self) {
+        if let Err(err) = self.playtime.save(self.paths.playtime_path()) {
+            self.on_error(err);
+        }
+        self.tx
+            .event(RendererEvent::Playtime(self.playtime.clone()));
+    }
+
+    fn start_race(&mut self, viewport_id: ViewportId, inputs: RaceInputs) {
+        self.race = Some(Race::start(viewport_id, inputs, &mut self.control_deck));
+        self.frame_time_diag.reset();
+        self.set_run_state(RunState::Running);
+        self.add_message(
+            MessageType::Info,
+            format!(
+                "Started second window with {}",
+                inputs.as_str().to_lowercase()
+            ),
+        );
+    }
+
+    fn stop_race(&mut self) {
+        if self.race.take().is_some() {
+            self.tx.event(RendererEvent::RaceStopped);
+        }
+    }
+
+    fn load_ghost_path(&mut self, path: impl AsRef<Path>) {
         let path = path.as_ref();
-        self.unload_rom();
-        match self.control_deck.load_rom_path(path) {
-            Ok(rom) => self.on_load_rom(rom),
+        match Ghost::load_path(&mut self.control_deck, path) {
+            Ok(ghost) => {
+                self.ghost = Some(ghost);
+                self.add_message(
+                    MessageType::Info,
+                    format!("Loaded Ghost Replay {:?}", path.to_string_lossy()),
+                );
+                self.set_run_state(RunState::Running);
+                self.tx.event(RendererEvent::GhostLoaded);
+            }
             Err(err) => self.on_error(err),
         }
     }
 
-    fn load_rom(&mut self, name: &str, rom: &mut impl Read) {
+    fn unload_ghost(&mut self) {
+        if self.ghost.take().is_some() {
+            self.tx.event(RendererEvent::GhostUnloaded);
+        }
+    }
+
+    fn load_rom_path(&mut self, path: impl AsRef<std::path::Path>) {
+        let path = path.as_ref();
+        match std::fs::read(path).with_context(|| format!("failed to read rom {path:?}")) {
+            Ok(rom) => self.load_rom(fs::filename(path), &rom, true),
+            Err(err) => {
+                self.unload_rom();
+                self.on_error(err);
+            }
+        }
+    }
+
+    /// Load `rom`, first prompting the user to repair any header problems if `check_header` is
+    /// set.
+    fn load_rom(&mut self, name: &str, rom: &[u8], check_header: bool) {
+        if check_header {
+            let issues = header_fix::detect(rom);
+            if !issues.is_empty() {
+                warn!("rom {name:?} has header issues: {issues:?}");
+                self.tx.event(RendererEvent::RomHeaderIssues((
+                    name.to_string(),
+                    issues.clone(),
+                )));
+                self.held_rom = Some((name.to_string(), rom.to_vec(), issues));
+                return;
+            }
+        }
+        self.held_rom = None;
         self.unload_rom();
-        match self.control_deck.load_rom(name, rom) {
+        match self.control_deck.load_rom(name, &mut io::Cursor::new(rom)) {
             Ok(rom) => self.on_load_rom(rom),
-            Err(err) => self.on_error(err),
+            Err(err) => self.on_load_rom_error(name, err),
+        }
+    }
+
+    /// Load the ROM held back for header problems, repairing its header if `repair` is set.
+    fn load_held_rom(&mut self, repair: bool) {
+        let Some((name, rom, issues)) = self.held_rom.take() else {
+            return;
+        };
+        if repair {
+            self.load_rom(&name, &header_fix::repair(&rom, &issues), false);
+        } else {
+            self.load_rom(&name, &rom, false);
+        }
+    }
+
+    fn on_load_rom_error(&mut self, name: &str, err: control_deck::Error) {
+        match err {
+            control_deck::Error::UnimplementedMapper(mapper_num) => {
+                error!("failed to load rom {name:?}: {err:?}");
+                self.tx.event(RendererEvent::UnsupportedMapper((
+                    name.to_string(),
+                    mapper_num,
+                )));
+            }
+            err => self.on_error(err),
         }
     }
 
@@ -775,14 +1823,29 @@ impl State {
 
     fn load_replay_path(&mut self, path: impl AsRef<Path>) {
         let path = path.as_ref();
-        match self.replay.load_path(path) {
+        let start = if replay::is_input_script(path) {
+            std::fs::File::open(path)
+                .with_context(|| format!("failed to open input script {path:?}"))
+                .and_then(|script| {
+                    self.replay
+                        .load_input_script(io::BufReader::new(script), &self.control_deck)
+                })
+        } else {
+            self.replay.load_path(path)
+        };
+        match start {
             Ok(start) => self.on_load_replay(start, path.to_string_lossy()),
             Err(err) => self.on_error(err),
         }
     }
 
     fn load_replay(&mut self, name: &str, replay: &mut impl Read) {
-        match self.replay.load(replay) {
+        let start = if replay::is_input_script(name) {
+            self.replay.load_input_script(replay, &self.control_deck)
+        } else {
+            self.replay.load(replay)
+        };
+        match start {
             Ok(start) => self.on_load_replay(start, name),
             Err(err) => self.on_error(err),
         }
@@ -793,23 +1856,36 @@ impl State {
         self.frame_latency = (self.audio.latency.as_secs_f32()
             / self.target_frame_duration.as_secs_f32())
         .ceil() as usize;
+        self.update_display_speed();
     }
 
     fn audio_record(&mut self, recording: bool) {
         if self.control_deck.is_running() {
             if !recording && self.audio.is_recording() {
                 match self.audio.stop_recording() {
-                    Ok(Some(filename)) => {
-                        self.add_message(
+                    Ok(filenames) => match filenames.as_slice() {
+                        [] => (),
+                        [filename] => self.add_message(
                             MessageType::Info,
-                            format!("Saved Replay Recording {filename:?}"),
-                        );
-                    }
+                            format!("Saved Audio Recording {filename:?}"),
+                        ),
+                        [filename, ..] => self.add_message(
+                            MessageType::Info,
+                            format!(
+                                "Saved Audio Recording in {} parts, starting with {filename:?}",
+                                filenames.len()
+                            ),
+                        ),
+                    },
                     Err(err) => self.on_error(err),
-                    _ => (),
                 }
             } else if recording {
-                if let Err(err) = self.audio.start_recording() {
+                let title = self
+                    .control_deck
+                    .loaded_rom()
+                    .map(|rom| rom.name.clone())
+                    .unwrap_or_default();
+                if let Err(err) = self.audio.start_recording(&self.paths.audio_dir(), &title) {
                     self.on_error(err);
                 }
             }
@@ -819,9 +1895,9 @@ impl State {
     fn replay_record(&mut self, recording: bool) {
         if self.control_deck.is_running() {
             if recording {
-                self.record.start(self.control_deck.cpu().clone());
+                self.record.start(self.control_deck.cpu().clone(), None);
             } else if let Some(rom) = self.control_deck.loaded_rom() {
-                match self.record.stop(&rom.name) {
+                match self.record.stop(&rom.name, &self.paths.replay_dir()) {
                     Ok(Some(filename)) => {
                         self.add_message(
                             MessageType::Info,
@@ -835,8 +1911,51 @@ impl State {
         }
     }
 
+    fn macro_record(&mut self, slot: u8) {
+        if let Some(recording) = self.macros.recording() {
+            self.stop_macro_recording();
+            // Toggling a different slot while recording starts recording it instead
+            if recording == slot {
+                return;
+            }
+        }
+        self.macros
+            .start_recording(slot, self.control_deck.frame_number());
+        self.add_message(MessageType::Info, format!("Recording Macro {slot}..."));
+        self.tx.event(RendererEvent::MacroRecording(Some(slot)));
+    }
+
+    fn stop_macro_recording(&mut self) {
+        self.tx.event(RendererEvent::MacroRecording(None));
+        let Some(slot) = self.macros.stop_recording(self.control_deck.frame_number()) else {
+            self.add_message(MessageType::Warn, "Macro not saved - no input recorded");
+            return;
+        };
+        let Some(rom) = self.control_deck.loaded_rom() else {
+            return;
+        };
+        match self.macros.macros.save(self.paths.macro_path(&rom.name)) {
+            Ok(()) => {
+                let frames = self.macros.macros.get(slot).map_or(0, |m| m.frame_count());
+                self.add_message(
+                    MessageType::Info,
+                    format!("Saved Macro {slot} ({frames} frames)"),
+                );
+            }
+            Err(err) => self.on_error(err),
+        }
+    }
+
+    fn macro_play(&mut self, slot: u8) {
+        if self.macros.recording().is_some() {
+            self.add_message(MessageType::Warn, "Can't play a macro while recording one");
+        } else if !self.macros.play(slot, self.control_deck.frame_number()) {
+            self.add_message(MessageType::Warn, format!("Macro {slot} is empty"));
+        }
+    }
+
     fn save_screenshot(&mut self) -> anyhow::Result<PathBuf> {
-        let picture_dir = Config::default_picture_dir();
+        let picture_dir = self.paths.screenshot_dir();
         let filename = picture_dir
             .join(
                 Local::now()
@@ -863,6 +1982,30 @@ impl State {
             .with_context(|| format!("failed to save screenshot: {filename:?}"))
     }
 
+    fn start_screenshot_burst(&mut self, frames: u32) {
+        self.stop_screenshot_burst();
+        match ScreenshotBurst::start(&self.paths.screenshot_dir(), frames, self.tx.clone()) {
+            Ok(burst) => {
+                self.screenshot_burst = Some(burst);
+                self.add_message(MessageType::Info, "Capturing Burst Screenshots...");
+            }
+            Err(err) => self.on_error(err),
+        }
+    }
+
+    /// Stop capturing a screenshot burst. Frames already captured are still written.
+    fn stop_screenshot_burst(&mut self) {
+        if let Some(burst) = self.screenshot_burst.take() {
+            debug!("captured {} burst screenshots", burst.captured());
+        }
+    }
+
+    /// Whether emulation is paused or no ROM is loaded, and any remaining audio has finished
+    /// playing.
+    fn is_idle(&mut self) -> bool {
+        (self.run_state.paused() || !self.control_deck.is_running()) && self.audio.try_suspend()
+    }
+
     fn park_duration(&self) -> Option<Duration> {
         #[cfg(feature = "profiling")]
         puffin::profile_function!();
@@ -871,12 +2014,17 @@ impl State {
         // Park if we're paused, occluded, or not running
         let duration = if self.run_state.paused() || !self.control_deck.is_running() {
             Some(self.target_frame_duration - park_epsilon)
+        } else if self.uncapped() && !self.rewinding {
+            None
         } else if self.rewinding || !self.audio.enabled() {
-            (self.clock_time_accumulator < self.target_frame_duration.as_secs_f32()).then(|| {
-                Duration::from_secs_f32(
-                    self.target_frame_duration.as_secs_f32() - self.clock_time_accumulator,
-                )
-                .saturating_sub(park_epsilon)
+            let frame_duration = if self.rewinding {
+                self.target_frame_duration
+            } else {
+                self.frame_duration()
+            };
+            (self.clock_time_accumulator < frame_duration.as_secs_f32()).then(|| {
+                Duration::from_secs_f32(frame_duration.as_secs_f32() - self.clock_time_accumulator)
+                    .saturating_sub(park_epsilon)
             })
         } else {
             (self.audio.queued_time() > self.audio.latency)
@@ -901,8 +2049,11 @@ impl State {
         let last_clock_duration = self.last_clock_time.elapsed();
         self.last_clock_time = Instant::now();
         self.clock_time_accumulator += last_clock_duration.as_secs_f32();
-        if self.clock_time_accumulator > 0.020 {
-            self.clock_time_accumulator = 0.020;
+        // Allow catching up on a few frames if we fall behind, but not so many that emulation
+        // visibly speeds up to compensate for a long stall
+        let max_accumulator = Self::MAX_CATCH_UP_FRAMES * self.frame_duration().as_secs_f32();
+        if self.clock_time_accumulator > max_accumulator {
+            self.clock_time_accumulator = max_accumulator;
         }
 
         // If any frames are still pending, request a redraw
@@ -913,52 +2064,153 @@ impl State {
             });
         }
 
+        // Nothing to emulate, so sleep until woken up by a new event to save power
+        if self.is_idle() {
+            if self.threaded {
+                // Wake up to close the suspended audio stream if still idle
+                match self.audio.close_timeout() {
+                    Some(timeout) => thread::park_timeout(timeout),
+                    None => thread::park(),
+                }
+            }
+            return;
+        }
+
         if let Some(park_timeout) = self.park_duration() {
             thread::park_timeout(park_timeout);
             return;
         }
 
         if self.rewinding {
-            match self.rewind.pop() {
-                Some(cpu) => {
-                    self.control_deck.load_cpu(cpu);
-                    self.send_frame();
-                    self.update_frame_stats();
-                }
-                None => self.rewinding = false,
+            if self.rewind.pop(&mut self.control_deck) {
+                self.send_frame();
+                self.update_frame_stats();
+            } else {
+                self.rewinding = false;
             }
         } else {
-            if let Some(event) = self.replay.next(self.control_deck.frame_number()) {
+            while let Some(event) = self.replay.next(self.control_deck.frame_number()) {
+                self.on_emulation_event(&event);
+            }
+            while let Some(event) = self.macros.next(self.control_deck.frame_number()) {
                 self.on_emulation_event(&event);
             }
 
-            let run_ahead = if self.speed > 1.0 { 0 } else { self.run_ahead };
+            // Run-ahead would stop on breakpoints or profile frames that get discarded
+            let run_ahead = if self.frame_speed() > 1.0
+                || self.uncapped()
+                || !self.control_deck.breakpoints().is_empty()
+                || self.control_deck.is_profiling()
+            {
+                0
+            } else {
+                self.run_ahead
+            };
+            // Skip rendering some frames while fast forwarding to reduce render load
+            let skip_frame =
+                self.fast_forwarding && self.frames_skipped < self.fast_forward_frame_skip;
+            if skip_frame {
+                self.frames_skipped += 1;
+            } else {
+                self.frames_skipped = 0;
+            }
+            let uncapped = self.uncapped();
+            let hd_pack_loaded = self.hd_pack_loaded;
+            let audio_channels = self.control_deck.audio_channels();
+            let clock_start = Instant::now();
             let res = self.control_deck.clock_frame_ahead(
                 run_ahead,
                 |_cycles, frame_buffer, audio_samples| {
-                    self.audio.process(audio_samples);
+                    // Audio can't keep up when uncapped, so it's muted
+                    if !uncapped {
+                        self.audio.process(audio_samples, audio_channels);
+                    }
+                    if let Some(av_stream) = &mut self.av_stream {
+                        av_stream.push(frame_buffer, audio_samples, audio_channels);
+                    }
+                    if let Some(burst) = &mut self.screenshot_burst {
+                        burst.push(frame_buffer);
+                    }
+                    // HD pack frames are sent once the frame is done clocking
+                    if skip_frame || hd_pack_loaded {
+                        return;
+                    }
                     match self.frame_tx.try_send_ref() {
                         Ok(mut frame) => {
                             frame.clear();
                             frame.extend_from_slice(frame_buffer);
                         }
-                        Err(TrySendError::Full(_)) => debug!("dropped frame"),
+                        Err(TrySendError::Full(_)) => {
+                            debug!("dropped frame");
+                            self.frame_time_diag.dropped_frames += 1;
+                        }
                         Err(_) => shutdown(&self.tx, "failed to get frame"),
                     }
                 },
             );
+            self.frame_time_diag.emulation_time = clock_start.elapsed().as_secs_f32();
             match res {
                 Ok(()) => {
+                    if hd_pack_loaded && !skip_frame {
+                        self.send_video_frame();
+                    }
                     self.update_frame_stats();
-                    if let Err(err) = self.rewind.push(self.control_deck.cpu()) {
+                    #[cfg(feature = "midi")]
+                    if let Some(midi) = &mut self.midi {
+                        midi.update(self.control_deck.apu());
+                    }
+                    if let Err(err) = self.record.push_hash(&self.control_deck) {
+                        self.on_error(err);
+                    }
+                    if self
+                        .screenshot_burst
+                        .as_ref()
+                        .is_some_and(ScreenshotBurst::is_done)
+                    {
+                        self.stop_screenshot_burst();
+                    }
+                    let speed = self.frame_speed();
+                    let race_res = self.race.as_mut().map(|race| {
+                        race.clock_frame(&self.tx, &self.race_frame_tx, speed, skip_frame)
+                    });
+                    if let Some(Err(err)) = race_res {
+                        self.stop_race();
+                        self.on_error(err);
+                    }
+                    let ghost_res = self
+                        .ghost
+                        .as_mut()
+                        .map(|ghost| ghost.clock_frame(&self.ghost_frame_tx, skip_frame));
+                    if let Some(Err(err)) = ghost_res {
+                        self.unload_ghost();
+                        self.on_error(err);
+                    }
+                    match self.replay.check_hash(&self.control_deck) {
+                        Ok(Some(desync)) => {
+                            warn!("{desync}");
+                            self.add_message(
+                                MessageType::Warn,
+                                format!("Replay desync at frame {}", desync.frame),
+                            );
+                        }
+                        Ok(None) => (),
+                        Err(err) => self.on_error(err),
+                    }
+                    if let Err(err) = self.rewind.push(&self.control_deck) {
                         self.rewind.set_enabled(false);
                         self.on_error(err);
                     }
-                    if self.auto_save && self.last_auto_save.elapsed() > self.auto_save_interval {
+                    if self.auto_save
+                        && !self.attract_mode
+                        && self.last_auto_save.elapsed() > self.auto_save_interval
+                    {
                         self.last_auto_save = Instant::now();
                         self.save_state(self.save_slot, true);
                     }
                 }
+                Err(control_deck::Error::Breakpoint(breakpoints)) => {
+                    self.on_breakpoint(breakpoints);
+                }
                 Err(err) => {
                     self.set_run_state(RunState::Paused);
                     self.on_error(err);
@@ -966,7 +2218,13 @@ impl State {
             }
         }
 
-        self.clock_time_accumulator -= self.target_frame_duration.as_secs_f32();
+        if self.uncapped() {
+            self.clock_time_accumulator = 0.0;
+        } else if self.rewinding {
+            self.clock_time_accumulator -= self.target_frame_duration.as_secs_f32();
+        } else {
+            self.clock_time_accumulator -= self.frame_duration().as_secs_f32();
+        }
         // Request to draw this frame
         self.tx.event(RendererEvent::RequestRedraw {
             viewport_id: ViewportId::ROOT,
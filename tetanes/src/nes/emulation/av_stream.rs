@@ -0,0 +1,167 @@
+//! Raw video and audio streaming over TCP for external tooling, enabled with the `--stream`
+//! command line flag.
+//!
+//! Each client first receives a header:
+//!
+//! | Field         | Type      | Value                               |
+//! |---------------|-----------|-------------------------------------|
+//! | `magic`       | `[u8; 4]` | `TNAV`                              |
+//! | `version`     | `u8`      | [`AvStream::VERSION`]               |
+//! | `width`       | `u16`     | Frame width in pixels               |
+//! | `height`      | `u16`     | Frame height in pixels              |
+//! | `sample_rate` | `u32`     | Audio sample rate in Hz             |
+//!
+//! Followed by a packet for each emulated frame's video and audio:
+//!
+//! | Field          | Type  | Value                                           |
+//! |----------------|-------|-------------------------------------------------|
+//! | `kind`         | `u8`  | `0` for RGBA video, `1` for mono `f32` PCM audio |
+//! | `frame`        | `u32` | Frame sequence number since the stream started  |
+//! | `timestamp_us` | `u64` | Microseconds since the stream started           |
+//! | `len`          | `u32` | Payload length in bytes                         |
+//! | `payload`      | bytes | Frame pixels or audio samples                   |
+//!
+//! All integers and samples are little-endian. Packets for clients that can't keep up are
+//! dropped rather than slowing down emulation.
+
+use anyhow::Context;
+use crossbeam::channel::{self, Sender, TrySendError};
+use parking_lot::Mutex;
+use std::{
+    io::{BufWriter, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    sync::Arc,
+};
+use tetanes_core::{ppu::Ppu, time::Instant};
+use tracing::{debug, error, info};
+
+type Packet = Arc<[u8]>;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[repr(u8)]
+enum PacketKind {
+    Video = 0,
+    Audio = 1,
+}
+
+/// Streams raw frames and audio samples to connected TCP clients.
+#[derive(Debug)]
+#[must_use]
+pub struct AvStream {
+    start: Instant,
+    frame: u32,
+    clients: Arc<Mutex<Vec<Sender<Packet>>>>,
+}
+
+impl AvStream {
+    pub const VERSION: u8 = 1;
+    const MAGIC: &'static [u8; 4] = b"TNAV";
+    /// Number of packets to queue per client before dropping packets.
+    const CLIENT_QUEUE_SIZE: usize = 16;
+
+    /// Start listening for clients on `addr`.
+    pub fn bind(addr: SocketAddr, sample_rate: f32) -> anyhow::Result<Self> {
+        let listener =
+            TcpListener::bind(addr).with_context(|| format!("failed to bind stream to {addr}"))?;
+        let clients = Arc::new(Mutex::new(Vec::new()));
+        std::thread::Builder::new()
+            .name("av_stream".into())
+            .spawn({
+                let clients = Arc::clone(&clients);
+                move || {
+                    for stream in listener.incoming() {
+                        match stream {
+                            Ok(stream) => match Self::connect(stream, sample_rate) {
+                                Ok(client) => clients.lock().push(client),
+                                Err(err) => error!("failed to connect stream client: {err:?}"),
+                            },
+                            Err(err) => error!("failed to accept stream client: {err:?}"),
+                        }
+                    }
+                }
+            })
+            .context("failed to spawn stream thread")?;
+        info!("streaming video and audio on {addr}");
+
+        Ok(Self {
+            start: Instant::now(),
+            frame: 0,
+            clients,
+        })
+    }
+
+    /// Send the stream header to a new client and spawn a thread to write its packets.
+    fn connect(stream: TcpStream, sample_rate: f32) -> anyhow::Result<Sender<Packet>> {
+        let peer = stream.peer_addr()?;
+        stream.set_nodelay(true)?;
+        let mut writer = BufWriter::new(stream);
+        writer.write_all(Self::MAGIC)?;
+        writer.write_all(&[Self::VERSION])?;
+        writer.write_all(&(Ppu::WIDTH as u16).to_le_bytes())?;
+        writer.write_all(&(Ppu::HEIGHT as u16).to_le_bytes())?;
+        writer.write_all(&(sample_rate as u32).to_le_bytes())?;
+        writer.flush()?;
+
+        let (tx, rx) = channel::bounded::<Packet>(Self::CLIENT_QUEUE_SIZE);
+        std::thread::Builder::new()
+            .name(format!("av_stream_{peer}"))
+            .spawn(move || {
+                let res = rx
+                    .iter()
+                    .try_for_each(|packet| writer.write_all(&packet).and_then(|_| writer.flush()));
+                if let Err(err) = res {
+                    debug!("stream client {peer} disconnected: {err:?}");
+                }
+            })
+            .context("failed to spawn stream client thread")?;
+        info!("stream client {peer} connected");
+
+        Ok(tx)
+    }
+
+    /// Send a frame's video and audio to all connected clients. Audio samples interleaved by
+    /// `audio_channels` are mixed down to mono.
+    pub fn push(&mut self, frame_buffer: &[u8], audio_samples: &[f32], audio_channels: usize) {
+        let frame = self.frame;
+        self.frame = self.frame.wrapping_add(1);
+
+        let mut clients = self.clients.lock();
+        if clients.is_empty() {
+            return;
+        }
+
+        let timestamp_us = self.start.elapsed().as_micros() as u64;
+        let video = Self::packet(PacketKind::Video, frame, timestamp_us, frame_buffer);
+        let audio = Self::packet(
+            PacketKind::Audio,
+            frame,
+            timestamp_us,
+            &audio_samples
+                .chunks_exact(audio_channels.max(1))
+                .flat_map(|frame| (frame.iter().sum::<f32>() / frame.len() as f32).to_le_bytes())
+                .collect::<Vec<_>>(),
+        );
+        clients.retain(|client| {
+            [&video, &audio]
+                .into_iter()
+                .all(|packet| match client.try_send(Arc::clone(packet)) {
+                    Ok(()) => true,
+                    Err(TrySendError::Full(_)) => {
+                        debug!("dropped stream packet for slow client");
+                        true
+                    }
+                    Err(TrySendError::Disconnected(_)) => false,
+                })
+        });
+    }
+
+    fn packet(kind: PacketKind, frame: u32, timestamp_us: u64, payload: &[u8]) -> Packet {
+        let mut packet = Vec::with_capacity(17 + payload.len());
+        packet.push(kind as u8);
+        packet.extend_from_slice(&frame.to_le_bytes());
+        packet.extend_from_slice(&timestamp_us.to_le_bytes());
+        packet.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+        packet.extend_from_slice(payload);
+        packet.into()
+    }
+}
@@ -0,0 +1,70 @@
+use crate::nes::{
+    emulation::replay::{self, Replay},
+    renderer::FrameRecycle,
+};
+use std::path::Path;
+use tetanes_core::{
+    control_deck::{self, ControlDeck, HeadlessMode},
+    video::Frame,
+};
+use thingbuf::mpsc::{blocking::Sender as BufSender, errors::TrySendError};
+use tracing::debug;
+
+/// A replay played back on a separate [`ControlDeck`] in lockstep with the main one. Its inputs
+/// are never applied to the live game, only its frames are sent to be drawn over the live game
+/// for comparing runs.
+#[derive(Debug)]
+#[must_use]
+pub struct Ghost {
+    control_deck: ControlDeck,
+    replay: Replay,
+}
+
+impl Ghost {
+    /// Load a ghost replay from `path`, restoring both `control_deck` and the ghost to the state
+    /// the replay was recorded from so they start together.
+    pub fn load_path(
+        control_deck: &mut ControlDeck,
+        path: impl AsRef<Path>,
+    ) -> anyhow::Result<Self> {
+        let mut replay = Replay::new();
+        let start = replay.load_path(path)?;
+        control_deck.load_cpu(start);
+        let mut ghost_deck = control_deck.clone();
+        // Audio is only played for the live game and debuggers only apply to it
+        ghost_deck.set_headless_mode(HeadlessMode::NO_AUDIO);
+        ghost_deck.cpu_mut().bus.ppu.debugger = None;
+        Ok(Self {
+            control_deck: ghost_deck,
+            replay,
+        })
+    }
+
+    /// Clock a frame of the ghost replay, sending the resulting frame to the renderer unless
+    /// `skip_frame` is set.
+    pub fn clock_frame(
+        &mut self,
+        frame_tx: &BufSender<Frame, FrameRecycle>,
+        skip_frame: bool,
+    ) -> control_deck::Result<()> {
+        while let Some(event) = self.replay.next(self.control_deck.frame_number()) {
+            replay::apply_event(&mut self.control_deck, &event);
+        }
+        self.control_deck.clock_frame()?;
+        self.control_deck.clear_audio_samples();
+        if skip_frame {
+            return Ok(());
+        }
+
+        match frame_tx.try_send_ref() {
+            Ok(mut frame) => {
+                frame.clear();
+                frame.extend_from_slice(self.control_deck.frame_buffer());
+            }
+            Err(TrySendError::Full(_)) => debug!("dropped ghost frame"),
+            Err(_) => (),
+        }
+
+        Ok(())
+    }
+}
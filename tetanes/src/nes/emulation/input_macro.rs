@@ -0,0 +1,211 @@
+use crate::nes::event::EmulationEvent;
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use std::{collections::VecDeque, path::Path};
+use tetanes_core::{
+    fs,
+    input::{JoypadBtn, Player},
+};
+use winit::event::ElementState;
+
+/// A single button press or release in an [`InputMacro`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[must_use]
+pub struct MacroStep {
+    /// Frame offset from when the macro started.
+    pub frame: u32,
+    pub player: Player,
+    pub button: JoypadBtn,
+    pub pressed: bool,
+}
+
+/// A short recorded joypad input sequence that can be played back with a single key.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[must_use]
+pub struct InputMacro {
+    pub steps: Vec<MacroStep>,
+}
+
+impl InputMacro {
+    /// Number of frames the macro takes to play back.
+    pub fn frame_count(&self) -> u32 {
+        self.steps.last().map_or(0, |step| step.frame + 1)
+    }
+}
+
+/// Macros recorded for a single ROM.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[must_use]
+#[serde(default)] // Ensures new fields don't break existing macros
+pub struct Macros {
+    pub slots: [Option<InputMacro>; Macros::SLOTS],
+}
+
+impl Macros {
+    pub const SLOTS: usize = 4;
+
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        if !fs::exists(path) {
+            return Ok(Self::default());
+        }
+        let data = fs::load_raw(path).context("failed to load macros")?;
+        serde_json::from_slice(&data).with_context(|| format!("failed to parse {path:?}"))
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let data = serde_json::to_vec_pretty(&self).context("failed to serialize macros")?;
+        fs::save_raw(path, &data).context("failed to save macros")?;
+        Ok(())
+    }
+
+    /// Returns the macro in `slot`, numbered starting at 1.
+    pub fn get(&self, slot: u8) -> Option<&InputMacro> {
+        self.slots
+            .get(usize::from(slot).checked_sub(1)?)
+            .and_then(Option::as_ref)
+    }
+
+    fn set(&mut self, slot: u8, input_macro: InputMacro) {
+        if let Some(entry) = usize::from(slot)
+            .checked_sub(1)
+            .and_then(|index| self.slots.get_mut(index))
+        {
+            *entry = Some(input_macro);
+        }
+    }
+}
+
+#[derive(Debug)]
+#[must_use]
+struct Recording {
+    slot: u8,
+    start: u32,
+    steps: Vec<MacroStep>,
+}
+
+#[derive(Debug)]
+#[must_use]
+struct Playback {
+    start: u32,
+    steps: VecDeque<MacroStep>,
+}
+
+/// Records and plays back the [`Macros`] for the loaded ROM.
+#[derive(Default, Debug)]
+#[must_use]
+pub struct MacroPlayer {
+    pub macros: Macros,
+    recording: Option<Recording>,
+    playback: Option<Playback>,
+}
+
+impl MacroPlayer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stop any recording or playback and replace the loaded macros.
+    pub fn set_macros(&mut self, macros: Macros) {
+        self.macros = macros;
+        self.recording = None;
+        self.playback = None;
+    }
+
+    /// Slot currently being recorded, if any.
+    pub fn recording(&self) -> Option<u8> {
+        self.recording.as_ref().map(|recording| recording.slot)
+    }
+
+    pub fn start_recording(&mut self, slot: u8, frame: u32) {
+        self.playback = None;
+        self.recording = Some(Recording {
+            slot,
+            start: frame,
+            steps: Vec::new(),
+        });
+    }
+
+    /// Stop recording, releasing any buttons still held, and store the macro in its slot. Returns
+    /// the recorded slot, or `None` if nothing was recorded.
+    pub fn stop_recording(&mut self, frame: u32) -> Option<u8> {
+        let Recording {
+            slot,
+            start,
+            mut steps,
+        } = self.recording.take()?;
+        if steps.is_empty() {
+            return None;
+        }
+
+        let mut held = Vec::new();
+        for step in &steps {
+            if step.pressed {
+                held.push((step.player, step.button));
+            } else {
+                held.retain(|&held| held != (step.player, step.button));
+            }
+        }
+        let frame = frame.saturating_sub(start);
+        steps.extend(held.into_iter().map(|(player, button)| MacroStep {
+            frame,
+            player,
+            button,
+            pressed: false,
+        }));
+
+        self.macros.set(slot, InputMacro { steps });
+        Some(slot)
+    }
+
+    /// Record a joypad button change, if recording.
+    pub fn push(&mut self, frame: u32, player: Player, button: JoypadBtn, pressed: bool) {
+        if let Some(recording) = &mut self.recording {
+            recording.steps.push(MacroStep {
+                frame: frame.saturating_sub(recording.start),
+                player,
+                button,
+                pressed,
+            });
+        }
+    }
+
+    /// Start playing back the macro in `slot`. Returns `false` if the slot is empty.
+    pub fn play(&mut self, slot: u8, frame: u32) -> bool {
+        match self.macros.get(slot) {
+            Some(input_macro) if !input_macro.steps.is_empty() => {
+                self.playback = Some(Playback {
+                    start: frame,
+                    steps: input_macro.steps.iter().copied().collect(),
+                });
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns the next joypad event due to be played back by `frame`, if any.
+    pub fn next(&mut self, frame: u32) -> Option<EmulationEvent> {
+        let playback = self.playback.as_mut()?;
+        let due = playback
+            .steps
+            .front()
+            .is_some_and(|step| playback.start + step.frame <= frame);
+        let step = if due {
+            playback.steps.pop_front()
+        } else {
+            None
+        };
+        if playback.steps.is_empty() {
+            self.playback = None;
+        }
+        step.map(|step| {
+            let state = if step.pressed {
+                ElementState::Pressed
+            } else {
+                ElementState::Released
+            };
+            EmulationEvent::Joypad((step.player, step.button, state))
+        })
+    }
+}
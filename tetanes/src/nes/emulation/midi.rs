@@ -0,0 +1,84 @@
+//! Experimental MIDI output of APU channel notes, enabled with the `--midi` command line flag
+//! and the `midi` feature.
+
+use anyhow::{anyhow, Context};
+use midir::{MidiOutput, MidiOutputConnection};
+use tetanes_core::apu::{
+    midi::{MidiMessage, MidiTracker},
+    Apu,
+};
+use tracing::{error, info};
+
+/// Sends MIDI messages generated from [`Apu`] channel state to a MIDI output port.
+#[must_use]
+pub struct MidiOut {
+    connection: MidiOutputConnection,
+    tracker: MidiTracker,
+    messages: Vec<MidiMessage>,
+}
+
+impl std::fmt::Debug for MidiOut {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MidiOut")
+            .field("tracker", &self.tracker)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for MidiOut {
+    fn drop(&mut self) {
+        self.silence();
+    }
+}
+
+impl MidiOut {
+    /// Connect to the first MIDI output port with a name containing `port`, ignoring case.
+    pub fn connect(port: &str) -> anyhow::Result<Self> {
+        let output = MidiOutput::new("TetaNES").context("failed to initialize MIDI output")?;
+        let ports = output
+            .ports()
+            .into_iter()
+            .filter_map(|port| output.port_name(&port).ok().map(|name| (port, name)))
+            .collect::<Vec<_>>();
+        let (port, name) = ports
+            .iter()
+            .find(|(_, name)| name.to_lowercase().contains(&port.to_lowercase()))
+            .with_context(|| {
+                let names = ports
+                    .iter()
+                    .map(|(_, name)| name.as_str())
+                    .collect::<Vec<_>>();
+                format!("no MIDI output port matching {port:?}, available ports: {names:?}")
+            })?;
+        let connection = output
+            .connect(port, "tetanes-apu")
+            .map_err(|err| anyhow!("failed to connect to MIDI port {name:?}: {err}"))?;
+        info!("sending APU notes to MIDI port {name:?}");
+
+        Ok(Self {
+            connection,
+            tracker: MidiTracker::new(),
+            messages: Vec::new(),
+        })
+    }
+
+    /// Send messages for any changes to [`Apu`] channels since the last update.
+    pub fn update(&mut self, apu: &Apu) {
+        self.tracker.update(apu, &mut self.messages);
+        self.send();
+    }
+
+    /// Turn off all notes, e.g. when emulation is paused.
+    pub fn silence(&mut self) {
+        self.tracker.silence(&mut self.messages);
+        self.send();
+    }
+
+    fn send(&mut self) {
+        for message in self.messages.drain(..) {
+            if let Err(err) = self.connection.send(&message) {
+                error!("failed to send MIDI message: {err}");
+            }
+        }
+    }
+}
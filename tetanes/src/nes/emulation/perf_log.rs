@@ -0,0 +1,134 @@
+use crate::nes::emulation::FrameStats;
+use anyhow::Context;
+use serde::Serialize;
+use std::{
+    collections::VecDeque,
+    fs::{File, OpenOptions},
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+/// Timing measurements for a single emulated frame.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize)]
+#[must_use]
+pub struct FrameSample {
+    /// Frame number of the loaded ROM.
+    pub frame: u32,
+    /// Time since the previous frame, in milliseconds.
+    pub frame_time_ms: f32,
+    /// Time spent emulating the frame, in milliseconds.
+    pub emulation_time_ms: f32,
+    /// How full the audio sample queue was, from `0.0` to `1.0`.
+    pub audio_buffer_fill: f32,
+    /// Bytes used by rewind snapshots.
+    pub rewind_memory: usize,
+}
+
+impl FrameSample {
+    const CSV_HEADER: &'static str =
+        "frame,frame_time_ms,emulation_time_ms,audio_buffer_fill,rewind_memory";
+
+    fn write_csv(&self, writer: &mut impl Write) -> std::io::Result<()> {
+        writeln!(
+            writer,
+            "{},{:.3},{:.3},{:.3},{}",
+            self.frame,
+            self.frame_time_ms,
+            self.emulation_time_ms,
+            self.audio_buffer_fill,
+            self.rewind_memory
+        )
+    }
+}
+
+/// Frame timing samples appended to a CSV file for the whole session, enabled with the
+/// `--perf-log` command line flag.
+#[derive(Debug)]
+#[must_use]
+pub struct PerfLog {
+    path: PathBuf,
+    writer: BufWriter<File>,
+}
+
+impl PerfLog {
+    /// How many samples to buffer before flushing to disk.
+    const FLUSH_INTERVAL: u32 = 60;
+
+    pub fn open(path: impl Into<PathBuf>) -> anyhow::Result<Self> {
+        let path = path.into();
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("failed to open perf log {path:?}"))?;
+        let is_empty = file.metadata().map_or(true, |metadata| metadata.len() == 0);
+        let mut writer = BufWriter::new(file);
+        if is_empty {
+            writeln!(writer, "{}", FrameSample::CSV_HEADER)
+                .with_context(|| format!("failed to write perf log {path:?}"))?;
+        }
+        Ok(Self { path, writer })
+    }
+
+    pub fn push(&mut self, sample: &FrameSample) -> anyhow::Result<()> {
+        sample
+            .write_csv(&mut self.writer)
+            .and_then(|_| {
+                if sample.frame % Self::FLUSH_INTERVAL == 0 {
+                    self.writer.flush()
+                } else {
+                    Ok(())
+                }
+            })
+            .with_context(|| format!("failed to write perf log {:?}", self.path))
+    }
+}
+
+#[derive(Serialize)]
+struct Report<'a> {
+    fps: f32,
+    fps_min: f32,
+    frame_time_ms: f32,
+    frame_time_max_ms: f32,
+    frame_count: usize,
+    late_frames: usize,
+    dropped_frames: usize,
+    samples: &'a VecDeque<FrameSample>,
+}
+
+/// Export the latest `stats` and recent frame `samples` to `path`, as JSON if it has a `.json`
+/// extension and CSV otherwise.
+pub fn export(
+    path: impl AsRef<Path>,
+    stats: &FrameStats,
+    samples: &VecDeque<FrameSample>,
+) -> anyhow::Result<()> {
+    let path = path.as_ref();
+    let file = File::create(path).with_context(|| format!("failed to create {path:?}"))?;
+    let mut writer = BufWriter::new(file);
+    let is_json = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+    if is_json {
+        let report = Report {
+            fps: stats.fps,
+            fps_min: stats.fps_min,
+            frame_time_ms: stats.frame_time,
+            frame_time_max_ms: stats.frame_time_max,
+            frame_count: stats.frame_count,
+            late_frames: stats.late_frames,
+            dropped_frames: stats.dropped_frames,
+            samples,
+        };
+        serde_json::to_writer_pretty(&mut writer, &report)
+            .context("failed to serialize frame stats")?;
+    } else {
+        writeln!(writer, "{}", FrameSample::CSV_HEADER)?;
+        for sample in samples {
+            sample.write_csv(&mut writer)?;
+        }
+    }
+    writer
+        .flush()
+        .with_context(|| format!("failed to write {path:?}"))
+}
@@ -0,0 +1,124 @@
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, path::Path};
+use tetanes_core::{
+    fs,
+    time::{Duration, Instant},
+};
+
+/// Play statistics for a single ROM.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[must_use]
+#[serde(default)] // Ensures new fields don't break existing statistics
+pub struct GamePlaytime {
+    /// Total time spent playing, in seconds, not counting time paused.
+    pub seconds: u64,
+    /// Number of times the ROM was loaded.
+    pub sessions: u32,
+    /// Unix timestamp in seconds of when the ROM was last loaded.
+    pub last_played: i64,
+}
+
+/// Play statistics for every ROM played, keyed by ROM name.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[must_use]
+#[serde(default)]
+pub struct Playtime {
+    pub games: BTreeMap<String, GamePlaytime>,
+}
+
+impl Playtime {
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        if !fs::exists(path) {
+            return Ok(Self::default());
+        }
+        let data = fs::load_raw(path).context("failed to load playtime statistics")?;
+        serde_json::from_slice(&data).with_context(|| format!("failed to parse {path:?}"))
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let data =
+            serde_json::to_vec_pretty(&self).context("failed to serialize playtime statistics")?;
+        fs::save_raw(path, &data).context("failed to save playtime statistics")?;
+        Ok(())
+    }
+
+    /// Record the start of a new session of `name`.
+    pub fn start_session(&mut self, name: &str) {
+        let game = self.games.entry(name.to_string()).or_default();
+        game.sessions += 1;
+        game.last_played = chrono::Local::now().timestamp();
+    }
+
+    /// Add time spent playing `name`.
+    pub fn add_time(&mut self, name: &str, played: Duration) {
+        self.games.entry(name.to_string()).or_default().seconds += played.as_secs();
+    }
+
+    /// Total time spent playing all ROMs, in seconds.
+    #[must_use]
+    pub fn total_seconds(&self) -> u64 {
+        self.games.values().map(|game| game.seconds).sum()
+    }
+
+    /// Total number of sessions across all ROMs.
+    #[must_use]
+    pub fn total_sessions(&self) -> u32 {
+        self.games.values().map(|game| game.sessions).sum()
+    }
+
+    /// ROMs sorted by most time played, then by most recently played.
+    pub fn most_played(&self) -> Vec<(&str, &GamePlaytime)> {
+        let mut games = self
+            .games
+            .iter()
+            .map(|(name, game)| (name.as_str(), game))
+            .collect::<Vec<_>>();
+        games.sort_by(|(_, a), (_, b)| {
+            b.seconds
+                .cmp(&a.seconds)
+                .then(b.last_played.cmp(&a.last_played))
+        });
+        games
+    }
+}
+
+/// The current play session of the loaded ROM, which only counts time while running.
+#[derive(Debug)]
+#[must_use]
+pub struct PlaySession {
+    pub name: String,
+    resumed: Option<Instant>,
+    played: Duration,
+}
+
+impl PlaySession {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            resumed: Some(Instant::now()),
+            played: Duration::ZERO,
+        }
+    }
+
+    /// Stop or start counting time played.
+    pub fn pause(&mut self, paused: bool) {
+        match (paused, self.resumed) {
+            (true, Some(resumed)) => {
+                self.played += resumed.elapsed();
+                self.resumed = None;
+            }
+            (false, None) => self.resumed = Some(Instant::now()),
+            _ => (),
+        }
+    }
+
+    /// Total time played in this session.
+    pub fn played(&self) -> Duration {
+        self.played
+            + self
+                .resumed
+                .map_or(Duration::ZERO, |resumed| resumed.elapsed())
+    }
+}
@@ -0,0 +1,133 @@
+use crate::nes::{
+    event::{NesEventProxy, RendererEvent},
+    renderer::FrameRecycle,
+};
+use egui::ViewportId;
+use tetanes_core::{
+    common::{Reset, ResetKind},
+    control_deck::{self, ControlDeck, HeadlessMode},
+    input::{JoypadBtn, Player},
+    time::Instant,
+    video::Frame,
+};
+use thingbuf::mpsc::{blocking::Sender as BufSender, errors::TrySendError};
+use tracing::debug;
+
+/// How controller input is shared between the main window and a [`Race`] window.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+#[must_use]
+pub enum RaceInputs {
+    /// Player Two's controls drive Player One in the second window.
+    #[default]
+    Separate,
+    /// Both windows receive the same input.
+    Mirrored,
+}
+
+impl RaceInputs {
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Separate => "Separate Inputs",
+            Self::Mirrored => "Mirrored Inputs",
+        }
+    }
+}
+
+impl AsRef<str> for RaceInputs {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+/// A second, independent [`ControlDeck`] running the same ROM in lockstep with the main one and
+/// displayed in its own viewport, allowing side-by-side race practice.
+#[derive(Debug)]
+#[must_use]
+pub struct Race {
+    viewport_id: ViewportId,
+    inputs: RaceInputs,
+    control_deck: ControlDeck,
+}
+
+impl Race {
+    /// Start a race by power cycling `control_deck` and making a copy of it, so both start from
+    /// the same state.
+    pub fn start(
+        viewport_id: ViewportId,
+        inputs: RaceInputs,
+        control_deck: &mut ControlDeck,
+    ) -> Self {
+        control_deck.reset(ResetKind::Hard);
+        let mut race_deck = control_deck.clone();
+        // Audio is only played for the main window and debuggers only apply to it
+        race_deck.set_headless_mode(HeadlessMode::NO_AUDIO);
+        race_deck.cpu_mut().bus.ppu.debugger = None;
+        Self {
+            viewport_id,
+            inputs,
+            control_deck: race_deck,
+        }
+    }
+
+    pub const fn viewport_id(&self) -> ViewportId {
+        self.viewport_id
+    }
+
+    /// Handle joypad input, returning whether the main window should handle it as well.
+    pub fn on_joypad(&mut self, player: Player, button: JoypadBtn, pressed: bool) -> bool {
+        match self.inputs {
+            RaceInputs::Mirrored => {
+                self.control_deck
+                    .joypad_mut(player)
+                    .set_button(button, pressed);
+                true
+            }
+            RaceInputs::Separate => {
+                if player == Player::Two {
+                    self.control_deck
+                        .joypad_mut(Player::One)
+                        .set_button(button, pressed);
+                    false
+                } else {
+                    true
+                }
+            }
+        }
+    }
+
+    pub fn reset(&mut self, kind: ResetKind) {
+        self.control_deck.reset(kind);
+    }
+
+    /// Clock a frame at the same `speed` as the main window, sending the resulting frame to the
+    /// renderer unless `skip_frame` is set.
+    pub fn clock_frame(
+        &mut self,
+        tx: &NesEventProxy,
+        frame_tx: &BufSender<Frame, FrameRecycle>,
+        speed: f32,
+        skip_frame: bool,
+    ) -> control_deck::Result<()> {
+        self.control_deck.set_frame_speed(speed);
+        self.control_deck.clock_frame()?;
+        self.control_deck.clear_audio_samples();
+        if skip_frame {
+            return Ok(());
+        }
+
+        match frame_tx.try_send_ref() {
+            Ok(mut frame) => {
+                frame.clear();
+                frame.extend_from_slice(self.control_deck.frame_buffer());
+            }
+            Err(TrySendError::Full(_)) => debug!("dropped race frame"),
+            Err(_) => (),
+        }
+        tx.event(RendererEvent::RequestRedraw {
+            viewport_id: self.viewport_id,
+            when: Instant::now(),
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,86 @@
+use serde::{Deserialize, Serialize};
+use tetanes_core::{control_deck::ControlDeck, mem::Mem};
+
+/// How many bytes a [`RamWatch`] reads.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[must_use]
+pub enum WatchSize {
+    #[default]
+    Byte,
+    /// Two bytes, little-endian.
+    Word,
+}
+
+impl WatchSize {
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Byte => "Byte",
+            Self::Word => "Word",
+        }
+    }
+}
+
+/// How the value of a [`RamWatch`] is displayed.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[must_use]
+pub enum WatchFormat {
+    #[default]
+    Hex,
+    Dec,
+    /// Binary-coded decimal, where each nibble is a single decimal digit.
+    Bcd,
+}
+
+impl WatchFormat {
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Hex => "Hex",
+            Self::Dec => "Dec",
+            Self::Bcd => "BCD",
+        }
+    }
+}
+
+/// A labeled CPU address whose value is read after every frame, optionally pinned over the NES
+/// frame.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[must_use]
+#[serde(default)] // Ensures new fields don't break existing configurations
+pub struct RamWatch {
+    pub label: String,
+    pub addr: u16,
+    pub size: WatchSize,
+    pub format: WatchFormat,
+    pub pinned: bool,
+}
+
+impl RamWatch {
+    /// Read the current value without side effects.
+    pub fn read(&self, deck: &ControlDeck) -> u16 {
+        let cpu = deck.cpu();
+        match self.size {
+            WatchSize::Byte => u16::from(cpu.peek(self.addr)),
+            WatchSize::Word => {
+                u16::from_le_bytes([cpu.peek(self.addr), cpu.peek(self.addr.wrapping_add(1))])
+            }
+        }
+    }
+
+    /// Format `value` as read by [`RamWatch::read`].
+    pub fn format_value(&self, value: u16) -> String {
+        let digits = match self.size {
+            WatchSize::Byte => 2,
+            WatchSize::Word => 4,
+        };
+        match self.format {
+            WatchFormat::Hex => format!("${value:0digits$X}"),
+            WatchFormat::Dec => value.to_string(),
+            WatchFormat::Bcd => (0..digits).rev().fold(String::new(), |mut s, digit| {
+                // Nibbles outside of 0-9 aren't valid BCD
+                let nibble = (value >> (digit * 4)) & 0x0F;
+                s.push(char::from_digit(nibble.into(), 10).unwrap_or('?'));
+                s
+            }),
+        }
+    }
+}
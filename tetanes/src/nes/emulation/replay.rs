@@ -1,12 +1,15 @@
-use crate::nes::{config::Config, event::EmulationEvent};
+use crate::nes::event::EmulationEvent;
+use anyhow::{anyhow, bail, Context};
 use chrono::Local;
 use serde::{Deserialize, Serialize};
 use std::{
     cmp::Ordering,
+    fmt::{self, Write},
     io::Read,
     path::{Path, PathBuf},
 };
 use tetanes_core::{
+    control_deck::ControlDeck,
     cpu::Cpu,
     fs,
     input::{JoypadBtn, Player},
@@ -15,13 +18,55 @@ use tracing::warn;
 use winit::event::ElementState;
 
 #[derive(Debug, Serialize, Deserialize)]
-pub struct State((Cpu, Vec<ReplayFrame>));
+pub struct State {
+    start: Cpu,
+    events: Vec<ReplayFrame>,
+    hashes: Vec<FrameHash>,
+}
+
+/// Replay format prior to recording state hashes.
+#[derive(Debug, Serialize, Deserialize)]
+struct LegacyState((Cpu, Vec<ReplayFrame>));
+
+impl From<LegacyState> for State {
+    fn from(LegacyState((start, events)): LegacyState) -> Self {
+        Self {
+            start,
+            events,
+            hashes: Vec::new(),
+        }
+    }
+}
+
+impl State {
+    fn load_path(path: &Path) -> anyhow::Result<Self> {
+        // Newer replays fail to load as the legacy format, so try the current format first
+        match fs::load(path) {
+            Ok(state) => Ok(state),
+            Err(err) => fs::load::<LegacyState>(path)
+                .map(Into::into)
+                .map_err(|_| err.into()),
+        }
+    }
+
+    fn load_bytes(bytes: &[u8]) -> anyhow::Result<Self> {
+        match fs::load_bytes(bytes) {
+            Ok(state) => Ok(state),
+            Err(err) => fs::load_bytes::<LegacyState>(bytes)
+                .map(Into::into)
+                .map_err(|_| err.into()),
+        }
+    }
+}
 
 #[derive(Debug, Serialize, Deserialize)]
 pub enum ReplayEvent {
     Joypad((Player, JoypadBtn, ElementState)),
     ZapperAim((u32, u32)),
     ZapperTrigger,
+    ScanBarcode(String),
+    VausMove(i32),
+    VausButton(bool),
 }
 
 impl From<ReplayEvent> for EmulationEvent {
@@ -30,6 +75,9 @@ impl From<ReplayEvent> for EmulationEvent {
             ReplayEvent::Joypad(state) => Self::Joypad(state),
             ReplayEvent::ZapperAim(pos) => Self::ZapperAim(pos),
             ReplayEvent::ZapperTrigger => Self::ZapperTrigger,
+            ReplayEvent::ScanBarcode(barcode) => Self::ScanBarcode(barcode),
+            ReplayEvent::VausMove(delta) => Self::VausMove(delta),
+            ReplayEvent::VausButton(pressed) => Self::VausButton(pressed),
         }
     }
 }
@@ -42,11 +90,34 @@ impl TryFrom<EmulationEvent> for ReplayEvent {
             EmulationEvent::Joypad(state) => Self::Joypad(state),
             EmulationEvent::ZapperAim(pos) => Self::ZapperAim(pos),
             EmulationEvent::ZapperTrigger => Self::ZapperTrigger,
+            EmulationEvent::ScanBarcode(barcode) => Self::ScanBarcode(barcode),
+            EmulationEvent::VausMove(delta) => Self::VausMove(delta),
+            EmulationEvent::VausButton(pressed) => Self::VausButton(pressed),
             _ => return Err(anyhow::anyhow!("invalid replay event: {event:?}")),
         })
     }
 }
 
+/// Applies a replayed input event directly to a [`ControlDeck`]. Other events are ignored.
+pub fn apply_event(deck: &mut ControlDeck, event: &EmulationEvent) {
+    match event {
+        EmulationEvent::Joypad((player, button, state)) => {
+            deck.joypad_mut(*player)
+                .set_button(*button, *state == ElementState::Pressed);
+        }
+        EmulationEvent::ZapperAim((x, y)) => deck.aim_zapper(*x, *y),
+        EmulationEvent::ZapperTrigger => deck.trigger_zapper(),
+        EmulationEvent::VausMove(delta) => deck.move_vaus(*delta),
+        EmulationEvent::VausButton(pressed) => deck.set_vaus_button(*pressed),
+        EmulationEvent::ScanBarcode(barcode) => {
+            if let Err(err) = deck.scan_barcode(barcode) {
+                warn!("failed to scan replayed barcode: {err:?}");
+            }
+        }
+        _ => (),
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[must_use]
 pub struct ReplayFrame {
@@ -54,25 +125,294 @@ pub struct ReplayFrame {
     pub event: ReplayEvent,
 }
 
+/// [`ControlDeck::state_hash`] recorded after a frame was emulated, used to detect desyncs when
+/// playing back a replay.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[must_use]
+pub struct FrameHash {
+    pub frame: u32,
+    pub hash: u64,
+}
+
+/// A mismatch between the emulation state during playback and the state recorded with a replay.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[must_use]
+pub struct Desync {
+    pub frame: u32,
+    pub expected: u64,
+    pub actual: u64,
+}
+
+/// File extension of input scripts, a hand-editable text format for the joypad inputs of a replay.
+pub const INPUT_SCRIPT_EXTENSION: &str = "txt";
+
+/// Explains the input script format at the top of exported scripts.
+const INPUT_SCRIPT_HEADER: &str = "\
+# TetaNES input script
+#
+# Each line lists a frame, or an inclusive range of frames, counted from where the replay
+# starts, followed by the buttons held on them, e.g. `120-135: Right A`. Buttons for players
+# two to four follow a `|`, e.g. `40: Start | A`, and `.` holds nothing. Frames that aren't
+# listed hold nothing. Lines must be in frame order and `#` starts a comment.
+#
+# Buttons: A B Select Start Up Down Left Right TurboA TurboB
+";
+
+/// Buttons in the order and with the names used by input scripts.
+const SCRIPT_BUTTONS: [(JoypadBtn, &str); 10] = [
+    (JoypadBtn::A, "A"),
+    (JoypadBtn::B, "B"),
+    (JoypadBtn::Select, "Select"),
+    (JoypadBtn::Start, "Start"),
+    (JoypadBtn::Up, "Up"),
+    (JoypadBtn::Down, "Down"),
+    (JoypadBtn::Left, "Left"),
+    (JoypadBtn::Right, "Right"),
+    (JoypadBtn::TurboA, "TurboA"),
+    (JoypadBtn::TurboB, "TurboB"),
+];
+
+/// Buttons held by each player, as bits indexed by [`SCRIPT_BUTTONS`].
+type HeldButtons = [u16; 4];
+
+fn button_bit(button: JoypadBtn) -> u16 {
+    SCRIPT_BUTTONS
+        .iter()
+        .position(|(btn, _)| *btn == button)
+        .map_or(0, |index| 1 << index)
+}
+
+/// Returns whether `path` is an input script rather than a replay recording.
+pub fn is_input_script(path: impl AsRef<Path>) -> bool {
+    path.as_ref()
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case(INPUT_SCRIPT_EXTENSION))
+}
+
+/// Exports the joypad inputs of a replay recording to an input script next to it, returning the
+/// path of the script. Other inputs, like the zapper, aren't exported.
+pub fn export_input_script(path: impl AsRef<Path>) -> anyhow::Result<PathBuf> {
+    let path = path.as_ref();
+    let state = State::load_path(path)?;
+    let script_path = path.with_extension(INPUT_SCRIPT_EXTENSION);
+    std::fs::write(&script_path, state.input_script())
+        .with_context(|| format!("failed to save input script {script_path:?}"))?;
+    Ok(script_path)
+}
+
+impl State {
+    fn input_script(&self) -> String {
+        let start = self.start.bus.ppu.frame_number();
+        // Buttons still held when the recording stopped are held until the last state hash, which
+        // is recorded at most a hash interval before the recording stopped
+        let last_frame = self
+            .hashes
+            .last()
+            .map(|hash| hash.frame.wrapping_sub(start).saturating_sub(1));
+
+        let mut script = INPUT_SCRIPT_HEADER.to_string();
+        let mut held = HeldButtons::default();
+        let mut held_since = 0;
+        let mut events = self
+            .events
+            .iter()
+            .filter_map(|event| match event.event {
+                ReplayEvent::Joypad((player, button, state)) => {
+                    Some((event.frame.wrapping_sub(start), player, button, state))
+                }
+                _ => None,
+            })
+            .peekable();
+        while let Some(&(frame, ..)) = events.peek() {
+            let previous = held;
+            while let Some((_, player, button, state)) = events.next_if(|event| event.0 == frame) {
+                let held = &mut held[player as usize];
+                match state {
+                    ElementState::Pressed => *held |= button_bit(button),
+                    ElementState::Released => *held &= !button_bit(button),
+                }
+            }
+            if held != previous {
+                write_script_line(&mut script, held_since, frame.saturating_sub(1), previous);
+                held_since = frame;
+            }
+        }
+        let last_frame = last_frame.unwrap_or(held_since).max(held_since);
+        write_script_line(&mut script, held_since, last_frame, held);
+        script
+    }
+}
+
+/// Appends a line holding `held` buttons from `first` to `last` frame, unless nothing is held.
+fn write_script_line(script: &mut String, first: u32, last: u32, held: HeldButtons) {
+    let players = held.iter().rposition(|buttons| *buttons != 0);
+    let Some(players) = players.map(|last_player| &held[..=last_player]) else {
+        return;
+    };
+    let frames = if first == last {
+        first.to_string()
+    } else {
+        format!("{first}-{last}")
+    };
+    let buttons = players
+        .iter()
+        .map(|held| {
+            let names = SCRIPT_BUTTONS
+                .iter()
+                .enumerate()
+                .filter(|(index, _)| held & (1 << index) != 0)
+                .map(|(_, (_, name))| *name)
+                .collect::<Vec<_>>();
+            if names.is_empty() {
+                ".".to_string()
+            } else {
+                names.join(" ")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" | ");
+    let _ = writeln!(script, "{frames}: {buttons}");
+}
+
+/// Parses the joypad inputs of an input script, played back starting from `start`.
+fn parse_input_script(text: &str, start: u32) -> anyhow::Result<Vec<ReplayFrame>> {
+    let mut events = Vec::new();
+    let mut held = HeldButtons::default();
+    let mut next_frame = 0;
+    for (number, line) in text.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+        let (first, last, buttons) =
+            parse_script_line(line).with_context(|| format!("line {}: {line:?}", number + 1))?;
+        if first < next_frame {
+            bail!(
+                "line {}: frame {first} overlaps or comes before an earlier line",
+                number + 1
+            );
+        }
+        if first > next_frame {
+            set_held(
+                &mut events,
+                start.wrapping_add(next_frame),
+                &mut held,
+                HeldButtons::default(),
+            );
+        }
+        set_held(&mut events, start.wrapping_add(first), &mut held, buttons);
+        next_frame = last.saturating_add(1);
+    }
+    set_held(
+        &mut events,
+        start.wrapping_add(next_frame),
+        &mut held,
+        HeldButtons::default(),
+    );
+    Ok(events)
+}
+
+fn parse_script_line(line: &str) -> anyhow::Result<(u32, u32, HeldButtons)> {
+    let (frames, players) = line
+        .split_once(':')
+        .ok_or_else(|| anyhow!("expected `frame: buttons`"))?;
+    let parse_frame = |frame: &str| {
+        frame
+            .trim()
+            .parse::<u32>()
+            .with_context(|| format!("invalid frame {frame:?}"))
+    };
+    let (first, last) = match frames.split_once('-') {
+        Some((first, last)) => (parse_frame(first)?, parse_frame(last)?),
+        None => {
+            let frame = parse_frame(frames)?;
+            (frame, frame)
+        }
+    };
+    if last < first {
+        bail!("frame range {first}-{last} ends before it starts");
+    }
+
+    let mut held = HeldButtons::default();
+    let players = players.split('|').collect::<Vec<_>>();
+    if players.len() > held.len() {
+        bail!("expected at most {} players", held.len());
+    }
+    for (held, buttons) in held.iter_mut().zip(players) {
+        for name in buttons.split_whitespace().filter(|name| *name != ".") {
+            let index = SCRIPT_BUTTONS
+                .iter()
+                .position(|(_, button)| button.eq_ignore_ascii_case(name))
+                .ok_or_else(|| anyhow!("unknown button {name:?}"))?;
+            *held |= 1 << index;
+        }
+    }
+    Ok((first, last, held))
+}
+
+/// Pushes the joypad events changing the buttons `held` to `next` on `frame`.
+fn set_held(events: &mut Vec<ReplayFrame>, frame: u32, held: &mut HeldButtons, next: HeldButtons) {
+    for (index, (held, next)) in held.iter_mut().zip(next).enumerate() {
+        let Ok(player) = Player::try_from(index) else {
+            continue;
+        };
+        for (bit, (button, _)) in SCRIPT_BUTTONS.iter().enumerate() {
+            let (was_held, is_held) = (*held & (1 << bit) != 0, next & (1 << bit) != 0);
+            if was_held != is_held {
+                let state = if is_held {
+                    ElementState::Pressed
+                } else {
+                    ElementState::Released
+                };
+                events.push(ReplayFrame {
+                    frame,
+                    event: ReplayEvent::Joypad((player, *button, state)),
+                });
+            }
+        }
+        *held = next;
+    }
+}
+
+impl fmt::Display for Desync {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "replay desync at frame {}: expected state hash {:#018x}, found {:#018x}",
+            self.frame, self.expected, self.actual
+        )
+    }
+}
+
 #[derive(Default, Debug)]
 #[must_use]
 pub struct Record {
     pub start: Option<Cpu>,
     pub events: Vec<ReplayFrame>,
+    pub hashes: Vec<FrameHash>,
+    /// Number of frames between recorded state hashes.
+    pub hash_interval: u32,
+    /// File to save the recording to instead of a generated name.
+    pub path: Option<PathBuf>,
 }
 
 impl Record {
-    pub fn new() -> Self {
-        Self::default()
+    pub fn new(hash_interval: u32) -> Self {
+        Self {
+            hash_interval,
+            ..Self::default()
+        }
     }
 
-    pub fn start(&mut self, cpu: Cpu) {
+    pub fn start(&mut self, cpu: Cpu, path: Option<PathBuf>) {
         self.start = Some(cpu);
         self.events.clear();
+        self.hashes.clear();
+        self.path = path;
     }
 
-    pub fn stop(&mut self, name: &str) -> anyhow::Result<Option<PathBuf>> {
-        self.save(name)
+    pub fn stop(&mut self, name: &str, dir: &Path) -> anyhow::Result<Option<PathBuf>> {
+        self.save(name, dir)
     }
 
     pub fn push(&mut self, frame: u32, event: EmulationEvent) {
@@ -83,8 +423,21 @@ impl Record {
         }
     }
 
-    /// Saves the replay recording out to a file.
-    pub fn save(&mut self, name: &str) -> anyhow::Result<Option<PathBuf>> {
+    /// Records the current state hash of the [`ControlDeck`], if recording and the current frame
+    /// falls on the hash interval.
+    pub fn push_hash(&mut self, deck: &ControlDeck) -> anyhow::Result<()> {
+        if self.start.is_some() && deck.frame_number() % self.hash_interval.max(1) == 0 {
+            self.hashes.push(FrameHash {
+                frame: deck.frame_number(),
+                hash: deck.state_hash()?,
+            });
+        }
+        Ok(())
+    }
+
+    /// Saves the replay recording out to a file in the given directory, or to the file provided
+    /// when recording started.
+    pub fn save(&mut self, name: &str, dir: &Path) -> anyhow::Result<Option<PathBuf>> {
         let Some(start) = self.start.take() else {
             return Ok(None);
         };
@@ -94,16 +447,21 @@ impl Record {
             return Ok(None);
         }
 
-        let replay_path = Config::default_data_dir()
-            .join(
+        let replay_path = self.path.take().unwrap_or_else(|| {
+            dir.join(
                 Local::now()
                     .format(&format!("tetanes_replay_{name}_%Y-%m-%d_%H.%M.%S"))
                     .to_string(),
             )
-            .with_extension("replay");
-        let events = std::mem::take(&mut self.events);
+            .with_extension("replay")
+        });
+        let state = State {
+            start,
+            events: std::mem::take(&mut self.events),
+            hashes: std::mem::take(&mut self.hashes),
+        };
 
-        fs::save(&replay_path, &State((start, events)))?;
+        fs::save(&replay_path, &state)?;
 
         Ok(Some(replay_path))
     }
@@ -113,6 +471,9 @@ impl Record {
 #[must_use]
 pub struct Replay {
     pub events: Vec<ReplayFrame>,
+    /// State hashes recorded with the replay, in reverse frame order. Empty for replays recorded
+    /// by older versions, or once a desync has been detected.
+    pub hashes: Vec<FrameHash>,
 }
 
 impl Replay {
@@ -122,21 +483,73 @@ impl Replay {
 
     /// Loads a replay recording file.
     pub fn load_path(&mut self, path: impl AsRef<Path>) -> anyhow::Result<Cpu> {
-        let path = path.as_ref();
-        let State((cpu, mut events)) = fs::load(path)?;
-        events.reverse(); // So we can pop off the end
-        self.events = events;
-        Ok(cpu)
+        let state = State::load_path(path.as_ref())?;
+        Ok(self.load_state(state))
     }
 
     /// Loads a replay from a reader.
     pub fn load(&mut self, mut replay: impl Read) -> anyhow::Result<Cpu> {
-        let mut events = Vec::new();
-        replay.read_to_end(&mut events)?;
-        let State((cpu, mut events)) = fs::load_bytes(&events)?;
+        let mut bytes = Vec::new();
+        replay.read_to_end(&mut bytes)?;
+        let state = State::load_bytes(&bytes)?;
+        Ok(self.load_state(state))
+    }
+
+    /// Loads an input script from a reader, played back from the current state of `deck`, which
+    /// is returned as the start of the replay.
+    pub fn load_input_script(
+        &mut self,
+        mut script: impl Read,
+        deck: &ControlDeck,
+    ) -> anyhow::Result<Cpu> {
+        let mut text = String::new();
+        script
+            .read_to_string(&mut text)
+            .context("failed to read input script")?;
+        let events = parse_input_script(&text, deck.frame_number())?;
+        Ok(self.load_state(State {
+            start: deck.cpu().clone(),
+            events,
+            hashes: Vec::new(),
+        }))
+    }
+
+    fn load_state(&mut self, state: State) -> Cpu {
+        let State {
+            start,
+            mut events,
+            hashes,
+        } = state;
         events.reverse(); // So we can pop off the end
         self.events = events;
-        Ok(cpu)
+        self.hashes = hashes;
+        self.hashes.reverse();
+        start
+    }
+
+    /// Compares the current state of the [`ControlDeck`] against the hash recorded for the
+    /// current frame, if any. Only the first desync is reported, as every following frame is
+    /// likely to differ as well.
+    pub fn check_hash(&mut self, deck: &ControlDeck) -> anyhow::Result<Option<Desync>> {
+        let frame = deck.frame_number();
+        while let Some(expected) = self.hashes.last().copied() {
+            if expected.frame > frame {
+                break;
+            }
+            self.hashes.pop();
+            if expected.frame == frame {
+                let actual = deck.state_hash()?;
+                if actual != expected.hash {
+                    self.hashes.clear();
+                    return Ok(Some(Desync {
+                        frame,
+                        expected: expected.hash,
+                        actual,
+                    }));
+                }
+            }
+        }
+        Ok(None)
     }
 
     pub fn next(&mut self, frame: u32) -> Option<EmulationEvent> {
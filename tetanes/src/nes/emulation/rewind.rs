@@ -1,18 +1,25 @@
 use crate::nes::{emulation::State, renderer::gui::MessageType};
 use tetanes_core::{
-    cpu::Cpu,
-    fs::{Error, Result},
+    control_deck::{self, ControlDeck, Result},
+    debug::Breakpoints,
     ppu::frame::Buffer,
 };
 use tracing::error;
 
+#[cfg(feature = "zstd")]
+use tetanes_core::fs;
+
 #[derive(Default, Debug, Clone)]
 #[must_use]
 pub struct Frame {
     pub buffer: Buffer,
     pub state: Vec<u8>,
+    /// CPU cycle the snapshot was taken at, for finding where to re-emulate from.
+    pub cycle: usize,
 }
 
+/// Ring buffer of recent snapshots. Slots are overwritten in place once filled so their
+/// allocations are reused instead of re-allocating every push.
 #[derive(Default, Debug)]
 #[must_use]
 pub struct Rewind {
@@ -23,6 +30,9 @@ pub struct Rewind {
     pub interval: usize,
     pub seconds: usize,
     pub frames: Vec<Option<Frame>>,
+    /// Scratch buffer for uncompressed snapshots.
+    #[cfg(feature = "zstd")]
+    pub snapshot: Vec<u8>,
 }
 
 impl Rewind {
@@ -39,6 +49,8 @@ impl Rewind {
             interval,
             seconds,
             frames: vec![None; Self::frame_size(seconds, interval)],
+            #[cfg(feature = "zstd")]
+            snapshot: Vec::new(),
         }
     }
 
@@ -55,32 +67,45 @@ impl Rewind {
 
     pub fn set_seconds(&mut self, seconds: u32) {
         self.seconds = seconds as usize;
-        self.frames
-            .resize(Self::frame_size(self.seconds, self.interval), None);
+        self.resize();
     }
 
     pub fn set_interval(&mut self, interval: u32) {
         self.interval = interval as usize;
-        self.frames
-            .resize(Self::frame_size(self.seconds, self.interval), None);
+        self.resize();
     }
 
-    pub fn push(&mut self, cpu: &Cpu) -> Result<()> {
-        if !self.enabled {
+    fn resize(&mut self) {
+        let size = Self::frame_size(self.seconds, self.interval);
+        self.frames.resize(size, None);
+        if self.index >= size {
+            self.index = 0;
+        }
+        self.count = self.count.min(size);
+    }
+
+    pub fn push(&mut self, deck: &ControlDeck) -> Result<()> {
+        if !self.enabled || self.frames.is_empty() {
             return Ok(());
         }
         self.interval_counter += 1;
         if self.interval_counter >= self.interval {
             self.interval_counter = 0;
 
-            let state = bincode::serialize(&cpu)
-                .map_err(|err| Error::SerializationFailed(err.to_string()))?;
-            self.frames[self.index] = Some(Frame {
-                buffer: cpu.bus.ppu.frame.buffer.clone(),
-                state,
-            });
+            let frame = self.frames[self.index].get_or_insert_with(Frame::default);
+            frame.buffer.copy_from_slice(&deck.ppu().frame.buffer);
+            frame.cycle = deck.cpu().cycle;
+            cfg_if::cfg_if! {
+                if #[cfg(feature = "zstd")] {
+                    deck.snapshot_into(&mut self.snapshot)?;
+                    fs::compress_snapshot(&self.snapshot, &mut frame.state)
+                        .map_err(fs::Error::EncodingFailed)?;
+                } else {
+                    deck.snapshot_into(&mut frame.state)?;
+                }
+            }
 
-            self.count += 1;
+            self.count = (self.count + 1).min(self.frames.len());
             self.index += 1;
             if self.index >= self.frames.len() {
                 self.index = 0;
@@ -89,29 +114,118 @@ impl Rewind {
         Ok(())
     }
 
-    pub fn pop(&mut self) -> Option<Cpu> {
-        if !self.enabled {
-            return None;
+    /// Restores the most recent snapshot, returning whether one was restored.
+    pub fn pop(&mut self, deck: &mut ControlDeck) -> bool {
+        if !self.enabled || self.count == 0 {
+            return false;
         }
-        if self.count > 0 {
+        self.count -= 1;
+        self.index = self.index.checked_sub(1).unwrap_or(self.frames.len() - 1);
+        let restored = self.restore(deck, self.index);
+        if restored {
+            deck.cpu_mut().bus.input.clear();
+        }
+        restored
+    }
+
+    /// Slots of stored snapshots, newest first.
+    fn slots(&self) -> impl Iterator<Item = usize> {
+        let len = self.frames.len();
+        let index = self.index;
+        (1..=self.count).map(move |n| (index + len - n) % len)
+    }
+
+    /// CPU cycle the snapshot in `slot` was taken at.
+    fn cycle(&self, slot: usize) -> Option<usize> {
+        self.frames[slot].as_ref().map(|frame| frame.cycle)
+    }
+
+    /// Discard snapshots taken after CPU `cycle`, after stepping back to before them.
+    fn discard_after(&mut self, cycle: usize) {
+        while self.count > 0 {
+            let slot = self.index.checked_sub(1).unwrap_or(self.frames.len() - 1);
+            if self.cycle(slot).is_some_and(|snapshot| snapshot <= cycle) {
+                break;
+            }
             self.count -= 1;
-            self.index -= 1;
-            if self.index == 0 {
-                self.index = self.frames.len() - 1;
+            self.index = slot;
+        }
+    }
+
+    /// Re-emulate from the snapshot in `slot` up to CPU `cycle`, returning the number of
+    /// instructions run and the breakpoints hit after each of them. Returns `None` if the
+    /// snapshot couldn't be restored or emulation didn't arrive back at `cycle`, e.g. because
+    /// inputs changed since the snapshot was taken.
+    fn replay(
+        &mut self,
+        deck: &mut ControlDeck,
+        slot: usize,
+        cycle: usize,
+    ) -> Option<(usize, Vec<(usize, Breakpoints)>)> {
+        if !self.restore(deck, slot) {
+            return None;
+        }
+        let mut instrs = 0;
+        let mut hits = Vec::new();
+        while deck.cpu().cycle < cycle {
+            match deck.clock_instr() {
+                Ok(_) => (),
+                Err(control_deck::Error::Breakpoint(breakpoints)) => {
+                    hits.push((instrs, breakpoints));
+                }
+                Err(_) => return None,
             }
+            instrs += 1;
+        }
+        (deck.cpu().cycle == cycle).then_some((instrs, hits))
+    }
 
-            let frame = self.frames[self.index].take()?;
-            bincode::deserialize::<Cpu>(&frame.state)
-                .map(|mut cpu| {
-                    cpu.bus.input.clear();
-                    cpu.bus.ppu.frame.buffer = frame.buffer;
-                    cpu
-                })
-                .map_err(|err| error!("Failed to deserialize CPU state: {err:?}"))
-                .ok()
-        } else {
-            None
+    /// Restore the snapshot in `slot` and run `instrs` instructions from it, ignoring
+    /// breakpoints. Returns whether all of the instructions ran.
+    fn run_from(&mut self, deck: &mut ControlDeck, slot: usize, instrs: usize) -> bool {
+        if !self.restore(deck, slot) {
+            return false;
         }
+        let breakpoints = deck.breakpoints();
+        deck.set_breakpoints(Breakpoints::empty());
+        let ran = (0..instrs).all(|_| deck.clock_instr().is_ok());
+        deck.set_breakpoints(breakpoints);
+        ran
+    }
+
+    /// Restores the snapshot in `slot`, returning whether it was restored.
+    fn restore(&mut self, deck: &mut ControlDeck, slot: usize) -> bool {
+        let Some(frame) = &self.frames[slot] else {
+            return false;
+        };
+        cfg_if::cfg_if! {
+            if #[cfg(feature = "zstd")] {
+                let restored = fs::decompress_snapshot(&frame.state, &mut self.snapshot)
+                    .map_err(|err| fs::Error::DecodingFailed(err).into())
+                    .and_then(|_| deck.restore_snapshot(&self.snapshot));
+            } else {
+                let restored = deck.restore_snapshot(&frame.state);
+            }
+        }
+        match restored {
+            Ok(()) => {
+                deck.ppu_mut().frame.buffer.copy_from_slice(&frame.buffer);
+                true
+            }
+            Err(err) => {
+                error!("Failed to restore CPU state: {err:?}");
+                false
+            }
+        }
+    }
+
+    /// Returns the number of bytes used by stored snapshots.
+    pub fn memory_usage(&self) -> usize {
+        self.frames
+            .iter()
+            .flatten()
+            .map(|frame| frame.state.capacity() + std::mem::size_of_val(&*frame.buffer))
+            .sum()
     }
 
     pub fn clear(&mut self) {
@@ -136,12 +250,145 @@ impl State {
         }
         // ~2 seconds worth of frames @ 60 FPS
         let mut rewind_frames = 120 / self.rewind.interval;
-        while let Some(cpu) = self.rewind.pop() {
-            self.control_deck.load_cpu(cpu);
+        while self.rewind.pop(&mut self.control_deck) {
             rewind_frames -= 1;
             if rewind_frames == 0 {
                 break;
             }
         }
     }
+
+    /// Step back one CPU instruction by re-emulating from the nearest earlier rewind snapshot.
+    pub fn step_back(&mut self) {
+        if !self.rewind.enabled {
+            return self.rewind_disabled();
+        }
+        let cycle = self.control_deck.cpu().cycle;
+        let Some(slot) = self
+            .rewind
+            .slots()
+            .find(|&slot| self.rewind.cycle(slot).is_some_and(|start| start < cycle))
+        else {
+            return self.add_message(
+                MessageType::Warn,
+                "No earlier rewind snapshot to step back to.",
+            );
+        };
+        let Some(current) = self.reverse_snapshot() else {
+            return;
+        };
+
+        let breakpoints = self.control_deck.breakpoints();
+        self.control_deck.set_breakpoints(Breakpoints::empty());
+        let stepped = self
+            .rewind
+            .replay(&mut self.control_deck, slot, cycle)
+            .is_some_and(|(instrs, _)| {
+                self.rewind
+                    .run_from(&mut self.control_deck, slot, instrs - 1)
+            });
+        self.control_deck.set_breakpoints(breakpoints);
+        self.finish_reverse(
+            stepped,
+            &current,
+            "Couldn't step back: emulation diverged from the rewind snapshot.",
+        );
+    }
+
+    /// Run backwards to the most recent breakpoint hit before the current instruction, by
+    /// re-emulating from earlier rewind snapshots.
+    pub fn reverse_continue(&mut self) {
+        if !self.rewind.enabled {
+            return self.rewind_disabled();
+        }
+        if self.control_deck.breakpoints().is_empty() {
+            return self.add_message(MessageType::Warn, "No breakpoints to reverse continue to.");
+        }
+        let Some(current) = self.reverse_snapshot() else {
+            return;
+        };
+
+        let start = self.control_deck.cpu().cycle;
+        let mut cycle = start;
+        let mut found = None;
+        let mut diverged = false;
+        for slot in self.rewind.slots().collect::<Vec<_>>() {
+            let Some(slot_cycle) = self
+                .rewind
+                .cycle(slot)
+                .filter(|&slot_cycle| slot_cycle < cycle)
+            else {
+                continue;
+            };
+            let Some((instrs, hits)) = self.rewind.replay(&mut self.control_deck, slot, cycle)
+            else {
+                diverged = true;
+                break;
+            };
+            // The instruction arriving back where we started already hit its breakpoint
+            let last = if cycle == start { instrs - 1 } else { instrs };
+            if let Some(&(instr, breakpoints)) = hits.iter().rev().find(|(instr, _)| *instr < last)
+            {
+                found = Some((slot, instr + 1, breakpoints));
+                break;
+            }
+            cycle = slot_cycle;
+        }
+
+        let diverged_warning =
+            "Couldn't reverse continue: emulation diverged from the rewind snapshot.";
+        match found {
+            Some((slot, instrs, breakpoints)) => {
+                let stepped = self.rewind.run_from(&mut self.control_deck, slot, instrs);
+                self.finish_reverse(stepped, &current, diverged_warning);
+                if stepped {
+                    self.on_breakpoint(breakpoints);
+                }
+            }
+            None if diverged => self.finish_reverse(false, &current, diverged_warning),
+            None => self.finish_reverse(
+                false,
+                &current,
+                "No earlier breakpoint hit in the rewind buffer.",
+            ),
+        }
+    }
+
+    /// Snapshot the current state to go back to if reverse stepping fails.
+    fn reverse_snapshot(&mut self) -> Option<Frame> {
+        let mut frame = Frame {
+            cycle: self.control_deck.cpu().cycle,
+            ..Frame::default()
+        };
+        frame
+            .buffer
+            .copy_from_slice(&self.control_deck.ppu().frame.buffer);
+        match self.control_deck.snapshot_into(&mut frame.state) {
+            Ok(()) => Some(frame),
+            Err(err) => {
+                self.on_error(err);
+                None
+            }
+        }
+    }
+
+    /// Keep the state reverse stepping arrived at, or go back to `current` with a warning if it
+    /// failed.
+    fn finish_reverse(&mut self, stepped: bool, current: &Frame, warning: &str) {
+        if stepped {
+            self.rewind.discard_after(self.control_deck.cpu().cycle);
+        } else {
+            match self.control_deck.restore_snapshot(&current.state) {
+                Ok(()) => self
+                    .control_deck
+                    .ppu_mut()
+                    .frame
+                    .buffer
+                    .copy_from_slice(&current.buffer),
+                Err(err) => self.on_error(err),
+            }
+            self.add_message(MessageType::Warn, warning);
+        }
+        self.send_frame();
+    }
 }
@@ -0,0 +1,71 @@
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tetanes_core::fs;
+
+/// A rotating ring of quick save states for a single ROM, kept separate from the numbered save
+/// slots so that repeated quick saves never overwrite a slot state.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[must_use]
+#[serde(default)] // Ensures new fields don't break existing rings
+pub struct SaveRing {
+    /// Unix timestamp in milliseconds of when each ring entry was saved, if it has been.
+    pub saved_at: Vec<Option<i64>>,
+    /// Number of states loaded since the last save, used to cycle back to older states.
+    #[serde(skip)]
+    load_offset: usize,
+}
+
+impl SaveRing {
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        if !fs::exists(path) {
+            return Ok(Self::default());
+        }
+        let data = fs::load_raw(path).context("failed to load save state ring")?;
+        serde_json::from_slice(&data).with_context(|| format!("failed to parse {path:?}"))
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let data =
+            serde_json::to_vec_pretty(&self).context("failed to serialize save state ring")?;
+        fs::save_raw(path, &data).context("failed to save save state ring")?;
+        Ok(())
+    }
+
+    /// Returns the entry to save the next state to out of `len` entries: the first empty one, or
+    /// the oldest once the ring is full.
+    pub fn next_save_index(&self, len: usize) -> usize {
+        (0..len.max(1))
+            .min_by_key(|&index| self.saved_at.get(index).copied().flatten())
+            .unwrap_or_default()
+    }
+
+    /// Record that a state was saved to `index`, restarting load cycling from the newest state.
+    pub fn saved(&mut self, index: usize, timestamp: i64) {
+        if self.saved_at.len() <= index {
+            self.saved_at.resize(index + 1, None);
+        }
+        self.saved_at[index] = Some(timestamp);
+        self.load_offset = 0;
+    }
+
+    /// Returns the entry and timestamp of the next state to load out of `len` entries, starting
+    /// with the newest and cycling to older states on each call.
+    pub fn next_load_index(&mut self, len: usize) -> Option<(usize, i64)> {
+        let mut entries = self
+            .saved_at
+            .iter()
+            .take(len)
+            .enumerate()
+            .filter_map(|(index, saved_at)| saved_at.map(|saved_at| (index, saved_at)))
+            .collect::<Vec<_>>();
+        if entries.is_empty() {
+            return None;
+        }
+        entries.sort_by_key(|&(_, saved_at)| std::cmp::Reverse(saved_at));
+        let entry = entries[self.load_offset % entries.len()];
+        self.load_offset = (self.load_offset % entries.len()) + 1;
+        Some(entry)
+    }
+}
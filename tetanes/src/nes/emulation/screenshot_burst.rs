@@ -0,0 +1,102 @@
+//! Saves every emulated frame for a short burst as numbered PNGs, for making sprite sheets and
+//! analyzing animations frame by frame.
+
+use crate::nes::{
+    event::{NesEventProxy, UiEvent},
+    renderer::gui::MessageType,
+};
+use anyhow::Context;
+use chrono::Local;
+use crossbeam::channel::{self, Sender};
+use std::path::Path;
+use tetanes_core::ppu::Ppu;
+use tracing::{debug, error};
+
+/// Captures frames into their own directory. Frames are queued to a background thread to be
+/// encoded and written, so slow disks don't stall emulation.
+#[derive(Debug)]
+#[must_use]
+pub struct ScreenshotBurst {
+    remaining: u32,
+    captured: u32,
+    frame_tx: Sender<Vec<u8>>,
+}
+
+impl ScreenshotBurst {
+    /// Most frames captured by one burst, which bounds the memory used by frames waiting to be
+    /// written.
+    pub const MAX_FRAMES: u32 = 1200;
+
+    /// Start capturing the next `frames` frames into a new directory in `dir`. A message is sent
+    /// once all captured frames have been written.
+    pub fn start(dir: &Path, frames: u32, tx: NesEventProxy) -> anyhow::Result<Self> {
+        let dir = dir.join(
+            Local::now()
+                .format("burst_%Y-%m-%d_at_%H_%M_%S")
+                .to_string(),
+        );
+        std::fs::create_dir_all(&dir)
+            .with_context(|| format!("failed to create screenshot burst dir: {dir:?}"))?;
+
+        let (frame_tx, frame_rx) = channel::unbounded::<Vec<u8>>();
+        std::thread::Builder::new()
+            .name("screenshot_burst".into())
+            .spawn(move || {
+                let mut saved = 0;
+                for frame in frame_rx {
+                    saved += 1;
+                    let path = dir.join(format!("frame_{saved:05}.png"));
+                    if let Err(err) = image::save_buffer(
+                        &path,
+                        &frame,
+                        Ppu::WIDTH,
+                        Ppu::HEIGHT,
+                        image::ColorType::Rgba8,
+                    ) {
+                        error!("failed to save burst screenshot: {path:?}. {err:?}");
+                        tx.event(UiEvent::Message((
+                            MessageType::Error,
+                            format!("Failed to save burst screenshot: {}", path.display()),
+                        )));
+                        // Dropping the receiver ends the burst
+                        return;
+                    }
+                }
+                debug!("saved {saved} burst screenshots to {dir:?}");
+                tx.event(UiEvent::Message((
+                    MessageType::Info,
+                    format!("Saved {saved} Burst Screenshots: {}", dir.display()),
+                )));
+            })
+            .context("failed to spawn screenshot burst thread")?;
+
+        Ok(Self {
+            remaining: frames.clamp(1, Self::MAX_FRAMES),
+            captured: 0,
+            frame_tx,
+        })
+    }
+
+    /// Queue a frame to be written, if the burst isn't done.
+    pub fn push(&mut self, frame_buffer: &[u8]) {
+        if self.remaining == 0 {
+            return;
+        }
+        if self.frame_tx.send(frame_buffer.to_vec()).is_ok() {
+            self.remaining -= 1;
+            self.captured += 1;
+        } else {
+            // Writing failed, so there's no point capturing more frames
+            self.remaining = 0;
+        }
+    }
+
+    /// Whether all frames have been captured. Remaining frames may still be being written.
+    pub const fn is_done(&self) -> bool {
+        self.remaining == 0
+    }
+
+    pub const fn captured(&self) -> u32 {
+        self.captured
+    }
+}
@@ -0,0 +1,321 @@
+use crate::nes::config::{Config, PathsConfig};
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+use tetanes_core::{control_deck::Config as DeckConfig, fs};
+use tracing::{debug, info};
+
+/// Contents of a synced file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[must_use]
+pub struct SyncFile {
+    pub data: Vec<u8>,
+    pub modified: Option<SystemTime>,
+}
+
+/// Remote storage that save states and battery-backed Save RAM are mirrored to. Files are
+/// identified by a key relative to the storage root, e.g. `save/<rom>/slot-1.sav`.
+pub trait SyncProvider: std::fmt::Debug + Send {
+    /// Uniquely identifies the remote storage, so that sync history isn't shared between
+    /// providers.
+    fn id(&self) -> String;
+
+    /// Read the file for `key`, if it exists.
+    fn read(&self, key: &str) -> anyhow::Result<Option<SyncFile>>;
+
+    /// Write the file for `key`, replacing any existing file.
+    fn write(&self, key: &str, data: &[u8]) -> anyhow::Result<()>;
+}
+
+/// Syncs to a folder that is kept in sync by another program, such as Dropbox or Syncthing.
+#[derive(Debug)]
+#[must_use]
+pub struct FolderProvider {
+    dir: PathBuf,
+}
+
+impl FolderProvider {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+}
+
+impl SyncProvider for FolderProvider {
+    fn id(&self) -> String {
+        format!("folder:{}", self.dir.display())
+    }
+
+    fn read(&self, key: &str) -> anyhow::Result<Option<SyncFile>> {
+        let path = self.dir.join(key);
+        if !path.is_file() {
+            return Ok(None);
+        }
+        let data = std::fs::read(&path).with_context(|| format!("failed to read {path:?}"))?;
+        Ok(Some(SyncFile {
+            data,
+            modified: modified(&path),
+        }))
+    }
+
+    fn write(&self, key: &str, data: &[u8]) -> anyhow::Result<()> {
+        let path = self.dir.join(key);
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("failed to create directory {dir:?}"))?;
+        }
+        std::fs::write(&path, data).with_context(|| format!("failed to write {path:?}"))
+    }
+}
+
+/// Which kind of file is synced.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[must_use]
+pub enum SyncKind {
+    Sram,
+    SaveState(u8),
+}
+
+/// A file to sync for a ROM.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[must_use]
+pub struct SyncEntry {
+    pub name: String,
+    pub kind: SyncKind,
+    pub key: String,
+    pub path: PathBuf,
+}
+
+impl SyncEntry {
+    pub fn sram(paths: &PathsConfig, name: &str) -> Self {
+        // Matches the path used by `ControlDeck::save_sram`
+        let path = paths
+            .sram_dir()
+            .join(name)
+            .with_extension(DeckConfig::SRAM_EXTENSION);
+        Self::new(name, SyncKind::Sram, DeckConfig::SRAM_DIR, path)
+    }
+
+    pub fn save_state(paths: &PathsConfig, name: &str, slot: u8) -> Self {
+        let path = paths.save_path(name, slot);
+        let dir = format!("{}/{name}", Config::SAVE_DIR);
+        Self::new(name, SyncKind::SaveState(slot), &dir, path)
+    }
+
+    /// All files to sync for a ROM.
+    pub fn all(paths: &PathsConfig, name: &str) -> Vec<Self> {
+        std::iter::once(Self::sram(paths, name))
+            .chain(SaveSync::SAVE_SLOTS.map(|slot| Self::save_state(paths, name, slot)))
+            .collect()
+    }
+
+    fn new(name: &str, kind: SyncKind, dir: &str, path: PathBuf) -> Self {
+        let key = format!("{dir}/{}", fs::filename(&path));
+        Self {
+            name: name.to_string(),
+            kind,
+            key,
+            path,
+        }
+    }
+
+    pub fn label(&self) -> String {
+        match self.kind {
+            SyncKind::Sram => format!("{} Save RAM", self.name),
+            SyncKind::SaveState(slot) => format!("{} State {slot}", self.name),
+        }
+    }
+}
+
+/// A file that changed both locally and remotely since it was last synced.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[must_use]
+pub struct SyncConflict {
+    pub entry: SyncEntry,
+    pub local_modified: Option<SystemTime>,
+    pub remote_modified: Option<SystemTime>,
+}
+
+/// How to resolve a [`SyncConflict`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[must_use]
+pub enum SyncChoice {
+    KeepLocal,
+    KeepRemote,
+}
+
+/// Files changed by a sync.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+#[must_use]
+pub struct SyncResult {
+    pub pushed: usize,
+    /// Local files replaced with the remote copy.
+    pub pulled: Vec<SyncEntry>,
+    pub conflicts: Vec<SyncConflict>,
+}
+
+/// Hashes of every file as of its last successful sync, used to tell which side changed.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[must_use]
+#[serde(default)] // Ensures new fields don't break existing manifests
+struct SyncManifest {
+    provider: String,
+    hashes: HashMap<String, u32>,
+}
+
+/// Mirrors save states and Save RAM to a [`SyncProvider`], detecting conflicts when a file
+/// changed on both sides since the last sync.
+#[derive(Debug)]
+#[must_use]
+pub struct SaveSync {
+    provider: Box<dyn SyncProvider>,
+    manifest: SyncManifest,
+    manifest_path: PathBuf,
+}
+
+impl SaveSync {
+    pub const MANIFEST: &'static str = "sync.json";
+    pub const SAVE_SLOTS: RangeInclusive<u8> = 1..=8;
+
+    /// Create a `SaveSync` for the configured provider, if syncing is enabled.
+    pub fn new(paths: &PathsConfig) -> anyhow::Result<Option<Self>> {
+        let Some(dir) = &paths.sync_dir else {
+            return Ok(None);
+        };
+        let provider = Box::new(FolderProvider::new(dir));
+        let manifest_path = paths.data_dir().join(Self::MANIFEST);
+        let mut manifest = if fs::exists(&manifest_path) {
+            let data = fs::load_raw(&manifest_path).context("failed to load sync manifest")?;
+            serde_json::from_slice::<SyncManifest>(&data)
+                .with_context(|| format!("failed to parse {manifest_path:?}"))?
+        } else {
+            SyncManifest::default()
+        };
+        if manifest.provider != provider.id() {
+            // History from another provider can't tell which side of this one changed
+            manifest = SyncManifest {
+                provider: provider.id(),
+                hashes: HashMap::new(),
+            };
+        }
+        Ok(Some(Self {
+            provider,
+            manifest,
+            manifest_path,
+        }))
+    }
+
+    /// Sync `entries`, copying whichever side changed since the last sync to the other. Files
+    /// changed on both sides are left alone and returned as conflicts to be resolved with
+    /// [`SaveSync::resolve`].
+    pub fn sync(&mut self, entries: &[SyncEntry]) -> anyhow::Result<SyncResult> {
+        let mut result = SyncResult::default();
+        for entry in entries {
+            let local = Self::read_local(&entry.path)?;
+            let remote = self.provider.read(&entry.key)?;
+            let base = self.manifest.hashes.get(&entry.key).copied();
+            match (local, remote) {
+                (None, None) => (),
+                (Some(local), None) => {
+                    self.push(entry, &local.data)?;
+                    result.pushed += 1;
+                }
+                (None, Some(remote)) => {
+                    self.pull(entry, &remote.data)?;
+                    result.pulled.push(entry.clone());
+                }
+                (Some(local), Some(remote)) => {
+                    let local_hash = fs::compute_crc32(&local.data);
+                    let remote_hash = fs::compute_crc32(&remote.data);
+                    if local_hash == remote_hash {
+                        self.manifest.hashes.insert(entry.key.clone(), local_hash);
+                    } else if base == Some(remote_hash) {
+                        self.push(entry, &local.data)?;
+                        result.pushed += 1;
+                    } else if base == Some(local_hash) {
+                        self.pull(entry, &remote.data)?;
+                        result.pulled.push(entry.clone());
+                    } else {
+                        debug!("sync conflict: {}", entry.key);
+                        result.conflicts.push(SyncConflict {
+                            entry: entry.clone(),
+                            local_modified: local.modified,
+                            remote_modified: remote.modified,
+                        });
+                    }
+                }
+            }
+        }
+        self.save_manifest()?;
+        if result.pushed > 0 || !result.pulled.is_empty() {
+            info!(
+                "synced saves: {} pushed, {} pulled",
+                result.pushed,
+                result.pulled.len()
+            );
+        }
+        Ok(result)
+    }
+
+    /// Resolve a conflict by overwriting the other side with the chosen copy.
+    pub fn resolve(&mut self, entry: &SyncEntry, choice: SyncChoice) -> anyhow::Result<()> {
+        match choice {
+            SyncChoice::KeepLocal => {
+                if let Some(local) = Self::read_local(&entry.path)? {
+                    self.push(entry, &local.data)?;
+                }
+            }
+            SyncChoice::KeepRemote => {
+                if let Some(remote) = self.provider.read(&entry.key)? {
+                    self.pull(entry, &remote.data)?;
+                }
+            }
+        }
+        self.save_manifest()
+    }
+
+    fn read_local(path: &Path) -> anyhow::Result<Option<SyncFile>> {
+        if !fs::exists(path) {
+            return Ok(None);
+        }
+        let data = fs::load_raw(path).with_context(|| format!("failed to read {path:?}"))?;
+        Ok(Some(SyncFile {
+            data,
+            modified: modified(path),
+        }))
+    }
+
+    fn push(&mut self, entry: &SyncEntry, data: &[u8]) -> anyhow::Result<()> {
+        self.provider.write(&entry.key, data)?;
+        self.manifest
+            .hashes
+            .insert(entry.key.clone(), fs::compute_crc32(data));
+        Ok(())
+    }
+
+    fn pull(&mut self, entry: &SyncEntry, data: &[u8]) -> anyhow::Result<()> {
+        fs::save_raw(&entry.path, data)
+            .with_context(|| format!("failed to write {:?}", entry.path))?;
+        self.manifest
+            .hashes
+            .insert(entry.key.clone(), fs::compute_crc32(data));
+        Ok(())
+    }
+
+    fn save_manifest(&self) -> anyhow::Result<()> {
+        let data =
+            serde_json::to_vec_pretty(&self.manifest).context("failed to serialize manifest")?;
+        fs::save_raw(&self.manifest_path, &data).context("failed to save sync manifest")?;
+        Ok(())
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
@@ -2,33 +2,60 @@ use crate::{
     feature,
     nes::{
         action::{Action, Debug, DebugKind, DebugStep, Feature, Setting, Ui},
-        config::Config,
-        emulation::FrameStats,
-        input::{ActionBindings, AxisDirection, Gamepads, Input, InputBindings},
+        attract::AttractMode,
+        audio::{recording::RecordingLimits, AudioBackend},
+        config::{
+            Config, FontSize, FullscreenTarget, KeybindProfile, PathsConfig, ThemePreset,
+            UpdateCheck,
+        },
+        emulation::{
+            playtime::Playtime,
+            race::RaceInputs,
+            ram_watch::RamWatch,
+            replay,
+            screenshot_burst::ScreenshotBurst,
+            sync::{SyncChoice, SyncConflict, SyncEntry},
+            FrameStats,
+        },
+        input::{
+            ActionBindings, AxisDirection, Gamepads, Input, InputBindings, InputDevice,
+            ShortcutPreset,
+        },
         renderer::{
-            gui::{Menu, MessageType},
-            shader::Shader,
+            gui::{unsupported_mapper::MapperStats, Menu, MessageType},
+            painter::PresentMode,
+            shader::{ColorBlindMode, Shader, Transition},
+            texture::{Rotation, TextureFilter},
         },
         rom::RomData,
+        rom_library::{LibraryRom, RomWatcher},
+        version::Release,
         Nes, RunState, Running, State,
     },
-    platform::open_file_dialog,
+    platform::{open_file_dialog, save_file_dialog},
+};
+use anyhow::{anyhow, Context};
+use chrono::Local;
+use egui::{Color32, ViewportId};
+use std::{
+    collections::BTreeSet,
+    path::{Path, PathBuf},
 };
-use anyhow::anyhow;
-use egui::ViewportId;
-use std::path::PathBuf;
 use tetanes_core::{
     action::Action as DeckAction,
-    apu::{Apu, Channel},
+    apu::{stereo::StereoConfig, Apu, Channel},
+    cart::header_fix::HeaderIssue,
     common::{NesRegion, ResetKind},
     control_deck::{LoadedRom, MapperRevisionsConfig},
-    debug::Debugger,
+    debug::{profiler::ProfileReport, symbols::Symbols, Breakpoints, Debugger, PpuLayers},
+    fs,
     genie::GenieCode,
-    input::{FourPlayer, JoypadBtn, Player},
+    input::{FourPlayer, JoypadBtn, Player, ZapperConfig},
+    mapper::{MapperDebugState, MapperIrqEvent, MapperWrite},
     mem::RamState,
     ppu::Ppu,
     time::{Duration, Instant},
-    video::VideoFilter,
+    video::{upscale::UpscaleFilter, VideoFilter},
 };
 use tracing::{debug, error, trace};
 use uuid::Uuid;
@@ -36,7 +63,7 @@ use winit::{
     application::ApplicationHandler,
     event::{DeviceEvent, DeviceId, ElementState, WindowEvent},
     event_loop::{ActiveEventLoop, ControlFlow, DeviceEvents, EventLoop, EventLoopProxy},
-    keyboard::PhysicalKey,
+    keyboard::{KeyCode, ModifiersState, PhysicalKey},
     window::WindowId,
 };
 
@@ -115,24 +142,50 @@ impl From<accesskit_winit::Event> for NesEvent {
 #[derive(Debug, Clone, PartialEq)]
 #[must_use]
 pub enum ConfigEvent {
+    AccentColor(Option<Color32>),
     ActionBindings(Vec<ActionBindings>),
     ActionBindingSet((Action, Input, usize)),
     ActionBindingClear(Input),
     AlwaysOnTop(bool),
+    AnnounceMessages(bool),
+    AttractModeMinutes(u32),
     ApuChannelEnabled((Channel, bool)),
     ApuChannelsEnabled([bool; Apu::MAX_CHANNEL_COUNT]),
+    AudioBackend(AudioBackend),
     AudioBuffer(usize),
     AudioEnabled(bool),
     AudioLatency(Duration),
+    AudioRecordingLimits(RecordingLimits),
     AutoLoad(bool),
+    AutoPeripherals(bool),
     AutoSave(bool),
     AutoSaveInterval(Duration),
+    BlackFrameInsertion(bool),
+    ColorBlindMode(ColorBlindMode),
+    CompactMode(bool),
+    /// Save the current configuration and switch to the named configuration profile, creating it
+    /// from the current configuration if it doesn't exist.
+    ConfigProfile(String),
+    ConfigProfileRemove(String),
     ConcurrentDpad(bool),
     CycleAccurate(bool),
+    /// Enable or disable DMC pop reduction for a ROM by name.
+    DmcClickReduction((String, bool)),
+    /// Replace the names of all ROMs with DMC pop reduction enabled.
+    DmcClickReductionRoms(BTreeSet<String>),
     DarkTheme(bool),
     EmbedViewports(bool),
+    ExclusiveDevices(bool),
+    FastBoot(bool),
+    FastForwardFrameSkip(u32),
+    FastForwardSpeed(f32),
+    FastForwardToggle(bool),
+    FastForwardUncapped(bool),
+    FlipHorizontal(bool),
+    FontSize(FontSize),
     FourPlayer(FourPlayer),
     Fullscreen(bool),
+    FullscreenTarget(FullscreenTarget),
     GamepadAssign((Player, Uuid)),
     GamepadAssignments([(Player, Option<Uuid>); 4]),
     GamepadUnassign(Player),
@@ -140,22 +193,160 @@ pub enum ConfigEvent {
     GenieCodeClear,
     GenieCodeRemoved(String),
     HideOverscan(bool),
+    HighContrast(bool),
+    /// Switch to a profile created from a shortcut preset, resetting it if it already exists.
+    KeybindPreset(ShortcutPreset),
+    KeybindProfile(String),
+    KeybindProfileImport(KeybindProfile),
+    KeybindProfileRemove(String),
+    KeyboardAssign((Player, DeviceId)),
+    KeyboardUnassign(Player),
+    /// Record when updates were last checked for, as a Unix timestamp in seconds.
+    LastUpdateCheck(i64),
     MapperRevisions(MapperRevisionsConfig),
+    MapperStats(bool),
+    Paths(PathsConfig),
+    PresentMode(PresentMode),
     RamState(RamState),
+    RamWatches(Vec<RamWatch>),
+    RecentRomPinned((PathBuf, bool)),
+    RecentRomRemoved(PathBuf),
     RecentRomsClear,
+    RecentRomsRemoveMissing,
     Region(NesRegion),
+    /// Set the number of frames between state hashes recorded with replays.
+    ReplayHashInterval(u32),
     RewindEnabled(bool),
     RewindInterval(u32),
     RewindSeconds(u32),
+    Rotation(Rotation),
     RunAhead(usize),
+    /// Set the number of states kept in the quick save state ring.
+    SaveRingLen(usize),
     SaveSlot(u8),
     Scale(f32),
+    Scanlines(bool),
+    ScreenshotBurstFrames(u32),
     Shader(Shader),
     ShowMenubar(bool),
     ShowMessages(bool),
     Speed(f32),
+    SpriteLimit(bool),
+    Stereo(StereoConfig),
+    SyncToDisplay(bool),
+    TextureFilter(TextureFilter),
+    Theme(ThemePreset),
+    Transition(Transition),
+    UpdateCheck(UpdateCheck),
+    UpscaleFilter(UpscaleFilter),
+    VausConnected(bool),
     VideoFilter(VideoFilter),
+    /// Watch the ROM directory in the background to keep the ROM library up to date.
+    WatchRomsPath(bool),
+    ZapperConfig(ZapperConfig),
     ZapperConnected(bool),
+    Zoom(f32),
+}
+
+impl ConfigEvent {
+    /// Events that update every setting to match `cfg`, such as when restoring defaults or
+    /// switching configuration profiles. Recent ROMs are left unchanged.
+    pub fn from_config(cfg: Config) -> Vec<Self> {
+        let Config {
+            version: _,
+            deck,
+            emulation,
+            audio,
+            renderer,
+            input,
+            paths,
+            profile: _,
+            startup: _,
+        } = cfg;
+
+        let mut events = vec![
+            ConfigEvent::AccentColor(renderer.accent_color),
+            ConfigEvent::ActionBindings(input.action_bindings),
+            ConfigEvent::AlwaysOnTop(renderer.always_on_top),
+            ConfigEvent::AnnounceMessages(renderer.announce_messages),
+            ConfigEvent::ApuChannelsEnabled(deck.channels_enabled),
+            ConfigEvent::AttractModeMinutes(renderer.attract_mode_minutes),
+            ConfigEvent::AudioBackend(audio.backend),
+            ConfigEvent::AudioBuffer(audio.buffer_size),
+            ConfigEvent::AudioEnabled(audio.enabled),
+            ConfigEvent::AudioLatency(audio.latency),
+            ConfigEvent::AudioRecordingLimits(audio.recording_limits),
+            ConfigEvent::AutoLoad(emulation.auto_load),
+            ConfigEvent::AutoPeripherals(deck.auto_peripherals),
+            ConfigEvent::AutoSave(emulation.auto_save),
+            ConfigEvent::AutoSaveInterval(emulation.auto_save_interval),
+            ConfigEvent::BlackFrameInsertion(renderer.black_frame_insertion),
+            ConfigEvent::CompactMode(renderer.compact_mode),
+            ConfigEvent::ColorBlindMode(renderer.color_blind_mode),
+            ConfigEvent::ConcurrentDpad(deck.concurrent_dpad),
+            ConfigEvent::CycleAccurate(deck.cycle_accurate),
+            ConfigEvent::DarkTheme(renderer.dark_theme),
+            ConfigEvent::DmcClickReductionRoms(audio.dmc_click_reduction),
+            ConfigEvent::EmbedViewports(renderer.embed_viewports),
+            ConfigEvent::ExclusiveDevices(input.exclusive_devices),
+            ConfigEvent::FastBoot(deck.fast_boot),
+            ConfigEvent::FastForwardFrameSkip(emulation.fast_forward_frame_skip),
+            ConfigEvent::FastForwardSpeed(emulation.fast_forward_speed),
+            ConfigEvent::FastForwardToggle(emulation.fast_forward_toggle),
+            ConfigEvent::FastForwardUncapped(emulation.fast_forward_uncapped),
+            ConfigEvent::FontSize(renderer.font_size),
+            ConfigEvent::FourPlayer(deck.four_player),
+            ConfigEvent::Fullscreen(renderer.fullscreen),
+            ConfigEvent::FullscreenTarget(renderer.fullscreen_target),
+            ConfigEvent::GamepadAssignments(input.gamepad_assignments),
+            ConfigEvent::GenieCodeClear,
+            ConfigEvent::FlipHorizontal(renderer.flip_horizontal),
+            ConfigEvent::HideOverscan(renderer.hide_overscan),
+            ConfigEvent::HighContrast(renderer.high_contrast),
+            ConfigEvent::MapperStats(renderer.mapper_stats),
+            ConfigEvent::MapperRevisions(deck.mapper_revisions),
+            ConfigEvent::Paths(paths),
+            ConfigEvent::PresentMode(renderer.present_mode),
+            ConfigEvent::RamState(deck.ram_state),
+            ConfigEvent::RamWatches(renderer.ram_watches),
+            // Clearing recent roms is handled in a separate button
+            ConfigEvent::Region(deck.region),
+            ConfigEvent::ReplayHashInterval(emulation.replay_hash_interval),
+            ConfigEvent::RewindEnabled(emulation.rewind),
+            ConfigEvent::RewindInterval(emulation.rewind_interval),
+            ConfigEvent::RewindSeconds(emulation.rewind_seconds),
+            ConfigEvent::Rotation(renderer.rotation),
+            ConfigEvent::RunAhead(emulation.run_ahead),
+            ConfigEvent::SaveRingLen(emulation.save_ring_len),
+            ConfigEvent::SaveSlot(emulation.save_slot),
+            ConfigEvent::Scanlines(renderer.scanlines),
+            ConfigEvent::ScreenshotBurstFrames(emulation.screenshot_burst_frames),
+            ConfigEvent::Shader(renderer.shader),
+            ConfigEvent::ShowMenubar(renderer.show_menubar),
+            ConfigEvent::ShowMessages(renderer.show_messages),
+            ConfigEvent::Speed(emulation.speed),
+            ConfigEvent::SpriteLimit(deck.sprite_limit),
+            ConfigEvent::Stereo(deck.stereo),
+            ConfigEvent::SyncToDisplay(emulation.sync_to_display),
+            ConfigEvent::TextureFilter(renderer.texture_filter),
+            ConfigEvent::Theme(renderer.theme),
+            ConfigEvent::Transition(renderer.transition),
+            ConfigEvent::UpdateCheck(renderer.update_check),
+            ConfigEvent::UpscaleFilter(renderer.upscale_filter),
+            ConfigEvent::VausConnected(deck.vaus),
+            ConfigEvent::VideoFilter(deck.filter),
+            ConfigEvent::WatchRomsPath(renderer.watch_roms_path),
+            ConfigEvent::ZapperConfig(deck.zapper_config),
+            ConfigEvent::ZapperConnected(deck.zapper),
+            ConfigEvent::Zoom(renderer.zoom),
+        ];
+        events.extend(
+            deck.genie_codes
+                .into_iter()
+                .map(ConfigEvent::GenieCodeAdded),
+        );
+        events
+    }
 }
 
 impl From<ConfigEvent> for NesEvent {
@@ -179,27 +370,91 @@ impl From<DebugEvent> for NesEvent {
 #[derive(Debug, Clone, PartialEq)]
 #[must_use]
 pub enum EmulationEvent {
+    /// Start or stop pausing emulation when any of these conditions are met, for debugging.
+    Breakpoints((Breakpoints, bool)),
     AddDebugger(Debugger),
     RemoveDebugger(Debugger),
+    /// Start or stop playing back a ROM in attract mode, which disables auto-loading and
+    /// auto-saving states.
+    AttractMode(bool),
     AudioRecord(bool),
+    /// Clear the game code profile gathered so far.
+    ClearProfile,
     DebugStep(DebugStep),
+    DisplayRefreshRate(Option<f32>),
     EmulatePpuWarmup(bool),
+    /// Export recent frame timing samples to a CSV or JSON file.
+    ExportFrameStats(PathBuf),
+    /// Export the joypad inputs of a replay recording to a hand-editable input script next to it.
+    ExportInputScript(PathBuf),
+    /// Save the pattern tables as a grayscale PNG sprite sheet.
+    ExportChr(PathBuf),
+    /// Save the current palette entries as RGB triplets in a `.pal` file.
+    ExportPalette(PathBuf),
+    FastForward(bool),
+    /// Load a PNG sprite sheet saved by [`EmulationEvent::ExportChr`] into CHR-RAM.
+    ImportChr(PathBuf),
     InstantRewind,
     Joypad((Player, JoypadBtn, ElementState)),
+    /// Play back a replay alongside the live game, drawn over it without applying its inputs.
+    LoadGhostPath(PathBuf),
     LoadReplay((String, ReplayData)),
     LoadReplayPath(PathBuf),
     LoadRom((String, RomData)),
     LoadRomPath(PathBuf),
+    /// Load the ROM held back by [`RendererEvent::RomHeaderIssues`], repairing its header if
+    /// set.
+    LoadHeldRom(bool),
     LoadState(u8),
+    /// Load a state from the quick save state ring, starting with the newest and cycling to older
+    /// states when repeated.
+    LoadStateRing,
+    /// Ignore game writes to a palette entry, or allow them again.
+    LockPalette((u16, bool)),
+    /// Start or stop recording an input macro into the given slot.
+    MacroRecord(u8),
+    /// Play back the input macro recorded in the given slot.
+    MacroPlay(u8),
+    /// Start or stop profiling game code, periodically sending a report for the profiler.
+    Profile(bool),
     RunState(RunState),
     ReplayRecord(bool),
+    /// Start recording a replay, saving it to the given file when stopped.
+    ReplayRecordPath(PathBuf),
     Reset(ResetKind),
     RequestFrame,
+    /// Resolve a save sync conflict by keeping one copy of the file.
+    ResolveSyncConflict((SyncEntry, SyncChoice)),
     Rewinding(bool),
     SaveState(u8),
+    /// Save a state to the oldest entry of the quick save state ring.
+    SaveStateRing,
+    /// Scan a barcode with the barcode reader of the loaded ROM.
+    ScanBarcode(String),
     ShowFrameStats(bool),
+    /// Send mapper state after each frame that changed it, for the mapper inspector.
+    ShowMapperState(bool),
+    /// Draw only some PPU layers, for debugging.
+    ShowPpuLayers(PpuLayers),
     Screenshot,
+    /// Save the given number of frames as screenshots, starting with the next frame, or stop
+    /// saving them if `None`.
+    ScreenshotBurst(Option<u32>),
+    /// Restore the state from before the most recent load state.
+    UndoLoadState,
+    /// Start running a copy of the current ROM in the given viewport.
+    StartRace((ViewportId, RaceInputs)),
+    StopRace,
+    UnloadGhost,
     UnloadRom,
+    /// Press or release the Vaus paddle fire button.
+    VausButton(bool),
+    /// Turn the Vaus paddle knob by a relative amount.
+    VausMove(i32),
+    /// Write a byte of sprite OAM, for editing sprites from the PPU viewer.
+    WriteOam((u8, u8)),
+    /// Write a palette entry, for editing colors from the PPU viewer.
+    WritePalette((u16, u8)),
     ZapperAim((u32, u32)),
     ZapperTrigger,
 }
@@ -217,7 +472,25 @@ pub enum RendererEvent {
     FrameStats(FrameStats),
     ShowMenubar(bool),
     ToggleFullscreen,
+    GhostLoaded,
+    GhostUnloaded,
+    RaceStopped,
+    /// Input macro slot being recorded, if any.
+    MacroRecording(Option<u8>),
+    /// Mapper state, recent mapper register writes and mapper IRQs from the last frame, oldest
+    /// first.
+    MapperState((MapperDebugState, Vec<MapperWrite>, Vec<MapperIrqEvent>)),
+    /// Play statistics for every ROM played.
+    Playtime(Playtime),
+    /// Game code profile gathered since profiling started.
+    ProfileReport(ProfileReport),
+    RamWatchValues(Vec<u16>),
     ReplayLoaded,
+    /// Symbols loaded from a symbol file, to name routines in the profiler.
+    SymbolsLoaded(Symbols),
+    /// Lock and hide the cursor over the main window, sending relative mouse motion to the Vaus
+    /// paddle or Zapper until released with Escape.
+    CaptureMouse(bool),
     ResizeTexture,
     ResizeWindow,
     ResourcesReady,
@@ -225,8 +498,20 @@ pub enum RendererEvent {
         viewport_id: ViewportId,
         when: Instant,
     },
+    /// ROMs found in the watched ROM directory.
+    RomLibrary(Vec<LibraryRom>),
+    /// Number of ROMs hashed out of the total number being hashed in the ROM library.
+    RomLibraryProgress((usize, usize)),
     RomLoaded(LoadedRom),
     RomUnloaded,
+    StateSaved(u8),
+    /// Save files changed both locally and remotely since they were last synced.
+    SyncConflicts(Vec<SyncConflict>),
+    /// ROM wasn't loaded because its header has problems the user can choose to repair. The ROM
+    /// is held back until [`EmulationEvent::LoadHeldRom`].
+    RomHeaderIssues((String, Vec<HeaderIssue>)),
+    /// ROM failed to load because its mapper number isn't implemented.
+    UnsupportedMapper((String, u16)),
     Menu(Menu),
 }
 
@@ -240,10 +525,25 @@ impl From<RendererEvent> for NesEvent {
 #[must_use]
 pub enum UiEvent {
     Error(String),
+    EmulationPanic {
+        message: String,
+        backtrace: String,
+    },
     Message((MessageType, String)),
-    UpdateAvailable(String),
+    /// Releases newer than the current version, newest first.
+    UpdateAvailable(Vec<Release>),
     LoadRomDialog,
     LoadReplayDialog,
+    LoadGhostDialog,
+    LoadSymbolsDialog,
+    LoadKeybindsDialog,
+    SaveKeybindsDialog,
+    ExportChrDialog,
+    ImportChrDialog,
+    ExportFrameStatsDialog,
+    ExportInputScriptDialog,
+    ExportMapperReportDialog,
+    ExportPaletteDialog,
     FileDialogCancelled,
     Terminate,
 }
@@ -439,19 +739,27 @@ impl ApplicationHandler<NesEvent> for Nes {
 }
 
 impl ApplicationHandler<NesEvent> for Running {
-    fn user_event(&mut self, _event_loop: &ActiveEventLoop, mut event: NesEvent) {
+    fn user_event(&mut self, event_loop: &ActiveEventLoop, mut event: NesEvent) {
+        if !self.kiosk_allows(&event) {
+            return;
+        }
+
         match event {
             NesEvent::Config(ref event) => {
                 let Config {
+                    version: _,
                     deck,
                     emulation,
                     audio,
                     renderer,
                     input,
+                    paths,
+                    profile: _,
+                    startup: _,
                 } = &mut self.cfg;
                 match event {
                     ConfigEvent::ActionBindings(bindings) => {
-                        input.action_bindings.clone_from(bindings);
+                        input.set_action_bindings(bindings.clone());
                         self.input_bindings = InputBindings::from_input_config(input);
                     }
                     ConfigEvent::ActionBindingSet((action, set_input, binding)) => {
@@ -464,8 +772,10 @@ impl ApplicationHandler<NesEvent> for Running {
                     }
                     ConfigEvent::AlwaysOnTop(always_on_top) => {
                         renderer.always_on_top = *always_on_top;
-                        self.renderer
-                            .set_always_on_top(self.cfg.renderer.always_on_top);
+                        self.renderer.set_always_on_top(&self.cfg.renderer);
+                    }
+                    ConfigEvent::AttractModeMinutes(minutes) => {
+                        renderer.attract_mode_minutes = *minutes;
                     }
                     ConfigEvent::ApuChannelEnabled((channel, enabled)) => {
                         deck.channels_enabled[*channel as usize] = *enabled;
@@ -473,22 +783,65 @@ impl ApplicationHandler<NesEvent> for Running {
                     ConfigEvent::ApuChannelsEnabled(enabled) => {
                         deck.channels_enabled = *enabled;
                     }
+                    ConfigEvent::AudioBackend(backend) => audio.backend = *backend,
                     ConfigEvent::AudioBuffer(buffer_size) => {
                         audio.buffer_size = *buffer_size;
                     }
                     ConfigEvent::AudioEnabled(enabled) => audio.enabled = *enabled,
                     ConfigEvent::AudioLatency(latency) => audio.latency = *latency,
+                    ConfigEvent::AudioRecordingLimits(limits) => {
+                        audio.recording_limits = *limits;
+                    }
                     ConfigEvent::AutoLoad(enabled) => emulation.auto_load = *enabled,
+                    ConfigEvent::AutoPeripherals(enabled) => deck.auto_peripherals = *enabled,
                     ConfigEvent::AutoSave(enabled) => emulation.auto_save = *enabled,
                     ConfigEvent::AutoSaveInterval(interval) => {
                         emulation.auto_save_interval = *interval;
                     }
+                    ConfigEvent::ColorBlindMode(mode) => renderer.color_blind_mode = *mode,
+                    ConfigEvent::CompactMode(compact) => {
+                        renderer.compact_mode = *compact;
+                        self.renderer.set_always_on_top(&self.cfg.renderer);
+                    }
+                    ConfigEvent::ConfigProfile(name) => self.switch_config_profile(name),
+                    ConfigEvent::ConfigProfileRemove(name) => self.remove_config_profile(name),
                     ConfigEvent::ConcurrentDpad(enabled) => deck.concurrent_dpad = *enabled,
                     ConfigEvent::CycleAccurate(enabled) => deck.cycle_accurate = *enabled,
+                    ConfigEvent::DmcClickReduction((name, enabled)) => {
+                        if *enabled {
+                            audio.dmc_click_reduction.insert(name.clone());
+                        } else {
+                            audio.dmc_click_reduction.remove(name);
+                        }
+                    }
+                    ConfigEvent::DmcClickReductionRoms(names) => {
+                        audio.dmc_click_reduction = names.clone();
+                    }
+                    ConfigEvent::AccentColor(color) => renderer.accent_color = *color,
                     ConfigEvent::DarkTheme(enabled) => renderer.dark_theme = *enabled,
                     ConfigEvent::EmbedViewports(embed) => renderer.embed_viewports = *embed,
+                    ConfigEvent::ExclusiveDevices(exclusive) => {
+                        input.exclusive_devices = *exclusive;
+                    }
+                    ConfigEvent::FastBoot(enabled) => deck.fast_boot = *enabled,
+                    ConfigEvent::FastForwardFrameSkip(frames) => {
+                        emulation.fast_forward_frame_skip = *frames;
+                    }
+                    ConfigEvent::FastForwardSpeed(speed) => {
+                        emulation.fast_forward_speed = *speed;
+                    }
+                    ConfigEvent::FastForwardToggle(toggle) => {
+                        emulation.fast_forward_toggle = *toggle;
+                    }
+                    ConfigEvent::FastForwardUncapped(uncapped) => {
+                        emulation.fast_forward_uncapped = *uncapped;
+                    }
+                    ConfigEvent::FontSize(size) => renderer.font_size = *size,
                     ConfigEvent::FourPlayer(four_player) => deck.four_player = *four_player,
                     ConfigEvent::Fullscreen(fullscreen) => renderer.fullscreen = *fullscreen,
+                    ConfigEvent::FullscreenTarget(target) => {
+                        renderer.fullscreen_target = target.clone();
+                    }
                     ConfigEvent::GamepadAssign((player, uuid)) => {
                         input.assign_gamepad(*player, *uuid);
                         if let Some(name) = self.gamepads.gamepad_name_by_uuid(uuid) {
@@ -511,6 +864,18 @@ impl ApplicationHandler<NesEvent> for Running {
                     ConfigEvent::GamepadAssignments(assignments) => {
                         input.gamepad_assignments = *assignments;
                     }
+                    ConfigEvent::KeyboardAssign((player, device_id)) => {
+                        input.assign_keyboard(*player, *device_id);
+                        if let Some(index) = self.gamepads.keyboard_index(device_id) {
+                            self.tx.event(UiEvent::Message((
+                                MessageType::Info,
+                                format!("Assigned Keyboard {} to player {player:?}.", index + 1),
+                            )));
+                        }
+                    }
+                    ConfigEvent::KeyboardUnassign(player) => {
+                        input.unassign_keyboard(*player);
+                    }
                     ConfigEvent::GenieCodeAdded(genie_code) => {
                         deck.genie_codes.push(genie_code.clone());
                     }
@@ -519,10 +884,87 @@ impl ApplicationHandler<NesEvent> for Running {
                         deck.genie_codes.retain(|genie| genie.code() != code);
                     }
                     ConfigEvent::HideOverscan(hide) => renderer.hide_overscan = *hide,
+                    ConfigEvent::FlipHorizontal(flip) => renderer.flip_horizontal = *flip,
+                    ConfigEvent::Scanlines(scanlines) => renderer.scanlines = *scanlines,
+                    ConfigEvent::BlackFrameInsertion(enabled) => {
+                        renderer.black_frame_insertion = *enabled;
+                    }
+                    ConfigEvent::Rotation(rotation) => renderer.rotation = *rotation,
+                    ConfigEvent::HighContrast(enabled) => renderer.high_contrast = *enabled,
+                    ConfigEvent::MapperStats(enabled) => renderer.mapper_stats = *enabled,
+                    ConfigEvent::Theme(theme) => renderer.theme = *theme,
+                    ConfigEvent::UpdateCheck(update_check) => renderer.update_check = *update_check,
+                    ConfigEvent::KeybindPreset(preset) => {
+                        match input.load_preset(*preset) {
+                            Ok(()) => self.tx.event(UiEvent::Message((
+                                MessageType::Info,
+                                format!("Loaded keybind preset `{}`.", preset.as_ref()),
+                            ))),
+                            Err(err) => {
+                                error!("failed to load keybind preset: {err:?}");
+                                self.tx.event(UiEvent::Error(format!(
+                                    "Failed to load keybind preset `{}`.",
+                                    preset.as_ref()
+                                )));
+                            }
+                        }
+                        self.input_bindings = InputBindings::from_input_config(input);
+                    }
+                    ConfigEvent::KeybindProfile(name) => {
+                        match input.switch_profile(name) {
+                            Ok(()) => self.tx.event(UiEvent::Message((
+                                MessageType::Info,
+                                format!("Switched to keybind profile `{name}`."),
+                            ))),
+                            Err(err) => {
+                                error!("failed to switch keybind profile: {err:?}");
+                                self.tx.event(UiEvent::Error(format!(
+                                    "Failed to switch to keybind profile `{name}`: {err}."
+                                )));
+                            }
+                        }
+                        self.input_bindings = InputBindings::from_input_config(input);
+                    }
+                    ConfigEvent::KeybindProfileImport(profile) => {
+                        match input.import_profile(profile.clone()) {
+                            Ok(()) => self.tx.event(UiEvent::Message((
+                                MessageType::Info,
+                                format!("Imported keybind profile `{}`.", profile.name),
+                            ))),
+                            Err(err) => {
+                                error!("failed to import keybind profile: {err:?}");
+                                self.tx.event(UiEvent::Error(format!(
+                                    "Failed to import keybind profile `{}`: {err}.",
+                                    profile.name
+                                )));
+                            }
+                        }
+                        self.input_bindings = InputBindings::from_input_config(input);
+                    }
+                    ConfigEvent::KeybindProfileRemove(name) => {
+                        if let Err(err) = input.remove_profile(name) {
+                            error!("failed to remove keybind profile: {err:?}");
+                        }
+                        self.input_bindings = InputBindings::from_input_config(input);
+                    }
+                    ConfigEvent::LastUpdateCheck(timestamp) => {
+                        renderer.last_update_check = *timestamp;
+                    }
                     ConfigEvent::MapperRevisions(revs) => deck.mapper_revisions = *revs,
+                    ConfigEvent::Paths(new_paths) => *paths = new_paths.clone(),
+                    ConfigEvent::PresentMode(mode) => renderer.present_mode = *mode,
                     ConfigEvent::RamState(ram_state) => deck.ram_state = *ram_state,
+                    ConfigEvent::RamWatches(watches) => renderer.ram_watches = watches.clone(),
+                    ConfigEvent::RecentRomPinned((path, pinned)) => {
+                        renderer.recent_roms.set_pinned(path, *pinned);
+                    }
+                    ConfigEvent::RecentRomRemoved(path) => renderer.recent_roms.remove(path),
                     ConfigEvent::RecentRomsClear => renderer.recent_roms.clear(),
+                    ConfigEvent::RecentRomsRemoveMissing => renderer.recent_roms.remove_missing(),
                     ConfigEvent::Region(region) => deck.region = *region,
+                    ConfigEvent::ReplayHashInterval(interval) => {
+                        emulation.replay_hash_interval = *interval;
+                    }
                     ConfigEvent::RewindEnabled(enabled) => emulation.rewind = *enabled,
                     ConfigEvent::RewindInterval(interval) => {
                         emulation.rewind_interval = *interval;
@@ -531,14 +973,36 @@ impl ApplicationHandler<NesEvent> for Running {
                         emulation.rewind_seconds = *seconds;
                     }
                     ConfigEvent::RunAhead(run_ahead) => emulation.run_ahead = *run_ahead,
-                    ConfigEvent::SaveSlot(slot) => emulation.save_slot = *slot,
+                    ConfigEvent::SaveRingLen(len) => emulation.save_ring_len = *len,
+                    ConfigEvent::SaveSlot(slot) => {
+                        emulation.set_save_slot(*slot);
+                    }
                     ConfigEvent::Scale(scale) => renderer.scale = *scale,
+                    ConfigEvent::ScreenshotBurstFrames(frames) => {
+                        emulation.screenshot_burst_frames = *frames;
+                    }
                     ConfigEvent::Shader(shader) => renderer.shader = *shader,
+                    ConfigEvent::UpscaleFilter(filter) => renderer.upscale_filter = *filter,
+                    ConfigEvent::TextureFilter(filter) => renderer.texture_filter = *filter,
+                    ConfigEvent::Transition(transition) => renderer.transition = *transition,
                     ConfigEvent::ShowMenubar(show) => renderer.show_menubar = *show,
                     ConfigEvent::ShowMessages(show) => renderer.show_messages = *show,
+                    ConfigEvent::AnnounceMessages(announce) => {
+                        renderer.announce_messages = *announce;
+                    }
                     ConfigEvent::Speed(speed) => emulation.speed = *speed,
+                    ConfigEvent::SpriteLimit(enabled) => deck.sprite_limit = *enabled,
+                    ConfigEvent::Stereo(stereo) => deck.stereo = *stereo,
+                    ConfigEvent::SyncToDisplay(sync) => emulation.sync_to_display = *sync,
                     ConfigEvent::VideoFilter(filter) => deck.filter = *filter,
+                    ConfigEvent::VausConnected(connected) => deck.vaus = *connected,
+                    ConfigEvent::WatchRomsPath(watch) => renderer.watch_roms_path = *watch,
+                    ConfigEvent::ZapperConfig(config) => deck.zapper_config = *config,
                     ConfigEvent::ZapperConnected(connected) => deck.zapper = *connected,
+                    ConfigEvent::Zoom(zoom) => renderer.zoom = *zoom,
+                }
+                if matches!(event, ConfigEvent::WatchRomsPath(_)) {
+                    self.update_rom_watcher();
                 }
 
                 self.renderer.prepare(&self.gamepads, &self.cfg);
@@ -553,7 +1017,27 @@ impl ApplicationHandler<NesEvent> for Running {
                     );
                 }
             }
+            NesEvent::Renderer(RendererEvent::Menu(Menu::BigPicture)) => {
+                // Pause while Big Picture mode is open, resuming on close unless manually paused
+                if self.renderer.rom_loaded() {
+                    if !self.renderer.big_picture_open() && !self.run_state.paused() {
+                        self.run_state = RunState::Paused;
+                        self.event(EmulationEvent::RunState(self.run_state));
+                    } else if self.renderer.big_picture_open() && self.run_state.auto_paused() {
+                        self.run_state = RunState::Running;
+                        self.event(EmulationEvent::RunState(self.run_state));
+                    }
+                }
+            }
+            NesEvent::Renderer(RendererEvent::CaptureMouse(captured)) => {
+                self.set_mouse_captured(event_loop, captured);
+            }
+            NesEvent::Renderer(RendererEvent::RomUnloaded) => {
+                self.set_mouse_captured(event_loop, false);
+            }
             NesEvent::Ui(ref event) => self.on_ui_event(event),
+            // Emulation can pause itself, e.g. on a breakpoint
+            NesEvent::Emulation(EmulationEvent::RunState(mode)) => self.run_state = mode,
             _ => (),
         }
 
@@ -572,6 +1056,44 @@ impl ApplicationHandler<NesEvent> for Running {
         window_id: WindowId,
         event: WindowEvent,
     ) {
+        if self.renderer.mouse_captured() {
+            match &event {
+                WindowEvent::KeyboardInput { event, .. }
+                    if event.physical_key == KeyCode::Escape
+                        && event.state == ElementState::Pressed =>
+                {
+                    // Don't let Escape reach any other bindings while releasing the mouse
+                    self.set_mouse_captured(event_loop, false);
+                    return;
+                }
+                WindowEvent::Focused(false) => self.set_mouse_captured(event_loop, false),
+                _ => (),
+            }
+        }
+
+        if self.cfg.startup.kiosk()
+            && event == WindowEvent::CloseRequested
+            && Some(window_id) == self.renderer.root_window_id()
+        {
+            // Only the admin chord can quit in kiosk mode
+            return;
+        }
+
+        let user_input = match &event {
+            WindowEvent::KeyboardInput {
+                event,
+                is_synthetic,
+                ..
+            } => !is_synthetic && event.state == ElementState::Pressed,
+            WindowEvent::MouseInput { state, .. } => *state == ElementState::Pressed,
+            WindowEvent::MouseWheel { .. } | WindowEvent::Touch(_) => true,
+            _ => false,
+        };
+        if user_input && self.on_user_activity() {
+            // Any input while in attract mode only returns to the ROM library
+            return;
+        }
+
         let res = self.renderer.on_window_event(window_id, &event);
         if res.repaint && event != WindowEvent::RedrawRequested {
             self.repaint_times.insert(window_id, Instant::now());
@@ -595,6 +1117,23 @@ impl ApplicationHandler<NesEvent> for Running {
                 WindowEvent::Resized(_) => {
                     if Some(window_id) == self.renderer.root_window_id() {
                         self.cfg.renderer.fullscreen = self.renderer.fullscreen();
+                        self.cfg.renderer.window_geometry = self
+                            .renderer
+                            .window_geometry(self.cfg.renderer.window_geometry.as_ref());
+                        self.event(EmulationEvent::DisplayRefreshRate(
+                            self.renderer.refresh_rate(),
+                        ));
+                    }
+                }
+                WindowEvent::Moved(_) => {
+                    // Window may have moved to a monitor with a different refresh rate
+                    if Some(window_id) == self.renderer.root_window_id() {
+                        self.cfg.renderer.window_geometry = self
+                            .renderer
+                            .window_geometry(self.cfg.renderer.window_geometry.as_ref());
+                        self.event(EmulationEvent::DisplayRefreshRate(
+                            self.renderer.refresh_rate(),
+                        ));
                     }
                 }
                 WindowEvent::Focused(focused) => {
@@ -642,10 +1181,11 @@ impl ApplicationHandler<NesEvent> for Running {
                     }
                 }
                 WindowEvent::KeyboardInput {
+                    device_id,
                     event,
                     is_synthetic,
-                    ..
                 } => {
+                    self.gamepads.connect_keyboard(device_id);
                     // Winit generates fake "synthetic" KeyboardInput events when the focus
                     // is changed to the window, or away from it. Synthetic key presses
                     // represent no real key presses and should be ignored.
@@ -654,6 +1194,7 @@ impl ApplicationHandler<NesEvent> for Running {
                         if let PhysicalKey::Code(key) = event.physical_key {
                             self.on_input(
                                 window_id,
+                                InputDevice::Keyboard(device_id),
                                 Input::Key(key, self.modifiers.state()),
                                 event.state,
                                 event.repeat,
@@ -665,7 +1206,13 @@ impl ApplicationHandler<NesEvent> for Running {
                     self.modifiers = modifiers;
                 }
                 WindowEvent::MouseInput { button, state, .. } => {
-                    self.on_input(window_id, Input::Mouse(button), state, false);
+                    self.on_input(
+                        window_id,
+                        InputDevice::Mouse,
+                        Input::Mouse(button),
+                        state,
+                        false,
+                    );
                 }
                 WindowEvent::DroppedFile(path) => {
                     if Some(window_id) == self.renderer.root_window_id() {
@@ -693,6 +1240,16 @@ impl ApplicationHandler<NesEvent> for Running {
         puffin::profile_function!();
 
         self.gamepads.update_events();
+        let gamepad_input = self.gamepads.events().any(|event| match event.event {
+            gilrs::EventType::ButtonPressed(..) => true,
+            gilrs::EventType::AxisChanged(_, value, _) => Gamepads::axis_state(value).0.is_some(),
+            _ => false,
+        });
+        if gamepad_input && self.on_user_activity() {
+            self.gamepads.clear_events();
+        }
+        self.check_attract_mode();
+
         if let Some(window_id) = self.renderer.root_window_id() {
             let res = self.renderer.on_gamepad_update(&self.gamepads);
             if res.repaint {
@@ -707,8 +1264,8 @@ impl ApplicationHandler<NesEvent> for Running {
                     self.repaint_times.insert(window_id, Instant::now());
                 }
             }
-            // Always repaint when single threaded
-            if !self.cfg.emulation.threaded {
+            // Always repaint when single threaded, unless there's nothing to emulate
+            if self.emulation.wants_redraw() {
                 self.repaint_times.insert(window_id, Instant::now());
             }
         }
@@ -729,6 +1286,9 @@ impl ApplicationHandler<NesEvent> for Running {
         if let Err(err) = self.renderer.save(&self.cfg) {
             error!("failed to save rendererer state: {err:?}");
         }
+        if self.cfg.startup.save_on_exit && self.renderer.rom_loaded() {
+            self.event(EmulationEvent::SaveState(self.cfg.emulation.save_slot));
+        }
         self.emulation.terminate();
         self.renderer.destroy();
 
@@ -748,6 +1308,187 @@ impl ApplicationHandler<NesEvent> for Running {
 }
 
 impl Running {
+    /// Admin chord to quit in kiosk mode, where all other quit shortcuts are disabled.
+    pub const KIOSK_QUIT: Input = Input::Key(
+        KeyCode::KeyQ,
+        ModifiersState::CONTROL
+            .union(ModifiersState::ALT)
+            .union(ModifiersState::SHIFT),
+    );
+
+    /// How often to wake up and poll for gamepad events when idle.
+    const IDLE_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+    /// Whether there's nothing to emulate, allowing the event loop to sleep longer between
+    /// polling for gamepad events.
+    fn is_idle(&self) -> bool {
+        !self.renderer.rom_loaded() || self.run_state.paused()
+    }
+
+    /// Whether an event is allowed in kiosk mode, warning when it would load a ROM from outside
+    /// the kiosk directory. ROM data without a path, like the bundled homebrew ROMs, can't be
+    /// checked and is always rejected.
+    fn kiosk_allows(&mut self, event: &NesEvent) -> bool {
+        let Some(kiosk_dir) = &self.cfg.startup.kiosk_dir else {
+            return true;
+        };
+        let allowed = match event {
+            NesEvent::Emulation(EmulationEvent::LoadRomPath(path)) => {
+                self.cfg.startup.rom_allowed(path)
+            }
+            NesEvent::Emulation(EmulationEvent::LoadRom(_)) => false,
+            // Only ROMs that were already allowed to load are held back for header issues
+            NesEvent::Emulation(EmulationEvent::LoadHeldRom(_)) => true,
+            _ => true,
+        };
+        if !allowed {
+            self.renderer.add_message(
+                MessageType::Warn,
+                format!(
+                    "Only ROMs in `{}` can be loaded in kiosk mode.",
+                    kiosk_dir.display()
+                ),
+            );
+        }
+        allowed
+    }
+
+    /// Record user input for attract mode, returning `true` if attract mode was stopped and the
+    /// input should be ignored.
+    fn on_user_activity(&mut self) -> bool {
+        let stopped = self.attract.on_input();
+        if stopped {
+            self.event(EmulationEvent::UnloadRom);
+            self.event(EmulationEvent::AttractMode(false));
+        }
+        stopped
+    }
+
+    /// Start attract mode if idle on the ROM library screen for long enough.
+    fn check_attract_mode(&mut self) {
+        if !self.attract.should_start(
+            self.cfg.renderer.attract_mode_minutes,
+            self.renderer.rom_loaded(),
+        ) {
+            return;
+        }
+        match AttractMode::pick(&self.cfg.renderer.recent_roms, &self.cfg.paths.replay_dir()) {
+            Some((rom, replay)) => {
+                self.attract.start(true);
+                self.event(EmulationEvent::AttractMode(true));
+                self.event(EmulationEvent::LoadRomPath(rom));
+                if let Some(replay) = replay {
+                    self.event(EmulationEvent::LoadReplayPath(replay));
+                }
+            }
+            None => self.attract.start(false),
+        }
+    }
+
+    /// Save the current configuration to its profile and switch to the named profile, replacing
+    /// all settings with it. New profiles start from the default profile, the same as
+    /// `--profile`.
+    fn switch_config_profile(&mut self, name: &str) {
+        let profile = match Config::profile_name(name) {
+            Ok(profile) => profile,
+            Err(err) => {
+                self.tx.event(UiEvent::Error(format!(
+                    "Failed to switch config profile: {err}."
+                )));
+                return;
+            }
+        };
+        if profile == self.cfg.profile {
+            return;
+        }
+        if let Err(err) = self.cfg.save() {
+            error!("failed to save config profile: {err:?}");
+            self.tx.event(UiEvent::Error(format!(
+                "Failed to save config profile `{}`.",
+                self.cfg.profile_label()
+            )));
+            return;
+        }
+
+        let created = !fs::exists(&Config::profile_path(profile.as_deref()));
+        let mut cfg = Config::load_profile(profile);
+        for warning in std::mem::take(&mut cfg.startup.config_warnings) {
+            self.renderer.add_message(MessageType::Warn, warning);
+        }
+        // Replace everything at once so settings that can't be changed by an event, like recent
+        // ROMs, aren't carried over from the previous profile. Startup options only come from
+        // the command line.
+        self.cfg = Config {
+            startup: std::mem::take(&mut self.cfg.startup),
+            ..cfg
+        };
+        self.input_bindings = InputBindings::from_input_config(&self.cfg.input);
+        for event in ConfigEvent::from_config(self.cfg.clone()) {
+            self.tx.event(event);
+        }
+        self.update_rom_watcher();
+
+        let label = self.cfg.profile_label().to_string();
+        if !created {
+            self.renderer.add_message(
+                MessageType::Info,
+                format!("Switched to config profile `{label}`."),
+            );
+            return;
+        }
+        match self.cfg.save() {
+            Ok(()) => self.renderer.add_message(
+                MessageType::Info,
+                format!("Created config profile `{label}`."),
+            ),
+            Err(err) => {
+                error!("failed to save config profile: {err:?}");
+                self.tx.event(UiEvent::Error(format!(
+                    "Failed to create config profile `{label}`."
+                )));
+            }
+        }
+    }
+
+    /// Delete the named configuration profile, switching back to the default profile if it was
+    /// active.
+    fn remove_config_profile(&mut self, name: &str) {
+        if self.cfg.profile.as_deref() == Some(name) {
+            self.switch_config_profile(Config::DEFAULT_PROFILE);
+        }
+        match Config::remove_profile(name) {
+            Ok(()) => self.renderer.add_message(
+                MessageType::Info,
+                format!("Removed config profile `{name}`."),
+            ),
+            Err(err) => {
+                error!("failed to remove config profile: {err:?}");
+                self.tx.event(UiEvent::Error(format!(
+                    "Failed to remove config profile `{name}`."
+                )));
+            }
+        }
+    }
+
+    /// Start watching the ROM directory for the ROM library, or stop if watching is disabled or
+    /// the ROM directory no longer exists.
+    pub(crate) fn update_rom_watcher(&mut self) {
+        let dir = self.cfg.renderer.roms_path.as_ref().filter(|path| {
+            feature!(Filesystem) && self.cfg.renderer.watch_roms_path && path.is_dir()
+        });
+        if self.rom_watcher.as_ref().map(RomWatcher::dir) == dir.map(PathBuf::as_path) {
+            return;
+        }
+        self.rom_watcher = None;
+        match dir {
+            Some(dir) => match RomWatcher::start(dir.clone(), &self.cfg.paths, self.tx.clone()) {
+                Ok(watcher) => self.rom_watcher = Some(watcher),
+                Err(err) => error!("failed to watch ROM directory: {err:?}"),
+            },
+            None => self.event(RendererEvent::RomLibrary(Vec::new())),
+        }
+    }
+
     pub fn update_repaint_times(&mut self, event_loop: &ActiveEventLoop) {
         let mut next_repaint_time = self.repaint_times.values().min().copied();
         self.repaint_times.retain(|window_id, when| {
@@ -769,6 +1510,7 @@ impl Running {
 
         event_loop.set_control_flow(ControlFlow::WaitUntil(match next_repaint_time {
             Some(next_repaint_time) => next_repaint_time,
+            None if self.is_idle() => Instant::now() + Self::IDLE_POLL_INTERVAL,
             None => Instant::now() + Duration::from_millis(16),
         }));
     }
@@ -780,6 +1522,9 @@ impl Running {
         match event {
             UiEvent::Message((ty, msg)) => self.renderer.add_message(*ty, msg),
             UiEvent::Error(err) => self.renderer.on_error(anyhow!(err.clone())),
+            UiEvent::EmulationPanic { message, backtrace } => {
+                self.renderer.on_emulation_panic(message, backtrace);
+            }
             UiEvent::LoadRomDialog => {
                 match open_file_dialog(
                     "Load ROM",
@@ -798,12 +1543,50 @@ impl Running {
                     }
                 }
             }
+            UiEvent::LoadGhostDialog => {
+                match open_file_dialog(
+                    "Load Ghost Replay",
+                    "Replay Recording",
+                    &["replay"],
+                    Some(self.cfg.paths.replay_dir()),
+                ) {
+                    Ok(maybe_path) => {
+                        if let Some(path) = maybe_path {
+                            self.event(EmulationEvent::LoadGhostPath(path));
+                        }
+                    }
+                    Err(err) => {
+                        error!("failed to open ghost replay dialog: {err:?}");
+                        self.event(UiEvent::Error(
+                            "failed to open ghost replay dialog".to_string(),
+                        ));
+                    }
+                }
+            }
+            UiEvent::LoadSymbolsDialog => {
+                match open_file_dialog(
+                    "Load Symbols",
+                    "Symbol Files",
+                    &["nl", "mlb"],
+                    self.cfg.renderer.roms_path.as_ref(),
+                ) {
+                    Ok(maybe_path) => {
+                        if let Some(path) = maybe_path {
+                            self.load_symbols(&path);
+                        }
+                    }
+                    Err(err) => {
+                        error!("failed to open symbols dialog: {err:?}");
+                        self.event(UiEvent::Error("failed to open symbols dialog".to_string()));
+                    }
+                }
+            }
             UiEvent::LoadReplayDialog => {
                 match open_file_dialog(
                     "Load Replay",
                     "Replay Recording",
-                    &["replay"],
-                    Some(Config::default_data_dir()),
+                    &["replay", replay::INPUT_SCRIPT_EXTENSION],
+                    Some(self.cfg.paths.replay_dir()),
                 ) {
                     Ok(maybe_path) => {
                         if let Some(path) = maybe_path {
@@ -816,6 +1599,205 @@ impl Running {
                     }
                 }
             }
+            UiEvent::LoadKeybindsDialog => {
+                match open_file_dialog(
+                    "Import Keybinds",
+                    "Keybind Profile",
+                    &[KeybindProfile::EXTENSION],
+                    Some(Config::default_config_dir().join(Config::KEYBINDS_DIR)),
+                ) {
+                    Ok(maybe_path) => {
+                        if let Some(path) = maybe_path {
+                            match KeybindProfile::load(&path) {
+                                Ok(profile) => {
+                                    self.tx.event(ConfigEvent::KeybindProfileImport(profile));
+                                }
+                                Err(err) => {
+                                    error!("failed to import keybinds: {err:?}");
+                                    self.event(UiEvent::Error(format!(
+                                        "failed to import keybinds from {path:?}"
+                                    )));
+                                }
+                            }
+                        }
+                    }
+                    Err(err) => {
+                        error!("failed to open keybinds dialog: {err:?}");
+                        self.event(UiEvent::Error("failed to open keybinds dialog".to_string()));
+                    }
+                }
+            }
+            UiEvent::SaveKeybindsDialog => {
+                let profile = self.cfg.input.active_profile();
+                match save_file_dialog(
+                    "Export Keybinds",
+                    "Keybind Profile",
+                    &[KeybindProfile::EXTENSION],
+                    format!("{}.{}", profile.name, KeybindProfile::EXTENSION),
+                    None::<PathBuf>,
+                ) {
+                    Ok(maybe_path) => {
+                        if let Some(path) = maybe_path {
+                            match profile.save(&path) {
+                                Ok(()) => self.renderer.add_message(
+                                    MessageType::Info,
+                                    format!("Exported keybind profile to {path:?}."),
+                                ),
+                                Err(err) => {
+                                    error!("failed to export keybinds: {err:?}");
+                                    self.event(UiEvent::Error(format!(
+                                        "failed to export keybinds to {path:?}"
+                                    )));
+                                }
+                            }
+                        }
+                    }
+                    Err(err) => {
+                        error!("failed to open keybinds dialog: {err:?}");
+                        self.event(UiEvent::Error("failed to open keybinds dialog".to_string()));
+                    }
+                }
+            }
+            UiEvent::ExportFrameStatsDialog => {
+                match save_file_dialog(
+                    "Export Frame Stats",
+                    "Frame Stats",
+                    &["csv", "json"],
+                    format!(
+                        "frame_stats_{}.csv",
+                        Local::now().format("%Y-%m-%d_%H.%M.%S")
+                    ),
+                    None::<PathBuf>,
+                ) {
+                    Ok(maybe_path) => {
+                        if let Some(path) = maybe_path {
+                            self.event(EmulationEvent::ExportFrameStats(path));
+                        }
+                    }
+                    Err(err) => {
+                        error!("failed to open frame stats dialog: {err:?}");
+                        self.event(UiEvent::Error(
+                            "failed to open frame stats dialog".to_string(),
+                        ));
+                    }
+                }
+            }
+            UiEvent::ExportInputScriptDialog => {
+                match open_file_dialog(
+                    "Export Replay as Input Script",
+                    "Replay Recording",
+                    &["replay"],
+                    Some(self.cfg.paths.replay_dir()),
+                ) {
+                    Ok(maybe_path) => {
+                        if let Some(path) = maybe_path {
+                            self.event(EmulationEvent::ExportInputScript(path));
+                        }
+                    }
+                    Err(err) => {
+                        error!("failed to open input script dialog: {err:?}");
+                        self.event(UiEvent::Error(
+                            "failed to open input script dialog".to_string(),
+                        ));
+                    }
+                }
+            }
+            UiEvent::ExportMapperReportDialog => {
+                match save_file_dialog(
+                    "Export Mapper Report",
+                    "Mapper Report",
+                    &["txt"],
+                    "unsupported_mappers.txt",
+                    None::<PathBuf>,
+                ) {
+                    Ok(maybe_path) => {
+                        if let Some(path) = maybe_path {
+                            let res = MapperStats::load(&self.cfg.paths).and_then(|stats| {
+                                std::fs::write(&path, stats.report())
+                                    .with_context(|| format!("failed to write {path:?}"))
+                            });
+                            match res {
+                                Ok(()) => self.renderer.add_message(
+                                    MessageType::Info,
+                                    format!("Exported mapper report to {path:?}."),
+                                ),
+                                Err(err) => {
+                                    error!("failed to export mapper report: {err:?}");
+                                    self.event(UiEvent::Error(format!(
+                                        "failed to export mapper report to {path:?}"
+                                    )));
+                                }
+                            }
+                        }
+                    }
+                    Err(err) => {
+                        error!("failed to open mapper report dialog: {err:?}");
+                        self.event(UiEvent::Error(
+                            "failed to open mapper report dialog".to_string(),
+                        ));
+                    }
+                }
+            }
+            UiEvent::ExportChrDialog => {
+                match save_file_dialog(
+                    "Export Pattern Tables",
+                    "PNG Image",
+                    &["png"],
+                    "pattern_tables.png",
+                    None::<PathBuf>,
+                ) {
+                    Ok(maybe_path) => {
+                        if let Some(path) = maybe_path {
+                            self.event(EmulationEvent::ExportChr(path));
+                        }
+                    }
+                    Err(err) => {
+                        error!("failed to open pattern tables dialog: {err:?}");
+                        self.event(UiEvent::Error(
+                            "failed to open pattern tables dialog".to_string(),
+                        ));
+                    }
+                }
+            }
+            UiEvent::ImportChrDialog => {
+                match open_file_dialog(
+                    "Import Pattern Tables",
+                    "PNG Image",
+                    &["png"],
+                    None::<PathBuf>,
+                ) {
+                    Ok(maybe_path) => {
+                        if let Some(path) = maybe_path {
+                            self.event(EmulationEvent::ImportChr(path));
+                        }
+                    }
+                    Err(err) => {
+                        error!("failed to open pattern tables dialog: {err:?}");
+                        self.event(UiEvent::Error(
+                            "failed to open pattern tables dialog".to_string(),
+                        ));
+                    }
+                }
+            }
+            UiEvent::ExportPaletteDialog => {
+                match save_file_dialog(
+                    "Export Palette",
+                    "Palette",
+                    &["pal"],
+                    "palette.pal",
+                    None::<PathBuf>,
+                ) {
+                    Ok(maybe_path) => {
+                        if let Some(path) = maybe_path {
+                            self.event(EmulationEvent::ExportPalette(path));
+                        }
+                    }
+                    Err(err) => {
+                        error!("failed to open palette dialog: {err:?}");
+                        self.event(UiEvent::Error("failed to open palette dialog".to_string()));
+                    }
+                }
+            }
             UiEvent::FileDialogCancelled => {
                 if self.renderer.rom_loaded() {
                     self.run_state = RunState::Running;
@@ -826,6 +1808,33 @@ impl Running {
         }
     }
 
+    /// Load symbols from a `.nl` or `.mlb` file to name routines in the profiler.
+    fn load_symbols(&mut self, path: &Path) {
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(err) => {
+                error!("failed to read symbols: {err:?}");
+                self.event(UiEvent::Error(format!(
+                    "failed to read symbols from {path:?}"
+                )));
+                return;
+            }
+        };
+        let filename = path
+            .file_name()
+            .map(|filename| filename.to_string_lossy())
+            .unwrap_or_default();
+        let mut symbols = Symbols::default();
+        let count = symbols.load(&filename, &text);
+        if count == 0 {
+            self.event(UiEvent::Error(format!("no symbols found in {path:?}")));
+            return;
+        }
+        self.renderer
+            .add_message(MessageType::Info, format!("Loaded {count} symbols"));
+        self.event(RendererEvent::SymbolsLoaded(symbols));
+    }
+
     /// Trigger a custom event.
     pub fn event(&mut self, event: impl Into<NesEvent>) {
         #[cfg(feature = "profiling")]
@@ -834,11 +1843,15 @@ impl Running {
         let mut event = event.into();
         trace!("Nes event: {event:?}");
 
+        if !self.kiosk_allows(&event) {
+            return;
+        }
+
         self.emulation.on_event(&event);
         self.renderer.on_event(&mut event, &self.cfg);
         match event {
             NesEvent::Ui(event) => self.on_ui_event(&event),
-            NesEvent::Emulation(EmulationEvent::LoadRomPath(path)) => {
+            NesEvent::Emulation(EmulationEvent::LoadRomPath(path)) if !self.attract.active() => {
                 if let Ok(path) = path.canonicalize() {
                     self.cfg.renderer.recent_roms.insert(path);
                 }
@@ -865,6 +1878,7 @@ impl Running {
                     if let Some(player) = self.cfg.input.gamepad_assignment(&uuid) {
                         self.on_input(
                             window_id,
+                            InputDevice::Gamepad(uuid),
                             Input::Button(player, button),
                             ElementState::Pressed,
                             false,
@@ -875,6 +1889,7 @@ impl Running {
                     if let Some(player) = self.cfg.input.gamepad_assignment(&uuid) {
                         self.on_input(
                             window_id,
+                            InputDevice::Gamepad(uuid),
                             Input::Button(player, button),
                             ElementState::Pressed,
                             true,
@@ -885,6 +1900,7 @@ impl Running {
                     if let Some(player) = self.cfg.input.gamepad_assignment(&uuid) {
                         self.on_input(
                             window_id,
+                            InputDevice::Gamepad(uuid),
                             Input::Button(player, button),
                             ElementState::Released,
                             false,
@@ -896,6 +1912,7 @@ impl Running {
                         if let (Some(direction), state) = Gamepads::axis_state(value) {
                             self.on_input(
                                 window_id,
+                                InputDevice::Gamepad(uuid),
                                 Input::Axis(player, axis, direction),
                                 state,
                                 false,
@@ -904,6 +1921,7 @@ impl Running {
                             for direction in [AxisDirection::Positive, AxisDirection::Negative] {
                                 self.on_input(
                                     window_id,
+                                    InputDevice::Gamepad(uuid),
                                     Input::Axis(player, axis, direction),
                                     ElementState::Released,
                                     false,
@@ -944,10 +1962,29 @@ impl Running {
         self.renderer.prepare(&self.gamepads, &self.cfg);
     }
 
+    /// Capture or release the mouse for relative motion input.
+    fn set_mouse_captured(&mut self, event_loop: &ActiveEventLoop, captured: bool) {
+        if self.renderer.set_mouse_captured(captured) {
+            // Relative motion is only reported through device events
+            event_loop.listen_device_events(if captured {
+                DeviceEvents::WhenFocused
+            } else {
+                DeviceEvents::Never
+            });
+            if captured {
+                self.renderer.add_message(
+                    MessageType::Info,
+                    "Mouse captured. Press Escape to release.",
+                );
+            }
+        }
+    }
+
     /// Handle user input mapped to key bindings.
     pub fn on_input(
         &mut self,
         window_id: WindowId,
+        device: InputDevice,
         input: Input,
         state: ElementState,
         repeat: bool,
@@ -955,8 +1992,43 @@ impl Running {
         #[cfg(feature = "profiling")]
         puffin::profile_function!();
 
+        let kiosk = self.cfg.startup.kiosk();
+        if kiosk && input == Self::KIOSK_QUIT {
+            if state == ElementState::Pressed {
+                self.tx.event(UiEvent::Terminate);
+            }
+            return;
+        }
+
+        let input = self.chords.resolve(input, state, &self.input_bindings);
+        if self.renderer.big_picture_open() {
+            if let Input::Button(_, button) = input {
+                if self.input_bindings.get(&input) != Some(&Action::Menu(Menu::BigPicture)) {
+                    if state == ElementState::Pressed {
+                        self.renderer.on_big_picture_button(button);
+                    }
+                    return;
+                }
+            }
+        } else if self.renderer.modal_open() {
+            if let Input::Button(_, button) = input {
+                if state == ElementState::Pressed {
+                    self.renderer.on_modal_button(button);
+                }
+                return;
+            }
+        }
         if let Some(action) = self.input_bindings.get(&input).copied() {
             trace!("action: {action:?}, state: {state:?}, repeat: {repeat:?}");
+            if kiosk
+                && match action {
+                    Action::Ui(Ui::Quit) | Action::Debug(_) => true,
+                    Action::Menu(menu) => menu.kiosk_locked(),
+                    _ => false,
+                }
+            {
+                return;
+            }
             let released = state == ElementState::Released;
             let is_root_window = Some(window_id) == self.renderer.root_window_id();
             match action {
@@ -997,6 +2069,10 @@ impl Running {
                         }
                     }
                 },
+                // Big Picture round-trips through the event loop so it can pause emulation
+                Action::Menu(Menu::BigPicture) if released => {
+                    self.tx.event(RendererEvent::Menu(Menu::BigPicture));
+                }
                 Action::Menu(menu) if released => self.event(RendererEvent::Menu(menu)),
                 Action::Feature(feature) if is_root_window => match feature {
                     Feature::ToggleReplayRecording if released => {
@@ -1037,6 +2113,54 @@ impl Running {
                             );
                         }
                     }
+                    Feature::ScreenshotBurst if !repeat => {
+                        if feature!(Filesystem) {
+                            if self.renderer.rom_loaded() {
+                                let frames = self.cfg.emulation.screenshot_burst_frames;
+                                if !released {
+                                    let frames = if frames == 0 {
+                                        ScreenshotBurst::MAX_FRAMES
+                                    } else {
+                                        frames
+                                    };
+                                    self.event(EmulationEvent::ScreenshotBurst(Some(frames)));
+                                } else if frames == 0 {
+                                    self.event(EmulationEvent::ScreenshotBurst(None));
+                                }
+                            }
+                        } else if released {
+                            self.renderer.add_message(
+                                MessageType::Warn,
+                                "Screenshots are not supported yet on this platform.",
+                            );
+                        }
+                    }
+                    Feature::UndoLoadState if released => {
+                        if self.renderer.rom_loaded() {
+                            self.event(EmulationEvent::UndoLoadState);
+                        }
+                    }
+                    Feature::SaveStateRing if released => {
+                        if self.renderer.rom_loaded() {
+                            self.event(EmulationEvent::SaveStateRing);
+                        }
+                    }
+                    Feature::LoadStateRing if released => {
+                        if self.renderer.rom_loaded() {
+                            self.event(EmulationEvent::LoadStateRing);
+                        }
+                    }
+                    Feature::ToggleMacroRecording(slot) if released => {
+                        if self.renderer.rom_loaded() {
+                            self.event(EmulationEvent::MacroRecord(slot));
+                        }
+                    }
+                    // Start on press rather than release for more predictable timing
+                    Feature::PlayMacro(slot) if !released && !repeat => {
+                        if self.renderer.rom_loaded() {
+                            self.event(EmulationEvent::MacroPlay(slot));
+                        }
+                    }
                     Feature::VisualRewind => {
                         if !self.rewinding {
                             if repeat {
@@ -1055,10 +2179,7 @@ impl Running {
                 Action::Setting(setting) => match setting {
                     Setting::ToggleFullscreen if released => {
                         self.cfg.renderer.fullscreen = !self.cfg.renderer.fullscreen;
-                        self.renderer.set_fullscreen(
-                            self.cfg.renderer.fullscreen,
-                            self.cfg.renderer.embed_viewports,
-                        );
+                        self.renderer.set_fullscreen(&self.cfg.renderer);
                     }
                     Setting::ToggleEmbedViewports if released => {
                         self.cfg.renderer.embed_viewports = !self.cfg.renderer.embed_viewports;
@@ -1067,8 +2188,7 @@ impl Running {
                     }
                     Setting::ToggleAlwaysOnTop if released => {
                         self.cfg.renderer.always_on_top = !self.cfg.renderer.always_on_top;
-                        self.renderer
-                            .set_always_on_top(self.cfg.renderer.always_on_top);
+                        self.renderer.set_always_on_top(&self.cfg.renderer);
                     }
                     Setting::ToggleAudio if released => {
                         self.cfg.audio.enabled = !self.cfg.audio.enabled;
@@ -1114,18 +2234,32 @@ impl Running {
                             );
                         }
                     }
+                    Setting::SetSpeed(percent) if released => {
+                        let new_speed = f32::from(percent) / 100.0;
+                        if self.cfg.emulation.speed != new_speed {
+                            self.cfg.emulation.speed = new_speed;
+                            self.event(ConfigEvent::Speed(new_speed));
+                            self.renderer.add_message(
+                                MessageType::Info,
+                                format!("Set Emulation Speed to {new_speed}"),
+                            );
+                        }
+                    }
                     Setting::FastForward
                         if !repeat && is_root_window && self.renderer.rom_loaded() =>
                     {
-                        let new_speed = if released { 1.0 } else { 2.0 };
-                        let speed = self.cfg.emulation.speed;
-                        if speed != new_speed {
-                            self.cfg.emulation.speed = new_speed;
-                            self.event(ConfigEvent::Speed(self.cfg.emulation.speed));
-                            if new_speed == 2.0 {
-                                self.renderer
-                                    .add_message(MessageType::Info, "Fast forwarding");
+                        let fast_forwarding = if self.cfg.emulation.fast_forward_toggle {
+                            if released {
+                                self.fast_forwarding
+                            } else {
+                                !self.fast_forwarding
                             }
+                        } else {
+                            !released
+                        };
+                        if fast_forwarding != self.fast_forwarding {
+                            self.fast_forwarding = fast_forwarding;
+                            self.event(EmulationEvent::FastForward(self.fast_forwarding));
                         }
                     }
                     _ => (),
@@ -1137,7 +2271,9 @@ impl Running {
                         self.event(EmulationEvent::RunState(self.run_state));
                     }
                     DeckAction::Joypad((player, button)) if !repeat && is_root_window => {
-                        self.event(EmulationEvent::Joypad((player, button, state)));
+                        if self.cfg.input.device_allowed(device, player) {
+                            self.event(EmulationEvent::Joypad((player, button, state)));
+                        }
                     }
                     // Handled by `gui` module
                     DeckAction::ZapperAim(_)
@@ -1146,7 +2282,7 @@ impl Running {
                     DeckAction::SetSaveSlot(slot) if released => {
                         if feature!(Storage) {
                             if self.cfg.emulation.save_slot != slot {
-                                self.cfg.emulation.save_slot = slot;
+                                self.tx.event(ConfigEvent::SaveSlot(slot));
                                 self.renderer.add_message(
                                     MessageType::Info,
                                     format!("Changed Save Slot to {slot}"),
@@ -1159,6 +2295,28 @@ impl Running {
                             );
                         }
                     }
+                    DeckAction::SwapSaveSlot if released => {
+                        if feature!(Storage) {
+                            match self.cfg.emulation.swap_save_slot() {
+                                Some(slot) => {
+                                    self.tx.event(ConfigEvent::SaveSlot(slot));
+                                    self.renderer.add_message(
+                                        MessageType::Info,
+                                        format!("Swapped to Save Slot {slot}"),
+                                    );
+                                }
+                                None => self.renderer.add_message(
+                                    MessageType::Info,
+                                    "No other save slot has been used yet.",
+                                ),
+                            }
+                        } else {
+                            self.renderer.add_message(
+                                MessageType::Warn,
+                                "Save states are not supported yet on this platform.",
+                            );
+                        }
+                    }
                     DeckAction::SaveState if released && is_root_window => {
                         if feature!(Storage) {
                             self.event(EmulationEvent::SaveState(self.cfg.emulation.save_slot));
@@ -15,12 +15,13 @@ use tetanes_core::{
     apu::Channel,
     common::ResetKind,
     input::{JoypadBtn, Player},
+    time::{Duration, Instant},
     video::VideoFilter,
 };
 use tracing::warn;
 use uuid::Uuid;
 use winit::{
-    event::{ElementState, MouseButton},
+    event::{DeviceId, ElementState, MouseButton},
     keyboard::{KeyCode, ModifiersState},
 };
 
@@ -82,6 +83,16 @@ macro_rules! mouse_map {
     };
 }
 
+/// The physical device an [`Input`] came from, used to keep devices assigned to one player from
+/// controlling another.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[must_use]
+pub enum InputDevice {
+    Keyboard(DeviceId),
+    Mouse,
+    Gamepad(Uuid),
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[must_use]
 pub enum Input {
@@ -89,6 +100,11 @@ pub enum Input {
     Mouse(MouseButton),
     Button(Player, gilrs::Button),
     Axis(Player, gilrs::Axis, AxisDirection),
+    /// Hold the first input and press the second, e.g. `Select + Start`.
+    Chord(ChordInput, ChordInput),
+    /// Press and release the first input, then press the second within
+    /// [`ChordState::SEQUENCE_TIMEOUT`].
+    Sequence(ChordInput, ChordInput),
 }
 
 impl Input {
@@ -252,6 +268,20 @@ impl Input {
             }
             Input::Button(_, button) => format!("{button:#?}"),
             Input::Axis(_, axis, direction) => format!("{axis:#?} {direction:#?}"),
+            Input::Chord(held, pressed) => {
+                format!(
+                    "{} + {}",
+                    Input::fmt(held.into()),
+                    Input::fmt(pressed.into())
+                )
+            }
+            Input::Sequence(first, second) => {
+                format!(
+                    "{}, {}",
+                    Input::fmt(first.into()),
+                    Input::fmt(second.into())
+                )
+            }
             Input::Mouse(button) => match button {
                 MouseButton::Left => String::from("Left Click"),
                 MouseButton::Right => String::from("Right Click"),
@@ -264,6 +294,112 @@ impl Input {
     }
 }
 
+/// A single input that can be combined with another to form an [`Input::Chord`] or
+/// [`Input::Sequence`]. Keyboard modifiers are ignored as they're already supported by
+/// [`Input::Key`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[must_use]
+pub enum ChordInput {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    Button(Player, gilrs::Button),
+}
+
+impl ChordInput {
+    pub const fn from_input(input: Input) -> Option<Self> {
+        match input {
+            Input::Key(keycode, _) => Some(Self::Key(keycode)),
+            Input::Mouse(button) => Some(Self::Mouse(button)),
+            Input::Button(player, button) => Some(Self::Button(player, button)),
+            Input::Axis(..) | Input::Chord(..) | Input::Sequence(..) => None,
+        }
+    }
+}
+
+impl From<ChordInput> for Input {
+    fn from(input: ChordInput) -> Self {
+        match input {
+            ChordInput::Key(keycode) => Self::Key(keycode, ModifiersState::empty()),
+            ChordInput::Mouse(button) => Self::Mouse(button),
+            ChordInput::Button(player, button) => Self::Button(player, button),
+        }
+    }
+}
+
+/// Tracks held and recently released inputs in order to match [`Input::Chord`] and
+/// [`Input::Sequence`] bindings.
+#[derive(Default, Debug)]
+#[must_use]
+pub struct ChordState {
+    held: Vec<ChordInput>,
+    active: HashMap<ChordInput, Input>,
+    last_released: Option<(ChordInput, Instant)>,
+}
+
+impl ChordState {
+    /// Maximum time allowed between steps of an [`Input::Sequence`].
+    pub const SEQUENCE_TIMEOUT: Duration = Duration::from_millis(1000);
+
+    /// Updates the held input state and returns the bound chord or sequence the input completes,
+    /// if any, otherwise the original input. Releasing the input that completed a chord or
+    /// sequence returns that same chord or sequence so that release actions fire.
+    pub fn resolve(
+        &mut self,
+        input: Input,
+        state: ElementState,
+        bindings: &InputBindings,
+    ) -> Input {
+        let Some(chord_input) = ChordInput::from_input(input) else {
+            return input;
+        };
+
+        match state {
+            ElementState::Pressed => {
+                if let Some(active) = self.active.get(&chord_input) {
+                    return *active;
+                }
+
+                let chord = self
+                    .held
+                    .iter()
+                    .rev()
+                    .filter(|held| **held != chord_input)
+                    .map(|held| Input::Chord(*held, chord_input))
+                    .find(|chord| bindings.contains_key(chord));
+                let sequence = self
+                    .last_released
+                    .filter(|(first, released)| {
+                        *first != chord_input && released.elapsed() <= Self::SEQUENCE_TIMEOUT
+                    })
+                    .map(|(first, _)| Input::Sequence(first, chord_input))
+                    .filter(|sequence| bindings.contains_key(sequence));
+                if !self.held.contains(&chord_input) {
+                    self.held.push(chord_input);
+                }
+
+                match chord.or(sequence) {
+                    Some(resolved) => {
+                        self.active.insert(chord_input, resolved);
+                        self.last_released = None;
+                        resolved
+                    }
+                    None => input,
+                }
+            }
+            ElementState::Released => {
+                self.held.retain(|held| *held != chord_input);
+                match self.active.remove(&chord_input) {
+                    Some(resolved) => resolved,
+                    None => {
+                        self.last_released = Some((chord_input, Instant::now()));
+                        input
+                    }
+                }
+            }
+        }
+    }
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum AxisDirection {
     Negative, // Left or Up
@@ -272,6 +408,50 @@ pub enum AxisDirection {
 
 pub type Bindings = [Option<Input>; 3];
 
+/// Alternative shortcut layouts, used as starting points for keybind profiles.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[must_use]
+pub enum ShortcutPreset {
+    Default,
+    LeftHand,
+    Laptop,
+    Dvorak,
+}
+
+impl ShortcutPreset {
+    pub const fn as_slice() -> &'static [Self] {
+        &[Self::Default, Self::LeftHand, Self::Laptop, Self::Dvorak]
+    }
+
+    pub const fn description(&self) -> &'static str {
+        match self {
+            Self::Default => "The default shortcuts.",
+            Self::LeftHand => concat!(
+                "Shortcuts on the left side of the keyboard, leaving the right hand free for the ",
+                "mouse. Save slots 7 and 8 are left unbound.",
+            ),
+            Self::Laptop => {
+                "Shortcuts without function keys, which need holding Fn on many laptops."
+            }
+            Self::Dvorak => concat!(
+                "The default shortcuts moved so their letters match a Dvorak keyboard layout, ",
+                "e.g. Ctrl+S is still the S key.",
+            ),
+        }
+    }
+}
+
+impl AsRef<str> for ShortcutPreset {
+    fn as_ref(&self) -> &str {
+        match self {
+            Self::Default => InputConfig::DEFAULT_PROFILE,
+            Self::LeftHand => "Left Hand",
+            Self::Laptop => "Laptop",
+            Self::Dvorak => "Dvorak",
+        }
+    }
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
 #[must_use]
 pub struct ActionBindings {
@@ -305,6 +485,7 @@ impl ActionBindings {
             .collect::<BTreeMap<_, _>>();
 
         bindings.extend(shortcut_map!(
+            { Debug::Step(DebugStep::Back) => :SHIFT, KeyC },
             { Debug::Step(DebugStep::Frame) => :SHIFT, KeyF },
             { Debug::Step(DebugStep::Into) => KeyC },
             { Debug::Step(DebugStep::Out) => :SHIFT, KeyO },
@@ -326,6 +507,7 @@ impl ActionBindings {
             { DeckAction::SetSaveSlot(7) => :CONTROL, Digit7 },
             { DeckAction::SetSaveSlot(8) => :CONTROL, Digit8 },
             { DeckAction::SetVideoFilter(VideoFilter::Ntsc) => :CONTROL, KeyN },
+            { DeckAction::SwapSaveSlot => :CONTROL, Digit0 },
             { DeckAction::ToggleApuChannel(Channel::Dmc) => :SHIFT, Digit5 },
             { DeckAction::ToggleApuChannel(Channel::Mapper) => :SHIFT, Digit6 },
             { DeckAction::ToggleApuChannel(Channel::Noise) => :SHIFT, Digit4 },
@@ -333,14 +515,20 @@ impl ActionBindings {
             { DeckAction::ToggleApuChannel(Channel::Pulse2) => :SHIFT, Digit2 },
             { DeckAction::ToggleApuChannel(Channel::Triangle) => :SHIFT, Digit3 },
             { Feature::InstantRewind => KeyR },
+            { Feature::LoadStateRing => F8 },
+            { Feature::SaveStateRing => F5 },
+            { Feature::ScreenshotBurst => :SHIFT, F10 },
             { Feature::TakeScreenshot => F10 },
             { Feature::ToggleAudioRecording => :SHIFT, KeyR },
             { Feature::ToggleReplayRecording => :SHIFT, KeyV },
+            { Feature::UndoLoadState => :CONTROL, KeyZ },
             { Feature::VisualRewind => KeyR },
             { Menu::About => F1 },
+            { Menu::BigPicture => :CONTROL, KeyB },
             { Menu::Keybinds => :CONTROL, KeyK; F3 },
             { Menu::Preferences => :CONTROL, KeyP; F2 },
             { Menu::PerfStats => :CONTROL, KeyF },
+            { Menu::Shortcuts => :SHIFT, Slash },
             { Setting::DecrementScale => :SHIFT, Minus },
             { Setting::DecrementSpeed => Minus },
             { Setting::FastForward => Space },
@@ -357,10 +545,137 @@ impl ActionBindings {
             { DeckAction::ZapperTrigger => MouseButton::Left },
             { DeckAction::ZapperAimOffscreen => MouseButton::Right }
         ));
+        // Press the Guide/Home button on a controller to open Big Picture mode
+        if let Some(bind) = bindings.get_mut(&Action::Menu(Menu::BigPicture)) {
+            if let Some(binding) = bind.bindings.iter_mut().find(|b| b.is_none()) {
+                *binding = Some(Input::Button(Player::One, gilrs::Button::Mode));
+            }
+        }
+
+        bindings
+    }
+
+    /// Shortcuts for a [`ShortcutPreset`], based on [`ActionBindings::default_shortcuts`].
+    pub fn preset_shortcuts(preset: ShortcutPreset) -> BTreeMap<Action, ActionBindings> {
+        use KeyCode::*;
+        const SHIFT: ModifiersState = ModifiersState::SHIFT;
+        const CONTROL: ModifiersState = ModifiersState::CONTROL;
+        const ALT: ModifiersState = ModifiersState::ALT;
+        const CONTROL_SHIFT: ModifiersState = CONTROL.union(SHIFT);
+
+        let mut bindings = Self::default_shortcuts();
+        let overrides = match preset {
+            ShortcutPreset::Default => Vec::new(),
+            ShortcutPreset::LeftHand => {
+                let mut overrides = shortcut_map!(
+                    { Debug::Step(DebugStep::Out) => :SHIFT, KeyG },
+                    { Debug::Step(DebugStep::Over) => KeyG },
+                    { Debug::Step(DebugStep::Scanline) => :SHIFT, KeyB },
+                    { Debug::Toggle(DebugKind::Ppu) => :SHIFT, KeyE },
+                    { DeckAction::LoadState => :CONTROL, KeyD },
+                    { DeckAction::Reset(ResetKind::Hard) => :CONTROL_SHIFT, KeyR },
+                    { DeckAction::SetVideoFilter(VideoFilter::Ntsc) => :CONTROL, KeyG },
+                    { DeckAction::SwapSaveSlot => :CONTROL, Backquote },
+                    { Feature::LoadStateRing => F4 },
+                    { Feature::ScreenshotBurst => :CONTROL_SHIFT, KeyT },
+                    { Feature::TakeScreenshot => :CONTROL, KeyT },
+                    { Menu::Keybinds => F3 },
+                    { Menu::Preferences => F2 },
+                    { Menu::PerfStats => :CONTROL_SHIFT, KeyF },
+                    { Menu::Shortcuts => F6 },
+                    { Setting::DecrementScale => :ALT, Digit3 },
+                    { Setting::DecrementSpeed => :ALT, Digit1 },
+                    { Setting::IncrementScale => :ALT, Digit4 },
+                    { Setting::IncrementSpeed => :ALT, Digit2 },
+                    { Setting::ToggleAudio => :CONTROL, KeyA },
+                    { Setting::ToggleFullscreen => :CONTROL, Tab },
+                    { Ui::LoadRom => :CONTROL, KeyF },
+                );
+                overrides.extend([7, 8].map(|slot| {
+                    let action = DeckAction::SetSaveSlot(slot).into();
+                    (action, ActionBindings::empty(action))
+                }));
+                overrides
+            }
+            ShortcutPreset::Laptop => shortcut_map!(
+                { Feature::LoadStateRing => :CONTROL_SHIFT, KeyL },
+                { Feature::SaveStateRing => :CONTROL_SHIFT, KeyS },
+                { Feature::ScreenshotBurst => :CONTROL_SHIFT, KeyT },
+                { Feature::TakeScreenshot => :CONTROL, KeyT },
+                { Menu::About => :CONTROL, KeyI },
+                { Menu::Keybinds => :CONTROL, KeyK },
+                { Menu::Preferences => :CONTROL, KeyP },
+                { Ui::LoadRom => :CONTROL, KeyO },
+            ),
+            ShortcutPreset::Dvorak => {
+                // Shortcuts are bound to physical keys, so move each to the key that types the
+                // same character on a Dvorak layout
+                for bind in bindings.values_mut() {
+                    for input in bind.bindings.iter_mut().flatten() {
+                        if let Input::Key(keycode, modifiers) = *input {
+                            *input = Input::Key(Self::dvorak_key(keycode), modifiers);
+                        }
+                    }
+                }
+                Vec::new()
+            }
+        };
+
+        // Replace keys, keeping any gamepad or mouse bindings
+        for (action, keys) in overrides {
+            let Some(bind) = bindings.get_mut(&action) else {
+                continue;
+            };
+            let others = bind
+                .bindings
+                .iter()
+                .flatten()
+                .filter(|input| !matches!(input, Input::Key(..)))
+                .copied()
+                .collect::<Vec<_>>();
+            let mut inputs = keys.bindings.into_iter().flatten().chain(others);
+            bind.bindings = std::array::from_fn(|_| inputs.next());
+        }
 
         bindings
     }
 
+    /// The physical key that types the same character on a Dvorak layout as `keycode` does on
+    /// QWERTY.
+    const fn dvorak_key(keycode: KeyCode) -> KeyCode {
+        use KeyCode::*;
+        match keycode {
+            KeyB => KeyN,
+            KeyC => KeyI,
+            KeyD => KeyH,
+            KeyE => KeyD,
+            KeyF => KeyY,
+            KeyG => KeyU,
+            KeyH => KeyJ,
+            KeyI => KeyG,
+            KeyJ => KeyC,
+            KeyK => KeyV,
+            KeyL => KeyP,
+            KeyN => KeyL,
+            KeyO => KeyS,
+            KeyP => KeyR,
+            KeyQ => KeyX,
+            KeyR => KeyO,
+            KeyS => Semicolon,
+            KeyT => KeyK,
+            KeyU => KeyF,
+            KeyV => Period,
+            KeyW => Comma,
+            KeyX => KeyB,
+            KeyY => KeyT,
+            KeyZ => Slash,
+            Minus => Quote,
+            Equal => BracketRight,
+            Slash => BracketLeft,
+            keycode => keycode,
+        }
+    }
+
     pub fn default_player_bindings(player: Player) -> BTreeMap<Action, ActionBindings> {
         use gilrs::{Axis, Button};
         use KeyCode::*;
@@ -459,6 +774,12 @@ pub struct Gamepads {
     connected: HashMap<gilrs::GamepadId, Uuid>,
     inner: Option<gilrs::Gilrs>,
     events: VecDeque<gilrs::Event>,
+    /// Keyboards in the order they were first typed on. Keyboards aren't enumerated up front
+    /// like gamepads and their IDs only last for the current session.
+    keyboards: Vec<DeviceId>,
+    /// Whether keyboard input arrived without a per-device ID, which is the case on Windows,
+    /// macOS and Wayland where every keyboard reports the same placeholder ID.
+    keyboards_shared: bool,
 }
 
 impl Gamepads {
@@ -484,6 +805,8 @@ impl Gamepads {
             connected,
             inner: gilrs.ok(),
             events,
+            keyboards: Vec::new(),
+            keyboards_shared: false,
         }
     }
 
@@ -617,6 +940,33 @@ impl Gamepads {
         self.connected.remove(&gamepad_id);
     }
 
+    /// Track a keyboard the first time it's typed on. Keyboards without their own ID can't be
+    /// told apart, so they aren't tracked.
+    pub fn connect_keyboard(&mut self, device_id: DeviceId) {
+        if device_id == DeviceId::dummy() {
+            if !self.keyboards_shared {
+                tracing::debug!("keyboards share a device ID and can't be assigned to players");
+                self.keyboards_shared = true;
+            }
+        } else if !self.keyboards.contains(&device_id) {
+            tracing::debug!("keyboard connected: {device_id:?}");
+            self.keyboards.push(device_id);
+        }
+    }
+
+    pub fn keyboards(&self) -> &[DeviceId] {
+        &self.keyboards
+    }
+
+    /// Whether keyboards can't be told apart on this platform, so can't be assigned to players.
+    pub const fn keyboards_shared(&self) -> bool {
+        self.keyboards_shared
+    }
+
+    pub fn keyboard_index(&self, device_id: &DeviceId) -> Option<usize> {
+        self.keyboards.iter().position(|id| id == device_id)
+    }
+
     pub fn create_uuid(gamepad: &gilrs::Gamepad<'_>) -> Uuid {
         let uuid = Uuid::from_bytes(gamepad.uuid());
         if uuid != Uuid::nil() {
@@ -0,0 +1,386 @@
+//! A small built-in web server that lets a phone browser act as a gamepad.
+//!
+//! `GET /?player=N&token=T` serves a touch gamepad page, which connects back over a WebSocket at
+//! `/ws?player=N&token=T` and sends a JSON text message whenever a button changes:
+//!
+//! ```json
+//! { "button": "A", "pressed": true }
+//! ```
+//!
+//! Button presses are sent as [`EmulationEvent::Joypad`] events for the chosen player. Any
+//! buttons still held when a phone disconnects are released. The token is randomly generated
+//! each time the server starts and only shared through the QR code, so other devices on the
+//! network can't connect without it.
+
+use crate::nes::event::{EmulationEvent, NesEventProxy};
+use anyhow::Context;
+use base64::Engine;
+use serde::Deserialize;
+use sha1::{Digest, Sha1};
+use std::{
+    collections::HashSet,
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream, UdpSocket},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+use tetanes_core::input::{JoypadBtn, Player};
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+use winit::event::ElementState;
+
+const PAGE: &str = include_str!("../../assets/phone_pad.html");
+/// Appended to the client key to compute `Sec-WebSocket-Accept`, per RFC 6455.
+const WEBSOCKET_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+/// How often blocked threads wake up to check whether the server stopped.
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+/// Largest message accepted from a phone. Button messages are only a few dozen bytes.
+const MAX_PAYLOAD_LEN: u64 = 1024;
+/// Largest payload allowed in a control frame, like a ping, per RFC 6455.
+const MAX_CONTROL_PAYLOAD_LEN: u64 = 125;
+/// Largest request line and headers accepted before upgrading to a WebSocket.
+const MAX_REQUEST_LEN: u64 = 8 * 1024;
+/// How long to wait for the rest of a request or frame once it starts arriving.
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+/// Most connections served at once. Each phone keeps one open.
+const MAX_CONNECTIONS: usize = 16;
+
+#[derive(Debug, Deserialize)]
+struct PadMessage {
+    button: JoypadBtn,
+    pressed: bool,
+}
+
+/// Serves the phone gamepad page and forwards button presses from connected phones. Stops
+/// when dropped.
+#[derive(Debug)]
+#[must_use]
+pub struct PhonePad {
+    addr: SocketAddr,
+    url: Option<String>,
+    token: Arc<str>,
+    running: Arc<AtomicBool>,
+    clients: Arc<AtomicUsize>,
+}
+
+impl PhonePad {
+    /// Port tried first so phone bookmarks keep working between launches.
+    pub const DEFAULT_PORT: u16 = 8787;
+
+    /// Start serving on all interfaces, falling back to any free port if the default port is
+    /// taken.
+    pub fn start(tx: NesEventProxy) -> anyhow::Result<Self> {
+        let listener = TcpListener::bind((Ipv4Addr::UNSPECIFIED, Self::DEFAULT_PORT))
+            .or_else(|_| TcpListener::bind((Ipv4Addr::UNSPECIFIED, 0)))
+            .context("failed to bind phone controller server")?;
+        listener.set_nonblocking(true)?;
+        let addr = listener.local_addr()?;
+
+        let token = Arc::<str>::from(Uuid::new_v4().simple().to_string());
+        let running = Arc::new(AtomicBool::new(true));
+        let clients = Arc::new(AtomicUsize::new(0));
+        thread::Builder::new()
+            .name("phone_pad".into())
+            .spawn({
+                let token = Arc::clone(&token);
+                let running = Arc::clone(&running);
+                let clients = Arc::clone(&clients);
+                move || Self::listen(&listener, &tx, &token, &running, &clients)
+            })
+            .context("failed to spawn phone controller thread")?;
+        info!("serving phone controller on {addr}");
+
+        Ok(Self {
+            addr,
+            url: Self::local_url(addr.port()),
+            token,
+            running,
+            clients,
+        })
+    }
+
+    /// The address phones on the local network should open, if a LAN address could be found.
+    fn local_url(port: u16) -> Option<String> {
+        // Connecting a UDP socket sends nothing, but picks the interface used to reach the
+        // network
+        let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).ok()?;
+        socket.connect((Ipv4Addr::new(192, 168, 0, 1), 80)).ok()?;
+        let ip = socket.local_addr().ok()?.ip();
+        (!ip.is_loopback() && !ip.is_unspecified()).then(|| format!("http://{ip}:{port}/"))
+    }
+
+    /// The page URL for a given player, including the pairing token.
+    pub fn player_url(&self, player: Player) -> Option<String> {
+        self.url
+            .as_ref()
+            .map(|url| format!("{url}?player={}&token={}", player as usize + 1, self.token))
+    }
+
+    /// The pairing token phones must provide to connect.
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+
+    pub const fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Number of phones currently connected.
+    pub fn clients(&self) -> usize {
+        self.clients.load(Ordering::Relaxed)
+    }
+
+    fn listen(
+        listener: &TcpListener,
+        tx: &NesEventProxy,
+        token: &Arc<str>,
+        running: &Arc<AtomicBool>,
+        clients: &Arc<AtomicUsize>,
+    ) {
+        let connections = Arc::new(AtomicUsize::new(0));
+        while running.load(Ordering::Relaxed) {
+            match listener.accept() {
+                Ok((stream, peer)) => {
+                    if connections.fetch_add(1, Ordering::Relaxed) >= MAX_CONNECTIONS {
+                        connections.fetch_sub(1, Ordering::Relaxed);
+                        warn!("too many phone controller connections, rejected {peer}");
+                        continue;
+                    }
+                    let tx = tx.clone();
+                    let token = Arc::clone(token);
+                    let running = Arc::clone(running);
+                    let clients = Arc::clone(clients);
+                    let thread_connections = Arc::clone(&connections);
+                    let res = thread::Builder::new()
+                        .name(format!("phone_pad_{peer}"))
+                        .spawn(move || {
+                            if let Err(err) = Self::handle(stream, &tx, &token, &running, &clients)
+                            {
+                                debug!("phone controller {peer} disconnected: {err:?}");
+                            }
+                            thread_connections.fetch_sub(1, Ordering::Relaxed);
+                        });
+                    if let Err(err) = res {
+                        connections.fetch_sub(1, Ordering::Relaxed);
+                        error!("failed to spawn phone controller client thread: {err:?}");
+                    }
+                }
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => {
+                    thread::sleep(POLL_INTERVAL);
+                }
+                Err(err) => error!("failed to accept phone controller client: {err:?}"),
+            }
+        }
+        debug!("phone controller server stopped");
+    }
+
+    /// Read a line of the request, failing if it goes past the `remaining` request length.
+    fn read_request_line(reader: &mut impl BufRead, remaining: &mut u64) -> anyhow::Result<String> {
+        let mut line = String::new();
+        let len = reader.by_ref().take(*remaining).read_line(&mut line)?;
+        *remaining -= len as u64;
+        anyhow::ensure!(
+            line.ends_with('\n') || (len == 0 && *remaining > 0),
+            "request too large"
+        );
+        Ok(line)
+    }
+
+    /// Serve the gamepad page, or upgrade to a WebSocket and forward button presses.
+    fn handle(
+        stream: TcpStream,
+        tx: &NesEventProxy,
+        token: &str,
+        running: &AtomicBool,
+        clients: &AtomicUsize,
+    ) -> anyhow::Result<()> {
+        stream.set_nonblocking(false)?;
+        stream.set_nodelay(true)?;
+        stream.set_read_timeout(Some(READ_TIMEOUT))?;
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let mut writer = stream;
+
+        let mut remaining = MAX_REQUEST_LEN;
+        let request_line = Self::read_request_line(&mut reader, &mut remaining)?;
+        let path = request_line.split_whitespace().nth(1).unwrap_or("/");
+        let (route, query) = path.split_once('?').unwrap_or((path, ""));
+        let player = query
+            .split('&')
+            .find_map(|param| param.strip_prefix("player="))
+            .and_then(|player| player.parse::<usize>().ok())
+            .and_then(|player| Player::try_from(player.checked_sub(1)?).ok())
+            .unwrap_or(Player::One);
+        let paired = query
+            .split('&')
+            .any(|param| param.strip_prefix("token=") == Some(token));
+
+        let mut websocket_key = None;
+        loop {
+            let line = Self::read_request_line(&mut reader, &mut remaining)?;
+            if line.trim().is_empty() {
+                break;
+            }
+            if let Some((name, value)) = line.split_once(':') {
+                if name.trim().eq_ignore_ascii_case("sec-websocket-key") {
+                    websocket_key = Some(value.trim().to_string());
+                }
+            }
+        }
+
+        match (route, websocket_key) {
+            ("/ws", Some(_)) if !paired => {
+                warn!("rejected phone controller without a valid pairing token");
+                write!(
+                    writer,
+                    "HTTP/1.1 403 Forbidden\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                )?;
+                Ok(writer.flush()?)
+            }
+            ("/ws", Some(key)) => {
+                let accept = base64::engine::general_purpose::STANDARD
+                    .encode(Sha1::digest(format!("{key}{WEBSOCKET_GUID}")));
+                write!(
+                    writer,
+                    "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {accept}\r\n\r\n"
+                )?;
+                writer.flush()?;
+
+                info!("phone controller connected as player {player:?}");
+                clients.fetch_add(1, Ordering::Relaxed);
+                let mut held = HashSet::new();
+                let res = Self::forward(&mut reader, &mut writer, tx, running, player, &mut held);
+                for button in held {
+                    tx.event(EmulationEvent::Joypad((
+                        player,
+                        button,
+                        ElementState::Released,
+                    )));
+                }
+                clients.fetch_sub(1, Ordering::Relaxed);
+                info!("phone controller for player {player:?} disconnected");
+                res
+            }
+            ("/", _) => {
+                write!(
+                    writer,
+                    "HTTP/1.1 200 OK\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nCache-Control: no-cache\r\nConnection: close\r\n\r\n{PAGE}",
+                    PAGE.len()
+                )?;
+                Ok(writer.flush()?)
+            }
+            _ => {
+                write!(
+                    writer,
+                    "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                )?;
+                Ok(writer.flush()?)
+            }
+        }
+    }
+
+    /// Read WebSocket frames until the phone disconnects or the server stops.
+    fn forward(
+        reader: &mut impl Read,
+        writer: &mut TcpStream,
+        tx: &NesEventProxy,
+        running: &AtomicBool,
+        player: Player,
+        held: &mut HashSet<JoypadBtn>,
+    ) -> anyhow::Result<()> {
+        writer.set_read_timeout(Some(POLL_INTERVAL))?;
+        while running.load(Ordering::Relaxed) {
+            // Only wait briefly for a new frame so stopping the server is noticed
+            let mut header = [0; 2];
+            match reader.read_exact(&mut header[..1]) {
+                Ok(()) => (),
+                Err(err)
+                    if matches!(
+                        err.kind(),
+                        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                    ) =>
+                {
+                    continue;
+                }
+                Err(err) => return Err(err.into()),
+            }
+            // Once a frame starts arriving, give the rest of it longer to arrive, but not forever
+            // so a stalled phone can't block stopping the server
+            writer.set_read_timeout(Some(READ_TIMEOUT))?;
+            reader.read_exact(&mut header[1..])?;
+
+            let opcode = header[0] & 0x0F;
+            let masked = header[1] & 0x80 != 0;
+            let len = match header[1] & 0x7F {
+                126 => {
+                    let mut len = [0; 2];
+                    reader.read_exact(&mut len)?;
+                    u64::from(u16::from_be_bytes(len))
+                }
+                127 => {
+                    let mut len = [0; 8];
+                    reader.read_exact(&mut len)?;
+                    u64::from_be_bytes(len)
+                }
+                len => u64::from(len),
+            };
+            anyhow::ensure!(len <= MAX_PAYLOAD_LEN, "message too large: {len} bytes");
+            // Control frames, like pings, are limited to a single byte length
+            anyhow::ensure!(
+                opcode < 0x8 || len <= MAX_CONTROL_PAYLOAD_LEN,
+                "control frame too large: {len} bytes"
+            );
+            let mut mask = [0; 4];
+            if masked {
+                reader.read_exact(&mut mask)?;
+            }
+            let mut payload = vec![0; len as usize];
+            reader.read_exact(&mut payload)?;
+            if masked {
+                for (i, byte) in payload.iter_mut().enumerate() {
+                    *byte ^= mask[i % 4];
+                }
+            }
+            writer.set_read_timeout(Some(POLL_INTERVAL))?;
+
+            match opcode {
+                // Text
+                0x1 => match serde_json::from_slice::<PadMessage>(&payload) {
+                    Ok(PadMessage { button, pressed }) => {
+                        let state = if pressed {
+                            held.insert(button);
+                            ElementState::Pressed
+                        } else {
+                            held.remove(&button);
+                            ElementState::Released
+                        };
+                        tx.event(EmulationEvent::Joypad((player, button, state)));
+                    }
+                    Err(err) => debug!("invalid phone controller message: {err:?}"),
+                },
+                // Close
+                0x8 => {
+                    writer.write_all(&[0x88, 0])?;
+                    return Ok(());
+                }
+                // Ping
+                0x9 => {
+                    writer.write_all(&[0x8A, payload.len() as u8])?;
+                    writer.write_all(&payload)?;
+                }
+                _ => (),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Drop for PhonePad {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+        info!("stopped phone controller server on {}", self.addr);
+    }
+}
@@ -1,14 +1,16 @@
 use crate::{
     feature,
     nes::{
-        config::Config,
+        audio::SampleTap,
+        config::{Config, FullscreenTarget, RendererConfig, VideoMode, WindowGeometry},
         event::{EmulationEvent, NesEvent, NesEventProxy, RendererEvent, UiEvent},
         input::Gamepads,
         renderer::{
             clipboard::Clipboard,
             event::translate_cursor,
-            gui::{Gui, MessageType},
+            gui::{error_report::Report, lib::gamepad_nav_key, Gui, MessageType, MouseCapture},
             painter::Painter,
+            texture::TextureFilter,
         },
         RunState,
     },
@@ -28,17 +30,21 @@ use tetanes_core::{
     fs,
     ppu::Ppu,
     time::{Duration, Instant},
-    video::Frame,
+    video::{
+        upscale::{UpscaleFilter, Upscaler},
+        Frame,
+    },
 };
 use thingbuf::{
     mpsc::{blocking::Receiver as BufReceiver, errors::TryRecvError},
     Recycle,
 };
-use tracing::{debug, error, info, trace};
+use tracing::{debug, error, info, trace, warn};
 use winit::{
     dpi::{LogicalSize, PhysicalPosition, PhysicalSize},
     event_loop::ActiveEventLoop,
-    window::{CursorGrabMode, Theme, Window, WindowButtons, WindowId},
+    monitor::{MonitorHandle, VideoModeHandle},
+    window::{CursorGrabMode, Fullscreen, Theme, Window, WindowButtons, WindowId},
 };
 
 pub mod clipboard;
@@ -62,6 +68,39 @@ impl Recycle<Frame> for FrameRecycle {
     fn recycle(&self, _frame: &mut Frame) {}
 }
 
+/// A connected monitor that can be chosen to go fullscreen on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[must_use]
+pub struct MonitorInfo {
+    pub name: String,
+    /// Whether the main window is currently on this monitor.
+    pub current: bool,
+    /// Available video modes for exclusive fullscreen, best first.
+    pub video_modes: Vec<VideoMode>,
+}
+
+impl MonitorInfo {
+    fn new(monitor: &MonitorHandle, current: Option<&MonitorHandle>) -> Option<Self> {
+        let mut video_modes = monitor
+            .video_modes()
+            .map(|mode| Renderer::video_mode(&mode))
+            .collect::<Vec<_>>();
+        video_modes.sort_by_key(|mode| {
+            std::cmp::Reverse((
+                mode.width * mode.height,
+                mode.refresh_rate_millihertz,
+                mode.width,
+            ))
+        });
+        video_modes.dedup();
+        Some(Self {
+            name: monitor.name()?,
+            current: current == Some(monitor),
+            video_modes,
+        })
+    }
+}
+
 #[must_use]
 pub struct State {
     pub(crate) viewports: ViewportIdMap<Viewport>,
@@ -122,6 +161,8 @@ pub struct Renderer {
     pub(crate) state: Rc<RefCell<State>>,
     painter: Rc<RefCell<Painter>>,
     frame_rx: BufReceiver<Frame, FrameRecycle>,
+    race_frame_rx: BufReceiver<Frame, FrameRecycle>,
+    ghost_frame_rx: BufReceiver<Frame, FrameRecycle>,
     tx: NesEventProxy,
     redraw_tx: Arc<Mutex<NesEventProxy>>,
     pub(crate) gui: Rc<RefCell<Gui>>,
@@ -129,9 +170,31 @@ pub struct Renderer {
     #[cfg(not(target_arch = "wasm32"))]
     accesskit: accesskit_winit::Adapter,
     first_frame: bool,
+    /// Whether the next paint of the main window is a black frame, for black frame insertion.
+    black_frame_pending: bool,
     pub(crate) last_save_time: Instant,
     zoom_changed: bool,
     resize_texture: bool,
+    /// The upscale filter `nes_texture` was last sized for, which may lag behind the config until
+    /// the texture is resized.
+    upscale_filter: UpscaleFilter,
+    upscaler: Upscaler,
+    /// The sampling `nes_texture` was last created with.
+    texture_filter: TextureFilter,
+    /// Integer scale applied to upscaled frames for `TextureFilter::SharpBilinear`.
+    prescale: u32,
+    prescaled: Vec<u8>,
+    /// Scale of the most recently received NES frame, which is larger than `1` while an HD pack
+    /// is loaded.
+    frame_scale: u32,
+    /// The NES frame scale `nes_texture` was last sized for, which may lag behind `frame_scale`
+    /// until the texture is resized.
+    hd_scale: u32,
+    /// Incremented whenever anything that may affect what's drawn changes, e.g. a new NES frame,
+    /// a window event or a config change.
+    generation: u64,
+    /// The `generation` and time each viewport was last painted, used to skip redundant redraws.
+    painted: ViewportIdMap<(u64, Instant)>,
 }
 
 impl std::fmt::Debug for Renderer {
@@ -140,6 +203,8 @@ impl std::fmt::Debug for Renderer {
             .field("state", &self.state)
             .field("painter", &self.painter)
             .field("frame_rx", &self.frame_rx)
+            .field("race_frame_rx", &self.race_frame_rx)
+            .field("ghost_frame_rx", &self.ghost_frame_rx)
             .field("tx", &self.tx)
             .field("redraw_tx", &self.redraw_tx)
             .field("gui", &self.gui)
@@ -148,6 +213,12 @@ impl std::fmt::Debug for Renderer {
             .field("last_save_time", &self.last_save_time)
             .field("zoom_changed", &self.zoom_changed)
             .field("resize_texture", &self.resize_texture)
+            .field("upscale_filter", &self.upscale_filter)
+            .field("texture_filter", &self.texture_filter)
+            .field("prescale", &self.prescale)
+            .field("frame_scale", &self.frame_scale)
+            .field("hd_scale", &self.hd_scale)
+            .field("generation", &self.generation)
             .finish_non_exhaustive()
     }
 }
@@ -168,11 +239,17 @@ impl std::fmt::Debug for Resources {
 }
 
 impl Renderer {
+    /// Maximum time between redraws of a viewport, even if nothing has changed.
+    const MAX_IDLE_REDRAW_INTERVAL: Duration = Duration::from_millis(500);
+
     /// Initializes the renderer in a platform-agnostic way.
     pub fn new(
         tx: NesEventProxy,
         resources: Resources,
         frame_rx: BufReceiver<Frame, FrameRecycle>,
+        race_frame_rx: BufReceiver<Frame, FrameRecycle>,
+        ghost_frame_rx: BufReceiver<Frame, FrameRecycle>,
+        audio_tap: Arc<SampleTap>,
         cfg: &Config,
     ) -> anyhow::Result<Self> {
         let Resources {
@@ -222,6 +299,9 @@ impl Renderer {
         viewports.insert(viewport.ids.this, viewport);
 
         painter.set_shader(cfg.renderer.shader);
+        painter.set_color_blind_mode(cfg.renderer.color_blind_mode);
+        painter.set_transition(cfg.renderer.transition);
+        painter.set_present_mode(cfg.renderer.present_mode);
         let render_state = painter.render_state_mut();
         let Some(render_state) = render_state else {
             anyhow::bail!("painter state is not initialized yet");
@@ -231,6 +311,7 @@ impl Renderer {
             ctx.clone(),
             tx.clone(),
             render_state,
+            audio_tap,
             cfg.clone(),
         )));
 
@@ -253,6 +334,8 @@ impl Renderer {
             state: Rc::new(RefCell::new(state)),
             painter: Rc::new(RefCell::new(painter)),
             frame_rx,
+            race_frame_rx,
+            ghost_frame_rx,
             tx,
             redraw_tx,
             ctx,
@@ -260,9 +343,19 @@ impl Renderer {
             accesskit,
             gui,
             first_frame: true,
+            black_frame_pending: false,
             last_save_time: Instant::now(),
             zoom_changed: false,
             resize_texture: false,
+            upscale_filter: cfg.renderer.upscale_filter,
+            upscaler: Upscaler::new(),
+            texture_filter: cfg.renderer.texture_filter,
+            prescale: 1,
+            prescaled: Vec::new(),
+            frame_scale: 1,
+            hd_scale: 1,
+            generation: 0,
+            painted: ViewportIdMap::default(),
         })
     }
 
@@ -357,23 +450,196 @@ impl Renderer {
             .unwrap_or(false)
     }
 
-    pub fn set_fullscreen(&mut self, fullscreen: bool, embed_viewports: bool) {
+    pub fn set_fullscreen(&mut self, cfg: &RendererConfig) {
         if feature!(OsViewports) {
-            self.ctx.set_embed_viewports(fullscreen || embed_viewports);
+            self.ctx
+                .set_embed_viewports(cfg.fullscreen || cfg.embed_viewports);
         }
         self.ctx
             .send_viewport_cmd_to(ViewportId::ROOT, ViewportCommand::Focus);
-        self.ctx
-            .send_viewport_cmd_to(ViewportId::ROOT, ViewportCommand::Fullscreen(fullscreen));
+        if let Some(window) = self.root_window() {
+            window.set_fullscreen(
+                cfg.fullscreen
+                    .then(|| Self::fullscreen_mode(&window, &cfg.fullscreen_target)),
+            );
+        }
+    }
+
+    /// Resolve the monitor and mode to go fullscreen with, falling back to the monitor `window`
+    /// is on and a borderless window if the target is unavailable.
+    fn fullscreen_mode(window: &Window, target: &FullscreenTarget) -> Fullscreen {
+        let monitor = target
+            .monitor
+            .as_ref()
+            .and_then(|name| {
+                window
+                    .available_monitors()
+                    .find(|monitor| monitor.name().as_ref() == Some(name))
+            })
+            .or_else(|| window.current_monitor());
+        if target.exclusive {
+            let video_mode = monitor.as_ref().and_then(|monitor| {
+                let video_modes = monitor.video_modes();
+                match target.video_mode {
+                    Some(target) => video_modes
+                        .filter(|mode| Self::video_mode(mode) == target)
+                        .max_by_key(VideoModeHandle::bit_depth),
+                    None => video_modes.max_by_key(|mode| {
+                        let size = mode.size();
+                        (
+                            size.width * size.height,
+                            mode.refresh_rate_millihertz(),
+                            mode.bit_depth(),
+                        )
+                    }),
+                }
+            });
+            match video_mode {
+                Some(video_mode) => return Fullscreen::Exclusive(video_mode),
+                None => warn!("no matching video mode for exclusive fullscreen, using borderless"),
+            }
+        }
+        Fullscreen::Borderless(monitor)
+    }
+
+    fn video_mode(mode: &VideoModeHandle) -> VideoMode {
+        let size = mode.size();
+        VideoMode {
+            width: size.width,
+            height: size.height,
+            refresh_rate_millihertz: mode.refresh_rate_millihertz(),
+        }
+    }
+
+    /// The current geometry of the main window to save, keeping the `previous` position and size
+    /// while fullscreen, minimized or maximized so the window can be restored to them.
+    pub fn window_geometry(&self, previous: Option<&WindowGeometry>) -> Option<WindowGeometry> {
+        if !feature!(WindowGeometry) {
+            return None;
+        }
+        let Some(window) = self.root_window() else {
+            return previous.cloned();
+        };
+        if window.fullscreen().is_some() || window.is_minimized() == Some(true) {
+            return previous.cloned();
+        }
+
+        let maximized = window.is_maximized();
+        let monitor = window.current_monitor().and_then(|monitor| monitor.name());
+        if let Some(previous) = previous.filter(|_| maximized) {
+            return Some(WindowGeometry {
+                maximized,
+                monitor,
+                ..previous.clone()
+            });
+        }
+        let size = window.inner_size();
+        Some(WindowGeometry {
+            position: window
+                .outer_position()
+                .ok()
+                .map(|position| (position.x, position.y)),
+            size: (size.width, size.height),
+            maximized,
+            monitor,
+        })
+    }
+
+    /// Apply saved window `geometry` to `builder`. The position is only restored if it's still
+    /// on a connected monitor.
+    fn restore_window_geometry(
+        ctx: &egui::Context,
+        event_loop: &ActiveEventLoop,
+        mut builder: ViewportBuilder,
+        geometry: &WindowGeometry,
+    ) -> ViewportBuilder {
+        // Inverse of the conversion to physical pixels in `create_window`
+        let pixels_per_point = ctx.zoom_factor() * Self::native_pixels_per_point(event_loop);
+        let (width, height) = geometry.size;
+        builder = builder
+            .with_inner_size(Vec2::new(width as f32, height as f32) / pixels_per_point)
+            .with_maximized(geometry.maximized);
+
+        if let Some((x, y)) = geometry.position {
+            let on_monitor = |monitor: &MonitorHandle| {
+                let position = monitor.position();
+                let size = monitor.size();
+                (position.x..position.x + size.width as i32).contains(&x)
+                    && (position.y..position.y + size.height as i32).contains(&y)
+            };
+            let mut monitors = event_loop.available_monitors();
+            let connected = match &geometry.monitor {
+                Some(name) => monitors
+                    .any(|monitor| monitor.name().as_ref() == Some(name) && on_monitor(&monitor)),
+                None => monitors.any(|monitor| on_monitor(&monitor)),
+            };
+            if connected {
+                builder = builder.with_position(egui::pos2(x as f32, y as f32) / pixels_per_point);
+            }
+        }
+
+        builder
+    }
+
+    /// Connected monitors that can be chosen to go fullscreen on.
+    pub fn monitors(&self) -> Vec<MonitorInfo> {
+        let Some(window) = self.root_window() else {
+            return Vec::new();
+        };
+        let current = window.current_monitor();
+        window
+            .available_monitors()
+            .filter_map(|monitor| MonitorInfo::new(&monitor, current.as_ref()))
+            .collect()
+    }
+
+    pub fn mouse_captured(&self) -> bool {
+        self.gui.borrow().mouse_capture.is_some()
+    }
+
+    /// Lock and hide the cursor over the main window for relative mouse input, returning whether
+    /// the capture changed.
+    pub fn set_mouse_captured(&mut self, captured: bool) -> bool {
+        let Some(window) = self.root_window() else {
+            return false;
+        };
+        let mut gui = self.gui.borrow_mut();
+        if gui.mouse_capture.is_some() == captured {
+            return false;
+        }
+
+        if captured {
+            // Not all platforms support locking the cursor in place, so fall back to confining it
+            // to the window
+            if let Err(err) = window
+                .set_cursor_grab(CursorGrabMode::Locked)
+                .or_else(|_| window.set_cursor_grab(CursorGrabMode::Confined))
+            {
+                warn!("failed to capture mouse: {err:?}");
+                return false;
+            }
+            gui.mouse_capture = Some(MouseCapture::new(gui.nes_frame.center()));
+        } else {
+            if let Err(err) = window.set_cursor_grab(CursorGrabMode::None) {
+                warn!("failed to release mouse: {err:?}");
+            }
+            gui.mouse_capture = None;
+        }
+        window.set_cursor_visible(!captured);
+
+        true
     }
 
     pub fn set_embed_viewports(&mut self, embed: bool) {
         self.ctx.set_embed_viewports(embed);
     }
 
-    pub fn set_always_on_top(&mut self, always_on_top: bool) {
+    /// Update the window level of all viewports. Compact mode keeps only the main window on top.
+    pub fn set_always_on_top(&mut self, cfg: &RendererConfig) {
         let state = self.state.borrow();
         for viewport_id in state.viewports.keys() {
+            let always_on_top =
+                cfg.always_on_top || (*viewport_id == ViewportId::ROOT && cfg.compact_mode);
             self.ctx.send_viewport_cmd_to(
                 *viewport_id,
                 ViewportCommand::WindowLevel(if always_on_top {
@@ -413,6 +679,80 @@ impl Renderer {
         self.gui.borrow().loaded_rom.is_some()
     }
 
+    /// The refresh rate of the monitor the root window is currently on, if known.
+    pub fn refresh_rate(&self) -> Option<f32> {
+        self.root_window()
+            .and_then(|window| window.current_monitor())
+            .and_then(|monitor| monitor.refresh_rate_millihertz())
+            .map(|millihertz| millihertz as f32 / 1000.0)
+    }
+
+    pub fn big_picture_open(&self) -> bool {
+        self.gui.borrow().big_picture.open()
+    }
+
+    /// Translate a controller button press into `egui` navigation while Big Picture mode is open.
+    pub fn on_big_picture_button(&mut self, button: gilrs::Button) {
+        let Some(key) = self.gui.borrow_mut().big_picture.on_button(button) else {
+            return;
+        };
+        self.push_nav_key(ViewportId::ROOT, key);
+    }
+
+    pub fn modal_open(&self) -> bool {
+        self.gui.borrow().modal_open()
+    }
+
+    /// Translate a controller button press into `egui` navigation while a dialog is open.
+    pub fn on_modal_button(&mut self, button: gilrs::Button) {
+        let Some(mut key) = gamepad_nav_key(button) else {
+            return;
+        };
+        // Arrow keys only move focus between widgets, so focus the first one instead
+        let arrow = matches!(
+            key,
+            egui::Key::ArrowUp
+                | egui::Key::ArrowDown
+                | egui::Key::ArrowLeft
+                | egui::Key::ArrowRight
+        );
+        if arrow && self.ctx.memory(|m| m.focused()).is_none() {
+            key = egui::Key::Tab;
+        }
+        let viewport_id = {
+            let gui = self.gui.borrow();
+            if gui.keybinds.wants_gamepad_nav() {
+                gui.keybinds.id()
+            } else {
+                ViewportId::ROOT
+            }
+        };
+        self.push_nav_key(viewport_id, key);
+    }
+
+    /// Queue a press and release of `key` for the given viewport, falling back to the root
+    /// viewport when it's embedded.
+    fn push_nav_key(&mut self, viewport_id: ViewportId, key: egui::Key) {
+        let mut state = self.state.borrow_mut();
+        let viewport_id = if state.viewports.contains_key(&viewport_id) {
+            viewport_id
+        } else {
+            ViewportId::ROOT
+        };
+        if let Some(viewport) = state.viewports.get_mut(&viewport_id) {
+            for pressed in [true, false] {
+                viewport.raw_input.events.push(egui::Event::Key {
+                    key,
+                    physical_key: None,
+                    pressed,
+                    repeat: false,
+                    modifiers: egui::Modifiers::NONE,
+                });
+            }
+        }
+        self.ctx.request_repaint();
+    }
+
     pub fn add_message<S>(&mut self, ty: MessageType, text: S)
     where
         S: Into<String>,
@@ -423,7 +763,26 @@ impl Renderer {
     pub fn on_error(&mut self, err: anyhow::Error) {
         error!("error: {err:?}");
         self.tx.event(EmulationEvent::RunState(RunState::Paused));
-        self.gui.borrow_mut().error = Some(err.to_string());
+        let painter = self.painter.borrow();
+        let adapter_info = painter.render_state().map(|state| &state.adapter_info);
+        self.gui
+            .borrow_mut()
+            .error_report
+            .open(Report::from_error(&err, adapter_info));
+    }
+
+    pub fn on_emulation_panic(&mut self, message: &str, backtrace: &str) {
+        let painter = self.painter.borrow();
+        let adapter_info = painter.render_state().map(|state| &state.adapter_info);
+        self.gui.borrow_mut().error_report.open(Report::new(
+            "Emulation Crashed",
+            vec![
+                message.to_string(),
+                "the emulation thread panicked and the ROM was unloaded".to_string(),
+            ],
+            (!backtrace.is_empty()).then(|| backtrace.to_string()),
+            adapter_info,
+        ));
     }
 
     pub fn load(ctx: &egui::Context, cfg: &Config) -> anyhow::Result<()> {
@@ -480,15 +839,31 @@ impl Renderer {
         let mut builder = egui::ViewportBuilder::default()
             .with_title(Config::WINDOW_TITLE)
             .with_visible(false) // hide until first frame is rendered. required by AccessKit
-            .with_fullscreen(cfg.renderer.fullscreen)
             .with_active(true)
             .with_resizable(true)
             .with_inner_size(window_size)
             .with_min_inner_size(Vec2::new(Ppu::WIDTH as f32, Ppu::HEIGHT as f32));
-        if cfg.renderer.always_on_top {
+        if cfg.renderer.always_on_top || cfg.renderer.compact_mode {
             builder = builder.with_always_on_top();
         }
+        if cfg.renderer.compact_mode {
+            builder = builder.with_resizable(false);
+        }
+        if let Some(geometry) = cfg
+            .renderer
+            .window_geometry
+            .as_ref()
+            .filter(|_| feature!(WindowGeometry))
+        {
+            builder = Self::restore_window_geometry(&ctx, event_loop, builder, geometry);
+        }
         let window = Arc::new(Self::create_window(&ctx, event_loop, builder)?);
+        if cfg.renderer.fullscreen {
+            window.set_fullscreen(Some(Self::fullscreen_mode(
+                &window,
+                &cfg.renderer.fullscreen_target,
+            )));
+        }
         window.set_theme(Some(if cfg.renderer.dark_theme {
             Theme::Dark
         } else {
@@ -517,12 +892,8 @@ impl Renderer {
         Ok((ctx, window, painter_rx))
     }
 
-    pub fn create_window(
-        ctx: &egui::Context,
-        event_loop: &ActiveEventLoop,
-        builder: ViewportBuilder,
-    ) -> anyhow::Result<Window> {
-        let native_pixels_per_point = event_loop
+    fn native_pixels_per_point(event_loop: &ActiveEventLoop) -> f32 {
+        event_loop
             .primary_monitor()
             .or_else(|| event_loop.available_monitors().next())
             .map_or_else(
@@ -533,9 +904,16 @@ impl Renderer {
                     1.0
                 },
                 |m| m.scale_factor() as f32,
-            );
+            )
+    }
+
+    pub fn create_window(
+        ctx: &egui::Context,
+        event_loop: &ActiveEventLoop,
+        builder: ViewportBuilder,
+    ) -> anyhow::Result<Window> {
         let zoom_factor = ctx.zoom_factor();
-        let pixels_per_point = zoom_factor * native_pixels_per_point;
+        let pixels_per_point = zoom_factor * Self::native_pixels_per_point(event_loop);
 
         let ViewportBuilder {
             title,
@@ -1037,7 +1415,12 @@ impl Renderer {
 
         if self.first_frame {
             self.initialize()?;
-            self.resize_window(cfg);
+            // Keep the size restored from the last run instead of resizing to the window scale
+            if !feature!(WindowGeometry) || cfg.renderer.window_geometry.is_none() {
+                self.resize_window(cfg);
+            }
+            let monitors = self.monitors();
+            self.gui.borrow_mut().preferences.set_monitors(monitors);
         }
         self.initialize_all_windows(event_loop);
 
@@ -1049,6 +1432,11 @@ impl Renderer {
             return Ok(());
         };
 
+        if viewport_id == ViewportId::ROOT && std::mem::take(&mut self.black_frame_pending) {
+            self.painter.borrow_mut().paint_black(viewport_id);
+            return Ok(());
+        }
+
         #[cfg(feature = "profiling")]
         puffin::GlobalProfiler::lock().new_frame();
 
@@ -1101,6 +1489,7 @@ impl Renderer {
 
         // Copy NES frame buffer before drawing UI because a UI interaction might cause a texture
         // resize tied to a configuration change.
+        let mut new_frame = false;
         if viewport_id == ViewportId::ROOT {
             if let Some(render_state) = &self.painter.borrow().render_state() {
                 let mut frame_buffer = self.frame_rx.try_recv_ref();
@@ -1109,17 +1498,53 @@ impl Renderer {
                     frame_buffer = self.frame_rx.try_recv_ref();
                 }
                 match frame_buffer {
+                    Ok(frame_buffer) if frame_buffer.scale() != self.hd_scale => {
+                        // An HD pack was loaded or unloaded, so skip this frame until the texture
+                        // is resized on the next redraw
+                        self.frame_scale = frame_buffer.scale();
+                        self.generation = self.generation.wrapping_add(1);
+                        self.tx.event(RendererEvent::RequestRedraw {
+                            viewport_id,
+                            when: Instant::now(),
+                        });
+                    }
                     Ok(frame_buffer) => {
+                        new_frame = true;
+                        self.generation = self.generation.wrapping_add(1);
                         let gui = self.gui.borrow_mut();
                         let is_ntsc = gui.loaded_region().unwrap_or(cfg.deck.region).is_ntsc();
-                        gui.nes_texture.update(
-                            &render_state.queue,
-                            if cfg.renderer.hide_overscan && is_ntsc {
-                                &frame_buffer[OVERSCAN_TRIM..frame_buffer.len() - OVERSCAN_TRIM]
-                            } else {
-                                &frame_buffer
-                            },
-                        );
+                        let hd_scale = self.hd_scale as usize;
+                        let trim = OVERSCAN_TRIM * hd_scale * hd_scale;
+                        let frame = if cfg.renderer.hide_overscan && is_ntsc {
+                            &frame_buffer[trim..frame_buffer.len() - trim]
+                        } else {
+                            &frame_buffer
+                        };
+                        let width = Ppu::WIDTH * self.hd_scale;
+                        let height = frame.len() as u32 / (4 * width);
+                        // HD pack frames replace upscaling
+                        let (frame, scale) = if self.hd_scale > 1 {
+                            (frame, 1)
+                        } else {
+                            (
+                                self.upscaler
+                                    .upscale(self.upscale_filter, frame, width, height),
+                                self.upscale_filter.scale(),
+                            )
+                        };
+                        let frame = if self.prescale > 1 {
+                            texture::scale_nearest(
+                                frame,
+                                width * scale,
+                                height * scale,
+                                self.prescale,
+                                &mut self.prescaled,
+                            );
+                            &self.prescaled
+                        } else {
+                            frame
+                        };
+                        gui.nes_texture.update(&render_state.queue, frame);
                         // self.nametables_texture.update_partial(
                         //     &render_state.queue,
                         //     &frame_buffer,
@@ -1138,8 +1563,16 @@ impl Renderer {
                 }
             }
         }
+        self.update_secondary_textures(viewport_id, cfg);
+
+        // Nothing has changed since this viewport was last painted (e.g. paused or sitting in a
+        // menu), so skip running and tessellating the UI
+        if self.is_idle(viewport_id, &raw_input) {
+            return Ok(());
+        }
 
         // Mutated by accesskit below on platforms that support it
+        let render_start = Instant::now();
         #[allow(unused_mut)]
         let mut output = self.ctx.run(raw_input, |ctx| {
             match &viewport_ui_cb {
@@ -1174,6 +1607,9 @@ impl Renderer {
 
             let clipped_primitives = self.ctx.tessellate(output.shapes, output.pixels_per_point);
 
+            self.painted
+                .insert(viewport_id, (self.generation, Instant::now()));
+
             window.pre_present_notify();
             self.painter.borrow_mut().paint(
                 viewport_id,
@@ -1181,6 +1617,23 @@ impl Renderer {
                 &clipped_primitives,
                 &output.textures_delta,
             );
+            // Follow each NES frame with a black frame on the next vertical blank
+            if new_frame && cfg.renderer.black_frame_insertion {
+                self.black_frame_pending = true;
+                self.ctx.request_repaint_of(ViewportId::ROOT);
+            }
+            if viewport_id == ViewportId::ROOT {
+                // Keep animating while a transition plays, even if emulation is paused
+                if self.painter.borrow().transition_active() {
+                    self.ctx.request_repaint_of(ViewportId::ROOT);
+                }
+                let mut gui = self.gui.borrow_mut();
+                if gui.perf_stats_open {
+                    let gpu_time = self.painter.borrow().gpu_time();
+                    gui.perf_history
+                        .push_render_time(render_start.elapsed(), gpu_time);
+                }
+            }
 
             if std::mem::take(&mut self.first_frame) {
                 window.set_visible(true);
@@ -1195,12 +1648,14 @@ impl Renderer {
             if feature!(ScreenReader) && self.ctx.options(|o| o.screen_reader) {
                 platform::speak_text(&output.platform_output.events_description());
             }
-            // TODO: Update accesskit when egui supports an updated version
-            // #[cfg(not(target_arch = "wasm32"))]
-            // if let Some(update) = output.platform_output.accesskit_update.take() {
-            //     tracing::trace!("update accesskit: {update:?}");
-            //     self.accesskit.update_if_active(|| update);
-            // }
+            // The adapter is only attached to the main window
+            #[cfg(not(target_arch = "wasm32"))]
+            if let Some(update) = output.platform_output.accesskit_update.take() {
+                if viewport_id == ViewportId::ROOT {
+                    tracing::trace!("update accesskit: {update:?}");
+                    self.accesskit.update_if_active(|| update);
+                }
+            }
 
             Self::handle_platform_output(viewport, output.platform_output);
             Self::handle_viewport_output(&self.ctx, viewports, output.viewport_output);
@@ -1211,6 +1666,8 @@ impl Renderer {
             // Prune dead viewports
             viewports.retain(|id, _| active_viewports_ids.contains(id));
             viewport_from_window.retain(|_, id| active_viewports_ids.contains(id));
+            self.painted
+                .retain(|id, _| active_viewports_ids.contains(id));
             self.painter
                 .borrow_mut()
                 .retain_surfaces(&active_viewports_ids);
@@ -1223,29 +1680,155 @@ impl Renderer {
         Ok(())
     }
 
+    /// Copy the latest frames from the race window and ghost replay instances, if any.
+    fn update_secondary_textures(&mut self, viewport_id: ViewportId, cfg: &Config) {
+        let painter = self.painter.borrow();
+        let Some(render_state) = painter.render_state() else {
+            return;
+        };
+        let gui = self.gui.borrow();
+        let is_ntsc = gui.loaded_region().unwrap_or(cfg.deck.region).is_ntsc();
+        let mut updated = false;
+        // The race window may be embedded in the root viewport, so update its texture from either
+        if viewport_id == ViewportId::ROOT || viewport_id == gui.race.id() {
+            updated |= Self::update_from_latest_frame(
+                &self.race_frame_rx,
+                &gui.race.texture,
+                &render_state.queue,
+                cfg.renderer.hide_overscan && is_ntsc,
+            );
+        }
+        if viewport_id == ViewportId::ROOT {
+            updated |= Self::update_from_latest_frame(
+                &self.ghost_frame_rx,
+                &gui.ghost_texture,
+                &render_state.queue,
+                cfg.renderer.hide_overscan && is_ntsc,
+            );
+        }
+        drop(gui);
+        drop(painter);
+        if updated {
+            self.generation = self.generation.wrapping_add(1);
+        }
+    }
+
+    /// Drain `frame_rx`, updating `texture` with the most recent frame. Returns whether a frame
+    /// was received.
+    fn update_from_latest_frame(
+        frame_rx: &BufReceiver<Frame, FrameRecycle>,
+        texture: &texture::Texture,
+        queue: &wgpu::Queue,
+        trim_overscan: bool,
+    ) -> bool {
+        let mut latest = None;
+        while let Ok(frame_buffer) = frame_rx.try_recv_ref() {
+            latest = Some(frame_buffer);
+        }
+        let Some(frame_buffer) = latest else {
+            return false;
+        };
+        texture.update(
+            queue,
+            if trim_overscan {
+                &frame_buffer[OVERSCAN_TRIM..frame_buffer.len() - OVERSCAN_TRIM]
+            } else {
+                &frame_buffer
+            },
+        );
+        true
+    }
+
+    /// Whether a viewport can skip redrawing because nothing has changed since it was last
+    /// painted.
+    fn is_idle(&self, viewport_id: ViewportId, raw_input: &egui::RawInput) -> bool {
+        let Some((generation, painted_time)) = self.painted.get(&viewport_id) else {
+            return false;
+        };
+        *generation == self.generation
+            // Some platforms expect a redraw to restore damaged window contents, so never skip
+            // for too long
+            && painted_time.elapsed() < Self::MAX_IDLE_REDRAW_INTERVAL
+            && raw_input.events.is_empty()
+            && raw_input.hovered_files.is_empty()
+            && raw_input.dropped_files.is_empty()
+            && !self.ctx.has_requested_repaint_for(&viewport_id)
+            && !self.gui.borrow().keybinds.wants_input()
+    }
+
+    /// Mark all viewports as needing to be redrawn.
+    pub(crate) fn invalidate(&mut self) {
+        self.generation = self.generation.wrapping_add(1);
+    }
+
     fn handle_resize(&mut self, viewport_id: ViewportId, cfg: &Config) {
         #[cfg(feature = "profiling")]
         puffin::profile_function!();
 
-        if viewport_id == ViewportId::ROOT && self.resize_texture {
-            tracing::debug!("resizing window and texture");
+        if viewport_id != ViewportId::ROOT {
+            return;
+        }
+
+        // Sharp bilinear filtering needs a larger texture as the frame is drawn larger, which
+        // only recreates the texture without resizing the window
+        let texture_filter = cfg.renderer.texture_filter;
+        let texture_scale = Self::texture_scale(cfg.renderer.upscale_filter, self.frame_scale);
+        let prescale = texture_filter.prescale(self.gui.borrow().nes_display_scale, texture_scale);
+        let resample_texture = texture_filter != self.texture_filter
+            || prescale != self.prescale
+            || self.frame_scale != self.hd_scale;
 
+        if self.resize_texture || resample_texture {
             self.tx.event(EmulationEvent::RequestFrame);
-            self.resize_window(cfg);
+            if self.resize_texture {
+                tracing::debug!("resizing window and texture");
+                self.resize_window(cfg);
+            } else {
+                tracing::debug!("resampling texture: {texture_filter:?} at {prescale}x");
+            }
 
             if let Some(render_state) = self.painter.borrow_mut().render_state_mut() {
                 let texture_size = cfg.texture_size();
                 let mut gui = self.gui.borrow_mut();
                 let aspect_ratio = gui.aspect_ratio();
-                gui.nes_texture
+                self.upscale_filter = cfg.renderer.upscale_filter;
+                self.texture_filter = texture_filter;
+                self.prescale = prescale;
+                self.hd_scale = self.frame_scale;
+                gui.nes_texture.resize_with_scale(
+                    render_state,
+                    texture_size,
+                    texture_scale * prescale,
+                    texture_filter,
+                    aspect_ratio,
+                );
+                gui.race
+                    .texture
+                    .resize(render_state, texture_size, aspect_ratio);
+                gui.ghost_texture
                     .resize(render_state, texture_size, aspect_ratio);
             }
             self.resize_texture = false;
         }
     }
 
+    /// Scale of the frames drawn to `nes_texture` before any prescaling, either from the upscale
+    /// filter or the HD pack scale when HD pack frames are received.
+    const fn texture_scale(upscale_filter: UpscaleFilter, frame_scale: u32) -> u32 {
+        if frame_scale > 1 {
+            frame_scale
+        } else {
+            upscale_filter.scale()
+        }
+    }
+
     fn resize_window(&self, cfg: &Config) {
         if !self.fullscreen() {
+            if cfg.renderer.compact_mode {
+                self.resize_compact_window(cfg);
+                return;
+            }
+
             let desired_window_size = self.window_size(cfg);
 
             // On some platforms, e.g. wasm, window width is constrained by the
@@ -1271,6 +1854,24 @@ impl Renderer {
             }
         }
     }
+
+    /// Size the main window to an exact integer multiple of the NES frame, in physical pixels, so
+    /// that every NES pixel maps to the same number of screen pixels.
+    fn resize_compact_window(&self, cfg: &Config) {
+        let Some(window) = self.root_window() else {
+            return;
+        };
+        let scale = cfg.renderer.scale.round().max(1.0);
+        let aspect_ratio = self.gui.borrow().aspect_ratio();
+        let texture_size = cfg.texture_size();
+        let frame_size = cfg.renderer.rotation.rotate_size(Vec2::new(
+            (texture_size.x * aspect_ratio * scale).round(),
+            texture_size.y * scale,
+        ));
+        let size = PhysicalSize::new(frame_size.x as u32, frame_size.y as u32);
+        tracing::debug!("resizing compact window: {size:?}");
+        let _ = window.request_inner_size(size);
+    }
 }
 
 impl Viewport {
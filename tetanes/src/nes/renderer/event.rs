@@ -2,7 +2,7 @@ use crate::{
     feature,
     nes::{
         config::Config,
-        event::{ConfigEvent, NesEvent, RendererEvent, Response, UiEvent},
+        event::{ConfigEvent, EmulationEvent, NesEvent, RendererEvent, Response, UiEvent},
         input::{Gamepads, Input},
         renderer::{
             gui::{lib::pixels_per_point, Gui},
@@ -27,6 +27,8 @@ impl Renderer {
         #[cfg(feature = "profiling")]
         puffin::profile_function!();
 
+        self.invalidate();
+
         {
             let painter = self.painter.borrow();
             if let Some(render_state) = painter.render_state() {
@@ -39,6 +41,7 @@ impl Renderer {
                 RendererEvent::ViewportResized(_) => self.resize_window(cfg),
                 RendererEvent::ResizeTexture => self.resize_texture = true,
                 RendererEvent::RomLoaded(_) => {
+                    self.painter.borrow_mut().start_transition();
                     let state = self.state.borrow();
                     if state.focused != Some(ViewportId::ROOT) {
                         self.ctx
@@ -48,12 +51,11 @@ impl Renderer {
                 _ => (),
             },
             NesEvent::Config(event) => match event {
-                ConfigEvent::DarkTheme(enabled) => {
-                    self.ctx.set_visuals(if *enabled {
-                        Gui::dark_theme()
-                    } else {
-                        Gui::light_theme()
-                    });
+                ConfigEvent::AccentColor(_)
+                | ConfigEvent::DarkTheme(_)
+                | ConfigEvent::HighContrast(_)
+                | ConfigEvent::Theme(_) => {
+                    self.ctx.set_visuals(Gui::theme(&cfg.renderer));
                 }
                 ConfigEvent::EmbedViewports(embed) => {
                     if feature!(OsViewports) {
@@ -66,50 +68,76 @@ impl Renderer {
                             .set_embed_viewports(*fullscreen || cfg.renderer.embed_viewports);
                     }
                     if self.fullscreen() != *fullscreen {
-                        self.ctx
-                            .send_viewport_cmd_to(ViewportId::ROOT, ViewportCommand::Focus);
-                        self.ctx.send_viewport_cmd_to(
-                            ViewportId::ROOT,
-                            ViewportCommand::Fullscreen(*fullscreen),
-                        );
+                        self.set_fullscreen(&cfg.renderer);
                     }
                 }
-                ConfigEvent::Region(_) | ConfigEvent::HideOverscan(_) | ConfigEvent::Scale(_) => {
+                ConfigEvent::FullscreenTarget(_) => {
+                    // Switch to the new monitor or video mode immediately
+                    if self.fullscreen() {
+                        self.set_fullscreen(&cfg.renderer);
+                    }
+                }
+                ConfigEvent::CompactMode(compact) => {
+                    if let Some(window) = self.root_window() {
+                        window.set_resizable(!*compact);
+                    }
+                    self.resize_texture = true;
+                }
+                ConfigEvent::Region(_)
+                | ConfigEvent::HideOverscan(_)
+                | ConfigEvent::Rotation(_)
+                | ConfigEvent::Scale(_)
+                | ConfigEvent::UpscaleFilter(_) => {
                     self.resize_texture = true;
                 }
                 ConfigEvent::Shader(shader) => {
                     self.painter.borrow_mut().set_shader(*shader);
                 }
+                ConfigEvent::ColorBlindMode(mode) => {
+                    self.painter.borrow_mut().set_color_blind_mode(*mode);
+                }
+                ConfigEvent::Transition(transition) => {
+                    self.painter.borrow_mut().set_transition(*transition);
+                }
+                ConfigEvent::PresentMode(mode) => {
+                    self.painter.borrow_mut().set_present_mode(*mode);
+                }
+                ConfigEvent::FontSize(font_size) => Gui::set_font_size(&self.ctx, *font_size),
+                ConfigEvent::Zoom(zoom) => self.ctx.set_zoom_factor(*zoom),
                 _ => (),
             },
-            // TODO: Update accesskit when egui supports an updated version
-            // #[cfg(not(target_arch = "wasm32"))]
-            // NesEvent::AccessKit { window_id, event } => {
-            //     use crate::nes::event::AccessKitWindowEvent;
-            //     if let Some(viewport_id) = self.viewport_id_for_window(*window_id) {
-            //         let mut state = self.state.borrow_mut();
-            //         if let Some(viewport) = state.viewports.get_mut(&viewport_id) {
-            //             match event {
-            //                 AccessKitWindowEvent::InitialTreeRequested => {
-            //                     self.ctx.enable_accesskit();
-            //                     let update = self.ctx.accesskit_placeholder_tree_update();
-            //                     self.accesskit.update_if_active(|| update);
-            //                 }
-            //                 AccessKitWindowEvent::ActionRequested(request) => {
-            //                     viewport
-            //                         .raw_input
-            //                         .events
-            //                         .push(egui::Event::AccessKitActionRequest(request.clone()));
-            //                 }
-            //                 AccessKitWindowEvent::AccessibilityDeactivated => {
-            //                     self.ctx.disable_accesskit();
-            //                 }
-            //             }
-
-            //             self.ctx.request_repaint_of(viewport_id);
-            //         };
-            //     }
-            // }
+            #[cfg(not(target_arch = "wasm32"))]
+            NesEvent::AccessKit { window_id, event } => {
+                use crate::nes::event::AccessKitWindowEvent;
+                if let Some(viewport_id) = self.viewport_id_for_window(*window_id) {
+                    let mut state = self.state.borrow_mut();
+                    if let Some(viewport) = state.viewports.get_mut(&viewport_id) {
+                        match event {
+                            AccessKitWindowEvent::InitialTreeRequested => {
+                                // The full tree is sent with the next repaint
+                                self.ctx.enable_accesskit();
+                            }
+                            AccessKitWindowEvent::ActionRequested(request) => {
+                                viewport
+                                    .raw_input
+                                    .events
+                                    .push(egui::Event::AccessKitActionRequest(request.clone()));
+                            }
+                            AccessKitWindowEvent::AccessibilityDeactivated => {
+                                self.ctx.disable_accesskit();
+                            }
+                        }
+
+                        self.ctx.request_repaint_of(viewport_id);
+                    };
+                }
+            }
+            NesEvent::Emulation(
+                EmulationEvent::Reset(_)
+                | EmulationEvent::LoadState(_)
+                | EmulationEvent::LoadStateRing
+                | EmulationEvent::UndoLoadState,
+            ) => self.painter.borrow_mut().start_transition(),
             _ => (),
         }
     }
@@ -123,6 +151,10 @@ impl Renderer {
             return Response::default();
         };
 
+        if *event != WindowEvent::RedrawRequested {
+            self.invalidate();
+        }
+
         let State {
             viewports, focused, ..
         } = &mut *self.state.borrow_mut();
@@ -595,7 +627,9 @@ impl Renderer {
         #[cfg(feature = "profiling")]
         puffin::profile_function!();
 
-        if self.gui.borrow().keybinds.wants_input() && gamepads.has_events() {
+        let gui = self.gui.borrow();
+        if gui.keybinds.wants_input() && !gui.keybinds.wants_gamepad_nav() && gamepads.has_events()
+        {
             Response {
                 consumed: true,
                 repaint: true,
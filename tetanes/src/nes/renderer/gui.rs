@@ -1,9 +1,15 @@
 use crate::{
-    feature,
+    feature, logging,
     nes::{
         action::{Debug, DebugKind, DebugStep, Feature, Setting, Ui as UiAction},
-        config::{Config, RendererConfig},
-        emulation::FrameStats,
+        audio::SampleTap,
+        config::{Config, FontSize, RendererConfig, ThemePreset},
+        emulation::{
+            input_macro::Macros,
+            race::RaceInputs,
+            sync::{SyncChoice, SyncConflict},
+            FrameStats,
+        },
         event::{
             ConfigEvent, DebugEvent, EmulationEvent, NesEvent, NesEventProxy, RendererEvent,
             Response, UiEvent,
@@ -11,29 +17,45 @@ use crate::{
         input::Gamepads,
         renderer::{
             gui::{
+                audio_visualizer::AudioVisualizer,
+                big_picture::BigPicture,
+                error_report::ErrorReport,
+                game_notes::GameNotes,
                 keybinds::Keybinds,
                 lib::{
                     cursor_to_zapper, input_down, ShortcutText, ShowShortcut, ToggleValue,
                     ViewportOptions,
                 },
+                log_viewer::{levels as log_levels, LogViewer},
+                mapper_inspector::MapperInspector,
+                message_history::MessageHistory,
+                perf_graph::PerfHistory,
                 ppu_viewer::PpuViewer,
                 preferences::Preferences,
+                profiler::Profiler,
+                race::RaceWindow,
+                ram_watch::RamWatchWindow,
+                save_preview::SaveStatePreviews,
+                statistics::Statistics,
+                unsupported_mapper::{MapperStats, UnsupportedMapper},
             },
             painter::RenderState,
-            texture::Texture,
+            texture::{Rotation, Texture},
         },
         rom::{RomAsset, HOMEBREW_ROMS},
         version::Version,
         RunState,
     },
+    platform,
     sys::{info::System, SystemInfo},
 };
 use egui::{
     hex_color, include_image, menu,
     style::{HandleShape, Selection, TextCursorStyle, WidgetVisuals},
-    Align, Button, CentralPanel, Color32, Context, CursorIcon, Direction, FontData,
-    FontDefinitions, FontFamily, Frame, Grid, Image, Layout, Pos2, Rect, RichText, Rounding,
-    ScrollArea, Sense, Stroke, TopBottomPanel, Ui, ViewportClass, Visuals,
+    Align, Align2, Button, CentralPanel, Color32, Context, CursorIcon, Direction, FontData,
+    FontDefinitions, FontFamily, Frame, Grid, Image, Key, Layout, Pos2, Rect, RichText, Rounding,
+    ScrollArea, Sense, Slider, Stroke, TextEdit, TopBottomPanel, Ui, UiBuilder, Vec2,
+    ViewportClass, Visuals,
 };
 use serde::{Deserialize, Serialize};
 use std::sync::{
@@ -42,26 +64,69 @@ use std::sync::{
 };
 use tetanes_core::{
     action::Action as DeckAction,
+    cart::header_fix::HeaderIssue,
     common::{NesRegion, ResetKind},
     control_deck::LoadedRom,
+    debug::Breakpoints,
+    input::{FourPlayer, Vaus},
+    mapper::bandai_fcg::BarcodeReader,
     ppu::Ppu,
     time::{Duration, Instant},
 };
 use tracing::{error, info, warn};
 use winit::event::WindowEvent;
 
+mod audio_visualizer;
+mod big_picture;
+pub mod error_report;
+mod game_notes;
 mod keybinds;
 pub mod lib;
+mod log_viewer;
+mod mapper_inspector;
+mod message_history;
+mod perf_graph;
+#[cfg(not(target_arch = "wasm32"))]
+mod phone_pad;
 mod ppu_viewer;
 mod preferences;
+mod profiler;
+mod race;
+mod ram_watch;
+mod save_preview;
+mod statistics;
+pub mod unsupported_mapper;
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub enum Menu {
     About,
+    AudioVisualizer,
+    BigPicture,
     Keybinds,
+    MapperInspector,
     PerfStats,
     PpuViewer,
     Preferences,
+    Profiler,
+    RamWatch,
+    Shortcuts,
+}
+
+impl Menu {
+    /// Whether this menu is hidden in kiosk mode because it can change settings or inspect
+    /// emulation.
+    #[must_use]
+    pub const fn kiosk_locked(self) -> bool {
+        matches!(
+            self,
+            Self::Keybinds
+                | Self::MapperInspector
+                | Self::PpuViewer
+                | Self::Preferences
+                | Self::Profiler
+                | Self::RamWatch
+        )
+    }
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -71,6 +136,39 @@ pub enum MessageType {
     Error,
 }
 
+impl MessageType {
+    /// A message as it should be read aloud, since the icons shown aren't.
+    pub fn describe(&self, text: &str) -> String {
+        match self {
+            Self::Info => text.to_string(),
+            Self::Warn => format!("Warning: {text}"),
+            Self::Error => format!("Error: {text}"),
+        }
+    }
+}
+
+/// Relative mouse input while the cursor is captured by the main window.
+#[derive(Debug, Copy, Clone)]
+#[must_use]
+pub struct MouseCapture {
+    /// Virtual cursor position over the NES frame, used to aim the Zapper.
+    cursor: Pos2,
+    /// Paddle motion smaller than a whole step, carried over to the next frame.
+    vaus_motion: f32,
+    vaus_button: bool,
+}
+
+impl MouseCapture {
+    pub const fn new(cursor: Pos2) -> Self {
+        Self {
+            cursor,
+            vaus_motion: 0.0,
+            // Treat the click that started the capture as held, so it doesn't also fire
+            vaus_button: true,
+        }
+    }
+}
+
 #[derive(Debug)]
 #[must_use]
 pub struct Gui {
@@ -80,53 +178,104 @@ pub struct Gui {
     pub tx: NesEventProxy,
     pub cfg: Config,
     pub nes_texture: Texture,
+    /// Physical pixels per NES pixel the frame was last drawn at, along its narrower axis.
+    pub nes_display_scale: f32,
+    /// Frames from a ghost replay, drawn over the live game.
+    pub ghost_texture: Texture,
+    pub ghost_loaded: bool,
+    pub ghost_opacity: f32,
     pub run_state: RunState,
     pub menu_height: f32,
     pub nes_frame: Rect,
+    /// Set while the mouse is captured for relative motion, e.g. for the Vaus paddle.
+    pub mouse_capture: Option<MouseCapture>,
     pub about_open: bool,
+    pub shortcuts_open: bool,
     pub gui_settings_open: Arc<AtomicBool>,
     #[cfg(debug_assertions)]
     pub gui_inspection_open: Arc<AtomicBool>,
     #[cfg(debug_assertions)]
     pub gui_memory_open: Arc<AtomicBool>,
     pub perf_stats_open: bool,
+    pub rom_info_open: bool,
     pub update_window_open: bool,
+    /// Four player adapter supported by the loaded ROM that the user is prompted to enable.
+    pub four_player_prompt: Option<FourPlayer>,
+    /// Barcode being entered for the barcode reader of the loaded ROM, if the dialog is open.
+    pub barcode_entry: Option<String>,
+    /// Save files changed on another device that the user is prompted to choose between.
+    pub sync_conflicts: Vec<SyncConflict>,
+    /// ROM with header problems that the user is prompted to repair before loading.
+    pub rom_header_issues: Option<(String, Vec<HeaderIssue>)>,
     pub version: Version,
     pub keybinds: Keybinds,
     pub preferences: Preferences,
+    /// Name entered for a new configuration profile in the Config menu.
+    pub new_config_profile: String,
     pub debugger_open: bool,
+    /// CPU conditions that pause emulation, for debugging.
+    pub breakpoints: Breakpoints,
     pub ppu_viewer: PpuViewer,
+    pub audio_visualizer: AudioVisualizer,
+    pub ram_watch: RamWatchWindow,
+    pub mapper_inspector: MapperInspector,
+    pub profiler: Profiler,
+    pub ram_watch_values: Vec<u16>,
+    pub save_previews: SaveStatePreviews,
+    pub race: RaceWindow,
+    pub big_picture: BigPicture,
     pub apu_mixer_open: bool,
     pub viewport_info_open: bool,
     pub replay_recording: bool,
     pub audio_recording: bool,
+    /// Input macro slot being recorded, if any.
+    pub macro_recording: Option<u8>,
     pub frame_stats: FrameStats,
+    pub perf_history: PerfHistory,
     pub messages: Vec<(MessageType, String, Instant)>,
+    pub message_history: MessageHistory,
+    pub game_notes: GameNotes,
+    pub statistics: Statistics,
+    #[cfg(not(target_arch = "wasm32"))]
+    pub phone_pad: phone_pad::PhonePadWindow,
     pub loaded_rom: Option<LoadedRom>,
     pub about_homebrew_rom_open: Option<RomAsset>,
     pub start: Instant,
     pub sys: System,
-    pub error: Option<String>,
+    pub error_report: ErrorReport,
+    pub unsupported_mapper: UnsupportedMapper,
+    pub log_viewer: LogViewer,
 }
 
 impl Gui {
     const MSG_TIMEOUT: Duration = Duration::from_secs(3);
     const MAX_MESSAGES: usize = 5;
     const NO_ROM_LOADED: &'static str = "No ROM is loaded.";
+    const DEFAULT_GHOST_OPACITY: f32 = 0.4;
 
     /// Create a `Gui` instance.
     pub fn new(
         ctx: Context,
         tx: NesEventProxy,
         render_state: &mut RenderState,
+        audio_tap: Arc<SampleTap>,
         cfg: Config,
     ) -> Self {
-        let nes_texture = Texture::new(
+        let nes_texture = Texture::with_scale(
             render_state,
             cfg.texture_size(),
+            cfg.renderer.upscale_filter.scale(),
+            cfg.renderer.texture_filter,
             cfg.deck.region.aspect_ratio(),
             Some("nes frame"),
         );
+        let ghost_texture = Texture::new(
+            render_state,
+            cfg.texture_size(),
+            cfg.deck.region.aspect_ratio(),
+            Some("ghost frame"),
+        );
+        let race = RaceWindow::new(tx.clone(), render_state, &cfg);
 
         Self {
             ctx,
@@ -135,33 +284,63 @@ impl Gui {
             tx: tx.clone(),
             cfg,
             nes_texture,
+            nes_display_scale: 1.0,
+            ghost_texture,
+            ghost_loaded: false,
+            ghost_opacity: Self::DEFAULT_GHOST_OPACITY,
             run_state: RunState::Running,
             menu_height: 0.0,
             nes_frame: Rect::ZERO,
+            mouse_capture: None,
             about_open: false,
+            shortcuts_open: false,
             gui_settings_open: Arc::new(AtomicBool::new(false)),
             #[cfg(debug_assertions)]
             gui_inspection_open: Arc::new(AtomicBool::new(false)),
             #[cfg(debug_assertions)]
             gui_memory_open: Arc::new(AtomicBool::new(false)),
             perf_stats_open: false,
+            rom_info_open: false,
             update_window_open: false,
+            four_player_prompt: None,
+            barcode_entry: None,
+            sync_conflicts: Vec::new(),
+            rom_header_issues: None,
             version: Version::new(),
             keybinds: Keybinds::new(tx.clone()),
-            preferences: Preferences::new(tx.clone()),
+            preferences: Preferences::new(tx.clone(), render_state.supported_present_modes()),
+            new_config_profile: String::new(),
             debugger_open: false,
-            ppu_viewer: PpuViewer::new(tx, render_state),
+            breakpoints: Breakpoints::empty(),
+            ppu_viewer: PpuViewer::new(tx.clone(), render_state),
+            audio_visualizer: AudioVisualizer::new(audio_tap),
+            ram_watch: RamWatchWindow::new(tx.clone()),
+            mapper_inspector: MapperInspector::new(tx.clone()),
+            profiler: Profiler::new(tx.clone()),
+            ram_watch_values: Vec::new(),
+            save_previews: SaveStatePreviews::default(),
+            race,
+            #[cfg(not(target_arch = "wasm32"))]
+            phone_pad: phone_pad::PhonePadWindow::new(tx.clone()),
+            big_picture: BigPicture::new(tx),
             apu_mixer_open: false,
             viewport_info_open: false,
             replay_recording: false,
             audio_recording: false,
+            macro_recording: None,
             frame_stats: FrameStats::new(),
+            perf_history: PerfHistory::default(),
             messages: Vec::new(),
+            message_history: MessageHistory::default(),
+            game_notes: GameNotes::default(),
+            statistics: Statistics::default(),
             loaded_rom: None,
             about_homebrew_rom_open: None,
             start: Instant::now(),
             sys: System::default(),
-            error: None,
+            error_report: ErrorReport::default(),
+            unsupported_mapper: UnsupportedMapper::default(),
+            log_viewer: LogViewer::default(),
         }
     }
 
@@ -189,14 +368,15 @@ impl Gui {
         puffin::profile_function!();
 
         match event {
-            NesEvent::Ui(UiEvent::UpdateAvailable(version)) => {
-                self.version.set_latest(version.clone());
+            NesEvent::Ui(UiEvent::UpdateAvailable(releases)) => {
+                self.version.set_releases(releases.clone());
                 self.update_window_open = true;
             }
             NesEvent::Emulation(event) => match event {
                 EmulationEvent::ReplayRecord(recording) => {
                     self.replay_recording = *recording;
                 }
+                EmulationEvent::ReplayRecordPath(_) => self.replay_recording = true,
                 EmulationEvent::AudioRecord(recording) => {
                     self.audio_recording = *recording;
                 }
@@ -208,6 +388,7 @@ impl Gui {
             NesEvent::Renderer(event) => match event {
                 RendererEvent::FrameStats(stats) => {
                     self.frame_stats = *stats;
+                    self.perf_history.push_frame_stats(stats);
                 }
                 RendererEvent::ShowMenubar(show) => {
                     // Toggling true is handled in the menu widget
@@ -216,19 +397,96 @@ impl Gui {
                     }
                 }
                 RendererEvent::ReplayLoaded => self.run_state = RunState::Running,
+                RendererEvent::GhostLoaded => {
+                    self.run_state = RunState::Running;
+                    self.ghost_loaded = true;
+                }
+                RendererEvent::GhostUnloaded => self.ghost_loaded = false,
+                RendererEvent::RaceStopped => self.race.stopped(),
+                RendererEvent::MacroRecording(slot) => self.macro_recording = *slot,
+                RendererEvent::RamWatchValues(values) => {
+                    self.ram_watch_values.clone_from(values);
+                }
+                RendererEvent::MapperState((mapper, writes, irqs)) => {
+                    self.mapper_inspector
+                        .update(mapper.clone(), writes.clone(), irqs.clone());
+                    self.ctx.request_repaint_of(self.mapper_inspector.id());
+                }
+                RendererEvent::ProfileReport(report) => {
+                    self.profiler.update(report.clone());
+                    self.ctx.request_repaint_of(self.profiler.id());
+                }
+                RendererEvent::SymbolsLoaded(symbols) => {
+                    self.profiler.load_symbols(symbols.clone());
+                    self.ctx.request_repaint_of(self.profiler.id());
+                }
+                RendererEvent::Playtime(playtime) => self.statistics.set_playtime(playtime.clone()),
+                RendererEvent::RomLibrary(roms) => self.big_picture.set_rom_library(roms.clone()),
+                RendererEvent::RomLibraryProgress(progress) => {
+                    self.big_picture.set_hash_progress(*progress);
+                }
+                RendererEvent::StateSaved(slot) => self.save_previews.invalidate(*slot),
+                RendererEvent::SyncConflicts(conflicts) => {
+                    for conflict in conflicts {
+                        self.sync_conflicts
+                            .retain(|existing| existing.entry.key != conflict.entry.key);
+                        self.sync_conflicts.push(conflict.clone());
+                    }
+                }
+                RendererEvent::RomHeaderIssues(rom) => {
+                    self.rom_header_issues = Some(rom.clone());
+                }
+                RendererEvent::UnsupportedMapper((name, mapper_num)) => {
+                    if self.cfg.renderer.mapper_stats {
+                        if let Err(err) = MapperStats::record(&self.cfg.paths, *mapper_num) {
+                            error!("failed to record unsupported mapper: {err:?}");
+                        }
+                    }
+                    self.unsupported_mapper.open(name.clone(), *mapper_num);
+                }
                 RendererEvent::RomUnloaded => {
+                    self.four_player_prompt = None;
+                    self.barcode_entry = None;
+                    self.macro_recording = None;
+                    self.race.stopped();
+                    self.save_previews.clear();
+                    self.ram_watch_values.clear();
+                    self.mapper_inspector.clear();
+                    self.profiler.clear();
+                    self.ghost_loaded = false;
                     self.run_state = RunState::Running;
                     self.loaded_rom = None;
+                    self.preferences.set_rom_name(None);
                     self.title = Config::WINDOW_TITLE.to_string();
                 }
                 RendererEvent::RomLoaded(rom) => {
+                    // Only prompt if the adapter wasn't already connected automatically
+                    self.four_player_prompt = rom
+                        .recommended_peripherals
+                        .filter(|_| rom.peripherals.is_none())
+                        .map(|peripherals| peripherals.four_player)
+                        .filter(|four_player| {
+                            *four_player != FourPlayer::Disabled
+                                && *four_player != self.cfg.deck.four_player
+                        });
+                    if !rom.barcode_reader {
+                        self.barcode_entry = None;
+                    }
+                    self.race.stopped();
+                    self.save_previews.clear();
+                    self.ghost_loaded = false;
                     self.run_state = RunState::Running;
                     self.title = format!("{} :: {}", Config::WINDOW_TITLE, rom.name);
                     self.loaded_rom = Some(rom.clone());
+                    self.preferences.set_rom_name(Some(rom.name.clone()));
                 }
+                RendererEvent::Menu(menu) if self.cfg.startup.kiosk() && menu.kiosk_locked() => {}
                 RendererEvent::Menu(menu) => match menu {
                     Menu::About => self.about_open = !self.about_open,
+                    Menu::AudioVisualizer => self.audio_visualizer.toggle_open(),
+                    Menu::BigPicture => self.big_picture.toggle_open(),
                     Menu::Keybinds => self.keybinds.toggle_open(),
+                    Menu::MapperInspector => self.mapper_inspector.toggle_open(),
                     Menu::PerfStats => {
                         self.perf_stats_open = !self.perf_stats_open;
                         self.tx
@@ -236,6 +494,9 @@ impl Gui {
                     }
                     Menu::PpuViewer => self.ppu_viewer.toggle_open(),
                     Menu::Preferences => self.preferences.toggle_open(),
+                    Menu::Profiler => self.profiler.toggle_open(),
+                    Menu::RamWatch => self.ram_watch.toggle_open(),
+                    Menu::Shortcuts => self.shortcuts_open = !self.shortcuts_open,
                 },
                 _ => (),
             },
@@ -257,6 +518,10 @@ impl Gui {
             MessageType::Warn => warn!("{text}"),
             MessageType::Error => error!("{text}"),
         }
+        if self.cfg.renderer.announce_messages {
+            platform::speak_text(&ty.describe(&text));
+        }
+        self.message_history.push(ty, text.clone());
         self.messages
             .push((ty, text, Instant::now() + Self::MSG_TIMEOUT));
     }
@@ -265,6 +530,16 @@ impl Gui {
         self.loaded_rom.as_ref().map(|rom| rom.region)
     }
 
+    /// Whether the Zapper is connected, either in the config or automatically for the loaded ROM.
+    pub fn zapper_connected(&self) -> bool {
+        self.cfg.deck.zapper
+            || self
+                .loaded_rom
+                .as_ref()
+                .and_then(|rom| rom.peripherals)
+                .is_some_and(|peripherals| peripherals.zapper)
+    }
+
     pub fn aspect_ratio(&self) -> f32 {
         let region = self
             .cfg
@@ -278,10 +553,14 @@ impl Gui {
     }
 
     pub fn prepare(&mut self, gamepads: &Gamepads, cfg: &Config) {
+        if cfg.emulation.save_slot != self.cfg.emulation.save_slot {
+            self.save_previews.select(cfg.emulation.save_slot);
+        }
         self.cfg = cfg.clone();
         self.preferences.prepare(&self.cfg);
         self.keybinds.prepare(gamepads, &self.cfg);
         self.ppu_viewer.prepare(&self.cfg);
+        self.ram_watch.prepare(&self.cfg, &self.ram_watch_values);
     }
 
     /// Create the UI.
@@ -293,7 +572,7 @@ impl Gui {
             self.initialize(ctx);
         }
 
-        if self.cfg.renderer.show_menubar {
+        if self.cfg.renderer.show_menubar && !self.cfg.renderer.compact_mode {
             TopBottomPanel::top("menubar").show(ctx, |ui| self.menubar(ui));
         }
 
@@ -311,12 +590,36 @@ impl Gui {
         self.preferences.show(ctx, viewport_opts);
         self.keybinds.show(ctx, viewport_opts);
         self.ppu_viewer.show(ctx, viewport_opts);
+        self.audio_visualizer.show(ctx, viewport_opts);
+        self.ram_watch.show(ctx, viewport_opts);
+        self.mapper_inspector.show(ctx, viewport_opts);
+        self.profiler.show(ctx, viewport_opts);
+        self.race.show(ctx, viewport_opts);
+        self.big_picture.show(ctx, &self.cfg);
 
         self.show_about_window(ctx, viewport_opts.enabled);
+        self.show_shortcuts_window(ctx, viewport_opts.enabled);
         self.show_about_homebrew_window(ctx, viewport_opts.enabled);
 
         self.show_performance_window(ctx, viewport_opts.enabled);
+        self.show_rom_info_window(ctx, viewport_opts.enabled);
         self.show_update_window(ctx, viewport_opts.enabled);
+        self.show_four_player_prompt(ctx, viewport_opts.enabled);
+        self.show_barcode_window(ctx, viewport_opts.enabled);
+        self.show_sync_conflicts_window(ctx, viewport_opts.enabled);
+        self.show_rom_header_issues_window(ctx, viewport_opts.enabled);
+        self.log_viewer.show(ctx, viewport_opts.enabled);
+        self.message_history.show(ctx, viewport_opts.enabled);
+        self.statistics.show(ctx, viewport_opts.enabled);
+        if feature!(Filesystem) {
+            let rom_name = self.loaded_rom.as_ref().map(|rom| rom.name.as_str());
+            self.game_notes.set_rom(&self.cfg, rom_name);
+            self.game_notes.show(ctx, viewport_opts.enabled);
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        self.phone_pad.show(ctx, viewport_opts.enabled);
+        self.error_report.show(ctx, &self.cfg.paths);
+        self.unsupported_mapper.show(ctx, &self.tx, &self.cfg);
 
         Self::show_viewport(
             "🔧 UI Settings",
@@ -358,22 +661,30 @@ impl Gui {
         // }
     }
 
+    /// Scale all UI text styles relative to the `egui` defaults.
+    pub fn set_font_size(ctx: &Context, font_size: FontSize) {
+        let default_text_styles = egui::Style::default().text_styles;
+        ctx.all_styles_mut(|style| {
+            for (text_style, font_id) in &mut style.text_styles {
+                if let Some(default) = default_text_styles.get(text_style) {
+                    font_id.size = (default.size * font_size.scale()).round();
+                }
+            }
+        });
+    }
+
     fn initialize(&mut self, ctx: &Context) {
         #[cfg(feature = "profiling")]
         puffin::profile_function!();
 
-        let theme = if self.cfg.renderer.dark_theme {
-            Self::dark_theme()
-        } else {
-            Self::light_theme()
-        };
-        ctx.set_visuals(theme);
+        ctx.set_visuals(Self::theme(&self.cfg.renderer));
         ctx.style_mut(|ctx| {
             let scroll = &mut ctx.spacing.scroll;
             scroll.floating = false;
             scroll.foreground_color = false;
             scroll.bar_width = 8.0;
         });
+        Self::set_font_size(ctx, self.cfg.renderer.font_size);
 
         const FONT: (&str, &[u8]) = (
             "pixeloid-sans",
@@ -406,15 +717,29 @@ impl Gui {
         }
         ctx.set_fonts(fonts);
 
-        // Check for update on start
-        if self.version.requires_updates() {
+        // Check for updates on start, if enabled
+        let renderer = &self.cfg.renderer;
+        let now = chrono::Local::now().timestamp();
+        if self.version.requires_updates()
+            && renderer
+                .update_check
+                .is_due(renderer.last_update_check, now)
+        {
             let notify_latest = false;
-            self.version.check_for_updates(&self.tx, notify_latest);
+            self.check_for_updates(notify_latest);
         }
 
         self.initialized = true;
     }
 
+    /// Check for a newer release in the background, recording when the check was made.
+    fn check_for_updates(&mut self, notify_latest: bool) {
+        self.version.check_for_updates(&self.tx, notify_latest);
+        self.tx.event(ConfigEvent::LastUpdateCheck(
+            chrono::Local::now().timestamp(),
+        ));
+    }
+
     fn show_about_window(&mut self, ctx: &Context, enabled: bool) {
         #[cfg(feature = "profiling")]
         puffin::profile_function!();
@@ -426,6 +751,74 @@ impl Gui {
         self.about_open = about_open;
     }
 
+    /// Cheatsheet listing every bound shortcut, along with how to navigate with the keyboard.
+    fn show_shortcuts_window(&mut self, ctx: &Context, enabled: bool) {
+        if !self.shortcuts_open {
+            return;
+        }
+
+        #[cfg(feature = "profiling")]
+        puffin::profile_function!();
+
+        let mut shortcuts_open = self.shortcuts_open;
+        egui::Window::new("⌨ Keyboard Shortcuts")
+            .open(&mut shortcuts_open)
+            .anchor(Align2::CENTER_CENTER, Vec2::ZERO)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.add_enabled_ui(enabled, |ui| {
+                    ScrollArea::vertical().max_height(420.0).show(ui, |ui| {
+                        let shortcuts = self.cfg.shortcut_keybinds();
+                        let half = shortcuts.len().div_ceil(2);
+                        ui.columns(2, |columns| {
+                            for (i, (category, bindings)) in shortcuts.iter().enumerate() {
+                                let ui = &mut columns[usize::from(i >= half)];
+                                ui.strong(*category);
+                                Grid::new(category)
+                                    .num_columns(2)
+                                    .striped(true)
+                                    .show(ui, |ui| {
+                                        for (action, inputs) in bindings {
+                                            ui.label(action.to_string());
+                                            ui.monospace(inputs.join(" or "));
+                                            ui.end_row();
+                                        }
+                                    });
+                                ui.add_space(8.0);
+                            }
+                        });
+
+                        ui.separator();
+
+                        ui.strong("Keyboard Navigation");
+                        Grid::new("keyboard_navigation")
+                            .num_columns(2)
+                            .striped(true)
+                            .show(ui, |ui| {
+                                for (keys, description) in [
+                                    ("Tab / Shift+Tab", "Move focus between controls"),
+                                    ("Space / Enter", "Activate the focused control"),
+                                    ("Arrow Keys", "Adjust the focused slider or value"),
+                                    ("Escape", "Close a menu or release focus"),
+                                ] {
+                                    ui.monospace(keys);
+                                    ui.label(description);
+                                    ui.end_row();
+                                }
+                            });
+                    });
+
+                    ui.separator();
+
+                    if ui.button("🖮 Customize Keybinds...").clicked() {
+                        self.keybinds.set_open(true);
+                    }
+                });
+            });
+        self.shortcuts_open = shortcuts_open;
+    }
+
     fn show_about_homebrew_window(&mut self, ctx: &Context, enabled: bool) {
         let Some(rom) = self.about_homebrew_rom_open else {
             return;
@@ -476,12 +869,72 @@ impl Gui {
         let mut perf_stats_open = self.perf_stats_open;
         egui::Window::new("🛠 Performance Stats")
             .open(&mut perf_stats_open)
+            .default_width(360.0)
             .show(ctx, |ui| {
-                ui.add_enabled_ui(enabled, |ui| self.performance_stats(ui));
+                ui.add_enabled_ui(enabled, |ui| {
+                    self.performance_stats(ui);
+                    ui.separator();
+                    self.perf_history.ui(ui);
+                    ui.separator();
+                    // icon: floppy disk
+                    if ui
+                        .button("💾 Export Frame Stats...")
+                        .on_hover_text(
+                            "Save recent frame timings as CSV or JSON, e.g. to report stutter.",
+                        )
+                        .clicked()
+                    {
+                        self.tx.event(UiEvent::ExportFrameStatsDialog);
+                    }
+                });
             });
         self.perf_stats_open = perf_stats_open;
     }
 
+    fn show_rom_info_window(&mut self, ctx: &Context, enabled: bool) {
+        #[cfg(feature = "profiling")]
+        puffin::profile_function!();
+
+        let mut rom_info_open = self.rom_info_open;
+        egui::Window::new("ℹ ROM Info")
+            .open(&mut rom_info_open)
+            .show(ctx, |ui| {
+                ui.add_enabled_ui(enabled, |ui| {
+                    let Some(rom) = &self.loaded_rom else {
+                        ui.label(Self::NO_ROM_LOADED);
+                        return;
+                    };
+
+                    Grid::new("rom_info")
+                        .num_columns(2)
+                        .spacing([40.0, 6.0])
+                        .show(ui, |ui| {
+                            ui.strong("Name:");
+                            ui.label(&rom.name);
+                            ui.end_row();
+
+                            ui.strong("Detected Region:");
+                            ui.label(format!("{} (from {})", rom.region, rom.region_source));
+                            ui.end_row();
+
+                            ui.strong("Battery-Backed:");
+                            ui.label(if rom.battery_backed { "Yes" } else { "No" });
+                            ui.end_row();
+                        });
+
+                    ui.separator();
+
+                    ui.strong("Region:").on_hover_text(
+                        "Override the detected region if a game runs too fast or slow.",
+                    );
+                    ui.horizontal(|ui| {
+                        Preferences::nes_region_radio(&self.tx, ui, self.cfg.deck.region);
+                    });
+                });
+            });
+        self.rom_info_open = rom_info_open;
+    }
+
     fn show_viewport(
         title: impl Into<String>,
         ctx: &Context,
@@ -525,6 +978,21 @@ impl Gui {
         });
     }
 
+    /// Whether a dialog is open that controller buttons should navigate instead of playing.
+    pub fn modal_open(&self) -> bool {
+        self.update_window_open
+            || self.four_player_prompt.is_some()
+            || self.barcode_entry.is_some()
+            || !self.sync_conflicts.is_empty()
+            || self.rom_header_issues.is_some()
+            || self.keybinds.wants_gamepad_nav()
+    }
+
+    /// Dialogs close on `Escape`, which a controller's `B` button is mapped to while they're open.
+    fn escape_pressed(ctx: &Context) -> bool {
+        ctx.input(|i| i.key_pressed(Key::Escape))
+    }
+
     fn show_update_window(&mut self, ctx: &Context, enabled: bool) {
         #[cfg(feature = "profiling")]
         puffin::profile_function!();
@@ -534,14 +1002,49 @@ impl Gui {
         let enable_auto_update = false;
         egui::Window::new("🌐 Update Available")
             .open(&mut update_window_open)
-            .resizable(false)
+            .default_width(480.0)
             .show(ctx, |ui| {
                 ui.add_enabled_ui(enabled, |ui| {
+                    let Some(latest) = self.version.latest() else {
+                        ui.label(format!(
+                            "TetaNES v{} is up to date!",
+                            self.version.current()
+                        ));
+                        return;
+                    };
                     ui.label(format!(
-                        "An update is available for TetaNES! (v{})",
-                        self.version.latest(),
+                        "An update is available for TetaNES! (v{} → v{})",
+                        self.version.current(),
+                        latest.version,
                     ));
-                    ui.hyperlink("https://github.com/lukexor/tetanes/releases");
+
+                    ui.add_space(8.0);
+                    ui.strong("Changelog:");
+                    ScrollArea::vertical()
+                        .max_height(300.0)
+                        .auto_shrink([false, true])
+                        .show(ui, |ui| {
+                            for release in self.version.releases() {
+                                ui.heading(format!("v{}", release.version));
+                                if release.changelog.is_empty() {
+                                    ui.weak("No release notes.");
+                                } else {
+                                    ui.label(&release.changelog);
+                                }
+                                ui.add_space(8.0);
+                            }
+                        });
+
+                    ui.add_space(8.0);
+                    ui.strong("Downloads:");
+                    if latest.downloads.is_empty() {
+                        ui.hyperlink_to("Release page", &latest.url);
+                    } else {
+                        for download in &latest.downloads {
+                            ui.hyperlink_to(&download.name, &download.url);
+                        }
+                    }
+                    ui.hyperlink_to("All releases", Version::RELEASES_URL);
 
                     ui.add_space(15.0);
                     ui.separator();
@@ -577,12 +1080,234 @@ impl Gui {
                     }
                 });
             });
-        if close_window {
+        if close_window || Self::escape_pressed(ctx) {
             update_window_open = false;
         }
         self.update_window_open = update_window_open;
     }
 
+    fn show_four_player_prompt(&mut self, ctx: &Context, enabled: bool) {
+        let Some(four_player) = self.four_player_prompt else {
+            return;
+        };
+
+        #[cfg(feature = "profiling")]
+        puffin::profile_function!();
+
+        let adapter = match four_player {
+            FourPlayer::FourScore => "Four Score",
+            FourPlayer::Satellite => "Satellite",
+            FourPlayer::Disabled => return,
+        };
+        let mut prompt_open = true;
+        let mut close_prompt = false;
+        egui::Window::new("🎮 Four Player Support")
+            .open(&mut prompt_open)
+            .resizable(false)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.add_enabled_ui(enabled, |ui| {
+                    ui.label(format!(
+                        "This game supports up to 4 players using the {adapter}. Enable it?"
+                    ));
+                    ui.label("It can be changed at any time from the Controls menu.");
+
+                    ui.add_space(15.0);
+
+                    ui.horizontal(|ui| {
+                        if ui.button(format!("Enable {adapter}")).clicked() {
+                            self.tx.event(ConfigEvent::FourPlayer(four_player));
+                            close_prompt = true;
+                        }
+                        if ui.button("Not Now").clicked() {
+                            close_prompt = true;
+                        }
+                    });
+                });
+            });
+        if !prompt_open || close_prompt || Self::escape_pressed(ctx) {
+            self.four_player_prompt = None;
+        }
+    }
+
+    fn show_barcode_window(&mut self, ctx: &Context, enabled: bool) {
+        let Some(barcode) = &mut self.barcode_entry else {
+            return;
+        };
+
+        #[cfg(feature = "profiling")]
+        puffin::profile_function!();
+
+        let mut window_open = true;
+        let mut close_window = false;
+        egui::Window::new("▥ Barcode Reader")
+            .open(&mut window_open)
+            .resizable(false)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.add_enabled_ui(enabled, |ui| {
+                    ui.label("Type or paste an 8 or 13 digit barcode to scan it.");
+
+                    ui.add_space(8.0);
+
+                    let valid = BarcodeReader::parse(barcode).is_some();
+                    let res = ui.add(
+                        TextEdit::singleline(barcode)
+                            .hint_text("4901234567894")
+                            .desired_width(160.0)
+                            .font(egui::TextStyle::Monospace),
+                    );
+                    if !barcode.is_empty() && !valid {
+                        ui.colored_label(
+                            ui.visuals().error_fg_color,
+                            "Barcodes must be 8 or 13 digits.",
+                        );
+                    }
+
+                    ui.add_space(8.0);
+
+                    ui.horizontal(|ui| {
+                        let scan_clicked = ui.add_enabled(valid, Button::new("Scan")).clicked();
+                        let submitted =
+                            valid && res.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter));
+                        if scan_clicked || submitted {
+                            self.tx
+                                .event(EmulationEvent::ScanBarcode(barcode.trim().to_string()));
+                            barcode.clear();
+                        }
+                        if ui.button("Close").clicked() {
+                            close_window = true;
+                        }
+                    });
+                });
+            });
+        if !window_open || close_window || Self::escape_pressed(ctx) {
+            self.barcode_entry = None;
+        }
+    }
+
+    fn show_sync_conflicts_window(&mut self, ctx: &Context, enabled: bool) {
+        if self.sync_conflicts.is_empty() {
+            return;
+        }
+
+        #[cfg(feature = "profiling")]
+        puffin::profile_function!();
+
+        let modified = |time: Option<std::time::SystemTime>| {
+            time.map_or_else(
+                || "unknown".to_string(),
+                |time| {
+                    chrono::DateTime::<chrono::Local>::from(time)
+                        .format("%Y-%m-%d %H:%M:%S")
+                        .to_string()
+                },
+            )
+        };
+
+        let mut window_open = true;
+        let mut resolved = None;
+        egui::Window::new("🔃 Save Sync Conflicts")
+            .open(&mut window_open)
+            .resizable(false)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.add_enabled_ui(enabled, |ui| {
+                    ui.label("These saves changed both here and in the sync folder.");
+                    ui.label("Choose which copy to keep. The other copy is overwritten.");
+
+                    ui.add_space(8.0);
+
+                    let grid = Grid::new("sync_conflicts")
+                        .num_columns(4)
+                        .spacing([20.0, 6.0]);
+                    grid.show(ui, |ui| {
+                        ui.strong("Save");
+                        ui.strong("Local");
+                        ui.strong("Remote");
+                        ui.end_row();
+
+                        for (i, conflict) in self.sync_conflicts.iter().enumerate() {
+                            ui.label(conflict.entry.label());
+                            ui.label(modified(conflict.local_modified));
+                            ui.label(modified(conflict.remote_modified));
+                            ui.horizontal(|ui| {
+                                if ui.button("Keep Local").clicked() {
+                                    resolved = Some((i, SyncChoice::KeepLocal));
+                                }
+                                if ui.button("Keep Remote").clicked() {
+                                    resolved = Some((i, SyncChoice::KeepRemote));
+                                }
+                            });
+                            ui.end_row();
+                        }
+                    });
+                });
+            });
+        if let Some((i, choice)) = resolved {
+            let conflict = self.sync_conflicts.remove(i);
+            self.tx.event(EmulationEvent::ResolveSyncConflict((
+                conflict.entry,
+                choice,
+            )));
+        }
+        // Unresolved conflicts are detected again on the next sync
+        if !window_open || Self::escape_pressed(ctx) {
+            self.sync_conflicts.clear();
+        }
+    }
+
+    fn show_rom_header_issues_window(&mut self, ctx: &Context, enabled: bool) {
+        let Some((name, issues)) = &self.rom_header_issues else {
+            return;
+        };
+
+        #[cfg(feature = "profiling")]
+        puffin::profile_function!();
+
+        let mut window_open = true;
+        let mut close_window = false;
+        let mut load = None;
+        egui::Window::new("⚠ ROM Header Problems")
+            .open(&mut window_open)
+            .anchor(Align2::CENTER_CENTER, Vec2::ZERO)
+            .resizable(false)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.add_enabled_ui(enabled, |ui| {
+                    ui.label(format!(
+                        "The header of {name:?} looks incorrect, which can prevent it from loading or running correctly:"
+                    ));
+                    ui.add_space(8.0);
+                    for issue in issues {
+                        ui.label(format!("• {issue}"));
+                    }
+                    ui.add_space(8.0);
+                    ui.label("The ROM file isn't modified either way.");
+                    ui.add_space(8.0);
+
+                    ui.horizontal(|ui| {
+                        if ui.button("🔧 Load with Corrected Header").clicked() {
+                            load = Some(EmulationEvent::LoadHeldRom(true));
+                        }
+                        if ui.button("Load Anyway").clicked() {
+                            load = Some(EmulationEvent::LoadHeldRom(false));
+                        }
+                        if ui.button("Cancel").clicked() {
+                            close_window = true;
+                        }
+                    });
+                });
+            });
+        if let Some(event) = load {
+            self.tx.event(event);
+            close_window = true;
+        }
+        if !window_open || close_window || Self::escape_pressed(ctx) {
+            self.rom_header_issues = None;
+        }
+    }
+
     fn menubar(&mut self, ui: &mut Ui) {
         #[cfg(feature = "profiling")]
         puffin::profile_function!();
@@ -590,19 +1315,30 @@ impl Gui {
         ui.add_enabled_ui(!self.keybinds.wants_input(), |ui| {
             let inner_res = menu::bar(ui, |ui| {
                 ui.horizontal_wrapped(|ui| {
-                    Self::toggle_dark_mode_button(&self.tx, ui);
+                    // High contrast overrides the light/dark theme
+                    // High contrast and theme presets override the light/dark theme
+                    if !self.cfg.renderer.high_contrast
+                        && self.cfg.renderer.theme == ThemePreset::Default
+                    {
+                        Self::toggle_dark_mode_button(&self.tx, ui);
 
-                    ui.separator();
+                        ui.separator();
+                    }
 
                     ui.menu_button("📁 File", |ui| self.file_menu(ui));
                     ui.menu_button("🔨 Controls", |ui| self.controls_menu(ui));
                     ui.menu_button("🔧 Config", |ui| self.config_menu(ui));
                     // icon: screen
                     ui.menu_button("🖵 Window", |ui| self.window_menu(ui));
-                    ui.menu_button("🕷 Debug", |ui| self.debug_menu(ui));
+                    if !self.cfg.startup.kiosk() {
+                        ui.menu_button("🕷 Debug", |ui| self.debug_menu(ui));
+                    }
                     ui.menu_button("❓ Help", |ui| self.help_menu(ui));
 
                     ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        if feature!(Storage) && self.loaded_rom.is_some() {
+                            self.save_slot_status(ui);
+                        }
                         egui::warn_if_debug_build(ui);
                     });
                 });
@@ -617,19 +1353,29 @@ impl Gui {
         });
     }
 
+    /// Show the active save slot, so it's clear which slot a save state overwrites.
+    fn save_slot_status(&self, ui: &mut Ui) {
+        let emulation = &self.cfg.emulation;
+        let res = ui.weak(format!("💾 Slot {}", emulation.save_slot));
+        match emulation.swap_save_slot() {
+            Some(slot) => res.on_hover_text(format!(
+                "Active save slot. Swap Save Slot swaps back to Slot {slot}."
+            )),
+            None => res.on_hover_text("Active save slot."),
+        };
+    }
+
     pub fn toggle_dark_mode_button(tx: &NesEventProxy, ui: &mut Ui) {
         if ui.ctx().style().visuals.dark_mode {
             let button = Button::new("☀").frame(false);
             let res = ui.add(button).on_hover_text("Switch to light mode");
             if res.clicked() {
-                ui.ctx().set_visuals(Self::light_theme());
                 tx.event(ConfigEvent::DarkTheme(false));
             }
         } else {
             let button = Button::new("🌙").frame(false);
             let res = ui.add(button).on_hover_text("Switch to dark mode");
             if res.clicked() {
-                ui.ctx().set_visuals(Self::dark_theme());
                 tx.event(ConfigEvent::DarkTheme(true));
             }
         }
@@ -653,7 +1399,9 @@ impl Gui {
             ui.close_menu();
         }
 
-        ui.menu_button("🍺 Homebrew ROM...", |ui| self.homebrew_rom_menu(ui));
+        if !self.cfg.startup.kiosk() {
+            ui.menu_button("🍺 Homebrew ROM...", |ui| self.homebrew_rom_menu(ui));
+        }
 
         let tx = &self.tx;
         let cfg = &self.cfg;
@@ -667,11 +1415,38 @@ impl Gui {
                 ui.close_menu();
             }
 
+            let toggle = ToggleValue::new(&mut self.rom_info_open, "ℹ ROM Info");
+            let res = ui
+                .add(toggle)
+                .on_hover_text("Show details of the loaded ROM, like its detected region.")
+                .on_disabled_hover_text(Self::NO_ROM_LOADED);
+            if res.clicked() {
+                ui.close_menu();
+            }
+
+            if feature!(Filesystem) {
+                let mut notes_open = self.game_notes.open();
+                let toggle = ToggleValue::new(&mut notes_open, "📝 Game Notes");
+                let res = ui
+                    .add(toggle)
+                    .on_hover_text(
+                        "View and edit notes for the loaded ROM, like its controls or passwords.",
+                    )
+                    .on_disabled_hover_text(Self::NO_ROM_LOADED);
+                if res.clicked() {
+                    self.game_notes.set_open(notes_open);
+                    ui.close_menu();
+                }
+            }
+
             let button =
                 Button::new("🎞 Load Replay").shortcut_text(cfg.shortcut(UiAction::LoadReplay));
             let res = ui
                 .add(button)
-                .on_hover_text("Load a replay file for the currently loaded ROM.")
+                .on_hover_text(
+                    "Load a replay file for the currently loaded ROM. \
+                    Input scripts play back from the current state.",
+                )
                 .on_disabled_hover_text(Self::NO_ROM_LOADED);
             if res.clicked() {
                 self.run_state = RunState::Paused;
@@ -684,23 +1459,96 @@ impl Gui {
             }
         });
 
-        // TODO: support saves and recent games on wasm? Requires storing the data
         if feature!(Filesystem) {
-            ui.menu_button("🗄 Recently Played...", |ui| {
-                use tetanes_core::fs;
+            let res = ui
+                .button("📝 Export Replay as Input Script...")
+                .on_hover_text(
+                    "Save the inputs of a replay as a text file listing the buttons held on \
+                    each frame, which can be edited and loaded back with Load Replay.",
+                );
+            if res.clicked() {
+                tx.event(UiEvent::ExportInputScriptDialog);
+                ui.close_menu();
+            }
+        }
 
-                if cfg.renderer.recent_roms.is_empty() {
-                    ui.label("No recent ROMs");
-                } else {
-                    ScrollArea::vertical().show(ui, |ui| {
-                        // TODO: add timestamp, save slots, and screenshot
-                        for rom in &cfg.renderer.recent_roms {
-                            if ui.button(fs::filename(rom)).clicked() {
-                                tx.event(EmulationEvent::LoadRomPath(rom.to_path_buf()));
-                                ui.close_menu();
-                            }
+        ui.add_enabled_ui(self.loaded_rom.is_some(), |ui| {
+            if self.race.open() {
+                if ui.button("🗙 Close Second Window").clicked() {
+                    self.race.close();
+                    ui.close_menu();
+                }
+            } else {
+                ui.menu_button("🗗 New Window With Same ROM", |ui| {
+                    for (inputs, hover_text) in [
+                        (
+                            RaceInputs::Separate,
+                            "Player Two's controls play the game in the second window.",
+                        ),
+                        (
+                            RaceInputs::Mirrored,
+                            "Player One's controls play the game in both windows.",
+                        ),
+                    ] {
+                        if ui
+                            .button(inputs.as_str())
+                            .on_hover_text(hover_text)
+                            .clicked()
+                        {
+                            self.race.start(inputs);
+                            ui.close_menu();
+                        }
+                    }
+                })
+                .response
+                .on_hover_text(
+                    "Run a second copy of the current game side-by-side for racing. \
+                    The current game is power cycled so both start together.",
+                )
+                .on_disabled_hover_text(Self::NO_ROM_LOADED);
+            }
+
+            if feature!(Filesystem) {
+                if self.ghost_loaded {
+                    ui.menu_button("👻 Ghost Replay...", |ui| {
+                        ui.add(
+                            Slider::new(&mut self.ghost_opacity, 0.1..=1.0)
+                                .text("Opacity")
+                                .fixed_decimals(1),
+                        );
+                        if ui.button("🗙 Unload Ghost Replay").clicked() {
+                            self.tx.event(EmulationEvent::UnloadGhost);
+                            ui.close_menu();
                         }
                     });
+                } else {
+                    let res = ui
+                        .button("👻 Load Ghost Replay...")
+                        .on_hover_text(
+                            "Play back a replay drawn over the current game to compare runs. \
+                            The game is restored to where the replay started so both start together.",
+                        )
+                        .on_disabled_hover_text(Self::NO_ROM_LOADED);
+                    if res.clicked() {
+                        self.run_state = RunState::Paused;
+                        self.tx.event(EmulationEvent::RunState(RunState::Paused));
+                        // NOTE: Due to some platforms file dialogs blocking the event loop,
+                        // loading requires a round-trip in order for the above pause to
+                        // get processed.
+                        self.tx.event(UiEvent::LoadGhostDialog);
+                        ui.close_menu();
+                    }
+                }
+            }
+        });
+
+        // TODO: support saves and recent games on wasm? Requires storing the data
+        if feature!(Filesystem) {
+            ui.menu_button("🗄 Recently Played...", |ui| {
+                if cfg.renderer.recent_roms.is_empty() {
+                    ui.label("No recent ROMs");
+                } else {
+                    Self::recent_roms_list(tx, ui, cfg);
                 }
             });
 
@@ -728,21 +1576,76 @@ impl Gui {
                 if res.clicked() {
                     tx.event(EmulationEvent::LoadState(cfg.emulation.save_slot));
                 }
+
+                let button = Button::new("↶ Undo Load State")
+                    .shortcut_text(cfg.shortcut(Feature::UndoLoadState));
+                let res = ui
+                    .add(button)
+                    .on_hover_text("Restore the state from before the last load state.")
+                    .on_disabled_hover_text(Self::NO_ROM_LOADED);
+                if res.clicked() {
+                    tx.event(EmulationEvent::UndoLoadState);
+                }
+
+                let button = Button::new("💾 Quick Save to Ring")
+                    .shortcut_text(cfg.shortcut(Feature::SaveStateRing));
+                let res = ui
+                    .add(button)
+                    .on_hover_text(
+                        "Save the current state over the oldest state in the quick save ring.",
+                    )
+                    .on_disabled_hover_text(Self::NO_ROM_LOADED);
+                if res.clicked() {
+                    tx.event(EmulationEvent::SaveStateRing);
+                }
+
+                let button = Button::new("⎗ Quick Load from Ring")
+                    .shortcut_text(cfg.shortcut(Feature::LoadStateRing));
+                let res = ui
+                    .add(button)
+                    .on_hover_text(concat!(
+                        "Load the newest state in the quick save ring. ",
+                        "Repeat to cycle back through older states."
+                    ))
+                    .on_disabled_hover_text(Self::NO_ROM_LOADED);
+                if res.clicked() {
+                    tx.event(EmulationEvent::LoadStateRing);
+                }
             });
 
             // icon: # in a square
             ui.menu_button("󾠬 Save Slot...", |ui| {
-                Preferences::save_slot_radio(
+                let hovered = Preferences::save_slot_radio(
                     tx,
                     ui,
                     cfg.emulation.save_slot,
                     cfg,
                     ShowShortcut::Yes,
                 );
+                if let (Some((slot, res)), Some(rom)) = (hovered, &self.loaded_rom) {
+                    res.on_hover_ui(|ui| self.save_previews.ui(ui, cfg, rom, slot));
+                }
+
+                ui.separator();
+
+                let swap_slot = cfg.emulation.swap_save_slot();
+                let button = Button::new(match swap_slot {
+                    Some(slot) => format!("⇄ Swap to Slot {slot}"),
+                    None => "⇄ Swap Save Slot".to_string(),
+                })
+                .shortcut_text(cfg.shortcut(DeckAction::SwapSaveSlot));
+                let res = ui
+                    .add_enabled(swap_slot.is_some(), button)
+                    .on_hover_text("Switch back to the previously used save slot.")
+                    .on_disabled_hover_text("No other save slot has been used yet.");
+                if let (true, Some(slot)) = (res.clicked(), swap_slot) {
+                    tx.event(ConfigEvent::SaveSlot(slot));
+                    ui.close_menu();
+                }
             });
         }
 
-        if feature!(OsViewports) {
+        if feature!(OsViewports) && !cfg.startup.kiosk() {
             ui.separator();
 
             let button = Button::new("⎆ Quit").shortcut_text(cfg.shortcut(UiAction::Quit));
@@ -857,6 +1760,44 @@ impl Gui {
             };
         });
 
+        ui.add_enabled_ui(self.loaded_rom.is_some(), |ui| {
+            ui.menu_button("⌨ Input Macros...", |ui| {
+                Self::macros_menu(tx, ui, cfg, self.macro_recording);
+            })
+            .response
+            .on_hover_text("Record short input sequences and play them back with a single key.")
+            .on_disabled_hover_text(Self::NO_ROM_LOADED);
+        });
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if !cfg.startup.kiosk() {
+            let mut phone_pad_open = self.phone_pad.open();
+            let toggle = ToggleValue::new(&mut phone_pad_open, "📱 Phone Controller");
+            let res = ui
+                .add(toggle)
+                .on_hover_text("Use a phone's web browser as a gamepad for any player.");
+            if res.clicked() {
+                self.phone_pad.set_open(phone_pad_open);
+                ui.close_menu();
+            }
+        }
+
+        let has_barcode_reader = self
+            .loaded_rom
+            .as_ref()
+            .is_some_and(|rom| rom.barcode_reader);
+        if has_barcode_reader {
+            ui.separator();
+
+            let res = ui
+                .button("▥ Scan Barcode...")
+                .on_hover_text("Scan a barcode with the Datach Joint ROM System barcode reader.");
+            if res.clicked() {
+                self.barcode_entry.get_or_insert_with(String::new);
+                ui.close_menu();
+            };
+        }
+
         if feature!(Filesystem) {
             ui.separator();
 
@@ -904,6 +1845,35 @@ impl Gui {
         }
     }
 
+    fn macros_menu(tx: &NesEventProxy, ui: &mut Ui, cfg: &Config, macro_recording: Option<u8>) {
+        for slot in 1..=Macros::SLOTS as u8 {
+            let recording = macro_recording == Some(slot);
+            let button_txt = if recording {
+                format!("⏹ Stop Recording Macro {slot}")
+            } else {
+                format!("⏺ Record Macro {slot}")
+            };
+            let button = Button::new(button_txt)
+                .shortcut_text(cfg.shortcut(Feature::ToggleMacroRecording(slot)));
+            if ui.add(button).clicked() {
+                tx.event(EmulationEvent::MacroRecord(slot));
+                ui.close_menu();
+            }
+
+            let button = Button::new(format!("▶ Play Macro {slot}"))
+                .shortcut_text(cfg.shortcut(Feature::PlayMacro(slot)));
+            let res = ui.add_enabled(macro_recording.is_none(), button);
+            if res.clicked() {
+                tx.event(EmulationEvent::MacroPlay(slot));
+                ui.close_menu();
+            }
+
+            if usize::from(slot) < Macros::SLOTS {
+                ui.separator();
+            }
+        }
+    }
+
     fn config_menu(&mut self, ui: &mut Ui) {
         #[cfg(feature = "profiling")]
         puffin::profile_function!();
@@ -923,6 +1893,19 @@ impl Gui {
             cfg.deck.zapper,
             cfg.shortcut(DeckAction::ToggleZapperConnected),
         );
+        Preferences::vaus_checkbox(tx, ui, cfg.deck.vaus);
+        let res = ui
+            .add_enabled(
+                self.loaded_rom.is_some() && (self.zapper_connected() || cfg.deck.vaus),
+                Button::new("🖱 Capture Mouse"),
+            )
+            .on_hover_text(
+                "Control the Zapper or Arkanoid paddle with relative mouse motion. Press Escape to release.",
+            );
+        if res.clicked() {
+            tx.event(RendererEvent::CaptureMouse(true));
+            ui.close_menu();
+        }
         Preferences::rewind_checkbox(
             tx,
             ui,
@@ -957,6 +1940,18 @@ impl Gui {
                     tx.event(ConfigEvent::Speed(new_speed));
                 }
             }
+
+            ui.separator();
+
+            for percent in [50, 100, 150, 200] {
+                let preset = Setting::SetSpeed(percent);
+                let new_speed = f32::from(percent) / 100.0;
+                let button =
+                    Button::new(format!("{new_speed}x")).shortcut_text(cfg.shortcut(preset));
+                if ui.add(button).clicked() && speed != new_speed {
+                    tx.event(ConfigEvent::Speed(new_speed));
+                }
+            }
             Preferences::speed_slider(tx, ui, cfg.emulation.speed);
         });
         ui.menu_button("🏃 Run Ahead...", |ui| {
@@ -987,25 +1982,93 @@ impl Gui {
 
         ui.separator();
 
-        let mut preferences_open = self.preferences.open();
-        // icon: gear
-        let toggle = ToggleValue::new(&mut preferences_open, "🔧 Preferences")
-            .shortcut_text(cfg.shortcut(Menu::Preferences));
-        if ui.add(toggle).clicked() {
-            self.preferences.set_open(preferences_open);
-            ui.close_menu();
+        if !cfg.startup.kiosk() {
+            if feature!(Filesystem) {
+                ui.menu_button("👤 Config Profile...", |ui| {
+                    Self::config_profile_menu(tx, ui, cfg, &mut self.new_config_profile);
+                });
+            }
+
+            let mut preferences_open = self.preferences.open();
+            // icon: gear
+            let toggle = ToggleValue::new(&mut preferences_open, "🔧 Preferences")
+                .shortcut_text(cfg.shortcut(Menu::Preferences));
+            if ui.add(toggle).clicked() {
+                self.preferences.set_open(preferences_open);
+                ui.close_menu();
+            }
+
+            let mut keybinds_open = self.keybinds.open();
+            // icon: keyboard
+            let toggle = ToggleValue::new(&mut keybinds_open, "🖮 Keybinds")
+                .shortcut_text(cfg.shortcut(Menu::Keybinds));
+            if ui.add(toggle).clicked() {
+                self.keybinds.set_open(keybinds_open);
+                ui.close_menu();
+            };
         }
 
-        let mut keybinds_open = self.keybinds.open();
-        // icon: keyboard
-        let toggle = ToggleValue::new(&mut keybinds_open, "🖮 Keybinds")
-            .shortcut_text(cfg.shortcut(Menu::Keybinds));
+        let mut big_picture_open = self.big_picture.open();
+        // icon: gamepad
+        let toggle = ToggleValue::new(&mut big_picture_open, "🎮 Big Picture")
+            .shortcut_text(cfg.shortcut(Menu::BigPicture));
         if ui.add(toggle).clicked() {
-            self.keybinds.set_open(keybinds_open);
+            self.tx.event(RendererEvent::Menu(Menu::BigPicture));
             ui.close_menu();
         };
     }
 
+    fn config_profile_menu(
+        tx: &NesEventProxy,
+        ui: &mut Ui,
+        cfg: &Config,
+        new_config_profile: &mut String,
+    ) {
+        let active = cfg.profile_label();
+
+        let profiles = std::iter::once(Config::DEFAULT_PROFILE.to_string())
+            .chain(Config::profiles())
+            .collect::<Vec<_>>();
+        for name in &profiles {
+            let res = ui
+                .radio(name == active, name.as_str())
+                .on_hover_text("Save the current settings and switch to this profile.");
+            if res.clicked() && name != active {
+                tx.event(ConfigEvent::ConfigProfile(name.clone()));
+                ui.close_menu();
+            }
+        }
+
+        ui.separator();
+
+        let can_remove = cfg.profile.is_some();
+        let res = ui
+            .add_enabled(can_remove, Button::new("🗑 Delete Current Profile"))
+            .on_hover_text("Remove the current config profile and switch back to the default.")
+            .on_disabled_hover_text("The default config profile can't be removed.");
+        if res.clicked() {
+            tx.event(ConfigEvent::ConfigProfileRemove(active.to_string()));
+            ui.close_menu();
+        }
+
+        ui.horizontal(|ui| {
+            ui.add(
+                TextEdit::singleline(new_config_profile)
+                    .hint_text("New profile name")
+                    .desired_width(140.0),
+            );
+            let name = new_config_profile.trim();
+            let res = ui
+                .add_enabled(!name.is_empty(), Button::new("➕ Create"))
+                .on_hover_text("Create a new config profile from the default profile's settings.");
+            if res.clicked() {
+                tx.event(ConfigEvent::ConfigProfile(name.to_string()));
+                new_config_profile.clear();
+                ui.close_menu();
+            }
+        });
+    }
+
     fn window_menu(&mut self, ui: &mut Ui) {
         use Setting::*;
 
@@ -1020,6 +2083,7 @@ impl Gui {
             always_on_top,
             show_menubar,
             show_messages,
+            compact_mode,
             ..
         } = cfg.renderer;
 
@@ -1049,6 +2113,7 @@ impl Gui {
         Preferences::fullscreen_checkbox(tx, ui, fullscreen, cfg.shortcut(ToggleFullscreen));
         Preferences::embed_viewports_checkbox(tx, ui, cfg, cfg.shortcut(ToggleEmbedViewports));
         Preferences::always_on_top_checkbox(tx, ui, always_on_top, cfg.shortcut(ToggleAlwaysOnTop));
+        Preferences::compact_mode_checkbox(tx, ui, compact_mode);
 
         ui.separator();
 
@@ -1057,6 +2122,26 @@ impl Gui {
         if feature!(ScreenReader) {
             Preferences::screen_reader_checkbox(ui, cfg.shortcut(ToggleScreenReader));
         }
+
+        let mut history_open = self.message_history.open();
+        let toggle = ToggleValue::new(&mut history_open, "🔔 Notification History");
+        let res = ui
+            .add(toggle)
+            .on_hover_text("Toggle a window listing all previous messages.");
+        if res.clicked() {
+            self.message_history.set_open(history_open);
+            ui.close_menu();
+        }
+
+        let mut statistics_open = self.statistics.open();
+        let toggle = ToggleValue::new(&mut statistics_open, "📊 Statistics");
+        let res = ui
+            .add(toggle)
+            .on_hover_text("Toggle a window summarizing time spent playing each game.");
+        if res.clicked() {
+            self.statistics.set_open(statistics_open);
+            ui.close_menu();
+        }
     }
 
     fn debug_menu(&mut self, ui: &mut Ui) {
@@ -1095,6 +2180,32 @@ impl Gui {
             ui.close_menu();
         }
 
+        let mut log_viewer_open = self.log_viewer.open();
+        let toggle = ToggleValue::new(&mut log_viewer_open, "📜 Log Viewer");
+        let res = ui
+            .add(toggle)
+            .on_hover_text("Toggle a window showing recent log messages.");
+        if res.clicked() {
+            self.log_viewer.set_open(log_viewer_open);
+            ui.close_menu();
+        }
+
+        ui.menu_button("📶 Log Level", |ui| {
+            let current_level = logging::level();
+            for level in log_levels() {
+                let res = ui.radio(current_level == level, level.to_string());
+                if res.clicked() {
+                    if let Err(err) = logging::set_level(level) {
+                        error!("{err:?}");
+                        tx.event(UiEvent::Error("failed to change log level".to_string()));
+                    }
+                    ui.close_menu();
+                }
+            }
+        })
+        .response
+        .on_hover_text("Change the maximum level of log messages captured.");
+
         #[cfg(debug_assertions)]
         {
             let mut gui_inspection_open = self.gui_inspection_open.load(Ordering::Acquire);
@@ -1143,6 +2254,44 @@ impl Gui {
             }
         });
 
+        ui.menu_button("🛑 Break On...", |ui| {
+            for (breakpoint, label, hover) in [
+                (
+                    Breakpoints::NMI,
+                    "NMI",
+                    "Pause when the CPU enters the NMI handler.",
+                ),
+                (
+                    Breakpoints::IRQ,
+                    "IRQ",
+                    "Pause when the CPU enters the IRQ handler for an APU or mapper interrupt.",
+                ),
+                (
+                    Breakpoints::BRK,
+                    "BRK",
+                    "Pause when the CPU executes a BRK instruction.",
+                ),
+                (
+                    Breakpoints::UNOFFICIAL_OPCODE,
+                    "Unofficial Opcode",
+                    "Pause when the CPU executes an unofficial opcode.",
+                ),
+                (
+                    Breakpoints::JAM,
+                    "CPU Jam",
+                    "Pause when the CPU jams on an opcode that halts it, instead of stopping \
+                    with an error.",
+                ),
+            ] {
+                let mut enabled = self.breakpoints.contains(breakpoint);
+                let res = ui.checkbox(&mut enabled, label).on_hover_text(hover);
+                if res.changed() {
+                    self.breakpoints.set(breakpoint, enabled);
+                    tx.event(EmulationEvent::Breakpoints((breakpoint, enabled)));
+                }
+            }
+        });
+
         let ppu_viewer_shortcut = cfg.shortcut(Debug::Toggle(DebugKind::Ppu));
         let mut open = self.ppu_viewer.open();
         let toggle =
@@ -1153,6 +2302,50 @@ impl Gui {
             ui.close_menu();
         }
 
+        let mut open = self.audio_visualizer.open();
+        let toggle = ToggleValue::new(&mut open, "🎵 Audio Visualizer")
+            .shortcut_text(cfg.shortcut(Menu::AudioVisualizer));
+        let res = ui
+            .add(toggle)
+            .on_hover_text("Toggle the Audio Visualizer showing the output waveform and spectrum.");
+        if res.clicked() {
+            self.audio_visualizer.set_open(open);
+            ui.close_menu();
+        }
+
+        let mut open = self.ram_watch.open();
+        let toggle =
+            ToggleValue::new(&mut open, "👁 RAM Watch").shortcut_text(cfg.shortcut(Menu::RamWatch));
+        let res = ui
+            .add(toggle)
+            .on_hover_text("Toggle the RAM Watch for pinning memory values over the game.");
+        if res.clicked() {
+            self.ram_watch.set_open(open);
+            ui.close_menu();
+        }
+
+        let mut open = self.mapper_inspector.open();
+        let toggle = ToggleValue::new(&mut open, "🗂 Mapper Inspector")
+            .shortcut_text(cfg.shortcut(Menu::MapperInspector));
+        let res = ui.add(toggle).on_hover_text(
+            "Toggle the Mapper Inspector showing bank mapping, IRQ and register state.",
+        );
+        if res.clicked() {
+            self.mapper_inspector.set_open(open);
+            ui.close_menu();
+        }
+
+        let mut open = self.profiler.open();
+        let toggle =
+            ToggleValue::new(&mut open, "⏱ Profiler").shortcut_text(cfg.shortcut(Menu::Profiler));
+        let res = ui.add(toggle).on_hover_text(
+            "Toggle the Profiler showing the CPU cycles spent in each game routine.",
+        );
+        if res.clicked() {
+            self.profiler.set_open(open);
+            ui.close_menu();
+        }
+
         ui.add_enabled_ui(false, |ui| {
             let apu_mixer_shortcut = cfg.shortcut(Debug::Toggle(DebugKind::Apu));
             let toggle = ToggleValue::new(&mut self.apu_mixer_open, "🎼 APU Mixer")
@@ -1218,13 +2411,222 @@ impl Gui {
             if res.clicked() {
                 tx.event(EmulationEvent::DebugStep(DebugStep::Frame));
             }
+
+            let button = Button::new("⬅ Step Back")
+                .shortcut_text(cfg.shortcut(Debug::Step(DebugStep::Back)));
+            let res = ui
+                .add(button)
+                .on_hover_text(
+                    "Step back a single CPU instruction by re-emulating from the rewind buffer.",
+                )
+                .on_disabled_hover_text(Self::NO_ROM_LOADED);
+            if res.clicked() {
+                tx.event(EmulationEvent::DebugStep(DebugStep::Back));
+            }
+
+            let button = Button::new("⏪ Reverse Continue")
+                .shortcut_text(cfg.shortcut(Debug::Step(DebugStep::ReverseContinue)));
+            let res = ui
+                .add(button)
+                .on_hover_text("Run backwards to the previous breakpoint hit in the rewind buffer.")
+                .on_disabled_hover_text(Self::NO_ROM_LOADED);
+            if res.clicked() {
+                tx.event(EmulationEvent::DebugStep(DebugStep::ReverseContinue));
+            }
+        });
+    }
+
+    fn recent_roms_list(tx: &NesEventProxy, ui: &mut Ui, cfg: &Config) {
+        use tetanes_core::fs;
+
+        let mut any_missing = false;
+        ScrollArea::vertical().show(ui, |ui| {
+            for rom in cfg.renderer.recent_roms.iter() {
+                let exists = rom.path.is_file();
+                any_missing |= !exists;
+
+                // icon: pushpin
+                let pin = if rom.pinned { "📌 " } else { "" };
+                let mut text = RichText::new(format!("{pin}{}", fs::filename(&rom.path)));
+                if !exists {
+                    text = text.weak().strikethrough();
+                }
+                let mut button = Button::new(text);
+                if let Some(last_played) = rom.last_played_text() {
+                    button = button.shortcut_text(last_played);
+                }
+                let res = ui.add(button).on_hover_text(if exists {
+                    rom.path.display().to_string()
+                } else {
+                    format!("File not found: {}", rom.path.display())
+                });
+                if res.clicked() && exists {
+                    tx.event(EmulationEvent::LoadRomPath(rom.path.clone()));
+                    ui.close_menu();
+                }
+
+                res.context_menu(|ui| {
+                    let pin_text = if rom.pinned {
+                        "Unpin"
+                    } else {
+                        "📌 Pin to Top"
+                    };
+                    if ui.button(pin_text).clicked() {
+                        tx.event(ConfigEvent::RecentRomPinned((
+                            rom.path.clone(),
+                            !rom.pinned,
+                        )));
+                        ui.close_menu();
+                    }
+
+                    let dir = rom.path.parent().filter(|dir| dir.is_dir());
+                    let res = ui
+                        .add_enabled(dir.is_some(), Button::new("📂 Open Containing Folder"))
+                        .on_disabled_hover_text("The folder no longer exists.");
+                    if res.clicked() {
+                        if let Some(dir) = dir {
+                            if let Err(err) = platform::open_dir(dir) {
+                                tx.event(UiEvent::Error(err.to_string()));
+                            }
+                        }
+                        ui.close_menu();
+                    }
+
+                    // icon: waste basket
+                    if ui.button("🗑 Remove").clicked() {
+                        tx.event(ConfigEvent::RecentRomRemoved(rom.path.clone()));
+                        ui.close_menu();
+                    }
+                });
+            }
+        });
+
+        ui.separator();
+
+        let res = ui
+            .add_enabled(any_missing, Button::new("Remove Missing"))
+            .on_hover_text("Remove ROMs whose files no longer exist.");
+        if res.clicked() {
+            tx.event(ConfigEvent::RecentRomsRemoveMissing);
+        }
+        let res = ui
+            .button("Clear Unpinned")
+            .on_hover_text("Remove all ROMs that aren't pinned.");
+        if res.clicked() {
+            tx.event(ConfigEvent::RecentRomsClear);
+            ui.close_menu();
+        }
+    }
+
+    /// Move a virtual cursor over the NES frame to aim the Zapper and turn the Vaus paddle knob
+    /// with relative mouse motion while the cursor is captured.
+    fn captured_mouse_input(
+        tx: &NesEventProxy,
+        ui: &mut Ui,
+        capture: &mut MouseCapture,
+        rect: Rect,
+        cfg: &Config,
+        zapper: bool,
+    ) {
+        let (motion, primary_down, primary_pressed) = ui.input(|i| {
+            (
+                i.pointer.motion().unwrap_or_default() / i.pixels_per_point,
+                i.pointer.primary_down(),
+                i.pointer.primary_pressed(),
+            )
         });
+        capture.cursor = capture.cursor.clamp(rect.min, rect.max);
+        if motion != Vec2::ZERO {
+            capture.cursor = (capture.cursor + motion).clamp(rect.min, rect.max);
+        }
+
+        if cfg.deck.vaus {
+            // Moving across the whole frame turns the knob through its full range
+            let range = f32::from(Vaus::MAX_POSITION - Vaus::MIN_POSITION);
+            capture.vaus_motion += motion.x / rect.width() * range;
+            let delta = capture.vaus_motion.trunc();
+            if delta != 0.0 {
+                capture.vaus_motion -= delta;
+                tx.event(EmulationEvent::VausMove(delta as i32));
+            }
+            if primary_down != capture.vaus_button {
+                capture.vaus_button = primary_down;
+                tx.event(EmulationEvent::VausButton(primary_down));
+            }
+        }
+
+        if zapper {
+            if primary_pressed {
+                tx.event(EmulationEvent::ZapperTrigger);
+            }
+            if motion != Vec2::ZERO {
+                if let Some(Pos2 { x, y }) = cursor_to_zapper(
+                    capture.cursor.x,
+                    capture.cursor.y,
+                    rect,
+                    cfg.renderer.rotation,
+                    cfg.renderer.flip_horizontal,
+                ) {
+                    tx.event(EmulationEvent::ZapperAim((
+                        x.round() as u32,
+                        y.round() as u32,
+                    )));
+                }
+            }
+
+            // The system cursor is hidden, so draw a crosshair where the Zapper is aimed
+            let painter = ui.painter_at(rect);
+            let Pos2 { x, y } = capture.cursor;
+            let stroke = Stroke::new(1.5, Color32::WHITE);
+            painter.line_segment([Pos2::new(x - 6.0, y), Pos2::new(x + 6.0, y)], stroke);
+            painter.line_segment([Pos2::new(x, y - 6.0), Pos2::new(x, y + 6.0)], stroke);
+        }
+    }
+
+    /// Black out the lower half of each of the `lines` NES lines drawn in `rect`, like the gaps
+    /// between lines of a 240p signal on a CRT. Lines run vertically if the frame is `sideways`.
+    fn draw_scanlines(painter: &egui::Painter, rect: Rect, lines: f32, sideways: bool) {
+        let line_size = if sideways {
+            rect.width()
+        } else {
+            rect.height()
+        } / lines;
+        let mut mesh = egui::Mesh::default();
+        for line in 0..lines as u32 {
+            let offset = (line as f32 + 0.5) * line_size;
+            let gap = if sideways {
+                Rect::from_min_size(
+                    Pos2::new(rect.left() + offset, rect.top()),
+                    Vec2::new(line_size * 0.5, rect.height()),
+                )
+            } else {
+                Rect::from_min_size(
+                    Pos2::new(rect.left(), rect.top() + offset),
+                    Vec2::new(rect.width(), line_size * 0.5),
+                )
+            };
+            mesh.add_colored_rect(gap, Color32::BLACK);
+        }
+        painter.add(mesh);
+    }
+
+    /// With the menu bar hidden in compact mode, right-clicking the game is the way back out.
+    fn compact_mode_context_menu(tx: &NesEventProxy, res: &egui::Response, compact_mode: bool) {
+        if compact_mode {
+            res.context_menu(|ui| {
+                if ui.button("Exit Compact Mode").clicked() {
+                    tx.event(ConfigEvent::CompactMode(false));
+                    ui.close_menu();
+                }
+            });
+        }
     }
 
     fn nes_frame(&mut self, ui: &mut Ui, enabled: bool, gamepads: Option<&Gamepads>) {
         #[cfg(feature = "profiling")]
         puffin::profile_function!();
 
+        let zapper = self.zapper_connected();
         ui.add_enabled_ui(enabled, |ui| {
             let tx = &self.tx;
 
@@ -1237,20 +2639,107 @@ impl Gui {
                         ..Default::default()
                     };
                     ui.with_layout(layout, |ui| {
-                        let image = Image::from_texture(self.nes_texture.sized())
-                            .shrink_to_fit()
-                            .sense(Sense::click());
+                        let RendererConfig {
+                            rotation,
+                            flip_horizontal,
+                            ..
+                        } = self.cfg.renderer;
+
+                        // Scale the rotated frame to fit, keeping its aspect ratio
+                        let frame_size = rotation.rotate_size(self.nes_texture.output_size);
+                        let available_size = ui.available_size();
+                        let scale =
+                            (available_size.x / frame_size.x).min(available_size.y / frame_size.y);
+                        let (rect, res) =
+                            ui.allocate_exact_size(frame_size * scale, Sense::click());
+                        self.nes_display_scale = scale
+                            * ui.ctx().pixels_per_point()
+                            * self.nes_texture.aspect_ratio.min(1.0);
+                        ui.painter().add(self.nes_texture.oriented_mesh(
+                            rect,
+                            rotation,
+                            flip_horizontal,
+                            Color32::WHITE,
+                        ));
+                        if self.cfg.renderer.scanlines {
+                            Self::draw_scanlines(
+                                &ui.painter_at(rect),
+                                rect,
+                                self.nes_texture.output_size.y,
+                                rotation.is_sideways(),
+                            );
+                        }
 
-                        let hover_cursor = if self.cfg.deck.zapper {
+                        let hover_cursor = if zapper {
                             CursorIcon::Crosshair
                         } else {
                             CursorIcon::Default
                         };
 
-                        let res = ui.add(image).on_hover_cursor(hover_cursor);
+                        let res = res.on_hover_cursor(hover_cursor);
                         self.nes_frame = res.rect;
+                        Self::compact_mode_context_menu(tx, &res, self.cfg.renderer.compact_mode);
+
+                        if self.ghost_loaded {
+                            ui.painter().add(self.ghost_texture.oriented_mesh(
+                                res.rect,
+                                rotation,
+                                flip_horizontal,
+                                Color32::from_white_alpha((self.ghost_opacity * 255.0) as u8),
+                            ));
+                        }
+
+                        if let Some(rom) = &self.loaded_rom {
+                            self.save_previews.draw_selected(
+                                &ui.painter_at(res.rect),
+                                res.rect,
+                                &self.cfg,
+                                rom,
+                                Self::MSG_TIMEOUT,
+                            );
+                        }
 
-                        if self.cfg.deck.zapper {
+                        RamWatchWindow::draw_pinned(
+                            &ui.painter_at(res.rect),
+                            res.rect,
+                            &self.cfg.renderer.ram_watches,
+                            &self.ram_watch_values,
+                        );
+
+                        // Debug overlays only line up while the frame isn't sideways
+                        if !rotation.is_sideways() {
+                            let is_ntsc = self
+                                .loaded_rom
+                                .as_ref()
+                                .map_or(self.cfg.deck.region, |rom| rom.region)
+                                .is_ntsc();
+                            let overscan = if self.cfg.renderer.hide_overscan && is_ntsc {
+                                8
+                            } else {
+                                0
+                            };
+                            let flip_y = rotation == Rotation::Rotate180;
+                            let painter = ui.painter_at(res.rect);
+                            self.mapper_inspector
+                                .draw_irq_scanlines(&painter, res.rect, overscan, flip_y);
+                            self.ppu_viewer.draw_selected_sprite(
+                                &painter,
+                                res.rect,
+                                overscan,
+                                flip_horizontal != flip_y,
+                                flip_y,
+                            );
+                        }
+
+                        if self.run_state.manually_paused() && !self.big_picture.open() {
+                            Self::pause_menu(tx, ui, res.rect, &self.cfg, &mut self.run_state);
+                        } else if let Some(capture) = &mut self.mouse_capture {
+                            Self::captured_mouse_input(
+                                tx, ui, capture, res.rect, &self.cfg, zapper,
+                            );
+                        } else if self.cfg.deck.vaus && res.clicked() {
+                            tx.event(RendererEvent::CaptureMouse(true));
+                        } else if zapper {
                             if res.clicked() {
                                 tx.event(EmulationEvent::ZapperTrigger);
                             }
@@ -1261,9 +2750,10 @@ impl Gui {
                             {
                                 let pos = (Ppu::WIDTH + 10, Ppu::HEIGHT + 10);
                                 tx.event(EmulationEvent::ZapperAim(pos));
-                            } else if let Some(Pos2 { x, y }) = res
-                                .hover_pos()
-                                .and_then(|Pos2 { x, y }| cursor_to_zapper(x, y, res.rect))
+                            } else if let Some(Pos2 { x, y }) =
+                                res.hover_pos().and_then(|Pos2 { x, y }| {
+                                    cursor_to_zapper(x, y, res.rect, rotation, flip_horizontal)
+                                })
                             {
                                 let pos = (x.round() as u32, y.round() as u32);
                                 tx.event(EmulationEvent::ZapperAim(pos));
@@ -1275,8 +2765,14 @@ impl Gui {
                         ui.horizontal_centered(|ui| {
                             let image = Image::new(include_image!("../../../assets/tetanes.png"))
                                 .shrink_to_fit()
-                                .tint(Color32::GRAY);
-                            ui.add(image);
+                                .tint(Color32::GRAY)
+                                .sense(Sense::click());
+                            let res = ui.add(image);
+                            Self::compact_mode_context_menu(
+                                tx,
+                                &res,
+                                self.cfg.renderer.compact_mode,
+                            );
                         });
                     });
                 }
@@ -1289,6 +2785,9 @@ impl Gui {
             if self.audio_recording {
                 recording_labels.push("Audio");
             }
+            if self.macro_recording.is_some() {
+                recording_labels.push("Macro");
+            }
             if !recording_labels.is_empty() {
                 Frame::side_top_panel(ui.style()).show(ui, |ui| {
                     ui.with_layout(
@@ -1306,21 +2805,19 @@ impl Gui {
                 });
             }
 
-            if self.cfg.renderer.show_messages
-                && (!self.messages.is_empty() || self.error.is_some())
-            {
+            if self.cfg.renderer.show_messages && !self.messages.is_empty() {
                 Frame::popup(ui.style()).show(ui, |ui| {
                     ui.with_layout(
                         Layout::top_down_justified(Align::LEFT).with_main_wrap(true),
                         |ui| {
                             self.message_bar(ui);
-                            self.error_bar(ui);
                         },
                     );
                 });
             }
 
-            if self.run_state.paused() {
+            // Manually pausing shows the pause menu instead
+            if self.run_state.auto_paused() {
                 Frame::none().inner_margin(5.0).show(ui, |ui| {
                     ui.heading(RichText::new("⏸").color(Color32::LIGHT_GRAY).size(40.0));
                 });
@@ -1328,11 +2825,85 @@ impl Gui {
         });
     }
 
+    /// Dim the NES frame while manually paused, showing large buttons for the most common
+    /// actions so they can be reached with a mouse or controller.
+    fn pause_menu(
+        tx: &NesEventProxy,
+        ui: &mut Ui,
+        rect: Rect,
+        cfg: &Config,
+        run_state: &mut RunState,
+    ) {
+        ui.painter()
+            .rect_filled(rect, Rounding::ZERO, Color32::from_black_alpha(160));
+
+        let slot = cfg.emulation.save_slot;
+        // Label, event to send and whether to resume afterwards to show the result right away
+        let mut buttons: Vec<(&str, Option<NesEvent>, bool)> = vec![("▶ Resume", None, true)];
+        if feature!(Storage) {
+            buttons.push((
+                "💾 Save State",
+                Some(EmulationEvent::SaveState(slot).into()),
+                false,
+            ));
+            buttons.push((
+                "⎗ Load State",
+                Some(EmulationEvent::LoadState(slot).into()),
+                true,
+            ));
+        }
+        let reset = EmulationEvent::Reset(ResetKind::Soft);
+        buttons.push(("↺ Reset", Some(reset.into()), true));
+        if feature!(Filesystem) {
+            buttons.push((
+                "🖼 Screenshot",
+                Some(EmulationEvent::Screenshot.into()),
+                false,
+            ));
+        }
+        if feature!(OsViewports) && !cfg.startup.kiosk() {
+            buttons.push(("⎆ Quit", Some(UiEvent::Terminate.into()), false));
+        }
+
+        let spacing = ui.spacing().item_spacing.y;
+        let button_size = Vec2::new(
+            (rect.width() * 0.5).clamp(120.0, 280.0),
+            (rect.height() / (buttons.len() as f32 + 2.0) - spacing).clamp(20.0, 44.0),
+        );
+        let menu_size = Vec2::new(
+            button_size.x,
+            buttons.len() as f32 * (button_size.y + spacing),
+        );
+        let builder = UiBuilder::new()
+            .max_rect(Rect::from_center_size(rect.center(), menu_size))
+            .layout(Layout::top_down(Align::Center));
+        ui.allocate_new_ui(builder, |ui| {
+            for (i, (label, event, resume)) in buttons.into_iter().enumerate() {
+                let text = RichText::new(label).size(button_size.y * 0.45);
+                let res = ui.add(Button::new(text).min_size(button_size));
+                // Focus the first button so the menu can be navigated with a controller
+                if i == 0 && ui.memory(|mem| mem.focused().is_none()) {
+                    res.request_focus();
+                }
+                if res.clicked() {
+                    if let Some(event) = event {
+                        tx.event(event);
+                    }
+                    if resume {
+                        *run_state = RunState::Running;
+                        tx.event(EmulationEvent::RunState(*run_state));
+                    }
+                }
+            }
+        });
+    }
+
     fn performance_stats(&mut self, ui: &mut Ui) {
         #[cfg(feature = "profiling")]
         puffin::profile_function!();
 
         let cfg = &self.cfg;
+        let zapper = self.zapper_connected();
 
         let grid = Grid::new("perf_stats").num_columns(2).spacing([40.0, 6.0]);
         grid.show(ui, |ui| {
@@ -1401,6 +2972,28 @@ impl Gui {
             ui.label(format!("{}", self.frame_stats.frame_count));
             ui.end_row();
 
+            let late_frames = self.frame_stats.late_frames;
+            ui.strong("Late Frames:")
+                .on_hover_cursor(CursorIcon::Help)
+                .on_hover_text("Frames that took longer than expected to emulate.");
+            if late_frames > 0 {
+                ui.colored_label(warn_color, format!("{late_frames}"));
+            } else {
+                ui.label("0");
+            }
+            ui.end_row();
+
+            let dropped_frames = self.frame_stats.dropped_frames;
+            ui.strong("Dropped Frames:")
+                .on_hover_cursor(CursorIcon::Help)
+                .on_hover_text("Frames that were emulated, but not rendered in time to be shown.");
+            if dropped_frames > 0 {
+                ui.colored_label(bad_color, format!("{dropped_frames}"));
+            } else {
+                ui.label("0");
+            }
+            ui.end_row();
+
             if let Some(stats) = self.sys.stats() {
                 let cpu_color = |cpu| match cpu {
                     cpu if cpu <= 25.0 => good_color,
@@ -1452,7 +3045,13 @@ impl Gui {
 
             let (cursor_pos, zapper_pos) = match ui.input(|i| i.pointer.latest_pos()) {
                 Some(Pos2 { x, y }) => {
-                    let zapper_pos = match cursor_to_zapper(x, y, self.nes_frame) {
+                    let zapper_pos = match cursor_to_zapper(
+                        x,
+                        y,
+                        self.nes_frame,
+                        cfg.renderer.rotation,
+                        cfg.renderer.flip_horizontal,
+                    ) {
                         Some(Pos2 { x, y }) => format!("({x:.0}, {y:.0})"),
                         None => "(-, -)".to_string(),
                     };
@@ -1465,7 +3064,7 @@ impl Gui {
             ui.label(cursor_pos);
             ui.end_row();
 
-            if cfg.deck.zapper {
+            if zapper {
                 ui.strong("Zapper Pos:");
                 ui.label(zapper_pos);
                 ui.end_row();
@@ -1479,7 +3078,12 @@ impl Gui {
 
         if self.version.requires_updates() && ui.button("🌐 Check for Updates...").clicked() {
             let notify_latest = true;
-            self.version.check_for_updates(&self.tx, notify_latest);
+            self.check_for_updates(notify_latest);
+            ui.close_menu();
+        }
+        let toggle = ToggleValue::new(&mut self.shortcuts_open, "⌨ Keyboard Shortcuts")
+            .shortcut_text(self.cfg.shortcut(Menu::Shortcuts));
+        if ui.add(toggle).clicked() {
             ui.close_menu();
         }
         ui.toggle_value(&mut self.about_open, "ℹ About");
@@ -1518,19 +3122,25 @@ impl Gui {
                                 ui.label(format!("{}", config_dir.display()));
                                 ui.end_row();
 
-                                let data_dir = Config::default_data_dir();
-                                ui.strong("Save States/RAM, Replays: ");
-                                ui.label(format!("{}", data_dir.display()));
+                                let paths = &self.cfg.paths;
+                                ui.strong("Save States: ");
+                                ui.label(format!("{}", paths.save_dir().display()));
+                                ui.end_row();
+
+                                ui.strong("Save RAM: ");
+                                ui.label(format!("{}", paths.sram_dir().display()));
+                                ui.end_row();
+
+                                ui.strong("Replays: ");
+                                ui.label(format!("{}", paths.replay_dir().display()));
                                 ui.end_row();
 
-                                let picture_dir = Config::default_picture_dir();
                                 ui.strong("Screenshots: ");
-                                ui.label(format!("{}", picture_dir.display()));
+                                ui.label(format!("{}", paths.screenshot_dir().display()));
                                 ui.end_row();
 
-                                let audio_dir = Config::default_audio_dir();
                                 ui.strong("Audio Recordings: ");
-                                ui.label(format!("{}", audio_dir.display()));
+                                ui.label(format!("{}", paths.audio_dir().display()));
                                 ui.end_row();
                             });
                         });
@@ -1559,6 +3169,10 @@ impl Gui {
         let now = Instant::now();
         self.messages.retain(|(_, _, expires)| now < *expires);
         self.messages.dedup_by(|a, b| a.1.eq(&b.1));
+        // Ensure messages are cleared when they expire, even if nothing else is redrawing
+        if let Some(expires) = self.messages.iter().map(|(_, _, expires)| *expires).min() {
+            ui.ctx().request_repaint_after(expires - now);
+        }
         for (ty, message, _) in self.messages.iter().take(Self::MAX_MESSAGES) {
             let visuals = &ui.style().visuals;
             let (icon, color) = match ty {
@@ -1566,19 +3180,48 @@ impl Gui {
                 MessageType::Warn => ("⚠", visuals.warn_fg_color),
                 MessageType::Error => ("❗", visuals.error_fg_color),
             };
-            ui.colored_label(color, format!("{icon} {message}"));
+            // Used by accesskit below on platforms that support it
+            #[allow(unused_variables)]
+            let res = ui.colored_label(color, format!("{icon} {message}"));
+            // Screen readers announce live regions as they appear, without needing focus
+            #[cfg(not(target_arch = "wasm32"))]
+            ui.ctx().accesskit_node_builder(res.id, |builder| {
+                builder.set_name(ty.describe(message));
+                builder.set_live(match ty {
+                    MessageType::Info | MessageType::Warn => accesskit::Live::Polite,
+                    MessageType::Error => accesskit::Live::Assertive,
+                });
+            });
         }
     }
 
-    fn error_bar(&mut self, ui: &mut Ui) {
-        if let Some(error) = self.error.clone() {
-            ui.horizontal(|ui| {
-                ui.label(RichText::new(error).color(Color32::RED));
-                if ui.button("").clicked() {
-                    self.error = None;
-                }
-            });
+    pub fn theme(cfg: &RendererConfig) -> egui::Visuals {
+        if cfg.high_contrast {
+            return Self::high_contrast_theme();
+        }
+        let mut visuals = match cfg.theme {
+            ThemePreset::Default if cfg.dark_theme => Self::dark_theme(),
+            ThemePreset::Default => Self::light_theme(),
+            ThemePreset::Famicom => Self::famicom_theme(),
+            ThemePreset::Phosphor => Self::phosphor_theme(),
+        };
+        if let Some(accent) = cfg.accent_color {
+            Self::apply_accent(&mut visuals, accent);
         }
+        visuals
+    }
+
+    /// Recolor the highlights of `visuals` with a custom accent color.
+    fn apply_accent(visuals: &mut Visuals, accent: Color32) {
+        let bg_fill = visuals.panel_fill;
+        let widgets = &mut visuals.widgets;
+        widgets.inactive.fg_stroke.color = accent.lerp_to_gamma(bg_fill, 0.25);
+        widgets.hovered.bg_stroke.color = accent;
+        widgets.hovered.fg_stroke.color = accent;
+        widgets.open.fg_stroke.color = accent;
+        visuals.selection.bg_fill = accent.lerp_to_gamma(bg_fill, 0.7);
+        visuals.selection.stroke.color = accent;
+        visuals.text_cursor.stroke.color = accent;
     }
 
     pub fn dark_theme() -> egui::Visuals {
@@ -1717,4 +3360,195 @@ impl Gui {
             ..Self::dark_theme()
         }
     }
+
+    pub fn famicom_theme() -> egui::Visuals {
+        egui::Visuals {
+            widgets: egui::style::Widgets {
+                noninteractive: WidgetVisuals {
+                    weak_bg_fill: hex_color!("#1c0f10"),
+                    bg_fill: hex_color!("#1c0f10"),
+                    bg_stroke: Stroke::new(1.0, hex_color!("#4a1f22")), // separators, indentation lines
+                    fg_stroke: Stroke::new(1.0, hex_color!("#e8c872")), // normal text color
+                    rounding: Rounding::ZERO,
+                    expansion: 0.0,
+                },
+                inactive: WidgetVisuals {
+                    weak_bg_fill: hex_color!("#4a1f22"), // button background
+                    bg_fill: hex_color!("#4a1f22"),      // checkbox background
+                    bg_stroke: Stroke::default(),
+                    fg_stroke: Stroke::new(1.0, hex_color!("#e05a47")), // button text
+                    rounding: Rounding::ZERO,
+                    expansion: 0.0,
+                },
+                hovered: WidgetVisuals {
+                    weak_bg_fill: hex_color!("#2b1416"),
+                    bg_fill: hex_color!("#2b1416"),
+                    bg_stroke: Stroke::new(1.0, hex_color!("#ffcc4d")), // e.g. hover over window edge or button
+                    fg_stroke: Stroke::new(1.5, hex_color!("#ffd966")),
+                    rounding: Rounding::ZERO,
+                    expansion: 1.0,
+                },
+                active: WidgetVisuals {
+                    weak_bg_fill: hex_color!("#4a1f22"),
+                    bg_fill: hex_color!("#4a1f22"),
+                    bg_stroke: Stroke::new(1.0, hex_color!("#fff0c2")),
+                    fg_stroke: Stroke::new(2.0, hex_color!("#fff0c2")),
+                    rounding: Rounding::ZERO,
+                    expansion: 1.0,
+                },
+                open: WidgetVisuals {
+                    weak_bg_fill: hex_color!("#21110f"),
+                    bg_fill: hex_color!("#1c0f10"),
+                    bg_stroke: Stroke::new(1.0, hex_color!("#4a1f22")),
+                    fg_stroke: Stroke::new(1.0, hex_color!("#ffd966")),
+                    rounding: Rounding::ZERO,
+                    expansion: 0.0,
+                },
+            },
+            selection: Selection {
+                bg_fill: hex_color!("#6b2226"),
+                stroke: Stroke::new(1.0, hex_color!("#ffd966")),
+            },
+            extreme_bg_color: hex_color!("#120809"), // e.g. TextEdit background
+            code_bg_color: hex_color!("#4a1f22"),
+            window_fill: hex_color!("#1c0f10"),
+            window_stroke: Stroke::new(1.0, hex_color!("#4a1f22")),
+            panel_fill: hex_color!("#1c0f10"),
+            text_cursor: TextCursorStyle {
+                stroke: Stroke::new(2.0, hex_color!("#ffd966")),
+                ..Default::default()
+            },
+            ..Self::dark_theme()
+        }
+    }
+
+    pub fn phosphor_theme() -> egui::Visuals {
+        egui::Visuals {
+            widgets: egui::style::Widgets {
+                noninteractive: WidgetVisuals {
+                    weak_bg_fill: hex_color!("#050a05"),
+                    bg_fill: hex_color!("#050a05"),
+                    bg_stroke: Stroke::new(1.0, hex_color!("#0f3d14")), // separators, indentation lines
+                    fg_stroke: Stroke::new(1.0, hex_color!("#33ff66")), // normal text color
+                    rounding: Rounding::ZERO,
+                    expansion: 0.0,
+                },
+                inactive: WidgetVisuals {
+                    weak_bg_fill: hex_color!("#0f3d14"), // button background
+                    bg_fill: hex_color!("#0f3d14"),      // checkbox background
+                    bg_stroke: Stroke::default(),
+                    fg_stroke: Stroke::new(1.0, hex_color!("#1fbf4a")), // button text
+                    rounding: Rounding::ZERO,
+                    expansion: 0.0,
+                },
+                hovered: WidgetVisuals {
+                    weak_bg_fill: hex_color!("#0a1f0d"),
+                    bg_fill: hex_color!("#0a1f0d"),
+                    bg_stroke: Stroke::new(1.0, hex_color!("#66ff8c")), // e.g. hover over window edge or button
+                    fg_stroke: Stroke::new(1.5, hex_color!("#66ff8c")),
+                    rounding: Rounding::ZERO,
+                    expansion: 1.0,
+                },
+                active: WidgetVisuals {
+                    weak_bg_fill: hex_color!("#0f3d14"),
+                    bg_fill: hex_color!("#0f3d14"),
+                    bg_stroke: Stroke::new(1.0, hex_color!("#b3ffc6")),
+                    fg_stroke: Stroke::new(2.0, hex_color!("#b3ffc6")),
+                    rounding: Rounding::ZERO,
+                    expansion: 1.0,
+                },
+                open: WidgetVisuals {
+                    weak_bg_fill: hex_color!("#071208"),
+                    bg_fill: hex_color!("#050a05"),
+                    bg_stroke: Stroke::new(1.0, hex_color!("#0f3d14")),
+                    fg_stroke: Stroke::new(1.0, hex_color!("#66ff8c")),
+                    rounding: Rounding::ZERO,
+                    expansion: 0.0,
+                },
+            },
+            selection: Selection {
+                bg_fill: hex_color!("#0f3d14"),
+                stroke: Stroke::new(1.0, hex_color!("#66ff8c")),
+            },
+            hyperlink_color: hex_color!("#33ffcc"),
+            extreme_bg_color: hex_color!("#000000"), // e.g. TextEdit background
+            code_bg_color: hex_color!("#0f3d14"),
+            warn_fg_color: hex_color!("#ccff33"),
+            error_fg_color: hex_color!("#ff5533"),
+            window_fill: hex_color!("#050a05"),
+            window_stroke: Stroke::new(1.0, hex_color!("#0f3d14")),
+            panel_fill: hex_color!("#050a05"),
+            text_cursor: TextCursorStyle {
+                stroke: Stroke::new(2.0, hex_color!("#66ff8c")),
+                ..Default::default()
+            },
+            ..Self::dark_theme()
+        }
+    }
+
+    pub fn high_contrast_theme() -> egui::Visuals {
+        egui::Visuals {
+            widgets: egui::style::Widgets {
+                noninteractive: WidgetVisuals {
+                    weak_bg_fill: hex_color!("#000000"),
+                    bg_fill: hex_color!("#000000"),
+                    bg_stroke: Stroke::new(1.0, hex_color!("#ffffff")), // separators, indentation lines
+                    fg_stroke: Stroke::new(1.0, hex_color!("#ffffff")), // normal text color
+                    rounding: Rounding::ZERO,
+                    expansion: 0.0,
+                },
+                inactive: WidgetVisuals {
+                    weak_bg_fill: hex_color!("#000000"), // button background
+                    bg_fill: hex_color!("#000000"),      // checkbox background
+                    bg_stroke: Stroke::new(1.0, hex_color!("#ffffff")),
+                    fg_stroke: Stroke::new(1.0, hex_color!("#ffff00")), // button text
+                    rounding: Rounding::ZERO,
+                    expansion: 0.0,
+                },
+                hovered: WidgetVisuals {
+                    weak_bg_fill: hex_color!("#ffff00"),
+                    bg_fill: hex_color!("#ffff00"),
+                    bg_stroke: Stroke::new(2.0, hex_color!("#ffffff")), // e.g. hover over window edge or button
+                    fg_stroke: Stroke::new(2.0, hex_color!("#000000")),
+                    rounding: Rounding::ZERO,
+                    expansion: 1.0,
+                },
+                active: WidgetVisuals {
+                    weak_bg_fill: hex_color!("#00ffff"),
+                    bg_fill: hex_color!("#00ffff"),
+                    bg_stroke: Stroke::new(2.0, hex_color!("#ffffff")),
+                    fg_stroke: Stroke::new(2.0, hex_color!("#000000")),
+                    rounding: Rounding::ZERO,
+                    expansion: 1.0,
+                },
+                open: WidgetVisuals {
+                    weak_bg_fill: hex_color!("#000000"),
+                    bg_fill: hex_color!("#000000"),
+                    bg_stroke: Stroke::new(1.0, hex_color!("#ffffff")),
+                    fg_stroke: Stroke::new(1.0, hex_color!("#ffff00")),
+                    rounding: Rounding::ZERO,
+                    expansion: 0.0,
+                },
+            },
+            selection: Selection {
+                bg_fill: hex_color!("#0000ff"),
+                stroke: Stroke::new(1.0, hex_color!("#ffffff")),
+            },
+            hyperlink_color: hex_color!("#00ffff"),
+            faint_bg_color: hex_color!("#1a1a1a"),
+            extreme_bg_color: hex_color!("#000000"), // e.g. TextEdit background
+            code_bg_color: hex_color!("#000000"),
+            warn_fg_color: hex_color!("#ffff00"),
+            error_fg_color: hex_color!("#ff4040"),
+            window_fill: hex_color!("#000000"),
+            window_stroke: Stroke::new(2.0, hex_color!("#ffffff")),
+            panel_fill: hex_color!("#000000"),
+            text_cursor: TextCursorStyle {
+                stroke: Stroke::new(2.0, hex_color!("#ffff00")),
+                ..Default::default()
+            },
+            striped: false,
+            ..Self::dark_theme()
+        }
+    }
 }
@@ -0,0 +1,311 @@
+use crate::nes::{audio::SampleTap, renderer::gui::lib::ViewportOptions};
+use egui::{
+    pos2, Align2, CentralPanel, Color32, Context, FontId, Rect, Sense, Shape, Stroke, Ui, Vec2,
+    ViewportClass, ViewportId,
+};
+use parking_lot::Mutex;
+use std::{
+    f32::consts::PI,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+/// Viewport showing the mixed audio output as an oscilloscope waveform and a frequency spectrum.
+#[derive(Debug)]
+#[must_use]
+pub struct AudioVisualizer {
+    id: ViewportId,
+    open: Arc<AtomicBool>,
+    state: Arc<Mutex<State>>,
+}
+
+#[derive(Debug)]
+#[must_use]
+struct State {
+    tap: Arc<SampleTap>,
+    last_head: usize,
+    samples: Vec<f32>,
+    window: Vec<f32>,
+    real: Vec<f32>,
+    imag: Vec<f32>,
+    /// Smoothed level in decibels of each spectrum band.
+    bands: Vec<f32>,
+}
+
+impl AudioVisualizer {
+    const TITLE: &'static str = "🎵 Audio Visualizer";
+
+    pub fn new(tap: Arc<SampleTap>) -> Self {
+        Self {
+            id: ViewportId::from_hash_of(Self::TITLE),
+            open: Arc::new(AtomicBool::new(false)),
+            state: Arc::new(Mutex::new(State::new(tap))),
+        }
+    }
+
+    pub fn open(&self) -> bool {
+        self.open.load(Ordering::Acquire)
+    }
+
+    pub fn set_open(&self, open: bool) {
+        self.open.store(open, Ordering::Release);
+    }
+
+    pub fn toggle_open(&self) {
+        let _ = self
+            .open
+            .fetch_update(Ordering::Release, Ordering::Acquire, |open| Some(!open));
+    }
+
+    pub fn show(&mut self, ctx: &Context, opts: ViewportOptions) {
+        if !self.open() {
+            return;
+        }
+
+        #[cfg(feature = "profiling")]
+        puffin::profile_function!();
+
+        let open = Arc::clone(&self.open);
+        let state = Arc::clone(&self.state);
+
+        let mut viewport_builder = egui::ViewportBuilder::default()
+            .with_title(Self::TITLE)
+            .with_inner_size(Vec2::new(640.0, 480.0));
+        if opts.always_on_top {
+            viewport_builder = viewport_builder.with_always_on_top();
+        }
+
+        ctx.show_viewport_deferred(self.id, viewport_builder, move |ctx, class| {
+            let mut state = state.lock();
+            state.update();
+            if class == ViewportClass::Embedded {
+                let mut window_open = open.load(Ordering::Acquire);
+                egui::Window::new(AudioVisualizer::TITLE)
+                    .open(&mut window_open)
+                    .default_size(Vec2::new(480.0, 360.0))
+                    .show(ctx, |ui| state.ui(ui));
+                open.store(window_open, Ordering::Release);
+            } else {
+                CentralPanel::default().show(ctx, |ui| state.ui(ui));
+                if ctx.input(|i| i.viewport().close_requested()) {
+                    open.store(false, Ordering::Release);
+                }
+            }
+            // Samples are played continuously, so keep redrawing while open
+            ctx.request_repaint();
+        });
+    }
+}
+
+impl State {
+    /// Number of samples used for each spectrum. Must be a power of two.
+    const FFT_SIZE: usize = 2048;
+    /// Number of samples drawn in the waveform.
+    const SCOPE_LEN: usize = 1024;
+    const BAND_COUNT: usize = 64;
+    const MIN_FREQ: f32 = 20.0;
+    const MIN_DB: f32 = -80.0;
+    /// How quickly spectrum bands fall, in decibels per update.
+    const DECAY_DB: f32 = 1.5;
+
+    fn new(tap: Arc<SampleTap>) -> Self {
+        const _: () = assert!(State::FFT_SIZE <= SampleTap::CAPACITY);
+
+        // Hann window to reduce spectral leakage
+        let window = (0..Self::FFT_SIZE)
+            .map(|i| 0.5 - 0.5 * (2.0 * PI * i as f32 / (Self::FFT_SIZE - 1) as f32).cos())
+            .collect();
+        Self {
+            tap,
+            last_head: 0,
+            samples: vec![0.0; Self::FFT_SIZE],
+            window,
+            real: vec![0.0; Self::FFT_SIZE],
+            imag: vec![0.0; Self::FFT_SIZE],
+            bands: vec![Self::MIN_DB; Self::BAND_COUNT],
+        }
+    }
+
+    /// Read the latest played samples and recalculate the spectrum if any new ones were played.
+    fn update(&mut self) {
+        #[cfg(feature = "profiling")]
+        puffin::profile_function!();
+
+        let head = self.tap.read_latest(&mut self.samples);
+        if head == self.last_head {
+            return;
+        }
+        self.last_head = head;
+
+        for ((real, imag), (sample, window)) in self
+            .real
+            .iter_mut()
+            .zip(self.imag.iter_mut())
+            .zip(self.samples.iter().zip(&self.window))
+        {
+            *real = sample * window;
+            *imag = 0.0;
+        }
+        fft(&mut self.real, &mut self.imag);
+
+        let sample_rate = self.tap.sample_rate().max(1) as f32;
+        let nyquist = sample_rate / 2.0;
+        let bin_count = Self::FFT_SIZE / 2;
+        // Normalize so a full scale sine wave is 0 dB. The Hann window halves the amplitude.
+        let scale = 4.0 / Self::FFT_SIZE as f32;
+        for (i, band) in self.bands.iter_mut().enumerate() {
+            let freq = |i: usize| {
+                Self::MIN_FREQ * (nyquist / Self::MIN_FREQ).powf(i as f32 / Self::BAND_COUNT as f32)
+            };
+            let bin = |freq: f32| ((freq / nyquist * bin_count as f32) as usize).min(bin_count);
+            let start = bin(freq(i));
+            let end = bin(freq(i + 1)).max(start + 1).min(bin_count);
+            let magnitude = (start..end)
+                .map(|bin| self.real[bin].hypot(self.imag[bin]))
+                .fold(0.0, f32::max);
+            let db = 20.0 * (magnitude * scale).max(1e-6).log10();
+            *band = db.max(*band - Self::DECAY_DB).max(Self::MIN_DB);
+        }
+    }
+
+    fn ui(&self, ui: &mut Ui) {
+        #[cfg(feature = "profiling")]
+        puffin::profile_function!();
+
+        let sample_rate = self.tap.sample_rate();
+        if sample_rate == 0 {
+            ui.label("Audio output is not running. Enable audio in Preferences to visualize it.");
+            return;
+        }
+
+        let height = (ui.available_height() - 2.0 * ui.spacing().item_spacing.y) / 2.0;
+        ui.strong("Waveform");
+        self.waveform(ui, height - ui.text_style_height(&egui::TextStyle::Body));
+        ui.strong("Spectrum");
+        self.spectrum(ui, sample_rate, ui.available_height());
+    }
+
+    fn waveform(&self, ui: &mut Ui, height: f32) {
+        let (rect, _) = ui.allocate_exact_size(
+            Vec2::new(ui.available_width(), height.max(32.0)),
+            Sense::hover(),
+        );
+        let painter = ui.painter_at(rect);
+        let visuals = ui.visuals();
+        painter.rect_filled(rect, 0.0, visuals.extreme_bg_color);
+        painter.hline(
+            rect.x_range(),
+            rect.center().y,
+            Stroke::new(1.0, visuals.weak_text_color()),
+        );
+
+        // Start on the most recent rising zero crossing to keep periodic waveforms steady
+        let latest_start = Self::FFT_SIZE - Self::SCOPE_LEN;
+        let start = (1..=latest_start)
+            .rev()
+            .find(|&i| self.samples[i - 1] < 0.0 && self.samples[i] >= 0.0)
+            .unwrap_or(latest_start);
+        let points = self.samples[start..start + Self::SCOPE_LEN]
+            .iter()
+            .enumerate()
+            .map(|(i, sample)| {
+                let x = rect.left() + rect.width() * i as f32 / (Self::SCOPE_LEN - 1) as f32;
+                let y = rect.center().y - sample.clamp(-1.0, 1.0) * rect.height() / 2.0;
+                pos2(x, y)
+            })
+            .collect();
+        painter.add(Shape::line(
+            points,
+            Stroke::new(1.5, visuals.selection.bg_fill),
+        ));
+    }
+
+    fn spectrum(&self, ui: &mut Ui, sample_rate: u32, height: f32) {
+        let (rect, _) = ui.allocate_exact_size(
+            Vec2::new(ui.available_width(), height.max(32.0)),
+            Sense::hover(),
+        );
+        let painter = ui.painter_at(rect);
+        let visuals = ui.visuals();
+        painter.rect_filled(rect, 0.0, visuals.extreme_bg_color);
+
+        let band_width = rect.width() / Self::BAND_COUNT as f32;
+        for (i, db) in self.bands.iter().enumerate() {
+            let level = (db - Self::MIN_DB) / -Self::MIN_DB;
+            let left = rect.left() + i as f32 * band_width;
+            let bar = Rect::from_min_max(
+                pos2(left + 1.0, rect.bottom() - level * rect.height()),
+                pos2(left + band_width - 1.0, rect.bottom()),
+            );
+            painter.rect_filled(bar, 0.0, visuals.selection.bg_fill);
+        }
+
+        let nyquist = sample_rate as f32 / 2.0;
+        let font = FontId::proportional(10.0);
+        for (freq, label) in [(100.0, "100 Hz"), (1_000.0, "1 kHz"), (10_000.0, "10 kHz")] {
+            if freq >= nyquist {
+                continue;
+            }
+            let x = rect.left()
+                + rect.width() * (freq / Self::MIN_FREQ).ln() / (nyquist / Self::MIN_FREQ).ln();
+            painter.vline(
+                x,
+                rect.y_range(),
+                Stroke::new(1.0, visuals.weak_text_color().gamma_multiply(0.5)),
+            );
+            painter.text(
+                pos2(x + 2.0, rect.top() + 2.0),
+                Align2::LEFT_TOP,
+                label,
+                font.clone(),
+                Color32::from_gray(160),
+            );
+        }
+    }
+}
+
+/// In-place iterative radix-2 fast fourier transform. `real` and `imag` must have the same
+/// power-of-two length.
+fn fft(real: &mut [f32], imag: &mut [f32]) {
+    let len = real.len();
+    debug_assert!(len.is_power_of_two() && imag.len() == len);
+
+    // Bit-reversal permutation
+    let mut j = 0;
+    for i in 1..len {
+        let mut bit = len >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            real.swap(i, j);
+            imag.swap(i, j);
+        }
+    }
+
+    let mut size = 2;
+    while size <= len {
+        let (step_im, step_re) = (-2.0 * PI / size as f32).sin_cos();
+        for start in (0..len).step_by(size) {
+            let (mut w_re, mut w_im) = (1.0, 0.0);
+            for k in 0..size / 2 {
+                let (a, b) = (start + k, start + k + size / 2);
+                let t_re = real[b] * w_re - imag[b] * w_im;
+                let t_im = real[b] * w_im + imag[b] * w_re;
+                real[b] = real[a] - t_re;
+                imag[b] = imag[a] - t_im;
+                real[a] += t_re;
+                imag[a] += t_im;
+                (w_re, w_im) = (
+                    w_re * step_re - w_im * step_im,
+                    w_re * step_im + w_im * step_re,
+                );
+            }
+        }
+        size <<= 1;
+    }
+}
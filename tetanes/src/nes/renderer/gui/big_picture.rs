@@ -0,0 +1,344 @@
+use crate::{
+    feature,
+    nes::{
+        config::Config,
+        event::{EmulationEvent, NesEventProxy, RendererEvent, UiEvent},
+        renderer::gui::{
+            lib::{gamepad_nav_key, ShowShortcut},
+            preferences::Preferences,
+            Menu,
+        },
+        rom::HOMEBREW_ROMS,
+        rom_library::LibraryRom,
+    },
+};
+use egui::{
+    Align, Button, Context, Frame, Id, Key, Layout, Order, ProgressBar, RichText, ScrollArea,
+    TextStyle, Ui, Vec2,
+};
+use std::collections::{hash_map::Entry, HashMap};
+use tetanes_core::fs;
+
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Tab {
+    #[default]
+    Library,
+    SaveStates,
+    Settings,
+}
+
+impl Tab {
+    const ALL: [Self; 3] = [Self::Library, Self::SaveStates, Self::Settings];
+
+    const fn label(&self) -> &'static str {
+        match self {
+            Self::Library => "📚 Library",
+            Self::SaveStates => "💾 Save States",
+            Self::Settings => "🔧 Settings",
+        }
+    }
+
+    fn cycle(&self, offset: isize) -> Self {
+        let len = Self::ALL.len() as isize;
+        let index = Self::ALL.iter().position(|tab| tab == self).unwrap_or(0) as isize;
+        Self::ALL[(index + offset).rem_euclid(len) as usize]
+    }
+}
+
+/// Fullscreen menu designed to be navigated entirely with a controller.
+#[derive(Debug)]
+#[must_use]
+pub struct BigPicture {
+    tx: NesEventProxy,
+    open: bool,
+    tab: Tab,
+    focus_first: bool,
+    /// ROMs found in the watched ROM directory, along with their button labels.
+    rom_library: Vec<(LibraryRom, String)>,
+    /// Number of ROMs hashed out of the total while hashing the ROM library.
+    hash_progress: Option<(usize, usize)>,
+}
+
+impl BigPicture {
+    const TITLE: &'static str = "🎮 Big Picture";
+    const TEXT_SCALE: f32 = 1.75;
+    const MARGIN: f32 = 32.0;
+    const BUTTON_SIZE: Vec2 = Vec2::new(480.0, 48.0);
+
+    pub fn new(tx: NesEventProxy) -> Self {
+        Self {
+            tx,
+            open: false,
+            tab: Tab::default(),
+            focus_first: false,
+            rom_library: Vec::new(),
+            hash_progress: None,
+        }
+    }
+
+    pub const fn open(&self) -> bool {
+        self.open
+    }
+
+    pub fn set_open(&mut self, open: bool) {
+        self.open = open;
+        self.focus_first = open;
+    }
+
+    pub fn toggle_open(&mut self) {
+        self.set_open(!self.open);
+    }
+
+    /// Set the ROM library, labeling each ROM with its game database entry and whether it's a
+    /// duplicate of an earlier ROM.
+    pub fn set_rom_library(&mut self, roms: Vec<LibraryRom>) {
+        let mut originals = HashMap::new();
+        self.rom_library = roms
+            .into_iter()
+            .map(|rom| {
+                let filename = fs::filename(&rom.path);
+                let mut label = filename.to_string();
+                if let Some(game) = &rom.game {
+                    label.push_str(&format!(
+                        " ({}, Mapper {:03})",
+                        game.region, game.mapper_num
+                    ));
+                }
+                if let Some(hash) = &rom.hash {
+                    match originals.entry(hash.sha1.clone()) {
+                        Entry::Occupied(original) => {
+                            label.push_str(&format!(" - Duplicate of {}", original.get()));
+                        }
+                        Entry::Vacant(entry) => {
+                            entry.insert(filename.to_string());
+                        }
+                    }
+                }
+                (rom, label)
+            })
+            .collect();
+    }
+
+    pub fn set_hash_progress(&mut self, (hashed, total): (usize, usize)) {
+        self.hash_progress = (hashed < total).then_some((hashed, total));
+    }
+
+    /// Handle a controller button press while open, returning the equivalent `egui` navigation
+    /// key, if any.
+    pub fn on_button(&mut self, button: gilrs::Button) -> Option<Key> {
+        use gilrs::Button;
+
+        match button {
+            Button::Start => Some(Key::Enter),
+            Button::East => {
+                self.tx.event(RendererEvent::Menu(Menu::BigPicture));
+                None
+            }
+            Button::LeftTrigger => {
+                self.set_tab(self.tab.cycle(-1));
+                None
+            }
+            Button::RightTrigger => {
+                self.set_tab(self.tab.cycle(1));
+                None
+            }
+            _ => gamepad_nav_key(button),
+        }
+    }
+
+    fn set_tab(&mut self, tab: Tab) {
+        self.tab = tab;
+        self.focus_first = true;
+    }
+
+    pub fn show(&mut self, ctx: &Context, cfg: &Config) {
+        #[cfg(feature = "profiling")]
+        puffin::profile_function!();
+
+        if !self.open {
+            return;
+        }
+
+        let screen_rect = ctx.screen_rect();
+        egui::Area::new(Id::new("big_picture"))
+            .order(Order::Foreground)
+            .fixed_pos(screen_rect.min)
+            .show(ctx, |ui| {
+                Frame::none()
+                    .fill(ui.visuals().panel_fill)
+                    .inner_margin(Self::MARGIN)
+                    .show(ui, |ui| {
+                        let size = screen_rect.size() - Vec2::splat(2.0 * Self::MARGIN);
+                        ui.set_min_size(size);
+                        ui.set_max_size(size);
+                        Self::scale_text(ui);
+                        self.ui(ui, cfg);
+                    });
+            });
+    }
+
+    fn scale_text(ui: &mut Ui) {
+        let style = ui.style_mut();
+        for font_id in style.text_styles.values_mut() {
+            font_id.size *= Self::TEXT_SCALE;
+        }
+        style.spacing.button_padding *= Self::TEXT_SCALE;
+        style.spacing.item_spacing *= Self::TEXT_SCALE;
+        style.spacing.interact_size *= Self::TEXT_SCALE;
+        style.spacing.icon_width *= Self::TEXT_SCALE;
+    }
+
+    fn ui(&mut self, ui: &mut Ui, cfg: &Config) {
+        ui.horizontal(|ui| {
+            ui.heading(Self::TITLE);
+            ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                if ui.button("✖ Close").clicked() {
+                    self.tx.event(RendererEvent::Menu(Menu::BigPicture));
+                }
+            });
+        });
+
+        ui.horizontal(|ui| {
+            for tab in Tab::ALL {
+                if ui.selectable_label(self.tab == tab, tab.label()).clicked() {
+                    self.set_tab(tab);
+                }
+            }
+        });
+
+        ui.separator();
+
+        let footer_height = ui.text_style_height(&TextStyle::Body) + Self::MARGIN;
+        ScrollArea::vertical()
+            .max_height(ui.available_height() - footer_height)
+            .auto_shrink(false)
+            .show(ui, |ui| match self.tab {
+                Tab::Library => self.library_tab(ui, cfg),
+                Tab::SaveStates => self.save_states_tab(ui, cfg),
+                Tab::Settings => self.settings_tab(ui, cfg),
+            });
+
+        ui.separator();
+        ui.label(
+            RichText::new("D-Pad: Navigate    A: Select    B: Close    LB/RB: Switch Tab").weak(),
+        );
+    }
+
+    /// Adds a large, focusable button, giving it focus if it's the first on a newly shown tab.
+    fn button(&mut self, ui: &mut Ui, text: impl Into<String>) -> bool {
+        let res = ui.add(Button::new(text.into()).min_size(Self::BUTTON_SIZE));
+        if self.focus_first {
+            self.focus_first = false;
+            res.request_focus();
+        }
+        if res.has_focus() {
+            res.scroll_to_me(None);
+        }
+        res.clicked()
+    }
+
+    fn library_tab(&mut self, ui: &mut Ui, cfg: &Config) {
+        if feature!(Filesystem) && self.button(ui, "📂 Load ROM...") {
+            self.tx.event(UiEvent::LoadRomDialog);
+            self.tx.event(RendererEvent::Menu(Menu::BigPicture));
+        }
+
+        if feature!(Filesystem) && !self.rom_library.is_empty() {
+            ui.add_space(Self::MARGIN);
+            ui.strong("ROM Library");
+            if let Some((hashed, total)) = self.hash_progress {
+                let progress = ProgressBar::new(hashed as f32 / total as f32)
+                    .desired_width(Self::BUTTON_SIZE.x)
+                    .text(format!(
+                        "Matching ROMs with the game database... {hashed}/{total}"
+                    ));
+                ui.add(progress);
+            }
+            for index in 0..self.rom_library.len() {
+                let label = self.rom_library[index].1.clone();
+                if self.button(ui, label) {
+                    let path = self.rom_library[index].0.path.clone();
+                    self.tx.event(EmulationEvent::LoadRomPath(path));
+                    self.tx.event(RendererEvent::Menu(Menu::BigPicture));
+                }
+            }
+        }
+
+        if feature!(Filesystem) && !cfg.renderer.recent_roms.is_empty() {
+            ui.add_space(Self::MARGIN);
+            ui.strong("Recent ROMs");
+            for rom in cfg.renderer.recent_roms.iter() {
+                if !rom.path.is_file() {
+                    continue;
+                }
+                if self.button(ui, fs::filename(&rom.path)) {
+                    self.tx.event(EmulationEvent::LoadRomPath(rom.path.clone()));
+                    self.tx.event(RendererEvent::Menu(Menu::BigPicture));
+                }
+            }
+        }
+
+        if cfg.startup.kiosk() {
+            return;
+        }
+
+        ui.add_space(Self::MARGIN);
+        ui.strong("Homebrew ROMs");
+        for rom in HOMEBREW_ROMS {
+            if self.button(ui, rom.name) {
+                self.tx
+                    .event(EmulationEvent::LoadRom((rom.name.to_string(), rom.data())));
+                self.tx.event(RendererEvent::Menu(Menu::BigPicture));
+            }
+        }
+    }
+
+    fn save_states_tab(&mut self, ui: &mut Ui, cfg: &Config) {
+        let save_slot = cfg.emulation.save_slot;
+
+        if self.button(ui, format!("💾 Save State (Slot {save_slot})")) {
+            self.tx.event(EmulationEvent::SaveState(save_slot));
+        }
+        if self.button(ui, format!("⟲ Load State (Slot {save_slot})")) {
+            self.tx.event(EmulationEvent::LoadState(save_slot));
+            self.tx.event(RendererEvent::Menu(Menu::BigPicture));
+        }
+
+        ui.add_space(Self::MARGIN);
+        ui.strong("Save Slot");
+        ui.horizontal(|ui| {
+            Preferences::save_slot_radio(&self.tx, ui, save_slot, cfg, ShowShortcut::No);
+        });
+    }
+
+    fn settings_tab(&mut self, ui: &mut Ui, cfg: &Config) {
+        if self.button(ui, "▶ Resume") {
+            self.tx.event(RendererEvent::Menu(Menu::BigPicture));
+        }
+
+        ui.add_space(Self::MARGIN);
+        Preferences::fullscreen_checkbox(&self.tx, ui, cfg.renderer.fullscreen, None);
+        Preferences::messages_checkbox(&self.tx, ui, cfg.renderer.show_messages, None);
+
+        ui.add_space(Self::MARGIN);
+        ui.strong("Speed");
+        Preferences::speed_slider(&self.tx, ui, cfg.emulation.speed);
+
+        ui.add_space(Self::MARGIN);
+        ui.strong("Video Filter");
+        Preferences::video_filter_radio(&self.tx, ui, cfg.deck.filter);
+
+        if cfg.startup.kiosk() {
+            return;
+        }
+
+        ui.add_space(Self::MARGIN);
+        if self.button(ui, "🔧 All Preferences...") {
+            self.tx.event(RendererEvent::Menu(Menu::BigPicture));
+            self.tx.event(RendererEvent::Menu(Menu::Preferences));
+        }
+        if self.button(ui, "⏹ Quit") {
+            self.tx.event(UiEvent::Terminate);
+        }
+    }
+}
@@ -0,0 +1,228 @@
+use crate::{feature, logging, nes::config::PathsConfig};
+use anyhow::Context as _;
+use chrono::{DateTime, Local};
+use egui::{Align2, Color32, Context, RichText, ScrollArea, Ui, Vec2};
+use std::{fmt::Write, path::PathBuf};
+
+/// Details about an error, including system information and recent logs to help with filing
+/// actionable bug reports.
+#[derive(Debug, Clone)]
+#[must_use]
+pub struct Report {
+    title: String,
+    /// The error followed by each of its underlying causes.
+    errors: Vec<String>,
+    backtrace: Option<String>,
+    system_info: Vec<(&'static str, String)>,
+    logs: Vec<String>,
+    timestamp: DateTime<Local>,
+}
+
+impl Report {
+    pub fn new(
+        title: impl Into<String>,
+        errors: Vec<String>,
+        backtrace: Option<String>,
+        adapter_info: Option<&wgpu::AdapterInfo>,
+    ) -> Self {
+        let mut system_info = vec![
+            ("Version", env!("CARGO_PKG_VERSION").to_string()),
+            (
+                "Platform",
+                format!("{} ({})", std::env::consts::OS, std::env::consts::ARCH),
+            ),
+        ];
+        if let Some(info) = adapter_info {
+            system_info.push((
+                "GPU",
+                format!("{} ({:?}, {:?})", info.name, info.backend, info.device_type),
+            ));
+            system_info.push(("Driver", format!("{} {}", info.driver, info.driver_info)));
+        }
+        Self {
+            title: title.into(),
+            errors,
+            backtrace,
+            system_info,
+            logs: logging::recent_logs(),
+            timestamp: Local::now(),
+        }
+    }
+
+    pub fn from_error(err: &anyhow::Error, adapter_info: Option<&wgpu::AdapterInfo>) -> Self {
+        Self::new(
+            "Error",
+            err.chain().map(ToString::to_string).collect(),
+            None,
+            adapter_info,
+        )
+    }
+
+    /// Plain-text version of the report, suitable for pasting into a bug report.
+    pub fn text(&self) -> String {
+        let mut text = format!(
+            "TetaNES {} - {}\n\n",
+            self.title,
+            self.timestamp.to_rfc3339()
+        );
+        for (i, error) in self.errors.iter().enumerate() {
+            if i == 0 {
+                let _ = writeln!(text, "{error}");
+            } else {
+                let _ = writeln!(text, "  caused by: {error}");
+            }
+        }
+        text.push_str("\nSystem:\n");
+        for (name, value) in &self.system_info {
+            let _ = writeln!(text, "  {name}: {value}");
+        }
+        if let Some(backtrace) = &self.backtrace {
+            let _ = write!(text, "\nBacktrace:\n{backtrace}\n");
+        }
+        text.push_str("\nRecent Logs:\n");
+        for line in &self.logs {
+            let _ = writeln!(text, "{line}");
+        }
+        text
+    }
+
+    fn save(&self, paths: &PathsConfig) -> anyhow::Result<PathBuf> {
+        let path = paths
+            .data_dir()
+            .join(ErrorReport::REPORT_DIR)
+            .join(
+                self.timestamp
+                    .format("report_%Y-%m-%d_at_%H_%M_%S")
+                    .to_string(),
+            )
+            .with_extension("txt");
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).with_context(|| {
+                format!("failed to create report directory: {}", parent.display())
+            })?;
+        }
+        std::fs::write(&path, self.text())
+            .with_context(|| format!("failed to save report: {}", path.display()))?;
+        Ok(path)
+    }
+}
+
+/// Dialog showing the most recent error [`Report`].
+#[derive(Default, Debug)]
+#[must_use]
+pub struct ErrorReport {
+    report: Option<Report>,
+    status: Option<(String, Color32)>,
+}
+
+impl ErrorReport {
+    const REPORT_DIR: &'static str = "reports";
+
+    pub fn open(&mut self, report: Report) {
+        self.report = Some(report);
+        self.status = None;
+    }
+
+    pub const fn is_open(&self) -> bool {
+        self.report.is_some()
+    }
+
+    pub fn show(&mut self, ctx: &Context, paths: &PathsConfig) {
+        #[cfg(feature = "profiling")]
+        puffin::profile_function!();
+
+        let Some(report) = &self.report else {
+            return;
+        };
+
+        let mut open = true;
+        let mut close_window = false;
+        egui::Window::new(format!("⚠ {}", report.title))
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(Align2::CENTER_CENTER, Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.set_max_width(600.0);
+                close_window = self.ui(ui, paths);
+            });
+        if !open || close_window {
+            self.report = None;
+        }
+    }
+
+    /// Returns whether the dialog should be closed.
+    fn ui(&mut self, ui: &mut Ui, paths: &PathsConfig) -> bool {
+        let Some(report) = &self.report else {
+            return true;
+        };
+
+        if let Some((error, causes)) = report.errors.split_first() {
+            ui.label(RichText::new(error).color(Color32::RED));
+            for cause in causes {
+                ui.label(format!("caused by: {cause}"));
+            }
+        }
+        ui.add_space(8.0);
+
+        ui.collapsing("System Information", |ui| {
+            egui::Grid::new("error_report_system")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    for (name, value) in &report.system_info {
+                        ui.strong(*name);
+                        ui.label(value);
+                        ui.end_row();
+                    }
+                });
+        });
+        if let Some(backtrace) = &report.backtrace {
+            ui.collapsing("Backtrace", |ui| {
+                ScrollArea::both()
+                    .id_salt("error_report_backtrace")
+                    .max_height(300.0)
+                    .show(ui, |ui| {
+                        ui.label(RichText::new(backtrace).monospace());
+                    });
+            });
+        }
+        ui.collapsing(format!("Recent Logs ({})", report.logs.len()), |ui| {
+            ScrollArea::both()
+                .id_salt("error_report_logs")
+                .max_height(300.0)
+                .stick_to_bottom(true)
+                .show(ui, |ui| {
+                    for line in &report.logs {
+                        ui.label(RichText::new(line).monospace());
+                    }
+                });
+        });
+        ui.add_space(8.0);
+
+        let mut close_window = false;
+        ui.horizontal(|ui| {
+            if ui.button("📋 Copy to Clipboard").clicked() {
+                ui.ctx().copy_text(report.text());
+                self.status = Some(("Copied report to clipboard.".to_string(), Color32::GRAY));
+            }
+            if feature!(Filesystem) && ui.button("💾 Save Report").clicked() {
+                self.status = Some(match report.save(paths) {
+                    Ok(path) => (format!("Saved report to {}", path.display()), Color32::GRAY),
+                    Err(err) => (format!("{err:?}"), Color32::RED),
+                });
+            }
+            if ui.button("OK").clicked() {
+                close_window = true;
+            }
+        });
+        if let Some((status, color)) = &self.status {
+            ui.label(RichText::new(status).color(*color));
+        }
+        ui.hyperlink_to(
+            "Report an Issue",
+            "https://github.com/lukexor/tetanes/issues/new",
+        );
+
+        close_window
+    }
+}
@@ -0,0 +1,201 @@
+use crate::nes::config::Config;
+use anyhow::Context as _;
+use egui::{Color32, Context, RichText, ScrollArea, TextEdit, Ui};
+use std::path::{Path, PathBuf};
+use tetanes_core::fs;
+use tracing::error;
+
+/// Window for viewing and editing notes about the loaded ROM, like its controls, passwords or
+/// anything else worth remembering.
+///
+/// Notes are read from a Markdown or text file next to the ROM with the same name, e.g.
+/// `Game.md` or `Game.txt` for `Game.nes`, if one exists. Otherwise they're kept with the
+/// ROM's save states.
+#[derive(Default, Debug)]
+#[must_use]
+pub struct GameNotes {
+    open: bool,
+    editing: bool,
+    /// Name of the ROM the notes were loaded for.
+    rom_name: Option<String>,
+    path: Option<PathBuf>,
+    text: String,
+    saved_text: String,
+    status: Option<(String, Color32)>,
+}
+
+impl GameNotes {
+    const TITLE: &'static str = "📝 Game Notes";
+    /// Extensions of notes files checked for next to the ROM, in order.
+    const SIDECAR_EXTENSIONS: [&'static str; 2] = ["md", "txt"];
+
+    pub const fn open(&self) -> bool {
+        self.open
+    }
+
+    pub fn set_open(&mut self, open: bool) {
+        self.open = open;
+    }
+
+    /// Path to the notes file for the ROM named `name`, preferring an existing file next to the
+    /// ROM.
+    fn path(cfg: &Config, name: &str) -> PathBuf {
+        cfg.renderer
+            .recent_roms
+            .iter()
+            .filter(|rom| fs::filename(&rom.path) == name)
+            .max_by_key(|rom| rom.last_played)
+            .and_then(|rom| {
+                Self::SIDECAR_EXTENSIONS
+                    .iter()
+                    .map(|ext| rom.path.with_extension(ext))
+                    .find(|path| path.is_file())
+            })
+            .unwrap_or_else(|| cfg.paths.notes_path(name))
+    }
+
+    fn load(path: &Path) -> anyhow::Result<String> {
+        if !path.exists() {
+            return Ok(String::new());
+        }
+        std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read game notes: {}", path.display()))
+    }
+
+    fn save(&mut self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let res = path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| std::fs::write(path, &self.text))
+            .with_context(|| format!("failed to save game notes: {}", path.display()));
+        match res {
+            Ok(()) => {
+                self.saved_text.clone_from(&self.text);
+                self.status = Some(("Saved.".to_string(), Color32::GREEN));
+            }
+            Err(err) => {
+                error!("{err:?}");
+                self.status = Some((err.to_string(), Color32::RED));
+            }
+        }
+    }
+
+    /// Switch to the notes for the loaded ROM, saving any unsaved edits to the previous notes
+    /// first.
+    pub fn set_rom(&mut self, cfg: &Config, rom_name: Option<&str>) {
+        if self.rom_name.as_deref() == rom_name {
+            return;
+        }
+        if self.text != self.saved_text {
+            self.save();
+        }
+
+        self.rom_name = rom_name.map(ToString::to_string);
+        self.path = rom_name.map(|name| Self::path(cfg, name));
+        self.status = None;
+        self.text = match self.path.as_deref().map(Self::load).transpose() {
+            Ok(text) => text.unwrap_or_default(),
+            Err(err) => {
+                error!("{err:?}");
+                self.status = Some((err.to_string(), Color32::RED));
+                String::new()
+            }
+        };
+        self.saved_text.clone_from(&self.text);
+    }
+
+    pub fn show(&mut self, ctx: &Context, enabled: bool) {
+        #[cfg(feature = "profiling")]
+        puffin::profile_function!();
+
+        if !self.open {
+            return;
+        }
+
+        let mut open = self.open;
+        egui::Window::new(Self::TITLE)
+            .open(&mut open)
+            .default_size([400.0, 320.0])
+            .show(ctx, |ui| {
+                ui.add_enabled_ui(enabled, |ui| self.ui(ui));
+            });
+        if !open && self.text != self.saved_text {
+            self.save();
+        }
+        self.open = open;
+    }
+
+    fn ui(&mut self, ui: &mut Ui) {
+        let Some(path) = &self.path else {
+            ui.label("No ROM is loaded.");
+            return;
+        };
+        let path = path.clone();
+
+        ui.horizontal(|ui| {
+            ui.selectable_value(&mut self.editing, false, "👁 View");
+            ui.selectable_value(&mut self.editing, true, "✏ Edit");
+
+            ui.separator();
+
+            let unsaved = self.text != self.saved_text;
+            if ui
+                .add_enabled(unsaved, egui::Button::new("💾 Save"))
+                .on_hover_text(format!("Save notes to {}.", path.display()))
+                .clicked()
+            {
+                self.save();
+            }
+            if unsaved {
+                ui.label(RichText::new("Unsaved changes").weak());
+            } else if let Some((status, color)) = &self.status {
+                ui.colored_label(*color, status);
+            }
+        });
+
+        ui.separator();
+
+        ScrollArea::vertical().auto_shrink(false).show(ui, |ui| {
+            if self.editing {
+                ui.add(
+                    TextEdit::multiline(&mut self.text)
+                        .hint_text("Controls, passwords, or anything else worth remembering.")
+                        .desired_width(f32::INFINITY)
+                        .desired_rows(12),
+                );
+            } else if self.text.trim().is_empty() {
+                ui.label("No notes for this game yet. Select Edit to add some.");
+            } else {
+                Self::markdown(ui, &self.text);
+            }
+        });
+    }
+
+    /// Show `text` with headings, list items and code blocks from Markdown styled.
+    fn markdown(ui: &mut Ui, text: &str) {
+        let mut in_code_block = false;
+        for line in text.lines() {
+            let trimmed = line.trim_start();
+            if trimmed.starts_with("```") {
+                in_code_block = !in_code_block;
+            } else if in_code_block {
+                ui.label(RichText::new(line).monospace());
+            } else if let Some(heading) = trimmed.strip_prefix('#') {
+                let heading = heading.trim_start_matches('#').trim();
+                ui.label(RichText::new(heading).heading());
+            } else if let Some(item) = trimmed
+                .strip_prefix("- ")
+                .or_else(|| trimmed.strip_prefix("* "))
+            {
+                ui.label(format!("  • {item}"));
+            } else if trimmed.is_empty() {
+                ui.add_space(ui.spacing().item_spacing.y);
+            } else {
+                ui.label(line);
+            }
+        }
+    }
+}
@@ -1,12 +1,13 @@
 use crate::nes::{
     action::Action,
-    config::Config,
-    event::{ConfigEvent, NesEventProxy},
-    input::{Gamepads, Input},
+    config::{Config, InputConfig},
+    event::{ConfigEvent, NesEventProxy, UiEvent},
+    input::{ChordInput, Gamepads, Input, ShortcutPreset},
     renderer::gui::lib::ViewportOptions,
 };
 use egui::{
-    Align2, Button, CentralPanel, Context, Grid, ScrollArea, Ui, Vec2, ViewportClass, ViewportId,
+    Align2, Button, CentralPanel, Context, Grid, Key, ScrollArea, Ui, Vec2, ViewportClass,
+    ViewportId,
 };
 use parking_lot::Mutex;
 use std::sync::{
@@ -16,7 +17,7 @@ use std::sync::{
 use tetanes_core::input::Player;
 use tracing::warn;
 use uuid::Uuid;
-use winit::event::ElementState;
+use winit::event::{DeviceId, ElementState};
 
 #[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
 pub enum Tab {
@@ -32,6 +33,7 @@ pub struct State {
     tab: Tab,
     pending_input: Option<PendingInput>,
     gamepad_unassign_confirm: Option<(Player, Player, Uuid)>,
+    new_profile: String,
 }
 
 #[derive(Debug)]
@@ -49,13 +51,18 @@ pub struct PendingInput {
     input: Option<Input>,
     binding: usize,
     conflict: Option<Action>,
+    sequence: bool,
+    first_step: Option<ChordInput>,
 }
 
 #[derive(Debug)]
 #[must_use]
 pub struct GamepadState {
     input_events: Vec<(Input, ElementState)>,
+    held_buttons: Vec<ChordInput>,
     connected: Option<Vec<ConnectedGamepad>>,
+    keyboards: Vec<(DeviceId, Option<Player>)>,
+    keyboards_shared: bool,
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -78,6 +85,7 @@ impl Keybinds {
                 tab: Tab::default(),
                 pending_input: None,
                 gamepad_unassign_confirm: None,
+                new_profile: String::new(),
             })),
             resources: None,
         }
@@ -89,6 +97,18 @@ impl Keybinds {
         })
     }
 
+    /// Whether a keybind dialog is open that controller buttons can navigate. Pending bindings
+    /// capture controller buttons instead, until a conflict needs resolving.
+    pub fn wants_gamepad_nav(&self) -> bool {
+        self.state.try_lock().is_some_and(|state| {
+            state.gamepad_unassign_confirm.is_some()
+                || state
+                    .pending_input
+                    .as_ref()
+                    .is_some_and(|pending| pending.conflict.is_some())
+        })
+    }
+
     pub const fn id(&self) -> ViewportId {
         self.id
     }
@@ -115,6 +135,7 @@ impl Keybinds {
                     .events()
                     .filter_map(|event| gamepads.input_from_event(event, cfg))
                     .collect::<Vec<_>>(),
+                held_buttons: Self::held_buttons(gamepads, cfg),
                 connected: gamepads.list().map(|gamepad_list| {
                     gamepad_list
                         .map(|(_, gamepad)| {
@@ -127,10 +148,63 @@ impl Keybinds {
                         })
                         .collect::<Vec<_>>()
                 }),
+                keyboards: gamepads
+                    .keyboards()
+                    .iter()
+                    .map(|id| (*id, cfg.input.keyboard_assignment(id)))
+                    .collect(),
+                keyboards_shared: gamepads.keyboards_shared(),
             },
         ));
     }
 
+    fn held_buttons(gamepads: &Gamepads, cfg: &Config) -> Vec<ChordInput> {
+        use gilrs::Button::*;
+
+        const BUTTONS: [gilrs::Button; 19] = [
+            South,
+            East,
+            North,
+            West,
+            C,
+            Z,
+            LeftTrigger,
+            LeftTrigger2,
+            RightTrigger,
+            RightTrigger2,
+            Select,
+            Start,
+            Mode,
+            LeftThumb,
+            RightThumb,
+            DPadUp,
+            DPadDown,
+            DPadLeft,
+            DPadRight,
+        ];
+
+        gamepads
+            .list()
+            .map(|gamepad_list| {
+                gamepad_list
+                    .filter_map(|(_, gamepad)| {
+                        let player = cfg
+                            .input
+                            .gamepad_assignment(&Gamepads::create_uuid(&gamepad))?;
+                        Some(
+                            BUTTONS
+                                .into_iter()
+                                .filter(|button| gamepad.is_pressed(*button))
+                                .map(|button| ChordInput::Button(player, button))
+                                .collect::<Vec<_>>(),
+                        )
+                    })
+                    .flatten()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
     pub fn show(&mut self, ctx: &Context, opts: ViewportOptions) {
         if !self.open() {
             return;
@@ -183,10 +257,14 @@ impl State {
         #[cfg(feature = "profiling")]
         puffin::profile_function!();
 
-        self.show_set_keybind_window(ui.ctx(), cfg, &gamepad_state.input_events);
+        self.show_set_keybind_window(ui.ctx(), cfg, gamepad_state);
         self.show_gamepad_unassign_window(ui.ctx());
 
         ui.add_enabled_ui(enabled, |ui| {
+            self.profile_controls(ui, cfg);
+
+            ui.separator();
+
             ui.horizontal(|ui| {
                 ui.selectable_value(&mut self.tab, Tab::Shortcuts, "Shortcuts");
                 ui.selectable_value(&mut self.tab, Tab::Joypad(Player::One), "Player1");
@@ -198,10 +276,83 @@ impl State {
             ui.separator();
 
             match self.tab {
-                Tab::Shortcuts => self.list(ui, None, cfg, gamepad_state.connected.as_deref()),
-                Tab::Joypad(player) => {
-                    self.list(ui, Some(player), cfg, gamepad_state.connected.as_deref())
+                Tab::Shortcuts => self.list(ui, None, cfg, gamepad_state),
+                Tab::Joypad(player) => self.list(ui, Some(player), cfg, gamepad_state),
+            }
+        });
+    }
+
+    fn profile_controls(&mut self, ui: &mut Ui, cfg: &Config) {
+        ui.horizontal_wrapped(|ui| {
+            let mut profile = cfg.input.profile.clone();
+            egui::ComboBox::from_label("Profile")
+                .selected_text(&profile)
+                .show_ui(ui, |ui| {
+                    for name in &cfg.input.profiles {
+                        ui.selectable_value(&mut profile, name.clone(), name);
+                    }
+                });
+            if profile != cfg.input.profile {
+                self.tx.event(ConfigEvent::KeybindProfile(profile));
+            }
+
+            let can_remove = cfg.input.profile != InputConfig::DEFAULT_PROFILE;
+            let res = ui
+                .add_enabled(can_remove, Button::new("🗑 Delete"))
+                .on_hover_text("Remove the current keybind profile.")
+                .on_disabled_hover_text("The default keybind profile can't be removed.");
+            if res.clicked() {
+                self.tx
+                    .event(ConfigEvent::KeybindProfileRemove(cfg.input.profile.clone()));
+            }
+
+            ui.separator();
+
+            ui.add(
+                egui::TextEdit::singleline(&mut self.new_profile)
+                    .hint_text("New profile name")
+                    .desired_width(140.0),
+            );
+            let name = self.new_profile.trim();
+            let res = ui
+                .add_enabled(!name.is_empty(), Button::new("➕ Create"))
+                .on_hover_text("Create a new keybind profile from the current keybinds.");
+            if res.clicked() {
+                self.tx.event(ConfigEvent::KeybindProfile(name.to_string()));
+                self.new_profile.clear();
+            }
+            ui.menu_button("⌨ Presets", |ui| {
+                for preset in ShortcutPreset::as_slice() {
+                    if ui
+                        .button(preset.as_ref())
+                        .on_hover_text(preset.description())
+                        .clicked()
+                    {
+                        self.tx.event(ConfigEvent::KeybindPreset(*preset));
+                        ui.close_menu();
+                    }
                 }
+            })
+            .response
+            .on_hover_text(
+                "Create a profile from a preset shortcut layout, or reset it if it already exists.",
+            );
+
+            ui.separator();
+
+            if ui
+                .button("📂 Import...")
+                .on_hover_text("Import a keybind profile from a JSON file.")
+                .clicked()
+            {
+                self.tx.event(UiEvent::LoadKeybindsDialog);
+            }
+            if ui
+                .button("💾 Export...")
+                .on_hover_text("Export the current keybind profile to a JSON file.")
+                .clicked()
+            {
+                self.tx.event(UiEvent::SaveKeybindsDialog);
             }
         });
     }
@@ -211,7 +362,7 @@ impl State {
         ui: &mut Ui,
         player: Option<Player>,
         cfg: &Config,
-        connected_gamepads: Option<&[ConnectedGamepad]>,
+        gamepad_state: &GamepadState,
     ) {
         #[cfg(feature = "profiling")]
         puffin::profile_function!();
@@ -219,7 +370,24 @@ impl State {
         ui.set_min_height(ui.available_height());
 
         if let Some(player) = player {
-            self.player_gamepad_combo(ui, player, connected_gamepads);
+            self.player_gamepad_combo(ui, player, gamepad_state.connected.as_deref());
+            self.player_keyboard_combo(
+                ui,
+                player,
+                &gamepad_state.keyboards,
+                gamepad_state.keyboards_shared,
+            );
+
+            let mut exclusive = cfg.input.exclusive_devices;
+            let res = ui
+                .checkbox(&mut exclusive, "Exclusive Devices")
+                .on_hover_text(concat!(
+                "Only let an assigned keyboard or gamepad control the player it's assigned to, ",
+                "so shared or split keyboard setups don't control other players."
+            ));
+            if res.changed() {
+                self.tx.event(ConfigEvent::ExclusiveDevices(exclusive));
+            }
 
             ui.separator();
         }
@@ -256,6 +424,8 @@ impl State {
                                 input: None,
                                 binding: slot,
                                 conflict: None,
+                                sequence: false,
+                                first_step: None,
                             });
                         } else if res.secondary_clicked() {
                             if let Some(input) = input {
@@ -342,11 +512,80 @@ impl State {
         });
     }
 
+    fn player_keyboard_combo(
+        &mut self,
+        ui: &mut Ui,
+        player: Player,
+        keyboards: &[(DeviceId, Option<Player>)],
+        keyboards_shared: bool,
+    ) {
+        ui.horizontal(|ui| {
+            let keyboard_label = "🖮 Assigned Keyboard:";
+            let keyboard_name = |index: usize| format!("Keyboard {}", index + 1);
+
+            if keyboards.is_empty() {
+                let selected_text = if keyboards_shared {
+                    "Not supported on this platform"
+                } else {
+                    "Press a key to detect keyboards"
+                };
+                ui.add_enabled_ui(false, |ui| {
+                    let combo =
+                        egui::ComboBox::from_label(keyboard_label).selected_text(selected_text);
+                    combo.show_ui(ui, |_| {});
+                })
+                .response
+                .on_disabled_hover_text(if keyboards_shared {
+                    concat!(
+                        "This platform reports every keyboard as the same device, ",
+                        "so keyboards can't be assigned to players."
+                    )
+                } else {
+                    "Keyboards are detected as they're typed on."
+                });
+                return;
+            }
+
+            let previous_assigned = keyboards
+                .iter()
+                .position(|(_, assignment)| *assignment == Some(player));
+            let mut assigned = previous_assigned;
+            let combo = egui::ComboBox::from_label(keyboard_label)
+                .selected_text(assigned.map_or_else(|| "Unassigned".to_string(), keyboard_name));
+            combo
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut assigned, None, "Unassigned");
+                    for (index, (_, assignment)) in keyboards.iter().enumerate() {
+                        let label = match assignment {
+                            Some(other) if *other != player => {
+                                format!("{} (Player {other:?})", keyboard_name(index))
+                            }
+                            _ => keyboard_name(index),
+                        };
+                        ui.selectable_value(&mut assigned, Some(index), label);
+                    }
+                })
+                .response
+                .on_hover_text(concat!(
+                    "Keyboards are numbered in the order they're first typed on. ",
+                    "Assignments last until TetaNES is closed."
+                ));
+            if previous_assigned != assigned {
+                match assigned {
+                    Some(index) => self
+                        .tx
+                        .event(ConfigEvent::KeyboardAssign((player, keyboards[index].0))),
+                    None => self.tx.event(ConfigEvent::KeyboardUnassign(player)),
+                }
+            }
+        });
+    }
+
     pub fn show_set_keybind_window(
         &mut self,
         ctx: &Context,
         cfg: &Config,
-        gamepad_events: &[(Input, ElementState)],
+        gamepad_state: &GamepadState,
     ) {
         if self.pending_input.is_none() {
             return;
@@ -361,7 +600,7 @@ impl State {
             .collapsible(false)
             .resizable(false)
             .open(&mut set_keybind_open)
-            .show(ctx, |ui| self.set_keybind(ui, cfg, gamepad_events));
+            .show(ctx, |ui| self.set_keybind(ui, cfg, gamepad_state));
         if let Some(ref res) = res {
             // Force on-top focus when embedded
             if set_keybind_open {
@@ -376,17 +615,13 @@ impl State {
         }
     }
 
-    pub fn set_keybind(
-        &mut self,
-        ui: &mut Ui,
-        cfg: &Config,
-        gamepad_events: &[(Input, ElementState)],
-    ) {
+    pub fn set_keybind(&mut self, ui: &mut Ui, cfg: &Config, gamepad_state: &GamepadState) {
         let Some(PendingInput {
             action,
             binding,
             mut input,
             mut conflict,
+            first_step,
             ..
         }) = self.pending_input
         else {
@@ -402,15 +637,25 @@ impl State {
                 if ui.button("Overwrite").clicked() {
                     conflict = None;
                 }
-                if ui.button("Cancel").clicked() {
+                if ui.button("Cancel").clicked() || ui.input(|i| i.key_pressed(Key::Escape)) {
                     self.pending_input = None;
                     input = None;
                 }
             });
         } else {
-            ui.label(format!(
-                "Press any key on your keyboard or controller to set a new binding for {action}.",
-            ));
+            match first_step {
+                Some(first_step) => ui.label(format!(
+                    "Press the next key or button after {} to finish the sequence for {action}.",
+                    Input::fmt(first_step.into()),
+                )),
+                None => ui.label(format!(
+                    "Press any key on your keyboard or controller to set a new binding for {action}. Hold one key or button while pressing another to bind a chord.",
+                )),
+            };
+            if let Some(keybind) = &mut self.pending_input {
+                ui.checkbox(&mut keybind.sequence, "Multi-step sequence")
+                    .on_hover_text("Bind two inputs pressed one after the other.");
+            }
         }
 
         match input {
@@ -437,7 +682,20 @@ impl State {
                                 } => {
                                     // TODO: Ignore unsupported key mappings for now as egui supports less
                                     // overall than winit
-                                    return Input::try_from((key, modifiers)).ok();
+                                    let input = Input::try_from((key, modifiers)).ok()?;
+                                    // Any other key still held down forms a chord
+                                    let held = i
+                                        .keys_down
+                                        .iter()
+                                        .filter(|held| **held != key)
+                                        .find_map(|held| {
+                                            Input::try_from((*held, egui::Modifiers::NONE)).ok()
+                                        })
+                                        .and_then(ChordInput::from_input);
+                                    return Some(match (held, ChordInput::from_input(input)) {
+                                        (Some(held), Some(pressed)) => Input::Chord(held, pressed),
+                                        _ => input,
+                                    });
                                 }
                                 Event::PointerButton {
                                     button,
@@ -449,14 +707,36 @@ impl State {
                                 _ => (),
                             }
                         }
-                        for (input, state) in gamepad_events {
+                        for (input, state) in &gamepad_state.input_events {
                             if *state == ElementState::Released {
-                                return Some(*input);
+                                let held = gamepad_state
+                                    .held_buttons
+                                    .iter()
+                                    .find(|held| Some(**held) != ChordInput::from_input(*input));
+                                return Some(match (held, ChordInput::from_input(*input)) {
+                                    (Some(held), Some(pressed)) => Input::Chord(*held, pressed),
+                                    _ => *input,
+                                });
                             }
                         }
                         None
                     });
 
+                    // Wait for the second step of a sequence
+                    let input = match (input, keybind.sequence) {
+                        (Some(input), true) => match ChordInput::from_input(input) {
+                            Some(step) => match keybind.first_step.take() {
+                                Some(first_step) => Some(Input::Sequence(first_step, step)),
+                                None => {
+                                    keybind.first_step = Some(step);
+                                    None
+                                }
+                            },
+                            None => Some(input),
+                        },
+                        (input, _) => input,
+                    };
+
                     if let Some(input) = input {
                         keybind.input = Some(input);
                         let binds = cfg
@@ -518,7 +798,7 @@ impl State {
                         .event(ConfigEvent::GamepadAssign((new_player, uuid)));
                     self.gamepad_unassign_confirm = None;
                 }
-                if ui.button("Cancel").clicked() {
+                if ui.button("Cancel").clicked() || ui.input(|i| i.key_pressed(Key::Escape)) {
                     self.gamepad_unassign_confirm = None;
                 }
             });
@@ -1,13 +1,14 @@
 use crate::nes::{
     config::Config,
     input::{Gamepads, Input},
-    renderer::event::{
-        key_from_keycode, modifiers_from_modifiers_state, pointer_button_from_mouse,
+    renderer::{
+        event::{key_from_keycode, modifiers_from_modifiers_state, pointer_button_from_mouse},
+        texture::Rotation,
     },
 };
 use egui::{
-    Checkbox, Context, KeyboardShortcut, Pos2, Rect, Response, Sense, TextStyle, TextWrapMode, Ui,
-    Widget, WidgetText,
+    pos2, Checkbox, Context, Key, KeyboardShortcut, Pos2, Rect, Response, Sense, TextStyle,
+    TextWrapMode, Ui, Widget, WidgetText,
 };
 use std::ops::{Deref, DerefMut};
 use tetanes_core::ppu::Ppu;
@@ -48,16 +49,52 @@ where
     }
 }
 
-pub fn cursor_to_zapper(x: f32, y: f32, rect: Rect) -> Option<Pos2> {
+pub fn cursor_to_zapper(
+    x: f32,
+    y: f32,
+    rect: Rect,
+    rotation: Rotation,
+    flip_horizontal: bool,
+) -> Option<Pos2> {
     let width = Ppu::WIDTH as f32;
     let height = Ppu::HEIGHT as f32;
-    // Normalize x/y to 0..=1 and scale to PPU dimensions
-    let x = ((x - rect.min.x) / rect.width()) * width;
-    let y = ((y - rect.min.y) / rect.height()) * height;
+    // Normalize x/y to 0..=1, undo any screen rotation or mirroring and scale to PPU dimensions
+    let pos = rotation.output_to_texture(
+        pos2(
+            (x - rect.min.x) / rect.width(),
+            (y - rect.min.y) / rect.height(),
+        ),
+        flip_horizontal,
+    );
+    let x = pos.x * width;
+    let y = pos.y * height;
     ((0.0..width).contains(&x) && (0.0..height).contains(&y)).then_some(Pos2::new(x, y))
 }
 
+/// The `egui` navigation key a controller button maps to while a dialog is open.
+pub const fn gamepad_nav_key(button: gilrs::Button) -> Option<Key> {
+    use gilrs::Button;
+
+    match button {
+        Button::DPadUp => Some(Key::ArrowUp),
+        Button::DPadDown => Some(Key::ArrowDown),
+        Button::DPadLeft => Some(Key::ArrowLeft),
+        Button::DPadRight => Some(Key::ArrowRight),
+        Button::South => Some(Key::Enter),
+        Button::East => Some(Key::Escape),
+        _ => None,
+    }
+}
+
 pub fn input_down(ui: &mut Ui, gamepads: Option<&Gamepads>, cfg: &Config, input: Input) -> bool {
+    match input {
+        Input::Chord(held, pressed) => {
+            return input_down(ui, gamepads, cfg, held.into())
+                && input_down(ui, gamepads, cfg, pressed.into());
+        }
+        Input::Sequence(_, pressed) => return input_down(ui, gamepads, cfg, pressed.into()),
+        _ => (),
+    }
     ui.input_mut(|i| match input {
         Input::Key(keycode, modifier_state) => key_from_keycode(keycode).is_some_and(|key| {
             let modifiers = modifiers_from_modifiers_state(modifier_state);
@@ -81,6 +118,7 @@ pub fn input_down(ui: &mut Ui, gamepads: Option<&Gamepads>, cfg: &Config, input:
                 let (dir, state) = Gamepads::axis_state(value);
                 dir == Some(direction) && state == ElementState::Pressed
             }),
+        Input::Chord(..) | Input::Sequence(..) => false,
     })
 }
 
@@ -0,0 +1,137 @@
+use crate::logging::{self, LogEntry};
+use egui::{Color32, ComboBox, Context, RichText, ScrollArea, TextEdit, TextStyle, Ui};
+use tetanes_core::time::Duration;
+use tracing::{
+    level_filters::{LevelFilter, STATIC_MAX_LEVEL},
+    Level,
+};
+
+/// Log levels selectable in the UI, limited to those not compiled out.
+pub fn levels() -> impl Iterator<Item = LevelFilter> {
+    [
+        LevelFilter::ERROR,
+        LevelFilter::WARN,
+        LevelFilter::INFO,
+        LevelFilter::DEBUG,
+        LevelFilter::TRACE,
+    ]
+    .into_iter()
+    .filter(|level| *level <= STATIC_MAX_LEVEL)
+}
+
+/// Window displaying recently captured log entries.
+#[derive(Debug)]
+#[must_use]
+pub struct LogViewer {
+    open: bool,
+    level: LevelFilter,
+    target: String,
+    auto_scroll: bool,
+}
+
+impl Default for LogViewer {
+    fn default() -> Self {
+        Self {
+            open: false,
+            level: STATIC_MAX_LEVEL,
+            target: String::new(),
+            auto_scroll: true,
+        }
+    }
+}
+
+impl LogViewer {
+    const TITLE: &'static str = "📜 Log Viewer";
+    /// How often to refresh while open, since new entries can arrive from any thread.
+    const REFRESH_INTERVAL: Duration = Duration::from_millis(250);
+
+    pub const fn open(&self) -> bool {
+        self.open
+    }
+
+    pub fn set_open(&mut self, open: bool) {
+        self.open = open;
+    }
+
+    pub fn show(&mut self, ctx: &Context, enabled: bool) {
+        #[cfg(feature = "profiling")]
+        puffin::profile_function!();
+
+        if !self.open {
+            return;
+        }
+
+        let mut open = self.open;
+        egui::Window::new(Self::TITLE)
+            .open(&mut open)
+            .default_size([640.0, 400.0])
+            .show(ctx, |ui| {
+                ui.add_enabled_ui(enabled, |ui| self.ui(ui));
+            });
+        self.open = open;
+
+        ctx.request_repaint_after(Self::REFRESH_INTERVAL);
+    }
+
+    fn ui(&mut self, ui: &mut Ui) {
+        let entries = logging::log_entries()
+            .into_iter()
+            .filter(|entry| self.level >= entry.level)
+            .filter(|entry| self.target.is_empty() || entry.target.contains(&self.target))
+            .collect::<Vec<_>>();
+
+        ui.horizontal(|ui| {
+            ComboBox::from_label("Level")
+                .selected_text(self.level.to_string())
+                .show_ui(ui, |ui| {
+                    for level in levels() {
+                        ui.selectable_value(&mut self.level, level, level.to_string());
+                    }
+                })
+                .response
+                .on_hover_text("Show entries up to and including this level.");
+
+            ui.add(
+                TextEdit::singleline(&mut self.target)
+                    .hint_text("Filter by module...")
+                    .desired_width(160.0),
+            );
+
+            ui.checkbox(&mut self.auto_scroll, "Auto-scroll");
+
+            if ui.button("📋 Copy").clicked() {
+                let text = entries
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                ui.ctx().copy_text(text);
+            }
+            if ui.button("🗑 Clear").clicked() {
+                logging::clear_log_entries();
+            }
+        });
+
+        ui.separator();
+
+        let row_height = ui.text_style_height(&TextStyle::Monospace);
+        ScrollArea::both()
+            .auto_shrink(false)
+            .stick_to_bottom(self.auto_scroll)
+            .show_rows(ui, row_height, entries.len(), |ui, range| {
+                for entry in &entries[range] {
+                    Self::entry_ui(ui, entry);
+                }
+            });
+    }
+
+    fn entry_ui(ui: &mut Ui, entry: &LogEntry) {
+        let color = match entry.level {
+            Level::ERROR => Color32::RED,
+            Level::WARN => Color32::YELLOW,
+            Level::INFO => ui.visuals().text_color(),
+            _ => ui.visuals().weak_text_color(),
+        };
+        ui.label(RichText::new(entry.to_string()).monospace().color(color));
+    }
+}
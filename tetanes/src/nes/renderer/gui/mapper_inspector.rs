@@ -0,0 +1,359 @@
+use crate::nes::{
+    event::{EmulationEvent, NesEventProxy},
+    renderer::gui::lib::ViewportOptions,
+};
+use egui::{
+    CentralPanel, Color32, Context, CursorIcon, Grid, Painter, Pos2, Rect, ScrollArea, Stroke, Ui,
+    Vec2, ViewportClass, ViewportId,
+};
+use parking_lot::Mutex;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+use tetanes_core::{
+    debug::Breakpoints,
+    mapper::{MapperDebugState, MapperIrqEvent, MapperWrite},
+    mem::{BankAccess, BankMapping},
+    ppu::Ppu,
+};
+
+/// Viewport for inspecting the bank mapping, mirroring, IRQ counter, IRQ timing and recent
+/// register writes of the loaded mapper.
+#[derive(Debug)]
+#[must_use]
+pub struct MapperInspector {
+    id: ViewportId,
+    open: Arc<AtomicBool>,
+    /// Whether emulation has been asked to send mapper state, to follow changes to `open`.
+    sending: bool,
+    tx: NesEventProxy,
+    state: Arc<Mutex<State>>,
+}
+
+#[derive(Debug)]
+#[must_use]
+struct State {
+    tx: NesEventProxy,
+    mapper: Option<MapperDebugState>,
+    writes: Vec<MapperWrite>,
+    irqs: Vec<MapperIrqEvent>,
+    break_on_irq: bool,
+    show_irq_scanlines: bool,
+}
+
+impl MapperInspector {
+    const TITLE: &'static str = "🗂 Mapper Inspector";
+    const IRQ_SCANLINE_COLOR: Color32 = Color32::from_rgb(0xFF, 0x40, 0x40);
+
+    pub fn new(tx: NesEventProxy) -> Self {
+        Self {
+            id: ViewportId::from_hash_of(Self::TITLE),
+            open: Arc::new(AtomicBool::new(false)),
+            sending: false,
+            tx: tx.clone(),
+            state: Arc::new(Mutex::new(State {
+                tx,
+                mapper: None,
+                writes: Vec::new(),
+                irqs: Vec::new(),
+                break_on_irq: false,
+                show_irq_scanlines: false,
+            })),
+        }
+    }
+
+    pub const fn id(&self) -> ViewportId {
+        self.id
+    }
+
+    pub fn open(&self) -> bool {
+        self.open.load(Ordering::Acquire)
+    }
+
+    pub fn set_open(&self, open: bool) {
+        self.open.store(open, Ordering::Release);
+    }
+
+    pub fn toggle_open(&self) {
+        let _ = self
+            .open
+            .fetch_update(Ordering::Release, Ordering::Acquire, |open| Some(!open));
+    }
+
+    /// Update the displayed mapper state, sent by emulation after a frame if it changed.
+    pub fn update(
+        &self,
+        mapper: MapperDebugState,
+        writes: Vec<MapperWrite>,
+        irqs: Vec<MapperIrqEvent>,
+    ) {
+        let mut state = self.state.lock();
+        state.mapper = Some(mapper);
+        state.writes = writes;
+        state.irqs = irqs;
+    }
+
+    /// Clear the displayed mapper state, keeping settings like breakpoints.
+    pub fn clear(&self) {
+        let mut state = self.state.lock();
+        state.mapper = None;
+        state.writes.clear();
+        state.irqs.clear();
+    }
+
+    /// Draw a line across the game frame at each scanline the mapper asserted its IRQ on last
+    /// frame, if enabled. `overscan` is the number of scanlines trimmed from the top and bottom.
+    pub fn draw_irq_scanlines(&self, painter: &Painter, rect: Rect, overscan: u32, flipped: bool) {
+        if !self.open() {
+            return;
+        }
+        let state = self.state.lock();
+        if !state.show_irq_scanlines {
+            return;
+        }
+
+        let visible = Ppu::HEIGHT - 2 * overscan;
+        let stroke = Stroke::new(1.0, Self::IRQ_SCANLINE_COLOR);
+        for irq in state
+            .irqs
+            .iter()
+            .filter(|irq| (overscan..visible + overscan).contains(&irq.scanline))
+        {
+            // IRQs take effect partway through a scanline, so offset by the cycle too
+            let scanline = (irq.scanline - overscan) as f32 + irq.cycle as f32 / 341.0;
+            let mut y = scanline / visible as f32;
+            if flipped {
+                y = 1.0 - y;
+            }
+            let y = rect.top() + y * rect.height();
+            painter.line_segment(
+                [Pos2::new(rect.left(), y), Pos2::new(rect.right(), y)],
+                stroke,
+            );
+        }
+    }
+
+    pub fn show(&mut self, ctx: &Context, opts: ViewportOptions) {
+        let open = self.open();
+        if open != self.sending {
+            self.sending = open;
+            self.tx.event(EmulationEvent::ShowMapperState(open));
+        }
+        if !open {
+            return;
+        }
+
+        #[cfg(feature = "profiling")]
+        puffin::profile_function!();
+
+        let open = Arc::clone(&self.open);
+        let state = Arc::clone(&self.state);
+
+        let mut viewport_builder = egui::ViewportBuilder::default()
+            .with_title(Self::TITLE)
+            .with_inner_size(Vec2::new(480.0, 640.0));
+        if opts.always_on_top {
+            viewport_builder = viewport_builder.with_always_on_top();
+        }
+
+        ctx.show_viewport_deferred(self.id, viewport_builder, move |ctx, class| {
+            let mut state = state.lock();
+            if class == ViewportClass::Embedded {
+                let mut window_open = open.load(Ordering::Acquire);
+                egui::Window::new(MapperInspector::TITLE)
+                    .open(&mut window_open)
+                    .default_size(Vec2::new(400.0, 480.0))
+                    .show(ctx, |ui| state.ui(ui, opts.enabled));
+                open.store(window_open, Ordering::Release);
+            } else {
+                CentralPanel::default().show(ctx, |ui| state.ui(ui, opts.enabled));
+                if ctx.input(|i| i.viewport().close_requested()) {
+                    open.store(false, Ordering::Release);
+                }
+            }
+        });
+    }
+}
+
+impl State {
+    fn ui(&mut self, ui: &mut Ui, enabled: bool) {
+        #[cfg(feature = "profiling")]
+        puffin::profile_function!();
+
+        let Some(mapper) = &self.mapper else {
+            ui.label("No ROM loaded.");
+            return;
+        };
+
+        ui.add_enabled_ui(enabled, |ui| {
+            ScrollArea::vertical().show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.strong("Mirroring:");
+                    ui.label(format!("{:?}", mapper.mirroring));
+                });
+
+                ui.separator();
+                bank_grid(ui, "PRG-ROM", &mapper.prg_rom_banks);
+                bank_grid(ui, "PRG-RAM", &mapper.prg_ram_banks);
+                bank_grid(ui, "CHR", &mapper.chr_banks);
+
+                if let Some(irq) = &mapper.irq {
+                    ui.separator();
+                    ui.heading("IRQ");
+                    Grid::new("mapper_irq")
+                        .num_columns(2)
+                        .spacing([40.0, 6.0])
+                        .show(ui, |ui| {
+                            ui.strong("Counter:");
+                            ui.monospace(format!("${:04X} ({})", irq.counter, irq.counter));
+                            ui.end_row();
+
+                            ui.strong("Reload:")
+                                .on_hover_cursor(CursorIcon::Help)
+                                .on_hover_text("Value the counter reloads with or is compared to.");
+                            ui.monospace(format!("${:04X} ({})", irq.reload, irq.reload));
+                            ui.end_row();
+
+                            ui.strong("Enabled:");
+                            ui.label(if irq.enabled { "Yes" } else { "No" });
+                            ui.end_row();
+                        });
+                }
+
+                if !mapper.regs.is_empty() {
+                    ui.separator();
+                    ui.heading("Registers");
+                    Grid::new("mapper_regs")
+                        .num_columns(2)
+                        .spacing([40.0, 6.0])
+                        .show(ui, |ui| {
+                            for (name, value) in &mapper.regs {
+                                ui.strong(format!("{name}:"));
+                                ui.monospace(value);
+                                ui.end_row();
+                            }
+                        });
+                }
+
+                ui.separator();
+                Self::irq_events_ui(
+                    ui,
+                    &self.tx,
+                    &self.irqs,
+                    &mut self.break_on_irq,
+                    &mut self.show_irq_scanlines,
+                );
+
+                ui.separator();
+                ui.heading("Recent Writes")
+                    .on_hover_cursor(CursorIcon::Help)
+                    .on_hover_text("CPU writes to mapper registers, newest first.");
+                if self.writes.is_empty() {
+                    ui.label("No mapper register writes.");
+                } else {
+                    Grid::new("mapper_writes")
+                        .num_columns(4)
+                        .spacing([20.0, 4.0])
+                        .striped(true)
+                        .show(ui, |ui| {
+                            ui.strong("Frame");
+                            ui.strong("Scanline");
+                            ui.strong("Cycle");
+                            ui.strong("Write");
+                            ui.end_row();
+
+                            for write in self.writes.iter().rev() {
+                                ui.monospace(write.frame.to_string());
+                                ui.monospace(write.scanline.to_string());
+                                ui.monospace(write.cycle.to_string());
+                                ui.monospace(format!("${:04X} = ${:02X}", write.addr, write.val));
+                                ui.end_row();
+                            }
+                        });
+                }
+            });
+        });
+    }
+
+    fn irq_events_ui(
+        ui: &mut Ui,
+        tx: &NesEventProxy,
+        irqs: &[MapperIrqEvent],
+        break_on_irq: &mut bool,
+        show_irq_scanlines: &mut bool,
+    ) {
+        ui.heading("IRQ Events")
+            .on_hover_cursor(CursorIcon::Help)
+            .on_hover_text("Where the mapper asserted its IRQ line during the last frame.");
+
+        ui.horizontal(|ui| {
+            let res = ui
+                .checkbox(break_on_irq, "Break on Mapper IRQ")
+                .on_hover_text("Pause emulation as soon as the mapper asserts its IRQ line.");
+            if res.changed() {
+                tx.event(EmulationEvent::Breakpoints((
+                    Breakpoints::MAPPER_IRQ,
+                    *break_on_irq,
+                )));
+            }
+            ui.checkbox(show_irq_scanlines, "Show IRQ Scanlines")
+                .on_hover_text("Mark IRQ scanlines over the game to spot mis-timed splits.");
+        });
+
+        if irqs.is_empty() {
+            ui.label("No mapper IRQs.");
+        } else {
+            Grid::new("mapper_irqs")
+                .num_columns(3)
+                .spacing([20.0, 4.0])
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.strong("Frame");
+                    ui.strong("Scanline");
+                    ui.strong("Cycle");
+                    ui.end_row();
+
+                    for irq in irqs.iter().rev() {
+                        ui.monospace(irq.frame.to_string());
+                        ui.monospace(irq.scanline.to_string());
+                        ui.monospace(irq.cycle.to_string());
+                        ui.end_row();
+                    }
+                });
+        }
+    }
+}
+
+fn bank_grid(ui: &mut Ui, name: &str, banks: &[BankMapping]) {
+    if banks.is_empty() {
+        return;
+    }
+
+    ui.heading(format!("{name} Banks"));
+    Grid::new(("mapper_banks", name))
+        .num_columns(3)
+        .spacing([40.0, 4.0])
+        .striped(true)
+        .show(ui, |ui| {
+            ui.strong("Address");
+            ui.strong("Page");
+            ui.strong("Access");
+            ui.end_row();
+
+            for bank in banks {
+                ui.monospace(format!(
+                    "${:04X}-${:04X}",
+                    bank.start,
+                    bank.start + bank.size - 1
+                ));
+                ui.monospace(format!("{} (${:05X})", bank.page, bank.page * bank.size));
+                ui.label(match bank.access {
+                    BankAccess::None => "None",
+                    BankAccess::Read => "Read",
+                    BankAccess::ReadWrite => "Read/Write",
+                });
+                ui.end_row();
+            }
+        });
+}
@@ -0,0 +1,86 @@
+use crate::nes::renderer::gui::MessageType;
+use chrono::{DateTime, Local};
+use egui::{Context, RichText, ScrollArea, TextStyle, Ui};
+use std::collections::VecDeque;
+
+/// Window listing every message shown, since messages disappear after a few seconds.
+#[derive(Default, Debug)]
+#[must_use]
+pub struct MessageHistory {
+    open: bool,
+    entries: VecDeque<(MessageType, String, DateTime<Local>)>,
+}
+
+impl MessageHistory {
+    const TITLE: &'static str = "🔔 Notification History";
+    /// Maximum number of messages kept, dropping the oldest first.
+    const CAPACITY: usize = 500;
+
+    pub const fn open(&self) -> bool {
+        self.open
+    }
+
+    pub fn set_open(&mut self, open: bool) {
+        self.open = open;
+    }
+
+    pub fn push(&mut self, ty: MessageType, text: impl Into<String>) {
+        if self.entries.len() >= Self::CAPACITY {
+            self.entries.pop_front();
+        }
+        self.entries.push_back((ty, text.into(), Local::now()));
+    }
+
+    pub fn show(&mut self, ctx: &Context, enabled: bool) {
+        #[cfg(feature = "profiling")]
+        puffin::profile_function!();
+
+        if !self.open {
+            return;
+        }
+
+        let mut open = self.open;
+        egui::Window::new(Self::TITLE)
+            .open(&mut open)
+            .default_size([480.0, 320.0])
+            .show(ctx, |ui| {
+                ui.add_enabled_ui(enabled, |ui| self.ui(ui));
+            });
+        self.open = open;
+    }
+
+    fn ui(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.label(format!("{} messages", self.entries.len()));
+            if ui.button("🗑 Clear").clicked() {
+                self.entries.clear();
+            }
+        });
+
+        ui.separator();
+
+        if self.entries.is_empty() {
+            ui.label("No messages yet.");
+            return;
+        }
+
+        let row_height = ui.text_style_height(&TextStyle::Body);
+        ScrollArea::both()
+            .auto_shrink(false)
+            .stick_to_bottom(true)
+            .show_rows(ui, row_height, self.entries.len(), |ui, range| {
+                for (ty, message, time) in self.entries.range(range) {
+                    let visuals = ui.visuals();
+                    let (icon, color) = match ty {
+                        MessageType::Info => ("ℹ", visuals.text_color()),
+                        MessageType::Warn => ("⚠", visuals.warn_fg_color),
+                        MessageType::Error => ("❗", visuals.error_fg_color),
+                    };
+                    ui.horizontal(|ui| {
+                        ui.label(RichText::new(time.format("%H:%M:%S").to_string()).weak());
+                        ui.colored_label(color, format!("{icon} {message}"));
+                    });
+                }
+            });
+    }
+}
@@ -0,0 +1,226 @@
+use crate::nes::emulation::FrameStats;
+use egui::{pos2, Align2, Color32, FontId, Sense, Shape, Stroke, Ui, Vec2};
+use std::collections::VecDeque;
+use tetanes_core::time::Duration;
+
+/// Rolling window of recent values for a single graphed measurement.
+#[derive(Debug, Clone)]
+#[must_use]
+struct History(VecDeque<f32>);
+
+impl Default for History {
+    fn default() -> Self {
+        Self(VecDeque::with_capacity(History::CAPACITY))
+    }
+}
+
+impl History {
+    /// Number of values kept, or about 4 seconds at 60 FPS.
+    const CAPACITY: usize = 240;
+
+    fn push(&mut self, value: f32) {
+        if self.0.len() >= Self::CAPACITY {
+            self.0.pop_front();
+        }
+        self.0.push_back(value);
+    }
+
+    fn latest(&self) -> Option<f32> {
+        self.0.back().copied()
+    }
+
+    fn max(&self) -> f32 {
+        self.0.iter().copied().fold(0.0, f32::max)
+    }
+}
+
+/// A series drawn in a [`PerfHistory`] graph.
+struct Series<'a> {
+    label: &'static str,
+    color: Color32,
+    history: &'a History,
+}
+
+/// Recent frame, emulation, render and GPU timings, audio buffer fill and rewind memory usage,
+/// drawn as rolling graphs in the performance stats window.
+#[derive(Default, Debug)]
+#[must_use]
+pub struct PerfHistory {
+    frame_time: History,
+    emulation_time: History,
+    render_time: History,
+    gpu_time: History,
+    audio_buffer_fill: History,
+    /// Megabytes used by rewind snapshots.
+    rewind_memory: History,
+}
+
+impl PerfHistory {
+    const GRAPH_HEIGHT: f32 = 60.0;
+
+    /// Record the measurements taken by the emulation thread for a single frame.
+    pub fn push_frame_stats(&mut self, stats: &FrameStats) {
+        self.frame_time.push(stats.last_frame_time);
+        self.emulation_time.push(stats.emulation_time);
+        self.audio_buffer_fill.push(stats.audio_buffer_fill * 100.0);
+        self.rewind_memory
+            .push(stats.rewind_memory as f32 / 0x100000 as f32);
+    }
+
+    /// Record the time taken to draw and paint the main window, and the GPU time if timestamp
+    /// queries are supported.
+    pub fn push_render_time(&mut self, render_time: Duration, gpu_time: Option<f32>) {
+        self.render_time.push(render_time.as_secs_f32() * 1000.0);
+        if let Some(gpu_time) = gpu_time {
+            self.gpu_time.push(gpu_time);
+        }
+    }
+
+    pub fn ui(&self, ui: &mut Ui) {
+        #[cfg(feature = "profiling")]
+        puffin::profile_function!();
+
+        let dark_mode = ui.visuals().dark_mode;
+        let (blue, green, orange, purple) = if dark_mode {
+            (
+                Color32::from_rgb(0x59, 0xc2, 0xff),
+                Color32::from_rgb(0xb8, 0xcc, 0x52),
+                Color32::from_rgb(0xff, 0x8f, 0x40),
+                Color32::from_rgb(0xd2, 0xa6, 0xff),
+            )
+        } else {
+            (
+                Color32::from_rgb(0x39, 0x9e, 0xe6),
+                Color32::from_rgb(0x86, 0xb3, 0x00),
+                Color32::from_rgb(0xfa, 0x8d, 0x3e),
+                Color32::from_rgb(0xa3, 0x7a, 0xcc),
+            )
+        };
+
+        ui.strong("Frame Time");
+        graph(
+            ui,
+            "ms",
+            // Always show at least one 60 Hz frame so a steady frame rate isn't a noisy line
+            1000.0 / 60.0,
+            &[Series {
+                label: "Frame",
+                color: blue,
+                history: &self.frame_time,
+            }],
+        );
+
+        ui.strong("Emulation vs Render Time");
+        let mut series = vec![
+            Series {
+                label: "Emulation",
+                color: green,
+                history: &self.emulation_time,
+            },
+            Series {
+                label: "Render (CPU)",
+                color: orange,
+                history: &self.render_time,
+            },
+        ];
+        if !self.gpu_time.0.is_empty() {
+            series.push(Series {
+                label: "Render (GPU)",
+                color: purple,
+                history: &self.gpu_time,
+            });
+        }
+        graph(ui, "ms", 1.0, &series);
+        if self.gpu_time.0.is_empty() {
+            ui.weak("GPU timing is not supported by this graphics adapter.");
+        }
+
+        ui.strong("Audio Buffer Fill");
+        graph(
+            ui,
+            "%",
+            100.0,
+            &[Series {
+                label: "Fill",
+                color: blue,
+                history: &self.audio_buffer_fill,
+            }],
+        );
+
+        ui.strong("Rewind Memory");
+        graph(
+            ui,
+            "MB",
+            1.0,
+            &[Series {
+                label: "Memory",
+                color: green,
+                history: &self.rewind_memory,
+            }],
+        );
+    }
+}
+
+/// Draw `series` as lines scaled to the largest value, or `min_max` if larger, with a legend of
+/// the latest values.
+fn graph(ui: &mut Ui, unit: &str, min_max: f32, series: &[Series<'_>]) {
+    let (rect, _) = ui.allocate_exact_size(
+        Vec2::new(ui.available_width().max(200.0), PerfHistory::GRAPH_HEIGHT),
+        Sense::hover(),
+    );
+    let painter = ui.painter_at(rect);
+    let visuals = ui.visuals();
+    painter.rect_filled(rect, 0.0, visuals.extreme_bg_color);
+
+    let max = series
+        .iter()
+        .map(|series| series.history.max())
+        .fold(min_max, f32::max);
+    let font = FontId::proportional(10.0);
+    painter.text(
+        rect.right_top() + Vec2::new(-2.0, 2.0),
+        Align2::RIGHT_TOP,
+        format!("{max:.1} {unit}"),
+        font.clone(),
+        visuals.weak_text_color(),
+    );
+
+    let step = rect.width() / (History::CAPACITY - 1) as f32;
+    let mut legend_pos = rect.left_top() + Vec2::splat(2.0);
+    for Series {
+        label,
+        color,
+        history,
+    } in series
+    {
+        // Newest values are on the right edge
+        let offset = History::CAPACITY - history.0.len();
+        let points = history
+            .0
+            .iter()
+            .enumerate()
+            .map(|(i, value)| {
+                let x = rect.left() + (offset + i) as f32 * step;
+                let y = rect.bottom() - (value / max).clamp(0.0, 1.0) * rect.height();
+                pos2(x, y)
+            })
+            .collect::<Vec<_>>();
+        if points.len() > 1 {
+            painter.add(Shape::line(points, Stroke::new(1.5, *color)));
+        }
+
+        let text = match history.latest() {
+            Some(latest) => format!("{label}: {latest:.2} {unit}"),
+            None => format!("{label}: N/A"),
+        };
+        let galley = painter.layout_no_wrap(text, font.clone(), *color);
+        let width = galley.size().x;
+        painter.galley(legend_pos, galley, *color);
+        legend_pos.x += width + 8.0;
+    }
+    painter.rect_stroke(
+        rect,
+        0.0,
+        Stroke::new(1.0, visuals.weak_text_color().gamma_multiply(0.5)),
+    );
+}
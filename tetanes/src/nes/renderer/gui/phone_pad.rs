@@ -0,0 +1,173 @@
+use crate::nes::{event::NesEventProxy, phone_pad::PhonePad};
+use egui::{Color32, Context, Rect, Sense, Ui, Vec2};
+use std::time::Duration;
+use tetanes_core::input::Player;
+use tracing::error;
+
+/// Window to start the phone controller server and show a QR code phones can scan to connect.
+#[derive(Debug)]
+#[must_use]
+pub struct PhonePadWindow {
+    open: bool,
+    tx: NesEventProxy,
+    server: Option<PhonePad>,
+    error: Option<String>,
+    player: Player,
+    /// Encoded QR code modules for a URL, as `(url, width, dark modules)`.
+    qr_code: Option<(String, usize, Vec<bool>)>,
+}
+
+impl PhonePadWindow {
+    const TITLE: &'static str = "📱 Phone Controller";
+    /// Size of the QR code in points, excluding its quiet zone.
+    const QR_SIZE: f32 = 200.0;
+    /// Light border in modules required around a QR code for it to scan reliably.
+    const QUIET_ZONE: usize = 4;
+
+    pub const fn new(tx: NesEventProxy) -> Self {
+        Self {
+            open: false,
+            tx,
+            server: None,
+            error: None,
+            player: Player::Two,
+            qr_code: None,
+        }
+    }
+
+    pub const fn open(&self) -> bool {
+        self.open
+    }
+
+    pub fn set_open(&mut self, open: bool) {
+        self.open = open;
+    }
+
+    pub fn show(&mut self, ctx: &Context, enabled: bool) {
+        #[cfg(feature = "profiling")]
+        puffin::profile_function!();
+
+        if !self.open {
+            return;
+        }
+
+        let mut open = self.open;
+        egui::Window::new(Self::TITLE)
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.add_enabled_ui(enabled, |ui| self.ui(ui));
+            });
+        self.open = open;
+
+        // Keep the connected phone count current
+        if self.open && self.server.is_some() {
+            ctx.request_repaint_after(Duration::from_secs(1));
+        }
+    }
+
+    fn ui(&mut self, ui: &mut Ui) {
+        ui.label("Use a phone's web browser as a gamepad. The phone must be on the same network.");
+
+        ui.add_space(8.0);
+
+        ui.horizontal(|ui| {
+            match &self.server {
+                Some(_) => {
+                    if ui.button("⏹ Stop Server").clicked() {
+                        self.server = None;
+                        self.qr_code = None;
+                    }
+                }
+                None => {
+                    if ui.button("▶ Start Server").clicked() {
+                        match PhonePad::start(self.tx.clone()) {
+                            Ok(server) => {
+                                self.server = Some(server);
+                                self.error = None;
+                            }
+                            Err(err) => {
+                                error!("{err:?}");
+                                self.error = Some(err.to_string());
+                            }
+                        }
+                    }
+                }
+            }
+
+            egui::ComboBox::from_label("Player")
+                .selected_text(format!("Player {:?}", self.player))
+                .show_ui(ui, |ui| {
+                    for player in [Player::One, Player::Two, Player::Three, Player::Four] {
+                        ui.selectable_value(&mut self.player, player, format!("Player {player:?}"));
+                    }
+                });
+        });
+
+        if let Some(err) = &self.error {
+            ui.colored_label(ui.visuals().error_fg_color, err);
+        }
+
+        let Some(server) = &self.server else {
+            return;
+        };
+
+        ui.add_space(8.0);
+
+        let Some(url) = server.player_url(self.player) else {
+            ui.colored_label(
+                ui.visuals().warn_fg_color,
+                format!(
+                    "No network address found. Listening on port {} with pairing token {}.",
+                    server.addr().port(),
+                    server.token()
+                ),
+            );
+            return;
+        };
+
+        ui.label("Scan the code with a phone camera, or open:");
+        ui.hyperlink(&url);
+        ui.label(format!("Connected phones: {}", server.clients()));
+
+        ui.add_space(8.0);
+
+        if self.qr_code.as_ref().map(|(qr_url, ..)| qr_url) != Some(&url) {
+            self.qr_code = match qrcode::QrCode::new(&url) {
+                Ok(code) => {
+                    let modules = code
+                        .to_colors()
+                        .into_iter()
+                        .map(|color| color == qrcode::Color::Dark)
+                        .collect();
+                    Some((url, code.width(), modules))
+                }
+                Err(err) => {
+                    error!("failed to encode phone controller QR code: {err:?}");
+                    None
+                }
+            };
+        }
+        if let Some((_, width, modules)) = &self.qr_code {
+            Self::qr_code(ui, *width, modules);
+        }
+    }
+
+    fn qr_code(ui: &mut Ui, width: usize, modules: &[bool]) {
+        let total = width + 2 * Self::QUIET_ZONE;
+        let module_size = (Self::QR_SIZE / width as f32).floor().max(1.0);
+        let (rect, _) =
+            ui.allocate_exact_size(Vec2::splat(module_size * total as f32), Sense::hover());
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(rect, 0.0, Color32::WHITE);
+        for (i, _) in modules.iter().enumerate().filter(|(_, dark)| **dark) {
+            let (x, y) = (i % width + Self::QUIET_ZONE, i / width + Self::QUIET_ZONE);
+            let min = rect.min + Vec2::new(x as f32, y as f32) * module_size;
+            painter.rect_filled(
+                Rect::from_min_size(min, Vec2::splat(module_size)),
+                0.0,
+                Color32::BLACK,
+            );
+        }
+    }
+}
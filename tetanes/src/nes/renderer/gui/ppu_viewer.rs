@@ -1,6 +1,6 @@
 use crate::nes::{
     config::Config,
-    event::{DebugEvent, EmulationEvent, NesEventProxy},
+    event::{DebugEvent, EmulationEvent, NesEventProxy, UiEvent},
     renderer::{
         gui::lib::{animated_dashed_rect, ViewportOptions},
         painter::RenderState,
@@ -8,9 +8,9 @@ use crate::nes::{
     },
 };
 use egui::{
-    show_tooltip_at_pointer, CentralPanel, Color32, Context, CursorIcon, DragValue, Grid, Image,
-    Label, Pos2, Rect, ScrollArea, Sense, SidePanel, Slider, TopBottomPanel, Ui, Vec2,
-    ViewportClass, ViewportId,
+    show_tooltip_at_pointer, Button, CentralPanel, Color32, Context, CursorIcon, DragValue, Grid,
+    Image, Label, Painter, Pos2, Rect, ScrollArea, Sense, SidePanel, Slider, Stroke,
+    TopBottomPanel, Ui, Vec2, ViewportClass, ViewportId,
 };
 use parking_lot::Mutex;
 use std::sync::{
@@ -18,10 +18,13 @@ use std::sync::{
     Arc,
 };
 use tetanes_core::{
-    debug::PpuDebugger,
+    debug::{PpuDebugger, PpuLayers, TileChange},
     ppu::{scroll::Scroll, sprite::Sprite, Ppu},
 };
 
+const TILE_CHANGE_CPU: Color32 = Color32::YELLOW;
+const TILE_CHANGE_MAPPER: Color32 = Color32::from_rgb(0xFF, 0x00, 0xFF);
+
 #[derive(Debug)]
 #[must_use]
 struct State {
@@ -36,6 +39,8 @@ struct State {
     show_scroll_overlay: bool,
     show_attr_grid_16x: bool,
     show_attr_grid_32x: bool,
+    show_tile_changes: bool,
+    layers: PpuLayers,
     nametables: NametablesState,
     pattern_tables: PatternTablesState,
     oam: OamState,
@@ -47,6 +52,8 @@ struct State {
 #[must_use]
 struct NametablesState {
     pixels: Vec<u8>,
+    /// How each tile changed since the last refresh, in rows of 64 tiles.
+    changes: Vec<TileChange>,
     texture: Texture,
     zoom: f32,
     selected: Option<Vec2>,
@@ -193,9 +200,13 @@ impl PpuViewer {
                 show_scroll_overlay: false,
                 show_attr_grid_16x: false,
                 show_attr_grid_32x: false,
+                show_tile_changes: false,
+                layers: PpuLayers::All,
                 nametables: NametablesState {
                     // 4 nametables with 4 color channels (RGBA)
                     pixels: vec![0x00; 4 * 4 * Ppu::SIZE],
+                    // 4 nametables of 32x30 tiles
+                    changes: vec![TileChange::None; 64 * 60],
                     texture: Texture::new(
                         render_state,
                         2.0 * Vec2::new(Ppu::WIDTH as f32, Ppu::HEIGHT as f32),
@@ -278,11 +289,55 @@ impl PpuViewer {
         self.resources = Some(cfg.clone());
     }
 
+    /// Outline the sprite selected in the OAM tab on the game frame drawn in `rect`. `overscan`
+    /// is the number of scanlines trimmed from the top and bottom.
+    pub fn draw_selected_sprite(
+        &self,
+        painter: &Painter,
+        rect: Rect,
+        overscan: u32,
+        flip_x: bool,
+        flip_y: bool,
+    ) {
+        if !self.open() {
+            return;
+        }
+        let state = self.state.lock();
+        if state.tab != Tab::Oam {
+            return;
+        }
+        let Some(index) = state.oam.oam_selected.map(sprite_index) else {
+            return;
+        };
+        let Some(&[y, _, _, x]) = state.ppu.oamdata.get(4 * index..4 * index + 4) else {
+            return;
+        };
+
+        // Sprites are drawn one scanline below their Y coordinate
+        let visible = (Ppu::HEIGHT - 2 * overscan) as f32;
+        let size = Vec2::new(8.0, state.ppu.ctrl.spr_height as f32);
+        let mut min = Pos2::new(f32::from(x), f32::from(y) + 1.0 - overscan as f32);
+        if flip_x {
+            min.x = Ppu::WIDTH as f32 - min.x - size.x;
+        }
+        if flip_y {
+            min.y = visible - min.y - size.y;
+        }
+        let scale = rect.size() / Vec2::new(Ppu::WIDTH as f32, visible);
+        let sprite = Rect::from_min_size(rect.min + scale * min.to_vec2(), scale * size);
+        painter.rect_stroke(sprite.expand(1.0), 0.0, Stroke::new(2.0, Color32::WHITE));
+    }
+
     pub fn update_ppu(&mut self, queue: &wgpu::Queue, ppu: Ppu) {
         let mut state = self.state.lock();
         match state.tab {
             Tab::Nametables => {
                 ppu.load_nametables(&mut state.nametables.pixels);
+                if state.show_tile_changes {
+                    let mut changes = std::mem::take(&mut state.nametables.changes);
+                    ppu.load_nametable_changes(&state.ppu, &mut changes);
+                    state.nametables.changes = changes;
+                }
                 let mut pixels = std::mem::take(&mut state.palette.pixels);
                 let mut colors = std::mem::take(&mut state.palette.colors);
                 ppu.load_palettes(&mut pixels, &mut colors);
@@ -383,6 +438,12 @@ impl State {
         } else {
             EmulationEvent::RemoveDebugger(debugger.into())
         });
+        // Layers are only hidden while the viewer is open
+        self.tx.event(EmulationEvent::ShowPpuLayers(if open {
+            self.layers
+        } else {
+            PpuLayers::All
+        }));
     }
 
     fn ui(&mut self, ui: &mut Ui, enabled: bool) {
@@ -450,6 +511,22 @@ impl State {
                 }
             });
         });
+
+        ui.strong("Show Layers:")
+            .on_hover_cursor(CursorIcon::Help)
+            .on_hover_text(
+                "Draw only the background or sprites to the main window while the viewer is open.",
+            );
+
+        ui.indent("layer_settings", |ui| {
+            let previous_layers = self.layers;
+            ui.radio_value(&mut self.layers, PpuLayers::All, "All");
+            ui.radio_value(&mut self.layers, PpuLayers::Background, "Background Only");
+            ui.radio_value(&mut self.layers, PpuLayers::Sprites, "Sprites Only");
+            if self.layers != previous_layers {
+                self.tx.event(EmulationEvent::ShowPpuLayers(self.layers));
+            }
+        });
     }
 
     fn nametables_tab(&mut self, ui: &mut Ui) {
@@ -466,6 +543,29 @@ impl State {
                     ui.strong("Mirroring:");
                     ui.label(format!("{:?}", self.ppu.mirroring()));
                     ui.end_row();
+
+                    if self.show_tile_changes {
+                        let count = |kind| {
+                            self.nametables
+                                .changes
+                                .iter()
+                                .filter(|change| **change == kind)
+                                .count()
+                        };
+                        ui.strong("CPU Changes:")
+                            .on_hover_text("Tiles whose index or attribute was written this frame.");
+                        ui.colored_label(TILE_CHANGE_CPU, count(TileChange::Cpu).to_string());
+                        ui.end_row();
+
+                        ui.strong("Mapper Changes:").on_hover_text(
+                            "Tiles whose pattern data or mirroring changed this frame without a nametable write.",
+                        );
+                        ui.colored_label(
+                            TILE_CHANGE_MAPPER,
+                            count(TileChange::Mapper).to_string(),
+                        );
+                        ui.end_row();
+                    }
                 });
 
                 ui.add_space(16.0);
@@ -511,6 +611,17 @@ impl State {
                         // TODO: update config
                     }
 
+                    let res = ui
+                        .checkbox(&mut self.show_tile_changes, "Tile Changes")
+                        .on_hover_text(concat!(
+                            "Highlight tiles that changed since the last refresh: yellow for tile ",
+                            "or attribute writes by the CPU, magenta for pattern data or mirroring ",
+                            "changes from mapper bank switching or CHR-RAM writes."
+                        ));
+                    if res.changed() {
+                        self.nametables.changes.fill(TileChange::None);
+                    }
+
                     zoom_slider(ui, &mut self.nametables.zoom);
                 });
             });
@@ -603,6 +714,10 @@ impl State {
                     paint_grid(ui, bot_rect, 7.5, 16.0, Color32::LIGHT_GREEN);
                 }
 
+                if self.show_tile_changes {
+                    self.nametable_changes_overlay(ui, image_rect);
+                }
+
                 if self.show_scroll_overlay {
                     self.nametable_scroll_overlay(ui, image_rect);
                 }
@@ -616,6 +731,23 @@ impl State {
         });
     }
 
+    fn nametable_changes_overlay(&self, ui: &mut Ui, image_rect: Rect) {
+        let tile_size = image_rect.size() / Vec2::new(64.0, 60.0);
+        for (i, change) in self.nametables.changes.iter().enumerate() {
+            let color = match change {
+                TileChange::None => continue,
+                TileChange::Cpu => TILE_CHANGE_CPU,
+                TileChange::Mapper => TILE_CHANGE_MAPPER,
+            };
+            let min = image_rect.min + Vec2::new((i % 64) as f32, (i / 64) as f32) * tile_size;
+            ui.painter().rect_filled(
+                Rect::from_min_size(min, tile_size),
+                0.0,
+                color.gamma_multiply(0.4),
+            );
+        }
+    }
+
     fn nametable_hover(&mut self, ui: &mut Ui, res: &egui::Response, pos: Pos2) {
         let image_rect = res.rect;
         let texture_size = self.nametables.texture.size;
@@ -879,6 +1011,25 @@ impl State {
                 ui.add_space(16.0);
                 ui.separator();
 
+                if ui
+                    .button("💾 Export PNG...")
+                    .on_hover_text("Save both pattern tables as a grayscale PNG sprite sheet.")
+                    .clicked()
+                {
+                    self.tx.event(UiEvent::ExportChrDialog);
+                }
+                let chr_ram = !self.ppu.bus.chr_ram.is_empty();
+                let res = ui
+                    .add_enabled(chr_ram, Button::new("📂 Import PNG..."))
+                    .on_hover_text("Load an edited sprite sheet back into CHR-RAM.")
+                    .on_disabled_hover_text("Only games with CHR-RAM can be edited.");
+                if res.clicked() {
+                    self.tx.event(UiEvent::ImportChrDialog);
+                }
+
+                ui.add_space(16.0);
+                ui.separator();
+
                 ui.collapsing("Settings", |ui| {
                     self.general_settings(ui);
                     self.grid_settings(ui);
@@ -1019,6 +1170,16 @@ impl State {
                 ui.separator();
                 self.oam_tile(ui, "oam_selected", self.oam.oam_selected);
 
+                ui.add_space(16.0);
+                ui.heading("Edit Sprite")
+                    .on_hover_cursor(CursorIcon::Help)
+                    .on_hover_text(
+                        "Edits are written to OAM directly. Games that copy OAM from RAM \
+                        every frame overwrite them on the next frame.",
+                    );
+                ui.separator();
+                self.sprite_editor(ui);
+
                 ui.add_space(16.0);
                 ui.separator();
 
@@ -1084,9 +1245,7 @@ impl State {
                             tile_selection(oam_image_rect, self.oam.oam_texture.size, offset);
                         animated_dashed_rect(ui, selection, (1.0, Color32::WHITE), 3.0, 3.0);
 
-                        let sprite_index =
-                            (offset.x / 8.0) as usize + (offset.y / 8.0) as usize * 8;
-                        let sprite = self.oam.sprites.get(sprite_index);
+                        let sprite = self.oam.sprites.get(sprite_index(offset));
                         if let Some(sprite) = sprite {
                             let offset = Vec2::new(
                                 ((sprite.x as f32) / 8.0).floor() * 8.0,
@@ -1128,8 +1287,7 @@ impl State {
             3.0,
         );
 
-        let sprite_index = (offset.x / 8.0) as usize + (offset.y / 8.0) as usize * 8;
-        let sprite = self.oam.sprites.get(sprite_index);
+        let sprite = self.oam.sprites.get(sprite_index(offset));
         if sprite.is_some() {
             show_tooltip_at_pointer(ui.ctx(), res.layer_id, res.id, |ui| {
                 self.oam_tile(ui, "oam_hover", Some(offset));
@@ -1214,6 +1372,78 @@ impl State {
         });
     }
 
+    fn sprite_editor(&mut self, ui: &mut Ui) {
+        let Some(offset) = self.oam.oam_selected else {
+            ui.label("Click a sprite to edit it.");
+            return;
+        };
+        let addr = 4 * sprite_index(offset);
+        let Some(&[y, tile, attr, x]) = self.ppu.oamdata.get(addr..addr + 4) else {
+            return;
+        };
+
+        let mut sprite = [y, tile, attr, x];
+        let mut palette = attr & 0x03;
+        let mut bg_priority = attr & 0x20 == 0x20;
+        let mut flip_horizontal = attr & 0x40 == 0x40;
+        let mut flip_vertical = attr & 0x80 == 0x80;
+
+        let grid = Grid::new("oam_sprite_editor")
+            .num_columns(2)
+            .spacing([40.0, 6.0]);
+        grid.show(ui, |ui| {
+            ui.strong("Sprite Index:");
+            ui.label(format!("{} (${addr:02X})", addr / 4));
+            ui.end_row();
+
+            ui.strong("X:");
+            ui.add(DragValue::new(&mut sprite[3]));
+            ui.end_row();
+
+            ui.strong("Y:");
+            ui.add(DragValue::new(&mut sprite[0]));
+            ui.end_row();
+
+            ui.strong("Tile Index:");
+            ui.add(
+                DragValue::new(&mut sprite[1])
+                    .hexadecimal(2, false, true)
+                    .prefix("$"),
+            );
+            ui.end_row();
+
+            ui.strong("Palette:");
+            ui.add(DragValue::new(&mut palette).range(0..=3));
+            ui.end_row();
+
+            ui.strong("Behind Background:");
+            ui.checkbox(&mut bg_priority, "");
+            ui.end_row();
+
+            ui.strong("Flip Horizontal:");
+            ui.checkbox(&mut flip_horizontal, "");
+            ui.end_row();
+
+            ui.strong("Flip Vertical:");
+            ui.checkbox(&mut flip_vertical, "");
+            ui.end_row();
+        });
+
+        sprite[2] = (attr & 0x1C)
+            | palette
+            | (u8::from(bg_priority) << 5)
+            | (u8::from(flip_horizontal) << 6)
+            | (u8::from(flip_vertical) << 7);
+        for (i, (new, old)) in sprite.into_iter().zip([y, tile, attr, x]).enumerate() {
+            if new != old {
+                // Update the local copy too so the edit shows before the next refresh
+                self.ppu.oamdata[addr + i] = new;
+                self.tx
+                    .event(EmulationEvent::WriteOam(((addr + i) as u8, new)));
+            }
+        }
+    }
+
     fn oam_tile_from_offset(&self, offset: Vec2, texture_size: Vec2) -> ChrTile {
         let Vec2 { x, y } = offset;
 
@@ -1240,6 +1470,21 @@ impl State {
                 ui.heading("Selected Color");
                 ui.separator();
                 self.palette(ui, "palette_info_selected", self.palette.selected);
+
+                ui.add_space(16.0);
+                ui.heading("Edit Color");
+                ui.separator();
+                self.palette_editor(ui);
+
+                ui.add_space(16.0);
+                ui.separator();
+                if ui
+                    .button("💾 Export Palette...")
+                    .on_hover_text("Save the current 32 palette colors as RGB in a .pal file.")
+                    .clicked()
+                {
+                    self.tx.event(UiEvent::ExportPaletteDialog);
+                }
             });
         });
 
@@ -1288,6 +1533,61 @@ impl State {
         }
     }
 
+    fn palette_editor(&mut self, ui: &mut Ui) {
+        let Some(offset) = self.palette.selected else {
+            ui.label("Click a color to edit it.");
+            return;
+        };
+        let PaletteColor { addr, value, .. } = self.palette_color_from_offset(offset);
+
+        let mut new_value = value & 0x3F;
+        let mut locked = self.ppu.bus.palette_locked(addr);
+        let grid = Grid::new("palette_editor")
+            .num_columns(2)
+            .spacing([40.0, 6.0]);
+        grid.show(ui, |ui| {
+            ui.strong("Value:");
+            ui.add(
+                DragValue::new(&mut new_value)
+                    .range(0..=0x3F)
+                    .hexadecimal(2, false, true)
+                    .prefix("$"),
+            );
+            ui.end_row();
+
+            ui.strong("Lock:")
+                .on_hover_cursor(CursorIcon::Help)
+                .on_hover_text("Ignore game writes to this color so edits stick.");
+            ui.checkbox(&mut locked, "");
+            ui.end_row();
+        });
+
+        if new_value != value & 0x3F {
+            self.ppu.bus.poke_palette(addr, new_value);
+            self.tx
+                .event(EmulationEvent::WritePalette((addr, new_value)));
+            // Refresh the local colors so the edit shows before the next refresh
+            let mut pixels = std::mem::take(&mut self.palette.pixels);
+            let mut colors = std::mem::take(&mut self.palette.colors);
+            self.ppu.load_palettes(&mut pixels, &mut colors);
+            self.palette.pixels = pixels;
+            self.palette.colors = colors;
+        }
+        if locked != self.ppu.bus.palette_locked(addr) {
+            self.ppu.bus.lock_palette(addr, locked);
+            self.tx.event(EmulationEvent::LockPalette((addr, locked)));
+        }
+
+        if self.ppu.bus.palette_locks != 0 && ui.button("Unlock All").clicked() {
+            for addr in Ppu::PALETTE_START..Ppu::PALETTE_END {
+                if self.ppu.bus.palette_locked(addr) {
+                    self.ppu.bus.lock_palette(addr, false);
+                    self.tx.event(EmulationEvent::LockPalette((addr, false)));
+                }
+            }
+        }
+    }
+
     fn palette_color_from_offset(&self, offset: Vec2) -> PaletteColor {
         let Vec2 { x, y } = offset;
 
@@ -1447,6 +1747,11 @@ fn translate_screen_pos_to_tile(pos: Pos2, image_rect: Rect, texture_size: Vec2)
 }
 
 /// Return tile selection rectangle given an offset.
+/// Index of the sprite at `offset` in the 8x8 grid of OAM tiles.
+fn sprite_index(offset: Vec2) -> usize {
+    (offset.x / 8.0) as usize + (offset.y / 8.0) as usize * 8
+}
+
 fn tile_selection(image_rect: Rect, texture_size: Vec2, tile_offset: Vec2) -> Rect {
     let scale = image_rect.size() / texture_size;
     Rect::from_min_size(
@@ -1,39 +1,68 @@
 use crate::{
     feature,
     nes::{
-        config::{AudioConfig, Config, EmulationConfig, RendererConfig},
+        audio::{recording::RecordingLimits, AudioBackend},
+        config::{
+            Config, EmulationConfig, FontSize, FullscreenTarget, PathsConfig, RendererConfig,
+            ThemePreset, UpdateCheck,
+        },
+        emulation::screenshot_burst::ScreenshotBurst,
         event::{ConfigEvent, EmulationEvent, NesEventProxy, UiEvent},
         renderer::{
             gui::{
                 lib::{RadioValue, ShortcutText, ShowShortcut, ViewportOptions},
                 MessageType,
             },
-            shader::Shader,
+            painter::PresentMode,
+            shader::{ColorBlindMode, Shader, Transition},
+            texture::{Rotation, TextureFilter},
+            MonitorInfo,
         },
     },
+    platform::{open_dir_dialog, Feature},
 };
 use egui::{
-    Align, CentralPanel, Checkbox, Context, CursorIcon, DragValue, Grid, Key, Layout, ScrollArea,
-    Slider, TextEdit, Ui, Vec2, ViewportClass, ViewportId,
+    text::{LayoutJob, TextFormat},
+    Align, Button, CentralPanel, Checkbox, Color32, ComboBox, Context, CursorIcon, DragValue, Grid,
+    Key, Layout, RadioButton, Response, ScrollArea, Slider, TextEdit, TextStyle, Ui, Vec2,
+    ViewportClass, ViewportId,
 };
 use parking_lot::Mutex;
-use std::sync::{
-    atomic::{AtomicBool, Ordering},
-    Arc,
+use std::{
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
 };
 use tetanes_core::{
-    action::Action as DeckAction, apu::Channel, common::NesRegion,
-    control_deck::Config as DeckConfig, fs, genie::GenieCode, input::FourPlayer, mem::RamState,
-    time::Duration, video::VideoFilter,
+    action::Action as DeckAction,
+    apu::{
+        stereo::{StereoConfig, StereoMode},
+        Channel,
+    },
+    common::NesRegion,
+    control_deck::Config as DeckConfig,
+    fs,
+    genie::GenieCode,
+    input::{FourPlayer, ZapperConfig},
+    mem::RamState,
+    time::Duration,
+    video::{upscale::UpscaleFilter, VideoFilter},
 };
-use tracing::warn;
+use tracing::{error, warn};
 
 #[derive(Debug)]
 #[must_use]
 pub struct State {
     tx: NesEventProxy,
     tab: Tab,
+    search: String,
     genie_entry: GenieEntry,
+    path_entry: Option<(PathKind, String)>,
+    supported_present_modes: Vec<PresentMode>,
+    monitors: Vec<MonitorInfo>,
+    rom_name: Option<String>,
 }
 
 #[derive(Debug)]
@@ -52,6 +81,116 @@ pub enum Tab {
     Audio,
     Video,
     Input,
+    Accessibility,
+    Integrations,
+}
+
+impl Tab {
+    const ALL: [Self; 6] = [
+        Self::Emulation,
+        Self::Audio,
+        Self::Video,
+        Self::Input,
+        Self::Accessibility,
+        Self::Integrations,
+    ];
+
+    const fn label(&self) -> &'static str {
+        match self {
+            Self::Emulation => "Emulation",
+            Self::Audio => "Audio",
+            Self::Video => "Video",
+            Self::Input => "Input",
+            Self::Accessibility => "Accessibility",
+            Self::Integrations => "Integrations",
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PathKind {
+    Data,
+    SaveStates,
+    Sram,
+    Screenshots,
+    AudioRecordings,
+    Replays,
+    Sync,
+}
+
+impl PathKind {
+    const ALL: [Self; 7] = [
+        Self::Data,
+        Self::SaveStates,
+        Self::Sram,
+        Self::Screenshots,
+        Self::AudioRecordings,
+        Self::Replays,
+        Self::Sync,
+    ];
+
+    const fn label(&self) -> &'static str {
+        match self {
+            Self::Data => "Data",
+            Self::SaveStates => "Save States",
+            Self::Sram => "Save RAM",
+            Self::Screenshots => "Screenshots",
+            Self::AudioRecordings => "Audio Recordings",
+            Self::Replays => "Replays",
+            Self::Sync => "Sync Folder",
+        }
+    }
+
+    const fn path<'a>(&self, paths: &'a PathsConfig) -> &'a Option<PathBuf> {
+        match self {
+            Self::Data => &paths.data_dir,
+            Self::SaveStates => &paths.save_dir,
+            Self::Sram => &paths.sram_dir,
+            Self::Screenshots => &paths.screenshot_dir,
+            Self::AudioRecordings => &paths.audio_dir,
+            Self::Replays => &paths.replay_dir,
+            Self::Sync => &paths.sync_dir,
+        }
+    }
+
+    fn path_mut<'a>(&self, paths: &'a mut PathsConfig) -> &'a mut Option<PathBuf> {
+        match self {
+            Self::Data => &mut paths.data_dir,
+            Self::SaveStates => &mut paths.save_dir,
+            Self::Sram => &mut paths.sram_dir,
+            Self::Screenshots => &mut paths.screenshot_dir,
+            Self::AudioRecordings => &mut paths.audio_dir,
+            Self::Replays => &mut paths.replay_dir,
+            Self::Sync => &mut paths.sync_dir,
+        }
+    }
+
+    /// The directory in use, falling back to the default if not customized.
+    fn dir(&self, paths: &PathsConfig) -> PathBuf {
+        match self {
+            Self::Data => paths.data_dir(),
+            Self::SaveStates => paths.save_dir(),
+            Self::Sram => paths.sram_dir(),
+            Self::Screenshots => paths.screenshot_dir(),
+            Self::AudioRecordings => paths.audio_dir(),
+            Self::Replays => paths.replay_dir(),
+            Self::Sync => paths.sync_dir.clone().unwrap_or_else(|| paths.data_dir()),
+        }
+    }
+
+    /// Hint shown when the directory isn't customized, which may be inside a customized data
+    /// directory.
+    fn hint(&self, paths: &PathsConfig) -> String {
+        match self {
+            // Syncing has no default folder
+            Self::Sync => "Disabled".to_string(),
+            _ => {
+                let mut paths = paths.clone();
+                *self.path_mut(&mut paths) = None;
+                self.dir(&paths).display().to_string()
+            }
+        }
+    }
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Eq)]
@@ -60,17 +199,47 @@ pub struct GenieEntry {
     error: Option<String>,
 }
 
+/// A setting that can be found by searching preferences, shown with the same control as on its
+/// tab.
+#[derive(Copy, Clone)]
+struct Setting {
+    tab: Tab,
+    label: &'static str,
+    /// Additional search terms, such as related names or option values.
+    keywords: &'static str,
+    /// Platform feature required to show this setting.
+    feature: Option<Feature>,
+    ui: fn(&mut State, &mut Ui, &Config),
+}
+
+impl Setting {
+    fn available(&self) -> bool {
+        self.feature.map_or(true, |feature| feature!(feature))
+    }
+
+    /// Whether every search term is found in the label, keywords or tab name.
+    fn matches(&self, terms: &[String]) -> bool {
+        let text = format!("{} {} {}", self.label, self.keywords, self.tab.label()).to_lowercase();
+        terms.iter().all(|term| text.contains(term.as_str()))
+    }
+}
+
 impl Preferences {
     const TITLE: &'static str = "🔧 Preferences";
 
-    pub fn new(tx: NesEventProxy) -> Self {
+    pub fn new(tx: NesEventProxy, supported_present_modes: Vec<PresentMode>) -> Self {
         Self {
             id: egui::ViewportId::from_hash_of(Self::TITLE),
             open: Arc::new(AtomicBool::new(false)),
             state: Arc::new(Mutex::new(State {
                 tx,
                 tab: Tab::default(),
+                search: String::new(),
                 genie_entry: GenieEntry::default(),
+                path_entry: None,
+                supported_present_modes,
+                monitors: Vec::new(),
+                rom_name: None,
             })),
             resources: None,
         }
@@ -94,6 +263,16 @@ impl Preferences {
             .fetch_update(Ordering::Release, Ordering::Acquire, |open| Some(!open));
     }
 
+    /// Update the monitors that can be chosen to go fullscreen on.
+    pub fn set_monitors(&self, monitors: Vec<MonitorInfo>) {
+        self.state.lock().monitors = monitors;
+    }
+
+    /// Update the name of the loaded ROM, used for per-game settings.
+    pub fn set_rom_name(&self, rom_name: Option<String>) {
+        self.state.lock().rom_name = rom_name;
+    }
+
     pub fn prepare(&mut self, cfg: &Config) {
         self.resources = Some(cfg.clone());
     }
@@ -177,37 +356,35 @@ impl Preferences {
         }
     }
 
+    /// Show a radio button for each save slot, returning the slot being hovered, if any.
     pub fn save_slot_radio(
         tx: &NesEventProxy,
         ui: &mut Ui,
         mut save_slot: u8,
         cfg: &Config,
         show_shortcut: ShowShortcut,
-    ) {
-        ui.vertical(|ui| {
-            for slot in 1..=4 {
-                let radio = RadioValue::new(&mut save_slot, slot, slot.to_string()).shortcut_text(
-                    show_shortcut
-                        .then(|| cfg.shortcut(DeckAction::SetSaveSlot(slot)))
-                        .unwrap_or_default(),
-                );
-                if ui.add(radio).changed() {
-                    tx.event(ConfigEvent::SaveSlot(save_slot));
-                }
-            }
-        });
-        ui.vertical(|ui| {
-            for slot in 5..=8 {
-                let radio = RadioValue::new(&mut save_slot, slot, slot.to_string()).shortcut_text(
-                    show_shortcut
-                        .then(|| cfg.shortcut(DeckAction::SetSaveSlot(slot)))
-                        .unwrap_or_default(),
-                );
-                if ui.add(radio).changed() {
-                    tx.event(ConfigEvent::SaveSlot(save_slot));
+    ) -> Option<(u8, Response)> {
+        let mut hovered = None;
+        for slots in [1..=4, 5..=8] {
+            ui.vertical(|ui| {
+                for slot in slots {
+                    let radio = RadioValue::new(&mut save_slot, slot, slot.to_string())
+                        .shortcut_text(
+                            show_shortcut
+                                .then(|| cfg.shortcut(DeckAction::SetSaveSlot(slot)))
+                                .unwrap_or_default(),
+                        );
+                    let res = ui.add(radio);
+                    if res.changed() {
+                        tx.event(ConfigEvent::SaveSlot(save_slot));
+                    }
+                    if res.hovered() {
+                        hovered = Some((slot, res));
+                    }
                 }
-            }
-        });
+            });
+        }
+        hovered
     }
 
     pub fn speed_slider(tx: &NesEventProxy, ui: &mut Ui, mut speed: f32) {
@@ -222,6 +399,16 @@ impl Preferences {
         }
     }
 
+    pub fn fast_forward_slider(tx: &NesEventProxy, ui: &mut Ui, mut speed: f32) {
+        let slider = Slider::new(&mut speed, 1.5..=8.0).step_by(0.5).suffix("x");
+        let res = ui
+            .add(slider)
+            .on_hover_text("Adjust the speed of the NES emulation while fast forwarding.");
+        if res.changed() {
+            tx.event(ConfigEvent::FastForwardSpeed(speed));
+        }
+    }
+
     pub fn run_ahead_slider(tx: &NesEventProxy, ui: &mut Ui, mut run_ahead: usize) {
         let slider = Slider::new(&mut run_ahead, 0..=4);
         let res = ui
@@ -268,6 +455,18 @@ impl Preferences {
         }
     }
 
+    pub fn vaus_checkbox(tx: &NesEventProxy, ui: &mut Ui, mut vaus: bool) {
+        let res = ui
+            .checkbox(&mut vaus, "Enable Arkanoid Paddle")
+            .on_hover_text(concat!(
+                "Enable the Arkanoid Vaus paddle controller. ",
+                "Click the game screen to control it with the mouse and press Escape to release.",
+            ));
+        if res.clicked() {
+            tx.event(ConfigEvent::VausConnected(vaus));
+        }
+    }
+
     pub fn zapper_checkbox(
         tx: &NesEventProxy,
         ui: &mut Ui,
@@ -303,6 +502,46 @@ impl Preferences {
         }
     }
 
+    pub fn scanlines_checkbox(tx: &NesEventProxy, ui: &mut Ui, mut scanlines: bool) {
+        let res = ui
+            .checkbox(&mut scanlines, "240p Scanlines")
+            .on_hover_text(concat!(
+                "Draw a black line between each line of the game, like a 240p signal on a CRT. ",
+                "Looks best at a window scale of 3x or more.",
+            ));
+        if res.changed() {
+            tx.event(ConfigEvent::Scanlines(scanlines));
+        }
+    }
+
+    pub fn black_frame_insertion_checkbox(
+        tx: &NesEventProxy,
+        ui: &mut Ui,
+        mut black_frame_insertion: bool,
+    ) {
+        let res = ui
+            .checkbox(&mut black_frame_insertion, "Black Frame Insertion")
+            .on_hover_text(concat!(
+                "Show a black frame after each frame to reduce motion blur on OLED and other ",
+                "sample-and-hold displays. Requires a 120Hz display with Vsync, and dims the picture.",
+            ));
+        if res.changed() {
+            tx.event(ConfigEvent::BlackFrameInsertion(black_frame_insertion));
+        }
+    }
+
+    pub fn sprite_limit_checkbox(tx: &NesEventProxy, ui: &mut Ui, mut sprite_limit: bool) {
+        let res = ui
+            .checkbox(&mut sprite_limit, "Limit Sprites Per Scanline")
+            .on_hover_text(concat!(
+                "The NES can only draw 8 sprites per scanline, causing flicker in busy games. ",
+                "Disable this to draw all sprites. May cause glitches in games that rely on the limit.",
+            ));
+        if res.changed() {
+            tx.event(ConfigEvent::SpriteLimit(sprite_limit));
+        }
+    }
+
     pub fn video_filter_radio(tx: &NesEventProxy, ui: &mut Ui, mut filter: VideoFilter) {
         let previous_filter = filter;
         ui.radio_value(&mut filter, VideoFilter::Pixellate, "Pixellate")
@@ -316,6 +555,62 @@ impl Preferences {
         }
     }
 
+    pub fn upscale_filter_combo(tx: &NesEventProxy, ui: &mut Ui, mut filter: UpscaleFilter) {
+        let previous_filter = filter;
+        ComboBox::from_id_salt("upscale_filter")
+            .selected_text(filter.as_ref())
+            .show_ui(ui, |ui| {
+                for value in UpscaleFilter::as_slice() {
+                    ui.selectable_value(&mut filter, *value, value.as_ref());
+                }
+            })
+            .response
+            .on_hover_cursor(CursorIcon::Help)
+            .on_hover_text(concat!(
+                "Smooth the edges of pixel art by upscaling each frame. ",
+                "Works best with the Pixellate video filter.",
+            ));
+        if filter != previous_filter {
+            tx.event(ConfigEvent::UpscaleFilter(filter));
+        }
+    }
+
+    pub fn transition_combo(tx: &NesEventProxy, ui: &mut Ui, mut transition: Transition) {
+        let previous_transition = transition;
+        ComboBox::from_id_salt("transition")
+            .selected_text(transition.as_ref())
+            .show_ui(ui, |ui| {
+                for value in Transition::as_slice() {
+                    ui.selectable_value(&mut transition, *value, value.as_ref());
+                }
+            })
+            .response
+            .on_hover_cursor(CursorIcon::Help)
+            .on_hover_text("Play a brief effect when loading a ROM, resetting or loading a state.");
+        if transition != previous_transition {
+            tx.event(ConfigEvent::Transition(transition));
+        }
+    }
+
+    pub fn texture_filter_radio(tx: &NesEventProxy, ui: &mut Ui, mut filter: TextureFilter) {
+        let previous_filter = filter;
+        ui.radio_value(&mut filter, TextureFilter::Nearest, "Nearest")
+            .on_hover_text(concat!(
+                "Sharp pixels, but pixels are drawn at uneven sizes when the window isn't an ",
+                "exact multiple of the frame size, which shimmers when scrolling.",
+            ));
+        ui.radio_value(&mut filter, TextureFilter::Bilinear, "Bilinear")
+            .on_hover_text("Smooth, evenly sized pixels at the cost of a blurrier image.");
+        ui.radio_value(&mut filter, TextureFilter::SharpBilinear, "Sharp Bilinear")
+            .on_hover_text(concat!(
+                "Scales pixels to the largest whole multiple that fits and smooths only the ",
+                "remainder, for sharp, evenly sized pixels at any window size.",
+            ));
+        if filter != previous_filter {
+            tx.event(ConfigEvent::TextureFilter(filter));
+        }
+    }
+
     pub fn shader_radio(tx: &NesEventProxy, ui: &mut Ui, mut shader: Shader) {
         let previous_shader = shader;
         ui.radio_value(&mut shader, Shader::None, "None")
@@ -327,13 +622,77 @@ impl Preferences {
         }
     }
 
+    pub fn present_mode_radio(
+        tx: &NesEventProxy,
+        ui: &mut Ui,
+        mut present_mode: PresentMode,
+        supported: &[PresentMode],
+    ) {
+        let previous_present_mode = present_mode;
+        for (mode, label, hover_text) in [
+            (
+                PresentMode::Vsync,
+                "Vsync",
+                concat!(
+                    "Synchronize presenting frames with the display refresh rate, presenting late ",
+                    "frames immediately if supported to reduce stuttering."
+                ),
+            ),
+            (
+                PresentMode::Fifo,
+                "Fifo (Strict Vsync)",
+                "Always synchronize presenting frames with the display refresh rate.",
+            ),
+            (
+                PresentMode::Mailbox,
+                "Mailbox (Triple Buffering)",
+                concat!(
+                    "Synchronize with the display refresh rate, always presenting the latest ",
+                    "frame. Reduces latency at the cost of more GPU usage."
+                ),
+            ),
+            (
+                PresentMode::Immediate,
+                "Immediate",
+                "Present frames as soon as they are ready, which may cause tearing.",
+            ),
+        ] {
+            let res = ui
+                .add_enabled(
+                    supported.contains(&mode),
+                    RadioButton::new(present_mode == mode, label),
+                )
+                .on_hover_text(hover_text)
+                .on_disabled_hover_text("Not supported by this graphics adapter.");
+            if res.clicked() {
+                present_mode = mode;
+            }
+        }
+        if !supported.contains(&present_mode) {
+            ui.colored_label(
+                ui.visuals().warn_fg_color,
+                format!(
+                    "{} is not supported by this graphics adapter, falling back to Vsync.",
+                    present_mode.as_ref()
+                ),
+            );
+        }
+        if present_mode != previous_present_mode {
+            tx.event(ConfigEvent::PresentMode(present_mode));
+        }
+    }
+
     pub fn four_player_radio(tx: &NesEventProxy, ui: &mut Ui, mut four_player: FourPlayer) {
         let previous_four_player = four_player;
         ui.radio_value(&mut four_player, FourPlayer::Disabled, "Disabled");
         ui.radio_value(&mut four_player, FourPlayer::FourScore, "Four Score")
-            .on_hover_text("Enable NES Four Score for games that support 4 players.");
+            .on_hover_text(
+                "Enable NES Four Score for games that support 4 players. Can be changed while playing.",
+            );
         ui.radio_value(&mut four_player, FourPlayer::Satellite, "Satellite")
-            .on_hover_text("Enable NES Satellite for games that support 4 players.");
+            .on_hover_text(
+                "Enable NES Satellite for games that support 4 players. Can be changed while playing.",
+            );
         if four_player != previous_four_player {
             tx.event(ConfigEvent::FourPlayer(four_player));
         }
@@ -383,6 +742,16 @@ impl Preferences {
         }
     }
 
+    pub fn compact_mode_checkbox(tx: &NesEventProxy, ui: &mut Ui, mut compact_mode: bool) {
+        let checkbox = Checkbox::new(&mut compact_mode, "📺 Compact Mode");
+        let res = ui.add(checkbox).on_hover_text(
+            "Hide the menu bar, keep the window on top and size it to a whole multiple of the NES frame. Right-click the game to exit.",
+        );
+        if res.clicked() {
+            tx.event(ConfigEvent::CompactMode(compact_mode));
+        }
+    }
+
     pub fn messages_checkbox(
         tx: &NesEventProxy,
         ui: &mut Ui,
@@ -402,6 +771,36 @@ impl Preferences {
         }
     }
 
+    pub fn mapper_stats_checkbox(
+        tx: &NesEventProxy,
+        ui: &mut Ui,
+        mut mapper_stats: bool,
+    ) -> Response {
+        let res = ui
+            .checkbox(&mut mapper_stats, "Count Unsupported Mappers")
+            .on_hover_text(concat!(
+                "Keep a local count of ROMs that failed to load due to an unsupported mapper, ",
+                "which can be exported as a report. No ROM names are recorded and nothing is sent anywhere."
+            ));
+        if res.clicked() {
+            tx.event(ConfigEvent::MapperStats(mapper_stats));
+        }
+        res
+    }
+
+    pub fn announce_messages_checkbox(tx: &NesEventProxy, ui: &mut Ui, mut announce: bool) {
+        let res = ui
+            .checkbox(&mut announce, "Announce Messages")
+            .on_hover_cursor(CursorIcon::Help)
+            .on_hover_text(concat!(
+                "Speak messages out loud, such as saving and loading states or errors. ",
+                "Screen readers already announce messages, so this is only needed without one.",
+            ));
+        if res.clicked() {
+            tx.event(ConfigEvent::AnnounceMessages(announce));
+        }
+    }
+
     pub fn screen_reader_checkbox(ui: &mut Ui, shortcut: impl Into<Option<String>>) {
         let shortcut = shortcut.into();
         // icon: document with text
@@ -433,6 +832,205 @@ impl Preferences {
         }
     }
 
+    pub fn ui_zoom_slider(tx: &NesEventProxy, ui: &mut Ui, mut zoom: f32) {
+        let slider = Slider::new(&mut zoom, 0.5..=3.0).step_by(0.1).suffix("x");
+        let res = ui
+            .add(slider)
+            .on_hover_text("Adjust the size of menus and windows, independent of Window Scale.");
+        if res.changed() {
+            tx.event(ConfigEvent::Zoom(zoom));
+        }
+    }
+
+    pub fn font_size_radio(tx: &NesEventProxy, ui: &mut Ui, mut font_size: FontSize) {
+        let previous_font_size = font_size;
+        for size in FontSize::as_slice() {
+            ui.radio_value(&mut font_size, *size, size.as_ref());
+        }
+        if font_size != previous_font_size {
+            tx.event(ConfigEvent::FontSize(font_size));
+        }
+    }
+
+    pub fn theme_radio(tx: &NesEventProxy, ui: &mut Ui, mut theme: ThemePreset) {
+        let previous_theme = theme;
+        for preset in ThemePreset::as_slice() {
+            ui.radio_value(&mut theme, *preset, preset.as_ref());
+        }
+        if theme != previous_theme {
+            tx.event(ConfigEvent::Theme(theme));
+        }
+    }
+
+    pub fn update_check_radio(tx: &NesEventProxy, ui: &mut Ui, mut update_check: UpdateCheck) {
+        let previous_update_check = update_check;
+        for option in UpdateCheck::as_slice() {
+            ui.radio_value(&mut update_check, *option, option.as_ref());
+        }
+        if update_check != previous_update_check {
+            tx.event(ConfigEvent::UpdateCheck(update_check));
+        }
+    }
+
+    pub fn accent_color_picker(tx: &NesEventProxy, ui: &mut Ui, accent_color: Option<Color32>) {
+        ui.horizontal(|ui| {
+            let mut custom = accent_color.is_some();
+            let res = ui
+                .checkbox(&mut custom, "Custom")
+                .on_hover_text("Use a custom color for highlights instead of the theme color.");
+            if res.clicked() {
+                let color = custom.then(|| ui.visuals().widgets.hovered.fg_stroke.color);
+                tx.event(ConfigEvent::AccentColor(color));
+            }
+            if let Some(mut color) = accent_color {
+                if ui.color_edit_button_srgba(&mut color).changed() {
+                    tx.event(ConfigEvent::AccentColor(Some(color)));
+                }
+            }
+        });
+    }
+
+    pub fn high_contrast_checkbox(tx: &NesEventProxy, ui: &mut Ui, mut high_contrast: bool) {
+        let res = ui
+            .checkbox(&mut high_contrast, "High Contrast Theme")
+            .on_hover_text("Use a high contrast theme for menus and windows.");
+        if res.clicked() {
+            tx.event(ConfigEvent::HighContrast(high_contrast));
+        }
+    }
+
+    pub fn color_blind_mode_radio(
+        tx: &NesEventProxy,
+        ui: &mut Ui,
+        mut color_blind_mode: ColorBlindMode,
+    ) {
+        let previous_mode = color_blind_mode;
+        ui.radio_value(&mut color_blind_mode, ColorBlindMode::None, "None")
+            .on_hover_text("No color correction.");
+        ui.radio_value(
+            &mut color_blind_mode,
+            ColorBlindMode::Deuteranopia,
+            "Deuteranopia",
+        )
+        .on_hover_text("Correct colors for green-blindness.");
+        ui.radio_value(
+            &mut color_blind_mode,
+            ColorBlindMode::Protanopia,
+            "Protanopia",
+        )
+        .on_hover_text("Correct colors for red-blindness.");
+        ui.radio_value(
+            &mut color_blind_mode,
+            ColorBlindMode::Tritanopia,
+            "Tritanopia",
+        )
+        .on_hover_text("Correct colors for blue-blindness.");
+        if color_blind_mode != previous_mode {
+            tx.event(ConfigEvent::ColorBlindMode(color_blind_mode));
+        }
+    }
+
+    pub fn rotation_radio(tx: &NesEventProxy, ui: &mut Ui, mut rotation: Rotation) {
+        let previous_rotation = rotation;
+        for value in Rotation::as_slice() {
+            ui.radio_value(&mut rotation, *value, value.as_ref())
+                .on_hover_text(
+                    "Rotate the game screen clockwise, for games designed for rotated displays.",
+                );
+        }
+        if rotation != previous_rotation {
+            tx.event(ConfigEvent::Rotation(rotation));
+        }
+    }
+
+    pub fn flip_horizontal_checkbox(tx: &NesEventProxy, ui: &mut Ui, mut flip_horizontal: bool) {
+        let res = ui
+            .checkbox(&mut flip_horizontal, "Mirror Horizontally")
+            .on_hover_text("Flip the game screen from left to right.");
+        if res.clicked() {
+            tx.event(ConfigEvent::FlipHorizontal(flip_horizontal));
+        }
+    }
+
+    /// Grid rows to choose the monitor and mode to go fullscreen with.
+    fn fullscreen_target_ui(
+        tx: &NesEventProxy,
+        ui: &mut Ui,
+        target: &FullscreenTarget,
+        monitors: &[MonitorInfo],
+    ) {
+        let mut new_target = target.clone();
+
+        ui.with_layout(Layout::left_to_right(Align::Min), |ui| {
+            ui.strong("Fullscreen Monitor:");
+        });
+        ComboBox::from_id_salt("fullscreen_monitor")
+            .selected_text(target.monitor.as_deref().unwrap_or("Current Monitor"))
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut new_target.monitor, None, "Current Monitor")
+                    .on_hover_text("Go fullscreen on the monitor the window is on.");
+                for monitor in monitors {
+                    ui.selectable_value(
+                        &mut new_target.monitor,
+                        Some(monitor.name.clone()),
+                        &monitor.name,
+                    );
+                }
+            });
+        ui.end_row();
+
+        ui.with_layout(Layout::left_to_right(Align::Min), |ui| {
+            ui.strong("Fullscreen Mode:");
+        });
+        ui.vertical(|ui| {
+            ui.radio_value(&mut new_target.exclusive, false, "Borderless")
+                .on_hover_text("Cover the monitor with a borderless window.");
+            ui.radio_value(&mut new_target.exclusive, true, "Exclusive")
+                .on_hover_text(concat!(
+                    "Switch the monitor video mode for the lowest latency. ",
+                    "Switching in and out of fullscreen may be slower."
+                ));
+
+            let video_modes = monitors
+                .iter()
+                .find(|monitor| match &new_target.monitor {
+                    Some(name) => monitor.name == *name,
+                    None => monitor.current,
+                })
+                .map(|monitor| monitor.video_modes.as_slice())
+                .unwrap_or_default();
+            ui.add_enabled_ui(new_target.exclusive, |ui| {
+                let selected_text = new_target
+                    .video_mode
+                    .map_or_else(|| "Best Available".to_string(), |mode| mode.to_string());
+                ComboBox::from_id_salt("fullscreen_video_mode")
+                    .selected_text(selected_text)
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut new_target.video_mode, None, "Best Available")
+                            .on_hover_text(
+                                "Use the highest resolution and refresh rate of the monitor.",
+                            );
+                        for mode in video_modes {
+                            ui.selectable_value(
+                                &mut new_target.video_mode,
+                                Some(*mode),
+                                mode.to_string(),
+                            );
+                        }
+                    });
+            });
+        });
+        ui.end_row();
+
+        if new_target.monitor != target.monitor {
+            // Video modes differ between monitors
+            new_target.video_mode = None;
+        }
+        if new_target != *target {
+            tx.event(ConfigEvent::FullscreenTarget(new_target));
+        }
+    }
+
     pub fn fullscreen_checkbox(
         tx: &NesEventProxy,
         ui: &mut Ui,
@@ -497,76 +1095,740 @@ impl Preferences {
 }
 
 impl State {
-    fn ui(&mut self, ui: &mut Ui, enabled: bool, cfg: &Config) {
-        #[cfg(feature = "profiling")]
-        puffin::profile_function!();
-
-        ui.add_enabled_ui(enabled, |ui| {
-            ui.set_min_height(ui.available_height());
-
-            ui.horizontal(|ui| {
-                ui.selectable_value(&mut self.tab, Tab::Emulation, "Emulation");
-                ui.selectable_value(&mut self.tab, Tab::Audio, "Audio");
-                ui.selectable_value(&mut self.tab, Tab::Video, "Video");
-                ui.selectable_value(&mut self.tab, Tab::Input, "Input");
-            });
-
-            ui.separator();
-
-            ScrollArea::both().show(ui, |ui| {
-                match self.tab {
-                    Tab::Emulation => self.emulation_tab(ui, cfg),
-                    Tab::Audio => Self::audio_tab(&self.tx, ui, cfg),
-                    Tab::Video => Self::video_tab(&self.tx, ui, cfg),
-                    Tab::Input => Self::input_tab(&self.tx, ui, cfg),
-                }
-
-                ui.separator();
-
-                ui.horizontal(|ui| {
-                    if ui.button("Restore Defaults").clicked() {
-                        Self::restore_defaults(&self.tx, ui.ctx());
-                    }
-
-                    if feature!(Storage) && ui.button("Clear Save States").clicked() {
-                        Self::clear_save_states(&self.tx);
-                    }
-
-                    if feature!(Filesystem) && ui.button("Clear Recent ROMs").clicked() {
-                        self.tx.event(ConfigEvent::RecentRomsClear);
-                    }
-
-                    #[cfg(target_arch = "wasm32")]
-                    if ui.button("Download Save States").clicked() {
-                        if let Err(err) = crate::platform::download_save_states() {
-                            self.tx
-                                .event(UiEvent::Message((MessageType::Error, err.to_string())));
-                        }
-                    }
+    /// Searchable settings, in the order they're shown on each tab.
+    const SETTINGS: &'static [Setting] = &[
+        Setting {
+            tab: Tab::Emulation,
+            label: "Cycle Accurate",
+            keywords: "accuracy performance",
+            feature: None,
+            ui: |state, ui, cfg| {
+                Preferences::cycle_accurate_checkbox(&state.tx, ui, cfg.deck.cycle_accurate, None);
+            },
+        },
+        Setting {
+            tab: Tab::Emulation,
+            label: "Auto-Load",
+            keywords: "save state slot",
+            feature: None,
+            ui: |state, ui, cfg| Self::auto_load_checkbox(&state.tx, ui, cfg.emulation.auto_load),
+        },
+        Setting {
+            tab: Tab::Emulation,
+            label: "Rewind",
+            keywords: "rewinding seconds interval memory",
+            feature: None,
+            ui: |state, ui, cfg| Self::rewind_settings(&state.tx, ui, cfg),
+        },
+        Setting {
+            tab: Tab::Emulation,
+            label: "Auto-Save",
+            keywords: "save state slot interval exit",
+            feature: None,
+            ui: |state, ui, cfg| Self::auto_save_settings(&state.tx, ui, cfg),
+        },
+        Setting {
+            tab: Tab::Emulation,
+            label: "Emulate PPU Warmup",
+            keywords: "accuracy registers",
+            feature: None,
+            ui: |state, ui, cfg| {
+                Self::emulate_ppu_warmup_checkbox(&state.tx, ui, cfg.deck.emulate_ppu_warmup);
+            },
+        },
+        Setting {
+            tab: Tab::Emulation,
+            label: "Fast Boot",
+            keywords: "power-on startup skip",
+            feature: None,
+            ui: |state, ui, cfg| Self::fast_boot_checkbox(&state.tx, ui, cfg.deck.fast_boot),
+        },
+        Setting {
+            tab: Tab::Emulation,
+            label: "Mapper Stats",
+            keywords: "debug overlay bank switches",
+            feature: None,
+            ui: |state, ui, cfg| {
+                Preferences::mapper_stats_checkbox(&state.tx, ui, cfg.renderer.mapper_stats);
+            },
+        },
+        Setting {
+            tab: Tab::Emulation,
+            label: "Attract Mode",
+            keywords: "idle demo replay library",
+            feature: None,
+            ui: |state, ui, cfg| {
+                Self::attract_mode_drag(&state.tx, ui, cfg.renderer.attract_mode_minutes);
+            },
+        },
+        Setting {
+            tab: Tab::Emulation,
+            label: "Emulation Speed",
+            keywords: "slow motion",
+            feature: None,
+            ui: |state, ui, cfg| Preferences::speed_slider(&state.tx, ui, cfg.emulation.speed),
+        },
+        Setting {
+            tab: Tab::Emulation,
+            label: "Fast Forward",
+            keywords: "speed turbo uncapped toggle frame skip",
+            feature: None,
+            ui: |state, ui, cfg| {
+                ui.add_enabled_ui(!cfg.emulation.fast_forward_uncapped, |ui| {
+                    Preferences::fast_forward_slider(
+                        &state.tx,
+                        ui,
+                        cfg.emulation.fast_forward_speed,
+                    );
                 });
-            });
-        });
-    }
-
-    fn emulation_tab(&mut self, ui: &mut Ui, cfg: &Config) {
-        #[cfg(feature = "profiling")]
-        puffin::profile_function!();
-
+                Self::fast_forward_settings(&state.tx, ui, cfg);
+            },
+        },
+        Setting {
+            tab: Tab::Emulation,
+            label: "Run Ahead",
+            keywords: "input lag latency",
+            feature: None,
+            ui: |state, ui, cfg| {
+                Preferences::run_ahead_slider(&state.tx, ui, cfg.emulation.run_ahead);
+            },
+        },
+        Setting {
+            tab: Tab::Emulation,
+            label: "Quick Save Ring",
+            keywords: "save state",
+            feature: None,
+            ui: |state, ui, cfg| Self::save_ring_drag(&state.tx, ui, cfg.emulation.save_ring_len),
+        },
+        Setting {
+            tab: Tab::Emulation,
+            label: "Screenshot Burst",
+            keywords: "frames sprite sheet animation capture png",
+            feature: Some(Feature::Filesystem),
+            ui: |state, ui, cfg| {
+                Self::screenshot_burst_drag(&state.tx, ui, cfg.emulation.screenshot_burst_frames);
+            },
+        },
+        Setting {
+            tab: Tab::Emulation,
+            label: "Replay Hash Interval",
+            keywords: "replay desync verify state hash",
+            feature: None,
+            ui: |state, ui, cfg| {
+                Self::replay_hash_drag(&state.tx, ui, cfg.emulation.replay_hash_interval);
+            },
+        },
+        Setting {
+            tab: Tab::Emulation,
+            label: "Save Slot",
+            keywords: "save state load",
+            feature: None,
+            ui: |state, ui, cfg| {
+                Grid::new("search_save_slots")
+                    .num_columns(2)
+                    .spacing([20.0, 6.0])
+                    .show(ui, |ui| {
+                        Preferences::save_slot_radio(
+                            &state.tx,
+                            ui,
+                            cfg.emulation.save_slot,
+                            cfg,
+                            ShowShortcut::No,
+                        );
+                    });
+            },
+        },
+        Setting {
+            tab: Tab::Emulation,
+            label: "Four Player",
+            keywords: "four score multitap controllers",
+            feature: None,
+            ui: |state, ui, cfg| {
+                Preferences::four_player_radio(&state.tx, ui, cfg.deck.four_player)
+            },
+        },
+        Setting {
+            tab: Tab::Emulation,
+            label: "NES Region",
+            keywords: "ntsc pal dendy timing",
+            feature: None,
+            ui: |state, ui, cfg| Preferences::nes_region_radio(&state.tx, ui, cfg.deck.region),
+        },
+        Setting {
+            tab: Tab::Emulation,
+            label: "RAM State",
+            keywords: "memory random zeroes ones",
+            feature: None,
+            ui: |state, ui, cfg| Preferences::ram_state_radio(&state.tx, ui, cfg.deck.ram_state),
+        },
+        Setting {
+            tab: Tab::Emulation,
+            label: "Game Genie Codes",
+            keywords: "cheats",
+            feature: None,
+            ui: |state, ui, cfg| {
+                state.genie_codes_entry(ui, cfg);
+                Preferences::genie_codes_list(&state.tx, ui, cfg, false);
+            },
+        },
+        Setting {
+            tab: Tab::Emulation,
+            label: "Directories",
+            keywords: "folders paths save states ram screenshots audio recordings replays sync",
+            feature: Some(Feature::Filesystem),
+            ui: |state, ui, cfg| state.directories(ui, cfg),
+        },
+        Setting {
+            tab: Tab::Emulation,
+            label: "Watch ROM Directory",
+            keywords: "library folder network",
+            feature: Some(Feature::Filesystem),
+            ui: |state, ui, cfg| {
+                Self::watch_roms_checkbox(&state.tx, ui, cfg.renderer.watch_roms_path);
+            },
+        },
+        Setting {
+            tab: Tab::Audio,
+            label: "Enable Audio",
+            keywords: "sound mute",
+            feature: None,
+            ui: |state, ui, cfg| Self::audio_enabled_checkbox(&state.tx, ui, cfg.audio.enabled),
+        },
+        Setting {
+            tab: Tab::Audio,
+            label: "APU Channels",
+            keywords: "pulse triangle noise dmc mapper sound mute",
+            feature: None,
+            ui: |state, ui, cfg| Self::apu_channels(&state.tx, ui, cfg.deck.channels_enabled),
+        },
+        Setting {
+            tab: Tab::Audio,
+            label: "Audio Buffer",
+            keywords: "sound samples underruns crackling",
+            feature: None,
+            ui: |state, ui, cfg| Self::audio_buffer_drag(&state.tx, ui, cfg.audio.buffer_size),
+        },
+        Setting {
+            tab: Tab::Audio,
+            label: "Audio Latency",
+            keywords: "sound delay underruns crackling",
+            feature: None,
+            ui: |state, ui, cfg| Self::audio_latency_drag(&state.tx, ui, cfg.audio.latency),
+        },
+        Setting {
+            tab: Tab::Audio,
+            label: "Audio Backend",
+            keywords: "sound host driver jack asio",
+            feature: None,
+            ui: |state, ui, cfg| Self::audio_backend_combo(&state.tx, ui, cfg.audio.backend),
+        },
+        Setting {
+            tab: Tab::Audio,
+            label: "Split Audio Recordings",
+            keywords: "record wav file size length minutes",
+            feature: Some(Feature::Filesystem),
+            ui: |state, ui, cfg| {
+                Self::recording_limits_drags(&state.tx, ui, cfg.audio.recording_limits);
+            },
+        },
+        Setting {
+            tab: Tab::Audio,
+            label: "Reduce DMC Pops",
+            keywords: "clicks sound game",
+            feature: None,
+            ui: |state, ui, cfg| {
+                Self::dmc_click_reduction_checkbox(&state.tx, ui, cfg, state.rom_name.as_deref());
+            },
+        },
+        Setting {
+            tab: Tab::Audio,
+            label: "Stereo",
+            keywords: "pan panning headphones pseudo mono sound",
+            feature: None,
+            ui: |state, ui, cfg| Self::stereo_settings(&state.tx, ui, cfg.deck.stereo),
+        },
+        Setting {
+            tab: Tab::Video,
+            label: "Menu Bar",
+            keywords: "menubar hide",
+            feature: None,
+            ui: |state, ui, cfg| {
+                Preferences::menubar_checkbox(&state.tx, ui, cfg.renderer.show_menubar, None);
+            },
+        },
+        Setting {
+            tab: Tab::Video,
+            label: "Fullscreen",
+            keywords: "window",
+            feature: None,
+            ui: |state, ui, cfg| {
+                Preferences::fullscreen_checkbox(&state.tx, ui, cfg.renderer.fullscreen, None);
+            },
+        },
+        Setting {
+            tab: Tab::Video,
+            label: "Messages",
+            keywords: "notifications",
+            feature: None,
+            ui: |state, ui, cfg| {
+                Preferences::messages_checkbox(&state.tx, ui, cfg.renderer.show_messages, None);
+            },
+        },
+        Setting {
+            tab: Tab::Video,
+            label: "Embed Viewports",
+            keywords: "windows",
+            feature: Some(Feature::OsViewports),
+            ui: |state, ui, cfg| Preferences::embed_viewports_checkbox(&state.tx, ui, cfg, None),
+        },
+        Setting {
+            tab: Tab::Video,
+            label: "Hide Overscan",
+            keywords: "border crop",
+            feature: None,
+            ui: |state, ui, cfg| {
+                Preferences::overscan_checkbox(&state.tx, ui, cfg.renderer.hide_overscan, None);
+            },
+        },
+        Setting {
+            tab: Tab::Video,
+            label: "Always On Top",
+            keywords: "window",
+            feature: None,
+            ui: |state, ui, cfg| {
+                Preferences::always_on_top_checkbox(
+                    &state.tx,
+                    ui,
+                    cfg.renderer.always_on_top,
+                    None,
+                );
+            },
+        },
+        Setting {
+            tab: Tab::Video,
+            label: "Sprite Limit",
+            keywords: "flicker",
+            feature: None,
+            ui: |state, ui, cfg| {
+                Preferences::sprite_limit_checkbox(&state.tx, ui, cfg.deck.sprite_limit);
+            },
+        },
+        Setting {
+            tab: Tab::Video,
+            label: "Window Scale",
+            keywords: "size",
+            feature: None,
+            ui: |state, ui, cfg| {
+                ui.horizontal(|ui| {
+                    Preferences::window_scale_radio(&state.tx, ui, cfg.renderer.scale);
+                });
+            },
+        },
+        Setting {
+            tab: Tab::Video,
+            label: "Video Filter",
+            keywords: "pixellate ntsc",
+            feature: None,
+            ui: |state, ui, cfg| Preferences::video_filter_radio(&state.tx, ui, cfg.deck.filter),
+        },
+        Setting {
+            tab: Tab::Video,
+            label: "Texture Filter",
+            keywords: "nearest bilinear sharp smooth blur shimmer sampling",
+            feature: None,
+            ui: |state, ui, cfg| {
+                ui.horizontal(|ui| {
+                    Preferences::texture_filter_radio(&state.tx, ui, cfg.renderer.texture_filter);
+                });
+            },
+        },
+        Setting {
+            tab: Tab::Video,
+            label: "Upscale Filter",
+            keywords: "scale2x scale3x hqx hq2x smooth pixel art",
+            feature: None,
+            ui: |state, ui, cfg| {
+                Preferences::upscale_filter_combo(&state.tx, ui, cfg.renderer.upscale_filter);
+            },
+        },
+        Setting {
+            tab: Tab::Video,
+            label: "Shader",
+            keywords: "crt scanlines",
+            feature: None,
+            ui: |state, ui, cfg| Preferences::shader_radio(&state.tx, ui, cfg.renderer.shader),
+        },
+        Setting {
+            tab: Tab::Video,
+            label: "Transition",
+            keywords: "melt fade crt power off effect reset load",
+            feature: None,
+            ui: |state, ui, cfg| {
+                Preferences::transition_combo(&state.tx, ui, cfg.renderer.transition);
+            },
+        },
+        Setting {
+            tab: Tab::Video,
+            label: "Screen Rotation",
+            keywords: "rotate mirror flip vertical tate",
+            feature: None,
+            ui: |state, ui, cfg| {
+                ui.horizontal(|ui| {
+                    Preferences::rotation_radio(&state.tx, ui, cfg.renderer.rotation);
+                });
+                Preferences::flip_horizontal_checkbox(&state.tx, ui, cfg.renderer.flip_horizontal);
+            },
+        },
+        Setting {
+            tab: Tab::Video,
+            label: "CRT Output",
+            keywords: "240p scanlines line doubling bfi black frame insertion oled motion blur",
+            feature: None,
+            ui: |state, ui, cfg| {
+                Preferences::scanlines_checkbox(&state.tx, ui, cfg.renderer.scanlines);
+                Preferences::black_frame_insertion_checkbox(
+                    &state.tx,
+                    ui,
+                    cfg.renderer.black_frame_insertion,
+                );
+            },
+        },
+        Setting {
+            tab: Tab::Video,
+            label: "Present Mode",
+            keywords: "vsync tearing",
+            feature: None,
+            ui: |state, ui, cfg| {
+                Preferences::present_mode_radio(
+                    &state.tx,
+                    ui,
+                    cfg.renderer.present_mode,
+                    &state.supported_present_modes,
+                );
+            },
+        },
+        Setting {
+            tab: Tab::Video,
+            label: "Fullscreen Monitor",
+            keywords: "display exclusive borderless refresh rate",
+            feature: None,
+            ui: |state, ui, cfg| {
+                Grid::new("search_fullscreen_target")
+                    .num_columns(2)
+                    .spacing([40.0, 6.0])
+                    .show(ui, |ui| {
+                        Preferences::fullscreen_target_ui(
+                            &state.tx,
+                            ui,
+                            &cfg.renderer.fullscreen_target,
+                            &state.monitors,
+                        );
+                    });
+            },
+        },
+        Setting {
+            tab: Tab::Video,
+            label: "Sync to Display Refresh Rate",
+            keywords: "vsync stutter hz",
+            feature: None,
+            ui: |state, ui, cfg| {
+                Self::sync_to_display_checkbox(&state.tx, ui, cfg.emulation.sync_to_display);
+            },
+        },
+        Setting {
+            tab: Tab::Input,
+            label: "Zapper",
+            keywords: "light gun sensor radius threshold latency",
+            feature: None,
+            ui: |state, ui, cfg| {
+                Preferences::zapper_checkbox(&state.tx, ui, cfg.deck.zapper, None);
+                ui.add_enabled_ui(cfg.deck.zapper, |ui| {
+                    Self::zapper_config_ui(&state.tx, ui, cfg.deck.zapper_config);
+                });
+            },
+        },
+        Setting {
+            tab: Tab::Input,
+            label: "Auto-Configure Peripherals",
+            keywords: "zapper four score",
+            feature: None,
+            ui: |state, ui, cfg| {
+                Self::auto_peripherals_checkbox(&state.tx, ui, cfg.deck.auto_peripherals);
+            },
+        },
+        Setting {
+            tab: Tab::Input,
+            label: "Concurrent D-Pad",
+            keywords: "dpad directions opposite",
+            feature: None,
+            ui: |state, ui, cfg| {
+                Self::concurrent_dpad_checkbox(&state.tx, ui, cfg.deck.concurrent_dpad);
+            },
+        },
+        Setting {
+            tab: Tab::Input,
+            label: "Arkanoid Paddle",
+            keywords: "vaus controller",
+            feature: None,
+            ui: |state, ui, cfg| Preferences::vaus_checkbox(&state.tx, ui, cfg.deck.vaus),
+        },
+        Setting {
+            tab: Tab::Accessibility,
+            label: "High Contrast Theme",
+            keywords: "colors",
+            feature: None,
+            ui: |state, ui, cfg| {
+                Preferences::high_contrast_checkbox(&state.tx, ui, cfg.renderer.high_contrast);
+            },
+        },
+        Setting {
+            tab: Tab::Accessibility,
+            label: "Screen Reader",
+            keywords: "accesskit narrator",
+            feature: Some(Feature::ScreenReader),
+            ui: |_, ui, _| Preferences::screen_reader_checkbox(ui, None),
+        },
+        Setting {
+            tab: Tab::Accessibility,
+            label: "Announce Messages",
+            keywords: "speak speech narrate text to speech tts blind",
+            feature: None,
+            ui: |state, ui, cfg| {
+                Preferences::announce_messages_checkbox(
+                    &state.tx,
+                    ui,
+                    cfg.renderer.announce_messages,
+                );
+            },
+        },
+        Setting {
+            tab: Tab::Accessibility,
+            label: "UI Zoom",
+            keywords: "scale size menus",
+            feature: None,
+            ui: |state, ui, cfg| Preferences::ui_zoom_slider(&state.tx, ui, cfg.renderer.zoom),
+        },
+        Setting {
+            tab: Tab::Accessibility,
+            label: "Font Size",
+            keywords: "text",
+            feature: None,
+            ui: |state, ui, cfg| {
+                Preferences::font_size_radio(&state.tx, ui, cfg.renderer.font_size)
+            },
+        },
+        Setting {
+            tab: Tab::Accessibility,
+            label: "Theme",
+            keywords: "dark light colors scheme",
+            feature: None,
+            ui: |state, ui, cfg| {
+                ui.add_enabled_ui(!cfg.renderer.high_contrast, |ui| {
+                    Preferences::theme_radio(&state.tx, ui, cfg.renderer.theme);
+                });
+            },
+        },
+        Setting {
+            tab: Tab::Accessibility,
+            label: "Accent Color",
+            keywords: "highlight theme",
+            feature: None,
+            ui: |state, ui, cfg| {
+                ui.add_enabled_ui(!cfg.renderer.high_contrast, |ui| {
+                    Preferences::accent_color_picker(&state.tx, ui, cfg.renderer.accent_color);
+                });
+            },
+        },
+        Setting {
+            tab: Tab::Accessibility,
+            label: "Color-blind Mode",
+            keywords: "colorblind deuteranopia protanopia tritanopia",
+            feature: None,
+            ui: |state, ui, cfg| {
+                Preferences::color_blind_mode_radio(&state.tx, ui, cfg.renderer.color_blind_mode);
+            },
+        },
+        Setting {
+            tab: Tab::Integrations,
+            label: "Check for Updates",
+            keywords: "privacy network online github releases changelog version",
+            feature: None,
+            ui: |state, ui, cfg| {
+                Preferences::update_check_radio(&state.tx, ui, cfg.renderer.update_check);
+                Self::last_update_check_label(ui, cfg.renderer.last_update_check);
+            },
+        },
+    ];
+
+    /// Shows the settings matching the search query across all tabs, grouped by tab with the
+    /// matching text highlighted.
+    fn search_results(&mut self, ui: &mut Ui, cfg: &Config) {
+        let terms = self
+            .search
+            .split_whitespace()
+            .map(str::to_lowercase)
+            .collect::<Vec<_>>();
+        let mut found = false;
+        for tab in Tab::ALL {
+            let settings = Self::SETTINGS
+                .iter()
+                .filter(|setting| setting.tab == tab && setting.available())
+                .filter(|setting| setting.matches(&terms))
+                .collect::<Vec<_>>();
+            if settings.is_empty() {
+                continue;
+            }
+            if found {
+                ui.separator();
+            }
+            found = true;
+
+            ui.horizontal(|ui| {
+                ui.heading(tab.label());
+                if ui.small_button("Show Tab").clicked() {
+                    self.tab = tab;
+                    self.search.clear();
+                }
+            });
+            for setting in settings {
+                ui.label(Self::highlight(ui, setting.label, &terms));
+                ui.indent(setting.label, |ui| (setting.ui)(self, ui, cfg));
+                ui.add_space(6.0);
+            }
+        }
+        if !found {
+            ui.label(format!("No settings found for \"{}\".", self.search.trim()));
+        }
+    }
+
+    /// Lays out `text` with any parts matching a search term highlighted.
+    fn highlight(ui: &Ui, text: &str, terms: &[String]) -> LayoutJob {
+        let lowercase = text.to_lowercase();
+        let mut highlighted = vec![false; text.len()];
+        if lowercase.len() == text.len() {
+            for term in terms {
+                for (start, _) in lowercase.match_indices(term.as_str()) {
+                    highlighted[start..start + term.len()].fill(true);
+                }
+            }
+        }
+
+        let font_id = TextStyle::Body.resolve(ui.style());
+        let normal = TextFormat::simple(font_id, ui.visuals().strong_text_color());
+        let matched = TextFormat {
+            background: ui.visuals().selection.bg_fill,
+            color: ui.visuals().selection.stroke.color,
+            ..normal.clone()
+        };
+        let mut job = LayoutJob::default();
+        let mut start = 0;
+        while start < text.len() {
+            let is_match = highlighted[start];
+            let end = highlighted[start..]
+                .iter()
+                .position(|&h| h != is_match)
+                .map_or(text.len(), |len| start + len);
+            let format = if is_match { &matched } else { &normal };
+            job.append(&text[start..end], 0.0, format.clone());
+            start = end;
+        }
+        job
+    }
+}
+
+impl State {
+    fn ui(&mut self, ui: &mut Ui, enabled: bool, cfg: &Config) {
+        #[cfg(feature = "profiling")]
+        puffin::profile_function!();
+
+        ui.add_enabled_ui(enabled, |ui| {
+            ui.set_min_height(ui.available_height());
+
+            ui.horizontal(|ui| {
+                let search = TextEdit::singleline(&mut self.search)
+                    .hint_text("🔍 Search settings")
+                    .desired_width(200.0);
+                ui.add(search)
+                    .on_hover_text("Find settings across all tabs by name or related terms.");
+                if !self.search.is_empty() && ui.button("✖").on_hover_text("Clear search").clicked()
+                {
+                    self.search.clear();
+                }
+            });
+
+            ui.add_enabled_ui(self.search.trim().is_empty(), |ui| {
+                ui.horizontal(|ui| {
+                    for tab in Tab::ALL {
+                        ui.selectable_value(&mut self.tab, tab, tab.label());
+                    }
+                });
+            });
+
+            ui.separator();
+
+            ScrollArea::both().show(ui, |ui| {
+                if !self.search.trim().is_empty() {
+                    self.search_results(ui, cfg);
+                } else {
+                    match self.tab {
+                        Tab::Emulation => self.emulation_tab(ui, cfg),
+                        Tab::Audio => {
+                            Self::audio_tab(&self.tx, ui, cfg, self.rom_name.as_deref());
+                        }
+                        Tab::Video => self.video_tab(ui, cfg),
+                        Tab::Input => Self::input_tab(&self.tx, ui, cfg),
+                        Tab::Accessibility => Self::accessibility_tab(&self.tx, ui, cfg),
+                        Tab::Integrations => Self::integrations_tab(&self.tx, ui, cfg),
+                    }
+                }
+
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    if ui.button("Restore Defaults").clicked() {
+                        Self::restore_defaults(&self.tx, ui.ctx());
+                    }
+
+                    if feature!(Storage) && ui.button("Clear Save States").clicked() {
+                        Self::clear_save_states(&self.tx, &cfg.paths);
+                    }
+
+                    if feature!(Filesystem) && ui.button("Clear Recent ROMs").clicked() {
+                        self.tx.event(ConfigEvent::RecentRomsClear);
+                    }
+
+                    #[cfg(target_arch = "wasm32")]
+                    if ui.button("Download Save States").clicked() {
+                        if let Err(err) = crate::platform::download_save_states() {
+                            self.tx
+                                .event(UiEvent::Message((MessageType::Error, err.to_string())));
+                        }
+                    }
+                });
+            });
+        });
+    }
+
+    fn emulation_tab(&mut self, ui: &mut Ui, cfg: &Config) {
+        #[cfg(feature = "profiling")]
+        puffin::profile_function!();
+
         let EmulationConfig {
-            mut auto_save,
-            auto_save_interval,
-            mut auto_load,
-            rewind,
-            mut rewind_interval,
-            mut rewind_seconds,
+            auto_load,
+            fast_forward_speed,
+            fast_forward_uncapped,
             run_ahead,
+            save_ring_len,
             save_slot,
+            screenshot_burst_frames,
+            replay_hash_interval,
             speed,
             ..
         } = cfg.emulation;
         let DeckConfig {
             cycle_accurate,
-            mut emulate_ppu_warmup,
+            emulate_ppu_warmup,
+            fast_boot,
             four_player,
             ram_state,
             region,
@@ -580,92 +1842,19 @@ impl State {
             let tx = &self.tx;
 
             Preferences::cycle_accurate_checkbox(tx, ui, cycle_accurate, None);
-            let res = ui.checkbox(&mut auto_load, "Auto-Load")
-                .on_hover_text("Automatically load game state from the current save slot on load.");
-            if res.changed() {
-                tx.event(ConfigEvent::AutoLoad(
-                    auto_load,
-                ));
-            }
+            Self::auto_load_checkbox(tx, ui, auto_load);
             ui.end_row();
 
-            ui.vertical(|ui| {
-                Preferences::rewind_checkbox(tx, ui, rewind, None);
-
-                ui.add_enabled_ui(rewind, |ui| {
-                    ui.indent("rewind_settings", |ui| {
-                        ui.horizontal(|ui| {
-                            let suffix = if rewind_seconds == 1 { " second" } else { " seconds" };
-                            let drag = DragValue::new(&mut rewind_seconds)
-                                .range(1..=360)
-                                .suffix(suffix);
-                            let res = ui.add(drag)
-                                .on_hover_text("The maximum number of seconds to rewind.");
-                            if res.changed() {
-                                tx.event(ConfigEvent::RewindSeconds(rewind_seconds));
-                            }
-                        });
-
-                        ui.horizontal(|ui| {
-                    let suffix = if rewind_interval == 1 { " frame" } else { " frames" };
-                            let drag = DragValue::new(&mut rewind_interval)
-                                .range(1..=60)
-                                .prefix("every ")
-                                .suffix(suffix);
-                            let res = ui.add(drag)
-                                .on_hover_text("The frame interval to save rewind states.");
-                            if res.changed() {
-                                tx.event(ConfigEvent::RewindInterval(rewind_interval));
-                            }
-                        });
-                    });
-                });
-            });
-
-            ui.vertical(|ui| {
-                let res = ui.checkbox(&mut auto_save, "Auto-Save")
-                    .on_hover_text(concat!(
-                        "Automatically save game state to the current save slot ",
-                        "on exit or unloading and an optional interval. ",
-                        "Setting to 0 will disable saving on an interval.",
-                    ));
-                if res.changed() {
-                    tx.event(ConfigEvent::AutoSave(
-                        auto_save,
-                    ));
-                }
+            ui.vertical(|ui| Self::rewind_settings(tx, ui, cfg));
+            ui.vertical(|ui| Self::auto_save_settings(tx, ui, cfg));
+            ui.end_row();
 
-                ui.add_enabled_ui(auto_save, |ui| {
-                    ui.indent("auto_save_settings", |ui| {
-                        ui.horizontal(|ui| {
-                            let mut auto_save_interval = auto_save_interval.as_secs();
-                            let suffix = if auto_save_interval == 1 { " second" } else { " seconds" };
-                            let drag = DragValue::new(&mut auto_save_interval)
-                                .range(0..=60)
-                                .prefix("every ")
-                                .suffix(suffix);
-                            let res = ui.add(drag)
-                                .on_hover_text(concat!(
-                                    "Set the interval to auto-save game state. ",
-                                    "A value of `0` will still save on exit or unload while Auto-Save is enabled."
-                                ));
-                            if res.changed() {
-                                tx.event(ConfigEvent::AutoSaveInterval(Duration::from_secs(auto_save_interval)));
-                            }
-                        });
-                    });
-                });
-            });
+            Self::emulate_ppu_warmup_checkbox(tx, ui, emulate_ppu_warmup);
+            Self::fast_boot_checkbox(tx, ui, fast_boot);
             ui.end_row();
 
-            let res = ui.checkbox(&mut emulate_ppu_warmup, "Emulate PPU Warmup")
-                .on_hover_text(concat!(
-                    "Set whether to emulate PPU warmup where writes to certain registers are ignored. ",
-                    "Can result in some games not working correctly"
-                ));
-            if res.clicked() {
-                tx.event(EmulationEvent::EmulatePpuWarmup(emulate_ppu_warmup));
-            }
+            Preferences::mapper_stats_checkbox(tx, ui, cfg.renderer.mapper_stats);
+            Self::attract_mode_drag(tx, ui, cfg.renderer.attract_mode_minutes);
             ui.end_row();
         });
 
@@ -685,6 +1874,19 @@ impl State {
             });
             ui.end_row();
 
+            ui.horizontal(|ui| {
+                ui.add_enabled_ui(!fast_forward_uncapped, |ui| {
+                    Preferences::fast_forward_slider(tx, ui, fast_forward_speed);
+                });
+                ui.label("Fast Forward Speed")
+                    .on_hover_cursor(CursorIcon::Help)
+                    .on_hover_text("Change the speed of the emulation while fast forwarding.");
+            });
+            ui.end_row();
+
+            Self::fast_forward_settings(tx, ui, cfg);
+            ui.end_row();
+
             ui.horizontal(|ui| {
                 Preferences::run_ahead_slider(tx, ui, run_ahead);
                 ui.label("Run Ahead")
@@ -694,6 +1896,17 @@ impl State {
                     );
             });
             ui.end_row();
+
+            Self::save_ring_drag(tx, ui, save_ring_len);
+            ui.end_row();
+
+            if feature!(Filesystem) {
+                Self::screenshot_burst_drag(tx, ui, screenshot_burst_frames);
+                ui.end_row();
+            }
+
+            Self::replay_hash_drag(tx, ui, replay_hash_interval);
+            ui.end_row();
         });
 
         ui.separator();
@@ -713,7 +1926,7 @@ impl State {
                 .num_columns(2)
                 .spacing([20.0, 6.0])
                 .show(ui, |ui| {
-                    Preferences::save_slot_radio(tx, ui, save_slot, cfg, ShowShortcut::No)
+                    Preferences::save_slot_radio(tx, ui, save_slot, cfg, ShowShortcut::No);
                 });
 
             ui.with_layout(Layout::left_to_right(Align::Min), |ui| {
@@ -747,120 +1960,644 @@ impl State {
             self.genie_codes_entry(ui, cfg);
             Preferences::genie_codes_list(&self.tx, ui, cfg, false);
         });
-    }
-
-    fn audio_tab(tx: &NesEventProxy, ui: &mut Ui, cfg: &Config) {
-        #[cfg(feature = "profiling")]
-        puffin::profile_function!();
 
-        let AudioConfig {
-            latency,
-            mut buffer_size,
-            mut enabled,
-        } = cfg.audio;
-        let DeckConfig {
-            channels_enabled, ..
-        } = cfg.deck;
+        if feature!(Filesystem) {
+            ui.separator();
+            self.directories(ui, cfg);
+            Self::watch_roms_checkbox(&self.tx, ui, cfg.renderer.watch_roms_path);
+        }
+    }
 
-        let res = ui.checkbox(&mut enabled, "Enable Audio");
-        if res.clicked() {
-            tx.event(ConfigEvent::AudioEnabled(enabled));
+    fn auto_load_checkbox(tx: &NesEventProxy, ui: &mut Ui, mut auto_load: bool) {
+        let res = ui
+            .checkbox(&mut auto_load, "Auto-Load")
+            .on_hover_text("Automatically load game state from the current save slot on load.");
+        if res.changed() {
+            tx.event(ConfigEvent::AutoLoad(auto_load));
         }
+    }
 
-        ui.add_enabled_ui(cfg.audio.enabled, |ui| {
-            ui.indent("apu_channels", |ui| {
-                Grid::new("apu_channels")
-                    .spacing([60.0, 6.0])
-                    .num_columns(2)
-                    .show(ui, |ui| {
+    fn rewind_settings(tx: &NesEventProxy, ui: &mut Ui, cfg: &Config) {
+        let EmulationConfig {
+            rewind,
+            mut rewind_interval,
+            mut rewind_seconds,
+            ..
+        } = cfg.emulation;
 
-                        let mut pulse1_enabled = channels_enabled[0];
-                        if ui.checkbox(&mut pulse1_enabled, "Enable Pulse1").clicked() {
-                            tx.event(ConfigEvent::ApuChannelEnabled((Channel::Pulse1, pulse1_enabled)));
-                        }
-                        let mut noise_enabled = channels_enabled[3];
-                        if ui.checkbox(&mut noise_enabled, "Enable Noise").clicked() {
-                            tx.event(ConfigEvent::ApuChannelEnabled((Channel::Noise, noise_enabled)));
-                        }
+        Preferences::rewind_checkbox(tx, ui, rewind, None);
+
+        ui.add_enabled_ui(rewind, |ui| {
+            ui.indent("rewind_settings", |ui| {
+                ui.horizontal(|ui| {
+                    let suffix = if rewind_seconds == 1 {
+                        " second"
+                    } else {
+                        " seconds"
+                    };
+                    let drag = DragValue::new(&mut rewind_seconds)
+                        .range(1..=360)
+                        .suffix(suffix);
+                    let res = ui
+                        .add(drag)
+                        .on_hover_text("The maximum number of seconds to rewind.");
+                    if res.changed() {
+                        tx.event(ConfigEvent::RewindSeconds(rewind_seconds));
+                    }
+                });
+
+                ui.horizontal(|ui| {
+                    let suffix = if rewind_interval == 1 {
+                        " frame"
+                    } else {
+                        " frames"
+                    };
+                    let drag = DragValue::new(&mut rewind_interval)
+                        .range(1..=60)
+                        .prefix("every ")
+                        .suffix(suffix);
+                    let res = ui
+                        .add(drag)
+                        .on_hover_text("The frame interval to save rewind states.");
+                    if res.changed() {
+                        tx.event(ConfigEvent::RewindInterval(rewind_interval));
+                    }
+                });
+            });
+        });
+    }
+
+    fn auto_save_settings(tx: &NesEventProxy, ui: &mut Ui, cfg: &Config) {
+        let EmulationConfig {
+            mut auto_save,
+            auto_save_interval,
+            ..
+        } = cfg.emulation;
+
+        let res = ui
+            .checkbox(&mut auto_save, "Auto-Save")
+            .on_hover_text(concat!(
+                "Automatically save game state to the current save slot ",
+                "on exit or unloading and an optional interval. ",
+                "Setting to 0 will disable saving on an interval.",
+            ));
+        if res.changed() {
+            tx.event(ConfigEvent::AutoSave(auto_save));
+        }
+
+        ui.add_enabled_ui(auto_save, |ui| {
+            ui.indent("auto_save_settings", |ui| {
+                ui.horizontal(|ui| {
+                    let mut auto_save_interval = auto_save_interval.as_secs();
+                    let suffix = if auto_save_interval == 1 { " second" } else { " seconds" };
+                    let drag = DragValue::new(&mut auto_save_interval)
+                        .range(0..=60)
+                        .prefix("every ")
+                        .suffix(suffix);
+                    let res = ui.add(drag).on_hover_text(concat!(
+                        "Set the interval to auto-save game state. ",
+                        "A value of `0` will still save on exit or unload while Auto-Save is enabled."
+                    ));
+                    if res.changed() {
+                        tx.event(ConfigEvent::AutoSaveInterval(Duration::from_secs(
+                            auto_save_interval,
+                        )));
+                    }
+                });
+            });
+        });
+    }
+
+    fn emulate_ppu_warmup_checkbox(tx: &NesEventProxy, ui: &mut Ui, mut emulate_ppu_warmup: bool) {
+        let res = ui
+            .checkbox(&mut emulate_ppu_warmup, "Emulate PPU Warmup")
+            .on_hover_text(concat!(
+                "Set whether to emulate PPU warmup where writes to certain registers are ignored. ",
+                "Can result in some games not working correctly"
+            ));
+        if res.clicked() {
+            tx.event(EmulationEvent::EmulatePpuWarmup(emulate_ppu_warmup));
+        }
+    }
+
+    fn fast_boot_checkbox(tx: &NesEventProxy, ui: &mut Ui, mut fast_boot: bool) {
+        let res = ui.checkbox(&mut fast_boot, "Fast Boot").on_hover_text(concat!(
+            "Skip the initial power-on frames where games wait for the PPU to warm up and clear RAM. ",
+            "Affects accuracy, as any input during the skipped frames is lost."
+        ));
+        if res.clicked() {
+            tx.event(ConfigEvent::FastBoot(fast_boot));
+        }
+    }
+
+    fn attract_mode_drag(tx: &NesEventProxy, ui: &mut Ui, mut attract_mode_minutes: u32) {
+        ui.horizontal(|ui| {
+            ui.label("Attract Mode:");
+            let suffix = if attract_mode_minutes == 1 { " minute" } else { " minutes" };
+            let drag = DragValue::new(&mut attract_mode_minutes)
+                .range(0..=60)
+                .prefix("after ")
+                .suffix(suffix);
+            let res = ui.add(drag).on_hover_text(concat!(
+                "Play a random recent ROM and its latest replay after being idle on the ROM library for this long. ",
+                "Any input returns to the library. A value of `0` disables attract mode."
+            ));
+            if res.changed() {
+                tx.event(ConfigEvent::AttractModeMinutes(attract_mode_minutes));
+            }
+        });
+    }
+
+    fn fast_forward_settings(tx: &NesEventProxy, ui: &mut Ui, cfg: &Config) {
+        let EmulationConfig {
+            mut fast_forward_frame_skip,
+            mut fast_forward_toggle,
+            mut fast_forward_uncapped,
+            ..
+        } = cfg.emulation;
+
+        ui.horizontal(|ui| {
+            let res = ui
+                .checkbox(&mut fast_forward_uncapped, "Uncapped")
+                .on_hover_text("Run as fast as possible while fast forwarding. Audio is muted.");
+            if res.changed() {
+                tx.event(ConfigEvent::FastForwardUncapped(fast_forward_uncapped));
+            }
+
+            let res = ui
+                .checkbox(&mut fast_forward_toggle, "Toggle")
+                .on_hover_text("Press once to start or stop fast forwarding instead of holding.");
+            if res.changed() {
+                tx.event(ConfigEvent::FastForwardToggle(fast_forward_toggle));
+            }
+
+            let suffix = if fast_forward_frame_skip == 1 {
+                " frame"
+            } else {
+                " frames"
+            };
+            let drag = DragValue::new(&mut fast_forward_frame_skip)
+                .range(0..=10)
+                .prefix("skip ")
+                .suffix(suffix);
+            let res = ui
+                .add(drag)
+                .on_hover_text("The number of frames to skip rendering while fast forwarding.");
+            if res.changed() {
+                tx.event(ConfigEvent::FastForwardFrameSkip(fast_forward_frame_skip));
+            }
+        });
+    }
+
+    fn save_ring_drag(tx: &NesEventProxy, ui: &mut Ui, mut save_ring_len: usize) {
+        ui.horizontal(|ui| {
+            let suffix = if save_ring_len == 1 {
+                " state"
+            } else {
+                " states"
+            };
+            let drag = DragValue::new(&mut save_ring_len)
+                .range(1..=20)
+                .suffix(suffix);
+            if ui.add(drag).changed() {
+                tx.event(ConfigEvent::SaveRingLen(save_ring_len));
+            }
+            ui.label("Quick Save Ring")
+                .on_hover_cursor(CursorIcon::Help)
+                .on_hover_text(concat!(
+                    "The number of quick save states to keep, separate from the save slots. ",
+                    "Once full, quick saving replaces the oldest state."
+                ));
+        });
+    }
+
+    fn replay_hash_drag(tx: &NesEventProxy, ui: &mut Ui, mut replay_hash_interval: u32) {
+        ui.horizontal(|ui| {
+            let suffix = if replay_hash_interval == 1 {
+                " frame"
+            } else {
+                " frames"
+            };
+            let drag = DragValue::new(&mut replay_hash_interval)
+                .range(1..=600)
+                .prefix("every ")
+                .suffix(suffix);
+            if ui.add(drag).changed() {
+                tx.event(ConfigEvent::ReplayHashInterval(replay_hash_interval));
+            }
+            ui.label("Replay Hash Interval")
+                .on_hover_cursor(CursorIcon::Help)
+                .on_hover_text(concat!(
+                    "How often replay recordings save a hash of the emulation state, used to detect ",
+                    "when playback no longer matches the recording. Lower intervals find desyncs ",
+                    "sooner but cost more while recording."
+                ));
+        });
+    }
+
+    fn screenshot_burst_drag(tx: &NesEventProxy, ui: &mut Ui, mut screenshot_burst_frames: u32) {
+        ui.horizontal(|ui| {
+            let suffix = if screenshot_burst_frames == 1 {
+                " frame"
+            } else {
+                " frames"
+            };
+            let drag = DragValue::new(&mut screenshot_burst_frames)
+                .range(0..=ScreenshotBurst::MAX_FRAMES)
+                .suffix(suffix);
+            if ui.add(drag).changed() {
+                tx.event(ConfigEvent::ScreenshotBurstFrames(screenshot_burst_frames));
+            }
+            ui.label("Screenshot Burst")
+                .on_hover_cursor(CursorIcon::Help)
+                .on_hover_text(concat!(
+                    "The number of frames saved as numbered screenshots by the Screenshot Burst key. ",
+                    "A value of `0` saves frames for as long as the key is held."
+                ));
+        });
+    }
+
+    fn directories(&mut self, ui: &mut Ui, cfg: &Config) {
+        ui.strong("Directories:")
+            .on_hover_cursor(CursorIcon::Help)
+            .on_hover_text(
+                "Where files created while playing are stored. Leave blank to use the default.\n\n\
+                Save states and Save RAM are mirrored to the Sync Folder if set, such as a \
+                Dropbox or Syncthing folder shared between devices.",
+            );
+        let grid = Grid::new("directories").num_columns(2).spacing([40.0, 6.0]);
+        grid.show(ui, |ui| {
+            for kind in PathKind::ALL {
+                self.path_entry(ui, cfg, kind);
+                ui.end_row();
+            }
+        });
+    }
+
+    fn watch_roms_checkbox(tx: &NesEventProxy, ui: &mut Ui, mut watch_roms_path: bool) {
+        let res = ui
+            .checkbox(&mut watch_roms_path, "Watch ROM Directory")
+            .on_hover_text(concat!(
+                "Refresh the ROM library and recently played ROMs as ROM files are added or ",
+                "removed. Disable to avoid polling slow network drives."
+            ));
+        if res.changed() {
+            tx.event(ConfigEvent::WatchRomsPath(watch_roms_path));
+        }
+    }
+
+    fn path_entry(&mut self, ui: &mut Ui, cfg: &Config, kind: PathKind) {
+        let current = kind.path(&cfg.paths);
+        let set_path = |path: Option<PathBuf>| {
+            if path != *current {
+                let mut paths = cfg.paths.clone();
+                *kind.path_mut(&mut paths) = path;
+                self.tx.event(ConfigEvent::Paths(paths));
+            }
+        };
+
+        ui.label(format!("{}:", kind.label()));
+        ui.horizontal(|ui| {
+            let mut text = match &self.path_entry {
+                Some((entry_kind, text)) if *entry_kind == kind => text.clone(),
+                _ => current
+                    .as_ref()
+                    .map(|path| path.display().to_string())
+                    .unwrap_or_default(),
+            };
+            let res = ui.add(
+                TextEdit::singleline(&mut text)
+                    .hint_text(kind.hint(&cfg.paths))
+                    .desired_width(320.0),
+            );
+            if res.gained_focus() || res.changed() {
+                self.path_entry = Some((kind, text.clone()));
+            }
+            if res.lost_focus() {
+                self.path_entry = None;
+                let text = text.trim();
+                set_path((!text.is_empty()).then(|| PathBuf::from(text)));
+            }
+
+            let res = ui
+                .button("📂")
+                .on_hover_text(format!("Select the {} directory.", kind.label()));
+            if res.clicked() {
+                match open_dir_dialog(
+                    format!("Select {} Directory", kind.label()),
+                    Some(kind.dir(&cfg.paths)),
+                ) {
+                    Ok(Some(dir)) => set_path(Some(dir)),
+                    Ok(None) => (),
+                    Err(err) => {
+                        error!("failed to open directory dialog: {err:?}");
+                        self.tx.event(UiEvent::Error(
+                            "failed to open directory dialog".to_string(),
+                        ));
+                    }
+                }
+            }
+
+            let res = ui
+                .add_enabled(current.is_some(), Button::new("↺"))
+                .on_hover_text("Reset to the default directory.");
+            if res.clicked() {
+                set_path(None);
+            }
+        });
+    }
+
+    fn audio_tab(tx: &NesEventProxy, ui: &mut Ui, cfg: &Config, rom_name: Option<&str>) {
+        #[cfg(feature = "profiling")]
+        puffin::profile_function!();
+
+        Self::audio_enabled_checkbox(tx, ui, cfg.audio.enabled);
+
+        ui.add_enabled_ui(cfg.audio.enabled, |ui| {
+            ui.indent("apu_channels", |ui| {
+                Self::apu_channels(tx, ui, cfg.deck.channels_enabled);
+
+                ui.separator();
+
+                Grid::new("audio_settings")
+                    .spacing([40.0, 6.0])
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        Self::audio_buffer_drag(tx, ui, cfg.audio.buffer_size);
                         ui.end_row();
 
-                        let mut pulse1_enabled = channels_enabled[1];
-                        if ui.checkbox(&mut pulse1_enabled, "Enable Pulse2").clicked() {
-                            tx.event(ConfigEvent::ApuChannelEnabled((Channel::Pulse2, pulse1_enabled)));
-                        }
-                        let mut dmc_enabled = channels_enabled[4];
-                        if ui.checkbox(&mut dmc_enabled, "Enable DMC").clicked() {
-                            tx.event(ConfigEvent::ApuChannelEnabled((Channel::Dmc, dmc_enabled)));
-                        }
+                        Self::audio_latency_drag(tx, ui, cfg.audio.latency);
                         ui.end_row();
 
-                        let mut triangle_enabled = channels_enabled[2];
-                        if ui.checkbox(&mut triangle_enabled, "Enable Triangle").clicked() {
-                            tx.event(ConfigEvent::ApuChannelEnabled((Channel::Triangle, triangle_enabled)));
-                        }
-                        let mut mapper_enabled = channels_enabled[5];
-                        if ui.checkbox(&mut mapper_enabled, "Enable Mapper").clicked() {
-                            tx.event(ConfigEvent::ApuChannelEnabled((Channel::Mapper, mapper_enabled)));
+                        Self::audio_backend_combo(tx, ui, cfg.audio.backend);
+                        ui.end_row();
+
+                        if feature!(Filesystem) {
+                            Self::recording_limits_drags(tx, ui, cfg.audio.recording_limits);
+                            ui.end_row();
                         }
+
+                        Self::dmc_click_reduction_checkbox(tx, ui, cfg, rom_name);
                         ui.end_row();
                     });
 
                 ui.separator();
 
-                Grid::new("audio_settings")
+                Self::stereo_settings(tx, ui, cfg.deck.stereo);
+            });
+        });
+    }
+
+    fn audio_enabled_checkbox(tx: &NesEventProxy, ui: &mut Ui, mut enabled: bool) {
+        let res = ui.checkbox(&mut enabled, "Enable Audio");
+        if res.clicked() {
+            tx.event(ConfigEvent::AudioEnabled(enabled));
+        }
+    }
+
+    fn apu_channels(tx: &NesEventProxy, ui: &mut Ui, channels_enabled: [bool; 6]) {
+        Grid::new("apu_channels")
+            .spacing([60.0, 6.0])
+            .num_columns(2)
+            .show(ui, |ui| {
+                let mut pulse1_enabled = channels_enabled[0];
+                if ui.checkbox(&mut pulse1_enabled, "Enable Pulse1").clicked() {
+                    tx.event(ConfigEvent::ApuChannelEnabled((
+                        Channel::Pulse1,
+                        pulse1_enabled,
+                    )));
+                }
+                let mut noise_enabled = channels_enabled[3];
+                if ui.checkbox(&mut noise_enabled, "Enable Noise").clicked() {
+                    tx.event(ConfigEvent::ApuChannelEnabled((
+                        Channel::Noise,
+                        noise_enabled,
+                    )));
+                }
+                ui.end_row();
+
+                let mut pulse1_enabled = channels_enabled[1];
+                if ui.checkbox(&mut pulse1_enabled, "Enable Pulse2").clicked() {
+                    tx.event(ConfigEvent::ApuChannelEnabled((
+                        Channel::Pulse2,
+                        pulse1_enabled,
+                    )));
+                }
+                let mut dmc_enabled = channels_enabled[4];
+                if ui.checkbox(&mut dmc_enabled, "Enable DMC").clicked() {
+                    tx.event(ConfigEvent::ApuChannelEnabled((Channel::Dmc, dmc_enabled)));
+                }
+                ui.end_row();
+
+                let mut triangle_enabled = channels_enabled[2];
+                if ui
+                    .checkbox(&mut triangle_enabled, "Enable Triangle")
+                    .clicked()
+                {
+                    tx.event(ConfigEvent::ApuChannelEnabled((
+                        Channel::Triangle,
+                        triangle_enabled,
+                    )));
+                }
+                let mut mapper_enabled = channels_enabled[5];
+                if ui.checkbox(&mut mapper_enabled, "Enable Mapper").clicked() {
+                    tx.event(ConfigEvent::ApuChannelEnabled((
+                        Channel::Mapper,
+                        mapper_enabled,
+                    )));
+                }
+                ui.end_row();
+            });
+    }
+
+    fn audio_buffer_drag(tx: &NesEventProxy, ui: &mut Ui, mut buffer_size: usize) {
+        ui.horizontal(|ui| {
+            let drag = DragValue::new(&mut buffer_size)
+                .speed(10)
+                .range(128..=8192)
+                .prefix("buffer ")
+                .suffix(" samples");
+            let res = ui.add(drag).on_hover_text(
+                "The audio sample buffer size allocated to the sound driver. Increased audio buffer size can help reduce audio underruns.",
+            );
+            if res.changed() {
+                tx.event(ConfigEvent::AudioBuffer(buffer_size));
+            }
+        });
+    }
+
+    fn audio_latency_drag(tx: &NesEventProxy, ui: &mut Ui, latency: Duration) {
+        ui.horizontal(|ui| {
+            let mut latency = latency.as_millis() as u64;
+            let drag = DragValue::new(&mut latency)
+                .range(1..=1000)
+                .suffix(" ms latency");
+            let res = ui.add(drag).on_hover_text(
+                "The amount of queued audio before sending to the sound driver. Increased audio latency can help reduce audio underruns.",
+            );
+            if res.changed() {
+                tx.event(ConfigEvent::AudioLatency(Duration::from_millis(latency)));
+            }
+        });
+    }
+
+    fn audio_backend_combo(tx: &NesEventProxy, ui: &mut Ui, current: AudioBackend) {
+        ui.horizontal(|ui| {
+            ui.label("Backend:")
+                .on_hover_cursor(CursorIcon::Help)
+                .on_hover_text(
+                    "The audio host used for output. Low latency hosts bypass the system mixer, which adds 20ms or more of latency, but require JACK on Linux or an ASIO driver on Windows.",
+                );
+            let mut backend = current;
+            ComboBox::from_id_salt("audio_backend")
+                .selected_text(backend.as_ref())
+                .show_ui(ui, |ui| {
+                    for option in AudioBackend::as_slice() {
+                        ui.selectable_value(&mut backend, *option, option.as_ref());
+                    }
+                });
+            if backend != current {
+                tx.event(ConfigEvent::AudioBackend(backend));
+            }
+        });
+    }
+
+    fn recording_limits_drags(tx: &NesEventProxy, ui: &mut Ui, limits: RecordingLimits) {
+        ui.horizontal(|ui| {
+            ui.label("Split Recordings:");
+            let mut new_limits = limits;
+            let drag = DragValue::new(&mut new_limits.max_minutes)
+                .range(0..=600)
+                .prefix("every ")
+                .suffix(" min");
+            ui.add(drag).on_hover_text(concat!(
+                "Start a new audio recording file once the current one is this long. ",
+                "A value of `0` disables splitting by length."
+            ));
+            let drag = DragValue::new(&mut new_limits.max_megabytes)
+                .speed(10)
+                .range(0..=4000)
+                .prefix("or ")
+                .suffix(" MB");
+            ui.add(drag).on_hover_text(concat!(
+                "Start a new audio recording file once the current one is this large. ",
+                "A value of `0` disables splitting by size."
+            ));
+            if new_limits != limits {
+                tx.event(ConfigEvent::AudioRecordingLimits(new_limits));
+            }
+        });
+    }
+
+    fn dmc_click_reduction_checkbox(
+        tx: &NesEventProxy,
+        ui: &mut Ui,
+        cfg: &Config,
+        rom_name: Option<&str>,
+    ) {
+        ui.add_enabled_ui(rom_name.is_some(), |ui| {
+            let mut dmc_click_reduction =
+                rom_name.is_some_and(|name| cfg.audio.dmc_click_reduction.contains(name));
+            let res = ui
+                .checkbox(&mut dmc_click_reduction, "Reduce DMC Pops For This Game")
+                .on_hover_text(concat!(
+                    "Smooth out audible pops when a game writes directly to the DMC output level. ",
+                    "Can muffle games that play voice samples that way."
+                ))
+                .on_disabled_hover_text("Load a ROM to change this setting for that game.");
+            if res.clicked() {
+                if let Some(name) = rom_name {
+                    tx.event(ConfigEvent::DmcClickReduction((
+                        name.to_string(),
+                        dmc_click_reduction,
+                    )));
+                }
+            }
+        });
+    }
+
+    fn stereo_settings(tx: &NesEventProxy, ui: &mut Ui, stereo: StereoConfig) {
+        let mut config = stereo;
+
+        ui.horizontal(|ui| {
+            ui.label("Stereo:")
+                .on_hover_cursor(CursorIcon::Help)
+                .on_hover_text(concat!(
+                    "How APU channels are mixed for output. Panned places each channel at its own position, ",
+                    "Pseudo-Stereo widens the mono mix with a short delay, and Headphones is Panned with some ",
+                    "of each side mixed into the other."
+                ));
+            ComboBox::from_id_salt("stereo_mode")
+                .selected_text(config.mode.as_ref())
+                .show_ui(ui, |ui| {
+                    for option in StereoMode::as_slice() {
+                        ui.selectable_value(&mut config.mode, *option, option.as_ref());
+                    }
+                });
+        });
+
+        match config.mode {
+            StereoMode::Mono => (),
+            StereoMode::Panned | StereoMode::Headphones => {
+                Grid::new("stereo_pan")
                     .spacing([40.0, 6.0])
                     .num_columns(2)
                     .show(ui, |ui| {
-                        ui.horizontal(|ui| {
-                            let drag = DragValue::new(&mut buffer_size)
-                                .speed(10)
-                                .range(128..=8192)
-                                .prefix("buffer ")
-                                .suffix(" samples");
-                            let res = ui.add(drag)
-                                .on_hover_text(
-                                    "The audio sample buffer size allocated to the sound driver. Increased audio buffer size can help reduce audio underruns.",
-                                );
-                            if res.changed() {
-                                tx.event(ConfigEvent::AudioBuffer(buffer_size));
-                            }
-                        });
+                        let channels = ["Pulse1", "Pulse2", "Triangle", "Noise", "DMC", "Mapper"];
+                        for (channel, pan) in channels.into_iter().zip(&mut config.pan) {
+                            ui.label(format!("{channel} Pan:"));
+                            ui.add(Slider::new(pan, -1.0..=1.0).step_by(0.05));
+                            ui.end_row();
+                        }
+                    });
+            }
+            StereoMode::PseudoStereo => {
+                Grid::new("pseudo_stereo")
+                    .spacing([40.0, 6.0])
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        ui.label("Delay:");
+                        ui.add(Slider::new(&mut config.delay_ms, 1.0..=50.0).suffix(" ms"));
                         ui.end_row();
 
-                        ui.horizontal(|ui| {
-                            let mut latency = latency.as_millis() as u64;
-                            let drag = DragValue::new(&mut latency)
-                                .range(1..=1000)
-                                .suffix(" ms latency");
-                            let res = ui.add(drag)
-                                .on_hover_text(
-                                    "The amount of queued audio before sending to the sound driver. Increased audio latency can help reduce audio underruns.",
-                                );
-                            if res.changed() {
-                                tx.event(ConfigEvent::AudioLatency(Duration::from_millis(latency)));
-                            }
-                    });
+                        ui.label("Depth:");
+                        ui.add(Slider::new(&mut config.depth, 0.0..=1.0).step_by(0.05));
                         ui.end_row();
                     });
-            });
-        });
+            }
+        }
+
+        if config != stereo {
+            tx.event(ConfigEvent::Stereo(config));
+        }
     }
 
-    fn video_tab(tx: &NesEventProxy, ui: &mut Ui, cfg: &Config) {
+    fn video_tab(&self, ui: &mut Ui, cfg: &Config) {
         #[cfg(feature = "profiling")]
         puffin::profile_function!();
 
+        let tx = &self.tx;
         let RendererConfig {
             always_on_top,
             fullscreen,
             hide_overscan,
+            present_mode,
+            rotation,
+            flip_horizontal,
             scale,
             shader,
             show_menubar,
             show_messages,
+            texture_filter,
+            transition,
+            upscale_filter,
             ..
         } = cfg.renderer;
-        let DeckConfig { filter, .. } = cfg.deck;
+        let DeckConfig {
+            filter,
+            sprite_limit,
+            ..
+        } = cfg.deck;
 
         Grid::new("video_checkboxes")
             .spacing([80.0, 6.0])
@@ -877,6 +2614,17 @@ impl State {
                 Preferences::overscan_checkbox(tx, ui, hide_overscan, None);
                 Preferences::always_on_top_checkbox(tx, ui, always_on_top, None);
                 ui.end_row();
+
+                Preferences::sprite_limit_checkbox(tx, ui, sprite_limit);
+                ui.end_row();
+
+                Preferences::scanlines_checkbox(tx, ui, cfg.renderer.scanlines);
+                Preferences::black_frame_insertion_checkbox(
+                    tx,
+                    ui,
+                    cfg.renderer.black_frame_insertion,
+                );
+                ui.end_row();
             });
 
         ui.separator();
@@ -902,11 +2650,194 @@ impl State {
                 ui.vertical(|ui| Preferences::video_filter_radio(tx, ui, filter));
                 ui.end_row();
 
+                ui.with_layout(Layout::left_to_right(Align::Min), |ui| {
+                    ui.strong("Texture Filter:");
+                });
+                ui.vertical(|ui| Preferences::texture_filter_radio(tx, ui, texture_filter));
+                ui.end_row();
+
+                ui.with_layout(Layout::left_to_right(Align::Min), |ui| {
+                    ui.strong("Upscale Filter:");
+                });
+                Preferences::upscale_filter_combo(tx, ui, upscale_filter);
+                ui.end_row();
+
                 ui.with_layout(Layout::left_to_right(Align::Min), |ui| {
                     ui.strong("Shader:");
                 });
                 ui.vertical(|ui| Preferences::shader_radio(tx, ui, shader));
+                ui.end_row();
+
+                ui.with_layout(Layout::left_to_right(Align::Min), |ui| {
+                    ui.strong("Transition:");
+                });
+                Preferences::transition_combo(tx, ui, transition);
+                ui.end_row();
+
+                ui.with_layout(Layout::left_to_right(Align::Min), |ui| {
+                    ui.strong("Screen Rotation:");
+                });
+                ui.vertical(|ui| {
+                    ui.horizontal(|ui| Preferences::rotation_radio(tx, ui, rotation));
+                    Preferences::flip_horizontal_checkbox(tx, ui, flip_horizontal);
+                });
+                ui.end_row();
+
+                ui.with_layout(Layout::left_to_right(Align::Min), |ui| {
+                    ui.strong("Present Mode:");
+                });
+                ui.vertical(|ui| {
+                    Preferences::present_mode_radio(
+                        tx,
+                        ui,
+                        present_mode,
+                        &self.supported_present_modes,
+                    );
+                });
+                ui.end_row();
+
+                Preferences::fullscreen_target_ui(
+                    tx,
+                    ui,
+                    &cfg.renderer.fullscreen_target,
+                    &self.monitors,
+                );
             });
+
+        ui.separator();
+
+        Self::sync_to_display_checkbox(tx, ui, cfg.emulation.sync_to_display);
+    }
+
+    fn sync_to_display_checkbox(tx: &NesEventProxy, ui: &mut Ui, mut sync_to_display: bool) {
+        let res = ui
+            .checkbox(&mut sync_to_display, "Sync to Display Refresh Rate")
+            .on_hover_text(concat!(
+                "Slightly adjust emulation speed to match the display refresh rate when it's close ",
+                "to a multiple of the NES frame rate (e.g. 59.94 Hz or 120 Hz) to reduce stutter.",
+            ));
+        if res.changed() {
+            tx.event(ConfigEvent::SyncToDisplay(sync_to_display));
+        }
+    }
+
+    fn accessibility_tab(tx: &NesEventProxy, ui: &mut Ui, cfg: &Config) {
+        #[cfg(feature = "profiling")]
+        puffin::profile_function!();
+
+        let RendererConfig {
+            zoom,
+            font_size,
+            high_contrast,
+            announce_messages,
+            theme,
+            accent_color,
+            color_blind_mode,
+            ..
+        } = cfg.renderer;
+
+        Grid::new("accessibility_checkboxes")
+            .num_columns(2)
+            .spacing([80.0, 6.0])
+            .show(ui, |ui| {
+                Preferences::high_contrast_checkbox(tx, ui, high_contrast);
+                Preferences::announce_messages_checkbox(tx, ui, announce_messages);
+                ui.end_row();
+
+                if feature!(ScreenReader) {
+                    Preferences::screen_reader_checkbox(ui, None);
+                    ui.end_row();
+                }
+            });
+
+        ui.separator();
+
+        Grid::new("accessibility_preferences")
+            .num_columns(2)
+            .spacing([40.0, 6.0])
+            .show(ui, |ui| {
+                ui.with_layout(Layout::left_to_right(Align::Min), |ui| {
+                    ui.strong("UI Zoom:");
+                });
+                Preferences::ui_zoom_slider(tx, ui, zoom);
+                ui.end_row();
+
+                ui.with_layout(Layout::left_to_right(Align::Min), |ui| {
+                    ui.strong("Font Size:");
+                });
+                ui.vertical(|ui| Preferences::font_size_radio(tx, ui, font_size));
+                ui.end_row();
+
+                ui.with_layout(Layout::left_to_right(Align::Min), |ui| {
+                    ui.strong("Theme:")
+                        .on_hover_cursor(CursorIcon::Help)
+                        .on_hover_text("Color scheme for menus and windows.");
+                });
+                ui.add_enabled_ui(!high_contrast, |ui| {
+                    ui.vertical(|ui| Preferences::theme_radio(tx, ui, theme));
+                })
+                .response
+                .on_disabled_hover_text("High contrast mode overrides the theme.");
+                ui.end_row();
+
+                ui.with_layout(Layout::left_to_right(Align::Min), |ui| {
+                    ui.strong("Accent Color:");
+                });
+                ui.add_enabled_ui(!high_contrast, |ui| {
+                    Preferences::accent_color_picker(tx, ui, accent_color);
+                })
+                .response
+                .on_disabled_hover_text("High contrast mode overrides the theme.");
+                ui.end_row();
+
+                ui.with_layout(Layout::left_to_right(Align::Min), |ui| {
+                    ui.strong("Color-blind Mode:");
+                });
+                ui.vertical(|ui| Preferences::color_blind_mode_radio(tx, ui, color_blind_mode));
+            });
+    }
+
+    fn integrations_tab(tx: &NesEventProxy, ui: &mut Ui, cfg: &Config) {
+        #[cfg(feature = "profiling")]
+        puffin::profile_function!();
+
+        ui.label("Features that connect to online services. All are disabled unless enabled here.");
+
+        ui.separator();
+
+        Grid::new("integrations_preferences")
+            .num_columns(2)
+            .spacing([40.0, 6.0])
+            .show(ui, |ui| {
+                ui.with_layout(Layout::left_to_right(Align::Min), |ui| {
+                    ui.strong("Check for Updates:")
+                        .on_hover_cursor(CursorIcon::Help)
+                        .on_hover_text(concat!(
+                            "Check GitHub for a newer release of TetaNES on startup, showing its ",
+                            "changelog and downloads when one is found."
+                        ));
+                });
+                ui.vertical(|ui| {
+                    Preferences::update_check_radio(tx, ui, cfg.renderer.update_check);
+                    Self::last_update_check_label(ui, cfg.renderer.last_update_check);
+                });
+                ui.end_row();
+            });
+    }
+
+    fn last_update_check_label(ui: &mut Ui, last_update_check: i64) {
+        let last_checked = chrono::DateTime::from_timestamp(last_update_check, 0)
+            .filter(|_| last_update_check > 0)
+            .map_or_else(
+                || "Never".to_string(),
+                |time| {
+                    time.with_timezone(&chrono::Local)
+                        .format("%Y-%m-%d %H:%M")
+                        .to_string()
+                },
+            );
+        ui.weak(format!("Last checked: {last_checked}"))
+            .on_hover_text("Check manually from the Help menu at any time.");
     }
 
     fn input_tab(tx: &NesEventProxy, ui: &mut Ui, cfg: &Config) {
@@ -914,8 +2845,11 @@ impl State {
         puffin::profile_function!();
 
         let DeckConfig {
-            mut concurrent_dpad,
+            concurrent_dpad,
             zapper,
+            zapper_config,
+            vaus,
+            auto_peripherals,
             ..
         } = cfg.deck;
 
@@ -924,13 +2858,90 @@ impl State {
             .spacing([80.0, 6.0])
             .show(ui, |ui| {
                 Preferences::zapper_checkbox(tx, ui, zapper, None);
+                Self::auto_peripherals_checkbox(tx, ui, auto_peripherals);
                 ui.end_row();
 
-                let res = ui.checkbox(&mut concurrent_dpad, "Enable Concurrent D-Pad");
-                if res.clicked() {
-                    tx.event(ConfigEvent::ConcurrentDpad(concurrent_dpad));
-                }
+                Self::concurrent_dpad_checkbox(tx, ui, concurrent_dpad);
+                Preferences::vaus_checkbox(tx, ui, vaus);
             });
+
+        ui.separator();
+
+        ui.add_enabled_ui(zapper, |ui| {
+            Self::zapper_config_ui(tx, ui, zapper_config);
+        });
+    }
+
+    fn auto_peripherals_checkbox(tx: &NesEventProxy, ui: &mut Ui, mut auto_peripherals: bool) {
+        let res = ui
+            .checkbox(&mut auto_peripherals, "Auto-Configure Peripherals")
+            .on_hover_text(
+                "Automatically enable the Zapper Gun or Four Score when loading a game that requires them.",
+            );
+        if res.clicked() {
+            tx.event(ConfigEvent::AutoPeripherals(auto_peripherals));
+        }
+    }
+
+    fn concurrent_dpad_checkbox(tx: &NesEventProxy, ui: &mut Ui, mut concurrent_dpad: bool) {
+        let res = ui.checkbox(&mut concurrent_dpad, "Enable Concurrent D-Pad");
+        if res.clicked() {
+            tx.event(ConfigEvent::ConcurrentDpad(concurrent_dpad));
+        }
+    }
+
+    fn zapper_config_ui(tx: &NesEventProxy, ui: &mut Ui, zapper_config: ZapperConfig) {
+        let mut new_config = zapper_config;
+
+        Grid::new("zapper_preferences")
+            .num_columns(2)
+            .spacing([40.0, 6.0])
+            .show(ui, |ui| {
+                ui.strong("Zapper Sensor Radius:");
+                ui.add(
+                    DragValue::new(&mut new_config.radius)
+                        .range(0..=16)
+                        .suffix(" px"),
+                )
+                .on_hover_text("How far around the cursor the Zapper sees light.");
+                ui.end_row();
+
+                ui.strong("Zapper Light Threshold:");
+                ui.add(Slider::new(&mut new_config.brightness_threshold, 0..=765))
+                    .on_hover_text(
+                        "How bright a pixel must be to be detected as light. Lower values detect dimmer colors.",
+                    );
+                ui.end_row();
+
+                ui.strong("Zapper Light Duration:");
+                ui.add(
+                    DragValue::new(&mut new_config.light_duration)
+                        .range(1..=60)
+                        .suffix(" scanlines"),
+                )
+                .on_hover_text("How long the Zapper keeps sensing light after a bright pixel is drawn.");
+                ui.end_row();
+
+                ui.strong("Zapper Display Latency:");
+                ui.add(
+                    DragValue::new(&mut new_config.latency)
+                        .range(0..=60)
+                        .suffix(" scanlines"),
+                )
+                .on_hover_text(concat!(
+                    "Delay before the Zapper sees a drawn pixel, simulating the response time of a display. ",
+                    "Adjust this for games with strict light timing, like Freedom Force.",
+                ));
+                ui.end_row();
+            });
+
+        if ui.button("Reset Zapper Settings").clicked() {
+            new_config = ZapperConfig::default();
+        }
+
+        if new_config != zapper_config {
+            tx.event(ConfigEvent::ZapperConfig(new_config));
+        }
     }
 
     pub fn genie_codes_entry(&mut self, ui: &mut Ui, cfg: &Config) {
@@ -999,58 +3010,13 @@ impl State {
         ctx.memory_mut(|mem| *mem = Default::default());
 
         // Inform all cfg updates
-        let Config {
-            deck,
-            emulation,
-            audio,
-            renderer,
-            input,
-        } = Config::default();
-
-        let events = [
-            ConfigEvent::ActionBindings(input.action_bindings),
-            ConfigEvent::AlwaysOnTop(renderer.always_on_top),
-            ConfigEvent::ApuChannelsEnabled(deck.channels_enabled),
-            ConfigEvent::AudioBuffer(audio.buffer_size),
-            ConfigEvent::AudioEnabled(audio.enabled),
-            ConfigEvent::AudioLatency(audio.latency),
-            ConfigEvent::AutoLoad(emulation.auto_load),
-            ConfigEvent::AutoSave(emulation.auto_save),
-            ConfigEvent::AutoSaveInterval(emulation.auto_save_interval),
-            ConfigEvent::ConcurrentDpad(deck.concurrent_dpad),
-            ConfigEvent::CycleAccurate(deck.cycle_accurate),
-            ConfigEvent::DarkTheme(renderer.dark_theme),
-            ConfigEvent::EmbedViewports(renderer.embed_viewports),
-            ConfigEvent::FourPlayer(deck.four_player),
-            ConfigEvent::Fullscreen(renderer.fullscreen),
-            ConfigEvent::GamepadAssignments(input.gamepad_assignments),
-            ConfigEvent::GenieCodeClear,
-            ConfigEvent::HideOverscan(renderer.hide_overscan),
-            ConfigEvent::MapperRevisions(deck.mapper_revisions),
-            ConfigEvent::RamState(deck.ram_state),
-            // Clearing recent roms is handled in a separate button
-            ConfigEvent::Region(deck.region),
-            ConfigEvent::RewindEnabled(emulation.rewind),
-            ConfigEvent::RewindInterval(emulation.rewind_interval),
-            ConfigEvent::RewindSeconds(emulation.rewind_seconds),
-            ConfigEvent::RunAhead(emulation.run_ahead),
-            ConfigEvent::SaveSlot(emulation.save_slot),
-            ConfigEvent::Shader(renderer.shader),
-            ConfigEvent::ShowMenubar(renderer.show_menubar),
-            ConfigEvent::ShowMessages(renderer.show_messages),
-            ConfigEvent::Speed(emulation.speed),
-            ConfigEvent::VideoFilter(deck.filter),
-            ConfigEvent::ZapperConnected(deck.zapper),
-        ];
-
-        for event in events {
+        for event in ConfigEvent::from_config(Config::default()) {
             tx.event(event);
         }
     }
 
-    fn clear_save_states(tx: &NesEventProxy) {
-        let data_dir = Config::default_data_dir();
-        match fs::clear_dir(data_dir) {
+    fn clear_save_states(tx: &NesEventProxy, paths: &PathsConfig) {
+        match fs::clear_dir(paths.save_dir()) {
             Ok(_) => tx.event(UiEvent::Message((
                 MessageType::Info,
                 "Save States cleared.".to_string(),
@@ -0,0 +1,379 @@
+use crate::nes::{
+    event::{EmulationEvent, NesEventProxy, UiEvent},
+    renderer::gui::lib::ViewportOptions,
+};
+use egui::{
+    Align2, Button, CentralPanel, Color32, Context, CursorIcon, FontId, Grid, Painter, Pos2, Rect,
+    ScrollArea, Sense, Ui, Vec2, ViewportClass, ViewportId,
+};
+use parking_lot::Mutex;
+use std::{
+    hash::{DefaultHasher, Hash, Hasher},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+use tetanes_core::debug::{
+    profiler::{CodeAddr, ProfileReport, Routine},
+    symbols::Symbols,
+};
+
+/// Viewport for profiling game code, showing the CPU cycles spent in each routine per frame.
+#[derive(Debug)]
+#[must_use]
+pub struct Profiler {
+    id: ViewportId,
+    open: Arc<AtomicBool>,
+    /// Whether emulation has been asked to profile, to follow changes to `open`.
+    profiling: bool,
+    tx: NesEventProxy,
+    state: Arc<Mutex<State>>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum View {
+    Table,
+    Flame,
+}
+
+#[derive(Debug)]
+#[must_use]
+struct State {
+    tx: NesEventProxy,
+    report: Option<ProfileReport>,
+    symbols: Symbols,
+    view: View,
+}
+
+impl Profiler {
+    const TITLE: &'static str = "⏱ Profiler";
+
+    pub fn new(tx: NesEventProxy) -> Self {
+        Self {
+            id: ViewportId::from_hash_of(Self::TITLE),
+            open: Arc::new(AtomicBool::new(false)),
+            profiling: false,
+            tx: tx.clone(),
+            state: Arc::new(Mutex::new(State {
+                tx,
+                report: None,
+                symbols: Symbols::default(),
+                view: View::Table,
+            })),
+        }
+    }
+
+    pub const fn id(&self) -> ViewportId {
+        self.id
+    }
+
+    pub fn open(&self) -> bool {
+        self.open.load(Ordering::Acquire)
+    }
+
+    pub fn set_open(&self, open: bool) {
+        self.open.store(open, Ordering::Release);
+    }
+
+    pub fn toggle_open(&self) {
+        let _ = self
+            .open
+            .fetch_update(Ordering::Release, Ordering::Acquire, |open| Some(!open));
+    }
+
+    /// Update the displayed profile, sent periodically by emulation while profiling.
+    pub fn update(&self, report: ProfileReport) {
+        self.state.lock().report = Some(report);
+    }
+
+    /// Add symbols naming routines, replacing names of any addresses already named.
+    pub fn load_symbols(&self, symbols: Symbols) {
+        self.state.lock().symbols.extend(symbols);
+    }
+
+    /// Clear the displayed profile and symbols.
+    pub fn clear(&self) {
+        let mut state = self.state.lock();
+        state.report = None;
+        state.symbols.clear();
+    }
+
+    pub fn show(&mut self, ctx: &Context, opts: ViewportOptions) {
+        let open = self.open();
+        if open != self.profiling {
+            self.profiling = open;
+            self.tx.event(EmulationEvent::Profile(open));
+        }
+        if !open {
+            return;
+        }
+
+        #[cfg(feature = "profiling")]
+        puffin::profile_function!();
+
+        let open = Arc::clone(&self.open);
+        let state = Arc::clone(&self.state);
+
+        let mut viewport_builder = egui::ViewportBuilder::default()
+            .with_title(Self::TITLE)
+            .with_inner_size(Vec2::new(640.0, 640.0));
+        if opts.always_on_top {
+            viewport_builder = viewport_builder.with_always_on_top();
+        }
+
+        ctx.show_viewport_deferred(self.id, viewport_builder, move |ctx, class| {
+            let mut state = state.lock();
+            if class == ViewportClass::Embedded {
+                let mut window_open = open.load(Ordering::Acquire);
+                egui::Window::new(Profiler::TITLE)
+                    .open(&mut window_open)
+                    .default_size(Vec2::new(560.0, 480.0))
+                    .show(ctx, |ui| state.ui(ui, opts.enabled));
+                open.store(window_open, Ordering::Release);
+            } else {
+                CentralPanel::default().show(ctx, |ui| state.ui(ui, opts.enabled));
+                if ctx.input(|i| i.viewport().close_requested()) {
+                    open.store(false, Ordering::Release);
+                }
+            }
+        });
+    }
+}
+
+impl State {
+    fn ui(&mut self, ui: &mut Ui, enabled: bool) {
+        #[cfg(feature = "profiling")]
+        puffin::profile_function!();
+
+        ui.add_enabled_ui(enabled, |ui| {
+            ui.horizontal(|ui| {
+                if ui
+                    .button("Load Symbols...")
+                    .on_hover_text("Name routines from FCEUX .nl or Mesen .mlb symbol files.")
+                    .clicked()
+                {
+                    self.tx.event(UiEvent::LoadSymbolsDialog);
+                }
+                if ui
+                    .add_enabled(!self.symbols.is_empty(), Button::new("Clear Symbols"))
+                    .clicked()
+                {
+                    self.symbols.clear();
+                }
+                if ui
+                    .button("Reset")
+                    .on_hover_text("Start profiling over from the current frame.")
+                    .clicked()
+                {
+                    self.report = None;
+                    self.tx.event(EmulationEvent::ClearProfile);
+                }
+
+                ui.separator();
+                ui.selectable_value(&mut self.view, View::Table, "Table");
+                ui.selectable_value(&mut self.view, View::Flame, "Flame Graph");
+            });
+
+            ui.separator();
+
+            let Some(report) = self.report.as_ref().filter(|report| report.frames > 0) else {
+                ui.label("Waiting for frames to profile...");
+                return;
+            };
+
+            ui.label(format!(
+                "{} frames profiled, {} symbols loaded.",
+                report.frames,
+                self.symbols.len()
+            ));
+            match self.view {
+                View::Table => routine_table(ui, report, &self.symbols),
+                View::Flame => flame_graph(ui, report, &self.symbols),
+            }
+        });
+    }
+}
+
+fn routine_table(ui: &mut Ui, report: &ProfileReport, symbols: &Symbols) {
+    let frames = u64::from(report.frames);
+    let total = report
+        .routines
+        .iter()
+        .map(|(_, stats)| stats.self_cycles)
+        .sum::<u64>()
+        .max(1);
+
+    ScrollArea::both().show(ui, |ui| {
+        Grid::new("profiler_routines")
+            .num_columns(6)
+            .spacing([20.0, 4.0])
+            .striped(true)
+            .show(ui, |ui| {
+                ui.strong("Routine");
+                ui.strong("Bank");
+                ui.strong("Calls");
+                ui.strong("Self")
+                    .on_hover_cursor(CursorIcon::Help)
+                    .on_hover_text("Cycles per frame spent in the routine itself.");
+                ui.strong("Total")
+                    .on_hover_cursor(CursorIcon::Help)
+                    .on_hover_text("Cycles per frame spent in the routine and routines it called.");
+                ui.strong("Self %");
+                ui.end_row();
+
+                for (routine, stats) in &report.routines {
+                    ui.monospace(routine_name(*routine, symbols));
+                    ui.monospace(
+                        routine_addr(*routine)
+                            .and_then(|addr| addr.bank())
+                            .map_or_else(|| "-".to_string(), |bank| format!("${bank:02X}")),
+                    );
+                    ui.monospace(format!("{:.1}", stats.calls as f32 / frames as f32));
+                    ui.monospace((stats.self_cycles / frames).to_string());
+                    ui.monospace((stats.total_cycles / frames).to_string());
+                    ui.monospace(format!(
+                        "{:.1}%",
+                        100.0 * stats.self_cycles as f32 / total as f32
+                    ));
+                    ui.end_row();
+                }
+            });
+    });
+}
+
+fn flame_graph(ui: &mut Ui, report: &ProfileReport, symbols: &Symbols) {
+    let total = report
+        .stacks
+        .iter()
+        .map(|(_, cycles)| cycles)
+        .sum::<u64>()
+        .max(1);
+    let depth = report
+        .stacks
+        .iter()
+        .map(|(stack, _)| stack.len())
+        .max()
+        .unwrap_or(1);
+
+    ui.label("Callers are drawn above the routines they called, sized by cycles spent.");
+    ScrollArea::vertical().show(ui, |ui| {
+        let size = Vec2::new(ui.available_width(), depth as f32 * Flame::ROW_HEIGHT);
+        let (res, painter) = ui.allocate_painter(size, Sense::hover());
+        let mut flame = Flame {
+            painter: &painter,
+            symbols,
+            origin: res.rect.min,
+            scale: res.rect.width() / total as f32,
+            frames: u64::from(report.frames),
+            total,
+            hover_pos: res.hover_pos(),
+            hovered: None,
+        };
+        flame.draw(&report.stacks, 0, 0.0);
+        if let Some(hovered) = flame.hovered {
+            res.on_hover_text_at_pointer(hovered);
+        }
+    });
+}
+
+/// Draws call stacks as an icicle graph, with callers above the routines they called.
+struct Flame<'a> {
+    painter: &'a Painter,
+    symbols: &'a Symbols,
+    origin: Pos2,
+    /// Width in points of one cycle.
+    scale: f32,
+    frames: u64,
+    total: u64,
+    hover_pos: Option<Pos2>,
+    hovered: Option<String>,
+}
+
+impl Flame<'_> {
+    const ROW_HEIGHT: f32 = 18.0;
+
+    /// Draw the routines at `depth` of sorted `stacks` that share the same callers, from `x`.
+    fn draw(&mut self, stacks: &[(Vec<Routine>, u64)], depth: usize, mut x: f32) {
+        let mut i = 0;
+        while i < stacks.len() {
+            let Some(routine) = stacks[i].0.get(depth).copied() else {
+                // Cycles spent in the caller itself, which sort before its callees
+                x += stacks[i].1 as f32 * self.scale;
+                i += 1;
+                continue;
+            };
+            let len = stacks[i..]
+                .iter()
+                .take_while(|(stack, _)| stack.get(depth) == Some(&routine))
+                .count();
+            let group = &stacks[i..i + len];
+            let cycles = group.iter().map(|(_, cycles)| cycles).sum::<u64>();
+            let width = cycles as f32 * self.scale;
+            i += len;
+            if width < 1.0 {
+                x += width;
+                continue;
+            }
+
+            let rect = Rect::from_min_size(
+                self.origin + Vec2::new(x, depth as f32 * Self::ROW_HEIGHT),
+                Vec2::new(width - 1.0, Self::ROW_HEIGHT - 1.0),
+            );
+            let name = routine_name(routine, self.symbols);
+            self.painter.rect_filled(rect, 2.0, routine_color(routine));
+            if width > 24.0 {
+                self.painter.with_clip_rect(rect).text(
+                    rect.left_center() + Vec2::new(3.0, 0.0),
+                    Align2::LEFT_CENTER,
+                    &name,
+                    FontId::monospace(11.0),
+                    Color32::BLACK,
+                );
+            }
+            if self.hover_pos.is_some_and(|pos| rect.contains(pos)) {
+                self.hovered = Some(format!(
+                    "{name}\n{} cycles per frame ({:.1}%)",
+                    cycles / self.frames.max(1),
+                    100.0 * cycles as f32 / self.total as f32
+                ));
+            }
+
+            self.draw(group, depth + 1, x);
+            x += width;
+        }
+    }
+}
+
+const fn routine_addr(routine: Routine) -> Option<CodeAddr> {
+    match routine {
+        Routine::Main => None,
+        Routine::Subroutine(addr) | Routine::Nmi(addr) | Routine::Irq(addr) => Some(addr),
+    }
+}
+
+fn routine_name(routine: Routine, symbols: &Symbols) -> String {
+    let name = |addr: CodeAddr| {
+        symbols
+            .name(addr)
+            .map_or_else(|| format!("${:04X}", addr.addr), str::to_string)
+    };
+    match routine {
+        Routine::Main => "(main)".to_string(),
+        Routine::Subroutine(addr) => name(addr),
+        Routine::Nmi(addr) => format!("NMI {}", name(addr)),
+        Routine::Irq(addr) => format!("IRQ {}", name(addr)),
+    }
+}
+
+/// A warm color that stays the same for a routine between frames.
+fn routine_color(routine: Routine) -> Color32 {
+    let mut hasher = DefaultHasher::new();
+    routine.hash(&mut hasher);
+    let hash = hasher.finish();
+    Color32::from_rgb(
+        0xC0 + (hash & 0x3F) as u8,
+        0x60 + ((hash >> 8) & 0x7F) as u8,
+        0x30 + ((hash >> 16) & 0x3F) as u8,
+    )
+}
@@ -0,0 +1,127 @@
+use crate::nes::{
+    config::Config,
+    emulation::race::RaceInputs,
+    event::{EmulationEvent, NesEventProxy},
+    renderer::{gui::lib::ViewportOptions, painter::RenderState, texture::Texture},
+};
+use egui::{CentralPanel, Context, Frame, Image, Ui, Vec2, ViewportClass, ViewportId};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+/// Second window running a copy of the loaded ROM side-by-side with the main window.
+#[derive(Debug)]
+#[must_use]
+pub struct RaceWindow {
+    tx: NesEventProxy,
+    id: ViewportId,
+    open: Arc<AtomicBool>,
+    running: bool,
+    inputs: RaceInputs,
+    pub texture: Texture,
+}
+
+impl RaceWindow {
+    const TITLE: &'static str = "🗗 Race";
+
+    pub fn new(tx: NesEventProxy, render_state: &mut RenderState, cfg: &Config) -> Self {
+        Self {
+            tx,
+            id: ViewportId::from_hash_of(Self::TITLE),
+            open: Arc::new(AtomicBool::new(false)),
+            running: false,
+            inputs: RaceInputs::default(),
+            texture: Texture::new(
+                render_state,
+                cfg.texture_size(),
+                cfg.deck.region.aspect_ratio(),
+                Some("race frame"),
+            ),
+        }
+    }
+
+    pub const fn id(&self) -> ViewportId {
+        self.id
+    }
+
+    pub fn open(&self) -> bool {
+        self.open.load(Ordering::Acquire)
+    }
+
+    /// Open the window and start running a copy of the loaded ROM in it.
+    pub fn start(&mut self, inputs: RaceInputs) {
+        self.inputs = inputs;
+        self.running = true;
+        self.open.store(true, Ordering::Release);
+        self.tx.event(EmulationEvent::StartRace((self.id, inputs)));
+    }
+
+    /// Close the window, stopping the second instance if it's still running.
+    pub fn close(&mut self) {
+        self.open.store(false, Ordering::Release);
+        if self.running {
+            self.running = false;
+            self.tx.event(EmulationEvent::StopRace);
+        }
+    }
+
+    /// Close the window after emulation has already stopped the second instance.
+    pub fn stopped(&mut self) {
+        self.running = false;
+        self.open.store(false, Ordering::Release);
+    }
+
+    pub fn show(&mut self, ctx: &Context, opts: ViewportOptions) {
+        if !self.open() {
+            if self.running {
+                self.close();
+            }
+            return;
+        }
+
+        #[cfg(feature = "profiling")]
+        puffin::profile_function!();
+
+        let open = Arc::clone(&self.open);
+        let texture = self.texture.sized();
+        let title = format!("{} - {}", Self::TITLE, self.inputs.as_str());
+
+        let mut viewport_builder = egui::ViewportBuilder::default()
+            .with_title(title.as_str())
+            .with_inner_size(texture.size);
+        if opts.always_on_top {
+            viewport_builder = viewport_builder.with_always_on_top();
+        }
+
+        ctx.show_viewport_deferred(self.id, viewport_builder, move |ctx, class| {
+            let frame_ui = |ui: &mut Ui| {
+                let available = ui.available_size();
+                let scale = (available.x / texture.size.x)
+                    .min(available.y / texture.size.y)
+                    .max(0.1);
+                ui.centered_and_justified(|ui| {
+                    ui.add(Image::from_texture(texture).fit_to_exact_size(texture.size * scale));
+                });
+            };
+            if class == ViewportClass::Embedded {
+                let mut window_open = open.load(Ordering::Acquire);
+                egui::Window::new(title.as_str())
+                    .open(&mut window_open)
+                    .default_size(texture.size)
+                    .show(ctx, |ui| {
+                        ui.set_min_size(Vec2::splat(64.0));
+                        frame_ui(ui);
+                    });
+                open.store(window_open, Ordering::Release);
+            } else {
+                CentralPanel::default()
+                    .frame(Frame::canvas(&ctx.style()))
+                    .show(ctx, frame_ui);
+                if ctx.input(|i| i.viewport().close_requested()) {
+                    open.store(false, Ordering::Release);
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,290 @@
+use crate::nes::{
+    config::Config,
+    emulation::ram_watch::{RamWatch, WatchFormat, WatchSize},
+    event::{ConfigEvent, NesEventProxy},
+    renderer::gui::lib::ViewportOptions,
+};
+use egui::{
+    CentralPanel, Color32, ComboBox, Context, CursorIcon, FontId, Grid, Key, Painter, Rect,
+    ScrollArea, TextEdit, Ui, Vec2, ViewportClass, ViewportId,
+};
+use parking_lot::Mutex;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+use tracing::warn;
+
+/// Viewport for watching CPU memory addresses, which can be pinned to be drawn over the NES
+/// frame.
+#[derive(Debug)]
+#[must_use]
+pub struct RamWatchWindow {
+    id: ViewportId,
+    open: Arc<AtomicBool>,
+    state: Arc<Mutex<State>>,
+    resources: Option<Resources>,
+}
+
+#[derive(Debug)]
+#[must_use]
+struct Resources {
+    watches: Vec<RamWatch>,
+    values: Vec<u16>,
+}
+
+#[derive(Debug)]
+#[must_use]
+struct State {
+    tx: NesEventProxy,
+    entry: WatchEntry,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+struct WatchEntry {
+    label: String,
+    addr: String,
+    size: WatchSize,
+    format: WatchFormat,
+    error: Option<String>,
+}
+
+impl RamWatchWindow {
+    const TITLE: &'static str = "👁 RAM Watch";
+
+    pub fn new(tx: NesEventProxy) -> Self {
+        Self {
+            id: ViewportId::from_hash_of(Self::TITLE),
+            open: Arc::new(AtomicBool::new(false)),
+            state: Arc::new(Mutex::new(State {
+                tx,
+                entry: WatchEntry::default(),
+            })),
+            resources: None,
+        }
+    }
+
+    pub fn open(&self) -> bool {
+        self.open.load(Ordering::Acquire)
+    }
+
+    pub fn set_open(&self, open: bool) {
+        self.open.store(open, Ordering::Release);
+    }
+
+    pub fn toggle_open(&self) {
+        let _ = self
+            .open
+            .fetch_update(Ordering::Release, Ordering::Acquire, |open| Some(!open));
+    }
+
+    pub fn prepare(&mut self, cfg: &Config, values: &[u16]) {
+        self.resources = Some(Resources {
+            watches: cfg.renderer.ram_watches.clone(),
+            values: values.to_vec(),
+        });
+    }
+
+    pub fn show(&mut self, ctx: &Context, opts: ViewportOptions) {
+        if !self.open() {
+            return;
+        }
+
+        #[cfg(feature = "profiling")]
+        puffin::profile_function!();
+
+        let open = Arc::clone(&self.open);
+        let state = Arc::clone(&self.state);
+        let Some(resources) = self.resources.take() else {
+            warn!("RamWatchWindow::prepare was not called with required resources");
+            return;
+        };
+
+        let mut viewport_builder = egui::ViewportBuilder::default()
+            .with_title(Self::TITLE)
+            .with_inner_size(Vec2::new(520.0, 320.0));
+        if opts.always_on_top {
+            viewport_builder = viewport_builder.with_always_on_top();
+        }
+
+        ctx.show_viewport_deferred(self.id, viewport_builder, move |ctx, class| {
+            let mut state = state.lock();
+            if class == ViewportClass::Embedded {
+                let mut window_open = open.load(Ordering::Acquire);
+                egui::Window::new(RamWatchWindow::TITLE)
+                    .open(&mut window_open)
+                    .default_size(Vec2::new(420.0, 240.0))
+                    .show(ctx, |ui| state.ui(ui, opts.enabled, &resources));
+                open.store(window_open, Ordering::Release);
+            } else {
+                CentralPanel::default().show(ctx, |ui| state.ui(ui, opts.enabled, &resources));
+                if ctx.input(|i| i.viewport().close_requested()) {
+                    open.store(false, Ordering::Release);
+                }
+            }
+        });
+    }
+
+    /// Draw all pinned watches in the top-left corner of the NES frame at `rect`.
+    pub fn draw_pinned(painter: &Painter, rect: Rect, watches: &[RamWatch], values: &[u16]) {
+        let font = FontId::monospace((rect.height() / 30.0).clamp(10.0, 24.0));
+        let mut pos = rect.left_top() + Vec2::splat(4.0);
+        for (watch, value) in watches.iter().zip(values).filter(|(watch, _)| watch.pinned) {
+            let text = if watch.label.is_empty() {
+                watch.format_value(*value)
+            } else {
+                format!("{}: {}", watch.label, watch.format_value(*value))
+            };
+            let galley = painter.layout_no_wrap(text, font.clone(), Color32::WHITE);
+            let bg = Rect::from_min_size(pos, galley.size()).expand(2.0);
+            painter.rect_filled(bg, 2.0, Color32::from_black_alpha(160));
+            pos.y += galley.size().y + 4.0;
+            painter.galley(bg.min + Vec2::splat(2.0), galley, Color32::WHITE);
+        }
+    }
+}
+
+impl State {
+    fn ui(&mut self, ui: &mut Ui, enabled: bool, resources: &Resources) {
+        #[cfg(feature = "profiling")]
+        puffin::profile_function!();
+
+        ui.add_enabled_ui(enabled, |ui| {
+            self.entry_ui(ui, &resources.watches);
+            ui.separator();
+            self.watches_ui(ui, resources);
+        });
+    }
+
+    fn entry_ui(&mut self, ui: &mut Ui, watches: &[RamWatch]) {
+        let entry = &mut self.entry;
+        let mut submitted = false;
+        ui.horizontal(|ui| {
+            ui.add(
+                TextEdit::singleline(&mut entry.label)
+                    .hint_text("Label")
+                    .desired_width(120.0),
+            );
+            let addr_res = ui
+                .add(
+                    TextEdit::singleline(&mut entry.addr)
+                        .hint_text("$0000")
+                        .desired_width(60.0),
+                )
+                .on_hover_cursor(CursorIcon::Help)
+                .on_hover_text("The CPU address to watch, in hexadecimal.");
+            if addr_res.changed() {
+                entry.error = None;
+            }
+            size_combo(ui, "new_watch_size", &mut entry.size);
+            format_combo(ui, "new_watch_format", &mut entry.format);
+
+            let has_entry = !entry.addr.is_empty();
+            let add_clicked = ui
+                .add_enabled(has_entry, egui::Button::new("Add"))
+                .clicked();
+            submitted =
+                (has_entry && addr_res.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter)))
+                    || add_clicked;
+        });
+
+        if submitted {
+            let addr = entry.addr.trim().trim_start_matches('$');
+            match u16::from_str_radix(addr, 16) {
+                Ok(addr) => {
+                    let mut watches = watches.to_vec();
+                    watches.push(RamWatch {
+                        label: entry.label.trim().to_string(),
+                        addr,
+                        size: entry.size,
+                        format: entry.format,
+                        pinned: true,
+                    });
+                    self.tx.event(ConfigEvent::RamWatches(watches));
+                    entry.label.clear();
+                    entry.addr.clear();
+                }
+                Err(_) => {
+                    entry.error = Some(format!("Invalid address: `{}`", entry.addr.trim()));
+                }
+            }
+        }
+
+        if let Some(error) = &entry.error {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        }
+    }
+
+    fn watches_ui(&mut self, ui: &mut Ui, resources: &Resources) {
+        let Resources { watches, values } = resources;
+        if watches.is_empty() {
+            ui.label("No RAM watches. Add an address above to start watching it.");
+            return;
+        }
+
+        let mut updated = watches.clone();
+        let mut removed = None;
+        ScrollArea::vertical().show(ui, |ui| {
+            let grid = Grid::new("ram_watches")
+                .num_columns(7)
+                .spacing([12.0, 6.0])
+                .striped(true);
+            grid.show(ui, |ui| {
+                ui.strong("📌")
+                    .on_hover_text("Pin the value over the NES frame.");
+                ui.strong("Label");
+                ui.strong("Address");
+                ui.strong("Size");
+                ui.strong("Format");
+                ui.strong("Value");
+                ui.end_row();
+
+                for (i, watch) in updated.iter_mut().enumerate() {
+                    ui.checkbox(&mut watch.pinned, "");
+                    ui.label(&watch.label);
+                    ui.monospace(format!("${:04X}", watch.addr));
+                    size_combo(ui, ("watch_size", i), &mut watch.size);
+                    format_combo(ui, ("watch_format", i), &mut watch.format);
+                    match values.get(i) {
+                        Some(value) => ui.monospace(watch.format_value(*value)),
+                        None => ui.monospace("--"),
+                    };
+                    // icon: waste basket
+                    if ui.button("🗑").clicked() {
+                        removed = Some(i);
+                    }
+                    ui.end_row();
+                }
+            });
+        });
+
+        if let Some(index) = removed {
+            let _ = updated.remove(index);
+        }
+        if updated != *watches {
+            self.tx.event(ConfigEvent::RamWatches(updated));
+        }
+    }
+}
+
+fn size_combo(ui: &mut Ui, id_salt: impl std::hash::Hash, size: &mut WatchSize) {
+    ComboBox::from_id_salt(id_salt)
+        .selected_text(size.as_str())
+        .width(64.0)
+        .show_ui(ui, |ui| {
+            for value in [WatchSize::Byte, WatchSize::Word] {
+                ui.selectable_value(size, value, value.as_str());
+            }
+        });
+}
+
+fn format_combo(ui: &mut Ui, id_salt: impl std::hash::Hash, format: &mut WatchFormat) {
+    ComboBox::from_id_salt(id_salt)
+        .selected_text(format.as_str())
+        .width(64.0)
+        .show_ui(ui, |ui| {
+            for value in [WatchFormat::Hex, WatchFormat::Dec, WatchFormat::Bcd] {
+                ui.selectable_value(format, value, value.as_str());
+            }
+        });
+}
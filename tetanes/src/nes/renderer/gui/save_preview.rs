@@ -0,0 +1,167 @@
+use crate::nes::config::Config;
+use egui::{
+    pos2, Align2, Color32, ColorImage, Context, FontId, Image, Painter, Rect, TextureHandle,
+    TextureOptions, Ui, Vec2,
+};
+use std::collections::HashMap;
+use tetanes_core::{
+    control_deck::LoadedRom,
+    fs,
+    time::{Duration, Instant},
+    video::Frame,
+};
+use tracing::warn;
+
+/// Thumbnails of the screen saved with each save state slot for the loaded ROM, loaded lazily
+/// and cached as textures.
+#[derive(Default)]
+#[must_use]
+pub struct SaveStatePreviews {
+    textures: HashMap<u8, Option<TextureHandle>>,
+    /// Slot most recently selected, shown over the NES frame until it times out.
+    selected: Option<(u8, Instant)>,
+}
+
+impl std::fmt::Debug for SaveStatePreviews {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SaveStatePreviews")
+            .field("slots", &self.textures.keys())
+            .field("selected", &self.selected)
+            .finish()
+    }
+}
+
+impl SaveStatePreviews {
+    /// Scale thumbnails are drawn at.
+    const SCALE: f32 = 2.0;
+
+    /// Drop all cached thumbnails, e.g. when the loaded ROM changes.
+    pub fn clear(&mut self) {
+        self.textures.clear();
+        self.selected = None;
+    }
+
+    /// Drop the cached thumbnail for `slot` after a new state has been saved to it.
+    pub fn invalidate(&mut self, slot: u8) {
+        self.textures.remove(&slot);
+    }
+
+    /// Show the thumbnail for `slot` over the NES frame for a short time.
+    pub fn select(&mut self, slot: u8) {
+        self.selected = Some((slot, Instant::now()));
+    }
+
+    fn size() -> Vec2 {
+        Vec2::new(
+            Frame::THUMBNAIL_WIDTH as f32,
+            Frame::THUMBNAIL_HEIGHT as f32,
+        ) * Self::SCALE
+    }
+
+    fn texture(
+        &mut self,
+        ctx: &Context,
+        cfg: &Config,
+        rom: &LoadedRom,
+        slot: u8,
+    ) -> Option<&TextureHandle> {
+        self.textures
+            .entry(slot)
+            .or_insert_with(|| {
+                let path = cfg.paths.save_path(&rom.name, slot);
+                if !fs::exists(&path) {
+                    return None;
+                }
+                match fs::load_thumbnail(&path) {
+                    Ok(Some(thumbnail)) if thumbnail.len() == Frame::THUMBNAIL_SIZE => {
+                        let image = ColorImage::from_rgba_unmultiplied(
+                            [
+                                Frame::THUMBNAIL_WIDTH as usize,
+                                Frame::THUMBNAIL_HEIGHT as usize,
+                            ],
+                            &thumbnail,
+                        );
+                        Some(ctx.load_texture(
+                            format!("save slot {slot}"),
+                            image,
+                            TextureOptions::LINEAR,
+                        ))
+                    }
+                    // Saved before thumbnails were added
+                    Ok(_) => None,
+                    Err(err) => {
+                        warn!("failed to load save state thumbnail {path:?}: {err:?}");
+                        None
+                    }
+                }
+            })
+            .as_ref()
+    }
+
+    /// Show the thumbnail for `slot`, or a placeholder if there isn't one.
+    pub fn ui(&mut self, ui: &mut Ui, cfg: &Config, rom: &LoadedRom, slot: u8) {
+        ui.strong(format!("Slot {slot}"));
+        match self.texture(ui.ctx(), cfg, rom, slot) {
+            Some(texture) => {
+                ui.add(Image::new((texture.id(), Self::size())));
+            }
+            None => {
+                ui.label("No preview available.");
+            }
+        }
+    }
+
+    /// Draw the thumbnail for the recently selected slot in the top-right corner of the NES frame
+    /// at `rect`.
+    pub fn draw_selected(
+        &mut self,
+        painter: &Painter,
+        rect: Rect,
+        cfg: &Config,
+        rom: &LoadedRom,
+        timeout: Duration,
+    ) {
+        let Some((slot, selected_at)) = self.selected else {
+            return;
+        };
+        if selected_at.elapsed() > timeout {
+            self.selected = None;
+            return;
+        }
+
+        let margin = 8.0;
+        let size = Self::size();
+        let image_rect = Rect::from_min_size(
+            pos2(rect.right() - size.x - margin, rect.top() + margin),
+            size,
+        );
+        painter.rect_filled(image_rect.expand(2.0), 2.0, Color32::from_black_alpha(200));
+        match self.texture(painter.ctx(), cfg, rom, slot) {
+            Some(texture) => {
+                painter.image(
+                    texture.id(),
+                    image_rect,
+                    Rect::from_min_max(pos2(0.0, 0.0), pos2(1.0, 1.0)),
+                    Color32::WHITE,
+                );
+            }
+            None => {
+                painter.text(
+                    image_rect.center(),
+                    Align2::CENTER_CENTER,
+                    "Empty",
+                    FontId::proportional(14.0),
+                    Color32::GRAY,
+                );
+            }
+        }
+        painter.text(
+            image_rect.left_bottom() + Vec2::new(4.0, -4.0),
+            Align2::LEFT_BOTTOM,
+            format!("Slot {slot}"),
+            FontId::proportional(14.0),
+            Color32::WHITE,
+        );
+        painter.ctx().request_repaint_after(timeout);
+    }
+}
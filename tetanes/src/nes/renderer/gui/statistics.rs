@@ -0,0 +1,114 @@
+use crate::nes::emulation::playtime::Playtime;
+use egui::{Context, Grid, RichText, ScrollArea, Ui};
+
+/// Window summarizing time spent playing each ROM.
+#[derive(Default, Debug)]
+#[must_use]
+pub struct Statistics {
+    open: bool,
+    playtime: Playtime,
+}
+
+impl Statistics {
+    const TITLE: &'static str = "📊 Statistics";
+
+    pub const fn open(&self) -> bool {
+        self.open
+    }
+
+    pub fn set_open(&mut self, open: bool) {
+        self.open = open;
+    }
+
+    pub fn set_playtime(&mut self, playtime: Playtime) {
+        self.playtime = playtime;
+    }
+
+    /// Format `seconds` as hours and minutes, e.g. `12h 05m`.
+    fn duration_text(seconds: u64) -> String {
+        let minutes = seconds / 60;
+        if minutes < 60 {
+            format!("{minutes}m")
+        } else {
+            format!("{}h {:02}m", minutes / 60, minutes % 60)
+        }
+    }
+
+    fn last_played_text(timestamp: i64) -> String {
+        chrono::DateTime::from_timestamp(timestamp, 0)
+            .filter(|_| timestamp > 0)
+            .map(|time| {
+                time.with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M")
+                    .to_string()
+            })
+            .unwrap_or_else(|| "Unknown".to_string())
+    }
+
+    pub fn show(&mut self, ctx: &Context, enabled: bool) {
+        #[cfg(feature = "profiling")]
+        puffin::profile_function!();
+
+        if !self.open {
+            return;
+        }
+
+        let mut open = self.open;
+        egui::Window::new(Self::TITLE)
+            .open(&mut open)
+            .default_size([480.0, 320.0])
+            .show(ctx, |ui| {
+                ui.add_enabled_ui(enabled, |ui| self.ui(ui));
+            });
+        self.open = open;
+    }
+
+    fn ui(&mut self, ui: &mut Ui) {
+        Grid::new("statistics_totals")
+            .num_columns(2)
+            .spacing([40.0, 6.0])
+            .show(ui, |ui| {
+                ui.strong("Total Playtime:");
+                ui.label(Self::duration_text(self.playtime.total_seconds()));
+                ui.end_row();
+
+                ui.strong("Games Played:");
+                ui.label(self.playtime.games.len().to_string());
+                ui.end_row();
+
+                ui.strong("Sessions:");
+                ui.label(self.playtime.total_sessions().to_string());
+                ui.end_row();
+            });
+
+        ui.separator();
+
+        if self.playtime.games.is_empty() {
+            ui.label("No games played yet.");
+            return;
+        }
+
+        ui.label(RichText::new("Most Played").heading());
+        ScrollArea::both().auto_shrink(false).show(ui, |ui| {
+            Grid::new("statistics_games")
+                .num_columns(4)
+                .spacing([20.0, 6.0])
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.strong("Game");
+                    ui.strong("Playtime");
+                    ui.strong("Sessions");
+                    ui.strong("Last Played");
+                    ui.end_row();
+
+                    for (name, game) in self.playtime.most_played() {
+                        ui.label(name);
+                        ui.label(Self::duration_text(game.seconds));
+                        ui.label(game.sessions.to_string());
+                        ui.label(Self::last_played_text(game.last_played));
+                        ui.end_row();
+                    }
+                });
+        });
+    }
+}
@@ -0,0 +1,197 @@
+use crate::{
+    feature,
+    nes::{
+        config::{Config, PathsConfig},
+        event::{NesEventProxy, UiEvent},
+        renderer::gui::preferences::Preferences,
+    },
+};
+use anyhow::Context as _;
+use egui::{Align2, Color32, Context, RichText, Ui, Vec2};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fmt::Write, path::PathBuf};
+use tetanes_core::{cart::NesHeader, fs};
+
+/// How many times ROMs failed to load for each unsupported mapper number. Only recorded when
+/// opted in, and contains no information about which ROMs were loaded.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[must_use]
+#[serde(default)] // Ensures new fields don't break existing stats
+pub struct MapperStats {
+    counts: BTreeMap<u16, u32>,
+}
+
+impl MapperStats {
+    const FILE: &'static str = "unsupported_mappers.json";
+
+    fn path(paths: &PathsConfig) -> PathBuf {
+        paths.data_dir().join(Self::FILE)
+    }
+
+    pub fn load(paths: &PathsConfig) -> anyhow::Result<Self> {
+        let path = Self::path(paths);
+        if !fs::exists(&path) {
+            return Ok(Self::default());
+        }
+        let data = fs::load_raw(&path).context("failed to load mapper stats")?;
+        serde_json::from_slice(&data).with_context(|| format!("failed to parse {path:?}"))
+    }
+
+    /// Increment the count for `mapper_num` and save the result.
+    pub fn record(paths: &PathsConfig, mapper_num: u16) -> anyhow::Result<()> {
+        let mut stats = Self::load(paths)?;
+        *stats.counts.entry(mapper_num).or_default() += 1;
+        let data = serde_json::to_vec_pretty(&stats).context("failed to serialize mapper stats")?;
+        fs::save_raw(Self::path(paths), &data).context("failed to save mapper stats")?;
+        Ok(())
+    }
+
+    /// Plain-text report of the most requested mappers, suitable for attaching to a feature
+    /// request.
+    pub fn report(&self) -> String {
+        let mut text = format!(
+            "TetaNES {} - Unsupported Mappers\n\n",
+            env!("CARGO_PKG_VERSION")
+        );
+        if self.counts.is_empty() {
+            text.push_str("No unsupported mappers recorded.\n");
+            return text;
+        }
+        let mut counts = self.counts.iter().collect::<Vec<_>>();
+        counts.sort_by(|(_, a), (_, b)| b.cmp(a));
+        for (mapper_num, count) in counts {
+            let _ = writeln!(
+                text,
+                "{} (mapper {mapper_num}): {count} attempted loads",
+                NesHeader::mapper_board(*mapper_num)
+            );
+        }
+        text
+    }
+}
+
+/// ROM that failed to load because its mapper isn't implemented.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[must_use]
+struct UnsupportedRom {
+    name: String,
+    mapper_num: u16,
+}
+
+/// Dialog explaining that a ROM uses a mapper that isn't supported yet.
+#[derive(Default, Debug)]
+#[must_use]
+pub struct UnsupportedMapper {
+    rom: Option<UnsupportedRom>,
+    status: Option<(String, Color32)>,
+}
+
+impl UnsupportedMapper {
+    const ISSUES_URL: &'static str = "https://github.com/lukexor/tetanes/issues";
+
+    pub fn open(&mut self, name: impl Into<String>, mapper_num: u16) {
+        self.rom = Some(UnsupportedRom {
+            name: name.into(),
+            mapper_num,
+        });
+        self.status = None;
+    }
+
+    pub fn show(&mut self, ctx: &Context, tx: &NesEventProxy, cfg: &Config) {
+        #[cfg(feature = "profiling")]
+        puffin::profile_function!();
+
+        if self.rom.is_none() {
+            return;
+        }
+
+        let mut open = true;
+        let mut close_window = false;
+        egui::Window::new("⚠ Unsupported Mapper")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(Align2::CENTER_CENTER, Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.set_max_width(480.0);
+                close_window = self.ui(ui, tx, cfg);
+            });
+        if !open || close_window {
+            self.rom = None;
+        }
+    }
+
+    /// Returns whether the dialog should be closed.
+    fn ui(&mut self, ui: &mut Ui, tx: &NesEventProxy, cfg: &Config) -> bool {
+        let Some(rom) = &self.rom else {
+            return true;
+        };
+
+        ui.label(format!(
+            "{:?} can't be loaded because its mapper isn't supported yet.",
+            rom.name
+        ));
+        ui.add_space(8.0);
+
+        egui::Grid::new("unsupported_mapper")
+            .num_columns(2)
+            .show(ui, |ui| {
+                ui.strong("Mapper:");
+                ui.label(rom.mapper_num.to_string());
+                ui.end_row();
+
+                ui.strong("Board:");
+                ui.label(NesHeader::mapper_board(rom.mapper_num));
+                ui.end_row();
+            });
+        ui.add_space(8.0);
+
+        ui.horizontal(|ui| {
+            ui.hyperlink_to(
+                "Find Tracking Issues",
+                format!(
+                    "{}?q=is%3Aissue+mapper+{}",
+                    Self::ISSUES_URL,
+                    rom.mapper_num
+                ),
+            );
+            ui.label("|");
+            ui.hyperlink_to("Request Support", format!("{}/new", Self::ISSUES_URL));
+        });
+        ui.add_space(8.0);
+
+        let mapper_stats = cfg.renderer.mapper_stats;
+        let res = Preferences::mapper_stats_checkbox(tx, ui, mapper_stats);
+        if res.clicked() && !mapper_stats {
+            // Include the ROM that prompted opting in
+            if let Err(err) = MapperStats::record(&cfg.paths, rom.mapper_num) {
+                self.status = Some((format!("{err:?}"), Color32::RED));
+            }
+        }
+
+        let mut close_window = false;
+        ui.horizontal(|ui| {
+            if ui.button("📋 Copy Report").clicked() {
+                match MapperStats::load(&cfg.paths) {
+                    Ok(stats) => {
+                        ui.ctx().copy_text(stats.report());
+                        self.status =
+                            Some(("Copied report to clipboard.".to_string(), Color32::GRAY));
+                    }
+                    Err(err) => self.status = Some((format!("{err:?}"), Color32::RED)),
+                }
+            }
+            if feature!(Filesystem) && ui.button("💾 Export Report...").clicked() {
+                tx.event(UiEvent::ExportMapperReportDialog);
+            }
+            if ui.button("OK").clicked() {
+                close_window = true;
+            }
+        });
+        if let Some((status, color)) = &self.status {
+            ui.label(RichText::new(status).color(*color));
+        }
+
+        close_window
+    }
+}
@@ -1,21 +1,85 @@
-use crate::nes::renderer::shader::{self, Shader};
+use crate::nes::renderer::shader::{self, ColorBlindMode, Shader, Transition};
 use anyhow::{anyhow, Context};
 use egui::{
     ahash::HashMap,
     epaint::{self, Primitive, Vertex},
     NumExt, ViewportId, ViewportIdMap, ViewportIdSet,
 };
+use serde::{Deserialize, Serialize};
 use std::{
     borrow::Cow,
     collections::hash_map::Entry,
     iter,
     num::{NonZeroU32, NonZeroU64},
     ops::{Deref, Range},
-    sync::Arc,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
 };
+use tetanes_core::time::Instant;
 use wgpu::util::DeviceExt;
 use winit::{dpi::PhysicalSize, window::Window};
 
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[must_use]
+pub enum PresentMode {
+    /// Wait for vertical blank, presenting late frames immediately if supported to avoid
+    /// stuttering. Also known as adaptive vsync.
+    #[default]
+    Vsync,
+    /// Always wait for vertical blank, queuing frames if the display can't keep up.
+    Fifo,
+    /// Wait for vertical blank, replacing any queued frame with the latest one. Also known as
+    /// triple-buffering.
+    Mailbox,
+    /// Present frames immediately, which may result in tearing.
+    Immediate,
+}
+
+impl PresentMode {
+    pub const fn as_slice() -> &'static [Self] {
+        &[Self::Vsync, Self::Fifo, Self::Mailbox, Self::Immediate]
+    }
+
+    const fn to_wgpu(self) -> wgpu::PresentMode {
+        match self {
+            Self::Vsync => wgpu::PresentMode::AutoVsync,
+            Self::Fifo => wgpu::PresentMode::Fifo,
+            Self::Mailbox => wgpu::PresentMode::Mailbox,
+            Self::Immediate => wgpu::PresentMode::Immediate,
+        }
+    }
+
+    /// Whether this mode can be used with the `supported` surface present modes. `Vsync` is
+    /// always supported as it falls back to `Fifo`.
+    fn is_supported(&self, supported: &[wgpu::PresentMode]) -> bool {
+        *self == Self::Vsync || supported.contains(&self.to_wgpu())
+    }
+
+    /// Resolve to a `wgpu::PresentMode`, falling back to `Vsync` if not supported.
+    fn resolve(&self, supported: &[wgpu::PresentMode]) -> wgpu::PresentMode {
+        let mode = self.to_wgpu();
+        if self.is_supported(supported) {
+            mode
+        } else {
+            tracing::warn!("present mode {mode:?} is not supported, falling back to vsync");
+            wgpu::PresentMode::AutoVsync
+        }
+    }
+}
+
+impl AsRef<str> for PresentMode {
+    fn as_ref(&self) -> &str {
+        match self {
+            Self::Vsync => "Vsync",
+            Self::Fifo => "Fifo",
+            Self::Mailbox => "Mailbox",
+            Self::Immediate => "Immediate",
+        }
+    }
+}
+
 #[derive(Debug)]
 #[must_use]
 pub struct Surface {
@@ -69,8 +133,13 @@ impl Surface {
         format: wgpu::TextureFormat,
         uniform_bind_group_layout: &wgpu::BindGroupLayout,
         shader: Shader,
+        color_blind_mode: ColorBlindMode,
+        transition: Transition,
     ) {
-        if matches!(shader, Shader::None) {
+        if matches!(shader, Shader::None)
+            && !color_blind_mode.is_enabled()
+            && !transition.is_enabled()
+        {
             self.shader_resources = None;
         } else {
             self.shader_resources = Some(shader::Resources::new(
@@ -117,12 +186,68 @@ impl Painter {
     pub fn set_shader(&mut self, shader: Shader) {
         if let Some(render_state) = &mut self.render_state {
             render_state.shader = shader;
+            self.update_surface_shaders();
+        }
+    }
+
+    pub fn set_color_blind_mode(&mut self, color_blind_mode: ColorBlindMode) {
+        if let Some(render_state) = &mut self.render_state {
+            render_state.color_blind_mode = color_blind_mode;
+            self.update_surface_shaders();
+        }
+    }
+
+    pub fn set_transition(&mut self, transition: Transition) {
+        if let Some(render_state) = &mut self.render_state {
+            render_state.transition = transition;
+            render_state.transition_start = None;
+            self.update_surface_shaders();
+        }
+    }
+
+    /// Start playing the configured transition over the main window, if any.
+    pub fn start_transition(&mut self) {
+        if let Some(render_state) = &mut self.render_state {
+            if render_state.transition.is_enabled() {
+                render_state.transition_start = Some(Instant::now());
+            }
+        }
+    }
+
+    /// Whether a transition is playing, requiring the main window to be repainted every frame.
+    #[must_use]
+    pub fn transition_active(&self) -> bool {
+        self.render_state
+            .as_ref()
+            .is_some_and(|render_state| render_state.transition_progress().is_some())
+    }
+
+    pub fn set_present_mode(&mut self, present_mode: PresentMode) {
+        if let Some(render_state) = &mut self.render_state {
+            if render_state.present_mode != present_mode {
+                render_state.present_mode = present_mode;
+                for surface in self.surfaces.values_mut() {
+                    if let (Some(width), Some(height)) = (
+                        NonZeroU32::new(surface.width),
+                        NonZeroU32::new(surface.height),
+                    ) {
+                        render_state.resize_surface(surface, width, height);
+                    }
+                }
+            }
+        }
+    }
+
+    fn update_surface_shaders(&mut self) {
+        if let Some(render_state) = &self.render_state {
             for surface in self.surfaces.values_mut() {
                 surface.set_shader(
                     &render_state.device,
                     render_state.format,
                     &render_state.uniform_bind_group_layout,
-                    shader,
+                    render_state.shader,
+                    render_state.color_blind_mode,
+                    render_state.transition,
                 );
             }
         }
@@ -185,6 +310,16 @@ impl Painter {
                     label: Some("encoder"),
                 });
 
+        // Only time the main window so that secondary viewports don't skew the results
+        let timed = match &mut render_state.gpu_timer {
+            Some(gpu_timer) if viewport_id == ViewportId::ROOT => {
+                gpu_timer.poll(&render_state.device);
+                !gpu_timer.pending()
+            }
+            _ => false,
+        };
+        let has_shader_pass = surface.shader_resources.is_some();
+
         // Upload all resources for the GPU.
 
         let size_in_pixels = [surface.width, surface.height];
@@ -196,7 +331,11 @@ impl Painter {
         for (id, image_delta) in &textures_delta.set {
             render_state.update_texture(*id, image_delta);
         }
-        render_state.update_buffers(clipped_primitives, &screen_descriptor);
+        // Only play transitions over the main window
+        let transition = (viewport_id == ViewportId::ROOT)
+            .then(|| render_state.transition_progress())
+            .flatten();
+        render_state.update_buffers(clipped_primitives, &screen_descriptor, transition);
 
         let output_frame = match surface.get_current_texture() {
             Ok(frame) => frame,
@@ -226,7 +365,13 @@ impl Painter {
                     },
                 })],
                 depth_stencil_attachment: None,
-                timestamp_writes: None,
+                timestamp_writes: render_state.gpu_timer.as_ref().filter(|_| timed).map(
+                    |gpu_timer| wgpu::RenderPassTimestampWrites {
+                        query_set: &gpu_timer.query_set,
+                        beginning_of_pass_write_index: Some(0),
+                        end_of_pass_write_index: (!has_shader_pass).then_some(1),
+                    },
+                ),
                 occlusion_query_set: None,
             });
 
@@ -249,7 +394,13 @@ impl Painter {
                     },
                 })],
                 depth_stencil_attachment: None,
-                timestamp_writes: None,
+                timestamp_writes: render_state.gpu_timer.as_ref().filter(|_| timed).map(
+                    |gpu_timer| wgpu::RenderPassTimestampWrites {
+                        query_set: &gpu_timer.query_set,
+                        beginning_of_pass_write_index: None,
+                        end_of_pass_write_index: Some(1),
+                    },
+                ),
                 occlusion_query_set: None,
             });
 
@@ -272,11 +423,77 @@ impl Painter {
             render_state.textures.remove(id);
         }
 
+        if let Some(gpu_timer) = render_state.gpu_timer.as_ref().filter(|_| timed) {
+            gpu_timer.resolve(&mut encoder);
+        }
+
+        render_state.queue.submit(iter::once(encoder.finish()));
+
+        if let Some(gpu_timer) = render_state.gpu_timer.as_mut().filter(|_| timed) {
+            gpu_timer.map();
+        }
+
+        output_frame.present();
+    }
+
+    /// Present a solid black frame, for black frame insertion.
+    pub fn paint_black(&mut self, viewport_id: ViewportId) {
+        let Some(render_state) = &self.render_state else {
+            return;
+        };
+        let Some(surface) = self.surfaces.get(&viewport_id) else {
+            return;
+        };
+
+        let output_frame = match surface.get_current_texture() {
+            Ok(frame) => frame,
+            Err(err) => {
+                if err != wgpu::SurfaceError::Outdated {
+                    tracing::error!("failed to acquire next frame: {:?}", err);
+                }
+                return;
+            }
+        };
+
+        let mut encoder =
+            render_state
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                    label: Some("black frame encoder"),
+                });
+        {
+            let view = &output_frame
+                .texture
+                .create_view(&wgpu::TextureViewDescriptor::default());
+            encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("black_frame_render_pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+        }
         render_state.queue.submit(iter::once(encoder.finish()));
 
         output_frame.present();
     }
 
+    /// GPU time spent drawing the most recently timed frame of the main window, if timestamp
+    /// queries are supported.
+    pub fn gpu_time(&self) -> Option<f32> {
+        self.render_state
+            .as_ref()
+            .and_then(|render_state| render_state.gpu_timer.as_ref())
+            .and_then(|gpu_timer| gpu_timer.last_time_ms)
+    }
+
     pub const fn render_state(&self) -> Option<&RenderState> {
         self.render_state.as_ref()
     }
@@ -313,12 +530,111 @@ struct SlicedBuffer {
     capacity: wgpu::BufferAddress,
 }
 
+/// Measures how long the GPU takes to draw a frame using timestamp queries.
+#[derive(Debug)]
+#[must_use]
+struct GpuTimer {
+    query_set: wgpu::QuerySet,
+    resolve_buffer: wgpu::Buffer,
+    readback_buffer: wgpu::Buffer,
+    /// Nanoseconds per timestamp tick.
+    period: f32,
+    /// Set while the readback buffer is being mapped.
+    mapping: bool,
+    mapped: Arc<AtomicBool>,
+    last_time_ms: Option<f32>,
+}
+
+impl GpuTimer {
+    const SIZE: wgpu::BufferAddress = 2 * std::mem::size_of::<u64>() as wgpu::BufferAddress;
+
+    fn new(device: &wgpu::Device, queue: &wgpu::Queue) -> Self {
+        let query_set = device.create_query_set(&wgpu::QuerySetDescriptor {
+            label: Some("gpu timer query set"),
+            ty: wgpu::QueryType::Timestamp,
+            count: 2,
+        });
+        let resolve_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("gpu timer resolve buffer"),
+            size: Self::SIZE,
+            usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let readback_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("gpu timer readback buffer"),
+            size: Self::SIZE,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        Self {
+            query_set,
+            resolve_buffer,
+            readback_buffer,
+            period: queue.get_timestamp_period(),
+            mapping: false,
+            mapped: Arc::new(AtomicBool::new(false)),
+            last_time_ms: None,
+        }
+    }
+
+    /// Whether the previous timing is still being read back, in which case this frame isn't
+    /// timed.
+    const fn pending(&self) -> bool {
+        self.mapping
+    }
+
+    fn resolve(&self, encoder: &mut wgpu::CommandEncoder) {
+        encoder.resolve_query_set(&self.query_set, 0..2, &self.resolve_buffer, 0);
+        encoder.copy_buffer_to_buffer(
+            &self.resolve_buffer,
+            0,
+            &self.readback_buffer,
+            0,
+            Self::SIZE,
+        );
+    }
+
+    fn map(&mut self) {
+        self.mapping = true;
+        let mapped = Arc::clone(&self.mapped);
+        self.readback_buffer
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |res| {
+                if res.is_ok() {
+                    mapped.store(true, Ordering::Release);
+                }
+            });
+    }
+
+    /// Read back the previous timing, if it's ready.
+    fn poll(&mut self, device: &wgpu::Device) {
+        if !self.mapping {
+            return;
+        }
+        device.poll(wgpu::Maintain::Poll);
+        if !self.mapped.swap(false, Ordering::Acquire) {
+            return;
+        }
+        {
+            let data = self.readback_buffer.slice(..).get_mapped_range();
+            let timestamps: &[u64] = bytemuck::cast_slice(&data);
+            if let [start, end] = timestamps {
+                let ticks = end.wrapping_sub(*start);
+                self.last_time_ms = Some(ticks as f32 * self.period / 1_000_000.0);
+            }
+        }
+        self.readback_buffer.unmap();
+        self.mapping = false;
+    }
+}
+
 #[derive(Debug)]
 #[must_use]
 pub struct RenderState {
     pub device: wgpu::Device,
     pub queue: wgpu::Queue,
     pub format: wgpu::TextureFormat,
+    pub adapter_info: wgpu::AdapterInfo,
 
     pipeline: wgpu::RenderPipeline,
 
@@ -332,6 +648,14 @@ pub struct RenderState {
     texture_bind_group_layout: wgpu::BindGroupLayout,
 
     shader: Shader,
+    color_blind_mode: ColorBlindMode,
+    transition: Transition,
+    /// When the playing transition started, if any.
+    transition_start: Option<Instant>,
+    present_mode: PresentMode,
+    supported_present_modes: Vec<wgpu::PresentMode>,
+    /// Not all adapters support timestamp queries, e.g. WebGL.
+    gpu_timer: Option<GpuTimer>,
     /// Map of egui texture IDs to textures and their associated bindgroups (texture view +
     /// sampler). The texture may be None if the `TextureId` is just a handle to a user-provided
     /// sampler.
@@ -363,6 +687,8 @@ impl RenderState {
         };
         let device_descriptor = wgpu::DeviceDescriptor {
             label: Some("wgpu device"),
+            // Used to show GPU frame timing in performance stats
+            required_features: adapter.features() & wgpu::Features::TIMESTAMP_QUERY,
             // TODO: maybe CLEAR_TEXTURE?
             required_limits: wgpu::Limits {
                 max_texture_dimension_2d: 8192,
@@ -407,6 +733,11 @@ impl RenderState {
         let (device, queue) =
             connection.map_err(|err| anyhow!("failed to create wgpu device: {err:?}"))?;
 
+        let gpu_timer = device
+            .features()
+            .contains(wgpu::Features::TIMESTAMP_QUERY)
+            .then(|| GpuTimer::new(&device, &queue));
+
         let shader_module_desc =
             wgpu::include_wgsl!(concat!(env!("CARGO_MANIFEST_DIR"), "/shaders/gui.wgsl"));
         let shader_module = device.create_shader_module(shader_module_desc);
@@ -542,6 +873,7 @@ impl RenderState {
             device,
             queue,
             format,
+            adapter_info: adapter.get_info(),
 
             pipeline,
 
@@ -555,12 +887,27 @@ impl RenderState {
             texture_bind_group_layout,
 
             shader: Shader::default(),
+            color_blind_mode: ColorBlindMode::default(),
+            transition: Transition::default(),
+            transition_start: None,
+            present_mode: PresentMode::default(),
+            supported_present_modes: capabilities.present_modes,
+            gpu_timer,
             textures: Default::default(),
             next_texture_id: 0,
             samplers: Default::default(),
         })
     }
 
+    /// Present modes supported by the graphics adapter.
+    pub fn supported_present_modes(&self) -> Vec<PresentMode> {
+        PresentMode::as_slice()
+            .iter()
+            .copied()
+            .filter(|mode| mode.is_supported(&self.supported_present_modes))
+            .collect()
+    }
+
     pub fn max_texture_side(&self) -> u32 {
         self.device.limits().max_texture_dimension_2d
     }
@@ -622,8 +969,7 @@ impl RenderState {
                 format: self.format,
                 width: width.get(),
                 height: height.get(),
-                // TODO: Support disabling vsync
-                present_mode: wgpu::PresentMode::AutoVsync,
+                present_mode: self.present_mode.resolve(&self.supported_present_modes),
                 desired_maximum_frame_latency: 2,
                 alpha_mode: wgpu::CompositeAlphaMode::Auto,
                 view_formats: vec![self.format],
@@ -634,9 +980,23 @@ impl RenderState {
             self.format,
             &self.uniform_bind_group_layout,
             self.shader,
+            self.color_blind_mode,
+            self.transition,
         );
     }
 
+    /// The playing transition and how far through it is, from `0.0` to `1.0`.
+    fn transition_progress(&self) -> Option<(Transition, f32)> {
+        let elapsed = self.transition_start?.elapsed();
+        let duration = self.transition.duration();
+        (elapsed < duration).then(|| {
+            (
+                self.transition,
+                elapsed.as_secs_f32() / duration.as_secs_f32(),
+            )
+        })
+    }
+
     pub fn update_texture(&mut self, id: epaint::TextureId, image_delta: &epaint::ImageDelta) {
         let width = image_delta.image.width() as u32;
         let height = image_delta.image.height() as u32;
@@ -747,11 +1107,16 @@ impl RenderState {
         &mut self,
         paint_jobs: &[epaint::ClippedPrimitive],
         screen_descriptor: &ScreenDescriptor,
+        transition: Option<(Transition, f32)>,
     ) {
         let screen_size_in_points = screen_descriptor.screen_size_in_points();
 
+        let (transition, transition_progress) = transition.unwrap_or_default();
         let uniform_buffer_content = UniformBuffer {
             screen_size_in_points,
+            color_blind_mode: self.color_blind_mode.into(),
+            transition: transition.into(),
+            transition_progress,
             _padding: Default::default(),
         };
         if uniform_buffer_content != self.previous_uniform_buffer_content {
@@ -957,14 +1322,20 @@ impl RenderState {
 #[repr(C)]
 struct UniformBuffer {
     screen_size_in_points: [f32; 2],
-    // Uniform buffers need to be at least 16 bytes in WebGL.
+    color_blind_mode: u32,
+    transition: u32,
+    transition_progress: f32,
+    // Uniform buffers need to be padded to 16 bytes in WebGL.
     // See https://github.com/gfx-rs/wgpu/issues/2072
-    _padding: [u32; 2],
+    _padding: [u32; 3],
 }
 
 impl PartialEq for UniformBuffer {
     fn eq(&self, other: &Self) -> bool {
         self.screen_size_in_points == other.screen_size_in_points
+            && self.color_blind_mode == other.color_blind_mode
+            && self.transition == other.transition
+            && self.transition_progress == other.transition_progress
     }
 }
 
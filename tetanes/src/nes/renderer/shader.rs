@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use tetanes_core::time::Duration;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -41,6 +42,136 @@ impl TryFrom<usize> for Shader {
     }
 }
 
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[must_use]
+pub enum ColorBlindMode {
+    #[default]
+    None,
+    Deuteranopia,
+    Protanopia,
+    Tritanopia,
+}
+
+impl ColorBlindMode {
+    pub const fn as_slice() -> &'static [Self] {
+        &[
+            Self::None,
+            Self::Deuteranopia,
+            Self::Protanopia,
+            Self::Tritanopia,
+        ]
+    }
+
+    /// Whether a color correction pass is required.
+    #[must_use]
+    pub const fn is_enabled(&self) -> bool {
+        !matches!(self, Self::None)
+    }
+}
+
+impl AsRef<str> for ColorBlindMode {
+    fn as_ref(&self) -> &str {
+        match self {
+            Self::None => "None",
+            Self::Deuteranopia => "Deuteranopia",
+            Self::Protanopia => "Protanopia",
+            Self::Tritanopia => "Tritanopia",
+        }
+    }
+}
+
+impl From<ColorBlindMode> for u32 {
+    fn from(mode: ColorBlindMode) -> Self {
+        // Must match the modes in `daltonize` in the wgsl shaders
+        match mode {
+            ColorBlindMode::None => 0,
+            ColorBlindMode::Deuteranopia => 1,
+            ColorBlindMode::Protanopia => 2,
+            ColorBlindMode::Tritanopia => 3,
+        }
+    }
+}
+
+/// Cosmetic effect played over the main window when loading a ROM, resetting or loading a state.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[must_use]
+pub enum Transition {
+    #[default]
+    None,
+    /// Columns of black melting down the screen at different speeds, revealing the game.
+    Melt,
+    /// Fade in from black.
+    Fade,
+    /// The picture collapsing into a line like a CRT turning off, then expanding back.
+    PowerOff,
+}
+
+impl Transition {
+    pub const fn as_slice() -> &'static [Self] {
+        &[Self::None, Self::Melt, Self::Fade, Self::PowerOff]
+    }
+
+    /// Whether a transition pass is required.
+    #[must_use]
+    pub const fn is_enabled(&self) -> bool {
+        !matches!(self, Self::None)
+    }
+
+    /// How long the transition plays for.
+    pub const fn duration(&self) -> Duration {
+        match self {
+            Self::None => Duration::ZERO,
+            Self::Melt => Duration::from_millis(900),
+            Self::Fade => Duration::from_millis(400),
+            Self::PowerOff => Duration::from_millis(600),
+        }
+    }
+}
+
+impl AsRef<str> for Transition {
+    fn as_ref(&self) -> &str {
+        match self {
+            Self::None => "None",
+            Self::Melt => "Melt",
+            Self::Fade => "Fade",
+            Self::PowerOff => "CRT Power Off",
+        }
+    }
+}
+
+impl From<Transition> for u32 {
+    fn from(transition: Transition) -> Self {
+        // Must match the transitions in `transition` in the wgsl shaders
+        match transition {
+            Transition::None => 0,
+            Transition::Melt => 1,
+            Transition::Fade => 2,
+            Transition::PowerOff => 3,
+        }
+    }
+}
+
+/// Include a shader from the `shaders` directory with the color-blind correction shared by all
+/// shaders appended.
+macro_rules! include_shader {
+    ($name:literal) => {
+        wgpu::ShaderModuleDescriptor {
+            label: Some($name),
+            source: wgpu::ShaderSource::Wgsl(
+                concat!(
+                    include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/shaders/", $name)),
+                    "\n",
+                    include_str!(concat!(
+                        env!("CARGO_MANIFEST_DIR"),
+                        "/shaders/daltonize.wgsl"
+                    )),
+                )
+                .into(),
+            ),
+        }
+    };
+}
+
 #[derive(Debug)]
 #[must_use]
 pub struct Resources {
@@ -58,11 +189,9 @@ impl Resources {
         shader: Shader,
     ) -> Self {
         let shader_module_desc = match shader {
-            Shader::None => panic!("No shader selected"),
-            Shader::CrtEasymode => wgpu::include_wgsl!(concat!(
-                env!("CARGO_MANIFEST_DIR"),
-                "/shaders/crt-easymode.wgsl"
-            )),
+            // Only used to apply color correction when no other shader is selected
+            Shader::None => include_shader!("color-correction.wgsl"),
+            Shader::CrtEasymode => include_shader!("crt-easymode.wgsl"),
         };
         let shader_module = device.create_shader_module(shader_module_desc);
 
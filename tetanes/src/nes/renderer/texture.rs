@@ -1,5 +1,135 @@
 use crate::nes::renderer::painter::RenderState;
-use egui::{load::SizedTexture, TextureId, Vec2};
+use egui::{epaint::Vertex, load::SizedTexture, pos2, Color32, Mesh, Pos2, Rect, TextureId, Vec2};
+use serde::{Deserialize, Serialize};
+
+/// Clockwise rotation of the NES output, for games designed for rotated displays.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[must_use]
+pub enum Rotation {
+    #[default]
+    None,
+    Rotate90,
+    Rotate180,
+    Rotate270,
+}
+
+impl Rotation {
+    pub const fn as_slice() -> &'static [Self] {
+        &[Self::None, Self::Rotate90, Self::Rotate180, Self::Rotate270]
+    }
+
+    /// Whether the output width and height are swapped.
+    #[must_use]
+    pub const fn is_sideways(&self) -> bool {
+        matches!(self, Self::Rotate90 | Self::Rotate270)
+    }
+
+    /// Output size of a texture with the given `size` after rotation.
+    pub const fn rotate_size(&self, size: Vec2) -> Vec2 {
+        if self.is_sideways() {
+            Vec2::new(size.y, size.x)
+        } else {
+            size
+        }
+    }
+
+    /// Map a normalized `0..=1` position in the output to a normalized position in the texture,
+    /// mirroring the output first if `flip_horizontal` is set.
+    pub fn output_to_texture(&self, pos: Pos2, flip_horizontal: bool) -> Pos2 {
+        let x = if flip_horizontal { 1.0 - pos.x } else { pos.x };
+        let y = pos.y;
+        match self {
+            Self::None => pos2(x, y),
+            Self::Rotate90 => pos2(y, 1.0 - x),
+            Self::Rotate180 => pos2(1.0 - x, 1.0 - y),
+            Self::Rotate270 => pos2(1.0 - y, x),
+        }
+    }
+}
+
+impl AsRef<str> for Rotation {
+    fn as_ref(&self) -> &str {
+        match self {
+            Self::None => "None",
+            Self::Rotate90 => "90°",
+            Self::Rotate180 => "180°",
+            Self::Rotate270 => "270°",
+        }
+    }
+}
+
+/// How a texture is sampled when drawn at a size other than its own.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[must_use]
+pub enum TextureFilter {
+    /// Sharp pixels, but uneven pixel sizes at non-integer scales cause shimmering when
+    /// scrolling.
+    #[default]
+    Nearest,
+    /// Even, but blurry pixels.
+    Bilinear,
+    /// Scales with nearest to the largest integer multiple that fits and bilinear filters the
+    /// remainder, keeping pixels sharp and even.
+    SharpBilinear,
+}
+
+impl TextureFilter {
+    /// Largest integer scale applied before bilinear filtering, which bounds texture memory and
+    /// upload size on large displays. Past this, the remaining blur is under a quarter pixel.
+    const MAX_PRESCALE: u32 = 4;
+
+    pub const fn as_slice() -> &'static [Self] {
+        &[Self::Nearest, Self::Bilinear, Self::SharpBilinear]
+    }
+
+    pub const fn filter_mode(&self) -> wgpu::FilterMode {
+        match self {
+            Self::Nearest => wgpu::FilterMode::Nearest,
+            Self::Bilinear | Self::SharpBilinear => wgpu::FilterMode::Linear,
+        }
+    }
+
+    /// Integer scale to apply with nearest filtering to a texture of `texture_scale` drawn at
+    /// `display_scale` physical pixels per texel of its unscaled size.
+    #[must_use]
+    pub fn prescale(&self, display_scale: f32, texture_scale: u32) -> u32 {
+        match self {
+            Self::Nearest | Self::Bilinear => 1,
+            Self::SharpBilinear => {
+                let prescale = (display_scale / texture_scale as f32).floor() as u32;
+                prescale.clamp(1, Self::MAX_PRESCALE)
+            }
+        }
+    }
+}
+
+impl AsRef<str> for TextureFilter {
+    fn as_ref(&self) -> &str {
+        match self {
+            Self::Nearest => "Nearest",
+            Self::Bilinear => "Bilinear",
+            Self::SharpBilinear => "Sharp Bilinear",
+        }
+    }
+}
+
+/// Scale an RGBA image of `width` x `height` pixels by an integer `scale` by repeating pixels.
+pub fn scale_nearest(bytes: &[u8], width: u32, height: u32, scale: u32, output: &mut Vec<u8>) {
+    let (width, height, scale) = (width as usize, height as usize, scale as usize);
+    output.clear();
+    output.reserve(bytes.len() * scale * scale);
+    for row in bytes.chunks_exact(4 * width).take(height) {
+        let start = output.len();
+        for pixel in row.chunks_exact(4) {
+            for _ in 0..scale {
+                output.extend_from_slice(pixel);
+            }
+        }
+        for _ in 1..scale {
+            output.extend_from_within(start..start + 4 * width * scale);
+        }
+    }
+}
 
 #[derive(Debug)]
 #[must_use]
@@ -20,6 +150,31 @@ impl Texture {
         aspect_ratio: f32,
         label: Option<&'static str>,
     ) -> Self {
+        Self::with_scale(
+            render_state,
+            size,
+            1,
+            TextureFilter::Nearest,
+            aspect_ratio,
+            label,
+        )
+    }
+
+    /// Create a texture holding frames upscaled by `scale`, which is drawn at the same output
+    /// size as an unscaled texture.
+    pub fn with_scale(
+        render_state: &mut RenderState,
+        size: Vec2,
+        scale: u32,
+        filter: TextureFilter,
+        aspect_ratio: f32,
+        label: Option<&'static str>,
+    ) -> Self {
+        let output_size = Vec2 {
+            x: size.x * aspect_ratio,
+            y: size.y,
+        };
+        let size = size * scale as f32;
         let max_texture_side = render_state.max_texture_side() as f32;
         let texture = render_state
             .device
@@ -48,8 +203,8 @@ impl Texture {
             address_mode_u: wgpu::AddressMode::ClampToEdge,
             address_mode_v: wgpu::AddressMode::ClampToEdge,
             address_mode_w: wgpu::AddressMode::ClampToEdge,
-            mag_filter: wgpu::FilterMode::Nearest,
-            min_filter: wgpu::FilterMode::Nearest,
+            mag_filter: filter.filter_mode(),
+            min_filter: filter.filter_mode(),
             mipmap_filter: wgpu::FilterMode::Nearest,
             ..Default::default()
         };
@@ -59,10 +214,7 @@ impl Texture {
             label,
             texture,
             size,
-            output_size: Vec2 {
-                x: size.x * aspect_ratio,
-                y: size.y,
-            },
+            output_size,
             view,
             aspect_ratio,
             id,
@@ -73,10 +225,53 @@ impl Texture {
         *self = Self::new(render_state, size, aspect_ratio, self.label);
     }
 
+    pub fn resize_with_scale(
+        &mut self,
+        render_state: &mut RenderState,
+        size: Vec2,
+        scale: u32,
+        filter: TextureFilter,
+        aspect_ratio: f32,
+    ) {
+        *self = Self::with_scale(render_state, size, scale, filter, aspect_ratio, self.label);
+    }
+
     pub fn sized(&self) -> SizedTexture {
         SizedTexture::new(self.id, self.output_size)
     }
 
+    /// A mesh drawing this texture into `rect` with the given orientation.
+    pub fn oriented_mesh(
+        &self,
+        rect: Rect,
+        rotation: Rotation,
+        flip_horizontal: bool,
+        tint: Color32,
+    ) -> Mesh {
+        let mut mesh = Mesh::with_texture(self.id);
+        let corners = [
+            rect.left_top(),
+            rect.right_top(),
+            rect.right_bottom(),
+            rect.left_bottom(),
+        ];
+        for (pos, output) in corners.into_iter().zip([
+            pos2(0.0, 0.0),
+            pos2(1.0, 0.0),
+            pos2(1.0, 1.0),
+            pos2(0.0, 1.0),
+        ]) {
+            mesh.vertices.push(Vertex {
+                pos,
+                uv: rotation.output_to_texture(output, flip_horizontal),
+                color: tint,
+            });
+        }
+        mesh.add_triangle(0, 1, 2);
+        mesh.add_triangle(0, 2, 3);
+        mesh
+    }
+
     pub fn update(&self, queue: &wgpu::Queue, bytes: &[u8]) {
         self.update_partial(queue, bytes, Vec2::ZERO, self.size);
     }
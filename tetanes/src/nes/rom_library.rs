@@ -0,0 +1,313 @@
+//! Watching of the ROM directory in the background, keeping the ROM library and recently played
+//! ROMs up to date as files are added, removed or modified.
+
+use crate::nes::{
+    config::PathsConfig,
+    event::{ConfigEvent, NesEventProxy, RendererEvent},
+};
+use anyhow::Context;
+use notify_debouncer_mini::{
+    new_debouncer,
+    notify::{RecommendedWatcher, RecursiveMode},
+    DebounceEventResult, Debouncer,
+};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc::{self, Receiver},
+    },
+    time::{Duration, UNIX_EPOCH},
+};
+use tetanes_core::{
+    cart::{Cart, GameInfo},
+    fs,
+};
+use tracing::{debug, warn};
+
+/// Lists the NES ROMs in `dir`, sorted by path.
+pub fn scan(dir: &Path) -> io::Result<BTreeSet<PathBuf>> {
+    Ok(std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("nes"))
+                && path.is_file()
+        })
+        .collect())
+}
+
+/// Hashes of a ROM file in the library.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[must_use]
+pub struct RomHash {
+    /// Game database CRC32 of the PRG-ROM and CHR-ROM, if the header is valid.
+    pub crc32: Option<u32>,
+    /// SHA-1 of the whole file, as lowercase hex.
+    pub sha1: String,
+}
+
+impl RomHash {
+    fn compute(path: &Path) -> io::Result<Self> {
+        let data = std::fs::read(path)?;
+        Ok(Self {
+            crc32: Cart::rom_crc32(&data).ok(),
+            sha1: format!("{:x}", Sha1::digest(&data)),
+        })
+    }
+}
+
+/// A ROM in the library, along with its hashes and game database entry once hashed.
+#[derive(Debug, Clone)]
+#[must_use]
+pub struct LibraryRom {
+    pub path: PathBuf,
+    pub hash: Option<RomHash>,
+    pub game: Option<GameInfo>,
+}
+
+impl LibraryRom {
+    const fn unhashed(path: PathBuf) -> Self {
+        Self {
+            path,
+            hash: None,
+            game: None,
+        }
+    }
+
+    fn hashed(path: PathBuf, hash: RomHash) -> Self {
+        let game = hash.crc32.and_then(Cart::lookup_info);
+        Self {
+            path,
+            hash: Some(hash),
+            game,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[must_use]
+struct CachedHash {
+    /// File modification time in milliseconds since the Unix epoch when hashed.
+    modified: u64,
+    hash: RomHash,
+}
+
+/// ROM hashes from previous scans, keyed by path and only reused while the file modification
+/// time is unchanged.
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+#[must_use]
+#[serde(default)] // Ensures new fields don't break existing caches
+pub struct HashCache {
+    entries: BTreeMap<PathBuf, CachedHash>,
+}
+
+impl HashCache {
+    const FILENAME: &'static str = "rom_hashes.json";
+
+    pub fn path(paths: &PathsConfig) -> PathBuf {
+        paths.data_dir().join(Self::FILENAME)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        if !fs::exists(path) {
+            return Ok(Self::default());
+        }
+        let data = fs::load_raw(path).context("failed to load rom hashes")?;
+        serde_json::from_slice(&data).with_context(|| format!("failed to parse {path:?}"))
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let data = serde_json::to_vec(&self).context("failed to serialize rom hashes")?;
+        fs::save_raw(path, &data).context("failed to save rom hashes")?;
+        Ok(())
+    }
+
+    fn modified(path: &Path) -> Option<u64> {
+        let modified = std::fs::metadata(path).ok()?.modified().ok()?;
+        u64::try_from(modified.duration_since(UNIX_EPOCH).ok()?.as_millis()).ok()
+    }
+
+    fn get(&self, path: &Path, modified: u64) -> Option<&RomHash> {
+        self.entries
+            .get(path)
+            .filter(|cached| cached.modified == modified)
+            .map(|cached| &cached.hash)
+    }
+
+    fn insert(&mut self, path: PathBuf, modified: u64, hash: RomHash) {
+        self.entries.insert(path, CachedHash { modified, hash });
+    }
+
+    fn remove(&mut self, path: &Path) {
+        self.entries.remove(path);
+    }
+
+    /// Send the ROM library with the hashes of unchanged files, then hash the rest in parallel,
+    /// sending progress as it goes, and send the fully hashed library.
+    fn hash_library(&mut self, roms: &BTreeSet<PathBuf>, tx: &NesEventProxy) {
+        let roms = roms
+            .iter()
+            .map(|path| {
+                let modified = Self::modified(path);
+                let cached = modified
+                    .and_then(|modified| self.get(path, modified))
+                    .cloned();
+                (path, modified, cached)
+            })
+            .collect::<Vec<_>>();
+        tx.event(RendererEvent::RomLibrary(
+            roms.iter()
+                .map(|(path, _, cached)| match cached {
+                    Some(hash) => LibraryRom::hashed(path.to_path_buf(), hash.clone()),
+                    None => LibraryRom::unhashed(path.to_path_buf()),
+                })
+                .collect(),
+        ));
+
+        let total = roms
+            .iter()
+            .filter(|(_, _, cached)| cached.is_none())
+            .count();
+        if total == 0 {
+            return;
+        }
+        debug!("hashing {total} ROMs");
+        tx.event(RendererEvent::RomLibraryProgress((0, total)));
+
+        let hashed = AtomicUsize::new(0);
+        let hashes = roms
+            .par_iter()
+            .map_with(tx.clone(), |tx, (path, _, cached)| {
+                if cached.is_some() {
+                    return cached.clone();
+                }
+                let hash = match RomHash::compute(path) {
+                    Ok(hash) => Some(hash),
+                    Err(err) => {
+                        warn!("failed to hash ROM {path:?}: {err:?}");
+                        None
+                    }
+                };
+                let hashed = hashed.fetch_add(1, Ordering::Relaxed) + 1;
+                tx.event(RendererEvent::RomLibraryProgress((hashed, total)));
+                hash
+            })
+            .collect::<Vec<_>>();
+
+        let library = roms
+            .into_iter()
+            .zip(hashes)
+            .map(|((path, modified, _), hash)| match hash {
+                Some(hash) => {
+                    if let Some(modified) = modified {
+                        self.insert(path.clone(), modified, hash.clone());
+                    }
+                    LibraryRom::hashed(path.clone(), hash)
+                }
+                None => LibraryRom::unhashed(path.clone()),
+            })
+            .collect();
+        tx.event(RendererEvent::RomLibrary(library));
+    }
+}
+
+/// Watches a ROM directory for changes on a background thread, sending the ROM library whenever
+/// files are added, removed or modified, followed by their hashes and game database entries.
+/// Stops when dropped.
+#[derive(Debug)]
+#[must_use]
+pub struct RomWatcher {
+    dir: PathBuf,
+    _debouncer: Debouncer<RecommendedWatcher>,
+}
+
+impl RomWatcher {
+    /// How long the directory has to be quiet before changes are handled, so copying a batch of
+    /// ROMs only rescans once.
+    const DEBOUNCE_TIMEOUT: Duration = Duration::from_millis(500);
+
+    /// Start watching `dir`, sending its initial ROM library right away.
+    pub fn start(dir: PathBuf, paths: &PathsConfig, tx: NesEventProxy) -> anyhow::Result<Self> {
+        let (changes_tx, changes_rx) = mpsc::channel();
+        let mut debouncer = new_debouncer(Self::DEBOUNCE_TIMEOUT, changes_tx)
+            .context("failed to create ROM directory watcher")?;
+        debouncer
+            .watcher()
+            .watch(&dir, RecursiveMode::NonRecursive)
+            .with_context(|| format!("failed to watch ROM directory {dir:?}"))?;
+        std::thread::Builder::new()
+            .name("rom_watcher".into())
+            .spawn({
+                let dir = dir.clone();
+                let cache_path = HashCache::path(paths);
+                move || Self::watch(&dir, &cache_path, &tx, &changes_rx)
+            })
+            .context("failed to spawn ROM directory watcher thread")?;
+        Ok(Self {
+            dir,
+            _debouncer: debouncer,
+        })
+    }
+
+    /// Directory being watched.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Scan `dir` once, then again after every change until the debouncer is dropped. Modified
+    /// ROMs are re-hashed as their modification time no longer matches the [`HashCache`].
+    fn watch(
+        dir: &Path,
+        cache_path: &Path,
+        tx: &NesEventProxy,
+        changes: &Receiver<DebounceEventResult>,
+    ) {
+        debug!("watching ROM directory: {dir:?}");
+        let mut cache = HashCache::load(cache_path).unwrap_or_else(|err| {
+            warn!("{err:?}");
+            HashCache::default()
+        });
+        let mut roms = BTreeSet::new();
+        Self::rescan(dir, tx, &mut cache, cache_path, &mut roms);
+        for change in changes {
+            match change {
+                Ok(_) => Self::rescan(dir, tx, &mut cache, cache_path, &mut roms),
+                Err(err) => warn!("failed to watch ROM directory {dir:?}: {err:?}"),
+            }
+        }
+        debug!("stopped watching ROM directory: {dir:?}");
+    }
+
+    fn rescan(
+        dir: &Path,
+        tx: &NesEventProxy,
+        cache: &mut HashCache,
+        cache_path: &Path,
+        roms: &mut BTreeSet<PathBuf>,
+    ) {
+        let scanned = match scan(dir) {
+            Ok(scanned) => scanned,
+            Err(err) => {
+                // Retried on the next change
+                warn!("failed to scan ROM directory {dir:?}: {err:?}");
+                return;
+            }
+        };
+        for removed in roms.difference(&scanned) {
+            cache.remove(removed);
+            tx.event(ConfigEvent::RecentRomRemoved(removed.clone()));
+        }
+        cache.hash_library(&scanned, tx);
+        if let Err(err) = cache.save(cache_path) {
+            warn!("{err:?}");
+        }
+        *roms = scanned;
+    }
+}
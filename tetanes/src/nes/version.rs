@@ -1,11 +1,12 @@
-use std::cell::RefCell;
-
 #[cfg(not(target_arch = "wasm32"))]
 mod fetcher {
-    use reqwest::blocking::Client;
+    use super::{Download, Release};
+    use reqwest::{blocking::Client, header::ACCEPT};
     use std::cell::Cell;
     use std::time::{Duration, Instant};
 
+    const RELEASES_URL: &str = "https://api.github.com/repos/lukexor/tetanes/releases?per_page=20";
+
     #[derive(Debug, Clone)]
     #[must_use]
     pub struct Fetcher {
@@ -39,32 +40,35 @@ mod fetcher {
                 .ok()
         }
 
-        pub fn update_available(&self, version: &'static str) -> anyhow::Result<Option<String>> {
+        /// Returns the releases newer than `version`, newest first.
+        pub fn updates_available(&self, version: &'static str) -> anyhow::Result<Vec<Release>> {
             #[derive(Debug, serde::Deserialize)]
             #[must_use]
             struct ApiError {
-                detail: Option<String>,
-            }
-
-            #[derive(Debug, serde::Deserialize)]
-            #[must_use]
-            struct ApiErrors {
-                errors: Vec<ApiError>,
+                message: String,
             }
 
             // Partial deserialization of the full response
             #[derive(Debug, serde::Deserialize)]
             #[must_use]
-            struct Crate {
-                newest_version: String,
+            struct Asset {
+                name: String,
+                browser_download_url: String,
             }
 
             // Partial deserialization of the full response
             #[derive(Debug, serde::Deserialize)]
             #[must_use]
-            struct CrateResponse {
-                #[serde(rename = "crate")]
-                cr: Crate,
+            struct GithubRelease {
+                tag_name: String,
+                body: Option<String>,
+                html_url: String,
+                #[serde(default)]
+                draft: bool,
+                #[serde(default)]
+                prerelease: bool,
+                #[serde(default)]
+                assets: Vec<Asset>,
             }
 
             if self.last_request_time.get().elapsed() < self.rate_limit {
@@ -78,32 +82,49 @@ mod fetcher {
                 anyhow::bail!("failed to create http client");
             };
             let content = client
-                .get("https://crates.io/api/v1/crates/tetanes")
+                .get(RELEASES_URL)
+                .header(ACCEPT, "application/vnd.github+json")
                 .send()
                 .and_then(|res| res.text())?;
-            if let Ok(res) = serde_json::from_str::<ApiErrors>(&content) {
-                anyhow::bail!(
-                    "encountered crates.io API errors: {}",
-                    res.errors
-                        .into_iter()
-                        .filter_map(|error| error.detail)
-                        .collect::<Vec<_>>()
-                        .join(",")
-                );
-            }
+            let releases = match serde_json::from_str::<Vec<GithubRelease>>(&content) {
+                Ok(releases) => releases,
+                Err(err) => match serde_json::from_str::<ApiError>(&content) {
+                    Ok(res) => anyhow::bail!("encountered GitHub API error: {}", res.message),
+                    Err(_) => anyhow::bail!("failed to deserialize GitHub response: {err:?}"),
+                },
+            };
 
-            match serde_json::from_str::<CrateResponse>(&content) {
-                Ok(CrateResponse {
-                    cr: Crate { newest_version, .. },
-                }) => {
-                    if Self::is_newer(&newest_version, version) {
-                        Ok(Some(newest_version))
-                    } else {
-                        Ok(None)
-                    }
-                }
-                Err(err) => anyhow::bail!("failed to deserialize crates.io response: {err:?}"),
-            }
+            let mut releases = releases
+                .into_iter()
+                .filter(|release| !release.draft && !release.prerelease)
+                .filter_map(|release| {
+                    // Releases for other crates in the workspace, like `tetanes-core`, are
+                    // tagged with their own prefix
+                    let tag = release.tag_name.as_str();
+                    let tag_version = tag
+                        .strip_prefix("tetanes-v")
+                        .or_else(|| tag.strip_prefix('v'))?;
+                    let parsed = semver::Version::parse(tag_version).ok()?;
+                    Self::is_newer(tag_version, version).then(|| {
+                        let release = Release {
+                            version: tag_version.to_string(),
+                            changelog: release.body.unwrap_or_default().trim().to_string(),
+                            url: release.html_url,
+                            downloads: release
+                                .assets
+                                .into_iter()
+                                .map(|asset| Download {
+                                    name: asset.name,
+                                    url: asset.browser_download_url,
+                                })
+                                .collect(),
+                        };
+                        (parsed, release)
+                    })
+                })
+                .collect::<Vec<_>>();
+            releases.sort_by(|(a, _), (b, _)| b.cmp(a));
+            Ok(releases.into_iter().map(|(_, release)| release).collect())
         }
 
         fn is_newer(new: &str, old: &str) -> bool {
@@ -115,11 +136,32 @@ mod fetcher {
     }
 }
 
+/// A published release of TetaNES.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[must_use]
+pub struct Release {
+    pub version: String,
+    /// Release notes, in Markdown.
+    pub changelog: String,
+    /// Release page, listing all downloads.
+    pub url: String,
+    pub downloads: Vec<Download>,
+}
+
+/// A downloadable file attached to a release.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[must_use]
+pub struct Download {
+    pub name: String,
+    pub url: String,
+}
+
 #[derive(Debug, Clone)]
 #[must_use]
 pub struct Version {
     current: &'static str,
-    latest: RefCell<String>,
+    /// Releases newer than the current version, newest first.
+    releases: Vec<Release>,
 }
 
 impl Default for Version {
@@ -129,10 +171,12 @@ impl Default for Version {
 }
 
 impl Version {
+    pub const RELEASES_URL: &'static str = "https://github.com/lukexor/tetanes/releases";
+
     pub fn new() -> Self {
         Self {
             current: env!("CARGO_PKG_VERSION"),
-            latest: RefCell::new(env!("CARGO_PKG_VERSION").to_string()),
+            releases: Vec::new(),
         }
     }
 
@@ -140,12 +184,18 @@ impl Version {
         self.current
     }
 
-    pub fn latest(&self) -> String {
-        self.latest.borrow().clone()
+    /// The newest release, if newer than the current version.
+    pub fn latest(&self) -> Option<&Release> {
+        self.releases.first()
+    }
+
+    /// Releases newer than the current version, newest first.
+    pub fn releases(&self) -> &[Release] {
+        &self.releases
     }
 
-    pub fn set_latest(&mut self, version: String) {
-        self.latest.replace(version);
+    pub fn set_releases(&mut self, releases: Vec<Release>) {
+        self.releases = releases;
     }
 
     pub const fn requires_updates(&self) -> bool {
@@ -177,22 +227,21 @@ impl Version {
                 let current_version = self.current;
                 let fetcher = fetcher::Fetcher::default();
                 let tx = tx.clone();
-                move || {
-                    let newest_version = fetcher.update_available(current_version);
-                    match newest_version {
-                        Ok(Some(version)) => tx.event(UiEvent::UpdateAvailable(version)),
-                        Ok(None) => {
-                            if notify_latest {
-                                tx.event(UiEvent::Message((
-                                    MessageType::Info,
-                                    format!("TetaNES v{current_version} is up to date!"),
-                                )));
-                            }
-                        }
-                        Err(err) => {
-                            tx.event(UiEvent::Message((MessageType::Error, err.to_string())));
+                move || match fetcher.updates_available(current_version) {
+                    Ok(releases) if !releases.is_empty() => {
+                        tx.event(UiEvent::UpdateAvailable(releases));
+                    }
+                    Ok(_) => {
+                        if notify_latest {
+                            tx.event(UiEvent::Message((
+                                MessageType::Info,
+                                format!("TetaNES v{current_version} is up to date!"),
+                            )));
                         }
                     }
+                    Err(err) => {
+                        tx.event(UiEvent::Message((MessageType::Error, err.to_string())));
+                    }
                 }
             });
         if let Err(err) = spawn_update {
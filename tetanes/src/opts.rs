@@ -1,5 +1,9 @@
-use clap::{Parser, ValueEnum};
-use std::path::PathBuf;
+use anyhow::Context;
+use clap::{Parser, Subcommand, ValueEnum};
+use std::{
+    net::SocketAddr,
+    path::{Path, PathBuf},
+};
 use tetanes::nes::config::Config;
 use tetanes_core::genie::GenieCode;
 
@@ -45,11 +49,34 @@ impl ValueEnum for NesRegion {
     }
 }
 
+/// `TetaNES` CLI Commands
+#[derive(Subcommand, Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Command {
+    /// Verify audio, graphics, input, data directory, and emulation and print a diagnostic
+    /// summary.
+    SelfTest,
+    /// Print a field-by-field diff of the CPU, PPU, APU, and mapper state of two save states.
+    StateDiff {
+        /// The first save state.
+        a: PathBuf,
+        /// The second save state.
+        b: PathBuf,
+    },
+    /// Run a TOML script of regression tests that play inputs into a ROM and check RAM and the
+    /// screen, exiting with an error if any fail.
+    RunTests {
+        /// The test script.
+        file: PathBuf,
+    },
+}
+
 /// `TetaNES` CLI Config Options
 #[derive(Parser, Debug)]
-#[command(version, author, about, long_about = None)]
+#[command(version, author, about, long_about = None, args_conflicts_with_subcommands = true)]
 #[must_use]
 pub struct Opts {
+    #[command(subcommand)]
+    pub(crate) command: Option<Command>,
     /// The NES ROM to load or a directory containing `.nes` ROM files. [default: current directory]
     pub(crate) path: Option<PathBuf>,
     /// Enable rewinding.
@@ -85,6 +112,45 @@ pub struct Opts {
     /// Don't auto save state or save on exit.
     #[arg(long)]
     pub(crate) no_save: bool,
+    /// Load state from a save slot after loading the ROM.
+    #[arg(long, value_name = "SLOT", requires = "path", value_parser = clap::value_parser!(u8).range(1..=8))]
+    pub(crate) load_slot: Option<u8>,
+    /// Save state to the current save slot on exit, even if auto save is disabled.
+    #[arg(long)]
+    pub(crate) save_on_exit: bool,
+    /// Play back a replay recording after loading the ROM.
+    #[arg(
+        long,
+        value_name = "FILE",
+        requires = "path",
+        conflicts_with = "record_replay"
+    )]
+    pub(crate) replay: Option<PathBuf>,
+    /// Record a replay after loading the ROM, saving it to a file on exit.
+    #[arg(long, value_name = "FILE", requires = "path")]
+    pub(crate) record_replay: Option<PathBuf>,
+    /// Play back a replay recording without opening a window and report the first frame that
+    /// desyncs from the recording.
+    #[arg(
+        long,
+        value_name = "FILE",
+        requires = "path",
+        conflicts_with_all = ["replay", "record_replay"]
+    )]
+    pub(crate) verify_replay: Option<PathBuf>,
+    /// Append frame timing samples to a CSV file for the whole session, e.g. to report stutter.
+    #[arg(long, value_name = "FILE")]
+    pub(crate) perf_log: Option<PathBuf>,
+    /// Stream raw RGBA frames and PCM audio to TCP clients on an address, e.g. `127.0.0.1:7370`.
+    #[arg(long, value_name = "ADDR")]
+    pub(crate) stream: Option<SocketAddr>,
+    /// Send APU channel notes to the first MIDI output port containing a name (experimental,
+    /// requires the `midi` feature).
+    #[arg(long, value_name = "PORT")]
+    pub(crate) midi: Option<String>,
+    /// Seed random power-up RAM, making emulation deterministic.
+    #[arg(long)]
+    pub(crate) seed: Option<u64>,
     #[arg(short = 'x', long)]
     /// Emulation speed. [default: 1.0]
     pub(crate) speed: Option<f32>,
@@ -94,12 +160,24 @@ pub struct Opts {
     /// Custom Config path.
     #[arg(long)]
     pub(crate) config: Option<PathBuf>,
+    /// Named configuration profile to load, created from the default profile if it doesn't
+    /// exist yet. e.g. `Laptop`
+    #[arg(long, conflicts_with_all = ["config", "clean"])]
+    pub(crate) profile: Option<String>,
     /// "Default Config" (skip user config and previous save states)
     #[arg(short, long)]
     pub(crate) clean: bool,
+    /// Store configuration and data in a `tetanes-data` folder next to the executable.
+    #[arg(long)]
+    pub(crate) portable: bool,
     /// Start with debugger open.
     #[arg(short, long)]
     pub(crate) debug: bool,
+    /// Lock settings for arcade cabinets and museum installs: hide the Preferences, Keybinds,
+    /// and Debug menus, only quit with `Ctrl+Alt+Shift+Q`, and only load ROMs from the ROM
+    /// directory.
+    #[arg(long, conflicts_with = "debug")]
+    pub(crate) kiosk: bool,
 }
 
 impl Opts {
@@ -107,6 +185,8 @@ impl Opts {
     pub fn load(self) -> anyhow::Result<Config> {
         let mut cfg = if self.clean {
             Config::default()
+        } else if let Some(profile) = &self.profile {
+            Config::load_profile(Config::profile_name(profile)?)
         } else {
             Config::load(self.config.clone())
         };
@@ -121,6 +201,7 @@ impl Opts {
         if let Some(NesRegion(region)) = self.region {
             cfg.deck.region = region;
         }
+        cfg.deck.seed = self.seed.or(cfg.deck.seed);
         cfg.deck.genie_codes.reserve(self.genie_code.len());
         for genie_code in self.genie_code.into_iter() {
             cfg.deck.genie_codes.push(GenieCode::new(genie_code)?);
@@ -145,6 +226,14 @@ impl Opts {
         }
         cfg.emulation.threaded = !self.no_threaded && cfg.emulation.threaded;
 
+        cfg.startup.load_slot = self.load_slot;
+        cfg.startup.save_on_exit = self.save_on_exit;
+        cfg.startup.replay_path = self.replay;
+        cfg.startup.record_replay_path = self.record_replay;
+        cfg.startup.perf_log_path = self.perf_log;
+        cfg.startup.stream_addr = self.stream;
+        cfg.startup.midi_port = self.midi;
+
         cfg.audio.enabled = !self.silent && cfg.audio.enabled;
 
         cfg.renderer.roms_path = self
@@ -153,6 +242,18 @@ impl Opts {
             .and_then(|path| path.canonicalize().ok());
         cfg.renderer.fullscreen = self.fullscreen || cfg.renderer.fullscreen;
 
+        if self.kiosk {
+            let rom_dir = match &cfg.renderer.roms_path {
+                Some(path) if path.is_file() => path.parent().map(Path::to_path_buf),
+                Some(path) => Some(path.clone()),
+                None => std::env::current_dir()
+                    .and_then(|dir| dir.canonicalize())
+                    .ok(),
+            };
+            cfg.startup.kiosk_dir =
+                Some(rom_dir.context("failed to determine the kiosk ROM directory")?);
+        }
+
         Ok(cfg)
     }
 }
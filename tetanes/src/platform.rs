@@ -25,8 +25,31 @@ pub fn open_file_dialog(
     platform::open_file_dialog_impl(title, name, extensions, dir)
 }
 
+/// Method for platforms supporting saving a file dialog.
+pub fn save_file_dialog(
+    title: impl Into<String>,
+    name: impl Into<String>,
+    extensions: &[impl ToString],
+    file_name: impl Into<String>,
+    dir: Option<impl AsRef<Path>>,
+) -> anyhow::Result<Option<PathBuf>> {
+    platform::save_file_dialog_impl(title, name, extensions, file_name, dir)
+}
+
+/// Method for platforms supporting picking a directory.
+pub fn open_dir_dialog(
+    title: impl Into<String>,
+    dir: Option<impl AsRef<Path>>,
+) -> anyhow::Result<Option<PathBuf>> {
+    platform::open_dir_dialog_impl(title, dir)
+}
+
+/// Method for platforms supporting showing a directory in the system file manager.
+pub fn open_dir(dir: impl AsRef<Path>) -> anyhow::Result<()> {
+    platform::open_dir_impl(dir)
+}
+
 /// Speak the given text out loud for platforms that support it.
-#[allow(clippy::missing_const_for_fn)]
 pub fn speak_text(text: &str) {
     platform::speak_text_impl(text);
 }
@@ -57,6 +80,8 @@ pub enum Feature {
     Storage,
     Suspend,
     OsViewports,
+    /// Window position and size can be saved and restored.
+    WindowGeometry,
 }
 
 /// Checks if the current platform supports a given feature.
@@ -67,7 +92,7 @@ macro_rules! feature {
         match $feature {
             // Wasm should never be able to exit
             AbortOnExit => cfg!(target_arch = "wasm32"),
-            Blocking | Filesystem | OsViewports => {
+            Blocking | Filesystem | OsViewports | WindowGeometry => {
                 cfg!(not(target_arch = "wasm32"))
             }
             ConstrainedViewport | ConsumePaste | ScreenReader => {
@@ -0,0 +1,51 @@
+//! `tetanes run-tests` for running scripted regression tests of game logic against a ROM.
+
+use anyhow::{bail, Context};
+use std::path::Path;
+use tetanes_core::{debug::harness::TestScript, fs};
+
+/// Runs the test cases of a TOML test script, printing the result of each. Returns an error if
+/// any test fails.
+pub fn run(path: &Path) -> anyhow::Result<()> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read test script: {}", path.display()))?;
+    let script = toml::from_str::<TestScript>(&text)
+        .with_context(|| format!("invalid test script: {}", path.display()))?;
+
+    let rom_path = path
+        .parent()
+        .map_or_else(|| script.rom.clone(), |dir| dir.join(&script.rom));
+    let rom = std::fs::read(&rom_path)
+        .with_context(|| format!("failed to read rom: {}", rom_path.display()))?;
+
+    println!(
+        "running {} tests against {}",
+        script.tests.len(),
+        rom_path.display()
+    );
+    let results = script
+        .run(fs::filename(&rom_path), &rom)
+        .with_context(|| format!("failed to run tests: {}", path.display()))?;
+
+    let mut failed = 0;
+    for result in &results {
+        if result.passed() {
+            println!("test {} ... ok", result.name);
+        } else {
+            failed += 1;
+            println!("test {} ... FAILED", result.name);
+            for failure in &result.failures {
+                println!("    {failure}");
+            }
+        }
+    }
+    println!(
+        "\ntest result: {} passed; {failed} failed",
+        results.len() - failed
+    );
+
+    if failed > 0 {
+        bail!("{failed} of {} tests failed", results.len());
+    }
+    Ok(())
+}
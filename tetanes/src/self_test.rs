@@ -0,0 +1,167 @@
+//! `tetanes self-test` diagnostics for verifying a working install.
+
+use anyhow::{anyhow, bail, Context};
+use std::{sync::Arc, time::Instant};
+use tetanes::nes::{
+    audio::{Audio, State as AudioState},
+    config::Config,
+    input::Gamepads,
+    rom::HOMEBREW_ROMS,
+};
+use tetanes_core::{
+    apu::Apu,
+    control_deck::{Config as DeckConfig, ControlDeck},
+};
+
+/// Number of frames to emulate when checking emulation.
+const EMULATION_FRAMES: u32 = 1000;
+
+/// Result of a single diagnostic check.
+#[derive(Debug)]
+#[must_use]
+struct Check {
+    name: &'static str,
+    result: anyhow::Result<String>,
+}
+
+/// Runs all diagnostic checks, printing a summary. Returns an error if any check failed.
+pub fn run(cfg: &Config) -> anyhow::Result<()> {
+    println!("TetaNES v{} self-test\n", env!("CARGO_PKG_VERSION"));
+
+    let checks = [
+        Check {
+            name: "Audio output",
+            result: check_audio(cfg),
+        },
+        Check {
+            name: "GPU adapter",
+            result: check_gpu(),
+        },
+        Check {
+            name: "Input devices",
+            result: check_input(),
+        },
+        Check {
+            name: "Data directory",
+            result: check_data_dir(cfg),
+        },
+        Check {
+            name: "Emulation",
+            result: check_emulation(),
+        },
+    ];
+
+    let mut failed = 0;
+    for check in &checks {
+        match &check.result {
+            Ok(details) => println!("[PASS] {}: {details}", check.name),
+            Err(err) => {
+                failed += 1;
+                println!("[FAIL] {}: {err:#}", check.name);
+            }
+        }
+    }
+
+    println!(
+        "\n{} of {} checks passed.",
+        checks.len() - failed,
+        checks.len()
+    );
+    if failed > 0 {
+        bail!("{failed} self-test check(s) failed");
+    }
+
+    Ok(())
+}
+
+/// Verifies an audio output device can be opened and started.
+fn check_audio(cfg: &Config) -> anyhow::Result<String> {
+    let mut audio = Audio::new(
+        true,
+        cfg.audio.backend,
+        Apu::DEFAULT_SAMPLE_RATE,
+        cfg.audio.latency,
+        cfg.audio.buffer_size,
+        Arc::default(),
+    );
+    match audio.start()? {
+        AudioState::Started => {
+            let details = format!(
+                "{} channel(s) at {} Hz using {}",
+                audio.channels(),
+                audio.sample_rate,
+                audio.host.id().name()
+            );
+            let _ = audio.stop();
+            Ok(details)
+        }
+        AudioState::NoOutputDevice => Err(anyhow!("no supported audio output device found")),
+        state => Err(anyhow!("failed to start audio stream: {state:?}")),
+    }
+}
+
+/// Verifies a GPU adapter and device can be created.
+fn check_gpu() -> anyhow::Result<String> {
+    let instance = wgpu::Instance::new(wgpu::InstanceDescriptor::default());
+    let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+        power_preference: wgpu::PowerPreference::HighPerformance,
+        compatible_surface: None,
+        force_fallback_adapter: false,
+    }))
+    .context("failed to find suitable wgpu adapter")?;
+    let info = adapter.get_info();
+    pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor::default(), None))
+        .with_context(|| format!("failed to create wgpu device for {}", info.name))?;
+
+    Ok(format!("{} ({:?})", info.name, info.backend))
+}
+
+/// Verifies input devices can be enumerated.
+fn check_input() -> anyhow::Result<String> {
+    let gamepads = Gamepads::new();
+    let names = gamepads
+        .list()
+        .context("failed to initialize gamepad support")?
+        .map(|(_, gamepad)| gamepad.name().to_string())
+        .collect::<Vec<_>>();
+
+    Ok(if names.is_empty() {
+        "no gamepads connected".to_string()
+    } else {
+        format!("{} gamepad(s): {}", names.len(), names.join(", "))
+    })
+}
+
+/// Verifies the configured data directory is writable.
+fn check_data_dir(cfg: &Config) -> anyhow::Result<String> {
+    let data_dir = cfg.paths.data_dir();
+    std::fs::create_dir_all(&data_dir).with_context(|| format!("failed to create {data_dir:?}"))?;
+    let path = data_dir.join(".self-test");
+    std::fs::write(&path, b"tetanes").with_context(|| format!("failed to write {path:?}"))?;
+    std::fs::remove_file(&path).with_context(|| format!("failed to remove {path:?}"))?;
+
+    Ok(format!("{data_dir:?} is writable"))
+}
+
+/// Verifies a bundled ROM can be loaded and emulated headlessly.
+fn check_emulation() -> anyhow::Result<String> {
+    let rom = &HOMEBREW_ROMS[0];
+    let mut deck = ControlDeck::with_config(DeckConfig::default());
+    deck.load_rom(rom.name, &mut rom.data().as_ref())
+        .with_context(|| format!("failed to load {}", rom.name))?;
+
+    let start = Instant::now();
+    for _ in 0..EMULATION_FRAMES {
+        deck.clock_frame()
+            .with_context(|| format!("failed to emulate {}", rom.name))?;
+        deck.clear_audio_samples();
+    }
+    let elapsed = start.elapsed();
+
+    Ok(format!(
+        "{EMULATION_FRAMES} frames of {} in {:.2?} ({:.0} FPS)",
+        rom.name,
+        elapsed,
+        f64::from(EMULATION_FRAMES) / elapsed.as_secs_f64()
+    ))
+}
@@ -0,0 +1,41 @@
+//! `tetanes state-diff` for comparing two save states, e.g. when hunting replay desyncs or save
+//! state serialization regressions.
+
+use anyhow::Context;
+use std::path::Path;
+use tetanes_core::{cpu::Cpu, debug, fs};
+
+/// Loads two save states and prints every field that differs between them, grouped by console
+/// component.
+pub fn run(a_path: &Path, b_path: &Path) -> anyhow::Result<()> {
+    let load = |path: &Path| {
+        fs::load::<Cpu>(path)
+            .with_context(|| format!("failed to load save state: {}", path.display()))
+    };
+    let (a, b) = (load(a_path)?, load(b_path)?);
+    let diffs = debug::diff_states(&a, &b).context("failed to compare save states")?;
+
+    println!("a: {}", a_path.display());
+    println!("b: {}", b_path.display());
+    if diffs.is_empty() {
+        println!("\nSave states are identical.");
+        return Ok(());
+    }
+
+    for component in ["CPU", "PPU", "APU", "Mapper"] {
+        let mut diffs = diffs
+            .iter()
+            .filter(|diff| diff.component() == component)
+            .peekable();
+        if diffs.peek().is_none() {
+            continue;
+        }
+        println!("\n{component}:");
+        for diff in diffs {
+            println!("  {diff}");
+        }
+    }
+    println!("\n{} fields differ.", diffs.len());
+
+    Ok(())
+}
@@ -1,5 +1,5 @@
+use crate::nes::config::Config;
 use anyhow::Context;
-use std::path::PathBuf;
 use tracing_appender::{
     non_blocking::WorkerGuard,
     rolling::{RollingFileAppender, Rotation},
@@ -22,11 +22,7 @@ where
         .max_log_files(3)
         .filename_prefix("tetanes")
         .filename_suffix("log")
-        .build(
-            dirs::data_local_dir()
-                .map(|dir| dir.join("tetanes/logs"))
-                .unwrap_or_else(|| PathBuf::from("logs")),
-        )
+        .build(Config::default_data_dir().join("logs"))
         .context("failed to create log file")?;
     let (file_writer, guard) = tracing_appender::non_blocking(file_appender);
 
@@ -2,6 +2,7 @@ use crate::{
     nes::{event::EmulationEvent, renderer::Renderer, Running},
     platform::{BuilderExt, Initialize},
 };
+use anyhow::Context;
 use std::path::{Path, PathBuf};
 use tracing::error;
 use winit::window::WindowAttributes;
@@ -22,11 +23,108 @@ pub fn open_file_dialog_impl(
     Ok(dialog.pick_file())
 }
 
-/// Speak the given text out loud.
-pub const fn speak_text_impl(_text: &str) {}
+/// Method for platforms supporting saving a file dialog.
+pub fn save_file_dialog_impl(
+    title: impl Into<String>,
+    name: impl Into<String>,
+    extensions: &[impl ToString],
+    file_name: impl Into<String>,
+    dir: Option<impl AsRef<Path>>,
+) -> anyhow::Result<Option<PathBuf>> {
+    let mut dialog = rfd::FileDialog::new()
+        .set_title(title)
+        .add_filter(name, extensions)
+        .set_file_name(file_name);
+    if let Some(dir) = dir {
+        dialog = dialog.set_directory(dir.as_ref());
+    }
+    Ok(dialog.save_file())
+}
+
+/// Method for platforms supporting picking a directory.
+pub fn open_dir_dialog_impl(
+    title: impl Into<String>,
+    dir: Option<impl AsRef<Path>>,
+) -> anyhow::Result<Option<PathBuf>> {
+    let mut dialog = rfd::FileDialog::new().set_title(title);
+    if let Some(dir) = dir {
+        dialog = dialog.set_directory(dir.as_ref());
+    }
+    Ok(dialog.pick_folder())
+}
+
+/// Method for platforms supporting showing a directory in the system file manager.
+pub fn open_dir_impl(dir: impl AsRef<Path>) -> anyhow::Result<()> {
+    let program = if cfg!(target_os = "windows") {
+        "explorer"
+    } else if cfg!(target_os = "macos") {
+        "open"
+    } else {
+        "xdg-open"
+    };
+    let dir = dir.as_ref();
+    std::process::Command::new(program)
+        .arg(dir)
+        .spawn()
+        .with_context(|| format!("failed to open {dir:?}"))?;
+    Ok(())
+}
+
+/// Speak the given text out loud using the system speech synthesizer: `say` on macOS,
+/// `System.Speech` on Windows and Speech Dispatcher's `spd-say` elsewhere.
+pub fn speak_text_impl(text: &str) {
+    if text.is_empty() {
+        return;
+    }
+
+    tracing::debug!("Speaking {text:?}");
+    let mut command = if cfg!(target_os = "windows") {
+        let mut command = std::process::Command::new("powershell");
+        // Passed through the environment so the text is never parsed as a script
+        command
+            .args([
+                "-NoProfile",
+                "-NonInteractive",
+                "-Command",
+                concat!(
+                    "Add-Type -AssemblyName System.Speech; ",
+                    "(New-Object System.Speech.Synthesis.SpeechSynthesizer)",
+                    ".Speak($env:TETANES_SPEAK_TEXT)",
+                ),
+            ])
+            .env("TETANES_SPEAK_TEXT", text);
+        command
+    } else {
+        let program = if cfg!(target_os = "macos") {
+            "say"
+        } else {
+            "spd-say"
+        };
+        let mut command = std::process::Command::new(program);
+        command.arg("--").arg(text);
+        command
+    };
+    match command.spawn() {
+        // Wait on a separate thread so finished processes are reaped instead of left as zombies
+        Ok(mut child) => {
+            let waiter = std::thread::Builder::new()
+                .name("speak_text".into())
+                .spawn(move || {
+                    if let Err(err) = child.wait() {
+                        error!("failed to wait for speech process: {err:?}");
+                    }
+                });
+            if let Err(err) = waiter {
+                error!("failed to spawn speech process waiter: {err:?}");
+            }
+        }
+        Err(err) => error!("failed to speak text: {err:?}"),
+    }
+}
 
 impl Initialize for Running {
-    /// Initialize by loading a ROM from the command line, if provided.
+    /// Initialize by loading a ROM from the command line, if provided, along with any startup
+    /// save state or replay actions.
     fn initialize(&mut self) -> anyhow::Result<()> {
         if let Some(path) = self.cfg.renderer.roms_path.take() {
             if path.is_file() {
@@ -34,10 +132,26 @@ impl Initialize for Running {
                     self.cfg.renderer.roms_path = Some(parent.to_path_buf());
                 }
                 self.event(EmulationEvent::LoadRomPath(path));
+
+                let startup = &mut self.cfg.startup;
+                let load_slot = startup.load_slot.take();
+                let replay_path = startup.replay_path.take();
+                let record_replay_path = startup.record_replay_path.take();
+                if let Some(slot) = load_slot {
+                    self.event(EmulationEvent::LoadState(slot));
+                }
+                if let Some(path) = replay_path {
+                    self.event(EmulationEvent::LoadReplayPath(path));
+                }
+                if let Some(path) = record_replay_path {
+                    self.replay_recording = true;
+                    self.event(EmulationEvent::ReplayRecordPath(path));
+                }
             } else if path.exists() {
                 self.cfg.renderer.roms_path = Some(path);
             }
         }
+        self.update_rom_watcher();
 
         Ok(())
     }
@@ -52,7 +166,6 @@ impl Initialize for Renderer {
 impl BuilderExt for WindowAttributes {
     /// Sets platform-specific window options.
     fn with_platform(self, _title: &str) -> Self {
-        use anyhow::Context;
         use image::{ImageFormat, ImageReader};
         use std::io::Cursor;
 
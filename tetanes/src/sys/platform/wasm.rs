@@ -66,6 +66,30 @@ pub fn open_file_dialog_impl(
     Ok(None)
 }
 
+/// Method for platforms supporting saving a file dialog.
+pub fn save_file_dialog_impl(
+    _title: impl Into<String>,
+    _name: impl Into<String>,
+    _extensions: &[impl ToString],
+    _file_name: impl Into<String>,
+    _dir: Option<impl AsRef<Path>>,
+) -> anyhow::Result<Option<PathBuf>> {
+    bail!("saving files is not supported on this platform")
+}
+
+/// Method for platforms supporting picking a directory.
+pub fn open_dir_dialog_impl(
+    _title: impl Into<String>,
+    _dir: Option<impl AsRef<Path>>,
+) -> anyhow::Result<Option<PathBuf>> {
+    bail!("picking directories is not supported on this platform")
+}
+
+/// Method for platforms supporting showing a directory in the system file manager.
+pub fn open_dir_impl(_dir: impl AsRef<Path>) -> anyhow::Result<()> {
+    bail!("opening directories is not supported on this platform")
+}
+
 /// Speak the given text out loud.
 pub fn speak_text_impl(text: &str) {
     if text.is_empty() {
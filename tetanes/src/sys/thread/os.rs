@@ -9,6 +9,11 @@ where
     pollster::block_on(future)
 }
 
+/// Blocks unless or until the current thread's token is made available (may wake spuriously).
+pub fn park_impl() {
+    thread::park();
+}
+
 /// Blocks unless or until the current thread's token is made available or
 /// the specified duration has been reached (may wake spuriously).
 pub fn park_timeout_impl(dur: Duration) {
@@ -11,6 +11,10 @@ where
     wasm_bindgen_futures::spawn_local(future);
 }
 
+/// Blocking, and thus parking is not allowed in wasm.
+#[allow(clippy::missing_const_for_fn)]
+pub fn park_impl() {}
+
 /// Blocking, and thus parking is not allowed in wasm.
 #[allow(clippy::missing_const_for_fn)]
 pub fn park_timeout_impl(_dur: Duration) {}
@@ -10,6 +10,11 @@ where
     thread::spawn_impl(future);
 }
 
+/// Blocks unless or until the current thread's token is made available (may wake spuriously).
+pub fn park() {
+    thread::park_impl();
+}
+
 /// Blocks unless or until the current thread's token is made available or
 /// the specified duration has been reached (may wake spuriously).
 pub fn park_timeout(dur: Duration) {
@@ -0,0 +1,61 @@
+//! `tetanes --verify-replay` for checking a replay plays back exactly as it was recorded.
+
+use anyhow::{bail, Context};
+use std::path::Path;
+use tetanes::nes::{
+    config::Config,
+    emulation::replay::{self, Replay},
+};
+use tetanes_core::control_deck::ControlDeck;
+
+/// Plays back a replay recording for the ROM given on the command line, comparing the emulation
+/// state after each frame against the state hashes saved with the replay. Returns an error
+/// describing the first frame that desyncs, if any.
+pub fn run(cfg: &Config, replay_path: &Path) -> anyhow::Result<()> {
+    let Some(rom_path) = cfg
+        .renderer
+        .roms_path
+        .as_deref()
+        .filter(|path| path.is_file())
+    else {
+        bail!("a ROM file is required to verify a replay");
+    };
+
+    let mut deck = ControlDeck::with_config(cfg.deck.clone());
+    deck.load_rom_path(rom_path)
+        .with_context(|| format!("failed to load rom: {}", rom_path.display()))?;
+
+    let mut replay = Replay::new();
+    let start = replay
+        .load_path(replay_path)
+        .with_context(|| format!("failed to load replay: {}", replay_path.display()))?;
+    if replay.hashes.is_empty() {
+        bail!("replay has no recorded state hashes to verify against");
+    }
+    deck.load_cpu(start);
+
+    let start_frame = deck.frame_number();
+    let hash_count = replay.hashes.len();
+    while !replay.hashes.is_empty() {
+        while let Some(event) = replay.next(deck.frame_number()) {
+            replay::apply_event(&mut deck, &event);
+        }
+        deck.clock_frame()
+            .with_context(|| format!("emulation failed on frame {}", deck.frame_number()))?;
+        deck.clear_audio_samples();
+
+        if let Some(desync) = replay.check_hash(&deck)? {
+            bail!(
+                "{desync} ({} frames in)",
+                desync.frame.wrapping_sub(start_frame)
+            );
+        }
+    }
+
+    println!(
+        "Replay verified: {} frames, {hash_count} state hashes matched.",
+        deck.frame_number().wrapping_sub(start_frame),
+    );
+
+    Ok(())
+}